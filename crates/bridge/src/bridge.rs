@@ -2,41 +2,90 @@ mod app_menus;
 
 use std::sync::Arc;
 
-use collection::Collection;
+use collection::{Collection, CollectionEvent};
 use gpui::{
-    App, AppContext, Context, TitlebarOptions, Window, WindowKind, WindowOptions, point, px,
+    App, AppContext, Context, Entity, TitlebarOptions, Window, WindowKind, WindowOptions, point, px,
 };
 use uuid::Uuid;
 
 pub use app_menus::*;
+use request::{FolderItem, GrpcItem, RequestItem, SchemaExplorer, WebSocketItem};
 use workspace::{AppState, Workspace};
 
-pub fn init(cx: &mut App) {}
+pub fn init(_cx: &mut App) {}
 
-pub fn initialize_workspace(state: Arc<AppState>, cx: &mut App) {
-    cx.observe_new(move |workspace: &mut Workspace, window, cx| {
+pub fn initialize_workspace(_state: Arc<AppState>, cx: &mut App) {
+    cx.observe_new(move |_workspace: &mut Workspace, window, cx| {
         let Some(window) = window else {
             return;
         };
 
-        let handle = cx.entity();
-
         initialize_panels(window, cx);
     })
     .detach();
 }
 
 pub fn initialize_panels(window: &mut Window, cx: &mut Context<Workspace>) {
+    let root = cx.entity().read(cx).root().cloned();
+
     cx.spawn_in(window, async move |handle, cx| {
-        let collection_panel = cx.new(|_| Collection {}).unwrap();
+        let collection_panel = cx.new(|cx| Collection::new(root, cx))?;
+        let schema_explorer = cx.new(SchemaExplorer::new)?;
 
         handle.update_in(cx, |workspace, window, cx| {
+            cx.subscribe_in(&collection_panel, window, handle_collection_event)
+                .detach();
+
             workspace.add_panel(collection_panel, window, cx);
+            workspace.add_panel(schema_explorer, window, cx);
         })
     })
     .detach();
 }
 
+fn handle_collection_event(
+    workspace: &mut Workspace,
+    collection: &Entity<Collection>,
+    event: &CollectionEvent,
+    _window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    match event {
+        CollectionEvent::OpenRequest(path) => {
+            if !workspace.activate_item_for_path(path, cx) {
+                let session = collection.read(cx).session().cloned();
+                let item = RequestItem::open(path.clone(), session, cx);
+
+                workspace.add_item(Box::new(item), cx);
+            }
+        }
+        CollectionEvent::OpenWebSocket(path) => {
+            if !workspace.activate_item_for_path(path, cx) {
+                let session = collection.read(cx).session().cloned();
+                let item = WebSocketItem::open(path.clone(), session, cx);
+
+                workspace.add_item(Box::new(item), cx);
+            }
+        }
+        CollectionEvent::OpenGrpc(path) => {
+            if !workspace.activate_item_for_path(path, cx) {
+                let session = collection.read(cx).session().cloned();
+                let item = GrpcItem::open(path.clone(), session, cx);
+
+                workspace.add_item(Box::new(item), cx);
+            }
+        }
+        CollectionEvent::OpenFolder(path) => {
+            if !workspace.activate_item_for_path(path, cx) {
+                let session = collection.read(cx).session().cloned();
+                let item = FolderItem::open(path.clone(), session, cx);
+
+                workspace.add_item(Box::new(item), cx);
+            }
+        }
+    }
+}
+
 pub fn build_window_options(display_uuid: Option<Uuid>, cx: &mut App) -> WindowOptions {
     let display = display_uuid.and_then(|uuid| {
         cx.displays()
@@ -1,8 +1,41 @@
-use gpui::{App, Menu};
+use collection::cookie_manager::ManageCookies;
+use gallery::OpenComponentGallery;
+use gpui::{App, Menu, MenuItem};
+use request::ToggleSchemaExplorer;
+use workspace::{
+    CloseActiveItem, MoveItemToNewWindow, MoveItemToNextWindow, NewWindow, OpenCollection,
+};
 
 pub fn app_menus(_cx: &mut App) -> Vec<Menu> {
-    vec![Menu {
-        name: "Bridge".into(),
-        items: vec![],
-    }]
+    vec![
+        Menu {
+            name: "Bridge".into(),
+            items: vec![],
+        },
+        Menu {
+            name: "File".into(),
+            items: vec![
+                MenuItem::action("New Window", NewWindow),
+                MenuItem::action("Open Collection…", OpenCollection),
+                MenuItem::separator(),
+                MenuItem::action("Close Tab", CloseActiveItem),
+            ],
+        },
+        Menu {
+            name: "View".into(),
+            items: vec![
+                MenuItem::action("Cookies", ManageCookies),
+                MenuItem::action("GraphQL Schema", ToggleSchemaExplorer),
+                MenuItem::separator(),
+                MenuItem::action("Component Gallery", OpenComponentGallery),
+            ],
+        },
+        Menu {
+            name: "Window".into(),
+            items: vec![
+                MenuItem::action("Move Tab to New Window", MoveItemToNewWindow),
+                MenuItem::action("Move Tab to Next Window", MoveItemToNextWindow),
+            ],
+        },
+    ]
 }
@@ -4,12 +4,16 @@ use bridge::app_menus;
 use gpui::Application;
 use workspace::AppState;
 
-use crate::bridge::{build_window_options, initialize_workspace};
+use crate::{
+    assets::Assets,
+    bridge::{build_window_options, initialize_workspace},
+};
 
+mod assets;
 mod bridge;
 
 fn main() {
-    let app = Application::new();
+    let app = Application::new().with_assets(Assets);
 
     app.run(|cx| {
         settings::init(cx);
@@ -22,7 +26,14 @@ fn main() {
 
         AppState::set_global(Arc::downgrade(&app_state), cx);
 
+        workspace::init(app_state.clone(), cx);
+
         theme::init(cx);
+        ui::init(cx);
+        editor::init(cx);
+        collection::init(cx);
+        request::init(cx);
+        gallery::init(cx);
 
         let menus = app_menus(cx);
         cx.set_menus(menus);
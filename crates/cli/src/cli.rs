@@ -0,0 +1,469 @@
+use std::{
+    collections::BTreeMap,
+    env,
+    fs::File,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Context as _, Result, bail};
+use collection::{
+    assertions::{AssertionResult, evaluate},
+    auth::Auth,
+    config::CollectionConfig,
+    request::{Request, RequestMode, RequestProxy},
+    scripts::{
+        ScriptPhase, apply_script_request, effective_scripts, script_request, script_response,
+    },
+    session::Session,
+};
+use futures::StreamExt as _;
+use http_client::{
+    HttpRequest, HttpResponse, ProxyConfig, RedirectPolicy, RequestOptions, SseMessage, Timeouts,
+    http::HeaderMap, protocol_name,
+};
+use script::{ConsoleLevel, ScriptOutput, ScriptRequest, ScriptResponse};
+use serde_json::json;
+
+const USAGE: &str = "\
+Usage: cli send [options] <path>
+
+Commands:
+  send           Sends the request stored at <path> and prints the response
+                 body. Requests in SSE mode print their events as they
+                 arrive instead, reconnecting when the stream is lost, until
+                 the server answers 204 No Content. The pre-request and
+                 post-response scripts of the request and its folders run
+                 around it, printing their console output to stderr.
+                 The assertions of the request are checked on the response
+                 and reported on stderr, the enabled ones deciding the exit
+                 status instead of whether the status is a success.
+                 The environment active in the app, its secrets and the
+                 OAuth 2.0 tokens it got are used for the request
+
+Options:
+  --json         Prints the status, headers, body, timing, connection, TLS
+                 session and redirects of the response as a JSON object
+                 instead, or each event as a line of JSON
+  --proxy <url>  Sends the request through the given HTTP, HTTPS or SOCKS5
+                 proxy, or directly with \"none\". Defaults to the proxy of
+                 the request, then to HTTP_PROXY, HTTPS_PROXY and ALL_PROXY
+                 unless the host is listed in NO_PROXY
+  --raw          Prints the body as received, without undoing its gzip,
+                 deflate, br or zstd content encoding
+  --no-redirects
+                 Prints redirect responses instead of following them
+  --max-redirects <n>
+                 Fails after following <n> redirects, 10 by default
+  --connect-timeout <seconds>
+                 Fails when the connection takes longer to open, 30 by
+                 default
+  --timeout <seconds>
+                 Fails when the whole request takes longer, which it may by
+                 default
+  --script-timeout <seconds>
+                 Fails when the scripts run before or after the request take
+                 longer, 5 by default";
+
+/// The size past which bodies are written to a temporary file before being
+/// printed, unless printed as JSON.
+const MAX_BODY_IN_MEMORY: u64 = 64 << 20;
+
+/// How long connections may take to open unless `--connect-timeout` says
+/// otherwise.
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long scripts may run unless `--script-timeout` says otherwise.
+const DEFAULT_SCRIPT_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn main() -> ExitCode {
+    match run(env::args().skip(1).collect()) {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("error: {error:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Vec<String>) -> Result<ExitCode> {
+    let Some((command, args)) = args.split_first() else {
+        eprintln!("{USAGE}");
+        return Ok(ExitCode::FAILURE);
+    };
+
+    match command.as_str() {
+        "send" => send(args),
+        "-h" | "--help" | "help" => {
+            println!("{USAGE}");
+            Ok(ExitCode::SUCCESS)
+        }
+        command => bail!("unknown command {command:?}\n\n{USAGE}"),
+    }
+}
+
+fn send(args: &[String]) -> Result<ExitCode> {
+    let mut json = false;
+    let mut raw = false;
+    let mut proxy = None;
+    let mut follow_redirects = None;
+    let mut redirect = RedirectPolicy::default();
+    let mut timeouts = Timeouts {
+        connect: Some(DEFAULT_CONNECT_TIMEOUT),
+        ..Timeouts::default()
+    };
+    let mut script_timeout = DEFAULT_SCRIPT_TIMEOUT;
+    let mut path = None;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--raw" => raw = true,
+            "--proxy" => match args.next() {
+                Some(url) if url == "none" => proxy = Some(RequestProxy::Direct),
+                Some(url) => proxy = Some(RequestProxy::Url(url.clone())),
+                None => bail!("--proxy expects a URL\n\n{USAGE}"),
+            },
+            "--no-redirects" => follow_redirects = Some(false),
+            "--max-redirects" => match args.next().map(|hops| hops.parse()) {
+                Some(Ok(hops)) => redirect.max_hops = hops,
+                _ => bail!("--max-redirects expects a number\n\n{USAGE}"),
+            },
+            "--connect-timeout" => timeouts.connect = Some(seconds(args.next(), arg)?),
+            "--timeout" => timeouts.total = Some(seconds(args.next(), arg)?),
+            "--script-timeout" => script_timeout = seconds(args.next(), arg)?,
+            flag if flag.starts_with('-') => bail!("unknown option {flag:?}\n\n{USAGE}"),
+            _ if path.is_some() => bail!("expected a single request path\n\n{USAGE}"),
+            _ => path = Some(PathBuf::from(arg)),
+        }
+    }
+
+    let Some(path) = path else {
+        bail!("missing request path\n\n{USAGE}");
+    };
+
+    let mut request = Request::load(&path)?;
+    if proxy.is_some() {
+        request.proxy = proxy;
+    }
+    if follow_redirects.is_some() {
+        request.follow_redirects = follow_redirects;
+    }
+
+    let config = CollectionConfig::for_request(&path)?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+    // The active environment, its secrets and the tokens the app cached are
+    // those of the session of the collection, when it was opened in the app.
+    let session = Session::for_request(&path);
+    if let Some(error) = session.as_ref().and_then(Session::error) {
+        eprintln!("warning: {error}");
+    }
+    let environment = session.as_ref().and_then(Session::environment);
+    let mut scripts = Scripts {
+        variables: match &session {
+            Some(session) => session.variables(&config, environment),
+            None => config.variables(None, None),
+        },
+        timeout: script_timeout,
+    };
+    // Pre-request scripts see the references to variables they may define
+    // as they are, the request being built once they ran.
+    let pre_request = effective_scripts(dir, &request.scripts, &config, ScriptPhase::PreRequest)?;
+    let changed = if pre_request.is_empty() {
+        None
+    } else {
+        let original = request.to_script_request(dir, &scripts.variables)?;
+        let output = scripts.run(ScriptPhase::PreRequest, pre_request, original.clone(), None);
+        if let Some(error) = output.error {
+            bail!("the pre-request script failed: {error}");
+        }
+        Some((original, output.request))
+    };
+    let mut http_request = request.to_http_request(dir, &scripts.variables)?;
+    if let Some((original, changed)) = changed {
+        apply_script_request(&mut http_request, &original, changed, &scripts.variables)?;
+    }
+    // The environment is only read for requests without a proxy of their
+    // own, so that a malformed variable doesn't break an explicit choice.
+    let defaults = match request.proxy {
+        Some(_) => ProxyConfig::default(),
+        None => ProxyConfig::from_env()?,
+    };
+    let proxy = request.proxy(&defaults, &http_request.url)?;
+    let mut options = RequestOptions {
+        timeouts,
+        spill_threshold: (!json).then_some(MAX_BODY_IN_MEMORY),
+        ..config.request_options(
+            &http_request.url,
+            request.http_version.unwrap_or_default(),
+            proxy,
+            request.redirect_policy(redirect),
+        )
+    };
+    request.apply_transport(&mut options, dir)?;
+    if let Some(auth) = request.effective_auth(dir, &config)? {
+        let token = match &auth {
+            Auth::OAuth2(oauth) => {
+                let key = oauth.token_key(&oauth.client(&scripts.variables)?);
+                let token = session
+                    .as_ref()
+                    .and_then(|session| session.token(environment, &key));
+                match token {
+                    None => bail!(
+                        "the request needs an OAuth 2.0 access token, get one in the Auth tab of \
+                         the app first"
+                    ),
+                    Some(token) if token.is_expired(SystemTime::now()) => bail!(
+                        "the OAuth 2.0 access token expired, get a new one in the Auth tab of \
+                         the app"
+                    ),
+                    Some(token) => Some(token),
+                }
+            }
+            _ => None,
+        };
+        auth.apply(&mut http_request, &mut options, &scripts.variables, token)?;
+    }
+
+    if options.tls.skip_verification && http_request.url.scheme() == "https" {
+        eprintln!("warning: TLS certificate verification is disabled for this request");
+    }
+
+    if request.mode == RequestMode::Sse {
+        return subscribe(http_request, options, json)
+            .with_context(|| format!("streaming {}", path.display()));
+    }
+
+    let sent = script_request(&http_request);
+    let response = futures::executor::block_on(http_client::send(http_request, options))
+        .with_context(|| format!("sending {}", path.display()))?;
+
+    let post_response =
+        effective_scripts(dir, &request.scripts, &config, ScriptPhase::PostResponse)?;
+    let mut script_failed = false;
+    if !post_response.is_empty() {
+        let output = scripts.run(
+            ScriptPhase::PostResponse,
+            post_response,
+            sent,
+            Some(script_response(&response)),
+        );
+        if let Some(error) = output.error {
+            eprintln!("error: the post-response script failed: {error}");
+            script_failed = true;
+        }
+    }
+
+    let tests = evaluate(&request.assertions, &response, dir);
+    if !json {
+        for test in &tests {
+            match &test.failure {
+                None => eprintln!("pass: {}", test.name),
+                Some(failure) => eprintln!("fail: {}: {failure}", test.name),
+            }
+        }
+    }
+
+    let mut stdout = io::stdout().lock();
+    if json {
+        let mut output = response_json(&response);
+        if !tests.is_empty() {
+            output["tests"] = tests_json(&tests);
+        }
+        serde_json::to_writer_pretty(&mut stdout, &output)?;
+        writeln!(stdout)?;
+    } else if raw {
+        match &response.spilled {
+            Some(spilled) => copy_file(spilled.raw_body.path(), &mut stdout)?,
+            None => stdout.write_all(&response.raw_body)?,
+        }
+    } else {
+        if let Some(error) = &response.decode_error {
+            eprintln!("warning: {error}, printing the body as received");
+        }
+        match &response.spilled {
+            Some(spilled) => copy_file(spilled.body.path(), &mut stdout)?,
+            None => stdout.write_all(&response.body)?,
+        }
+    }
+
+    let passed = if tests.is_empty() {
+        response.status.is_success()
+    } else {
+        tests.iter().all(AssertionResult::passed)
+    };
+    Ok(if passed && !script_failed {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// Prints the events of an event stream as they arrive, in the event stream
+/// format or as JSON Lines.
+fn subscribe(request: HttpRequest, options: RequestOptions, json: bool) -> Result<ExitCode> {
+    let (stream, mut messages) = http_client::subscribe(request, options, None);
+
+    futures::executor::block_on(async {
+        while let Some(message) = messages.next().await {
+            let mut stdout = io::stdout().lock();
+
+            match message {
+                SseMessage::Open { status, .. } => eprintln!("connected: {status}"),
+                SseMessage::Reconnecting { error, retry_in } => match error {
+                    Some(error) => {
+                        eprintln!("lost the stream: {error}, reconnecting in {retry_in:?}")
+                    }
+                    None => eprintln!("the stream ended, reconnecting in {retry_in:?}"),
+                },
+                SseMessage::Event { event, .. } if json => {
+                    serde_json::to_writer(
+                        &mut stdout,
+                        &json!({
+                            "event": event.event,
+                            "id": event.id,
+                            "data": event.data,
+                            "retry_ms": event.retry.map(|retry| retry.as_millis() as u64),
+                        }),
+                    )?;
+                    writeln!(stdout)?;
+                }
+                SseMessage::Event { event, .. } => {
+                    writeln!(stdout, "event: {}", event.event)?;
+                    if let Some(id) = &event.id {
+                        writeln!(stdout, "id: {id}")?;
+                    }
+                    for line in event.data.split('\n') {
+                        writeln!(stdout, "data: {line}")?;
+                    }
+                    writeln!(stdout)?;
+                }
+            }
+            stdout.flush()?;
+        }
+
+        stream.await?;
+        Ok(ExitCode::SUCCESS)
+    })
+}
+
+/// What the scripts of a request run with, the variables the pre-request
+/// ones set being seen by the post-response ones.
+struct Scripts {
+    variables: BTreeMap<String, String>,
+    timeout: Duration,
+}
+
+impl Scripts {
+    /// Runs `scripts`, printing their console output to stderr.
+    fn run(
+        &mut self,
+        phase: ScriptPhase,
+        scripts: Vec<script::Script>,
+        request: ScriptRequest,
+        response: Option<ScriptResponse>,
+    ) -> ScriptOutput {
+        let output = script::run(
+            scripts,
+            self.variables.clone(),
+            request,
+            response,
+            self.timeout,
+        );
+
+        for line in &output.console {
+            let level = match line.level {
+                ConsoleLevel::Log => "log",
+                ConsoleLevel::Info => "info",
+                ConsoleLevel::Warn => "warn",
+                ConsoleLevel::Error => "error",
+            };
+            eprintln!("{} {level}: {}", phase.as_str(), line.message);
+        }
+        for (name, value) in &output.changes {
+            match value {
+                Some(value) => self.variables.insert(name.clone(), value.clone()),
+                None => self.variables.remove(name),
+            };
+        }
+
+        output
+    }
+}
+
+fn seconds(value: Option<&String>, flag: &str) -> Result<Duration> {
+    value
+        .and_then(|value| value.parse().ok())
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .with_context(|| format!("{flag} expects a number of seconds\n\n{USAGE}"))
+}
+
+fn copy_file(path: &Path, writer: &mut impl Write) -> Result<()> {
+    let mut file = File::open(path).with_context(|| format!("reading {}", path.display()))?;
+    io::copy(&mut file, writer)?;
+
+    Ok(())
+}
+
+fn response_json(response: &HttpResponse) -> serde_json::Value {
+    let redirects = response
+        .redirects
+        .iter()
+        .map(|hop| {
+            json!({
+                "method": hop.method.as_str(),
+                "url": hop.url.as_str(),
+                "status": hop.status.as_u16(),
+                "protocol": protocol_name(hop.version),
+                "location": hop.location.as_str(),
+                "headers": headers_json(&hop.headers),
+                "timing": hop.timing,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "url": response.url.as_str(),
+        "redirects": redirects,
+        "status": response.status.as_u16(),
+        "reason": response.status.canonical_reason(),
+        "protocol": protocol_name(response.version),
+        "headers": headers_json(&response.headers),
+        "body": String::from_utf8_lossy(&response.body),
+        "size": response.body_len(),
+        "encodings": response.encodings,
+        "encoded_size": response.raw_body_len(),
+        "decode_error": response.decode_error,
+        "timing": response.timing,
+        "connection": response.connection,
+        "tls": response.tls,
+    })
+}
+
+fn tests_json(tests: &[AssertionResult]) -> serde_json::Value {
+    tests
+        .iter()
+        .map(|test| {
+            json!({
+                "name": test.name,
+                "passed": test.passed(),
+                "failure": test.failure,
+            })
+        })
+        .collect()
+}
+
+fn headers_json(headers: &HeaderMap) -> serde_json::Value {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            json!({
+                "name": name.as_str(),
+                "value": String::from_utf8_lossy(value.as_bytes()),
+            })
+        })
+        .collect()
+}
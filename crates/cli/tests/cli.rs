@@ -0,0 +1,92 @@
+use std::{
+    env, fs,
+    io::{BufRead as _, BufReader, Write as _},
+    net::TcpListener,
+    path::PathBuf,
+    process::Command,
+    thread,
+};
+
+use serde_json::json;
+
+/// A folder of its own for each test, removed when dropped.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let dir = env::temp_dir().join(format!("cli-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Answers a single request with `body`, handing back the head of the
+/// request.
+fn serve_once(listener: TcpListener, body: &'static str) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut head = String::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            head.push_str(&line);
+            if line == "\r\n" {
+                break;
+            }
+        }
+
+        write!(
+            reader.get_mut(),
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+        head
+    })
+}
+
+#[test]
+fn pre_request_scripts_define_variables_of_the_request() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = serve_once(listener, "done");
+
+    let dir = TempDir::new("variables");
+    let path = dir.0.join("signed.request.json");
+    let request = json!({
+        "method": "GET",
+        "url": format!("http://{address}/{{{{path}}}}"),
+        "headers": [{ "name": "X-Signature", "value": "{{signature}}" }],
+        "scripts": {
+            "pre_request": r#"
+                env.set("path", "items")
+                env.set("signature", crypto.sha256("items").slice(0, 8))
+            "#,
+        },
+    });
+    fs::write(&path, request.to_string()).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cli"))
+        .arg("send")
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "done");
+
+    let head = server.join().unwrap();
+    assert!(head.starts_with("GET /items HTTP/1.1\r\n"));
+    assert!(head.contains("x-signature: 5f3c4f85\r\n"));
+}
@@ -0,0 +1,284 @@
+use std::{borrow::Cow, fmt, fs, path::Path, time::Duration};
+
+use anyhow::{Context as _, Result, anyhow};
+use http_client::HttpResponse;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_json_path::JsonPath;
+
+use crate::request::{enabled, is_enabled};
+
+/// How many schema violations a failed JSON Schema assertion lists.
+const MAX_SCHEMA_ERRORS: usize = 5;
+
+/// A check made on the response of a request after every send.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Assertion {
+    #[serde(flatten)]
+    pub check: Check,
+    #[serde(default = "enabled", skip_serializing_if = "is_enabled")]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Check {
+    Status {
+        equals: u16,
+    },
+    /// Passes when some value of the header matches the regular
+    /// expression.
+    Header {
+        name: String,
+        matches: String,
+    },
+    /// Checks the values of the JSON body that the JSONPath expression
+    /// selects.
+    JsonPath {
+        path: String,
+        #[serde(flatten)]
+        expect: Expectation,
+    },
+    ResponseTime {
+        below_ms: u64,
+    },
+    /// Validates the JSON body against a schema, written inline as JSON or
+    /// given as the path of a schema file relative to the folder of the
+    /// request.
+    JsonSchema {
+        schema: String,
+    },
+}
+
+/// What the values selected by a JSONPath expression are expected to be.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Expectation {
+    /// The value selected equals this one, or the values selected, as an
+    /// array, do when there are several.
+    Equals(Value),
+    /// A value selected is this one, or contains it: as a substring of a
+    /// string, an element of an array or a key of an object.
+    Contains(Value),
+    /// A value selected, as text, matches this regular expression.
+    Matches(String),
+}
+
+/// The outcome of an assertion on a response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssertionResult {
+    /// What was checked, such as `status equals 200`.
+    pub name: String,
+    /// Why the assertion failed, if it did.
+    pub failure: Option<String>,
+}
+
+impl AssertionResult {
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Check::Status { equals } => write!(f, "status equals {equals}"),
+            Check::Header { name, matches } => write!(f, "header {name} matches /{matches}/"),
+            Check::JsonPath { path, expect } => match expect {
+                Expectation::Equals(value) => write!(f, "{path} equals {value}"),
+                Expectation::Contains(value) => write!(f, "{path} contains {value}"),
+                Expectation::Matches(pattern) => write!(f, "{path} matches /{pattern}/"),
+            },
+            Check::ResponseTime { below_ms } => write!(f, "response time below {below_ms} ms"),
+            Check::JsonSchema { schema } if schema.trim_start().starts_with('{') => {
+                write!(f, "body matches the JSON schema")
+            }
+            Check::JsonSchema { schema } => write!(f, "body matches the JSON schema {schema}"),
+        }
+    }
+}
+
+/// Checks `response` against the enabled `assertions` of a request inside
+/// `dir`, reading the body from disk when it was spilled there.
+pub fn evaluate(
+    assertions: &[Assertion],
+    response: &HttpResponse,
+    dir: &Path,
+) -> Vec<AssertionResult> {
+    let mut body = None;
+
+    assertions
+        .iter()
+        .filter(|assertion| assertion.enabled)
+        .map(|assertion| {
+            let check = &assertion.check;
+            let result = match check {
+                Check::Status { equals } => check_status(response, *equals),
+                Check::Header { name, matches } => check_header(response, name, matches),
+                Check::JsonPath { path, expect } => body
+                    .get_or_insert_with(|| json_body(response))
+                    .as_ref()
+                    .map_err(|error| anyhow!("{error:#}"))
+                    .and_then(|body| check_json_path(body, path, expect)),
+                Check::ResponseTime { below_ms } => check_time(response.timing.total, *below_ms),
+                Check::JsonSchema { schema } => body
+                    .get_or_insert_with(|| json_body(response))
+                    .as_ref()
+                    .map_err(|error| anyhow!("{error:#}"))
+                    .and_then(|body| check_schema(body, schema, dir)),
+            };
+
+            AssertionResult {
+                name: check.to_string(),
+                failure: result.err().map(|error| format!("{error:#}")),
+            }
+        })
+        .collect()
+}
+
+fn check_status(response: &HttpResponse, expected: u16) -> Result<()> {
+    let status = response.status.as_u16();
+    if status != expected {
+        return Err(anyhow!("the status is {status}"));
+    }
+
+    Ok(())
+}
+
+fn check_header(response: &HttpResponse, name: &str, pattern: &str) -> Result<()> {
+    let regex = regex(pattern)?;
+    let values = response
+        .headers
+        .get_all(name.trim())
+        .iter()
+        .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+        .collect::<Vec<_>>();
+
+    match values.as_slice() {
+        [] => Err(anyhow!("there is no {name} header")),
+        values if values.iter().any(|value| regex.is_match(value)) => Ok(()),
+        [value] => Err(anyhow!("the header is {value:?}")),
+        values => Err(anyhow!("the headers are {}", values.join(", "))),
+    }
+}
+
+fn check_json_path(body: &Value, path: &str, expect: &Expectation) -> Result<()> {
+    let path = JsonPath::parse(path.trim())
+        .map_err(|error| anyhow!("invalid JSONPath {path:?}: {error}"))?;
+    let nodes = path.query(body).all();
+    let found = || match nodes.as_slice() {
+        [] => "nothing was found".to_string(),
+        [node] => format!("the value is {node}"),
+        nodes => format!(
+            "the values are {}",
+            Value::from_iter(nodes.iter().copied().cloned())
+        ),
+    };
+
+    let passed = match expect {
+        Expectation::Equals(expected) => match nodes.as_slice() {
+            [node] => same(node, expected),
+            nodes => same(&Value::from_iter(nodes.iter().copied().cloned()), expected),
+        },
+        Expectation::Contains(expected) => nodes.iter().any(|node| contains(node, expected)),
+        Expectation::Matches(pattern) => {
+            let regex = regex(pattern)?;
+            nodes.iter().any(|node| regex.is_match(&text(node)))
+        }
+    };
+
+    if !passed {
+        return Err(anyhow!(found()));
+    }
+
+    Ok(())
+}
+
+fn check_time(time: Duration, below_ms: u64) -> Result<()> {
+    if time >= Duration::from_millis(below_ms) {
+        return Err(anyhow!("the response took {} ms", time.as_millis()));
+    }
+
+    Ok(())
+}
+
+fn check_schema(body: &Value, schema: &str, dir: &Path) -> Result<()> {
+    let schema = if schema.trim_start().starts_with('{') {
+        serde_json::from_str(schema).context("parsing the schema")?
+    } else {
+        let path = dir.join(schema.trim());
+        let content =
+            fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+        serde_json::from_str(&content).with_context(|| format!("parsing {}", path.display()))?
+    };
+    let validator =
+        jsonschema::validator_for(&schema).map_err(|error| anyhow!("invalid schema: {error}"))?;
+
+    let errors = validator.iter_errors(body).collect::<Vec<_>>();
+    if errors.is_empty() {
+        return Ok(());
+    }
+
+    let mut violations = errors
+        .iter()
+        .take(MAX_SCHEMA_ERRORS)
+        .map(|error| match error.instance_path.to_string() {
+            path if path.is_empty() => error.to_string(),
+            path => format!("at {path}: {error}"),
+        })
+        .collect::<Vec<_>>();
+    if errors.len() > MAX_SCHEMA_ERRORS {
+        violations.push(format!("and {} more", errors.len() - MAX_SCHEMA_ERRORS));
+    }
+
+    Err(anyhow!("{}", violations.join("; ")))
+}
+
+/// The body of `response` parsed as JSON.
+fn json_body(response: &HttpResponse) -> Result<Value> {
+    let body = match &response.spilled {
+        Some(spilled) => Cow::Owned(fs::read(spilled.body.path()).context("reading the body")?),
+        None => Cow::Borrowed(&response.body[..]),
+    };
+
+    serde_json::from_slice(&body).context("the body isn't JSON")
+}
+
+fn regex(pattern: &str) -> Result<Regex> {
+    Regex::new(pattern).map_err(|error| anyhow!("invalid regular expression: {error}"))
+}
+
+/// Whether JSON values are equal, numbers being compared by value so that
+/// `1` equals `1.0`.
+fn same(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same(a, b))
+        }
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| same(a, b)))
+        }
+        (a, b) => a == b,
+    }
+}
+
+fn contains(value: &Value, expected: &Value) -> bool {
+    match (value, expected) {
+        (Value::String(value), Value::String(expected)) => value.contains(expected.as_str()),
+        (Value::Array(values), expected) => values.iter().any(|value| same(value, expected)),
+        (Value::Object(object), Value::String(key)) => object.contains_key(key),
+        (value, expected) => same(value, expected),
+    }
+}
+
+/// A JSON value as text, strings without their quotes.
+fn text(value: &Value) -> Cow<'_, str> {
+    match value {
+        Value::String(value) => Cow::Borrowed(value),
+        value => Cow::Owned(value.to_string()),
+    }
+}
@@ -0,0 +1,338 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use anyhow::{Context as _, Result};
+use http_client::{
+    AwsCredentials, AwsSigV4, DigestCredentials, HmacSigner, HttpRequest, OAuthClient, OAuthToken,
+    RequestOptions, RequestSigner, basic_authorization,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{config::CollectionConfig, scripts::Scripts, variables::interpolate};
+
+/// Name of the file configuring the requests of a folder, in that folder.
+pub const FOLDER_FILE: &str = "folder.json";
+
+/// How a request authenticates. Credentials may reference variables of the
+/// active environment as `{{name}}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Auth {
+    /// Sends no credentials, even when a parent folder or the collection
+    /// has some.
+    None,
+    Basic {
+        username: String,
+        password: String,
+    },
+    Bearer {
+        token: String,
+    },
+    /// Answers the Digest challenge of the server, sending the request
+    /// again.
+    Digest {
+        username: String,
+        password: String,
+    },
+    ApiKey {
+        name: String,
+        value: String,
+        #[serde(default)]
+        location: ApiKeyLocation,
+    },
+    /// Sends an access token got from an OAuth 2.0 authorization server.
+    #[serde(rename = "oauth2")]
+    OAuth2(OAuth2Config),
+    /// Signs the request with AWS Signature Version 4, as API Gateway and
+    /// other AWS services expect.
+    #[serde(rename = "aws_sigv4")]
+    AwsSigV4 {
+        service: String,
+        region: String,
+        #[serde(default)]
+        credentials: AwsCredentialSource,
+        /// Leaves the body out of the signature, which services such as S3
+        /// accept for large uploads.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        unsigned_payload: bool,
+    },
+    /// Signs a message made of parts of the request with a shared key.
+    Hmac(HmacSigner),
+}
+
+/// Where the credentials signing requests to AWS come from.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "from", rename_all = "snake_case")]
+pub enum AwsCredentialSource {
+    /// `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and `AWS_SESSION_TOKEN`.
+    #[default]
+    Environment,
+    /// A profile of the shared credentials file.
+    Profile { name: String },
+    Keys {
+        access_key_id: String,
+        secret_access_key: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        session_token: String,
+    },
+}
+
+impl AwsCredentialSource {
+    fn credentials(&self, resolve: impl Fn(&str) -> Result<String>) -> Result<AwsCredentials> {
+        match self {
+            AwsCredentialSource::Environment => AwsCredentials::from_env(),
+            AwsCredentialSource::Profile { name } => {
+                let name = resolve(name)?;
+                AwsCredentials::from_profile(if name.trim().is_empty() {
+                    "default"
+                } else {
+                    name.trim()
+                })
+            }
+            AwsCredentialSource::Keys {
+                access_key_id,
+                secret_access_key,
+                session_token,
+            } => Ok(AwsCredentials {
+                access_key_id: resolve(access_key_id)?,
+                secret_access_key: resolve(secret_access_key)?,
+                session_token: Some(resolve(session_token)?).filter(|token| !token.is_empty()),
+            }),
+        }
+    }
+}
+
+/// Where an API key is sent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyLocation {
+    #[default]
+    Header,
+    Query,
+}
+
+/// How an OAuth 2.0 client gets its access tokens, which are cached per
+/// environment rather than saved here.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OAuth2Config {
+    #[serde(default)]
+    pub grant: OAuthGrant,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub token_url: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub authorization_url: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub device_authorization_url: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub client_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub client_secret: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub scope: String,
+    /// The port the authorization code flow listens for the redirect on,
+    /// any free one when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redirect_port: Option<u16>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OAuthGrant {
+    #[default]
+    ClientCredentials,
+    /// With PKCE, the browser being redirected back to a listener on the
+    /// loopback interface.
+    AuthorizationCode,
+    DeviceCode,
+}
+
+impl OAuth2Config {
+    /// The client, with the variables of the active environment resolved.
+    pub fn client(&self, variables: &BTreeMap<String, String>) -> Result<OAuthClient> {
+        let resolve = |text: &str| {
+            interpolate(text, variables).context("resolving the OAuth 2.0 client of the request")
+        };
+
+        Ok(OAuthClient {
+            token_url: resolve(&self.token_url)?,
+            authorization_url: resolve(&self.authorization_url)?,
+            device_authorization_url: resolve(&self.device_authorization_url)?,
+            client_id: resolve(&self.client_id)?,
+            client_secret: resolve(&self.client_secret)?,
+            scope: resolve(&self.scope)?,
+            redirect_port: self.redirect_port.unwrap_or_default(),
+        })
+    }
+
+    /// What the tokens of `client` are cached under, so that requests using
+    /// the same client and scope share them.
+    pub fn token_key(&self, client: &OAuthClient) -> String {
+        let grant = match self.grant {
+            OAuthGrant::ClientCredentials => "client_credentials",
+            OAuthGrant::AuthorizationCode => "authorization_code",
+            OAuthGrant::DeviceCode => "device_code",
+        };
+
+        format!(
+            "{grant} {} {} {}",
+            client.token_url, client.client_id, client.scope
+        )
+    }
+}
+
+impl Auth {
+    /// Adds the credentials to `request`, or to `options` for those sent in
+    /// answer to a challenge and signatures, which are made last. A header
+    /// of the request set by hand takes precedence, except over signatures.
+    /// `token` is the access token cached for an OAuth 2.0 client.
+    pub fn apply(
+        &self,
+        request: &mut HttpRequest,
+        options: &mut RequestOptions,
+        variables: &BTreeMap<String, String>,
+        token: Option<&OAuthToken>,
+    ) -> Result<()> {
+        let resolve = |text: &str| {
+            interpolate(text, variables).context("resolving the credentials of the request")
+        };
+        let mut add_header = |name: &str, value: String| {
+            if !request
+                .headers
+                .iter()
+                .any(|(header, _)| header.trim().eq_ignore_ascii_case(name))
+            {
+                request.headers.push((name.to_string(), value));
+            }
+            options.auth_headers.push(name.to_string());
+        };
+
+        match self {
+            Auth::None => {}
+            Auth::Basic { username, password } => add_header(
+                "Authorization",
+                basic_authorization(&resolve(username)?, &resolve(password)?),
+            ),
+            Auth::Bearer { token } => {
+                add_header("Authorization", format!("Bearer {}", resolve(token)?))
+            }
+            Auth::Digest { username, password } => {
+                options.digest = Some(DigestCredentials {
+                    username: resolve(username)?,
+                    password: resolve(password)?,
+                });
+            }
+            Auth::ApiKey {
+                name,
+                value,
+                location: ApiKeyLocation::Header,
+            } => add_header(&resolve(name)?, resolve(value)?),
+            Auth::ApiKey {
+                name,
+                value,
+                location: ApiKeyLocation::Query,
+            } => {
+                let (name, value) = (resolve(name)?, resolve(value)?);
+                request.url.query_pairs_mut().append_pair(&name, &value);
+            }
+            Auth::OAuth2(_) => {
+                let token = token
+                    .context("no OAuth 2.0 access token yet, get one in the Auth tab first")?;
+                add_header("Authorization", token.authorization());
+            }
+            Auth::AwsSigV4 {
+                service,
+                region,
+                credentials,
+                unsigned_payload,
+            } => {
+                options.signer = Some(RequestSigner::AwsSigV4(AwsSigV4 {
+                    service: resolve(service)?,
+                    region: resolve(region)?,
+                    credentials: credentials
+                        .credentials(resolve)
+                        .context("getting the AWS credentials")?,
+                    unsigned_payload: *unsigned_payload,
+                }));
+            }
+            Auth::Hmac(signer) => {
+                options.signer = Some(RequestSigner::Hmac(HmacSigner {
+                    key: resolve(&signer.key)?,
+                    message: resolve(&signer.message)?,
+                    header: resolve(&signer.header)?,
+                    value: resolve(&signer.value)?,
+                    timestamp_header: signer
+                        .timestamp_header
+                        .as_deref()
+                        .map(resolve)
+                        .transpose()?,
+                    ..signer.clone()
+                }));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The configuration a folder of a collection gives the requests inside
+/// it, including those of its subfolders.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FolderConfig {
+    /// Used by requests of the folder that don't have their own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<Auth>,
+    /// Run around each request of the folder, before the scripts of inner
+    /// folders and of the request itself.
+    #[serde(default, skip_serializing_if = "Scripts::is_empty")]
+    pub scripts: Scripts,
+}
+
+impl FolderConfig {
+    /// The configuration of the folder at `dir`, empty when it has none.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(FOLDER_FILE);
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+
+        let content =
+            fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+
+        serde_json::from_str(&content).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let path = dir.join(FOLDER_FILE);
+        let mut content = serde_json::to_string_pretty(self)?;
+        content.push('\n');
+
+        fs::write(&path, content).with_context(|| format!("writing {}", path.display()))
+    }
+}
+
+/// The folders whose configuration applies to the requests inside `dir`,
+/// from `dir` up to the root of the collection of `config`.
+pub(crate) fn applying_folders<'a>(dir: &'a Path, config: &CollectionConfig) -> Vec<&'a Path> {
+    // Outside of a collection only the folder of the request counts.
+    let depth = if config.root.as_os_str().is_empty() {
+        1
+    } else {
+        dir.ancestors()
+            .take_while(|ancestor| ancestor.starts_with(&config.root))
+            .count()
+    };
+
+    dir.ancestors().take(depth).collect()
+}
+
+/// The authentication the requests inside `dir` inherit: that of the
+/// closest folder having one, up to the root of the collection of
+/// `config`, or else that of the collection.
+pub fn inherited_auth(dir: &Path, config: &CollectionConfig) -> Result<Option<Auth>> {
+    for folder in applying_folders(dir, config) {
+        if let Some(auth) = FolderConfig::load(folder)?.auth {
+            return Ok(Some(auth));
+        }
+    }
+
+    Ok(config.auth.clone())
+}
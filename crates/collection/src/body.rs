@@ -0,0 +1,290 @@
+use std::{
+    hash::{BuildHasher, RandomState},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Result, bail};
+use http_client::{HttpBody, QueryMode, graphql_body, url::form_urlencoded};
+use serde::{Deserialize, Serialize};
+
+use crate::request::{enabled, is_enabled};
+
+/// The body of a request.
+///
+/// Text bodies are stored as a plain string, the others as an object
+/// holding their content under `urlencoded`, `multipart`, `file` or
+/// `graphql`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "StoredBody", into = "StoredBody")]
+pub enum RequestBody {
+    Text(String),
+    /// Fields sent as `application/x-www-form-urlencoded`.
+    UrlEncoded(Vec<FormField>),
+    /// Parts sent as `multipart/form-data`.
+    Multipart(Vec<MultipartPart>),
+    /// A file sent as is, read from disk while the request is sent.
+    File(PathBuf),
+    /// A GraphQL document sent as `application/json`.
+    GraphQl(GraphQlBody),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphQlBody {
+    pub query: String,
+    /// The variables as JSON text, which may be left blank.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub variables: String,
+    /// The operation to run when the document has several.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation_name: Option<String>,
+    /// Sends the hash of the query rather than the query itself, which
+    /// servers supporting automatic persisted queries cache.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub persisted: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FormField {
+    pub name: String,
+    pub value: String,
+    #[serde(default = "enabled", skip_serializing_if = "is_enabled")]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MultipartPart {
+    pub name: String,
+    #[serde(flatten)]
+    pub content: PartContent,
+    /// Sent as the `Content-Type` of the part, which files default to
+    /// `application/octet-stream`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// The file name sent with the part, which defaults to the name of the
+    /// file for file parts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    #[serde(default = "enabled", skip_serializing_if = "is_enabled")]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PartContent {
+    Value(String),
+    File(PathBuf),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum StoredBody {
+    Text(String),
+    Form(FormBody),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum FormBody {
+    Urlencoded(Vec<FormField>),
+    Multipart(Vec<MultipartPart>),
+    File(PathBuf),
+    #[serde(rename = "graphql")]
+    GraphQl(GraphQlBody),
+}
+
+impl From<StoredBody> for RequestBody {
+    fn from(body: StoredBody) -> Self {
+        match body {
+            StoredBody::Text(text) => Self::Text(text),
+            StoredBody::Form(FormBody::Urlencoded(fields)) => Self::UrlEncoded(fields),
+            StoredBody::Form(FormBody::Multipart(parts)) => Self::Multipart(parts),
+            StoredBody::Form(FormBody::File(path)) => Self::File(path),
+            StoredBody::Form(FormBody::GraphQl(body)) => Self::GraphQl(body),
+        }
+    }
+}
+
+impl From<RequestBody> for StoredBody {
+    fn from(body: RequestBody) -> Self {
+        match body {
+            RequestBody::Text(text) => Self::Text(text),
+            RequestBody::UrlEncoded(fields) => Self::Form(FormBody::Urlencoded(fields)),
+            RequestBody::Multipart(parts) => Self::Form(FormBody::Multipart(parts)),
+            RequestBody::File(path) => Self::Form(FormBody::File(path)),
+            RequestBody::GraphQl(body) => Self::Form(FormBody::GraphQl(body)),
+        }
+    }
+}
+
+impl RequestBody {
+    /// The text of a text body.
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            Self::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// The body with `resolve` applied to its text, the names and values of
+    /// its enabled fields and parts, and the variables of its GraphQL
+    /// document.
+    pub fn interpolated(&self, resolve: &impl Fn(&str) -> Result<String>) -> Result<Self> {
+        Ok(match self {
+            Self::Text(text) => Self::Text(resolve(text)?),
+            Self::UrlEncoded(fields) => Self::UrlEncoded(
+                fields
+                    .iter()
+                    .map(|field| {
+                        if !field.enabled {
+                            return Ok(field.clone());
+                        }
+                        Ok(FormField {
+                            name: resolve(&field.name)?,
+                            value: resolve(&field.value)?,
+                            enabled: true,
+                        })
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+            Self::Multipart(parts) => Self::Multipart(
+                parts
+                    .iter()
+                    .map(|part| {
+                        if !part.enabled {
+                            return Ok(part.clone());
+                        }
+                        Ok(MultipartPart {
+                            name: resolve(&part.name)?,
+                            content: match &part.content {
+                                PartContent::Value(value) => PartContent::Value(resolve(value)?),
+                                PartContent::File(path) => PartContent::File(path.clone()),
+                            },
+                            content_type: part.content_type.as_deref().map(resolve).transpose()?,
+                            filename: part.filename.as_deref().map(resolve).transpose()?,
+                            enabled: true,
+                        })
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+            Self::File(path) => Self::File(path.clone()),
+            // The query is left alone, its selections being braces too.
+            Self::GraphQl(body) => Self::GraphQl(GraphQlBody {
+                variables: resolve(&body.variables)?,
+                ..body.clone()
+            }),
+        })
+    }
+
+    /// The body to send, with relative file paths resolved against `dir`,
+    /// along with the content type it implies, if any.
+    pub fn to_http_body(&self, dir: &Path) -> Result<(HttpBody, Option<String>)> {
+        Ok(match self {
+            Self::Text(text) => (text.clone().into(), None),
+            Self::UrlEncoded(fields) => {
+                let mut serializer = form_urlencoded::Serializer::new(String::new());
+                for field in fields.iter().filter(|field| field.enabled) {
+                    serializer.append_pair(&field.name, &field.value);
+                }
+
+                (
+                    serializer.finish().into(),
+                    Some("application/x-www-form-urlencoded".to_string()),
+                )
+            }
+            Self::Multipart(parts) => {
+                let boundary = boundary();
+                let mut body = HttpBody::default();
+
+                for part in parts.iter().filter(|part| part.enabled) {
+                    let mut head = format!(
+                        "--{boundary}\r\nContent-Disposition: form-data; name=\"{}\"",
+                        escape_quoted(&part.name)
+                    );
+
+                    let filename = part.filename.clone().or_else(|| match &part.content {
+                        PartContent::File(path) => path
+                            .file_name()
+                            .map(|name| name.to_string_lossy().into_owned()),
+                        PartContent::Value(_) => None,
+                    });
+                    if let Some(filename) = filename {
+                        head.push_str(&format!("; filename=\"{}\"", escape_quoted(&filename)));
+                    }
+
+                    let content_type = part.content_type.as_deref().or(match part.content {
+                        PartContent::File(_) => Some("application/octet-stream"),
+                        PartContent::Value(_) => None,
+                    });
+                    if let Some(content_type) = content_type {
+                        head.push_str(&format!("\r\nContent-Type: {content_type}"));
+                    }
+                    head.push_str("\r\n\r\n");
+
+                    body.push_bytes(head);
+                    match &part.content {
+                        PartContent::Value(value) => body.push_bytes(value.clone()),
+                        PartContent::File(path) if path.as_os_str().is_empty() => {
+                            bail!("no file chosen for part {:?}", part.name)
+                        }
+                        PartContent::File(path) => body.push_file(dir.join(path)),
+                    }
+                    body.push_bytes("\r\n");
+                }
+                body.push_bytes(format!("--{boundary}--\r\n"));
+
+                (
+                    body,
+                    Some(format!("multipart/form-data; boundary={boundary}")),
+                )
+            }
+            Self::File(path) if path.as_os_str().is_empty() => bail!("no file chosen for the body"),
+            Self::File(path) => (
+                HttpBody::file(dir.join(path)),
+                Some("application/octet-stream".to_string()),
+            ),
+            Self::GraphQl(body) => {
+                let mode = if body.persisted {
+                    QueryMode::PersistedHash
+                } else {
+                    QueryMode::Full
+                };
+
+                (
+                    body.to_json(mode)?.into(),
+                    Some("application/json".to_string()),
+                )
+            }
+        })
+    }
+}
+
+impl GraphQlBody {
+    /// The JSON sent for the document, with or without its query.
+    pub fn to_json(&self, mode: QueryMode) -> Result<String> {
+        graphql_body(
+            &self.query,
+            &self.variables,
+            self.operation_name.as_deref(),
+            mode,
+        )
+    }
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+/// A random boundary, which is all but certain not to appear in the parts.
+fn boundary() -> String {
+    let random = || RandomState::new().hash_one(0u8);
+
+    format!("----FormBoundary{:016x}{:016x}", random(), random())
+}
+
+/// Escapes a name for a quoted string of `Content-Disposition` the way
+/// browsers do.
+fn escape_quoted(name: &str) -> String {
+    name.replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
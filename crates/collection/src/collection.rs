@@ -1,24 +1,351 @@
-use gpui::{Render, div};
+pub mod assertions;
+pub mod auth;
+pub mod body;
+pub mod config;
+pub mod cookie_manager;
+pub mod curl;
+pub mod entry;
+pub mod grpc;
+pub mod request;
+pub mod scripts;
+pub mod secrets;
+pub mod session;
+pub mod variables;
+pub mod websocket;
+
+use std::{collections::HashSet, path::Path, sync::Arc};
+
+use gpui::{
+    AnyElement, App, AppContext, Context, Entity, EventEmitter, InteractiveElement, IntoElement,
+    ParentElement, Render, SharedString, StatefulInteractiveElement, Styled, Subscription, Task,
+    div, prelude::FluentBuilder, px,
+};
+use theme::ActiveTheme;
+use ui::{
+    color::Color,
+    components::{
+        button::ButtonSize,
+        dropdown::{Dropdown, DropdownEvent},
+        icon::{Icon, IconName, IconSize},
+        icon_button::IconButton,
+    },
+    placement::Placement,
+    traits::{clickable::Clickable, styled_ext::StyledExt},
+};
 use workspace::dock::Panel;
 
-pub struct Collection {}
+use crate::{
+    entry::{Entry, EntryKind},
+    session::Session,
+};
+
+pub fn init(cx: &mut App) {
+    cookie_manager::init(cx);
+}
+
+pub enum CollectionEvent {
+    OpenRequest(Arc<Path>),
+    OpenWebSocket(Arc<Path>),
+    OpenGrpc(Arc<Path>),
+    /// Opens the settings of a folder, the root one included.
+    OpenFolder(Arc<Path>),
+}
+
+pub struct Collection {
+    root: Option<Arc<Path>>,
+    entries: Vec<Entry>,
+    collapsed: HashSet<Arc<Path>>,
+    error: Option<SharedString>,
+    session: Option<Entity<Session>>,
+    environment_dropdown: Option<Entity<Dropdown>>,
+    _subscriptions: Vec<Subscription>,
+    _scan: Task<()>,
+}
+
+impl Collection {
+    pub fn new(root: Option<Arc<Path>>, cx: &mut Context<Self>) -> Self {
+        let session = root.clone().map(|root| Session::for_root(root, cx));
+        let mut subscriptions = Vec::new();
+
+        let environment_dropdown = session.as_ref().map(|session| {
+            let environments = session.read(cx).environments().to_vec();
+            let options = std::iter::once(SharedString::from("No Environment"))
+                .chain(environments.iter().cloned());
+            let dropdown = cx.new(|cx| Dropdown::new("environment", options, cx));
+
+            subscriptions.push(cx.subscribe(&dropdown, {
+                let session = session.clone();
+
+                move |_, _, event, cx| {
+                    let DropdownEvent::Changed(index) = event;
+                    let environment = index
+                        .checked_sub(1)
+                        .and_then(|index| environments.get(index))
+                        .map(ToString::to_string);
+
+                    session.update(cx, |session, cx| session.set_environment(environment, cx));
+                }
+            }));
+            subscriptions.push(cx.observe(session, |this, _, cx| this.sync_environment(cx)));
+
+            dropdown
+        });
+
+        let mut this = Self {
+            root,
+            entries: Vec::new(),
+            collapsed: HashSet::new(),
+            error: None,
+            session,
+            environment_dropdown,
+            _subscriptions: subscriptions,
+            _scan: Task::ready(()),
+        };
+
+        this.sync_environment(cx);
+        this.rescan(cx);
+        this
+    }
+
+    pub fn root(&self) -> Option<&Arc<Path>> {
+        self.root.as_ref()
+    }
+
+    /// The state requests of the collection share, when one is open.
+    pub fn session(&self) -> Option<&Entity<Session>> {
+        self.session.as_ref()
+    }
+
+    /// Shows the active environment of the session in the dropdown.
+    fn sync_environment(&mut self, cx: &mut Context<Self>) {
+        let (Some(session), Some(dropdown)) = (&self.session, &self.environment_dropdown) else {
+            return;
+        };
+
+        let session = session.read(cx);
+        let selected = session
+            .environment()
+            .and_then(|name| {
+                session
+                    .environments()
+                    .iter()
+                    .position(|known| known == name)
+            })
+            .map_or(0, |index| index + 1);
+
+        dropdown.update(cx, |dropdown, cx| {
+            if dropdown.selected_index() != Some(selected) {
+                dropdown.set_selected_index(Some(selected), cx);
+            }
+        });
+    }
+
+    pub fn rescan(&mut self, cx: &mut Context<Self>) {
+        let Some(root) = self.root.clone() else {
+            return;
+        };
+
+        let scan = cx.background_spawn(async move { entry::scan(&root) });
+
+        self._scan = cx.spawn(async move |this, cx| {
+            let result = scan.await;
+
+            this.update(cx, |this, cx| {
+                match result {
+                    Ok(entries) => {
+                        this.entries = entries;
+                        this.error = None;
+                    }
+                    Err(error) => this.error = Some(error.to_string().into()),
+                }
+
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn toggle_folder(&mut self, path: &Arc<Path>, cx: &mut Context<Self>) {
+        if !self.collapsed.remove(path) {
+            self.collapsed.insert(path.clone());
+        }
+
+        cx.notify();
+    }
+
+    fn render_entries(
+        &self,
+        entries: &[Entry],
+        depth: usize,
+        elements: &mut Vec<AnyElement>,
+        cx: &mut Context<Self>,
+    ) {
+        let colors = *cx.theme().colors();
+
+        for entry in entries {
+            let path = entry.path.clone();
+            let collapsed = self.collapsed.contains(&path);
+
+            let row = div()
+                .id(SharedString::from(path.to_string_lossy().into_owned()))
+                .h_flex()
+                .w_full()
+                .h_6()
+                .pl(px(8.0 + depth as f32 * 12.0))
+                .pr_2()
+                .gap_1()
+                .cursor_pointer()
+                .hover(|this| this.bg(colors.list_hover))
+                .map(|this| match entry.kind {
+                    EntryKind::Folder(_) => this
+                        .child(
+                            Icon::new(if collapsed {
+                                IconName::ChevronRight
+                            } else {
+                                IconName::ChevronDown
+                            })
+                            .size(IconSize::Small)
+                            .color(Color::Muted),
+                        )
+                        .child(
+                            Icon::new(if collapsed {
+                                IconName::Folder
+                            } else {
+                                IconName::FolderOpen
+                            })
+                            .size(IconSize::Small),
+                        )
+                        .child(div().flex_1().truncate().child(entry.name.clone()))
+                        .child(
+                            IconButton::new("folder-settings", IconName::Settings)
+                                .size(ButtonSize::Compact)
+                                .tooltip("Folder Settings")
+                                .on_click({
+                                    let path = path.clone();
+
+                                    cx.listener(move |_, _, _, cx| {
+                                        cx.emit(CollectionEvent::OpenFolder(path.clone()))
+                                    })
+                                }),
+                        )
+                        .on_click(cx.listener(move |this, _, _, cx| {
+                            this.toggle_folder(&path, cx);
+                        })),
+                    EntryKind::Request | EntryKind::WebSocket | EntryKind::Grpc => this
+                        .text_color(colors.sidebar_foreground)
+                        .child(div().w(IconSize::Small.rems()))
+                        .child(
+                            Icon::new(match entry.kind {
+                                EntryKind::WebSocket => IconName::Plug,
+                                EntryKind::Grpc => IconName::Zap,
+                                _ => IconName::File,
+                            })
+                            .size(IconSize::Small)
+                            .color(Color::Muted),
+                        )
+                        .child(entry.name.clone())
+                        .on_click({
+                            let open: fn(Arc<Path>) -> CollectionEvent = match entry.kind {
+                                EntryKind::WebSocket => CollectionEvent::OpenWebSocket,
+                                EntryKind::Grpc => CollectionEvent::OpenGrpc,
+                                _ => CollectionEvent::OpenRequest,
+                            };
+
+                            cx.listener(move |_, _, _, cx| cx.emit(open(path.clone())))
+                        }),
+                });
+
+            elements.push(row.into_any_element());
+
+            if let EntryKind::Folder(children) = &entry.kind
+                && !collapsed
+            {
+                self.render_entries(children, depth + 1, elements, cx);
+            }
+        }
+    }
+}
+
+impl EventEmitter<CollectionEvent> for Collection {}
 
 impl Panel for Collection {
     fn priority(&self) -> u32 {
         0
     }
 
-    fn placement(&self) -> ui::placement::Placement {
-        ui::placement::Placement::Left
+    fn placement(&self) -> Placement {
+        Placement::Left
     }
 }
 
 impl Render for Collection {
     fn render(
         &mut self,
-        window: &mut gpui::Window,
+        _window: &mut gpui::Window,
         cx: &mut gpui::Context<Self>,
     ) -> impl gpui::IntoElement {
+        let colors = *cx.theme().colors();
+
+        let title: SharedString = self
+            .root
+            .as_ref()
+            .and_then(|root| root.file_name())
+            .map(|name| name.to_string_lossy().into_owned().into())
+            .unwrap_or_else(|| "No collection".into());
+
+        let mut rows = Vec::new();
+        self.render_entries(&self.entries, 0, &mut rows, cx);
+
         div()
+            .id("collection")
+            .v_flex()
+            .w_64()
+            .h_full()
+            .bg(colors.sidebar)
+            .border_r_1()
+            .border_color(colors.sidebar_border)
+            .child(
+                div()
+                    .h_flex()
+                    .h_8()
+                    .px_2()
+                    .text_color(colors.sidebar_foreground)
+                    .child(div().flex_1().truncate().child(title))
+                    .when_some(self.root.clone(), |this, root| {
+                        this.child(
+                            IconButton::new("collection-settings", IconName::Settings)
+                                .size(ButtonSize::Compact)
+                                .tooltip("Collection Folder Settings")
+                                .on_click(cx.listener(move |_, _, _, cx| {
+                                    cx.emit(CollectionEvent::OpenFolder(root.clone()))
+                                })),
+                        )
+                    }),
+            )
+            .when_some(
+                self.environment_dropdown
+                    .clone()
+                    .filter(|_| self.session.is_some()),
+                |this, dropdown| this.child(div().px_2().pb_2().child(dropdown)),
+            )
+            .when_some(self.error.clone(), |this, error| {
+                this.child(div().px_2().text_color(colors.danger).child(error))
+            })
+            .when(self.root.is_none(), |this| {
+                this.child(
+                    div()
+                        .px_2()
+                        .text_color(colors.muted_foreground)
+                        .child("Open a collection folder to list its requests."),
+                )
+            })
+            .child(
+                div()
+                    .id("collection-entries")
+                    .v_flex()
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .children(rows),
+            )
     }
 }
@@ -0,0 +1,209 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, Result};
+use http_client::{
+    ClientCertificate, HttpVersion, Proxy, RedirectPolicy, RequestOptions, TlsOptions, TlsVersion,
+    url::Url,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::Auth;
+
+/// Name of the file configuring how the requests of a collection are sent,
+/// at the root of the collection.
+pub const CONFIG_FILE: &str = "collection.json";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CollectionConfig {
+    /// The folder holding the configuration, which relative paths in it are
+    /// resolved against.
+    #[serde(skip)]
+    pub root: PathBuf,
+    #[serde(default)]
+    pub tls: TlsConfig,
+    /// Overrides for requests to given hosts, either exact names or
+    /// wildcards such as `*.internal.example.com`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub hosts: BTreeMap<String, HostConfig>,
+    /// Named contexts requests are sent in, such as `staging` or
+    /// `production`, each with its own variables and cookies.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub environments: BTreeMap<String, Environment>,
+    /// Used by requests that neither have their own nor inherit one from a
+    /// folder.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<Auth>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Environment {
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, String>,
+    /// Variables whose values each user keeps in the vault of the
+    /// collection rather than in this file.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub secrets: BTreeSet<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HostConfig {
+    #[serde(default)]
+    pub tls: TlsConfig,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TlsConfig {
+    /// PEM bundles of extra certificate authorities to trust.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ca_certificates: Vec<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_certificate: Option<ClientCertificate>,
+    /// The name to send as SNI instead of the host of the URL.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_version: Option<TlsVersion>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip_verification: Option<bool>,
+}
+
+impl TlsConfig {
+    /// Layers `other` on top, adding its CA bundles and taking its other
+    /// fields where set.
+    fn merge(&mut self, other: &TlsConfig) {
+        self.ca_certificates
+            .extend(other.ca_certificates.iter().cloned());
+
+        if other.client_certificate.is_some() {
+            self.client_certificate = other.client_certificate.clone();
+        }
+        if other.server_name.is_some() {
+            self.server_name = other.server_name.clone();
+        }
+        self.min_version = other.min_version.or(self.min_version);
+        self.skip_verification = other.skip_verification.or(self.skip_verification);
+    }
+}
+
+impl CollectionConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        let mut config: Self = serde_json::from_str(&content)
+            .with_context(|| format!("parsing {}", path.display()))?;
+
+        config.root = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(config)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = self.root.join(CONFIG_FILE);
+        let mut content = serde_json::to_string_pretty(self)?;
+        content.push('\n');
+
+        fs::write(&path, content).with_context(|| format!("writing {}", path.display()))
+    }
+
+    /// The configuration of the collection holding the request at
+    /// `request_path`, found in its folder or the closest parent having one.
+    pub fn for_request(request_path: &Path) -> Result<Self> {
+        for dir in request_path.ancestors().skip(1) {
+            let path = dir.join(CONFIG_FILE);
+
+            if path.is_file() {
+                return Self::load(&path);
+            }
+        }
+
+        Ok(Self::default())
+    }
+
+    /// The variables of `environment`, with the values of its secrets taken
+    /// from `secrets`. None when there is no such environment.
+    pub fn variables(
+        &self,
+        environment: Option<&str>,
+        secrets: Option<&BTreeMap<String, String>>,
+    ) -> BTreeMap<String, String> {
+        let Some(environment) = environment.and_then(|name| self.environments.get(name)) else {
+            return BTreeMap::new();
+        };
+
+        let mut variables = environment.variables.clone();
+        for name in &environment.secrets {
+            match secrets.and_then(|secrets| secrets.get(name)) {
+                Some(value) => variables.insert(name.clone(), value.clone()),
+                None => variables.remove(name),
+            };
+        }
+
+        variables
+    }
+
+    /// How to send a request to `url`, given the HTTP version, proxy and
+    /// redirect policy it goes with.
+    pub fn request_options(
+        &self,
+        url: &Url,
+        version: HttpVersion,
+        proxy: Option<Proxy>,
+        redirect: RedirectPolicy,
+    ) -> RequestOptions {
+        RequestOptions {
+            version,
+            tls: self.tls_options(url.host_str().unwrap_or_default()),
+            proxy,
+            redirect,
+            ..RequestOptions::default()
+        }
+    }
+
+    /// The TLS settings for requests to `host`, with host overrides applied
+    /// and paths made absolute.
+    pub fn tls_options(&self, host: &str) -> TlsOptions {
+        let mut tls = self.tls.clone();
+
+        for (pattern, config) in &self.hosts {
+            if host_matches(pattern, host) {
+                tls.merge(&config.tls);
+            }
+        }
+
+        let resolve = |path: &PathBuf| self.root.join(path);
+        let client_certificate = tls.client_certificate.map(|certificate| match certificate {
+            ClientCertificate::Pem { certificate, key } => ClientCertificate::Pem {
+                certificate: resolve(&certificate),
+                key: resolve(&key),
+            },
+            ClientCertificate::Pkcs12 { path, password } => ClientCertificate::Pkcs12 {
+                path: resolve(&path),
+                password,
+            },
+        });
+
+        TlsOptions {
+            ca_certificates: tls.ca_certificates.iter().map(resolve).collect(),
+            client_certificate,
+            server_name: tls.server_name,
+            min_version: tls.min_version.unwrap_or_default(),
+            skip_verification: tls.skip_verification.unwrap_or_default(),
+        }
+    }
+}
+
+fn host_matches(pattern: &str, host: &str) -> bool {
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host
+            .len()
+            .checked_sub(domain.len())
+            .and_then(|split| Some((host.get(..split)?, host.get(split..)?)))
+            .is_some_and(|(prefix, suffix)| {
+                prefix.ends_with('.') && suffix.eq_ignore_ascii_case(domain)
+            }),
+        None => pattern.eq_ignore_ascii_case(host),
+    }
+}
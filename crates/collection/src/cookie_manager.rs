@@ -0,0 +1,360 @@
+use gpui::{
+    App, AppContext, Context, Entity, InteractiveElement, IntoElement, ParentElement, Render,
+    SharedString, StatefulInteractiveElement, Styled, Subscription, Window, actions, div,
+    prelude::FluentBuilder, px,
+};
+use theme::ActiveTheme;
+use ui::{
+    color::Color,
+    components::{
+        button::{Button, ButtonSize, ButtonStyle},
+        dropdown::{Dropdown, DropdownEvent},
+        icon::IconName,
+        icon_button::IconButton,
+        input::{InputEvent, TextInput},
+    },
+    traits::{clickable::Clickable, disableable::Disableable, styled_ext::StyledExt},
+};
+use workspace::{Workspace, item::Item};
+
+use crate::session::Session;
+
+actions!(
+    collection,
+    [
+        /// Opens a tab listing the cookies received in each environment of
+        /// the collection.
+        ManageCookies,
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &ManageCookies, _, cx| {
+            let Some(root) = workspace.root().cloned() else {
+                return;
+            };
+
+            let existing = workspace
+                .center()
+                .read(cx)
+                .items()
+                .find_map(|item| item.to_any().downcast::<CookieManager>().ok());
+
+            match existing {
+                Some(manager) => {
+                    workspace.center().update(cx, |area, cx| {
+                        if let Some(index) = area.index_for_item_id(manager.entity_id()) {
+                            area.activate_item(index, cx);
+                        }
+                    });
+                }
+                None => {
+                    let session = Session::for_root(root, cx);
+                    let manager = cx.new(|cx| CookieManager::new(session, cx));
+
+                    workspace.add_item(Box::new(manager), cx);
+                }
+            }
+        });
+    })
+    .detach();
+}
+
+/// A center area item to review, edit and delete the cookies of a
+/// collection, one environment at a time.
+pub struct CookieManager {
+    session: Entity<Session>,
+    environment_dropdown: Entity<Dropdown>,
+    /// The environment whose cookies are listed, the active one at first.
+    environment: Option<String>,
+    value_inputs: Vec<Entity<TextInput>>,
+    _value_subscriptions: Vec<Subscription>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl CookieManager {
+    fn new(session: Entity<Session>, cx: &mut Context<Self>) -> Self {
+        let (environments, environment) = {
+            let session = session.read(cx);
+
+            (
+                session.environments().to_vec(),
+                session.environment().map(str::to_string),
+            )
+        };
+
+        let environment_dropdown = cx.new(|cx| {
+            let options = std::iter::once(SharedString::from("No Environment"))
+                .chain(environments.iter().cloned());
+            let selected = environment
+                .as_ref()
+                .and_then(|name| environments.iter().position(|known| known == name))
+                .map_or(0, |index| index + 1);
+
+            let mut dropdown = Dropdown::new("cookie-environment", options, cx);
+            dropdown.set_selected_index(Some(selected), cx);
+            dropdown
+        });
+
+        let subscriptions = vec![
+            cx.observe(&session, |this, _, cx| this.sync_value_inputs(cx)),
+            cx.subscribe(&environment_dropdown, move |this, _, event, cx| {
+                let DropdownEvent::Changed(index) = event;
+
+                this.environment = index
+                    .checked_sub(1)
+                    .and_then(|index| environments.get(index))
+                    .map(ToString::to_string);
+                this.sync_value_inputs(cx);
+            }),
+        ];
+
+        let mut this = Self {
+            session,
+            environment_dropdown,
+            environment,
+            value_inputs: Vec::new(),
+            _value_subscriptions: Vec::new(),
+            _subscriptions: subscriptions,
+        };
+
+        this.sync_value_inputs(cx);
+        this
+    }
+
+    /// Keeps one input per listed cookie, updating those whose value changed
+    /// elsewhere without disturbing the one being edited.
+    fn sync_value_inputs(&mut self, cx: &mut Context<Self>) {
+        let values = self
+            .session
+            .read(cx)
+            .cookies(self.environment.as_deref())
+            .map(|jar| {
+                jar.cookies()
+                    .iter()
+                    .map(|cookie| cookie.value.clone())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        if values.len() != self.value_inputs.len() {
+            self.value_inputs = values
+                .iter()
+                .map(|_| cx.new(|cx| TextInput::new(cx).placeholder("Value")))
+                .collect();
+            self._value_subscriptions = self
+                .value_inputs
+                .iter()
+                .enumerate()
+                .map(|(index, input)| {
+                    cx.subscribe(input, move |this, input, event, cx| {
+                        if let InputEvent::Changed = event {
+                            let value = input.read(cx).text().to_string();
+                            this.set_value(index, value, cx);
+                        }
+                    })
+                })
+                .collect();
+        }
+
+        for (input, value) in self.value_inputs.iter().zip(values) {
+            if input.read(cx).text().as_ref() != value {
+                input.update(cx, |input, cx| input.set_text(value, cx));
+            }
+        }
+
+        cx.notify();
+    }
+
+    fn set_value(&mut self, index: usize, value: String, cx: &mut Context<Self>) {
+        let environment = self.environment.clone();
+
+        self.session.update(cx, |session, cx| {
+            session.update_cookies(
+                environment.as_deref(),
+                |jar| {
+                    let Some(mut cookie) = jar.cookies().get(index).cloned() else {
+                        return false;
+                    };
+                    if cookie.value == value {
+                        return false;
+                    }
+
+                    cookie.value = value;
+                    jar.replace(index, cookie);
+                    true
+                },
+                cx,
+            )
+        });
+    }
+
+    fn delete(&mut self, index: usize, cx: &mut Context<Self>) {
+        let environment = self.environment.clone();
+
+        self.session.update(cx, |session, cx| {
+            session.update_cookies(
+                environment.as_deref(),
+                |jar| jar.remove(index).is_some(),
+                cx,
+            )
+        });
+    }
+
+    fn clear(&mut self, cx: &mut Context<Self>) {
+        let environment = self.environment.clone();
+
+        self.session.update(cx, |session, cx| {
+            session.update_cookies(
+                environment.as_deref(),
+                |jar| {
+                    let changed = !jar.is_empty();
+                    jar.clear();
+                    changed
+                },
+                cx,
+            )
+        });
+    }
+}
+
+impl Item for CookieManager {
+    fn tab_content_text(&self, _cx: &App) -> SharedString {
+        "Cookies".into()
+    }
+
+    fn tab_icon(&self, _cx: &App) -> Option<IconName> {
+        Some(IconName::Cookie)
+    }
+}
+
+impl Render for CookieManager {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = *cx.theme().colors();
+        let session = self.session.read(cx);
+        let cookies = session
+            .cookies(self.environment.as_deref())
+            .map(|jar| jar.cookies().to_vec())
+            .unwrap_or_default();
+        let error = session.error().cloned();
+
+        let column = |width| div().w(width).flex_none().truncate();
+        let header = div()
+            .h_flex()
+            .gap_2()
+            .px_2()
+            .pb_1()
+            .border_b_1()
+            .border_color(colors.border)
+            .text_xs()
+            .text_color(colors.muted_foreground)
+            .child(column(px(140.)).child("Name"))
+            .child(div().flex_1().child("Value"))
+            .child(column(px(160.)).child("Domain"))
+            .child(column(px(80.)).child("Path"))
+            .child(column(px(170.)).child("Expires"))
+            .child(column(px(110.)).child("Flags"))
+            .child(div().w_6().flex_none());
+
+        let rows =
+            cookies
+                .iter()
+                .zip(&self.value_inputs)
+                .enumerate()
+                .map(|(index, (cookie, input))| {
+                    let domain = if cookie.include_subdomains {
+                        format!(".{}", cookie.domain)
+                    } else {
+                        cookie.domain.clone()
+                    };
+                    let flags = [
+                        cookie.secure.then_some("Secure"),
+                        cookie.http_only.then_some("HttpOnly"),
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                    div()
+                        .id(("cookie", index))
+                        .h_flex()
+                        .gap_2()
+                        .px_2()
+                        .py_0p5()
+                        .text_sm()
+                        .hover(|this| this.bg(colors.list_hover))
+                        .child(column(px(140.)).child(cookie.name.clone()))
+                        .child(div().flex_1().min_w_0().child(input.clone()))
+                        .child(column(px(160.)).child(domain))
+                        .child(column(px(80.)).child(cookie.path.clone()))
+                        .child(
+                            column(px(170.)).text_color(colors.muted_foreground).child(
+                                cookie
+                                    .expires_display()
+                                    .unwrap_or_else(|| "Session".to_string()),
+                            ),
+                        )
+                        .child(
+                            column(px(110.))
+                                .text_color(colors.muted_foreground)
+                                .child(flags),
+                        )
+                        .child(
+                            IconButton::new(("delete-cookie", index), IconName::Trash)
+                                .icon_color(Color::Muted)
+                                .style(ButtonStyle::Ghost)
+                                .size(ButtonSize::Compact)
+                                .tooltip("Delete Cookie")
+                                .on_click(
+                                    cx.listener(move |this, _, _, cx| this.delete(index, cx)),
+                                ),
+                        )
+                });
+
+        div()
+            .v_flex()
+            .size_full()
+            .p_2()
+            .gap_2()
+            .child(
+                div()
+                    .h_flex()
+                    .gap_2()
+                    .child(div().flex_1().text_sm().child(format!(
+                        "{} cookie{}",
+                        cookies.len(),
+                        if cookies.len() == 1 { "" } else { "s" }
+                    )))
+                    .child(self.environment_dropdown.clone())
+                    .child(
+                        Button::new("clear-cookies", "Clear All")
+                            .style(ButtonStyle::Danger)
+                            .disabled(cookies.is_empty())
+                            .on_click(cx.listener(|this, _, _, cx| this.clear(cx))),
+                    ),
+            )
+            .when_some(error, |this, error| {
+                this.child(div().text_sm().text_color(colors.danger).child(error))
+            })
+            .child(header)
+            .child(
+                div()
+                    .id("cookies")
+                    .v_flex()
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .children(rows)
+                    .when(cookies.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .p_2()
+                                .text_sm()
+                                .text_color(colors.muted_foreground)
+                                .child("No cookies were received in this environment."),
+                        )
+                    }),
+            )
+    }
+}
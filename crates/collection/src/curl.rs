@@ -0,0 +1,248 @@
+use std::{borrow::Cow, net::IpAddr, path::Path, time::SystemTime};
+
+use http_client::{
+    BodyPart, ClientCertificate, HttpRequest, HttpVersion, RequestOptions, RequestSigner,
+    TlsVersion,
+    http::{Method, header::CONTENT_TYPE},
+};
+
+use crate::body::{PartContent, RequestBody};
+
+/// The curl command sending `request` the way `options` say, one option
+/// per line. Multipart forms with files are written as `--form` arguments
+/// from `body`, the body of the request file, since curl has no other way
+/// of sending files along with text. HMAC signatures and the server name
+/// of the TLS options are left out, curl having no equivalent.
+pub fn curl_command(
+    request: &HttpRequest,
+    body: Option<&RequestBody>,
+    dir: &Path,
+    options: &RequestOptions,
+) -> String {
+    let mut args = Vec::new();
+
+    let parts = request.body.parts();
+    let has_body = !request.body.is_empty();
+    let form = match body {
+        Some(RequestBody::Multipart(form))
+            if parts.iter().any(|part| matches!(part, BodyPart::File(_))) =>
+        {
+            Some(form)
+        }
+        _ => None,
+    };
+
+    match (&request.method, has_body) {
+        (&Method::GET, false) | (&Method::POST, true) => {}
+        (method, _) => args.push(line(&["-X", method.as_str()])),
+    }
+
+    for (name, value) in &request.headers {
+        // curl writes the boundary of the forms it sends itself.
+        if form.is_some() && name.trim().eq_ignore_ascii_case(CONTENT_TYPE.as_str()) {
+            continue;
+        }
+        args.push(line(&["-H", &format!("{}: {value}", name.trim())]));
+    }
+
+    let has_cookie_header = request
+        .headers
+        .iter()
+        .any(|(name, _)| name.trim().eq_ignore_ascii_case("cookie"));
+    if !has_cookie_header
+        && let Some(cookies) = options
+            .cookies
+            .as_ref()
+            .and_then(|jar| jar.header_for(&request.url, SystemTime::now()))
+    {
+        args.push(line(&["-b", &cookies]));
+    }
+
+    match (form, parts) {
+        (Some(form), _) => {
+            for part in form.iter().filter(|part| part.enabled) {
+                match &part.content {
+                    PartContent::Value(value) => {
+                        args.push(line(&["--form-string", &format!("{}={value}", part.name)]))
+                    }
+                    PartContent::File(path) => {
+                        let mut field =
+                            format!("{}=@{}", part.name, dir.join(path).to_string_lossy());
+                        if let Some(content_type) = &part.content_type {
+                            field.push_str(&format!(";type={content_type}"));
+                        }
+                        if let Some(filename) = &part.filename {
+                            field.push_str(&format!(";filename={filename}"));
+                        }
+                        args.push(line(&["-F", &field]));
+                    }
+                }
+            }
+        }
+        (None, [BodyPart::File(path)]) => args.push(line(&[
+            "--data-binary",
+            &format!("@{}", path.to_string_lossy()),
+        ])),
+        (None, parts) if has_body => {
+            let bytes: Vec<u8> = parts
+                .iter()
+                .filter_map(|part| match part {
+                    BodyPart::Bytes(bytes) => Some(&bytes[..]),
+                    BodyPart::File(_) => None,
+                })
+                .flatten()
+                .copied()
+                .collect();
+
+            match String::from_utf8(bytes) {
+                Ok(text) => args.push(line(&["--data-raw", &text])),
+                Err(error) => args.push(format!("--data-binary {}", quote_bytes(error.as_bytes()))),
+            }
+        }
+        (None, _) => {}
+    }
+
+    match options.version {
+        HttpVersion::Auto => {}
+        HttpVersion::Http1 => args.push(line(&["--http1.1"])),
+        HttpVersion::Http2 => args.push(line(&["--http2"])),
+        HttpVersion::Http2PriorKnowledge => args.push(line(&["--http2-prior-knowledge"])),
+    }
+
+    if let Some(socket) = &options.unix_socket {
+        args.push(line(&["--unix-socket", &socket.to_string_lossy()]));
+    } else if let Some(proxy) = &options.proxy {
+        args.push(line(&["-x", proxy.url().as_str().trim_end_matches('/')]));
+    }
+
+    let default_port = request.url.port_or_known_default().unwrap_or(80);
+    for host in &options.resolve {
+        let port = host.port.unwrap_or(default_port);
+        let address = match host.address {
+            IpAddr::V4(address) => address.to_string(),
+            IpAddr::V6(address) => format!("[{address}]"),
+        };
+        args.push(line(&[
+            "--resolve",
+            &format!("{}:{port}:{address}", host.host),
+        ]));
+    }
+
+    let redirect = &options.redirect;
+    if redirect.follow {
+        args.push(line(&["-L"]));
+        args.push(line(&["--max-redirs", &redirect.max_hops.to_string()]));
+        if redirect.forward_auth {
+            args.push(line(&["--location-trusted"]));
+        }
+        if redirect.keep_method {
+            args.push(line(&["--post301", "--post302"]));
+        }
+    }
+
+    let tls = &options.tls;
+    if tls.skip_verification {
+        args.push(line(&["-k"]));
+    }
+    for path in &tls.ca_certificates {
+        args.push(line(&["--cacert", &path.to_string_lossy()]));
+    }
+    match &tls.client_certificate {
+        None => {}
+        Some(ClientCertificate::Pem { certificate, key }) => args.push(line(&[
+            "--cert",
+            &certificate.to_string_lossy(),
+            "--key",
+            &key.to_string_lossy(),
+        ])),
+        Some(ClientCertificate::Pkcs12 { path, password }) => {
+            // curl reads the password after the last colon of the path.
+            let mut certificate = path.to_string_lossy().replace(':', "\\:");
+            if !password.is_empty() {
+                certificate.push_str(&format!(":{password}"));
+            }
+            args.push(line(&["--cert-type", "P12", "--cert", &certificate]));
+        }
+    }
+    if tls.min_version == TlsVersion::Tls13 {
+        args.push(line(&["--tlsv1.3"]));
+    }
+
+    if let Some(digest) = &options.digest {
+        args.push(line(&[
+            "--digest",
+            "-u",
+            &format!("{}:{}", digest.username, digest.password),
+        ]));
+    }
+    if let Some(RequestSigner::AwsSigV4(signer)) = &options.signer {
+        let credentials = &signer.credentials;
+        args.push(line(&[
+            "--aws-sigv4",
+            &format!("aws:amz:{}:{}", signer.region, signer.service),
+            "-u",
+            &format!(
+                "{}:{}",
+                credentials.access_key_id, credentials.secret_access_key
+            ),
+        ]));
+        if let Some(token) = &credentials.session_token {
+            args.push(line(&["-H", &format!("x-amz-security-token: {token}")]));
+        }
+    }
+
+    if let Some(connect) = options.timeouts.connect {
+        args.push(line(&[
+            "--connect-timeout",
+            &connect.as_secs_f64().to_string(),
+        ]));
+    }
+    if let Some(total) = options.timeouts.total {
+        args.push(line(&["--max-time", &total.as_secs_f64().to_string()]));
+    }
+
+    let mut command = format!("curl {}", quote(request.url.as_str()));
+    for arg in args {
+        command.push_str(" \\\n  ");
+        command.push_str(&arg);
+    }
+    command
+}
+
+/// The arguments `parts` quoted for a shell, on one line.
+fn line(parts: &[&str]) -> String {
+    parts
+        .iter()
+        .map(|part| quote(part))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `text` as a single shell word, quoted unless it needs not be.
+fn quote(text: &str) -> Cow<'_, str> {
+    let safe = !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c));
+
+    if safe {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(format!("'{}'", text.replace('\'', r"'\''")))
+    }
+}
+
+/// `bytes` that aren't UTF-8 as a single shell word, written with the
+/// `$'...'` quoting of bash and zsh.
+fn quote_bytes(bytes: &[u8]) -> String {
+    let mut quoted = String::from("$'");
+    for &byte in bytes {
+        match byte {
+            b'\'' | b'\\' => quoted.push_str(&format!("\\{}", byte as char)),
+            0x20..=0x7e => quoted.push(byte as char),
+            byte => quoted.push_str(&format!("\\x{byte:02x}")),
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
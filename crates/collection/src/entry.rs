@@ -0,0 +1,101 @@
+use std::{fs, path::Path, sync::Arc};
+
+use anyhow::Result;
+use gpui::SharedString;
+
+/// Suffix of the files holding a single [`crate::request::Request`].
+pub const REQUEST_SUFFIX: &str = ".request.json";
+/// Suffix of the files holding a single
+/// [`crate::websocket::WebSocketRequest`].
+pub const WEBSOCKET_SUFFIX: &str = ".websocket.json";
+/// Suffix of the files holding a single [`crate::grpc::GrpcRequest`].
+pub const GRPC_SUFFIX: &str = ".grpc.json";
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub name: SharedString,
+    pub path: Arc<Path>,
+    pub kind: EntryKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum EntryKind {
+    Folder(Vec<Entry>),
+    Request,
+    WebSocket,
+    Grpc,
+}
+
+impl Entry {
+    pub fn is_folder(&self) -> bool {
+        matches!(self.kind, EntryKind::Folder(_))
+    }
+}
+
+/// Walks `dir` and returns its folders, request, WebSocket and gRPC files,
+/// folders first and each group sorted by name. Hidden entries are skipped.
+pub fn scan(dir: &Path) -> Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+
+    for dir_entry in fs::read_dir(dir)? {
+        let dir_entry = dir_entry?;
+        let file_name = dir_entry.file_name().to_string_lossy().into_owned();
+
+        if file_name.starts_with('.') {
+            continue;
+        }
+
+        let path: Arc<Path> = dir_entry.path().into();
+
+        if dir_entry.file_type()?.is_dir() {
+            entries.push(Entry {
+                name: file_name.into(),
+                kind: EntryKind::Folder(scan(&path)?),
+                path,
+            });
+        } else if let Some(name) = file_name.strip_suffix(REQUEST_SUFFIX) {
+            entries.push(Entry {
+                name: name.to_owned().into(),
+                kind: EntryKind::Request,
+                path,
+            });
+        } else if let Some(name) = file_name.strip_suffix(WEBSOCKET_SUFFIX) {
+            entries.push(Entry {
+                name: name.to_owned().into(),
+                kind: EntryKind::WebSocket,
+                path,
+            });
+        } else if let Some(name) = file_name.strip_suffix(GRPC_SUFFIX) {
+            entries.push(Entry {
+                name: name.to_owned().into(),
+                kind: EntryKind::Grpc,
+                path,
+            });
+        }
+    }
+
+    entries.sort_by(|a, b| {
+        b.is_folder()
+            .cmp(&a.is_folder())
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    Ok(entries)
+}
+
+/// The display name of the request, WebSocket or gRPC call stored at
+/// `path`.
+pub fn request_name(path: &Path) -> SharedString {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+
+    file_name
+        .strip_suffix(REQUEST_SUFFIX)
+        .or_else(|| file_name.strip_suffix(WEBSOCKET_SUFFIX))
+        .or_else(|| file_name.strip_suffix(GRPC_SUFFIX))
+        .unwrap_or(&file_name)
+        .to_owned()
+        .into()
+}
@@ -0,0 +1,133 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, Result, ensure};
+use http_client::{DescriptorPool, Proxy, ProxyConfig, grpc_http_url, url::Url};
+use serde::{Deserialize, Serialize};
+
+use crate::request::{Header, RequestProxy};
+
+/// A gRPC call as stored in a `*.grpc.json` file of a collection.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GrpcRequest {
+    /// The server, as `grpc://`, `grpcs://`, `http://` or `https://` URL.
+    #[serde(default)]
+    pub url: String,
+    /// Where the services and their message types come from.
+    #[serde(default)]
+    pub schema: GrpcSchema,
+    /// The fully qualified name of the service.
+    #[serde(default)]
+    pub service: String,
+    #[serde(default)]
+    pub method: String,
+    /// The request message, as the JSON mapping of its type.
+    #[serde(default)]
+    pub message: String,
+    /// Sent as headers of the call.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub metadata: Vec<Header>,
+    /// Overrides the `proxy` settings for this call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<RequestProxy>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GrpcSchema {
+    /// Asks the server for its services over server reflection.
+    #[default]
+    Reflection,
+    /// Reads `.proto` files, with paths relative to the directory of the
+    /// request file.
+    ProtoFiles {
+        files: Vec<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        import_paths: Vec<String>,
+    },
+}
+
+impl GrpcRequest {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+
+        serde_json::from_str(&content).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut content = serde_json::to_string_pretty(self)?;
+        content.push('\n');
+
+        fs::write(path, content).with_context(|| format!("writing {}", path.display()))
+    }
+
+    /// The URL of the server, defaulting to `grpc://` when it has no scheme.
+    pub fn parsed_url(&self) -> Result<Url> {
+        let url = self.url.trim();
+
+        if url.contains("://") {
+            Url::parse(url)
+        } else {
+            Url::parse(&format!("grpc://{url}"))
+        }
+        .with_context(|| format!("invalid URL {url:?}"))
+    }
+
+    /// The proxy to reach the server at `url` through, given the default
+    /// ones.
+    pub fn proxy(&self, defaults: &ProxyConfig, url: &Url) -> Result<Option<Proxy>> {
+        match &self.proxy {
+            Some(RequestProxy::Direct) => Ok(None),
+            Some(RequestProxy::Url(proxy)) => Proxy::parse(proxy).map(Some),
+            None => Ok(defaults.proxy_for(&grpc_http_url(url)?).cloned()),
+        }
+    }
+
+    /// The enabled metadata, in order.
+    pub fn metadata(&self) -> Vec<(String, String)> {
+        self.metadata
+            .iter()
+            .filter(|header| header.enabled && !header.name.trim().is_empty())
+            .map(|header| (header.name.clone(), header.value.clone()))
+            .collect()
+    }
+
+    /// The call to make, once the service and method are chosen.
+    pub fn to_grpc_request(&self) -> Result<http_client::GrpcRequest> {
+        ensure!(!self.service.is_empty(), "no service chosen");
+        ensure!(!self.method.is_empty(), "no method chosen");
+
+        Ok(http_client::GrpcRequest {
+            url: self.parsed_url()?,
+            service: self.service.clone(),
+            method: self.method.clone(),
+            metadata: self.metadata(),
+        })
+    }
+}
+
+impl GrpcSchema {
+    /// Reads the `.proto` files of the schema, resolving relative paths
+    /// against `dir`. Reflection schemas come from the server instead.
+    pub fn load_proto_files(&self, dir: &Path) -> Result<Option<DescriptorPool>> {
+        let GrpcSchema::ProtoFiles {
+            files,
+            import_paths,
+        } = self
+        else {
+            return Ok(None);
+        };
+
+        let resolve = |paths: &[String]| {
+            paths
+                .iter()
+                .map(|path| dir.join(path))
+                .collect::<Vec<PathBuf>>()
+        };
+
+        DescriptorPool::load_proto_files(&resolve(files), &resolve(import_paths)).map(Some)
+    }
+}
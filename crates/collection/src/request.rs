@@ -0,0 +1,393 @@
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, Result};
+use http_client::{
+    HttpRequest, HttpVersion, INTROSPECTION_QUERY, Proxy, ProxyConfig, QueryMode, RedirectPolicy,
+    RequestOptions, ResolveOverride, graphql_body, http, url::Url,
+};
+use script::ScriptRequest;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assertions::Assertion,
+    auth::{Auth, inherited_auth},
+    body::RequestBody,
+    config::CollectionConfig,
+    scripts::{Scripts, script_request},
+    variables::{interpolate, interpolate_defined},
+};
+
+/// A request as stored in a `*.request.json` file of a collection.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Request {
+    #[serde(default, skip_serializing_if = "RequestMode::is_http")]
+    pub mode: RequestMode,
+    #[serde(default)]
+    pub method: Method,
+    #[serde(default)]
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<Header>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<RequestBody>,
+    /// Overrides the `http.version` setting for this request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_version: Option<HttpVersion>,
+    /// Overrides the `proxy` settings for this request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<RequestProxy>,
+    /// Overrides the `redirects.follow` setting for this request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub follow_redirects: Option<bool>,
+    /// Where the request connects to, when not to the addresses DNS has for
+    /// its host.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transport: Option<RequestTransport>,
+    /// How the request authenticates, inheriting that of its folders or
+    /// collection when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<Auth>,
+    #[serde(default, skip_serializing_if = "Scripts::is_empty")]
+    pub scripts: Scripts,
+    /// Checked on the response after every send.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assertions: Vec<Assertion>,
+}
+
+/// How a request is sent and its response received.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestMode {
+    /// A single response, read in full.
+    #[default]
+    Http,
+    /// A `text/event-stream` response, read as events as they arrive and
+    /// reopened when lost.
+    Sse,
+}
+
+impl RequestMode {
+    pub const ALL: [RequestMode; 2] = [RequestMode::Http, RequestMode::Sse];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RequestMode::Http => "HTTP",
+            RequestMode::Sse => "SSE",
+        }
+    }
+
+    fn is_http(&self) -> bool {
+        *self == RequestMode::Http
+    }
+}
+
+/// A proxy choice of a single request, taking precedence over the settings
+/// and the environment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestProxy {
+    /// Connects to the server directly.
+    Direct,
+    Url(String),
+}
+
+/// Where a single request connects to instead of its host.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestTransport {
+    /// Fixed addresses for some hosts, like curl's `--resolve`, to reach a
+    /// given backend behind a load balancer.
+    Resolve(Vec<HostAddress>),
+    /// A Unix domain socket, such as the Docker daemon's.
+    UnixSocket(PathBuf),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HostAddress {
+    /// The host, followed by `:port` when only that port is redirected.
+    pub host: String,
+    pub address: String,
+    #[serde(default = "enabled", skip_serializing_if = "is_enabled")]
+    pub enabled: bool,
+}
+
+impl Request {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+
+        serde_json::from_str(&content).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut content = serde_json::to_string_pretty(self)?;
+        content.push('\n');
+
+        fs::write(path, content).with_context(|| format!("writing {}", path.display()))
+    }
+
+    /// The URL to send the request to, defaulting to `http://` when it has
+    /// no scheme.
+    pub fn parsed_url(&self) -> Result<Url> {
+        let url = self.url.trim();
+
+        if url.contains("://") {
+            Url::parse(url)
+        } else {
+            Url::parse(&format!("http://{url}"))
+        }
+        .with_context(|| format!("invalid URL {url:?}"))
+    }
+
+    /// The proxy to send the request to `url` through, given the default
+    /// ones.
+    pub fn proxy(&self, defaults: &ProxyConfig, url: &Url) -> Result<Option<Proxy>> {
+        match &self.proxy {
+            Some(RequestProxy::Direct) => Ok(None),
+            Some(RequestProxy::Url(proxy)) => Proxy::parse(proxy).map(Some),
+            None => Ok(defaults.proxy_for(url).cloned()),
+        }
+    }
+
+    /// Points `options` at the Unix socket or addresses of the transport of
+    /// the request, resolving a relative socket path against `dir`.
+    pub fn apply_transport(&self, options: &mut RequestOptions, dir: &Path) -> Result<()> {
+        match &self.transport {
+            None => {}
+            Some(RequestTransport::UnixSocket(path)) => {
+                options.unix_socket = Some(dir.join(path));
+            }
+            Some(RequestTransport::Resolve(hosts)) => {
+                options.resolve = hosts
+                    .iter()
+                    .filter(|host| host.enabled && !host.host.trim().is_empty())
+                    .map(|host| ResolveOverride::parse(&host.host, &host.address))
+                    .collect::<Result<_>>()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The authentication of the request, given `dir`, its folder, and the
+    /// configuration of its collection.
+    pub fn effective_auth(&self, dir: &Path, config: &CollectionConfig) -> Result<Option<Auth>> {
+        match &self.auth {
+            Some(auth) => Ok(Some(auth.clone())),
+            None => inherited_auth(dir, config),
+        }
+    }
+
+    /// How to handle redirects, given the default policy.
+    pub fn redirect_policy(&self, default: RedirectPolicy) -> RedirectPolicy {
+        RedirectPolicy {
+            follow: self.follow_redirects.unwrap_or(default.follow),
+            ..default
+        }
+    }
+
+    /// The request with the `{{name}}` references of its URL, enabled
+    /// headers and body replaced with the values of `variables`.
+    pub fn interpolated(&self, variables: &BTreeMap<String, String>) -> Result<Self> {
+        self.resolved(|text| {
+            interpolate(text, variables).context("resolving the variables of the request")
+        })
+    }
+
+    fn resolved(&self, resolve: impl Fn(&str) -> Result<String>) -> Result<Self> {
+        Ok(Self {
+            url: resolve(&self.url)?,
+            headers: self
+                .headers
+                .iter()
+                .map(|header| {
+                    if !header.enabled {
+                        return Ok(header.clone());
+                    }
+                    Ok(Header {
+                        name: resolve(&header.name)?,
+                        value: resolve(&header.value)?,
+                        enabled: true,
+                    })
+                })
+                .collect::<Result<_>>()?,
+            body: self
+                .body
+                .as_ref()
+                .map(|body| body.interpolated(&resolve))
+                .transpose()?,
+            ..self.clone()
+        })
+    }
+
+    /// Converts the request to one the client can send, resolving its
+    /// variables with `variables` and the relative paths of its body
+    /// against `dir`, the folder of the request.
+    pub fn to_http_request(
+        &self,
+        dir: &Path,
+        variables: &BTreeMap<String, String>,
+    ) -> Result<HttpRequest> {
+        self.interpolated(variables)?.build(dir)
+    }
+
+    /// The request as pre-request scripts see it, the references to
+    /// variables they may yet define left as they are.
+    pub fn to_script_request(
+        &self,
+        dir: &Path,
+        variables: &BTreeMap<String, String>,
+    ) -> Result<ScriptRequest> {
+        let request = self.resolved(|text| Ok(interpolate_defined(text, variables)))?;
+        Ok(script_request(&request.build(dir)?))
+    }
+
+    /// Converts the request, its variables resolved, to one the client can
+    /// send.
+    fn build(&self, dir: &Path) -> Result<HttpRequest> {
+        let url = self.parsed_url()?;
+
+        let mut headers = self.enabled_headers();
+
+        let (body, content_type) = match &self.body {
+            Some(body) => body.to_http_body(dir)?,
+            None => Default::default(),
+        };
+        // A content type set by hand wins over the one the body implies.
+        if let Some(content_type) = content_type
+            && !headers
+                .iter()
+                .any(|(name, _)| name.trim().eq_ignore_ascii_case("content-type"))
+        {
+            headers.push(("Content-Type".to_string(), content_type));
+        }
+
+        Ok(HttpRequest {
+            method: self.method.as_str().parse()?,
+            url,
+            headers,
+            body,
+        })
+    }
+
+    /// The request to send again when the server doesn't know the
+    /// persisted query of a GraphQL body yet, with the query along with its
+    /// hash for the server to cache it.
+    pub fn to_persisted_query_registration(
+        &self,
+        dir: &Path,
+        variables: &BTreeMap<String, String>,
+    ) -> Option<Result<HttpRequest>> {
+        let Some(RequestBody::GraphQl(body)) = &self.body else {
+            return None;
+        };
+        if !body.persisted {
+            return None;
+        }
+
+        Some(self.interpolated(variables).and_then(|request| {
+            let mut http_request = request.build(dir)?;
+            if let Some(RequestBody::GraphQl(body)) = &request.body {
+                http_request.body = body.to_json(QueryMode::PersistedFull)?.into();
+            }
+            Ok(http_request)
+        }))
+    }
+
+    /// A request asking the server for its GraphQL schema, sent to the same
+    /// URL with the same headers, whatever the body is.
+    pub fn to_introspection_request(
+        &self,
+        variables: &BTreeMap<String, String>,
+    ) -> Result<HttpRequest> {
+        let request = self.interpolated(variables)?;
+        let mut headers = request.enabled_headers();
+        headers.retain(|(name, _)| {
+            let name = name.trim();
+            !name.eq_ignore_ascii_case("content-type")
+                && !name.eq_ignore_ascii_case("content-length")
+        });
+        headers.push(("Content-Type".to_string(), "application/json".to_string()));
+
+        Ok(HttpRequest {
+            method: http::Method::POST,
+            url: request.parsed_url()?,
+            headers,
+            body: graphql_body(INTROSPECTION_QUERY, "", None, QueryMode::Full)?.into(),
+        })
+    }
+
+    fn enabled_headers(&self) -> Vec<(String, String)> {
+        self.headers
+            .iter()
+            .filter(|header| header.enabled && !header.name.trim().is_empty())
+            .map(|header| (header.name.clone(), header.value.clone()))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Header {
+    pub name: String,
+    pub value: String,
+    #[serde(default = "enabled", skip_serializing_if = "is_enabled")]
+    pub enabled: bool,
+}
+
+pub(crate) fn enabled() -> bool {
+    true
+}
+
+pub(crate) fn is_enabled(enabled: &bool) -> bool {
+    *enabled
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Method {
+    #[default]
+    Get,
+    Head,
+    Post,
+    Put,
+    Patch,
+    Delete,
+    Options,
+    Trace,
+}
+
+impl Method {
+    pub const ALL: [Method; 8] = [
+        Method::Get,
+        Method::Head,
+        Method::Post,
+        Method::Put,
+        Method::Patch,
+        Method::Delete,
+        Method::Options,
+        Method::Trace,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Head => "HEAD",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Patch => "PATCH",
+            Method::Delete => "DELETE",
+            Method::Options => "OPTIONS",
+            Method::Trace => "TRACE",
+        }
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
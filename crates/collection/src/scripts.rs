@@ -0,0 +1,159 @@
+use std::{collections::BTreeMap, path::Path};
+
+use anyhow::{Context as _, Result};
+use http_client::{HttpRequest, HttpResponse, url::Url};
+use script::{Script, ScriptRequest, ScriptResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::{FolderConfig, applying_folders},
+    config::CollectionConfig,
+    variables::interpolate,
+};
+
+/// JavaScript run before a request is sent, to compute its headers or
+/// variables, and after its response arrives, to extract values from it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Scripts {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub pre_request: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub post_response: String,
+}
+
+impl Scripts {
+    pub fn is_empty(&self) -> bool {
+        self.pre_request.trim().is_empty() && self.post_response.trim().is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptPhase {
+    PreRequest,
+    PostResponse,
+}
+
+impl ScriptPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScriptPhase::PreRequest => "Pre-request",
+            ScriptPhase::PostResponse => "Post-response",
+        }
+    }
+
+    fn source<'a>(&self, scripts: &'a Scripts) -> &'a str {
+        match self {
+            ScriptPhase::PreRequest => &scripts.pre_request,
+            ScriptPhase::PostResponse => &scripts.post_response,
+        }
+    }
+}
+
+/// The scripts of `phase` to run for a request inside `dir` having
+/// `scripts`: those of its folders from the root of the collection of
+/// `config` down, then its own.
+pub fn effective_scripts(
+    dir: &Path,
+    scripts: &Scripts,
+    config: &CollectionConfig,
+    phase: ScriptPhase,
+) -> Result<Vec<Script>> {
+    let mut effective = Vec::new();
+
+    for folder in applying_folders(dir, config).into_iter().rev() {
+        let source = phase
+            .source(&FolderConfig::load(folder)?.scripts)
+            .to_string();
+        if source.trim().is_empty() {
+            continue;
+        }
+
+        let origin = match folder.strip_prefix(&config.root) {
+            Ok(relative) if relative.as_os_str().is_empty() => "collection".to_string(),
+            Ok(relative) => format!("folder {}", relative.display()),
+            Err(_) => format!("folder {}", folder.display()),
+        };
+        effective.push(Script { source, origin });
+    }
+
+    let source = phase.source(scripts);
+    if !source.trim().is_empty() {
+        effective.push(Script {
+            source: source.to_string(),
+            origin: "request".to_string(),
+        });
+    }
+
+    Ok(effective)
+}
+
+/// The request as scripts see it. A body made of files is left empty.
+pub fn script_request(request: &HttpRequest) -> ScriptRequest {
+    ScriptRequest {
+        method: request.method.to_string(),
+        url: request.url.to_string(),
+        headers: request.headers.clone(),
+        body: request
+            .body
+            .as_bytes()
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+            .unwrap_or_default(),
+    }
+}
+
+/// Applies to `request` what scripts changed of `original`, its view
+/// before they ran, resolving the references to `variables` left in it.
+pub fn apply_script_request(
+    request: &mut HttpRequest,
+    original: &ScriptRequest,
+    changed: ScriptRequest,
+    variables: &BTreeMap<String, String>,
+) -> Result<()> {
+    let resolve = |text: &str| {
+        interpolate(text, variables).context("resolving the variables the script left")
+    };
+
+    if changed.method != original.method {
+        request.method = changed
+            .method
+            .to_ascii_uppercase()
+            .parse()
+            .with_context(|| format!("a script set the invalid method {:?}", changed.method))?;
+    }
+    if changed.url != original.url {
+        request.url = Url::parse(&resolve(&changed.url)?)
+            .with_context(|| format!("a script set the invalid URL {:?}", changed.url))?;
+    }
+    if changed.headers != original.headers {
+        request.headers = changed
+            .headers
+            .iter()
+            .map(|(name, value)| Ok((resolve(name)?, resolve(value)?)))
+            .collect::<Result<_>>()?;
+    }
+    if changed.body != original.body {
+        request.body = resolve(&changed.body)?.into();
+    }
+
+    Ok(())
+}
+
+/// The response as scripts see it, with the part of the body kept in
+/// memory.
+pub fn script_response(response: &HttpResponse) -> ScriptResponse {
+    ScriptResponse {
+        status: response.status.as_u16(),
+        headers: response
+            .headers
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                )
+            })
+            .collect(),
+        body: String::from_utf8_lossy(&response.body).into_owned(),
+        time: response.timing.total,
+    }
+}
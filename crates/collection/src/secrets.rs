@@ -0,0 +1,209 @@
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    io::Write as _,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use base64::{Engine as _, prelude::BASE64_STANDARD};
+use ring::{
+    aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey},
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::{Deserialize, Serialize};
+
+/// Name of the file holding the values of the secret variables of a
+/// collection, in its state folder.
+pub const VAULT_FILE: &str = "vault.json";
+
+/// Shown in place of the value of a secret.
+pub const MASK: &str = "••••••••";
+
+const KEY_LEN: usize = 32;
+
+/// Binds the ciphertext to its purpose, so that it can't pass for another
+/// file encrypted with the same key.
+const VAULT_AAD: &[u8] = b"bridge vault v1";
+
+/// Secret values by environment, then by variable name.
+pub type Secrets = BTreeMap<String, BTreeMap<String, String>>;
+
+#[derive(Serialize, Deserialize)]
+struct VaultFile {
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Where the key encrypting the vaults of all collections is kept: in the
+/// configuration folder of the user, so that the vault of a collection is
+/// useless to anyone getting hold of the collection folder alone.
+pub fn key_path() -> Result<PathBuf> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+        .context("neither XDG_CONFIG_HOME, HOME nor APPDATA is set")?;
+
+    Ok(config_dir.join("bridge").join("vault.key"))
+}
+
+/// Reads the vault at `path`, decrypting it with the key at `key_path`.
+/// There are no secrets yet when it doesn't exist.
+pub fn read_vault(path: &Path, key_path: &Path) -> Result<Secrets> {
+    if !path.is_file() {
+        return Ok(Secrets::new());
+    }
+
+    let content =
+        fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    let file: VaultFile =
+        serde_json::from_str(&content).with_context(|| format!("parsing {}", path.display()))?;
+    let key = read_key(key_path)?.with_context(|| {
+        format!(
+            "the key of {} is missing from {}",
+            path.display(),
+            key_path.display()
+        )
+    })?;
+
+    let nonce = BASE64_STANDARD.decode(&file.nonce)?;
+    let nonce = Nonce::try_assume_unique_for_key(&nonce)
+        .map_err(|_| anyhow!("invalid nonce in {}", path.display()))?;
+    let mut data = BASE64_STANDARD.decode(&file.ciphertext)?;
+    let plaintext = key
+        .open_in_place(nonce, Aad::from(VAULT_AAD), &mut data)
+        .map_err(|_| {
+            anyhow!(
+                "decrypting {}: it was encrypted with another key than {}",
+                path.display(),
+                key_path.display()
+            )
+        })?;
+
+    serde_json::from_slice(plaintext).with_context(|| format!("parsing {}", path.display()))
+}
+
+/// Encrypts `secrets` into the vault at `path` with the key at `key_path`,
+/// creating the key on first use.
+pub fn write_vault(path: &Path, key_path: &Path, secrets: &Secrets) -> Result<()> {
+    let random = SystemRandom::new();
+    let key = match read_key(key_path)? {
+        Some(key) => key,
+        None => create_key(key_path, &random)?,
+    };
+
+    let mut nonce = [0; NONCE_LEN];
+    random
+        .fill(&mut nonce)
+        .map_err(|_| anyhow!("generating a nonce"))?;
+    let mut data = serde_json::to_vec(secrets)?;
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(VAULT_AAD),
+        &mut data,
+    )
+    .map_err(|_| anyhow!("encrypting {}", path.display()))?;
+
+    let file = VaultFile {
+        nonce: BASE64_STANDARD.encode(nonce),
+        ciphertext: BASE64_STANDARD.encode(data),
+    };
+    let mut content = serde_json::to_string_pretty(&file)?;
+    content.push('\n');
+
+    fs::write(path, content).with_context(|| format!("writing {}", path.display()))
+}
+
+fn read_key(path: &Path) -> Result<Option<LessSafeKey>> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    let content =
+        fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    let bytes = BASE64_STANDARD
+        .decode(content.trim())
+        .with_context(|| format!("parsing {}", path.display()))?;
+    if bytes.len() != KEY_LEN {
+        bail!("{} doesn't hold a {KEY_LEN} byte key", path.display());
+    }
+
+    Ok(Some(key_from_bytes(&bytes)?))
+}
+
+fn create_key(path: &Path, random: &SystemRandom) -> Result<LessSafeKey> {
+    let mut bytes = [0; KEY_LEN];
+    random
+        .fill(&mut bytes)
+        .map_err(|_| anyhow!("generating a key"))?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    }
+
+    // The file is only readable by the user from the start, rather than
+    // restricted once the key is already in it.
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt as _;
+
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("creating {}", path.display()))?;
+    writeln!(file, "{}", BASE64_STANDARD.encode(bytes))
+        .with_context(|| format!("writing {}", path.display()))?;
+
+    key_from_bytes(&bytes)
+}
+
+fn key_from_bytes(bytes: &[u8]) -> Result<LessSafeKey> {
+    let key = UnboundKey::new(&AES_256_GCM, bytes).map_err(|_| anyhow!("invalid vault key"))?;
+    Ok(LessSafeKey::new(key))
+}
+
+/// The names of the secrets of `secrets` whose value appears in `content`,
+/// as is or escaped as a JSON string.
+pub fn leaked_secrets<'a>(
+    content: &str,
+    secrets: impl IntoIterator<Item = (&'a String, &'a String)>,
+) -> Vec<String> {
+    let mut leaked: Vec<String> = secrets
+        .into_iter()
+        .filter(|(_, value)| !value.is_empty())
+        .filter(|(_, value)| {
+            let escaped = serde_json::to_string(value).unwrap_or_default();
+            content.contains(value.as_str()) || content.contains(escaped.trim_matches('"'))
+        })
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    leaked.sort();
+    leaked.dedup();
+    leaked
+}
+
+/// `text` with the values of `secrets` put back as the `{{name}}`
+/// references they came from, for showing or exporting it.
+pub fn redact<'a>(
+    text: &str,
+    secrets: impl IntoIterator<Item = (&'a String, &'a String)>,
+) -> String {
+    let mut secrets: Vec<_> = secrets
+        .into_iter()
+        .filter(|(_, value)| !value.is_empty())
+        .collect();
+    // Longer values go first so that a value containing another one is
+    // replaced whole.
+    secrets.sort_by_key(|(_, value)| std::cmp::Reverse(value.len()));
+
+    secrets
+        .into_iter()
+        .fold(text.to_string(), |text, (name, value)| {
+            text.replace(value.as_str(), &format!("{{{{{name}}}}}"))
+        })
+}
@@ -0,0 +1,487 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs, mem,
+    path::Path,
+    sync::Arc,
+    time::SystemTime,
+};
+
+use anyhow::{Context as _, Result};
+use gpui::{App, AppContext, Context, Entity, Global, SharedString, Task, WeakEntity};
+use http_client::{CookieJar, OAuthToken, http::HeaderMap, url::Url};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{CONFIG_FILE, CollectionConfig, Environment},
+    secrets::{self, Secrets, VAULT_FILE},
+};
+
+/// Folder of a collection holding state that belongs to the user rather
+/// than to the collection, left out of the request listing.
+pub const STATE_DIR: &str = ".bridge";
+
+const SESSION_FILE: &str = "session.json";
+
+/// Keeps the folder out of version control, since cookies, tokens and the
+/// vault are credentials.
+const STATE_DIR_GITIGNORE: &str = "*\n";
+
+/// The sessions of the collections open in any window, so that windows of
+/// the same collection share one.
+#[derive(Default)]
+struct Sessions(HashMap<Arc<Path>, WeakEntity<Session>>);
+
+impl Global for Sessions {}
+
+#[derive(Default, Serialize, Deserialize)]
+struct SessionFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    environment: Option<String>,
+    /// Jars by environment, the empty name standing for no environment.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    cookies: BTreeMap<String, CookieJar>,
+    /// OAuth 2.0 tokens by environment, then by client.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    tokens: BTreeMap<String, BTreeMap<String, OAuthToken>>,
+    /// Variables set by scripts by environment, overriding those of the
+    /// collection, or unsetting them when null.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    variables: BTreeMap<String, BTreeMap<String, Option<String>>>,
+}
+
+/// What requests of a collection share while it is open: the active
+/// environment, the values of its secret variables, the variables scripts
+/// set, and the cookies and OAuth 2.0 tokens received in each environment. They persist across
+/// restarts in the state folder of the collection, the secrets encrypted.
+pub struct Session {
+    root: Arc<Path>,
+    environments: Vec<SharedString>,
+    environment_configs: BTreeMap<String, Environment>,
+    environment: Option<String>,
+    cookies: BTreeMap<String, CookieJar>,
+    tokens: BTreeMap<String, BTreeMap<String, OAuthToken>>,
+    variables: BTreeMap<String, BTreeMap<String, Option<String>>>,
+    secrets: Secrets,
+    error: Option<SharedString>,
+    _save: Task<()>,
+}
+
+impl Session {
+    /// The session of the collection at `root`, loading it when no window
+    /// has it open yet. The files are small, so they are read right away
+    /// for the first request to already send its cookies.
+    pub fn for_root(root: Arc<Path>, cx: &mut App) -> Entity<Self> {
+        let existing = cx
+            .default_global::<Sessions>()
+            .0
+            .get(&root)
+            .and_then(WeakEntity::upgrade);
+        if let Some(session) = existing {
+            return session;
+        }
+
+        let session = cx.new(|_| Self::load(root.clone()));
+        let sessions = &mut cx.global_mut::<Sessions>().0;
+
+        sessions.retain(|_, session| session.upgrade().is_some());
+        sessions.insert(root, session.downgrade());
+        session
+    }
+
+    /// The session of the collection a request file belongs to, read from
+    /// the nearest folder above it with a state folder, for tools that send
+    /// requests outside the app. Nothing they change is saved.
+    pub fn for_request(request_path: &Path) -> Option<Self> {
+        request_path
+            .ancestors()
+            .skip(1)
+            .find(|dir| dir.join(STATE_DIR).is_dir())
+            .map(|root| Self::load(root.into()))
+    }
+
+    fn load(root: Arc<Path>) -> Self {
+        let mut error = None;
+
+        let config_path = root.join(CONFIG_FILE);
+        let environment_configs = if config_path.is_file() {
+            match CollectionConfig::load(&config_path) {
+                Ok(config) => config.environments,
+                Err(load_error) => {
+                    error = Some(format!("{load_error:#}").into());
+                    BTreeMap::new()
+                }
+            }
+        } else {
+            BTreeMap::new()
+        };
+        let environments: Vec<SharedString> = environment_configs
+            .keys()
+            .map(|name| name.clone().into())
+            .collect();
+
+        let session_path = root.join(STATE_DIR).join(SESSION_FILE);
+        let file = match read_session_file(&session_path) {
+            Ok(file) => file,
+            Err(load_error) => {
+                error = Some(format!("{load_error:#}").into());
+                SessionFile::default()
+            }
+        };
+
+        let vault_path = root.join(STATE_DIR).join(VAULT_FILE);
+        let secrets = match secrets::key_path()
+            .and_then(|key_path| secrets::read_vault(&vault_path, &key_path))
+        {
+            Ok(secrets) => secrets,
+            Err(load_error) if vault_path.is_file() => {
+                error = Some(format!("{load_error:#}").into());
+                Secrets::new()
+            }
+            Err(_) => Secrets::new(),
+        };
+
+        let mut cookies = file.cookies;
+        let now = SystemTime::now();
+        for jar in cookies.values_mut() {
+            jar.remove_expired(now);
+        }
+
+        Self {
+            environment: file
+                .environment
+                .filter(|name| environments.iter().any(|known| known == name)),
+            root,
+            environments,
+            environment_configs,
+            cookies,
+            tokens: file.tokens,
+            variables: file.variables,
+            secrets,
+            error,
+            _save: Task::ready(()),
+        }
+    }
+
+    pub fn root(&self) -> &Arc<Path> {
+        &self.root
+    }
+
+    /// The environments defined in the configuration of the collection.
+    pub fn environments(&self) -> &[SharedString] {
+        &self.environments
+    }
+
+    /// The environment requests are sent in, if any.
+    pub fn environment(&self) -> Option<&str> {
+        self.environment.as_deref()
+    }
+
+    /// The last error loading or saving the session.
+    pub fn error(&self) -> Option<&SharedString> {
+        self.error.as_ref()
+    }
+
+    pub fn set_environment(&mut self, environment: Option<String>, cx: &mut Context<Self>) {
+        if self.environment != environment {
+            self.environment = environment;
+            self.save(cx);
+        }
+    }
+
+    /// The cookies received in `environment`.
+    pub fn cookies(&self, environment: Option<&str>) -> Option<&CookieJar> {
+        self.cookies.get(environment.unwrap_or_default())
+    }
+
+    /// Changes the cookies of `environment` with `update`, saving them when
+    /// it reports a change.
+    pub fn update_cookies(
+        &mut self,
+        environment: Option<&str>,
+        update: impl FnOnce(&mut CookieJar) -> bool,
+        cx: &mut Context<Self>,
+    ) {
+        let key = environment.unwrap_or_default();
+        let jar = self.cookies.entry(key.to_string()).or_default();
+        let changed = update(jar);
+
+        if jar.is_empty() {
+            self.cookies.remove(key);
+        }
+
+        if changed {
+            self.save(cx);
+        }
+    }
+
+    /// Stores the cookies a response to a request to `url` sets, in the
+    /// environment the request was sent in.
+    pub fn store_cookies(
+        &mut self,
+        environment: Option<&str>,
+        url: &Url,
+        headers: &HeaderMap,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_cookies(
+            environment,
+            |jar| jar.store_response(url, headers, SystemTime::now()),
+            cx,
+        );
+    }
+
+    /// The OAuth 2.0 token cached under `key` in `environment`.
+    pub fn token(&self, environment: Option<&str>, key: &str) -> Option<&OAuthToken> {
+        self.tokens.get(environment.unwrap_or_default())?.get(key)
+    }
+
+    /// Caches `token` under `key` in `environment`, or forgets the token
+    /// cached there when `None`.
+    pub fn set_token(
+        &mut self,
+        environment: Option<&str>,
+        key: &str,
+        token: Option<OAuthToken>,
+        cx: &mut Context<Self>,
+    ) {
+        let environment = environment.unwrap_or_default();
+        let tokens = self.tokens.entry(environment.to_string()).or_default();
+        let changed = match token {
+            Some(token) => tokens.insert(key.to_string(), token.clone()) != Some(token),
+            None => tokens.remove(key).is_some(),
+        };
+
+        if tokens.is_empty() {
+            self.tokens.remove(environment);
+        }
+
+        if changed {
+            self.save(cx);
+        }
+    }
+
+    /// How `environment` is defined in the configuration of the collection.
+    pub fn environment_config(&self, environment: &str) -> Option<&Environment> {
+        self.environment_configs.get(environment)
+    }
+
+    /// The values of the secret variables of `environment` this user has
+    /// stored.
+    pub fn secrets(&self, environment: Option<&str>) -> Option<&BTreeMap<String, String>> {
+        self.secrets.get(environment?)
+    }
+
+    /// The variables of `environment` as defined in `config`, with the
+    /// values of its secrets and those scripts set.
+    pub fn variables(
+        &self,
+        config: &CollectionConfig,
+        environment: Option<&str>,
+    ) -> BTreeMap<String, String> {
+        let mut variables = config.variables(environment, self.secrets(environment));
+
+        let set = self.variables.get(environment.unwrap_or_default());
+        for (name, value) in set.into_iter().flatten() {
+            match value {
+                Some(value) => variables.insert(name.clone(), value.clone()),
+                None => variables.remove(name),
+            };
+        }
+
+        variables
+    }
+
+    /// Keeps the variables scripts set in `environment`, or unset when
+    /// `None`. Those that are secret go to the vault.
+    pub fn set_variables(
+        &mut self,
+        environment: Option<&str>,
+        changes: BTreeMap<String, Option<String>>,
+        cx: &mut Context<Self>,
+    ) {
+        if changes.is_empty() {
+            return;
+        }
+
+        let key = environment.unwrap_or_default();
+        let secret_names = environment
+            .and_then(|environment| self.environment_configs.get(environment))
+            .map(|config| config.secrets.clone())
+            .unwrap_or_default();
+
+        for (name, value) in changes {
+            if secret_names.contains(&name) {
+                let secrets = self.secrets.entry(key.to_string()).or_default();
+                match value {
+                    Some(value) => secrets.insert(name, value),
+                    None => secrets.remove(&name),
+                };
+            } else {
+                self.variables
+                    .entry(key.to_string())
+                    .or_default()
+                    .insert(name, value);
+            }
+        }
+
+        self.secrets.retain(|_, secrets| !secrets.is_empty());
+        self.save(cx);
+    }
+
+    /// Stores the value of the secret `name` of `environment`, or forgets it
+    /// when `None`.
+    pub fn set_secret(
+        &mut self,
+        environment: &str,
+        name: &str,
+        value: Option<String>,
+        cx: &mut Context<Self>,
+    ) {
+        let secrets = self.secrets.entry(environment.to_string()).or_default();
+        let changed = match value {
+            Some(value) => secrets.insert(name.to_string(), value.clone()) != Some(value),
+            None => secrets.remove(name).is_some(),
+        };
+
+        if secrets.is_empty() {
+            self.secrets.remove(environment);
+        }
+
+        if changed {
+            self.save(cx);
+        }
+    }
+
+    /// Marks the variable `name` of `environment` as secret, moving its value
+    /// out of the configuration of the collection into the vault.
+    pub fn mark_secret(&mut self, environment: &str, name: &str, cx: &mut Context<Self>) {
+        let Some(config) = self.environment_configs.get_mut(environment) else {
+            return;
+        };
+        if !config.secrets.insert(name.to_string()) {
+            return;
+        }
+
+        if let Some(value) = config.variables.remove(name) {
+            self.secrets
+                .entry(environment.to_string())
+                .or_default()
+                .entry(name.to_string())
+                .or_insert(value);
+        }
+
+        self.save_config(environment, cx);
+        self.save(cx);
+    }
+
+    /// Stops treating the variable `name` of `environment` as secret. Its
+    /// value is forgotten rather than written to the configuration.
+    pub fn unmark_secret(&mut self, environment: &str, name: &str, cx: &mut Context<Self>) {
+        let Some(config) = self.environment_configs.get_mut(environment) else {
+            return;
+        };
+        if !config.secrets.remove(name) {
+            return;
+        }
+
+        self.save_config(environment, cx);
+        self.set_secret(environment, name, None, cx);
+        cx.notify();
+    }
+
+    /// The names of the secrets, of any environment, whose value appears in
+    /// `content` about to be written to a file of the collection.
+    pub fn leaked_secrets(&self, content: &str) -> Vec<String> {
+        secrets::leaked_secrets(content, self.secrets.values().flatten())
+    }
+
+    /// Writes how `environment` is defined now to the configuration of the
+    /// collection, leaving the rest of the file as it is on disk.
+    fn save_config(&mut self, environment: &str, cx: &mut Context<Self>) {
+        let path = self.root.join(CONFIG_FILE);
+        let environment = environment.to_string();
+        let definition = self.environment_configs.get(&environment).cloned();
+
+        self.enqueue(
+            move || {
+                let mut config = CollectionConfig::load(&path)?;
+                match definition {
+                    Some(definition) => config.environments.insert(environment, definition),
+                    None => config.environments.remove(&environment),
+                };
+                config.save()
+            },
+            cx,
+        );
+    }
+
+    fn save(&mut self, cx: &mut Context<Self>) {
+        let dir = self.root.join(STATE_DIR);
+        let file = SessionFile {
+            environment: self.environment.clone(),
+            cookies: self.cookies.clone(),
+            tokens: self.tokens.clone(),
+            variables: self.variables.clone(),
+        };
+        let secrets = self.secrets.clone();
+
+        self.enqueue(move || write_session_file(&dir, &file, &secrets), cx);
+    }
+
+    fn enqueue(
+        &mut self,
+        write: impl FnOnce() -> Result<()> + Send + 'static,
+        cx: &mut Context<Self>,
+    ) {
+        cx.notify();
+
+        // Saves run one after the other so that an earlier one can't finish
+        // last and leave stale cookies, tokens or secrets behind.
+        let previous = mem::replace(&mut self._save, Task::ready(()));
+        self._save = cx.spawn(async move |this, cx| {
+            previous.await;
+
+            let result = cx.background_spawn(async move { write() }).await;
+
+            this.update(cx, |this, cx| {
+                this.error = result.err().map(|error| format!("{error:#}").into());
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+}
+
+fn read_session_file(path: &Path) -> Result<SessionFile> {
+    if !path.is_file() {
+        return Ok(SessionFile::default());
+    }
+
+    let content =
+        fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+
+    serde_json::from_str(&content).with_context(|| format!("parsing {}", path.display()))
+}
+
+fn write_session_file(dir: &Path, file: &SessionFile, secrets: &Secrets) -> Result<()> {
+    fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+
+    let gitignore = dir.join(".gitignore");
+    if !gitignore.exists() {
+        fs::write(&gitignore, STATE_DIR_GITIGNORE)
+            .with_context(|| format!("writing {}", gitignore.display()))?;
+    }
+
+    let path = dir.join(SESSION_FILE);
+    let mut content = serde_json::to_string_pretty(file)?;
+    content.push('\n');
+    fs::write(&path, content).with_context(|| format!("writing {}", path.display()))?;
+
+    // The key is only created once there is something to keep secret.
+    let vault_path = dir.join(VAULT_FILE);
+    if !secrets.is_empty() || vault_path.is_file() {
+        secrets::write_vault(&vault_path, &secrets::key_path()?, secrets)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Result, bail};
+
+/// Replaces the `{{name}}` references in `text` with the values of the
+/// variables they name, failing on a variable that isn't defined rather
+/// than sending the reference as is.
+pub fn interpolate(text: &str, variables: &BTreeMap<String, String>) -> Result<String> {
+    replace(text, variables, true)
+}
+
+/// Like [`interpolate`], leaving the references to variables that aren't
+/// defined as they are, for scripts that may yet define them.
+pub fn interpolate_defined(text: &str, variables: &BTreeMap<String, String>) -> String {
+    replace(text, variables, false).unwrap_or_else(|_| text.to_string())
+}
+
+fn replace(text: &str, variables: &BTreeMap<String, String>, strict: bool) -> Result<String> {
+    let mut interpolated = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let reference = &rest[..start + 2 + end + 2];
+        let name = rest[start + 2..start + 2 + end].trim();
+        match variables.get(name) {
+            Some(value) => {
+                interpolated.push_str(&rest[..start]);
+                interpolated.push_str(value);
+            }
+            None if strict => bail!("undefined variable {name:?}"),
+            None => interpolated.push_str(reference),
+        }
+        rest = &rest[reference.len()..];
+    }
+
+    interpolated.push_str(rest);
+    Ok(interpolated)
+}
@@ -0,0 +1,151 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context as _, Result, bail};
+use http_client::{
+    HttpBody, HttpRequest, Proxy, ProxyConfig, WebSocketMessage, http::Method, url::Url,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::request::{Header, RequestProxy};
+
+/// A WebSocket connection as stored in a `*.websocket.json` file of a
+/// collection.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WebSocketRequest {
+    #[serde(default)]
+    pub url: String,
+    /// Sent with the opening handshake.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<Header>,
+    /// Offered in `Sec-WebSocket-Protocol`, in order of preference.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subprotocols: Vec<String>,
+    /// Messages kept at hand to send again.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub templates: Vec<MessageTemplate>,
+    /// Overrides the `proxy` settings for this connection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<RequestProxy>,
+}
+
+/// A message saved to be sent again.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MessageTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub format: MessageFormat,
+    pub content: String,
+}
+
+/// How the content of a message is written and sent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageFormat {
+    #[default]
+    Text,
+    /// Text that must be valid JSON.
+    Json,
+    /// Bytes written as hex, ignoring whitespace.
+    Binary,
+}
+
+impl MessageFormat {
+    pub const ALL: [MessageFormat; 3] = [
+        MessageFormat::Text,
+        MessageFormat::Json,
+        MessageFormat::Binary,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MessageFormat::Text => "Text",
+            MessageFormat::Json => "JSON",
+            MessageFormat::Binary => "Binary (hex)",
+        }
+    }
+
+    /// The message to send for `content` written in this format.
+    pub fn to_message(&self, content: &str) -> Result<WebSocketMessage> {
+        match self {
+            MessageFormat::Text => Ok(WebSocketMessage::Text(content.to_string())),
+            MessageFormat::Json => {
+                serde_json::from_str::<serde_json::Value>(content).context("invalid JSON")?;
+                Ok(WebSocketMessage::Text(content.to_string()))
+            }
+            MessageFormat::Binary => Ok(WebSocketMessage::Binary(parse_hex(content)?.into())),
+        }
+    }
+}
+
+fn parse_hex(content: &str) -> Result<Vec<u8>> {
+    let digits = content
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| {
+            c.to_digit(16)
+                .map(|digit| digit as u8)
+                .with_context(|| format!("invalid hex digit {c:?}"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if digits.len() % 2 != 0 {
+        bail!("odd number of hex digits");
+    }
+
+    Ok(digits
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair[1])
+        .collect())
+}
+
+impl WebSocketRequest {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+
+        serde_json::from_str(&content).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut content = serde_json::to_string_pretty(self)?;
+        content.push('\n');
+
+        fs::write(path, content).with_context(|| format!("writing {}", path.display()))
+    }
+
+    /// The URL to connect to, defaulting to `ws://` when it has no scheme.
+    pub fn parsed_url(&self) -> Result<Url> {
+        let url = self.url.trim();
+
+        if url.contains("://") {
+            Url::parse(url)
+        } else {
+            Url::parse(&format!("ws://{url}"))
+        }
+        .with_context(|| format!("invalid URL {url:?}"))
+    }
+
+    /// The proxy to connect to `url` through, given the default ones.
+    pub fn proxy(&self, defaults: &ProxyConfig, url: &Url) -> Result<Option<Proxy>> {
+        match &self.proxy {
+            Some(RequestProxy::Direct) => Ok(None),
+            Some(RequestProxy::Url(proxy)) => Proxy::parse(proxy).map(Some),
+            None => Ok(defaults.proxy_for(url).cloned()),
+        }
+    }
+
+    /// The request opening the connection, which the client turns into the
+    /// handshake.
+    pub fn to_http_request(&self) -> Result<HttpRequest> {
+        Ok(HttpRequest {
+            method: Method::GET,
+            url: self.parsed_url()?,
+            headers: self
+                .headers
+                .iter()
+                .filter(|header| header.enabled && !header.name.trim().is_empty())
+                .map(|header| (header.name.clone(), header.value.clone()))
+                .collect(),
+            body: HttpBody::default(),
+        })
+    }
+}
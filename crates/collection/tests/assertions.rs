@@ -0,0 +1,250 @@
+use std::{
+    env, fs,
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    path::Path,
+    thread,
+};
+
+use collection::assertions::{Assertion, AssertionResult, Check, Expectation, evaluate};
+use http_client::{HttpRequest, HttpResponse, RequestOptions, http::Method, url::Url};
+use serde_json::json;
+
+const BODY: &str = r#"{"id":7,"name":"Ada Lovelace","roles":["admin","editor"],"score":1.0}"#;
+
+/// Answers a single request with a JSON body.
+fn response() -> HttpResponse {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    thread::spawn(move || {
+        let (mut socket, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(socket.try_clone().unwrap());
+        let mut line = String::new();
+        while reader.read_line(&mut line).unwrap() > 2 {
+            line.clear();
+        }
+
+        write!(
+            socket,
+            "HTTP/1.1 201 Created\r\nContent-Type: application/json; charset=utf-8\r\n\
+             X-Trace: a1\r\nX-Trace: b2\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{BODY}",
+            BODY.len()
+        )
+        .unwrap();
+    });
+
+    let request = HttpRequest {
+        method: Method::GET,
+        url: Url::parse(&format!("http://{address}/users/7")).unwrap(),
+        headers: Vec::new(),
+        body: Default::default(),
+    };
+    futures::executor::block_on(http_client::send(request, RequestOptions::default())).unwrap()
+}
+
+fn assertion(check: Check) -> Assertion {
+    Assertion {
+        check,
+        enabled: true,
+    }
+}
+
+fn json_path(path: &str, expect: Expectation) -> Assertion {
+    assertion(Check::JsonPath {
+        path: path.into(),
+        expect,
+    })
+}
+
+fn failures(results: &[AssertionResult]) -> Vec<(&str, Option<&str>)> {
+    results
+        .iter()
+        .map(|result| (result.name.as_str(), result.failure.as_deref()))
+        .collect()
+}
+
+#[test]
+fn checks_status_headers_and_time() {
+    let results = evaluate(
+        &[
+            assertion(Check::Status { equals: 201 }),
+            assertion(Check::Status { equals: 200 }),
+            assertion(Check::Header {
+                name: "content-type".into(),
+                matches: "^application/json".into(),
+            }),
+            assertion(Check::Header {
+                name: "X-Trace".into(),
+                matches: "^b".into(),
+            }),
+            assertion(Check::Header {
+                name: "X-Missing".into(),
+                matches: ".*".into(),
+            }),
+            assertion(Check::ResponseTime { below_ms: 60_000 }),
+            assertion(Check::ResponseTime { below_ms: 0 }),
+            Assertion {
+                check: Check::Status { equals: 500 },
+                enabled: false,
+            },
+        ],
+        &response(),
+        Path::new("."),
+    );
+
+    assert_eq!(results.len(), 7);
+    assert_eq!(
+        failures(&results)[..5],
+        [
+            ("status equals 201", None),
+            ("status equals 200", Some("the status is 201")),
+            ("header content-type matches /^application/json/", None),
+            ("header X-Trace matches /^b/", None),
+            (
+                "header X-Missing matches /.*/",
+                Some("there is no X-Missing header")
+            ),
+        ]
+    );
+    assert!(results[5].passed());
+    assert!(
+        results[6]
+            .failure
+            .as_deref()
+            .unwrap()
+            .starts_with("the response took")
+    );
+}
+
+#[test]
+fn checks_json_path_values() {
+    let results = evaluate(
+        &[
+            json_path("$.id", Expectation::Equals(json!(7.0))),
+            json_path("$.roles", Expectation::Contains(json!("editor"))),
+            json_path(
+                "$.roles[*]",
+                Expectation::Equals(json!(["admin", "editor"])),
+            ),
+            json_path("$.name", Expectation::Contains(json!("Love"))),
+            json_path("$.name", Expectation::Matches("^Ada [A-Z]".into())),
+            json_path("$.score", Expectation::Matches(r"^1(\.0)?$".into())),
+            json_path("$.name", Expectation::Equals(json!("Grace"))),
+            json_path("$.missing", Expectation::Contains(json!(1))),
+            json_path("$[", Expectation::Equals(json!(1))),
+            json_path("$.name", Expectation::Matches("(".into())),
+        ],
+        &response(),
+        Path::new("."),
+    );
+
+    assert!(results[..6].iter().all(AssertionResult::passed));
+    assert_eq!(
+        failures(&results)[6..8],
+        [
+            (
+                r#"$.name equals "Grace""#,
+                Some(r#"the value is "Ada Lovelace""#)
+            ),
+            ("$.missing contains 1", Some("nothing was found")),
+        ]
+    );
+    assert!(
+        results[8]
+            .failure
+            .as_deref()
+            .unwrap()
+            .starts_with("invalid JSONPath")
+    );
+    assert!(
+        results[9]
+            .failure
+            .as_deref()
+            .unwrap()
+            .starts_with("invalid regular expression")
+    );
+}
+
+#[test]
+fn validates_json_schemas() {
+    let dir = env::temp_dir().join(format!("assertions-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("user.schema.json"),
+        json!({
+            "type": "object",
+            "required": ["id", "email"],
+            "properties": { "id": { "type": "string" } },
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let results = evaluate(
+        &[
+            assertion(Check::JsonSchema {
+                schema: r#"{ "type": "object", "required": ["id", "name"] }"#.into(),
+            }),
+            assertion(Check::JsonSchema {
+                schema: "user.schema.json".into(),
+            }),
+            assertion(Check::JsonSchema {
+                schema: "missing.schema.json".into(),
+            }),
+        ],
+        &response(),
+        &dir,
+    );
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(results[0].name, "body matches the JSON schema");
+    assert!(results[0].passed());
+
+    assert_eq!(
+        results[1].name,
+        "body matches the JSON schema user.schema.json"
+    );
+    let failure = results[1].failure.as_deref().unwrap();
+    assert!(
+        failure.contains("\"email\" is a required property"),
+        "{failure}"
+    );
+    assert!(
+        failure.contains("at /id: 7 is not of type \"string\""),
+        "{failure}"
+    );
+
+    assert!(
+        results[2]
+            .failure
+            .as_deref()
+            .unwrap()
+            .starts_with("reading")
+    );
+}
+
+#[test]
+fn reads_assertions_from_request_files() {
+    let assertions: Vec<Assertion> = serde_json::from_value(json!([
+        { "type": "status", "equals": 200 },
+        { "type": "json_path", "path": "$.id", "equals": 7 },
+        { "type": "json_path", "path": "$.name", "matches": "^Ada", "enabled": false },
+        { "type": "response_time", "below_ms": 500 },
+    ]))
+    .unwrap();
+
+    assert_eq!(
+        assertions[1],
+        json_path("$.id", Expectation::Equals(json!(7)))
+    );
+    assert!(!assertions[2].enabled);
+    assert_eq!(
+        serde_json::to_value(&assertions[2]).unwrap(),
+        json!({ "type": "json_path", "path": "$.name", "matches": "^Ada", "enabled": false })
+    );
+    assert_eq!(
+        serde_json::to_value(&assertions[3]).unwrap(),
+        json!({ "type": "response_time", "below_ms": 500 })
+    );
+}
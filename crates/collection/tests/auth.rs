@@ -0,0 +1,47 @@
+use std::collections::BTreeMap;
+
+use collection::auth::{ApiKeyLocation, Auth};
+use http_client::{HttpRequest, RequestOptions, http::Method, url::Url};
+
+fn request() -> HttpRequest {
+    HttpRequest {
+        method: Method::GET,
+        url: Url::parse("https://api.example.com/items").unwrap(),
+        headers: Vec::new(),
+        body: Default::default(),
+    }
+}
+
+#[test]
+fn headers_added_by_authentication_are_tracked() {
+    let variables = BTreeMap::from([("key".to_string(), "secret".to_string())]);
+    let auth = Auth::ApiKey {
+        name: "X-Api-Key".into(),
+        value: "{{key}}".into(),
+        location: ApiKeyLocation::Header,
+    };
+
+    let mut http_request = request();
+    let mut options = RequestOptions::default();
+    auth.apply(&mut http_request, &mut options, &variables, None)
+        .unwrap();
+
+    assert_eq!(
+        http_request.headers,
+        [("X-Api-Key".to_string(), "secret".to_string())]
+    );
+    assert_eq!(options.auth_headers, ["X-Api-Key"]);
+
+    let auth = Auth::ApiKey {
+        name: "key".into(),
+        value: "{{key}}".into(),
+        location: ApiKeyLocation::Query,
+    };
+    let mut http_request = request();
+    let mut options = RequestOptions::default();
+    auth.apply(&mut http_request, &mut options, &variables, None)
+        .unwrap();
+
+    assert_eq!(http_request.url.query(), Some("key=secret"));
+    assert!(options.auth_headers.is_empty());
+}
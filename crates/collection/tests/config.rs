@@ -0,0 +1,27 @@
+use std::collections::BTreeMap;
+
+use collection::config::{CollectionConfig, HostConfig, TlsConfig};
+
+#[test]
+fn host_patterns_ignore_case() {
+    let host = |server_name: &str| HostConfig {
+        tls: TlsConfig {
+            server_name: Some(server_name.to_string()),
+            ..Default::default()
+        },
+    };
+    let config = CollectionConfig {
+        hosts: BTreeMap::from([
+            ("*.Example.com".to_string(), host("wildcard")),
+            ("API.internal".to_string(), host("exact")),
+        ]),
+        ..Default::default()
+    };
+    let server_name = |host: &str| config.tls_options(host).server_name;
+
+    assert_eq!(server_name("api.example.com").as_deref(), Some("wildcard"));
+    assert_eq!(server_name("API.EXAMPLE.COM").as_deref(), Some("wildcard"));
+    assert_eq!(server_name("example.com"), None);
+    assert_eq!(server_name("notexample.com"), None);
+    assert_eq!(server_name("api.internal").as_deref(), Some("exact"));
+}
@@ -0,0 +1,148 @@
+use std::{path::Path, time::Duration};
+
+use collection::{
+    body::{MultipartPart, PartContent, RequestBody},
+    curl::curl_command,
+};
+use http_client::{
+    AwsCredentials, AwsSigV4, HttpBody, HttpRequest, HttpVersion, Proxy, RedirectPolicy,
+    RequestOptions, RequestSigner, Timeouts, http::Method, url::Url,
+};
+
+fn request(method: Method, body: HttpBody) -> HttpRequest {
+    HttpRequest {
+        method,
+        url: Url::parse("https://api.example.com/items?q=a b").unwrap(),
+        headers: vec![
+            ("Content-Type".into(), "application/json".into()),
+            ("X-Note".into(), "it's here".into()),
+        ],
+        body,
+    }
+}
+
+fn no_redirects() -> RequestOptions {
+    RequestOptions {
+        redirect: RedirectPolicy {
+            follow: false,
+            ..RedirectPolicy::default()
+        },
+        ..RequestOptions::default()
+    }
+}
+
+#[test]
+fn writes_the_method_headers_and_body() {
+    let command = curl_command(
+        &request(Method::GET, HttpBody::default()),
+        None,
+        Path::new("."),
+        &no_redirects(),
+    );
+    assert_eq!(
+        command,
+        "curl 'https://api.example.com/items?q=a%20b' \\\n  \
+         -H 'Content-Type: application/json' \\\n  \
+         -H 'X-Note: it'\\''s here'"
+    );
+
+    let command = curl_command(
+        &request(Method::PUT, r#"{"id":1}"#.to_string().into()),
+        None,
+        Path::new("."),
+        &no_redirects(),
+    );
+    assert!(
+        command.contains("\\\n  -X PUT \\\n") && command.ends_with(r#"--data-raw '{"id":1}'"#),
+        "{command}"
+    );
+
+    let command = curl_command(
+        &request(Method::POST, b"\xffa'".as_slice().into()),
+        None,
+        Path::new("."),
+        &no_redirects(),
+    );
+    assert!(!command.contains("-X"), "{command}");
+    assert!(command.ends_with(r"--data-binary $'\xffa\''"), "{command}");
+}
+
+#[test]
+fn writes_forms_with_files_as_form_arguments() {
+    let form = RequestBody::Multipart(vec![
+        MultipartPart {
+            name: "title".into(),
+            content: PartContent::Value("@not a file".into()),
+            content_type: None,
+            filename: None,
+            enabled: true,
+        },
+        MultipartPart {
+            name: "upload".into(),
+            content: PartContent::File("report.pdf".into()),
+            content_type: Some("application/pdf".into()),
+            filename: None,
+            enabled: true,
+        },
+    ]);
+    let dir = Path::new("/collection");
+    let (body, content_type) = form.to_http_body(dir).unwrap();
+    let mut http_request = request(Method::POST, body);
+    http_request.headers[0].1 = content_type.unwrap();
+
+    let command = curl_command(&http_request, Some(&form), dir, &no_redirects());
+    assert!(!command.contains("multipart/form-data"), "{command}");
+    assert!(
+        command.ends_with(
+            "--form-string 'title=@not a file' \\\n  \
+             -F 'upload=@/collection/report.pdf;type=application/pdf'"
+        ),
+        "{command}"
+    );
+}
+
+#[test]
+fn writes_how_the_request_is_sent() {
+    let options = RequestOptions {
+        version: HttpVersion::Http2,
+        proxy: Some(Proxy::parse("http://user:pw@proxy:3128").unwrap()),
+        redirect: RedirectPolicy {
+            max_hops: 3,
+            ..RedirectPolicy::default()
+        },
+        signer: Some(RequestSigner::AwsSigV4(AwsSigV4 {
+            service: "execute-api".into(),
+            region: "eu-west-1".into(),
+            credentials: AwsCredentials {
+                access_key_id: "AKID".into(),
+                secret_access_key: "SECRET".into(),
+                session_token: None,
+            },
+            unsigned_payload: false,
+        })),
+        timeouts: Timeouts {
+            connect: Some(Duration::from_millis(2500)),
+            ..Timeouts::default()
+        },
+        ..RequestOptions::default()
+    };
+
+    let command = curl_command(
+        &request(Method::GET, HttpBody::default()),
+        None,
+        Path::new("."),
+        &options,
+    );
+    let lines: Vec<&str> = command.lines().skip(3).map(str::trim).collect();
+    assert_eq!(
+        lines,
+        [
+            "--http2 \\",
+            "-x http://user:pw@proxy:3128 \\",
+            "-L \\",
+            "--max-redirs 3 \\",
+            "--aws-sigv4 aws:amz:eu-west-1:execute-api -u AKID:SECRET \\",
+            "--connect-timeout 2.5",
+        ]
+    );
+}
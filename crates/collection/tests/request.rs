@@ -0,0 +1,180 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{BufRead as _, BufReader, Read as _, Write as _},
+    net::TcpListener,
+    path::Path,
+    thread,
+};
+
+use collection::{
+    body::{FormField, MultipartPart, PartContent, RequestBody},
+    config::{CollectionConfig, Environment},
+    request::{Header, Method, Request},
+    scripts::apply_script_request,
+};
+use http_client::RequestOptions;
+
+fn config() -> CollectionConfig {
+    CollectionConfig {
+        environments: BTreeMap::from([(
+            "production".to_string(),
+            Environment {
+                variables: BTreeMap::from([("user".to_string(), "ada".to_string())]),
+                secrets: BTreeSet::from(["token".to_string()]),
+            },
+        )]),
+        ..Default::default()
+    }
+}
+
+/// Answers a single request with an empty response, handing back what was
+/// received.
+fn serve_once(listener: TcpListener) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut received = String::new();
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if let Some((name, value)) = line.split_once(':')
+                && name.eq_ignore_ascii_case("content-length")
+            {
+                content_length = value.trim().parse().unwrap();
+            }
+            received.push_str(&line);
+            if line == "\r\n" {
+                break;
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        received.push_str(&String::from_utf8(body).unwrap());
+
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        received
+    })
+}
+
+#[test]
+fn sends_secrets_through_headers() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = serve_once(listener);
+
+    let secrets = BTreeMap::from([("token".to_string(), "s3cr3t-token".to_string())]);
+    let mut variables = config().variables(Some("production"), Some(&secrets));
+    variables.insert("base".to_string(), format!("http://{address}"));
+    let request = Request {
+        method: Method::Post,
+        url: "{{base}}/users/{{user}}".into(),
+        headers: vec![Header {
+            name: "Authorization".into(),
+            value: "Bearer {{token}}".into(),
+            enabled: true,
+        }],
+        body: Some(RequestBody::Text(r#"{"name":"{{user}}"}"#.into())),
+        ..Default::default()
+    };
+
+    let http_request = request.to_http_request(Path::new("."), &variables).unwrap();
+    let response =
+        futures::executor::block_on(http_client::send(http_request, RequestOptions::default()))
+            .unwrap();
+    assert_eq!(response.status, 200);
+
+    let received = server.join().unwrap();
+    assert!(received.starts_with("POST /users/ada HTTP/1.1\r\n"));
+    assert!(received.contains("authorization: Bearer s3cr3t-token\r\n"));
+    assert!(received.ends_with(r#"{"name":"ada"}"#));
+}
+
+#[test]
+fn resolves_variables_of_enabled_fields() {
+    let variables = BTreeMap::from([("user".to_string(), "ada".to_string())]);
+    let field = |name: &str, value: &str, enabled| FormField {
+        name: name.into(),
+        value: value.into(),
+        enabled,
+    };
+    let request = Request {
+        url: "https://example.com".into(),
+        headers: vec![Header {
+            name: "X-{{missing}}".into(),
+            value: String::new(),
+            enabled: false,
+        }],
+        body: Some(RequestBody::UrlEncoded(vec![
+            field("{{user}}", "{{user}} lovelace", true),
+            field("unused", "{{missing}}", false),
+        ])),
+        ..Default::default()
+    };
+
+    let request = request.interpolated(&variables).unwrap();
+    assert_eq!(
+        request.body,
+        Some(RequestBody::UrlEncoded(vec![
+            field("ada", "ada lovelace", true),
+            field("unused", "{{missing}}", false),
+        ]))
+    );
+
+    let request = Request {
+        url: "https://example.com".into(),
+        body: Some(RequestBody::Multipart(vec![MultipartPart {
+            name: "note".into(),
+            content: PartContent::Value("by {{missing}}".into()),
+            content_type: None,
+            filename: None,
+            enabled: true,
+        }])),
+        ..Default::default()
+    };
+    let error = request.interpolated(&variables).unwrap_err();
+    assert!(format!("{error:#}").contains(r#"undefined variable "missing""#));
+}
+
+#[test]
+fn scripts_see_the_variables_they_may_define_as_references() {
+    let request = Request {
+        url: "https://{{host}}/items".into(),
+        headers: vec![Header {
+            name: "X-Token".into(),
+            value: "{{token}}".into(),
+            enabled: true,
+        }],
+        ..Default::default()
+    };
+    let mut variables = BTreeMap::from([("host".to_string(), "example.com".to_string())]);
+
+    let original = request
+        .to_script_request(Path::new("."), &variables)
+        .unwrap();
+    assert_eq!(original.url, "https://example.com/items");
+    assert_eq!(
+        original.headers,
+        [("X-Token".to_string(), "{{token}}".to_string())]
+    );
+
+    // As a pre-request script would.
+    variables.insert("token".to_string(), "abc".to_string());
+    let mut changed = original.clone();
+    changed
+        .headers
+        .push(("X-Signed".to_string(), "{{token}}!".to_string()));
+
+    let mut http_request = request.to_http_request(Path::new("."), &variables).unwrap();
+    apply_script_request(&mut http_request, &original, changed, &variables).unwrap();
+    assert_eq!(
+        http_request.headers,
+        [
+            ("X-Token".to_string(), "abc".to_string()),
+            ("X-Signed".to_string(), "abc!".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,143 @@
+use std::{collections::BTreeMap, env, fs, path::PathBuf};
+
+use collection::secrets::{Secrets, leaked_secrets, read_vault, redact, write_vault};
+use serde_json::{Value, json};
+
+/// A folder of its own for each test, removed when dropped.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let dir = env::temp_dir().join(format!("secrets-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn secrets() -> Secrets {
+    BTreeMap::from([
+        (
+            "production".to_string(),
+            BTreeMap::from([
+                ("token".to_string(), "s3cr3t-token".to_string()),
+                ("password".to_string(), "p\"w".to_string()),
+            ]),
+        ),
+        (
+            "staging".to_string(),
+            BTreeMap::from([("token".to_string(), "staging-token".to_string())]),
+        ),
+    ])
+}
+
+#[test]
+fn vaults_read_back_what_was_written() {
+    let dir = TempDir::new("round-trip");
+    let vault = dir.0.join("vault.json");
+    let key = dir.0.join("config").join("vault.key");
+
+    assert_eq!(read_vault(&vault, &key).unwrap(), Secrets::new());
+
+    write_vault(&vault, &key, &secrets()).unwrap();
+    assert_eq!(read_vault(&vault, &key).unwrap(), secrets());
+
+    let content = fs::read_to_string(&vault).unwrap();
+    assert!(!content.contains("s3cr3t-token"), "{content}");
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        let mode = fs::metadata(&key).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // The key is kept, so that the vault can still be read after being
+    // written again.
+    let mut changed = secrets();
+    changed.remove("staging");
+    write_vault(&vault, &key, &changed).unwrap();
+    assert_eq!(read_vault(&vault, &key).unwrap(), changed);
+}
+
+#[test]
+fn vaults_need_their_key() {
+    let dir = TempDir::new("wrong-key");
+    let vault = dir.0.join("vault.json");
+    let key = dir.0.join("vault.key");
+    let other_key = dir.0.join("other.key");
+
+    write_vault(&vault, &key, &secrets()).unwrap();
+    write_vault(&dir.0.join("other.json"), &other_key, &secrets()).unwrap();
+
+    let error = read_vault(&vault, &other_key).unwrap_err().to_string();
+    assert!(error.contains("encrypted with another key"), "{error}");
+
+    let error = read_vault(&vault, &dir.0.join("missing.key"))
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("is missing from"), "{error}");
+}
+
+#[test]
+fn tampered_vaults_are_rejected() {
+    let dir = TempDir::new("tampered");
+    let vault = dir.0.join("vault.json");
+    let key = dir.0.join("vault.key");
+    write_vault(&vault, &key, &secrets()).unwrap();
+
+    let mut file: Value = serde_json::from_str(&fs::read_to_string(&vault).unwrap()).unwrap();
+    let ciphertext = file["ciphertext"].as_str().unwrap();
+    let flipped = if ciphertext.starts_with('A') {
+        'B'
+    } else {
+        'A'
+    };
+    file["ciphertext"] = json!(format!("{flipped}{}", &ciphertext[1..]));
+    fs::write(&vault, file.to_string()).unwrap();
+
+    assert!(read_vault(&vault, &key).is_err());
+}
+
+#[test]
+fn leaked_secrets_are_found_as_is_or_escaped() {
+    let secrets = secrets();
+    let production = &secrets["production"];
+
+    let content = json!({
+        "headers": [{ "name": "Authorization", "value": "Bearer s3cr3t-token" }],
+    })
+    .to_string();
+    assert_eq!(leaked_secrets(&content, production), ["token"]);
+
+    let content = json!({ "password": "p\"w", "token": "{{token}}" }).to_string();
+    assert_eq!(leaked_secrets(&content, production), ["password"]);
+
+    let empty = BTreeMap::from([("unset".to_string(), String::new())]);
+    assert!(leaked_secrets("anything", &empty).is_empty());
+}
+
+#[test]
+fn redacting_puts_references_back() {
+    let secrets = BTreeMap::from([
+        ("token".to_string(), "abc".to_string()),
+        ("long_token".to_string(), "abcdef".to_string()),
+        ("unset".to_string(), String::new()),
+    ]);
+
+    assert_eq!(
+        redact(
+            "curl -H 'X-Key: abcdef' -H 'X-Other: abc' example.com",
+            &secrets
+        ),
+        "curl -H 'X-Key: {{long_token}}' -H 'X-Other: {{token}}' example.com"
+    );
+    assert_eq!(redact("nothing secret", &secrets), "nothing secret");
+}
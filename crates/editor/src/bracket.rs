@@ -0,0 +1,98 @@
+use std::ops::Range;
+
+use crate::language::HighlightSpan;
+
+const PAIRS: [(u8, u8); 3] = [(b'(', b')'), (b'[', b']'), (b'{', b'}')];
+
+/// Finds the bracket touching `offset` and its counterpart, ignoring the
+/// brackets that appear inside strings and comments.
+pub fn matching_brackets(
+    text: &str,
+    highlights: &[HighlightSpan],
+    offset: usize,
+) -> Option<(Range<usize>, Range<usize>)> {
+    let bytes = text.as_bytes();
+    let is_literal = |offset: usize| {
+        let index = highlights.partition_point(|span| span.range.end <= offset);
+
+        highlights.get(index).is_some_and(|span| {
+            span.range.start <= offset
+                && (span.name.starts_with("string") || span.name.starts_with("comment"))
+        })
+    };
+
+    [offset, offset.wrapping_sub(1)]
+        .into_iter()
+        .filter(|offset| *offset < bytes.len() && !is_literal(*offset))
+        .find_map(|offset| {
+            let byte = bytes[offset];
+            let (open, close) = PAIRS
+                .into_iter()
+                .find(|(open, close)| byte == *open || byte == *close)?;
+            let mut depth = 0usize;
+
+            let found = if byte == open {
+                (offset + 1..bytes.len()).find(|index| {
+                    step(bytes[*index], open, close, &mut depth) && !is_literal(*index)
+                })
+            } else {
+                (0..offset).rev().find(|index| {
+                    step(bytes[*index], close, open, &mut depth) && !is_literal(*index)
+                })
+            }?;
+
+            Some((offset..offset + 1, found..found + 1))
+        })
+}
+
+/// Tracks nesting while scanning away from a bracket, returning true once
+/// the scan reaches its counterpart.
+fn step(byte: u8, same: u8, counterpart: u8, depth: &mut usize) -> bool {
+    if byte == same {
+        *depth += 1;
+    } else if byte == counterpart {
+        if *depth == 0 {
+            return true;
+        }
+
+        *depth -= 1;
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_nested_brackets_from_either_side() {
+        let text = "f(a[1], {b})";
+
+        assert_eq!(matching_brackets(text, &[], 1), Some((1..2, 11..12)));
+        assert_eq!(matching_brackets(text, &[], 12), Some((11..12, 1..2)));
+        assert_eq!(matching_brackets(text, &[], 8), Some((8..9, 10..11)));
+        assert_eq!(matching_brackets(text, &[], 5), Some((5..6, 3..4)));
+        assert_eq!(matching_brackets(text, &[], 0), None);
+        assert_eq!(matching_brackets("(()", &[], 0), None);
+    }
+
+    #[test]
+    fn ignores_brackets_in_strings_and_comments() {
+        let text = r#"{"}": 1} // }"#;
+        let highlights = [
+            HighlightSpan {
+                range: 1..4,
+                name: "string.special.key",
+            },
+            HighlightSpan {
+                range: 9..13,
+                name: "comment",
+            },
+        ];
+
+        assert_eq!(matching_brackets(text, &highlights, 0), Some((0..1, 7..8)));
+        assert_eq!(matching_brackets(text, &highlights, 2), None);
+        assert_eq!(matching_brackets(text, &highlights, 12), None);
+    }
+}
@@ -0,0 +1,305 @@
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The editor text together with the offsets its rows start at.
+pub struct Buffer {
+    text: String,
+    row_starts: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharKind {
+    Whitespace,
+    Punctuation,
+    Word,
+}
+
+impl CharKind {
+    fn of(c: char) -> Self {
+        if c.is_whitespace() {
+            CharKind::Whitespace
+        } else if c.is_alphanumeric() || c == '_' {
+            CharKind::Word
+        } else {
+            CharKind::Punctuation
+        }
+    }
+}
+
+impl Buffer {
+    pub fn new(text: String) -> Self {
+        let mut buffer = Self {
+            text,
+            row_starts: Vec::new(),
+        };
+
+        buffer.reindex();
+        buffer
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn len(&self) -> usize {
+        self.text.len()
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.row_starts.len()
+    }
+
+    /// The byte range of `row`, excluding its newline.
+    pub fn row_range(&self, row: usize) -> Range<usize> {
+        let start = self.row_starts[row];
+        let end = self
+            .row_starts
+            .get(row + 1)
+            .map_or(self.text.len(), |next| next - 1);
+
+        start..end
+    }
+
+    pub fn row_text(&self, row: usize) -> &str {
+        &self.text[self.row_range(row)]
+    }
+
+    pub fn row_for_offset(&self, offset: usize) -> usize {
+        self.row_starts
+            .partition_point(|start| *start <= offset)
+            .saturating_sub(1)
+    }
+
+    pub fn clip_offset(&self, offset: usize) -> usize {
+        let mut offset = offset.min(self.text.len());
+
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        offset
+    }
+
+    /// The leading whitespace of `row`.
+    pub fn indent(&self, row: usize) -> &str {
+        let text = self.row_text(row);
+
+        &text[..text.len() - text.trim_start().len()]
+    }
+
+    pub fn is_blank(&self, row: usize) -> bool {
+        self.row_text(row).trim().is_empty()
+    }
+
+    pub fn previous_boundary(&self, offset: usize) -> usize {
+        let row_start = self.row_range(self.row_for_offset(offset)).start;
+
+        if offset == row_start {
+            return offset.saturating_sub(1);
+        }
+
+        self.text[row_start..offset]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(row_start, |(index, _)| row_start + index)
+    }
+
+    pub fn next_boundary(&self, offset: usize) -> usize {
+        let row_end = self.row_range(self.row_for_offset(offset)).end;
+
+        if offset >= row_end {
+            return (offset + 1).min(self.text.len());
+        }
+
+        self.text[offset..row_end]
+            .grapheme_indices(true)
+            .nth(1)
+            .map_or(row_end, |(index, _)| offset + index)
+    }
+
+    /// The start of the word before `offset`, skipping whitespace first.
+    pub fn previous_word_start(&self, offset: usize) -> usize {
+        let mut chars = self.text[..offset].char_indices().rev().peekable();
+        let mut start = offset;
+
+        while let Some((index, _)) = chars.next_if(|(_, c)| c.is_whitespace() && *c != '\n') {
+            start = index;
+        }
+
+        let Some(&(_, first)) = chars.peek() else {
+            return start;
+        };
+        let kind = CharKind::of(first);
+
+        if first == '\n' {
+            return start.min(offset.saturating_sub(1));
+        }
+
+        while let Some((index, _)) = chars.next_if(|(_, c)| CharKind::of(*c) == kind && *c != '\n')
+        {
+            start = index;
+        }
+
+        start
+    }
+
+    /// The end of the word after `offset`, skipping whitespace first.
+    pub fn next_word_end(&self, offset: usize) -> usize {
+        let mut chars = self.text[offset..].char_indices().peekable();
+        let mut end = offset;
+
+        while let Some((index, c)) = chars.next_if(|(_, c)| c.is_whitespace() && *c != '\n') {
+            end = offset + index + c.len_utf8();
+        }
+
+        let Some(&(_, first)) = chars.peek() else {
+            return end;
+        };
+        let kind = CharKind::of(first);
+
+        if first == '\n' {
+            return if end == offset { end + 1 } else { end };
+        }
+
+        while let Some((index, c)) = chars.next_if(|(_, c)| CharKind::of(*c) == kind && *c != '\n')
+        {
+            end = offset + index + c.len_utf8();
+        }
+
+        end
+    }
+
+    /// The word around `offset`, if any.
+    pub fn word_range(&self, offset: usize) -> Option<Range<usize>> {
+        let is_word = |c: &char| CharKind::of(*c) == CharKind::Word;
+        let start = self.text[..offset]
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| is_word(c))
+            .last()
+            .map_or(offset, |(index, _)| index);
+        let end = offset
+            + self.text[offset..]
+                .chars()
+                .take_while(is_word)
+                .map(char::len_utf8)
+                .sum::<usize>();
+
+        (start < end).then_some(start..end)
+    }
+
+    /// Applies `edits`, which must be sorted and disjoint, returning the
+    /// range each replacement ends up occupying.
+    pub fn edit(&mut self, edits: &[(Range<usize>, String)]) -> Vec<Range<usize>> {
+        let mut ranges = Vec::with_capacity(edits.len());
+        let mut delta = 0isize;
+
+        for (range, text) in edits {
+            let start = (range.start as isize + delta) as usize;
+            let end = (range.end as isize + delta) as usize;
+
+            self.text.replace_range(start..end, text);
+            ranges.push(start..start + text.len());
+            delta += text.len() as isize - range.len() as isize;
+        }
+
+        self.reindex();
+        ranges
+    }
+
+    pub fn set_text(&mut self, text: String) {
+        self.text = text;
+        self.reindex();
+    }
+
+    fn reindex(&mut self) {
+        self.row_starts.clear();
+        self.row_starts.push(0);
+        self.row_starts
+            .extend(self.text.match_indices('\n').map(|(index, _)| index + 1));
+    }
+}
+
+/// Maps `offset` in the text before `edits` to the text after them.
+pub fn transform_offset(offset: usize, edits: &[(Range<usize>, String)]) -> usize {
+    let mut delta = 0isize;
+
+    for (range, text) in edits {
+        if offset < range.start {
+            break;
+        }
+
+        if offset <= range.end {
+            return (range.start as isize + delta) as usize + text.len();
+        }
+
+        delta += text.len() as isize - range.len() as isize;
+    }
+
+    (offset as isize + delta) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indexes_rows() {
+        let buffer = Buffer::new("one\n  two\n\nfour".into());
+
+        assert_eq!(buffer.row_count(), 4);
+        assert_eq!(buffer.row_range(1), 4..9);
+        assert_eq!(buffer.row_text(3), "four");
+        assert_eq!(buffer.row_for_offset(3), 0);
+        assert_eq!(buffer.row_for_offset(4), 1);
+        assert_eq!(buffer.row_for_offset(buffer.len()), 3);
+        assert_eq!(buffer.indent(1), "  ");
+        assert!(buffer.is_blank(2));
+        assert_eq!(Buffer::new(String::new()).row_count(), 1);
+    }
+
+    #[test]
+    fn moves_by_grapheme_across_rows() {
+        let buffer = Buffer::new("ae\u{301}\nb".into());
+
+        assert_eq!(buffer.next_boundary(1), 4);
+        assert_eq!(buffer.previous_boundary(4), 1);
+        assert_eq!(buffer.next_boundary(4), 5);
+        assert_eq!(buffer.previous_boundary(5), 4);
+        assert_eq!(buffer.previous_boundary(0), 0);
+        assert_eq!(buffer.next_boundary(buffer.len()), buffer.len());
+        assert_eq!(buffer.clip_offset(3), 2);
+        assert_eq!(buffer.clip_offset(100), buffer.len());
+    }
+
+    #[test]
+    fn moves_by_word() {
+        let buffer = Buffer::new("let foo_bar = (1);\n  baz".into());
+
+        assert_eq!(buffer.next_word_end(0), 3);
+        assert_eq!(buffer.next_word_end(3), 11);
+        assert_eq!(buffer.next_word_end(13), 15);
+        assert_eq!(buffer.next_word_end(18), 19);
+        assert_eq!(buffer.previous_word_start(11), 4);
+        assert_eq!(buffer.previous_word_start(19), 18);
+        assert_eq!(buffer.previous_word_start(24), 21);
+        assert_eq!(buffer.word_range(6), Some(4..11));
+        assert_eq!(buffer.word_range(12), None);
+    }
+
+    #[test]
+    fn applies_edits_and_maps_offsets() {
+        let mut buffer = Buffer::new("abc\ndef".into());
+        let edits = [(1..2, "XY".to_string()), (4..7, "g\nh".to_string())];
+
+        assert_eq!(buffer.edit(&edits), [1..3, 5..8]);
+        assert_eq!(buffer.text(), "aXYc\ng\nh");
+        assert_eq!(buffer.row_count(), 3);
+
+        assert_eq!(transform_offset(0, &edits), 0);
+        assert_eq!(transform_offset(2, &edits), 3);
+        assert_eq!(transform_offset(3, &edits), 4);
+        assert_eq!(transform_offset(5, &edits), 8);
+    }
+}
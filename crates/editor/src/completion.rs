@@ -0,0 +1,226 @@
+use std::{ops::Range, rc::Rc};
+
+use gpui::{
+    App, Context, InteractiveElement, IntoElement, ParentElement, ScrollHandle, SharedString,
+    StatefulInteractiveElement, Styled, Window, anchored, deferred, div, point,
+    prelude::FluentBuilder, px,
+};
+use theme::ActiveTheme;
+use ui::traits::styled_ext::StyledExt;
+
+use crate::{Editor, ShowCompletions};
+
+/// A suggestion of the completion menu.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    pub label: SharedString,
+    /// Shown dimmed after the label, such as the type of a field.
+    pub detail: Option<SharedString>,
+    /// The text replaced when accepted, usually the word before the cursor.
+    pub range: Range<usize>,
+    pub new_text: String,
+}
+
+/// Suggests completions for the text around a cursor.
+pub trait CompletionProvider {
+    /// The suggestions at `offset` of `text`, already filtered by what is
+    /// typed before it and in the order to show them.
+    fn completions(&self, text: &str, offset: usize, cx: &App) -> Vec<Completion>;
+
+    /// Whether typing `ch` opens the menu, besides word characters.
+    fn is_trigger_character(&self, _ch: char) -> bool {
+        false
+    }
+}
+
+/// The open completion menu.
+pub struct CompletionMenu {
+    items: Vec<Completion>,
+    selected: usize,
+    scroll_handle: ScrollHandle,
+}
+
+impl Editor {
+    pub fn set_completion_provider(
+        &mut self,
+        provider: Option<Rc<dyn CompletionProvider>>,
+        cx: &mut Context<Self>,
+    ) {
+        self.completion_provider = provider;
+        self.completions = None;
+        cx.notify();
+    }
+
+    pub(crate) fn show_completions(
+        &mut self,
+        _: &ShowCompletions,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.open_completions(cx);
+    }
+
+    /// Opens the menu after typing `text` when it ends with a word or
+    /// trigger character.
+    pub(crate) fn completions_after_typing(&mut self, text: &str, cx: &mut Context<Self>) {
+        let Some(provider) = self.completion_provider.as_ref() else {
+            return;
+        };
+
+        let triggers = text.chars().next_back().is_some_and(|ch| {
+            ch.is_alphanumeric() || ch == '_' || provider.is_trigger_character(ch)
+        });
+
+        if triggers && self.completions.is_none() {
+            self.open_completions(cx);
+        }
+    }
+
+    fn open_completions(&mut self, cx: &mut Context<Self>) {
+        let items = self.query_completions(cx);
+
+        self.completions = (!items.is_empty()).then(|| CompletionMenu {
+            items,
+            selected: 0,
+            scroll_handle: ScrollHandle::new(),
+        });
+        cx.notify();
+    }
+
+    /// Requeries the open menu after the text or the cursor changed,
+    /// closing it when nothing matches anymore.
+    pub(crate) fn refresh_completions(&mut self, cx: &mut Context<Self>) {
+        if self.completions.is_none() {
+            return;
+        }
+
+        let items = self.query_completions(cx);
+
+        match self.completions.as_mut() {
+            Some(menu) if !items.is_empty() => {
+                if menu.items != items {
+                    menu.items = items;
+                    menu.selected = 0;
+                    menu.scroll_handle.scroll_to_item(0);
+                }
+            }
+            _ => self.completions = None,
+        }
+    }
+
+    fn query_completions(&self, cx: &App) -> Vec<Completion> {
+        let Some(provider) = self.completion_provider.as_ref() else {
+            return Vec::new();
+        };
+
+        let newest = self.newest_selection();
+        if self.read_only || self.selections.len() > 1 || !newest.is_empty() {
+            return Vec::new();
+        }
+
+        provider.completions(self.buffer.text(), newest.head(), cx)
+    }
+
+    pub(crate) fn has_completions(&self) -> bool {
+        self.completions.is_some()
+    }
+
+    pub(crate) fn dismiss_completions(&mut self, cx: &mut Context<Self>) {
+        if self.completions.take().is_some() {
+            cx.notify();
+        }
+    }
+
+    pub(crate) fn select_completion(&mut self, delta: isize, cx: &mut Context<Self>) {
+        let Some(menu) = self.completions.as_mut() else {
+            return;
+        };
+
+        let len = menu.items.len() as isize;
+        menu.selected = (menu.selected as isize + delta).rem_euclid(len) as usize;
+        menu.scroll_handle.scroll_to_item(menu.selected);
+        cx.notify();
+    }
+
+    /// Replaces the range of the selected completion with its text.
+    pub(crate) fn confirm_completion(&mut self, cx: &mut Context<Self>) {
+        let Some(menu) = self.completions.as_ref() else {
+            return;
+        };
+
+        let completion = menu.items[menu.selected].clone();
+
+        self.history.break_group();
+        self.edit(vec![(completion.range, completion.new_text)], cx);
+        self.history.break_group();
+        self.completions = None;
+    }
+
+    pub(crate) fn render_completions(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let menu = self.completions.as_ref()?;
+        let layout = self.last_layout.as_ref()?;
+        let start = menu.items[menu.selected].range.start;
+        let row = self.buffer.row_for_offset(start);
+        let column = start - self.buffer.row_range(row).start;
+        let position = layout.position_for_offset(row, column)?;
+        let colors = cx.theme().colors();
+
+        Some(
+            deferred(
+                anchored()
+                    .position(point(position.x, position.y + layout.line_height))
+                    .snap_to_window_with_margin(px(8.))
+                    .child(
+                        div()
+                            .id("completions")
+                            .v_flex()
+                            .min_w_48()
+                            .max_w_96()
+                            .max_h_64()
+                            .overflow_y_scroll()
+                            .track_scroll(&menu.scroll_handle)
+                            .p_1()
+                            .rounded_md()
+                            .border_1()
+                            .border_color(colors.border)
+                            .bg(colors.popover)
+                            .text_color(colors.popover_foreground)
+                            .text_sm()
+                            .shadow_lg()
+                            .on_mouse_down_out(
+                                cx.listener(|this, _, _, cx| this.dismiss_completions(cx)),
+                            )
+                            .children(menu.items.iter().enumerate().map(|(index, item)| {
+                                div()
+                                    .id(index)
+                                    .h_flex()
+                                    .gap_4()
+                                    .justify_between()
+                                    .px_2()
+                                    .py_0p5()
+                                    .rounded_sm()
+                                    .cursor_pointer()
+                                    .when(index == menu.selected, |this| {
+                                        this.bg(colors.list_active)
+                                    })
+                                    .hover(|this| this.bg(colors.list_hover))
+                                    .on_click(cx.listener(move |this, _, _, cx| {
+                                        if let Some(menu) = this.completions.as_mut() {
+                                            menu.selected = index;
+                                        }
+                                        this.confirm_completion(cx);
+                                    }))
+                                    .child(item.label.clone())
+                                    .children(item.detail.clone().map(|detail| {
+                                        div()
+                                            .text_xs()
+                                            .text_color(colors.muted_foreground)
+                                            .child(detail)
+                                    }))
+                            })),
+                    ),
+            )
+            .with_priority(1),
+        )
+    }
+}
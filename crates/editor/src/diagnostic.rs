@@ -0,0 +1,10 @@
+use std::ops::Range;
+
+use gpui::SharedString;
+
+/// A problem found in the text, underlined with a wavy line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub range: Range<usize>,
+    pub message: SharedString,
+}
@@ -0,0 +1,1712 @@
+mod bracket;
+mod buffer;
+mod completion;
+mod diagnostic;
+mod element;
+mod fold_map;
+mod history;
+pub mod language;
+mod search;
+mod selection;
+
+use std::{ops::Range, rc::Rc, sync::Arc};
+
+use gpui::{
+    App, AppContext, Bounds, ClipboardItem, Context, CursorStyle, EntityInputHandler, EventEmitter,
+    FocusHandle, Focusable, InteractiveElement, IntoElement, KeyBinding, MouseButton,
+    MouseDownEvent, MouseMoveEvent, MouseUpEvent, ParentElement, Pixels, Point, Render,
+    ScrollWheelEvent, SharedString, Styled, Task, UTF16Selection, Window, actions, div, point,
+    prelude::FluentBuilder, px,
+};
+use theme::ActiveTheme;
+use ui::traits::styled_ext::StyledExt;
+
+use crate::{
+    buffer::{Buffer, transform_offset},
+    completion::CompletionMenu,
+    element::{EditorElement, LastLayout},
+    fold_map::{FoldMap, fold_range_for_row},
+    history::{History, Snapshot},
+    language::{HighlightSpan, Language},
+    search::Search,
+    selection::Selection,
+};
+pub use crate::{
+    completion::{Completion, CompletionProvider},
+    diagnostic::Diagnostic,
+};
+
+const KEY_CONTEXT: &str = "Editor";
+const SEARCH_KEY_CONTEXT: &str = "EditorSearchBar";
+const INDENT: &str = "  ";
+
+#[cfg(target_os = "macos")]
+const FONT_FAMILY: &str = "Menlo";
+#[cfg(target_os = "windows")]
+const FONT_FAMILY: &str = "Consolas";
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const FONT_FAMILY: &str = "DejaVu Sans Mono";
+
+actions!(
+    editor,
+    [
+        MoveLeft,
+        MoveRight,
+        MoveUp,
+        MoveDown,
+        SelectLeft,
+        SelectRight,
+        SelectUp,
+        SelectDown,
+        MoveToPreviousWordStart,
+        MoveToNextWordEnd,
+        SelectToPreviousWordStart,
+        SelectToNextWordEnd,
+        MoveToBeginningOfLine,
+        MoveToEndOfLine,
+        SelectToBeginningOfLine,
+        SelectToEndOfLine,
+        MoveToBeginning,
+        MoveToEnd,
+        SelectToBeginning,
+        SelectToEnd,
+        SelectAll,
+        Backspace,
+        Delete,
+        DeleteToPreviousWordStart,
+        DeleteToNextWordEnd,
+        Newline,
+        Tab,
+        Backtab,
+        Copy,
+        Cut,
+        Paste,
+        Undo,
+        Redo,
+        AddSelectionAbove,
+        AddSelectionBelow,
+        SelectNextOccurrence,
+        Cancel,
+        Fold,
+        Unfold,
+        FoldAll,
+        UnfoldAll,
+        ToggleSoftWrap,
+        MoveToMatchingBracket,
+        Find,
+        SelectNextMatch,
+        SelectPreviousMatch,
+        SelectAllMatches,
+        DismissSearch,
+        ToggleCaseSensitive,
+        ShowCharacterPalette,
+        ShowCompletions,
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    let context = Some(KEY_CONTEXT);
+    let search_context = Some(SEARCH_KEY_CONTEXT);
+
+    cx.bind_keys([
+        KeyBinding::new("left", MoveLeft, context),
+        KeyBinding::new("right", MoveRight, context),
+        KeyBinding::new("up", MoveUp, context),
+        KeyBinding::new("down", MoveDown, context),
+        KeyBinding::new("shift-left", SelectLeft, context),
+        KeyBinding::new("shift-right", SelectRight, context),
+        KeyBinding::new("shift-up", SelectUp, context),
+        KeyBinding::new("shift-down", SelectDown, context),
+        KeyBinding::new("home", MoveToBeginningOfLine, context),
+        KeyBinding::new("end", MoveToEndOfLine, context),
+        KeyBinding::new("shift-home", SelectToBeginningOfLine, context),
+        KeyBinding::new("shift-end", SelectToEndOfLine, context),
+        KeyBinding::new("backspace", Backspace, context),
+        KeyBinding::new("shift-backspace", Backspace, context),
+        KeyBinding::new("delete", Delete, context),
+        KeyBinding::new("enter", Newline, context),
+        KeyBinding::new("shift-enter", Newline, context),
+        KeyBinding::new("tab", Tab, context),
+        KeyBinding::new("shift-tab", Backtab, context),
+        KeyBinding::new("secondary-a", SelectAll, context),
+        KeyBinding::new("secondary-c", Copy, context),
+        KeyBinding::new("secondary-x", Cut, context),
+        KeyBinding::new("secondary-v", Paste, context),
+        KeyBinding::new("secondary-z", Undo, context),
+        KeyBinding::new("secondary-shift-z", Redo, context),
+        KeyBinding::new("secondary-alt-up", AddSelectionAbove, context),
+        KeyBinding::new("secondary-alt-down", AddSelectionBelow, context),
+        KeyBinding::new("secondary-d", SelectNextOccurrence, context),
+        KeyBinding::new("escape", Cancel, context),
+        KeyBinding::new("secondary-alt-[", Fold, context),
+        KeyBinding::new("secondary-alt-]", Unfold, context),
+        KeyBinding::new("secondary-k secondary-0", FoldAll, context),
+        KeyBinding::new("secondary-k secondary-j", UnfoldAll, context),
+        KeyBinding::new("alt-z", ToggleSoftWrap, context),
+        KeyBinding::new("ctrl-m", MoveToMatchingBracket, context),
+        KeyBinding::new("ctrl-space", ShowCompletions, context),
+        KeyBinding::new("secondary-f", Find, context),
+        KeyBinding::new("secondary-g", SelectNextMatch, context),
+        KeyBinding::new("secondary-shift-g", SelectPreviousMatch, context),
+        KeyBinding::new("secondary-f", Find, search_context),
+        KeyBinding::new("secondary-g", SelectNextMatch, search_context),
+        KeyBinding::new("secondary-shift-g", SelectPreviousMatch, search_context),
+        KeyBinding::new("shift-enter", SelectPreviousMatch, search_context),
+        KeyBinding::new("alt-enter", SelectAllMatches, search_context),
+        KeyBinding::new("alt-c", ToggleCaseSensitive, search_context),
+        KeyBinding::new("escape", DismissSearch, search_context),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("alt-left", MoveToPreviousWordStart, context),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("alt-right", MoveToNextWordEnd, context),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("alt-shift-left", SelectToPreviousWordStart, context),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("alt-shift-right", SelectToNextWordEnd, context),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("alt-backspace", DeleteToPreviousWordStart, context),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("alt-delete", DeleteToNextWordEnd, context),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-left", MoveToBeginningOfLine, context),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-right", MoveToEndOfLine, context),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-shift-left", SelectToBeginningOfLine, context),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-shift-right", SelectToEndOfLine, context),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-up", MoveToBeginning, context),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-down", MoveToEnd, context),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-shift-up", SelectToBeginning, context),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-shift-down", SelectToEnd, context),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-ctrl-space", ShowCharacterPalette, context),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-left", MoveToPreviousWordStart, context),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-right", MoveToNextWordEnd, context),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-shift-left", SelectToPreviousWordStart, context),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-shift-right", SelectToNextWordEnd, context),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-backspace", DeleteToPreviousWordStart, context),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-delete", DeleteToNextWordEnd, context),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-home", MoveToBeginning, context),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-end", MoveToEnd, context),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-shift-home", SelectToBeginning, context),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-shift-end", SelectToEnd, context),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-y", Redo, context),
+    ]);
+}
+
+pub enum EditorEvent {
+    Changed,
+}
+
+/// A multi-line code editor with syntax highlighting, line numbers, soft
+/// wrap, folding, search, multiple cursors, and the completions and
+/// diagnostics of an optional provider.
+pub struct Editor {
+    focus_handle: FocusHandle,
+    buffer: Buffer,
+    language: Option<Language>,
+    highlights: Arc<Vec<HighlightSpan>>,
+    diagnostics: Vec<Diagnostic>,
+    completion_provider: Option<Rc<dyn CompletionProvider>>,
+    completions: Option<CompletionMenu>,
+    selections: Vec<Selection>,
+    next_selection_id: usize,
+    marked_range: Option<Range<usize>>,
+    folds: FoldMap,
+    read_only: bool,
+    soft_wrap: bool,
+    show_line_numbers: bool,
+    placeholder: SharedString,
+    search: Option<Search>,
+    history: History,
+    scroll_position: Point<Pixels>,
+    autoscroll: bool,
+    is_selecting: bool,
+    last_layout: Option<LastLayout>,
+    _highlight: Task<()>,
+}
+
+impl Editor {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        Self {
+            focus_handle: cx.focus_handle(),
+            buffer: Buffer::new(String::new()),
+            language: None,
+            highlights: Arc::default(),
+            diagnostics: Vec::new(),
+            completion_provider: None,
+            completions: None,
+            selections: vec![Selection::new(0, 0)],
+            next_selection_id: 1,
+            marked_range: None,
+            folds: FoldMap::default(),
+            read_only: false,
+            soft_wrap: true,
+            show_line_numbers: true,
+            placeholder: SharedString::default(),
+            search: None,
+            history: History::default(),
+            scroll_position: Point::default(),
+            autoscroll: false,
+            is_selecting: false,
+            last_layout: None,
+            _highlight: Task::ready(()),
+        }
+    }
+
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    pub fn soft_wrap(mut self, soft_wrap: bool) -> Self {
+        self.soft_wrap = soft_wrap;
+        self
+    }
+
+    pub fn show_line_numbers(mut self, show_line_numbers: bool) -> Self {
+        self.show_line_numbers = show_line_numbers;
+        self
+    }
+
+    pub fn placeholder(mut self, placeholder: impl Into<SharedString>) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    pub fn text(&self) -> &str {
+        self.buffer.text()
+    }
+
+    pub fn language(&self) -> Option<Language> {
+        self.language
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Replaces the whole text, resetting selections, folds and undo
+    /// history, without emitting [`EditorEvent::Changed`].
+    pub fn set_text(&mut self, text: impl Into<String>, cx: &mut Context<Self>) {
+        self.buffer.set_text(text.into().replace("\r\n", "\n"));
+        self.selections = vec![Selection::new(self.next_selection_id, 0)];
+        self.next_selection_id += 1;
+        self.marked_range = None;
+        self.folds.clear();
+        self.history.clear();
+        self.diagnostics.clear();
+        self.completions = None;
+        self.scroll_position = Point::default();
+        self.highlights = Arc::default();
+        self.refresh_highlights(cx);
+        self.refresh_search(false, cx);
+        cx.notify();
+    }
+
+    pub fn set_language(&mut self, language: Option<Language>, cx: &mut Context<Self>) {
+        if self.language != language {
+            self.language = language;
+            self.highlights = Arc::default();
+            self.refresh_highlights(cx);
+            cx.notify();
+        }
+    }
+
+    pub fn set_read_only(&mut self, read_only: bool, cx: &mut Context<Self>) {
+        self.read_only = read_only;
+        cx.notify();
+    }
+
+    /// Replaces the underlined problems, which move along with later edits
+    /// until the next call.
+    pub fn set_diagnostics(&mut self, mut diagnostics: Vec<Diagnostic>, cx: &mut Context<Self>) {
+        diagnostics.sort_by_key(|diagnostic| (diagnostic.range.start, diagnostic.range.end));
+        self.diagnostics = diagnostics;
+        cx.notify();
+    }
+
+    fn refresh_highlights(&mut self, cx: &mut Context<Self>) {
+        let Some(language) = self.language else {
+            self.highlights = Arc::default();
+            self._highlight = Task::ready(());
+            return;
+        };
+
+        let text = self.buffer.text().to_string();
+        let highlights = cx.background_spawn(async move { language.highlight(&text) });
+
+        self._highlight = cx.spawn(async move |this, cx| {
+            let highlights = highlights.await;
+
+            this.update(cx, |this, cx| {
+                this.highlights = Arc::new(highlights);
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            text: self.buffer.text().to_string(),
+            selections: self.selections.clone(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot, cx: &mut Context<Self>) {
+        self.buffer.set_text(snapshot.text);
+        self.selections = snapshot.selections;
+        self.marked_range = None;
+        self.folds.clear();
+        self.text_changed(cx);
+    }
+
+    fn newest_selection(&self) -> &Selection {
+        self.selections
+            .iter()
+            .max_by_key(|selection| selection.id)
+            .expect("an editor always has a selection")
+    }
+
+    fn selected_text(&self, selection: &Selection) -> &str {
+        &self.buffer.text()[selection.range()]
+    }
+
+    /// Applies `edits`, which must be sorted and disjoint, moving the
+    /// selections, folds and highlights along. Returns the range each
+    /// replacement ends up occupying.
+    fn edit(
+        &mut self,
+        edits: Vec<(Range<usize>, String)>,
+        cx: &mut Context<Self>,
+    ) -> Vec<Range<usize>> {
+        if self.read_only || edits.is_empty() {
+            return Vec::new();
+        }
+
+        self.history.push(|| Snapshot {
+            text: self.buffer.text().to_string(),
+            selections: self.selections.clone(),
+        });
+
+        for selection in &mut self.selections {
+            selection.start = transform_offset(selection.start, &edits);
+            selection.end = transform_offset(selection.end, &edits);
+        }
+
+        // Shift the stale highlights until the new ones are computed, so that
+        // colors don't jump around while typing.
+        self.highlights = Arc::new(
+            self.highlights
+                .iter()
+                .map(|span| HighlightSpan {
+                    range: transform_offset(span.range.start, &edits)
+                        ..transform_offset(span.range.end, &edits),
+                    name: span.name,
+                })
+                .filter(|span| !span.range.is_empty())
+                .collect(),
+        );
+
+        for diagnostic in &mut self.diagnostics {
+            diagnostic.range = transform_offset(diagnostic.range.start, &edits)
+                ..transform_offset(diagnostic.range.end, &edits);
+        }
+        self.diagnostics
+            .retain(|diagnostic| !diagnostic.range.is_empty());
+
+        self.folds.edit(&edits);
+        let ranges = self.buffer.edit(&edits);
+        self.marked_range = None;
+        self.text_changed(cx);
+
+        ranges
+    }
+
+    fn text_changed(&mut self, cx: &mut Context<Self>) {
+        self.refresh_highlights(cx);
+        self.refresh_search(false, cx);
+        self.refresh_completions(cx);
+        self.autoscroll = true;
+        cx.emit(EditorEvent::Changed);
+        cx.notify();
+    }
+
+    /// Replaces every selection with the text `f` returns for it, placing
+    /// the cursor at the offset `f` returns within that text.
+    fn replace_selections(
+        &mut self,
+        cx: &mut Context<Self>,
+        f: impl Fn(&Buffer, &Selection) -> (String, usize),
+    ) {
+        if self.read_only {
+            return;
+        }
+
+        let replacements = self
+            .selections
+            .iter()
+            .map(|selection| f(&self.buffer, selection))
+            .collect::<Vec<_>>();
+        let edits = self
+            .selections
+            .iter()
+            .zip(&replacements)
+            .map(|(selection, (text, _))| (selection.range(), text.clone()))
+            .collect();
+        let ranges = self.edit(edits, cx);
+
+        for ((selection, range), (_, cursor)) in
+            self.selections.iter_mut().zip(ranges).zip(&replacements)
+        {
+            selection.collapse_to(range.start + cursor);
+        }
+
+        selection::normalize(&mut self.selections);
+    }
+
+    fn insert(&mut self, text: &str, cx: &mut Context<Self>) {
+        self.replace_selections(cx, |_, _| (text.to_string(), text.len()));
+    }
+
+    /// Keeps `offset` out of folded regions, moving it past the fold in the
+    /// direction it came from.
+    fn clip_folded(&self, offset: usize, previous: usize) -> usize {
+        match self.folds.fold_containing(offset) {
+            Some(fold) if offset > previous && fold.end < self.buffer.len() => fold.end + 1,
+            Some(fold) => fold.start.saturating_sub(1),
+            None => offset,
+        }
+    }
+
+    fn change_selections(&mut self, cx: &mut Context<Self>, f: impl FnOnce(&mut Vec<Selection>)) {
+        f(&mut self.selections);
+
+        if self.selections.is_empty() {
+            self.selections
+                .push(Selection::new(self.next_selection_id, 0));
+            self.next_selection_id += 1;
+        }
+
+        selection::normalize(&mut self.selections);
+        self.marked_range = None;
+        self.history.break_group();
+        self.refresh_completions(cx);
+        self.autoscroll = true;
+        cx.notify();
+    }
+
+    /// Moves the head of every selection to the offset `f` returns for it,
+    /// extending the selections when `select` is set.
+    fn move_heads(
+        &mut self,
+        select: bool,
+        cx: &mut Context<Self>,
+        f: impl Fn(&Self, &Selection) -> usize,
+    ) {
+        let heads = self
+            .selections
+            .iter()
+            .map(|selection| self.clip_folded(f(self, selection), selection.head()))
+            .collect::<Vec<_>>();
+
+        self.change_selections(cx, |selections| {
+            for (selection, head) in selections.iter_mut().zip(heads) {
+                if select {
+                    selection.set_head(head);
+                } else {
+                    selection.collapse_to(head);
+                }
+            }
+        });
+    }
+
+    fn move_vertically(&mut self, delta: isize, select: bool, cx: &mut Context<Self>) {
+        let targets = self
+            .selections
+            .iter()
+            .map(|selection| {
+                let head = if select || selection.is_empty() {
+                    selection.head()
+                } else if delta < 0 {
+                    selection.start
+                } else {
+                    selection.end
+                };
+
+                self.vertical_offset(head, selection.goal_x, delta)
+            })
+            .collect::<Vec<_>>();
+
+        self.change_selections(cx, |selections| {
+            for (selection, (head, goal_x)) in selections.iter_mut().zip(targets) {
+                if select {
+                    selection.set_head(head);
+                } else {
+                    selection.collapse_to(head);
+                }
+
+                selection.goal_x = goal_x;
+            }
+        });
+    }
+
+    /// The offset one display line above or below `offset`, keeping the
+    /// horizontal position `goal_x` when a layout is available.
+    fn vertical_offset(
+        &self,
+        offset: usize,
+        goal_x: Option<Pixels>,
+        delta: isize,
+    ) -> (usize, Option<Pixels>) {
+        let row = self.buffer.row_for_offset(offset);
+        let column = offset - self.buffer.row_range(row).start;
+        let visible_rows = self.folds.visible_rows(&self.buffer);
+        let Ok(index) = visible_rows.binary_search(&row) else {
+            return (offset, goal_x);
+        };
+
+        if let Some(layout) = self.last_layout.as_ref()
+            && let Some(current) = layout.row(row)
+            && let Some(position) = current.line.position_for_index(column, layout.line_height)
+        {
+            let goal_x = goal_x.unwrap_or(position.x);
+            let line_height = layout.line_height;
+            let y = position.y + line_height * delta as f32;
+
+            if y >= px(0.) && y < current.height(line_height) {
+                return (
+                    current.offset_for_position(point(goal_x, y), line_height),
+                    Some(goal_x),
+                );
+            }
+
+            let target = index as isize + delta.signum();
+            if target < 0 {
+                return (0, Some(goal_x));
+            }
+
+            let Some(target_row) = visible_rows.get(target as usize) else {
+                return (self.buffer.len(), Some(goal_x));
+            };
+
+            if let Some(target) = layout.row(*target_row) {
+                let y = if delta < 0 {
+                    target.height(line_height) - line_height / 2.
+                } else {
+                    line_height / 2.
+                };
+
+                return (
+                    target.offset_for_position(point(goal_x, y), line_height),
+                    Some(goal_x),
+                );
+            }
+        }
+
+        let target = index as isize + delta;
+        if target < 0 {
+            return (0, goal_x);
+        }
+
+        match visible_rows.get(target as usize) {
+            Some(target_row) => {
+                let range = self.buffer.row_range(*target_row);
+
+                (
+                    self.buffer
+                        .clip_offset((range.start + column).min(range.end)),
+                    goal_x,
+                )
+            }
+            None => (self.buffer.len(), goal_x),
+        }
+    }
+
+    /// The rows touched by the selections, each once.
+    fn selected_rows(&self) -> Vec<usize> {
+        let mut rows = Vec::new();
+
+        for selection in &self.selections {
+            let start = self.buffer.row_for_offset(selection.start);
+            let mut end = self.buffer.row_for_offset(selection.end);
+
+            if end > start && self.buffer.row_range(end).start == selection.end {
+                end -= 1;
+            }
+
+            let after = rows.last().map_or(0, |last| last + 1);
+
+            rows.extend(start.max(after)..=end);
+        }
+
+        rows
+    }
+
+    fn move_left(&mut self, _: &MoveLeft, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_heads(false, cx, |this, selection| {
+            if selection.is_empty() {
+                this.buffer.previous_boundary(selection.head())
+            } else {
+                selection.start
+            }
+        });
+    }
+
+    fn move_right(&mut self, _: &MoveRight, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_heads(false, cx, |this, selection| {
+            if selection.is_empty() {
+                this.buffer.next_boundary(selection.head())
+            } else {
+                selection.end
+            }
+        });
+    }
+
+    fn move_up(&mut self, _: &MoveUp, _: &mut Window, cx: &mut Context<Self>) {
+        if self.has_completions() {
+            self.select_completion(-1, cx);
+        } else {
+            self.move_vertically(-1, false, cx);
+        }
+    }
+
+    fn move_down(&mut self, _: &MoveDown, _: &mut Window, cx: &mut Context<Self>) {
+        if self.has_completions() {
+            self.select_completion(1, cx);
+        } else {
+            self.move_vertically(1, false, cx);
+        }
+    }
+
+    fn select_left(&mut self, _: &SelectLeft, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_heads(true, cx, |this, selection| {
+            this.buffer.previous_boundary(selection.head())
+        });
+    }
+
+    fn select_right(&mut self, _: &SelectRight, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_heads(true, cx, |this, selection| {
+            this.buffer.next_boundary(selection.head())
+        });
+    }
+
+    fn select_up(&mut self, _: &SelectUp, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_vertically(-1, true, cx);
+    }
+
+    fn select_down(&mut self, _: &SelectDown, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_vertically(1, true, cx);
+    }
+
+    fn move_to_previous_word_start(
+        &mut self,
+        _: &MoveToPreviousWordStart,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.move_heads(false, cx, |this, selection| {
+            this.buffer.previous_word_start(selection.head())
+        });
+    }
+
+    fn move_to_next_word_end(
+        &mut self,
+        _: &MoveToNextWordEnd,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.move_heads(false, cx, |this, selection| {
+            this.buffer.next_word_end(selection.head())
+        });
+    }
+
+    fn select_to_previous_word_start(
+        &mut self,
+        _: &SelectToPreviousWordStart,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.move_heads(true, cx, |this, selection| {
+            this.buffer.previous_word_start(selection.head())
+        });
+    }
+
+    fn select_to_next_word_end(
+        &mut self,
+        _: &SelectToNextWordEnd,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.move_heads(true, cx, |this, selection| {
+            this.buffer.next_word_end(selection.head())
+        });
+    }
+
+    /// The first non-whitespace character of the row, or the row start when
+    /// already there.
+    fn line_start(&self, offset: usize) -> usize {
+        let row = self.buffer.row_for_offset(offset);
+        let start = self.buffer.row_range(row).start;
+        let indented = start + self.buffer.indent(row).len();
+
+        if offset == indented { start } else { indented }
+    }
+
+    fn line_end(&self, offset: usize) -> usize {
+        self.buffer
+            .row_range(self.buffer.row_for_offset(offset))
+            .end
+    }
+
+    fn move_to_beginning_of_line(
+        &mut self,
+        _: &MoveToBeginningOfLine,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.move_heads(false, cx, |this, selection| {
+            this.line_start(selection.head())
+        });
+    }
+
+    fn move_to_end_of_line(&mut self, _: &MoveToEndOfLine, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_heads(false, cx, |this, selection| this.line_end(selection.head()));
+    }
+
+    fn select_to_beginning_of_line(
+        &mut self,
+        _: &SelectToBeginningOfLine,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.move_heads(true, cx, |this, selection| {
+            this.line_start(selection.head())
+        });
+    }
+
+    fn select_to_end_of_line(
+        &mut self,
+        _: &SelectToEndOfLine,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.move_heads(true, cx, |this, selection| this.line_end(selection.head()));
+    }
+
+    fn move_to_beginning(&mut self, _: &MoveToBeginning, _: &mut Window, cx: &mut Context<Self>) {
+        let id = self.newest_selection().id;
+
+        self.change_selections(cx, |selections| *selections = vec![Selection::new(id, 0)]);
+    }
+
+    fn move_to_end(&mut self, _: &MoveToEnd, _: &mut Window, cx: &mut Context<Self>) {
+        let id = self.newest_selection().id;
+        let len = self.clip_folded(self.buffer.len(), 0);
+
+        self.change_selections(cx, |selections| *selections = vec![Selection::new(id, len)]);
+    }
+
+    fn select_to_beginning(
+        &mut self,
+        _: &SelectToBeginning,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let mut selection = self.newest_selection().clone();
+
+        self.change_selections(cx, |selections| {
+            selection.set_head(0);
+            *selections = vec![selection];
+        });
+    }
+
+    fn select_to_end(&mut self, _: &SelectToEnd, _: &mut Window, cx: &mut Context<Self>) {
+        let mut selection = self.newest_selection().clone();
+        let len = self.buffer.len();
+
+        self.change_selections(cx, |selections| {
+            selection.set_head(len);
+            *selections = vec![selection];
+        });
+    }
+
+    fn select_all(&mut self, _: &SelectAll, _: &mut Window, cx: &mut Context<Self>) {
+        let id = self.newest_selection().id;
+        let len = self.buffer.len();
+
+        self.change_selections(cx, |selections| {
+            let mut selection = Selection::new(id, 0);
+
+            selection.select(0..len, false);
+            *selections = vec![selection];
+        });
+    }
+
+    fn backspace(&mut self, _: &Backspace, _: &mut Window, cx: &mut Context<Self>) {
+        self.delete_empty_selections(cx, |buffer, offset| buffer.previous_boundary(offset));
+    }
+
+    fn delete(&mut self, _: &Delete, _: &mut Window, cx: &mut Context<Self>) {
+        self.delete_empty_selections(cx, |buffer, offset| buffer.next_boundary(offset));
+    }
+
+    fn delete_to_previous_word_start(
+        &mut self,
+        _: &DeleteToPreviousWordStart,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.history.break_group();
+        self.delete_empty_selections(cx, |buffer, offset| buffer.previous_word_start(offset));
+    }
+
+    fn delete_to_next_word_end(
+        &mut self,
+        _: &DeleteToNextWordEnd,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.history.break_group();
+        self.delete_empty_selections(cx, |buffer, offset| buffer.next_word_end(offset));
+    }
+
+    /// Extends the empty selections to the offset `f` returns for their head,
+    /// then deletes every selection.
+    fn delete_empty_selections(
+        &mut self,
+        cx: &mut Context<Self>,
+        f: impl Fn(&Buffer, usize) -> usize,
+    ) {
+        if self.read_only {
+            return;
+        }
+
+        let heads = self
+            .selections
+            .iter()
+            .map(|selection| {
+                selection
+                    .is_empty()
+                    .then(|| self.clip_folded(f(&self.buffer, selection.head()), selection.head()))
+            })
+            .collect::<Vec<_>>();
+
+        for (selection, head) in self.selections.iter_mut().zip(heads) {
+            if let Some(head) = head {
+                selection.set_head(head);
+            }
+        }
+
+        selection::normalize(&mut self.selections);
+        self.replace_selections(cx, |_, _| (String::new(), 0));
+    }
+
+    /// Inserts a newline, keeping the indentation of the current row and
+    /// indenting once more after an opening bracket.
+    fn newline(&mut self, _: &Newline, _: &mut Window, cx: &mut Context<Self>) {
+        if self.has_completions() {
+            self.confirm_completion(cx);
+            return;
+        }
+
+        self.history.break_group();
+        self.replace_selections(cx, |buffer, selection| {
+            let row = buffer.row_for_offset(selection.start);
+            let row_start = buffer.row_range(row).start;
+            let indent = buffer.indent(row);
+            let indent = &indent[..indent.len().min(selection.start - row_start)];
+            let before = buffer.text()[row_start..selection.start]
+                .trim_end()
+                .chars()
+                .next_back();
+            let after = buffer.text()[selection.end..]
+                .trim_start_matches([' ', '\t'])
+                .chars()
+                .next();
+
+            let mut text = format!("\n{indent}");
+            if matches!(before, Some('{' | '[' | '(')) {
+                text.push_str(INDENT);
+            }
+
+            let cursor = text.len();
+            if matches!(
+                (before, after),
+                (Some('{'), Some('}')) | (Some('['), Some(']')) | (Some('('), Some(')'))
+            ) {
+                text.push('\n');
+                text.push_str(indent);
+            }
+
+            (text, cursor)
+        });
+    }
+
+    fn tab(&mut self, _: &Tab, _: &mut Window, cx: &mut Context<Self>) {
+        if self.has_completions() {
+            self.confirm_completion(cx);
+            return;
+        }
+
+        if self.selections.iter().all(Selection::is_empty) {
+            self.insert(INDENT, cx);
+            return;
+        }
+
+        let edits = self
+            .selected_rows()
+            .into_iter()
+            .filter(|row| !self.buffer.is_blank(*row))
+            .map(|row| {
+                let start = self.buffer.row_range(row).start;
+
+                (start..start, INDENT.to_string())
+            })
+            .collect();
+
+        self.history.break_group();
+        self.edit(edits, cx);
+    }
+
+    fn backtab(&mut self, _: &Backtab, _: &mut Window, cx: &mut Context<Self>) {
+        let edits = self
+            .selected_rows()
+            .into_iter()
+            .filter_map(|row| {
+                let start = self.buffer.row_range(row).start;
+                let indent = self.buffer.indent(row);
+                let len = if indent.starts_with('\t') {
+                    1
+                } else {
+                    indent.len().min(INDENT.len())
+                };
+
+                (len > 0).then(|| (start..start + len, String::new()))
+            })
+            .collect();
+
+        self.history.break_group();
+        self.edit(edits, cx);
+    }
+
+    fn copy(&mut self, _: &Copy, _: &mut Window, cx: &mut Context<Self>) {
+        let text = self
+            .selections
+            .iter()
+            .filter(|selection| !selection.is_empty())
+            .map(|selection| self.selected_text(selection))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if !text.is_empty() {
+            cx.write_to_clipboard(ClipboardItem::new_string(text));
+        }
+    }
+
+    fn cut(&mut self, _: &Cut, window: &mut Window, cx: &mut Context<Self>) {
+        if self.read_only {
+            return;
+        }
+
+        self.copy(&Copy, window, cx);
+
+        if self
+            .selections
+            .iter()
+            .any(|selection| !selection.is_empty())
+        {
+            self.history.break_group();
+            self.insert("", cx);
+        }
+    }
+
+    /// Pastes the clipboard, spreading its lines over the cursors when there
+    /// are as many of both.
+    fn paste(&mut self, _: &Paste, _: &mut Window, cx: &mut Context<Self>) {
+        let Some(text) = cx.read_from_clipboard().and_then(|item| item.text()) else {
+            return;
+        };
+
+        let text = text.replace("\r\n", "\n");
+        let lines = text.split('\n').collect::<Vec<_>>();
+
+        self.history.break_group();
+
+        if self.selections.len() > 1 && lines.len() == self.selections.len() {
+            let edits = self
+                .selections
+                .iter()
+                .zip(&lines)
+                .map(|(selection, line)| (selection.range(), line.to_string()))
+                .collect();
+            let ranges = self.edit(edits, cx);
+
+            for (selection, range) in self.selections.iter_mut().zip(ranges) {
+                selection.collapse_to(range.end);
+            }
+        } else {
+            self.insert(&text, cx);
+        }
+    }
+
+    fn undo(&mut self, _: &Undo, _: &mut Window, cx: &mut Context<Self>) {
+        if !self.read_only
+            && let Some(snapshot) = self.history.undo(self.snapshot())
+        {
+            self.restore(snapshot, cx);
+        }
+    }
+
+    fn redo(&mut self, _: &Redo, _: &mut Window, cx: &mut Context<Self>) {
+        if !self.read_only
+            && let Some(snapshot) = self.history.redo(self.snapshot())
+        {
+            self.restore(snapshot, cx);
+        }
+    }
+
+    fn add_selection_above(
+        &mut self,
+        _: &AddSelectionAbove,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.add_selection_vertically(-1, cx);
+    }
+
+    fn add_selection_below(
+        &mut self,
+        _: &AddSelectionBelow,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.add_selection_vertically(1, cx);
+    }
+
+    fn add_selection_vertically(&mut self, delta: isize, cx: &mut Context<Self>) {
+        let newest = self.newest_selection();
+        let (offset, goal_x) = self.vertical_offset(newest.head(), newest.goal_x, delta);
+
+        if offset == newest.head() {
+            return;
+        }
+
+        let mut selection = Selection::new(self.next_selection_id, offset);
+        selection.goal_x = goal_x;
+        self.next_selection_id += 1;
+        self.change_selections(cx, |selections| selections.push(selection));
+    }
+
+    /// Selects the word under the newest cursor, or adds a selection for the
+    /// next occurrence of the newest selection's text.
+    fn select_next_occurrence(
+        &mut self,
+        _: &SelectNextOccurrence,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let newest = self.newest_selection().clone();
+
+        if newest.is_empty() {
+            if let Some(range) = self.buffer.word_range(newest.head()) {
+                self.change_selections(cx, |selections| {
+                    if let Some(selection) = selections
+                        .iter_mut()
+                        .find(|selection| selection.id == newest.id)
+                    {
+                        selection.select(range, false);
+                    }
+                });
+            }
+
+            return;
+        }
+
+        let query = self.selected_text(&newest);
+        let text = self.buffer.text();
+        let found = text[newest.end..]
+            .find(query)
+            .map(|index| newest.end + index)
+            .or_else(|| text.find(query))
+            .map(|start| start..start + query.len())
+            .filter(|range| {
+                !self
+                    .selections
+                    .iter()
+                    .any(|selection| selection.range() == *range)
+            });
+
+        if let Some(range) = found {
+            let mut selection = Selection::new(self.next_selection_id, 0);
+            selection.select(range, false);
+            self.next_selection_id += 1;
+            self.change_selections(cx, |selections| selections.push(selection));
+        }
+    }
+
+    fn cancel(&mut self, _: &Cancel, window: &mut Window, cx: &mut Context<Self>) {
+        let newest = self.newest_selection().clone();
+
+        if self.has_completions() {
+            self.dismiss_completions(cx);
+        } else if self.selections.len() > 1 || !newest.is_empty() {
+            self.change_selections(cx, |selections| {
+                let mut selection = newest;
+
+                selection.collapse_to(selection.head());
+                *selections = vec![selection];
+            });
+        } else if self.search.as_ref().is_some_and(|search| search.visible) {
+            self.dismiss_search(&DismissSearch, window, cx);
+        } else {
+            cx.propagate();
+        }
+    }
+
+    /// Folds the innermost foldable region around the newest cursor.
+    fn fold(&mut self, _: &Fold, _: &mut Window, cx: &mut Context<Self>) {
+        let head = self.newest_selection().head();
+        let head_row = self.buffer.row_for_offset(head);
+        let fold = (0..=head_row).rev().find_map(|row| {
+            fold_range_for_row(&self.buffer, row)
+                .filter(|range| row == head_row || (range.start <= head && head <= range.end))
+        });
+
+        if let Some(fold) = fold {
+            self.insert_fold(fold, cx);
+        }
+    }
+
+    fn unfold(&mut self, _: &Unfold, _: &mut Window, cx: &mut Context<Self>) {
+        let head = self.newest_selection().head();
+        let row = self.buffer.row_for_offset(head);
+
+        if (self.folds.is_folded(&self.buffer, row)
+            && self
+                .folds
+                .remove_containing(self.buffer.row_range(row + 1).start))
+            || self.folds.remove_containing(head)
+        {
+            cx.notify();
+        }
+    }
+
+    fn fold_all(&mut self, _: &FoldAll, _: &mut Window, cx: &mut Context<Self>) {
+        for row in 0..self.buffer.row_count() {
+            if let Some(fold) = fold_range_for_row(&self.buffer, row) {
+                self.folds.insert(fold);
+            }
+        }
+
+        self.folds_changed(cx);
+    }
+
+    fn unfold_all(&mut self, _: &UnfoldAll, _: &mut Window, cx: &mut Context<Self>) {
+        self.folds.clear();
+        cx.notify();
+    }
+
+    fn insert_fold(&mut self, fold: Range<usize>, cx: &mut Context<Self>) {
+        self.folds.insert(fold);
+        self.folds_changed(cx);
+    }
+
+    /// Folds or unfolds the region below `row`, as clicking its gutter
+    /// chevron does.
+    fn toggle_fold_at_row(&mut self, row: usize, cx: &mut Context<Self>) {
+        if self.folds.is_folded(&self.buffer, row) {
+            self.folds
+                .remove_containing(self.buffer.row_range(row + 1).start);
+            cx.notify();
+        } else if let Some(fold) = fold_range_for_row(&self.buffer, row) {
+            self.insert_fold(fold, cx);
+        }
+    }
+
+    /// Moves the selections hidden by a fold to the end of its header row.
+    fn folds_changed(&mut self, cx: &mut Context<Self>) {
+        let selections = self
+            .selections
+            .iter()
+            .map(|selection| {
+                let mut selection = selection.clone();
+                let start = self.clip_folded(selection.start, selection.start);
+                let end = self.clip_folded(selection.end, selection.end);
+
+                selection.select(start..end, selection.reversed);
+                selection
+            })
+            .collect();
+
+        self.change_selections(cx, |current| *current = selections);
+    }
+
+    fn toggle_soft_wrap(&mut self, _: &ToggleSoftWrap, _: &mut Window, cx: &mut Context<Self>) {
+        self.soft_wrap = !self.soft_wrap;
+        self.scroll_position.x = px(0.);
+        self.autoscroll = true;
+        cx.notify();
+    }
+
+    fn move_to_matching_bracket(
+        &mut self,
+        _: &MoveToMatchingBracket,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let highlights = self.highlights.clone();
+
+        self.move_heads(false, cx, |this, selection| {
+            bracket::matching_brackets(this.buffer.text(), &highlights, selection.head())
+                .map_or(selection.head(), |(_, other)| other.start)
+        });
+    }
+
+    fn show_character_palette(
+        &mut self,
+        _: &ShowCharacterPalette,
+        window: &mut Window,
+        _: &mut Context<Self>,
+    ) {
+        window.show_character_palette();
+    }
+
+    fn on_mouse_down(
+        &mut self,
+        event: &MouseDownEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        window.focus(&self.focus_handle);
+        self.dismiss_completions(cx);
+
+        let Some(layout) = self.last_layout.as_ref() else {
+            return;
+        };
+
+        if layout.gutter_bounds.contains(&event.position) {
+            if let Some(row) = layout.row_at_y(event.position.y) {
+                let row = row.row;
+
+                self.toggle_fold_at_row(row, cx);
+            }
+
+            return;
+        }
+
+        let Some(offset) = layout.offset_for_position(event.position) else {
+            return;
+        };
+        let offset = self.clip_folded(offset, offset);
+
+        self.is_selecting = true;
+
+        if event.modifiers.shift {
+            let mut selection = self.newest_selection().clone();
+
+            self.change_selections(cx, |selections| {
+                selection.set_head(offset);
+                *selections = vec![selection];
+            });
+            return;
+        }
+
+        let range = match event.click_count {
+            2 => self.buffer.word_range(offset).unwrap_or(offset..offset),
+            3.. => {
+                let row = self.buffer.row_for_offset(offset);
+                let range = self.buffer.row_range(row);
+
+                range.start..(range.end + 1).min(self.buffer.len())
+            }
+            _ => offset..offset,
+        };
+
+        let mut selection = Selection::new(self.next_selection_id, 0);
+        selection.select(range, false);
+        self.next_selection_id += 1;
+
+        // Alt-clicking adds a cursor instead of replacing the selections.
+        let add = event.modifiers.alt;
+        self.change_selections(cx, |selections| {
+            if !add {
+                selections.clear();
+            }
+
+            selections.push(selection);
+        });
+    }
+
+    fn on_mouse_up(&mut self, _: &MouseUpEvent, _: &mut Window, _: &mut Context<Self>) {
+        self.is_selecting = false;
+    }
+
+    fn on_mouse_move(&mut self, event: &MouseMoveEvent, _: &mut Window, cx: &mut Context<Self>) {
+        if !self.is_selecting {
+            return;
+        }
+
+        let Some(offset) = self
+            .last_layout
+            .as_ref()
+            .and_then(|layout| layout.offset_for_position(event.position))
+        else {
+            return;
+        };
+
+        let id = self.newest_selection().id;
+        let offset = self.clip_folded(offset, offset);
+
+        self.change_selections(cx, |selections| {
+            if let Some(selection) = selections.iter_mut().find(|selection| selection.id == id) {
+                selection.set_head(offset);
+            }
+        });
+    }
+
+    fn on_scroll_wheel(
+        &mut self,
+        event: &ScrollWheelEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(layout) = self.last_layout.as_ref() else {
+            return;
+        };
+
+        let delta = event.delta.pixel_delta(window.line_height());
+        let y = (self.scroll_position.y - delta.y).clamp(px(0.), layout.max_scroll_y());
+        let x = if self.soft_wrap {
+            px(0.)
+        } else {
+            (self.scroll_position.x - delta.x).clamp(px(0.), layout.max_scroll_x())
+        };
+
+        if point(x, y) != self.scroll_position {
+            self.scroll_position = point(x, y);
+            cx.stop_propagation();
+            cx.notify();
+        }
+    }
+
+    fn offset_from_utf16(&self, offset: usize) -> usize {
+        let mut utf8_offset = 0;
+        let mut utf16_count = 0;
+
+        for ch in self.buffer.text().chars() {
+            if utf16_count >= offset {
+                break;
+            }
+
+            utf16_count += ch.len_utf16();
+            utf8_offset += ch.len_utf8();
+        }
+
+        utf8_offset
+    }
+
+    fn offset_to_utf16(&self, offset: usize) -> usize {
+        let mut utf16_offset = 0;
+        let mut utf8_count = 0;
+
+        for ch in self.buffer.text().chars() {
+            if utf8_count >= offset {
+                break;
+            }
+
+            utf8_count += ch.len_utf8();
+            utf16_offset += ch.len_utf16();
+        }
+
+        utf16_offset
+    }
+
+    fn range_to_utf16(&self, range: &Range<usize>) -> Range<usize> {
+        self.offset_to_utf16(range.start)..self.offset_to_utf16(range.end)
+    }
+
+    fn range_from_utf16(&self, range_utf16: &Range<usize>) -> Range<usize> {
+        self.offset_from_utf16(range_utf16.start)..self.offset_from_utf16(range_utf16.end)
+    }
+}
+
+impl EntityInputHandler for Editor {
+    fn text_for_range(
+        &mut self,
+        range_utf16: Range<usize>,
+        actual_range: &mut Option<Range<usize>>,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<String> {
+        let range = self.range_from_utf16(&range_utf16);
+        actual_range.replace(self.range_to_utf16(&range));
+
+        Some(self.buffer.text()[range].to_string())
+    }
+
+    fn selected_text_range(
+        &mut self,
+        _ignore_disabled_input: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<UTF16Selection> {
+        let newest = self.newest_selection();
+
+        Some(UTF16Selection {
+            range: self.range_to_utf16(&newest.range()),
+            reversed: newest.reversed,
+        })
+    }
+
+    fn marked_text_range(
+        &self,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<Range<usize>> {
+        self.marked_range
+            .as_ref()
+            .map(|range| self.range_to_utf16(range))
+    }
+
+    fn unmark_text(&mut self, _window: &mut Window, _cx: &mut Context<Self>) {
+        self.marked_range = None;
+    }
+
+    fn replace_text_in_range(
+        &mut self,
+        range_utf16: Option<Range<usize>>,
+        new_text: &str,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.read_only {
+            return;
+        }
+
+        let new_text = new_text.replace("\r\n", "\n");
+        let range = range_utf16
+            .as_ref()
+            .map(|range_utf16| self.range_from_utf16(range_utf16))
+            .or(self.marked_range.clone());
+
+        match range {
+            Some(range) => {
+                let id = self.newest_selection().id;
+                let inserted = self.edit(vec![(range, new_text)], cx);
+
+                if let Some(inserted) = inserted.first() {
+                    let end = inserted.end;
+
+                    self.change_selections(cx, |selections| {
+                        selections.retain(|selection| selection.id == id);
+
+                        if let Some(selection) = selections.first_mut() {
+                            selection.collapse_to(end);
+                        }
+                    });
+                }
+            }
+            None => {
+                self.insert(&new_text, cx);
+                self.completions_after_typing(&new_text, cx);
+            }
+        }
+    }
+
+    fn replace_and_mark_text_in_range(
+        &mut self,
+        range_utf16: Option<Range<usize>>,
+        new_text: &str,
+        new_selected_range_utf16: Option<Range<usize>>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.read_only {
+            return;
+        }
+
+        let newest = self.newest_selection().clone();
+        let range = range_utf16
+            .as_ref()
+            .map(|range_utf16| self.range_from_utf16(range_utf16))
+            .or(self.marked_range.clone())
+            .unwrap_or(newest.range());
+
+        let Some(inserted) = self
+            .edit(vec![(range.clone(), new_text.to_string())], cx)
+            .pop()
+        else {
+            return;
+        };
+
+        let selected = new_selected_range_utf16
+            .as_ref()
+            .map(|range_utf16| self.range_from_utf16(range_utf16))
+            .map(|new_range| new_range.start + range.start..new_range.end + range.start)
+            .unwrap_or_else(|| inserted.end..inserted.end);
+
+        self.change_selections(cx, |selections| {
+            let mut selection = newest;
+
+            selection.select(selected, false);
+            *selections = vec![selection];
+        });
+        self.marked_range = (!inserted.is_empty()).then_some(inserted);
+    }
+
+    fn bounds_for_range(
+        &mut self,
+        range_utf16: Range<usize>,
+        _bounds: Bounds<Pixels>,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<Bounds<Pixels>> {
+        let layout = self.last_layout.as_ref()?;
+        let range = self.range_from_utf16(&range_utf16);
+        let row = self.buffer.row_for_offset(range.start);
+        let row_range = self.buffer.row_range(row);
+        let start = layout.position_for_offset(row, range.start - row_range.start)?;
+        let end = layout
+            .position_for_offset(row, range.end.min(row_range.end) - row_range.start)
+            .filter(|end| end.y == start.y)
+            .unwrap_or(start);
+
+        Some(Bounds::from_corners(
+            start,
+            point(end.x, start.y + layout.line_height),
+        ))
+    }
+
+    fn character_index_for_point(
+        &mut self,
+        point: Point<Pixels>,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<usize> {
+        let layout = self.last_layout.as_ref()?;
+
+        if !layout.text_bounds.contains(&point) {
+            return None;
+        }
+
+        Some(self.offset_to_utf16(layout.offset_for_position(point)?))
+    }
+}
+
+impl EventEmitter<EditorEvent> for Editor {}
+
+impl Focusable for Editor {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for Editor {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = cx.theme().colors();
+        let focused = self.focus_handle.is_focused(window);
+
+        div()
+            .id(("editor", cx.entity_id()))
+            .v_flex()
+            .size_full()
+            .overflow_hidden()
+            .border_1()
+            .rounded_sm()
+            .bg(colors.background)
+            .text_color(colors.foreground)
+            .border_color(if focused { colors.ring } else { colors.input })
+            .on_action(cx.listener(Self::find))
+            .on_action(cx.listener(Self::select_next_match))
+            .on_action(cx.listener(Self::select_previous_match))
+            .on_action(cx.listener(Self::select_all_matches))
+            .on_action(cx.listener(Self::dismiss_search))
+            .on_action(cx.listener(Self::toggle_case_sensitive))
+            .children(self.render_search_bar(cx))
+            .children(self.render_completions(cx))
+            .child(
+                div()
+                    .id("editor-text")
+                    .key_context(KEY_CONTEXT)
+                    .track_focus(&self.focus_handle)
+                    .flex_1()
+                    .w_full()
+                    .overflow_hidden()
+                    .py_1()
+                    .font_family(FONT_FAMILY)
+                    .text_sm()
+                    .cursor(CursorStyle::IBeam)
+                    .on_action(cx.listener(Self::move_left))
+                    .on_action(cx.listener(Self::move_right))
+                    .on_action(cx.listener(Self::move_up))
+                    .on_action(cx.listener(Self::move_down))
+                    .on_action(cx.listener(Self::select_left))
+                    .on_action(cx.listener(Self::select_right))
+                    .on_action(cx.listener(Self::select_up))
+                    .on_action(cx.listener(Self::select_down))
+                    .on_action(cx.listener(Self::move_to_previous_word_start))
+                    .on_action(cx.listener(Self::move_to_next_word_end))
+                    .on_action(cx.listener(Self::select_to_previous_word_start))
+                    .on_action(cx.listener(Self::select_to_next_word_end))
+                    .on_action(cx.listener(Self::move_to_beginning_of_line))
+                    .on_action(cx.listener(Self::move_to_end_of_line))
+                    .on_action(cx.listener(Self::select_to_beginning_of_line))
+                    .on_action(cx.listener(Self::select_to_end_of_line))
+                    .on_action(cx.listener(Self::move_to_beginning))
+                    .on_action(cx.listener(Self::move_to_end))
+                    .on_action(cx.listener(Self::select_to_beginning))
+                    .on_action(cx.listener(Self::select_to_end))
+                    .on_action(cx.listener(Self::select_all))
+                    .on_action(cx.listener(Self::copy))
+                    .on_action(cx.listener(Self::add_selection_above))
+                    .on_action(cx.listener(Self::add_selection_below))
+                    .on_action(cx.listener(Self::select_next_occurrence))
+                    .on_action(cx.listener(Self::cancel))
+                    .on_action(cx.listener(Self::fold))
+                    .on_action(cx.listener(Self::unfold))
+                    .on_action(cx.listener(Self::fold_all))
+                    .on_action(cx.listener(Self::unfold_all))
+                    .on_action(cx.listener(Self::toggle_soft_wrap))
+                    .on_action(cx.listener(Self::move_to_matching_bracket))
+                    .when(!self.read_only, |this| {
+                        this.on_action(cx.listener(Self::backspace))
+                            .on_action(cx.listener(Self::delete))
+                            .on_action(cx.listener(Self::delete_to_previous_word_start))
+                            .on_action(cx.listener(Self::delete_to_next_word_end))
+                            .on_action(cx.listener(Self::newline))
+                            .on_action(cx.listener(Self::tab))
+                            .on_action(cx.listener(Self::backtab))
+                            .on_action(cx.listener(Self::cut))
+                            .on_action(cx.listener(Self::paste))
+                            .on_action(cx.listener(Self::undo))
+                            .on_action(cx.listener(Self::redo))
+                            .on_action(cx.listener(Self::show_character_palette))
+                            .on_action(cx.listener(Self::show_completions))
+                    })
+                    .on_mouse_down(MouseButton::Left, cx.listener(Self::on_mouse_down))
+                    .on_mouse_up(MouseButton::Left, cx.listener(Self::on_mouse_up))
+                    .on_mouse_up_out(MouseButton::Left, cx.listener(Self::on_mouse_up))
+                    .on_mouse_move(cx.listener(Self::on_mouse_move))
+                    .on_scroll_wheel(cx.listener(Self::on_scroll_wheel))
+                    .child(EditorElement::new(cx.entity())),
+            )
+    }
+}
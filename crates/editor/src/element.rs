@@ -0,0 +1,812 @@
+use std::ops::Range;
+
+use gpui::{
+    App, Bounds, ContentMask, Element, ElementId, ElementInputHandler, Entity, Font,
+    GlobalElementId, Hsla, InspectorElementId, IntoElement, LayoutId, PaintQuad, Pixels, Point,
+    ShapedLine, SharedString, Style, TextAlign, TextRun, TransformationMatrix, UnderlineStyle,
+    Window, WrappedLine, fill, point, px, relative, size,
+};
+use theme::{ActiveTheme, syntax::SyntaxTheme};
+use ui::components::icon::IconName;
+
+use crate::{Diagnostic, Editor, bracket, fold_map::fold_range_for_row, language::HighlightSpan};
+
+const FOLD_INDICATOR_WIDTH: Pixels = px(16.);
+const GUTTER_PADDING: Pixels = px(8.);
+
+/// A buffer row shaped during the last paint.
+pub struct LayoutRow {
+    pub row: usize,
+    pub range: Range<usize>,
+    /// The distance from the top of the content to the row.
+    pub top: Pixels,
+    pub line: WrappedLine,
+}
+
+impl LayoutRow {
+    pub fn height(&self, line_height: Pixels) -> Pixels {
+        line_height * (self.line.wrap_boundaries.len() + 1) as f32
+    }
+
+    /// The offset closest to `position`, relative to the top left of the row.
+    pub fn offset_for_position(&self, position: Point<Pixels>, line_height: Pixels) -> usize {
+        let y = position.y.clamp(px(0.), self.height(line_height) - px(1.));
+        let index = self
+            .line
+            .closest_index_for_position(point(position.x, y), line_height)
+            .unwrap_or_else(|index| index);
+
+        self.range.start + index
+    }
+
+    /// The offsets at which each wrapped line of the row starts and ends.
+    fn segments(&self) -> Vec<Range<usize>> {
+        let layout = &self.line.unwrapped_layout;
+        let mut start = 0;
+        let mut segments = Vec::with_capacity(self.line.wrap_boundaries.len() + 1);
+
+        for boundary in &self.line.wrap_boundaries {
+            let end = layout.runs[boundary.run_ix].glyphs[boundary.glyph_ix].index;
+
+            segments.push(start..end);
+            start = end;
+        }
+
+        segments.push(start..self.line.len());
+        segments
+    }
+
+    /// The rectangles covering `columns` of the row, one per wrapped line.
+    fn range_bounds(
+        &self,
+        columns: Range<usize>,
+        origin: Point<Pixels>,
+        line_height: Pixels,
+        trailing: Pixels,
+    ) -> Vec<Bounds<Pixels>> {
+        let layout = &self.line.unwrapped_layout;
+        let segments = self.segments();
+        let last = segments.len() - 1;
+
+        segments
+            .into_iter()
+            .enumerate()
+            .filter(|(_, segment)| columns.start <= segment.end && segment.start <= columns.end)
+            .filter_map(|(index, segment)| {
+                let start = columns.start.max(segment.start);
+                let end = columns.end.min(segment.end);
+                let trailing = if index == last { trailing } else { px(0.) };
+
+                if start == end && trailing == px(0.) && start != columns.start {
+                    return None;
+                }
+
+                let left = layout.x_for_index(segment.start);
+                let top = origin.y + self.top + line_height * index as f32;
+
+                Some(Bounds::from_corners(
+                    point(origin.x + layout.x_for_index(start) - left, top),
+                    point(
+                        origin.x + layout.x_for_index(end) - left + trailing,
+                        top + line_height,
+                    ),
+                ))
+            })
+            .collect()
+    }
+}
+
+/// The layout of the last paint, used to map between offsets and positions.
+pub struct LastLayout {
+    pub text_bounds: Bounds<Pixels>,
+    pub gutter_bounds: Bounds<Pixels>,
+    pub line_height: Pixels,
+    pub scroll_position: Point<Pixels>,
+    pub content_size: gpui::Size<Pixels>,
+    pub rows: Vec<LayoutRow>,
+}
+
+impl LastLayout {
+    /// The position of the top left of the content, accounting for scrolling.
+    pub fn origin(&self) -> Point<Pixels> {
+        self.text_bounds.origin - self.scroll_position
+    }
+
+    pub fn row(&self, row: usize) -> Option<&LayoutRow> {
+        self.rows
+            .binary_search_by_key(&row, |layout| layout.row)
+            .ok()
+            .map(|index| &self.rows[index])
+    }
+
+    /// The shaped row at `y`, clamped to the first and last ones.
+    pub fn row_at_y(&self, y: Pixels) -> Option<&LayoutRow> {
+        let y = y - self.origin().y;
+        let index = self
+            .rows
+            .partition_point(|row| row.top + row.height(self.line_height) <= y);
+
+        self.rows.get(index).or(self.rows.last())
+    }
+
+    pub fn offset_for_position(&self, position: Point<Pixels>) -> Option<usize> {
+        let row = self.row_at_y(position.y)?;
+        let origin = self.origin();
+
+        Some(row.offset_for_position(
+            point(position.x - origin.x, position.y - origin.y - row.top),
+            self.line_height,
+        ))
+    }
+
+    /// The top left of the character at `column` of `row`, in window
+    /// coordinates.
+    pub fn position_for_offset(&self, row: usize, column: usize) -> Option<Point<Pixels>> {
+        let layout = self.row(row)?;
+        let position = layout.line.position_for_index(column, self.line_height)?;
+
+        Some(self.origin() + point(position.x, layout.top + position.y))
+    }
+
+    pub fn max_scroll_y(&self) -> Pixels {
+        (self.content_size.height - self.text_bounds.size.height).max(px(0.))
+    }
+
+    pub fn max_scroll_x(&self) -> Pixels {
+        (self.content_size.width - self.text_bounds.size.width).max(px(0.))
+    }
+}
+
+pub struct EditorElement {
+    editor: Entity<Editor>,
+}
+
+impl EditorElement {
+    pub fn new(editor: Entity<Editor>) -> Self {
+        Self { editor }
+    }
+}
+
+struct GutterLine {
+    line: ShapedLine,
+    origin: Point<Pixels>,
+}
+
+struct FoldIndicator {
+    bounds: Bounds<Pixels>,
+    icon: IconName,
+}
+
+pub struct PrepaintState {
+    layout: Option<LastLayout>,
+    highlights: Vec<PaintQuad>,
+    cursors: Vec<PaintQuad>,
+    gutter: Vec<GutterLine>,
+    fold_indicators: Vec<FoldIndicator>,
+    fold_placeholders: Vec<(PaintQuad, GutterLine)>,
+    placeholder: Option<GutterLine>,
+}
+
+impl IntoElement for EditorElement {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+/// Builds the text runs of `range` from the syntax highlights, underlining
+/// the part of it that is being composed and, with a wavy line, the
+/// diagnostics.
+#[allow(clippy::too_many_arguments)]
+fn runs_for_row(
+    range: &Range<usize>,
+    highlights: &[HighlightSpan],
+    marked_range: Option<&Range<usize>>,
+    diagnostics: &[Diagnostic],
+    diagnostic_color: Hsla,
+    font: &Font,
+    color: Hsla,
+    syntax: &SyntaxTheme,
+) -> Vec<TextRun> {
+    let first = highlights.partition_point(|span| span.range.end <= range.start);
+    let spans = highlights[first..]
+        .iter()
+        .take_while(|span| span.range.start < range.end);
+
+    let mut boundaries = vec![range.start, range.end];
+    for span in spans.clone() {
+        boundaries.push(span.range.start.clamp(range.start, range.end));
+        boundaries.push(span.range.end.clamp(range.start, range.end));
+    }
+    if let Some(marked) = marked_range {
+        boundaries.push(marked.start.clamp(range.start, range.end));
+        boundaries.push(marked.end.clamp(range.start, range.end));
+    }
+    let diagnostics = diagnostics
+        .iter()
+        .filter(|diagnostic| {
+            diagnostic.range.start < range.end && diagnostic.range.end > range.start
+        })
+        .collect::<Vec<_>>();
+    for diagnostic in &diagnostics {
+        boundaries.push(diagnostic.range.start.clamp(range.start, range.end));
+        boundaries.push(diagnostic.range.end.clamp(range.start, range.end));
+    }
+    boundaries.sort_unstable();
+    boundaries.dedup();
+
+    let mut spans = spans.peekable();
+    boundaries
+        .windows(2)
+        .map(|window| {
+            let (start, end) = (window[0], window[1]);
+
+            while spans.next_if(|span| span.range.end <= start).is_some() {}
+
+            let style = spans
+                .peek()
+                .filter(|span| span.range.start <= start)
+                .and_then(|span| syntax.get(span.name))
+                .unwrap_or_default();
+            let marked =
+                marked_range.is_some_and(|marked| marked.start <= start && end <= marked.end);
+            let diagnosed = diagnostics
+                .iter()
+                .any(|diagnostic| diagnostic.range.start <= start && end <= diagnostic.range.end);
+            let mut font = font.clone();
+
+            if let Some(weight) = style.font_weight {
+                font.weight = weight;
+            }
+
+            if let Some(style) = style.font_style {
+                font.style = style;
+            }
+
+            let color = style.color.unwrap_or(color);
+
+            TextRun {
+                len: end - start,
+                font,
+                color,
+                background_color: None,
+                underline: if marked {
+                    Some(UnderlineStyle {
+                        color: Some(color),
+                        thickness: px(1.),
+                        wavy: false,
+                    })
+                } else {
+                    diagnosed.then_some(UnderlineStyle {
+                        color: Some(diagnostic_color),
+                        thickness: px(1.),
+                        wavy: true,
+                    })
+                },
+                strikethrough: None,
+            }
+        })
+        .collect()
+}
+
+impl Element for EditorElement {
+    type RequestLayoutState = ();
+    type PrepaintState = PrepaintState;
+
+    fn id(&self) -> Option<ElementId> {
+        None
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let mut style = Style::default();
+        style.size.width = relative(1.).into();
+        style.size.height = relative(1.).into();
+
+        (window.request_layout(style, [], cx), ())
+    }
+
+    fn prepaint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        _request_layout: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Self::PrepaintState {
+        let colors = *cx.theme().colors();
+        let syntax = cx.theme().syntax().clone();
+        let editor = self.editor.read(cx);
+        let buffer = &editor.buffer;
+        let style = window.text_style();
+        let font = style.font();
+        let font_size = style.font_size.to_pixels(window.rem_size());
+        let line_height = window.line_height();
+        let text_system = window.text_system().clone();
+        let plain_run = |len: usize, color: Hsla| TextRun {
+            len,
+            font: font.clone(),
+            color,
+            background_color: None,
+            underline: None,
+            strikethrough: None,
+        };
+
+        let digits = buffer.row_count().to_string().len().max(2);
+        let number_width = if editor.show_line_numbers {
+            text_system
+                .shape_line(
+                    "0".repeat(digits).into(),
+                    font_size,
+                    &[plain_run(digits, colors.muted_foreground)],
+                    None,
+                )
+                .width
+                + GUTTER_PADDING
+        } else {
+            px(0.)
+        };
+        let gutter_width = number_width + FOLD_INDICATOR_WIDTH + px(4.);
+        let gutter_bounds = Bounds::new(bounds.origin, size(gutter_width, bounds.size.height));
+        let text_bounds = Bounds::from_corners(
+            point(bounds.left() + gutter_width, bounds.top()),
+            bounds.bottom_right(),
+        );
+        let wrap_width = editor
+            .soft_wrap
+            .then(|| (text_bounds.size.width - px(4.)).max(px(40.)));
+
+        let shape = |row: usize, top: Pixels| {
+            let range = buffer.row_range(row);
+            let runs = runs_for_row(
+                &range,
+                &editor.highlights,
+                editor.marked_range.as_ref(),
+                &editor.diagnostics,
+                colors.danger,
+                &font,
+                style.color,
+                &syntax,
+            );
+            let line = text_system
+                .shape_text(
+                    SharedString::new(&buffer.text()[range.clone()]),
+                    font_size,
+                    &runs,
+                    wrap_width,
+                    None,
+                )
+                .ok()
+                .and_then(|mut lines| lines.pop())
+                .unwrap_or_default();
+
+            LayoutRow {
+                row,
+                range,
+                top,
+                line,
+            }
+        };
+
+        let visible_rows = editor.folds.visible_rows(buffer);
+        let newest = editor.newest_selection();
+        let head = newest.head();
+        let head_row = buffer.row_for_offset(head);
+        let head_column = head - buffer.row_range(head_row).start;
+        let viewport = text_bounds.size;
+        let mut scroll = editor.scroll_position;
+
+        // With soft wrap on, row heights are only known once shaped, so every
+        // visible row is shaped. Otherwise each row is one line high and only
+        // the rows in view are.
+        let (mut rows, content_height) = if editor.soft_wrap {
+            let mut top = px(0.);
+            let rows = visible_rows
+                .iter()
+                .map(|row| {
+                    let row = shape(*row, top);
+
+                    top += row.height(line_height);
+                    row
+                })
+                .collect::<Vec<_>>();
+
+            (rows, top)
+        } else {
+            (Vec::new(), line_height * visible_rows.len() as f32)
+        };
+
+        if editor.autoscroll {
+            let cursor_top = if editor.soft_wrap {
+                rows.iter().find(|row| row.row == head_row).and_then(|row| {
+                    let position = row.line.position_for_index(head_column, line_height)?;
+
+                    Some(row.top + position.y)
+                })
+            } else {
+                visible_rows
+                    .binary_search(&head_row)
+                    .ok()
+                    .map(|index| line_height * index as f32)
+            };
+
+            if let Some(top) = cursor_top {
+                if top < scroll.y {
+                    scroll.y = top;
+                } else if top + line_height > scroll.y + viewport.height {
+                    scroll.y = top + line_height - viewport.height;
+                }
+            }
+        }
+
+        scroll.y = scroll
+            .y
+            .clamp(px(0.), (content_height - viewport.height).max(px(0.)));
+
+        if !editor.soft_wrap {
+            let first = (scroll.y / line_height).floor() as usize;
+            let last = ((scroll.y + viewport.height) / line_height).ceil() as usize;
+
+            rows = visible_rows
+                .iter()
+                .enumerate()
+                .skip(first)
+                .take(last.saturating_sub(first) + 1)
+                .map(|(index, row)| shape(*row, line_height * index as f32))
+                .collect();
+        }
+
+        let content_width = if editor.soft_wrap {
+            viewport.width
+        } else {
+            rows.iter()
+                .map(|row| row.line.width() + px(8.))
+                .fold(viewport.width, Pixels::max)
+        };
+
+        if editor.soft_wrap {
+            scroll.x = px(0.);
+        } else {
+            if editor.autoscroll
+                && let Ok(index) = rows.binary_search_by_key(&head_row, |row| row.row)
+            {
+                let x = rows[index].line.unwrapped_layout.x_for_index(head_column);
+                let margin = px(16.);
+
+                if x < scroll.x {
+                    scroll.x = (x - margin).max(px(0.));
+                } else if x + margin > scroll.x + viewport.width {
+                    scroll.x = x + margin - viewport.width;
+                }
+            }
+
+            scroll.x = scroll
+                .x
+                .clamp(px(0.), (content_width - viewport.width).max(px(0.)));
+        }
+
+        let origin = text_bounds.origin - scroll;
+        let in_view = |row: &&LayoutRow| {
+            row.top + row.height(line_height) >= scroll.y && row.top <= scroll.y + viewport.height
+        };
+        let range_quads = |range: &Range<usize>, color: Hsla, trailing: bool| {
+            let first_row = buffer.row_for_offset(range.start);
+            let last_row = buffer.row_for_offset(range.end);
+
+            rows.iter()
+                .filter(in_view)
+                .filter(move |row| first_row <= row.row && row.row <= last_row)
+                .flat_map(move |row| {
+                    let columns = range.start.max(row.range.start) - row.range.start
+                        ..range.end.min(row.range.end) - row.range.start;
+                    let trailing = if trailing && range.end > row.range.end {
+                        px(4.)
+                    } else {
+                        px(0.)
+                    };
+
+                    row.range_bounds(columns, origin, line_height, trailing)
+                })
+                .map(|bounds| fill(bounds, color))
+                .collect::<Vec<_>>()
+        };
+
+        let mut highlights = Vec::new();
+        let (matches, active_match) = editor.search_matches();
+        let mut shown = rows.iter().filter(in_view).map(|row| row.range.clone());
+        let shown = shown
+            .next()
+            .map(|first| first.start..shown.next_back().unwrap_or(first).end)
+            .unwrap_or_default();
+        let first_match = matches.partition_point(|range| range.end < shown.start);
+        for (index, range) in matches
+            .iter()
+            .enumerate()
+            .skip(first_match)
+            .take_while(|(_, range)| range.start <= shown.end)
+        {
+            let color = if Some(index) == active_match {
+                colors.warning.opacity(0.6)
+            } else {
+                colors.warning.opacity(0.25)
+            };
+
+            highlights.extend(range_quads(range, color, false));
+        }
+
+        for selection in &editor.selections {
+            if !selection.is_empty() {
+                highlights.extend(range_quads(&selection.range(), colors.selection, true));
+            }
+        }
+
+        if newest.is_empty()
+            && let Some((bracket, other)) =
+                bracket::matching_brackets(buffer.text(), &editor.highlights, head)
+        {
+            for range in [bracket, other] {
+                highlights.extend(range_quads(&range, colors.accent, false));
+            }
+        }
+
+        let cursors = editor
+            .selections
+            .iter()
+            .filter_map(|selection| {
+                let head = selection.head();
+                let row = buffer.row_for_offset(head);
+                let layout = rows.iter().find(|layout| layout.row == row)?;
+                let position = layout
+                    .line
+                    .position_for_index(head - layout.range.start, line_height)?;
+
+                Some(fill(
+                    Bounds::new(
+                        point(origin.x + position.x, origin.y + layout.top + position.y),
+                        size(px(1.5), line_height),
+                    ),
+                    colors.caret,
+                ))
+            })
+            .collect();
+
+        let cursor_rows = editor
+            .selections
+            .iter()
+            .map(|selection| buffer.row_for_offset(selection.head()))
+            .collect::<Vec<_>>();
+        let mut gutter = Vec::new();
+        let mut fold_indicators = Vec::new();
+        let mut fold_placeholders = Vec::new();
+
+        for row in rows.iter().filter(in_view) {
+            let top = origin.y + row.top;
+
+            if editor.show_line_numbers {
+                let number = (row.row + 1).to_string();
+                let color = if cursor_rows.contains(&row.row) {
+                    colors.foreground
+                } else {
+                    colors.muted_foreground
+                };
+                let line = text_system.shape_line(
+                    number.clone().into(),
+                    font_size,
+                    &[plain_run(number.len(), color)],
+                    None,
+                );
+
+                gutter.push(GutterLine {
+                    origin: point(
+                        gutter_bounds.left() + number_width - GUTTER_PADDING / 2. - line.width,
+                        top,
+                    ),
+                    line,
+                });
+            }
+
+            let folded = editor.folds.is_folded(buffer, row.row);
+            if folded || fold_range_for_row(buffer, row.row).is_some() {
+                let icon_size = px(12.);
+
+                fold_indicators.push(FoldIndicator {
+                    bounds: Bounds::new(
+                        point(
+                            gutter_bounds.left()
+                                + number_width
+                                + (FOLD_INDICATOR_WIDTH - icon_size) / 2.,
+                            top + (line_height - icon_size) / 2.,
+                        ),
+                        size(icon_size, icon_size),
+                    ),
+                    icon: if folded {
+                        IconName::ChevronRight
+                    } else {
+                        IconName::ChevronDown
+                    },
+                });
+            }
+
+            if folded {
+                let line = text_system.shape_line(
+                    "⋯".into(),
+                    font_size,
+                    &[plain_run("⋯".len(), colors.muted_foreground)],
+                    None,
+                );
+                let height = row.height(line_height);
+                let left = origin.x
+                    + row
+                        .line
+                        .position_for_index(row.line.len(), line_height)
+                        .map_or(px(0.), |position| position.x)
+                    + px(6.);
+                let top = top + height - line_height;
+                let badge = Bounds::new(
+                    point(left - px(3.), top + px(2.)),
+                    size(line.width + px(6.), line_height - px(4.)),
+                );
+
+                fold_placeholders.push((
+                    fill(badge, colors.muted).corner_radii(px(3.)),
+                    GutterLine {
+                        origin: point(left, top),
+                        line,
+                    },
+                ));
+            }
+        }
+
+        let placeholder =
+            (buffer.len() == 0 && !editor.placeholder.is_empty()).then(|| GutterLine {
+                line: text_system.shape_line(
+                    editor.placeholder.clone(),
+                    font_size,
+                    &[plain_run(editor.placeholder.len(), colors.muted_foreground)],
+                    None,
+                ),
+                origin,
+            });
+
+        PrepaintState {
+            layout: Some(LastLayout {
+                text_bounds,
+                gutter_bounds,
+                line_height,
+                scroll_position: scroll,
+                content_size: size(content_width, content_height),
+                rows,
+            }),
+            highlights,
+            cursors,
+            gutter,
+            fold_indicators,
+            fold_placeholders,
+            placeholder,
+        }
+    }
+
+    fn paint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        _request_layout: &mut Self::RequestLayoutState,
+        prepaint: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let Some(layout) = prepaint.layout.take() else {
+            return;
+        };
+
+        let colors = *cx.theme().colors();
+        let editor = self.editor.read(cx);
+        let focus_handle = editor.focus_handle.clone();
+
+        if !editor.read_only {
+            window.handle_input(
+                &focus_handle,
+                ElementInputHandler::new(layout.text_bounds, self.editor.clone()),
+                cx,
+            );
+        }
+
+        let line_height = layout.line_height;
+        let origin = layout.origin();
+
+        window.with_content_mask(Some(ContentMask { bounds }), |window| {
+            window.with_content_mask(
+                Some(ContentMask {
+                    bounds: layout.gutter_bounds,
+                }),
+                |window| {
+                    for number in &prepaint.gutter {
+                        number
+                            .line
+                            .paint(number.origin, line_height, window, cx)
+                            .ok();
+                    }
+
+                    for indicator in &prepaint.fold_indicators {
+                        window
+                            .paint_svg(
+                                indicator.bounds,
+                                indicator.icon.path(),
+                                TransformationMatrix::unit(),
+                                colors.muted_foreground,
+                                cx,
+                            )
+                            .ok();
+                    }
+                },
+            );
+
+            window.with_content_mask(
+                Some(ContentMask {
+                    bounds: layout.text_bounds,
+                }),
+                |window| {
+                    for quad in prepaint.highlights.drain(..) {
+                        window.paint_quad(quad);
+                    }
+
+                    if let Some(placeholder) = prepaint.placeholder.as_ref() {
+                        placeholder
+                            .line
+                            .paint(placeholder.origin, line_height, window, cx)
+                            .ok();
+                    }
+
+                    let visible = layout.text_bounds;
+                    for row in &layout.rows {
+                        let top = origin.y + row.top;
+
+                        if top + row.height(line_height) < visible.top() || top > visible.bottom() {
+                            continue;
+                        }
+
+                        row.line
+                            .paint(
+                                point(origin.x, top),
+                                line_height,
+                                TextAlign::Left,
+                                None,
+                                window,
+                                cx,
+                            )
+                            .ok();
+                    }
+
+                    for (badge, label) in prepaint.fold_placeholders.drain(..) {
+                        window.paint_quad(badge);
+                        label.line.paint(label.origin, line_height, window, cx).ok();
+                    }
+
+                    if focus_handle.is_focused(window) {
+                        for cursor in prepaint.cursors.drain(..) {
+                            window.paint_quad(cursor);
+                        }
+                    }
+                },
+            );
+        });
+
+        self.editor.update(cx, |editor, _cx| {
+            editor.scroll_position = layout.scroll_position;
+            editor.autoscroll = false;
+            editor.last_layout = Some(layout);
+        });
+    }
+}
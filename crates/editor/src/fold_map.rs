@@ -0,0 +1,185 @@
+use std::ops::Range;
+
+use crate::buffer::Buffer;
+
+/// Folded regions, each hiding the whole rows below a header row.
+///
+/// Folds are kept as the byte ranges they hide, from the start of the first
+/// hidden row to the end of the last one, so that edits can shift them.
+#[derive(Default)]
+pub struct FoldMap {
+    folds: Vec<Range<usize>>,
+}
+
+impl FoldMap {
+    pub fn clear(&mut self) {
+        self.folds.clear();
+    }
+
+    pub fn insert(&mut self, range: Range<usize>) {
+        if self
+            .folds
+            .iter()
+            .any(|fold| fold.start <= range.start && range.end <= fold.end)
+        {
+            return;
+        }
+
+        self.folds
+            .retain(|fold| !(range.start <= fold.start && fold.end <= range.end));
+
+        let index = self.folds.partition_point(|fold| fold.start < range.start);
+        self.folds.insert(index, range);
+    }
+
+    /// Removes the folds hiding `offset`, returning whether there were any.
+    pub fn remove_containing(&mut self, offset: usize) -> bool {
+        let len = self.folds.len();
+
+        self.folds
+            .retain(|fold| !(fold.start <= offset && offset <= fold.end));
+        self.folds.len() != len
+    }
+
+    /// The fold hiding `offset`, if any.
+    pub fn fold_containing(&self, offset: usize) -> Option<Range<usize>> {
+        self.folds
+            .iter()
+            .find(|fold| fold.start <= offset && offset <= fold.end)
+            .cloned()
+    }
+
+    /// Whether the fold starting right after `row` is folded.
+    pub fn is_folded(&self, buffer: &Buffer, row: usize) -> bool {
+        row + 1 < buffer.row_count() && {
+            let start = buffer.row_range(row + 1).start;
+
+            self.folds.iter().any(|fold| fold.start == start)
+        }
+    }
+
+    /// Drops the folds touched by `edits` and shifts the ones after them.
+    pub fn edit(&mut self, edits: &[(Range<usize>, String)]) {
+        let mut folds = Vec::with_capacity(self.folds.len());
+
+        'folds: for fold in self.folds.drain(..) {
+            let mut delta = 0isize;
+
+            for (range, text) in edits {
+                if range.start <= fold.end && fold.start <= range.end {
+                    continue 'folds;
+                }
+
+                if range.end < fold.start {
+                    delta += text.len() as isize - range.len() as isize;
+                }
+            }
+
+            folds
+                .push((fold.start as isize + delta) as usize..(fold.end as isize + delta) as usize);
+        }
+
+        self.folds = folds;
+    }
+
+    /// The rows that are not hidden by a fold, in order.
+    pub fn visible_rows(&self, buffer: &Buffer) -> Vec<usize> {
+        let mut rows = Vec::with_capacity(buffer.row_count());
+        let mut folds = self.folds.iter().peekable();
+        let mut row = 0;
+
+        while row < buffer.row_count() {
+            let start = buffer.row_range(row).start;
+
+            while folds.next_if(|fold| fold.end < start).is_some() {}
+
+            match folds.peek() {
+                Some(fold) if fold.start <= start => {
+                    row = buffer.row_for_offset(fold.end) + 1;
+                }
+                _ => {
+                    rows.push(row);
+                    row += 1;
+                }
+            }
+        }
+
+        rows
+    }
+}
+
+/// The rows below `row` indented deeper than it, which folding it hides.
+pub fn fold_range_for_row(buffer: &Buffer, row: usize) -> Option<Range<usize>> {
+    if buffer.is_blank(row) {
+        return None;
+    }
+
+    let indent = buffer.indent(row).len();
+    let mut last = row;
+
+    for next in row + 1..buffer.row_count() {
+        if buffer.is_blank(next) {
+            continue;
+        }
+
+        if buffer.indent(next).len() > indent {
+            last = next;
+        } else {
+            break;
+        }
+    }
+
+    (last > row).then(|| buffer.row_range(row + 1).start..buffer.row_range(last).end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "a {\n  b {\n    c\n  }\n}\nd";
+
+    #[test]
+    fn folds_the_rows_indented_below() {
+        let buffer = Buffer::new(TEXT.into());
+
+        assert_eq!(fold_range_for_row(&buffer, 0), Some(4..19));
+        assert_eq!(fold_range_for_row(&buffer, 1), Some(10..15));
+        assert_eq!(fold_range_for_row(&buffer, 2), None);
+        assert_eq!(fold_range_for_row(&buffer, 5), None);
+    }
+
+    #[test]
+    fn hides_folded_rows() {
+        let buffer = Buffer::new(TEXT.into());
+        let mut folds = FoldMap::default();
+
+        folds.insert(10..15);
+        assert_eq!(folds.visible_rows(&buffer), [0, 1, 3, 4, 5]);
+        assert!(folds.is_folded(&buffer, 1));
+
+        // An enclosing fold replaces the ones it contains.
+        folds.insert(4..19);
+        folds.insert(10..15);
+        assert_eq!(folds.visible_rows(&buffer), [0, 4, 5]);
+        assert_eq!(folds.fold_containing(12), Some(4..19));
+
+        assert!(folds.remove_containing(12));
+        assert!(!folds.remove_containing(12));
+        assert_eq!(folds.visible_rows(&buffer), [0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn shifts_folds_after_edits_and_drops_the_ones_touched() {
+        let mut folds = FoldMap::default();
+        folds.insert(4..19);
+
+        folds.edit(&[(0..1, "abc".to_string())]);
+        assert_eq!(folds.fold_containing(6), Some(6..21));
+
+        folds.edit(&[(30..31, String::new())]);
+        assert_eq!(folds.fold_containing(6), Some(6..21));
+
+        folds.edit(&[(10..11, String::new())]);
+        assert_eq!(folds.fold_containing(6), None);
+    }
+}
@@ -0,0 +1,136 @@
+use std::time::{Duration, Instant};
+
+use crate::selection::Selection;
+
+/// Edits closer than this are undone and redone together.
+const GROUP_INTERVAL: Duration = Duration::from_millis(300);
+const MAX_ENTRIES: usize = 200;
+
+#[derive(Clone)]
+pub struct Snapshot {
+    pub text: String,
+    pub selections: Vec<Selection>,
+}
+
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    last_push: Option<Instant>,
+}
+
+impl History {
+    /// Records the state preceding an edit.
+    pub fn push(&mut self, snapshot: impl FnOnce() -> Snapshot) {
+        let now = Instant::now();
+        let grouped = self
+            .last_push
+            .is_some_and(|last_push| now.duration_since(last_push) < GROUP_INTERVAL);
+
+        self.last_push = Some(now);
+        self.redo.clear();
+
+        if grouped && !self.undo.is_empty() {
+            return;
+        }
+
+        if self.undo.len() == MAX_ENTRIES {
+            self.undo.remove(0);
+        }
+
+        self.undo.push(snapshot());
+    }
+
+    /// Ends the current group, so that the next edit starts a new undo step.
+    pub fn break_group(&mut self) {
+        self.last_push = None;
+    }
+
+    pub fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let snapshot = self.undo.pop()?;
+
+        self.redo.push(current);
+        self.last_push = None;
+
+        Some(snapshot)
+    }
+
+    pub fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let snapshot = self.redo.pop()?;
+
+        self.undo.push(current);
+        self.last_push = None;
+
+        Some(snapshot)
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.last_push = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(text: &str) -> Snapshot {
+        Snapshot {
+            text: text.into(),
+            selections: vec![Selection::new(0, text.len())],
+        }
+    }
+
+    #[test]
+    fn groups_quick_edits_until_broken() {
+        let mut history = History::default();
+
+        history.push(|| snapshot(""));
+        history.push(|| snapshot("a"));
+        history.break_group();
+        history.push(|| snapshot("ab"));
+
+        let undone = history.undo(snapshot("abc")).unwrap();
+        assert_eq!(undone.text, "ab");
+        let undone = history.undo(undone).unwrap();
+        assert_eq!(undone.text, "");
+        assert!(history.undo(undone).is_none());
+    }
+
+    #[test]
+    fn redoes_until_the_next_edit() {
+        let mut history = History::default();
+        history.push(|| snapshot(""));
+
+        let undone = history.undo(snapshot("a")).unwrap();
+        let redone = history.redo(undone).unwrap();
+        assert_eq!(redone.text, "a");
+
+        let undone = history.undo(redone).unwrap();
+        history.push(|| undone);
+        assert!(history.redo(snapshot("b")).is_none());
+
+        history.clear();
+        assert!(history.undo(snapshot("b")).is_none());
+    }
+
+    #[test]
+    fn forgets_the_oldest_edits() {
+        let mut history = History::default();
+
+        for index in 0..=MAX_ENTRIES {
+            history.break_group();
+            history.push(|| snapshot(&index.to_string()));
+        }
+
+        let mut current = snapshot("last");
+        let mut undone = 0;
+        while let Some(snapshot) = history.undo(current.clone()) {
+            current = snapshot;
+            undone += 1;
+        }
+        assert_eq!(undone, MAX_ENTRIES);
+        assert_eq!(current.text, "1");
+    }
+}
@@ -0,0 +1,247 @@
+mod graphql;
+mod xml;
+
+use std::{borrow::Cow, cmp::Reverse, collections::BTreeSet, ops::Range, sync::LazyLock};
+
+use streaming_iterator::StreamingIterator;
+use tree_sitter::{Parser, Query, QueryCursor};
+
+/// A span of text styled by the theme's syntax color named `name`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighlightSpan {
+    pub range: Range<usize>,
+    pub name: &'static str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    Json,
+    /// Highlighted with the HTML grammar, no XML one being bundled, apart
+    /// from processing instructions and CDATA sections.
+    Xml,
+    Html,
+    /// Highlighted by a tokenizer, no GraphQL grammar being bundled, so
+    /// that names are told apart by their case and what follows them.
+    GraphQl,
+    Yaml,
+    JavaScript,
+}
+
+struct Grammar {
+    language: tree_sitter::Language,
+    query: Query,
+    /// Whether later patterns override earlier ones capturing the same node,
+    /// which some bundled queries assume instead of the opposite.
+    later_patterns_win: bool,
+}
+
+impl Grammar {
+    fn new(language: tree_sitter::Language, highlights: &str) -> Self {
+        let query = Query::new(&language, highlights).expect("invalid highlights query");
+
+        Self {
+            language,
+            query,
+            later_patterns_win: false,
+        }
+    }
+
+    fn later_patterns_win(mut self) -> Self {
+        self.later_patterns_win = true;
+        self
+    }
+}
+
+static JSON: LazyLock<Grammar> = LazyLock::new(|| {
+    Grammar::new(
+        tree_sitter_json::LANGUAGE.into(),
+        tree_sitter_json::HIGHLIGHTS_QUERY,
+    )
+});
+
+static HTML: LazyLock<Grammar> = LazyLock::new(|| {
+    Grammar::new(
+        tree_sitter_html::LANGUAGE.into(),
+        tree_sitter_html::HIGHLIGHTS_QUERY,
+    )
+});
+
+static YAML: LazyLock<Grammar> = LazyLock::new(|| {
+    Grammar::new(
+        tree_sitter_yaml::LANGUAGE.into(),
+        tree_sitter_yaml::HIGHLIGHTS_QUERY,
+    )
+    .later_patterns_win()
+});
+
+static JAVASCRIPT: LazyLock<Grammar> = LazyLock::new(|| {
+    Grammar::new(
+        tree_sitter_javascript::LANGUAGE.into(),
+        tree_sitter_javascript::HIGHLIGHT_QUERY,
+    )
+});
+
+impl Language {
+    pub const ALL: [Language; 6] = [
+        Language::Json,
+        Language::Xml,
+        Language::Html,
+        Language::GraphQl,
+        Language::Yaml,
+        Language::JavaScript,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::Json => "JSON",
+            Language::Xml => "XML",
+            Language::Html => "HTML",
+            Language::GraphQl => "GraphQL",
+            Language::Yaml => "YAML",
+            Language::JavaScript => "JavaScript",
+        }
+    }
+
+    /// Picks the language for a `Content-Type` header value, ignoring its
+    /// parameters and honouring structured syntax suffixes like `+json`.
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let mime = content_type.split(';').next()?.trim().to_ascii_lowercase();
+        let (_, subtype) = mime.split_once('/')?;
+        let suffix = subtype.rsplit_once('+').map(|(_, suffix)| suffix);
+
+        match (subtype, suffix) {
+            ("json" | "x-ndjson", _) | (_, Some("json")) => Some(Language::Json),
+            ("html" | "xhtml+xml", _) => Some(Language::Html),
+            ("xml", _) | (_, Some("xml")) => Some(Language::Xml),
+            ("graphql", _) => Some(Language::GraphQl),
+            ("yaml" | "x-yaml", _) | (_, Some("yaml")) => Some(Language::Yaml),
+            ("javascript" | "ecmascript" | "x-javascript", _) => Some(Language::JavaScript),
+            _ => None,
+        }
+    }
+
+    /// Guesses the language of `text` from how it starts.
+    pub fn detect(text: &str) -> Option<Self> {
+        let text = text.trim_start();
+
+        if text.starts_with('{') || text.starts_with('[') {
+            Some(Language::Json)
+        } else if text
+            .get(..14)
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case("<!doctype html"))
+        {
+            Some(Language::Html)
+        } else if text.starts_with('<') {
+            Some(Language::Xml)
+        } else if text.starts_with("query")
+            || text.starts_with("mutation")
+            || text.starts_with("subscription")
+            || text.starts_with("fragment")
+        {
+            Some(Language::GraphQl)
+        } else {
+            None
+        }
+    }
+
+    /// Computes the non-overlapping highlight spans of `text`, sorted by
+    /// their start.
+    pub fn highlight(&self, text: &str) -> Vec<HighlightSpan> {
+        let mut captures = Vec::new();
+        let (grammar, text) = match self {
+            Language::Json => (&*JSON, Cow::Borrowed(text)),
+            // There is no XML grammar yet. The HTML one covers tags,
+            // attributes and comments, what it would misread is masked.
+            Language::Xml => {
+                let (masked, spans) = xml::mask(text);
+                captures.extend(spans.into_iter().map(|span| (span, 0)));
+                (&*HTML, Cow::Owned(masked))
+            }
+            Language::Html => (&*HTML, Cow::Borrowed(text)),
+            Language::Yaml => (&*YAML, Cow::Borrowed(text)),
+            Language::JavaScript => (&*JAVASCRIPT, Cow::Borrowed(text)),
+            Language::GraphQl => return graphql::highlight(text),
+        };
+        let text = text.as_ref();
+
+        let mut parser = Parser::new();
+        if parser.set_language(&grammar.language).is_err() {
+            return Vec::new();
+        }
+
+        let Some(tree) = parser.parse(text, None) else {
+            return Vec::new();
+        };
+
+        let names = grammar.query.capture_names();
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.captures(&grammar.query, tree.root_node(), text.as_bytes());
+
+        while let Some((query_match, index)) = matches.next() {
+            let capture = query_match.captures[*index];
+            let priority = if grammar.later_patterns_win {
+                usize::MAX - query_match.pattern_index
+            } else {
+                query_match.pattern_index
+            };
+
+            captures.push((
+                HighlightSpan {
+                    range: capture.node.byte_range(),
+                    name: names[capture.index as usize],
+                },
+                priority,
+            ));
+        }
+
+        flatten(captures, text.len())
+    }
+}
+
+/// Resolves nested and duplicate captures: inner captures win over the ones
+/// containing them, and the lowest priority wins among identical ranges.
+fn flatten(mut captures: Vec<(HighlightSpan, usize)>, len: usize) -> Vec<HighlightSpan> {
+    captures.sort_by_key(|(span, priority)| (span.range.start, span.range.end, *priority));
+    captures.dedup_by(|(next, _), (previous, _)| next.range == previous.range);
+
+    // The points where captures start and end, swept over in order while
+    // keeping the captures covering the text between them, shortest first.
+    let mut boundaries = Vec::with_capacity(captures.len() * 2);
+    for (index, (span, _)) in captures.iter().enumerate() {
+        let (start, end) = (span.range.start.min(len), span.range.end.min(len));
+        if start < end {
+            boundaries.push((start, index));
+            boundaries.push((end, index));
+        }
+    }
+    boundaries.sort_unstable();
+
+    let mut covering: BTreeSet<(usize, Reverse<usize>, usize)> = BTreeSet::new();
+    let mut spans: Vec<HighlightSpan> = Vec::new();
+    let mut previous = 0;
+    for (offset, index) in boundaries {
+        if offset > previous
+            && let Some(&(_, _, innermost)) = covering.first()
+        {
+            let name = captures[innermost].0.name;
+            match spans.last_mut() {
+                Some(last) if last.range.end == previous && last.name == name => {
+                    last.range.end = offset
+                }
+                _ => spans.push(HighlightSpan {
+                    range: previous..offset,
+                    name,
+                }),
+            }
+        }
+        previous = offset;
+
+        let range = &captures[index].0.range;
+        let key = (range.len(), Reverse(range.start), index);
+        if !covering.remove(&key) {
+            covering.insert(key);
+        }
+    }
+
+    spans
+}
@@ -0,0 +1,129 @@
+//! A lexical highlighter for GraphQL documents, used until a tree-sitter
+//! grammar is available. Once `tree-sitter-graphql` can be depended on, its
+//! `LANGUAGE` and highlights query replace this as a `Grammar` of its own.
+
+use super::HighlightSpan;
+
+const KEYWORDS: &[&str] = &[
+    "query",
+    "mutation",
+    "subscription",
+    "fragment",
+    "on",
+    "schema",
+    "scalar",
+    "type",
+    "interface",
+    "union",
+    "enum",
+    "input",
+    "extend",
+    "directive",
+    "implements",
+    "repeatable",
+];
+
+const CONSTANTS: &[&str] = &["true", "false", "null"];
+
+pub fn highlight(text: &str) -> Vec<HighlightSpan> {
+    let bytes = text.as_bytes();
+    let mut spans = Vec::new();
+    let mut offset = 0;
+
+    let push = |spans: &mut Vec<HighlightSpan>, start: usize, end: usize, name| {
+        spans.push(HighlightSpan {
+            range: start..end,
+            name,
+        })
+    };
+
+    while offset < bytes.len() {
+        let start = offset;
+
+        match bytes[offset] {
+            b'#' => {
+                offset = text[offset..]
+                    .find('\n')
+                    .map_or(bytes.len(), |index| offset + index);
+                push(&mut spans, start, offset, "comment");
+            }
+            b'"' if text[offset..].starts_with("\"\"\"") => {
+                offset = text[offset + 3..]
+                    .find("\"\"\"")
+                    .map_or(bytes.len(), |index| offset + 3 + index + 3);
+                push(&mut spans, start, offset, "string");
+            }
+            b'"' => {
+                offset += 1;
+                while offset < bytes.len() && !matches!(bytes[offset], b'"' | b'\n') {
+                    offset += if bytes[offset] == b'\\' { 2 } else { 1 };
+                }
+                offset = (offset + 1).min(bytes.len());
+                push(&mut spans, start, offset, "string");
+            }
+            b'$' | b'@' => {
+                offset += 1 + identifier_len(&bytes[offset + 1..]);
+                let name = if bytes[start] == b'$' {
+                    "variable"
+                } else {
+                    "attribute"
+                };
+                push(&mut spans, start, offset, name);
+            }
+            b'-' | b'0'..=b'9' => {
+                offset += 1;
+                while offset < bytes.len()
+                    && matches!(
+                        bytes[offset],
+                        b'0'..=b'9' | b'.' | b'e' | b'E' | b'+' | b'-'
+                    )
+                {
+                    offset += 1;
+                }
+                push(&mut spans, start, offset, "number");
+            }
+            b'{' | b'}' | b'(' | b')' | b'[' | b']' => {
+                offset += 1;
+                push(&mut spans, start, offset, "punctuation.bracket");
+            }
+            b':' | b'=' | b'!' | b'|' | b'&' => {
+                offset += 1;
+                push(&mut spans, start, offset, "punctuation.delimiter");
+            }
+            byte if byte == b'_' || byte.is_ascii_alphabetic() => {
+                offset += identifier_len(&bytes[offset..]);
+                let word = &text[start..offset];
+                let followed_by_colon = text[offset..].trim_start().starts_with(':');
+
+                let name = if KEYWORDS.contains(&word) {
+                    Some("keyword")
+                } else if CONSTANTS.contains(&word) {
+                    Some("constant.builtin")
+                } else if followed_by_colon {
+                    Some("property")
+                } else if word.starts_with(|c: char| c.is_ascii_uppercase()) {
+                    Some("type")
+                } else {
+                    None
+                };
+
+                if let Some(name) = name {
+                    push(&mut spans, start, offset, name);
+                }
+            }
+            byte if byte.is_ascii() => offset += 1,
+            _ => {
+                offset += text[offset..].chars().next().map_or(1, char::len_utf8);
+            }
+        }
+    }
+
+    spans
+}
+
+fn identifier_len(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .take_while(|byte| **byte == b'_' || byte.is_ascii_alphanumeric())
+        .count()
+}
@@ -0,0 +1,57 @@
+//! What the HTML grammar, standing in for an XML one until a tree-sitter
+//! grammar is available, gets wrong: processing instructions like the
+//! `<?xml ?>` declaration and CDATA sections, whose content it reads as
+//! markup. Once `tree-sitter-xml` can be depended on, its `LANGUAGE_XML`
+//! and highlights query replace the HTML grammar and this module.
+
+use super::HighlightSpan;
+
+/// `text` with its processing instructions and CDATA sections blanked out,
+/// keeping offsets and rows, and the spans highlighting them.
+pub fn mask(text: &str) -> (String, Vec<HighlightSpan>) {
+    let mut masked = String::with_capacity(text.len());
+    let mut spans = Vec::new();
+    let mut copied = 0;
+    let mut offset = 0;
+
+    while let Some(index) = text[offset..].find('<') {
+        let start = offset + index;
+        let rest = &text[start..];
+
+        let (open, close, name) = if rest.starts_with("<!--") {
+            ("<!--", "-->", None)
+        } else if rest.starts_with("<?") {
+            ("<?", "?>", Some("keyword"))
+        } else if rest.starts_with("<![CDATA[") {
+            ("<![CDATA[", "]]>", Some("string"))
+        } else {
+            offset = start + 1;
+            continue;
+        };
+
+        let end = text[start + open.len()..]
+            .find(close)
+            .map_or(text.len(), |index| start + open.len() + index + close.len());
+
+        // Comments are left to the grammar, they are only skipped so that
+        // the markers they contain are not taken for real ones.
+        if let Some(name) = name {
+            masked.push_str(&text[copied..start]);
+            masked.extend(
+                text[start..end]
+                    .bytes()
+                    .map(|byte| if byte == b'\n' { '\n' } else { ' ' }),
+            );
+            copied = end;
+            spans.push(HighlightSpan {
+                range: start..end,
+                name,
+            });
+        }
+
+        offset = end;
+    }
+
+    masked.push_str(&text[copied..]);
+    (masked, spans)
+}
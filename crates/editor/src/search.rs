@@ -0,0 +1,341 @@
+use std::ops::Range;
+
+use gpui::{
+    AppContext, Context, Entity, Focusable, InteractiveElement, IntoElement, ParentElement, Styled,
+    Subscription, Window, div,
+};
+use theme::ActiveTheme;
+use ui::{
+    components::{
+        button::{Button, ButtonSize},
+        icon::IconName,
+        icon_button::IconButton,
+        input::{InputEvent, TextInput},
+    },
+    traits::{clickable::Clickable, disableable::Disableable, styled_ext::StyledExt},
+};
+
+use crate::{
+    DismissSearch, Editor, Find, SEARCH_KEY_CONTEXT, SelectAllMatches, SelectNextMatch,
+    SelectPreviousMatch, ToggleCaseSensitive, selection::Selection,
+};
+
+/// The find bar shown above the text, and the matches of its query.
+pub struct Search {
+    pub visible: bool,
+    input: Entity<TextInput>,
+    pub matches: Vec<Range<usize>>,
+    pub active: Option<usize>,
+    case_sensitive: bool,
+    _subscription: Subscription,
+}
+
+/// Finds the non-overlapping occurrences of `query`, folding ASCII case
+/// unless `case_sensitive` is set.
+fn find_matches(text: &str, query: &str, case_sensitive: bool) -> Vec<Range<usize>> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let to_ranges = |text: &str, query: &str| {
+        text.match_indices(query)
+            .map(|(index, found)| index..index + found.len())
+            .collect()
+    };
+
+    if case_sensitive {
+        to_ranges(text, query)
+    } else {
+        to_ranges(&text.to_ascii_lowercase(), &query.to_ascii_lowercase())
+    }
+}
+
+impl Editor {
+    pub(crate) fn find(&mut self, _: &Find, window: &mut Window, cx: &mut Context<Self>) {
+        let newest = self.newest_selection();
+        let query = (!newest.is_empty() && !self.selected_text(newest).contains('\n'))
+            .then(|| self.selected_text(newest).to_string());
+
+        let search = self.search.get_or_insert_with(|| {
+            let input = cx.new(|cx| TextInput::new(cx).placeholder("Find"));
+            let subscription =
+                cx.subscribe_in(&input, window, |this, _, event, window, cx| match event {
+                    InputEvent::Changed => this.refresh_search(true, cx),
+                    InputEvent::PressEnter => this.select_next_match(&SelectNextMatch, window, cx),
+                });
+
+            Search {
+                visible: false,
+                input,
+                matches: Vec::new(),
+                active: None,
+                case_sensitive: false,
+                _subscription: subscription,
+            }
+        });
+
+        search.visible = true;
+        let input = search.input.clone();
+
+        input.update(cx, |input, cx| {
+            if let Some(query) = query {
+                input.set_text(query, cx);
+            }
+
+            let len = input.text().len();
+            input.select_range(0..len, cx);
+        });
+        window.focus(&input.read(cx).focus_handle(cx));
+        self.refresh_search(true, cx);
+    }
+
+    /// Recomputes the matches of the query, selecting the first one after
+    /// the newest cursor when `select` is set.
+    pub(crate) fn refresh_search(&mut self, select: bool, cx: &mut Context<Self>) {
+        let head = self.newest_selection().start;
+        let Some(search) = self.search.as_mut().filter(|search| search.visible) else {
+            return;
+        };
+
+        let query = search.input.read(cx).text().clone();
+        search.matches = find_matches(self.buffer.text(), &query, search.case_sensitive);
+        search.active = if search.matches.is_empty() {
+            None
+        } else if select {
+            Some(
+                search
+                    .matches
+                    .iter()
+                    .position(|range| range.start >= head)
+                    .unwrap_or(0),
+            )
+        } else {
+            search
+                .active
+                .map(|active| active.min(search.matches.len() - 1))
+        };
+
+        if select {
+            self.select_active_match(cx);
+        }
+
+        cx.notify();
+    }
+
+    fn select_active_match(&mut self, cx: &mut Context<Self>) {
+        let Some(range) = self.search.as_ref().and_then(|search| {
+            search
+                .active
+                .and_then(|active| search.matches.get(active).cloned())
+        }) else {
+            return;
+        };
+
+        if let Some(fold) = self.folds.fold_containing(range.start) {
+            self.folds.remove_containing(fold.start);
+        }
+
+        let mut selection = Selection::new(self.next_selection_id, 0);
+        selection.select(range, false);
+        self.next_selection_id += 1;
+        self.change_selections(cx, |selections| *selections = vec![selection]);
+    }
+
+    fn step_match(&mut self, forward: bool, cx: &mut Context<Self>) {
+        let Some(search) = self.search.as_mut().filter(|search| search.visible) else {
+            return;
+        };
+
+        let count = search.matches.len();
+        if count == 0 {
+            return;
+        }
+
+        search.active = Some(match search.active {
+            Some(active) if forward => (active + 1) % count,
+            Some(active) => (active + count - 1) % count,
+            None => 0,
+        });
+
+        self.select_active_match(cx);
+    }
+
+    pub(crate) fn select_next_match(
+        &mut self,
+        _: &SelectNextMatch,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.step_match(true, cx);
+    }
+
+    pub(crate) fn select_previous_match(
+        &mut self,
+        _: &SelectPreviousMatch,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.step_match(false, cx);
+    }
+
+    /// Puts a cursor on every match and moves focus back to the text.
+    pub(crate) fn select_all_matches(
+        &mut self,
+        _: &SelectAllMatches,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(search) = self.search.as_ref().filter(|search| search.visible) else {
+            return;
+        };
+
+        if search.matches.is_empty() {
+            return;
+        }
+
+        let selections = search
+            .matches
+            .iter()
+            .map(|range| {
+                let mut selection = Selection::new(self.next_selection_id, 0);
+
+                selection.select(range.clone(), false);
+                self.next_selection_id += 1;
+                selection
+            })
+            .collect();
+
+        self.folds.clear();
+        self.change_selections(cx, |current| *current = selections);
+        window.focus(&self.focus_handle);
+    }
+
+    pub(crate) fn dismiss_search(
+        &mut self,
+        _: &DismissSearch,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(search) = self.search.as_mut() {
+            search.visible = false;
+            search.matches.clear();
+            search.active = None;
+            window.focus(&self.focus_handle);
+            cx.notify();
+        }
+    }
+
+    pub(crate) fn toggle_case_sensitive(
+        &mut self,
+        _: &ToggleCaseSensitive,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(search) = self.search.as_mut() {
+            search.case_sensitive = !search.case_sensitive;
+            self.refresh_search(true, cx);
+        }
+    }
+
+    /// The matches to highlight and the index of the active one.
+    pub(crate) fn search_matches(&self) -> (&[Range<usize>], Option<usize>) {
+        match self.search.as_ref().filter(|search| search.visible) {
+            Some(search) => (&search.matches, search.active),
+            None => (&[], None),
+        }
+    }
+
+    pub(crate) fn render_search_bar(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let search = self.search.as_ref().filter(|search| search.visible)?;
+        let colors = cx.theme().colors();
+        let status = match (search.active, search.matches.len()) {
+            (_, 0) if search.input.read(cx).text().is_empty() => String::new(),
+            (_, 0) => "No results".to_string(),
+            (Some(active), count) => format!("{} of {count}", active + 1),
+            (None, count) => format!("{count} matches"),
+        };
+        let has_matches = !search.matches.is_empty();
+
+        Some(
+            div()
+                .key_context(SEARCH_KEY_CONTEXT)
+                .h_flex()
+                .gap_1()
+                .p_1()
+                .border_b_1()
+                .border_color(colors.border)
+                .bg(colors.muted)
+                .child(div().flex_1().child(search.input.clone()))
+                .child(
+                    div()
+                        .min_w_20()
+                        .text_xs()
+                        .text_color(colors.muted_foreground)
+                        .child(status),
+                )
+                .child(
+                    Button::new("case-sensitive", "Aa")
+                        .size(ButtonSize::Compact)
+                        .selected(search.case_sensitive)
+                        .tooltip("Match Case")
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.toggle_case_sensitive(&ToggleCaseSensitive, window, cx)
+                        })),
+                )
+                .child(
+                    IconButton::new("previous-match", IconName::ChevronUp)
+                        .tooltip("Previous Match")
+                        .disabled(!has_matches)
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.select_previous_match(&SelectPreviousMatch, window, cx)
+                        })),
+                )
+                .child(
+                    IconButton::new("next-match", IconName::ChevronDown)
+                        .tooltip("Next Match")
+                        .disabled(!has_matches)
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.select_next_match(&SelectNextMatch, window, cx)
+                        })),
+                )
+                .child(
+                    IconButton::new("select-all-matches", IconName::Check)
+                        .tooltip("Select All Matches")
+                        .disabled(!has_matches)
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.select_all_matches(&SelectAllMatches, window, cx)
+                        })),
+                )
+                .child(
+                    IconButton::new("dismiss-search", IconName::Close)
+                        .tooltip("Close")
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.dismiss_search(&DismissSearch, window, cx)
+                        })),
+                ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_matches_folding_ascii_case_unless_asked() {
+        let text = "Foo foo FOO fooo";
+
+        assert_eq!(
+            find_matches(text, "foo", false),
+            [0..3, 4..7, 8..11, 12..15]
+        );
+        assert_eq!(find_matches(text, "foo", true), [4..7, 12..15]);
+        assert_eq!(find_matches("aaaa", "aa", true), [0..2, 2..4]);
+        // Only ASCII case is folded.
+        assert_eq!(
+            find_matches("ÉTÉ été", "été", false),
+            [Range { start: 6, end: 11 }]
+        );
+        assert!(find_matches(text, "", false).is_empty());
+    }
+}
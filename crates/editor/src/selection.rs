@@ -0,0 +1,137 @@
+use std::ops::Range;
+
+use gpui::Pixels;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selection {
+    pub id: usize,
+    pub start: usize,
+    pub end: usize,
+    pub reversed: bool,
+    /// The horizontal position vertical movement tries to keep.
+    pub goal_x: Option<Pixels>,
+}
+
+impl Selection {
+    pub fn new(id: usize, offset: usize) -> Self {
+        Self {
+            id,
+            start: offset,
+            end: offset,
+            reversed: false,
+            goal_x: None,
+        }
+    }
+
+    pub fn head(&self) -> usize {
+        if self.reversed { self.start } else { self.end }
+    }
+
+    pub fn tail(&self) -> usize {
+        if self.reversed { self.end } else { self.start }
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn collapse_to(&mut self, offset: usize) {
+        self.start = offset;
+        self.end = offset;
+        self.reversed = false;
+        self.goal_x = None;
+    }
+
+    /// Moves the head to `offset`, keeping the tail in place.
+    pub fn set_head(&mut self, offset: usize) {
+        let tail = self.tail();
+
+        self.reversed = offset < tail;
+        self.start = offset.min(tail);
+        self.end = offset.max(tail);
+        self.goal_x = None;
+    }
+
+    pub fn select(&mut self, range: Range<usize>, reversed: bool) {
+        self.start = range.start;
+        self.end = range.end;
+        self.reversed = reversed;
+        self.goal_x = None;
+    }
+}
+
+/// Sorts `selections` and merges the ones that overlap or touch, so that
+/// every edit applies to each part of the text at most once.
+pub fn normalize(selections: &mut Vec<Selection>) {
+    selections.sort_by_key(|selection| (selection.start, selection.end));
+
+    let mut merged: Vec<Selection> = Vec::with_capacity(selections.len());
+    for selection in selections.drain(..) {
+        match merged.last_mut() {
+            Some(last)
+                if selection.start < last.end
+                    || (selection.start == last.end
+                        && (selection.is_empty() || last.is_empty())) =>
+            {
+                last.end = last.end.max(selection.end);
+                last.id = last.id.max(selection.id);
+            }
+            _ => merged.push(selection),
+        }
+    }
+
+    *selections = merged;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selection(id: usize, range: Range<usize>) -> Selection {
+        let mut selection = Selection::new(id, 0);
+        selection.select(range, false);
+        selection
+    }
+
+    #[test]
+    fn keeps_the_tail_when_moving_the_head() {
+        let mut selection = Selection::new(0, 5);
+
+        selection.set_head(2);
+        assert_eq!((selection.range(), selection.reversed), (2..5, true));
+        assert_eq!((selection.head(), selection.tail()), (2, 5));
+
+        selection.set_head(8);
+        assert_eq!((selection.range(), selection.reversed), (5..8, false));
+
+        selection.collapse_to(3);
+        assert!(selection.is_empty());
+        assert_eq!(selection.head(), 3);
+    }
+
+    #[test]
+    fn merges_overlapping_and_touching_selections() {
+        let mut selections = vec![
+            selection(0, 6..8),
+            selection(1, 0..2),
+            selection(2, 1..4),
+            selection(3, 8..8),
+            selection(4, 10..12),
+            selection(5, 12..14),
+        ];
+
+        normalize(&mut selections);
+
+        let ranges: Vec<_> = selections
+            .iter()
+            .map(|selection| (selection.id, selection.range()))
+            .collect();
+        // Non-empty selections that only touch stay apart, so that each
+        // keeps its own edit.
+        assert_eq!(ranges, [(2, 0..4), (3, 6..8), (4, 10..12), (5, 12..14)]);
+    }
+}
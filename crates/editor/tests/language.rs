@@ -0,0 +1,147 @@
+use editor::language::{HighlightSpan, Language};
+
+fn highlights(language: Language, text: &str) -> Vec<(&str, &'static str)> {
+    language
+        .highlight(text)
+        .into_iter()
+        .map(|HighlightSpan { range, name }| (&text[range], name))
+        .collect()
+}
+
+#[test]
+fn picks_the_language_of_a_content_type() {
+    let cases = [
+        ("application/json; charset=utf-8", Some(Language::Json)),
+        ("application/problem+json", Some(Language::Json)),
+        ("application/xhtml+xml", Some(Language::Html)),
+        ("application/atom+xml", Some(Language::Xml)),
+        ("text/XML", Some(Language::Xml)),
+        ("application/graphql", Some(Language::GraphQl)),
+        ("application/x-yaml", Some(Language::Yaml)),
+        ("text/javascript", Some(Language::JavaScript)),
+        ("text/plain", None),
+        ("nonsense", None),
+    ];
+
+    for (content_type, language) in cases {
+        assert_eq!(
+            Language::from_content_type(content_type),
+            language,
+            "{content_type}"
+        );
+    }
+}
+
+#[test]
+fn detects_the_language_of_text() {
+    assert_eq!(Language::detect("  [1, 2]"), Some(Language::Json));
+    assert_eq!(Language::detect("<!DOCTYPE html><p>"), Some(Language::Html));
+    assert_eq!(
+        Language::detect("<?xml version=\"1.0\"?>"),
+        Some(Language::Xml)
+    );
+    assert_eq!(Language::detect("query { me }"), Some(Language::GraphQl));
+    assert_eq!(Language::detect("plain"), None);
+}
+
+#[test]
+fn highlights_xml_markup_the_html_grammar_misreads() {
+    let text = "<?xml version=\"1.0\"?>\n\
+                <!-- <?not an instruction?> -->\n\
+                <a:root xmlns:a=\"urn:a\"><![CDATA[<b>\n]]>&amp;</a:root>";
+
+    assert_eq!(
+        highlights(Language::Xml, text),
+        [
+            ("<?xml version=\"1.0\"?>", "keyword"),
+            ("<!-- <?not an instruction?> -->", "comment"),
+            ("<", "punctuation.bracket"),
+            ("a:root", "tag"),
+            ("xmlns:a", "attribute"),
+            ("urn:a", "string"),
+            (">", "punctuation.bracket"),
+            ("<![CDATA[<b>\n]]>", "string"),
+            ("</", "punctuation.bracket"),
+            ("a:root", "tag"),
+            (">", "punctuation.bracket"),
+        ]
+    );
+
+    // Unterminated sections run to the end.
+    assert_eq!(
+        highlights(Language::Xml, "<a><![CDATA[<b>"),
+        [
+            ("<", "punctuation.bracket"),
+            ("a", "tag"),
+            (">", "punctuation.bracket"),
+            ("<![CDATA[<b>", "string"),
+        ]
+    );
+}
+
+#[test]
+fn highlights_graphql_tokens() {
+    let text = "query Q($id: ID! = \"a\\\"b\") @cached {\n  user(id: $id, n: -1.5e3) { ...on User { ok: true } } # done\n}";
+
+    assert_eq!(
+        highlights(Language::GraphQl, text),
+        [
+            ("query", "keyword"),
+            ("Q", "type"),
+            ("(", "punctuation.bracket"),
+            ("$id", "variable"),
+            (":", "punctuation.delimiter"),
+            ("ID", "type"),
+            ("!", "punctuation.delimiter"),
+            ("=", "punctuation.delimiter"),
+            ("\"a\\\"b\"", "string"),
+            (")", "punctuation.bracket"),
+            ("@cached", "attribute"),
+            ("{", "punctuation.bracket"),
+            ("(", "punctuation.bracket"),
+            ("id", "property"),
+            (":", "punctuation.delimiter"),
+            ("$id", "variable"),
+            ("n", "property"),
+            (":", "punctuation.delimiter"),
+            ("-1.5e3", "number"),
+            (")", "punctuation.bracket"),
+            ("{", "punctuation.bracket"),
+            ("on", "keyword"),
+            ("User", "type"),
+            ("{", "punctuation.bracket"),
+            ("ok", "property"),
+            (":", "punctuation.delimiter"),
+            ("true", "constant.builtin"),
+            ("}", "punctuation.bracket"),
+            ("}", "punctuation.bracket"),
+            ("# done", "comment"),
+            ("}", "punctuation.bracket"),
+        ]
+    );
+}
+
+#[test]
+fn highlights_with_the_bundled_grammars() {
+    let json = highlights(Language::Json, r#"{"key": [1, "two", null]}"#);
+    assert!(
+        json.contains(&("\"key\"", "string.special.key")),
+        "{json:?}"
+    );
+    assert!(json.contains(&("1", "number")), "{json:?}");
+
+    let yaml = highlights(Language::Yaml, "key: 'value' # note\n");
+    assert!(yaml.contains(&("# note", "comment")), "{yaml:?}");
+
+    let javascript = highlights(Language::JavaScript, "const a = `x`; // done");
+    assert!(javascript.contains(&("const", "keyword")), "{javascript:?}");
+    assert!(
+        javascript.contains(&("// done", "comment")),
+        "{javascript:?}"
+    );
+
+    for language in Language::ALL {
+        let spans = language.highlight("");
+        assert!(spans.is_empty(), "{}", language.name());
+    }
+}
@@ -0,0 +1,357 @@
+use gpui::{
+    App, AppContext, Context, Div, Entity, Focusable, InteractiveElement, IntoElement, MouseButton,
+    ParentElement, Pixels, Point, Render, SharedString, StatefulInteractiveElement, Styled,
+    Subscription, Window, actions, div, prelude::FluentBuilder,
+};
+use theme::ActiveTheme;
+use ui::{
+    color::Color,
+    components::{
+        button::{Button, ButtonSize, ButtonStyle},
+        checkbox::Checkbox,
+        context_menu::ContextMenu,
+        dropdown::{Dropdown, DropdownEvent},
+        icon::{Icon, IconName, IconSize},
+        icon_button::IconButton,
+        table::{KeyValue, KeyValueTable, KeyValueTableEvent},
+        tooltip::Tooltip,
+    },
+    traits::{clickable::Clickable, disableable::Disableable, styled_ext::StyledExt},
+};
+use workspace::{Workspace, item::Item};
+
+actions!(
+    gallery,
+    [
+        /// Opens a tab showcasing every ui component in its states.
+        OpenComponentGallery,
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &OpenComponentGallery, _, cx| {
+            let existing = workspace
+                .center()
+                .read(cx)
+                .items()
+                .find_map(|item| item.to_any().downcast::<ComponentGallery>().ok());
+
+            match existing {
+                Some(gallery) => {
+                    workspace.center().update(cx, |area, cx| {
+                        if let Some(index) = area.index_for_item_id(gallery.entity_id()) {
+                            area.activate_item(index, cx);
+                        }
+                    });
+                }
+                None => {
+                    let gallery = cx.new(ComponentGallery::new);
+
+                    workspace.add_item(Box::new(gallery), cx);
+                }
+            }
+        });
+    })
+    .detach();
+}
+
+/// A center area item rendering the ui components for visual review.
+pub struct ComponentGallery {
+    checked: bool,
+    last_event: Option<SharedString>,
+    dropdown: Entity<Dropdown>,
+    disabled_dropdown: Entity<Dropdown>,
+    table: Entity<KeyValueTable>,
+    context_menu: Option<(Entity<ContextMenu>, Point<Pixels>, Subscription)>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ComponentGallery {
+    fn new(cx: &mut Context<Self>) -> Self {
+        let dropdown = cx.new(|cx| Dropdown::new("dropdown", ["GET", "POST", "PUT", "DELETE"], cx));
+        let disabled_dropdown = cx.new(|cx| {
+            let mut dropdown = Dropdown::new("disabled-dropdown", ["Unavailable"], cx);
+
+            dropdown.set_disabled(true, cx);
+            dropdown
+        });
+        let table = cx.new(|cx| {
+            let mut table = KeyValueTable::new(cx)
+                .key_placeholder("Header")
+                .value_placeholder("Value");
+
+            table.set_rows(
+                [
+                    KeyValue {
+                        key: "Accept".into(),
+                        value: "application/json".into(),
+                        enabled: true,
+                    },
+                    KeyValue {
+                        key: "X-Debug".into(),
+                        value: "1".into(),
+                        enabled: false,
+                    },
+                ],
+                cx,
+            );
+            table
+        });
+
+        let subscriptions = vec![
+            cx.subscribe(&dropdown, |this, dropdown, event, cx| match event {
+                DropdownEvent::Changed(_) => {
+                    let selected = dropdown.read(cx).selected().cloned().unwrap_or_default();
+
+                    this.log(format!("Selected {selected}"), cx);
+                }
+            }),
+            cx.subscribe(&table, |this, table, event, cx| match event {
+                KeyValueTableEvent::Changed => {
+                    let rows = table.read(cx).rows(cx).len();
+
+                    this.log(format!("Table has {rows} rows"), cx);
+                }
+            }),
+        ];
+
+        Self {
+            checked: true,
+            last_event: None,
+            dropdown,
+            disabled_dropdown,
+            table,
+            context_menu: None,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn log(&mut self, event: impl Into<SharedString>, cx: &mut Context<Self>) {
+        self.last_event = Some(event.into());
+        cx.notify();
+    }
+
+    fn deploy_context_menu(
+        &mut self,
+        position: Point<Pixels>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let this = cx.entity().downgrade();
+        let entry = move |label: &'static str| {
+            let this = this.clone();
+
+            move |_: &mut Window, cx: &mut App| {
+                this.update(cx, |this, cx| this.log(format!("Ran {label}"), cx))
+                    .ok();
+            }
+        };
+
+        let menu = ContextMenu::build(window, cx, |menu, _, _| {
+            menu.header("Request")
+                .entry("Rename", entry("Rename"))
+                .entry("Duplicate", entry("Duplicate"))
+                .disabled_entry("Move")
+                .separator()
+                .entry("Delete", entry("Delete"))
+        });
+
+        window.focus(&menu.focus_handle(cx));
+
+        let subscription = cx.subscribe(&menu, |this, _, _: &gpui::DismissEvent, cx| {
+            this.context_menu = None;
+            cx.notify();
+        });
+
+        self.context_menu = Some((menu, position, subscription));
+        cx.notify();
+    }
+
+    fn render_section(title: &'static str, content: impl IntoElement, cx: &Context<Self>) -> Div {
+        let colors = cx.theme().colors();
+
+        div()
+            .v_flex()
+            .gap_2()
+            .child(
+                div()
+                    .text_sm()
+                    .text_color(colors.muted_foreground)
+                    .child(title),
+            )
+            .child(content)
+    }
+}
+
+impl Item for ComponentGallery {
+    fn tab_content_text(&self, _cx: &App) -> SharedString {
+        "Component Gallery".into()
+    }
+
+    fn tab_icon(&self, _cx: &App) -> Option<IconName> {
+        Some(IconName::Code)
+    }
+}
+
+impl Render for ComponentGallery {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = *cx.theme().colors();
+
+        let buttons = div()
+            .h_flex()
+            .flex_wrap()
+            .gap_2()
+            .children(
+                [
+                    ("Primary", ButtonStyle::Primary),
+                    ("Secondary", ButtonStyle::Secondary),
+                    ("Ghost", ButtonStyle::Ghost),
+                    ("Danger", ButtonStyle::Danger),
+                ]
+                .into_iter()
+                .map(|(label, style)| {
+                    Button::new(label, label)
+                        .style(style)
+                        .tooltip(format!("{label} button"))
+                        .on_click(cx.listener(move |this, _, _, cx| {
+                            this.log(format!("Clicked {label}"), cx)
+                        }))
+                }),
+            )
+            .child(Button::new("selected", "Selected").selected(true))
+            .child(Button::new("compact", "Compact").size(ButtonSize::Compact))
+            .child(Button::new("large", "Large").size(ButtonSize::Large))
+            .child(Button::new("disabled", "Disabled").disabled(true));
+
+        let icon_buttons = div()
+            .h_flex()
+            .gap_2()
+            .child(IconButton::new("add", IconName::Plus).tooltip("Add"))
+            .child(IconButton::new("remove", IconName::Trash).style(ButtonStyle::Danger))
+            .child(IconButton::new("selected-icon", IconName::Eye).selected(true))
+            .child(
+                IconButton::new("muted-icon", IconName::Settings)
+                    .icon_color(Color::Muted)
+                    .size(ButtonSize::Compact),
+            )
+            .child(IconButton::new("disabled-icon", IconName::Pencil).disabled(true));
+
+        let icons = div()
+            .h_flex()
+            .flex_wrap()
+            .gap_3()
+            .children(IconName::ALL.into_iter().map(|name| {
+                div()
+                    .id(name.file_stem())
+                    .child(Icon::new(name).size(IconSize::Large))
+                    .tooltip(Tooltip::text(name.file_stem()))
+            }))
+            .child(
+                div()
+                    .h_flex()
+                    .gap_2()
+                    .child(Icon::new(IconName::Warning).color(Color::Warning))
+                    .child(Icon::new(IconName::Error).color(Color::Danger))
+                    .child(Icon::new(IconName::Check).color(Color::Success))
+                    .child(Icon::new(IconName::Info).color(Color::Info))
+                    .child(Icon::new(IconName::Zap).color(Color::Accent))
+                    .child(Icon::new(IconName::Lock).size(IconSize::XSmall)),
+            );
+
+        let checkboxes = div()
+            .h_flex()
+            .gap_4()
+            .child(
+                Checkbox::new("checkbox", self.checked)
+                    .label("Follow redirects")
+                    .on_toggle(cx.listener(|this, checked, _, cx| {
+                        this.checked = *checked;
+                        cx.notify();
+                    })),
+            )
+            .child(
+                Checkbox::new("disabled-checked", true)
+                    .label("Disabled")
+                    .disabled(true),
+            )
+            .child(Checkbox::new("disabled-unchecked", false).disabled(true));
+
+        let tooltips = div()
+            .h_flex()
+            .gap_4()
+            .child(
+                div()
+                    .id("tooltip")
+                    .text_sm()
+                    .child("Hover for a tooltip")
+                    .tooltip(Tooltip::text("A tooltip")),
+            )
+            .child(
+                div()
+                    .id("tooltip-meta")
+                    .text_sm()
+                    .child("Hover for details")
+                    .tooltip(Tooltip::with_meta("A tooltip", "With a secondary line")),
+            );
+
+        let context_menu = div()
+            .id("context-menu-target")
+            .h_flex()
+            .justify_center()
+            .h_16()
+            .rounded_md()
+            .border_1()
+            .border_dashed()
+            .border_color(colors.border)
+            .text_sm()
+            .text_color(colors.muted_foreground)
+            .child("Right click for a context menu")
+            .on_mouse_down(
+                MouseButton::Right,
+                cx.listener(|this, event: &gpui::MouseDownEvent, window, cx| {
+                    this.deploy_context_menu(event.position, window, cx)
+                }),
+            );
+
+        div()
+            .id("component-gallery")
+            .v_flex()
+            .size_full()
+            .overflow_y_scroll()
+            .p_4()
+            .gap_6()
+            .text_color(colors.foreground)
+            .child(Self::render_section("Button", buttons, cx))
+            .child(Self::render_section("Icon", icons, cx))
+            .child(Self::render_section("IconButton", icon_buttons, cx))
+            .child(Self::render_section("Checkbox", checkboxes, cx))
+            .child(Self::render_section("Tooltip", tooltips, cx))
+            .child(Self::render_section(
+                "Dropdown",
+                div()
+                    .h_flex()
+                    .gap_2()
+                    .child(self.dropdown.clone())
+                    .child(self.disabled_dropdown.clone()),
+                cx,
+            ))
+            .child(Self::render_section("ContextMenu", context_menu, cx))
+            .child(Self::render_section(
+                "KeyValueTable",
+                self.table.clone(),
+                cx,
+            ))
+            .when_some(self.last_event.clone(), |this, event| {
+                this.child(
+                    div()
+                        .text_xs()
+                        .text_color(colors.muted_foreground)
+                        .child(event),
+                )
+            })
+            .when_some(self.context_menu.as_ref(), |this, (menu, position, _)| {
+                this.child(ContextMenu::popover(menu, *position))
+            })
+    }
+}
@@ -0,0 +1,250 @@
+use anyhow::{Result, anyhow};
+use base64::{Engine as _, prelude::BASE64_STANDARD};
+use http::{HeaderMap, header::WWW_AUTHENTICATE};
+use md5::Md5;
+use ring::rand::{SecureRandom, SystemRandom};
+use sha2::{Digest as _, Sha256};
+
+use crate::HttpRequest;
+
+/// A user name and password answering the Digest challenge of a `401`
+/// response, which can't be sent up front since it depends on the
+/// challenge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+/// The `Authorization` header value of Basic authentication.
+pub fn basic_authorization(username: &str, password: &str) -> String {
+    format!(
+        "Basic {}",
+        BASE64_STANDARD.encode(format!("{username}:{password}"))
+    )
+}
+
+/// One challenge of a `WWW-Authenticate` header.
+#[derive(Debug, Default)]
+struct Challenge {
+    scheme: String,
+    params: Vec<(String, String)>,
+}
+
+impl Challenge {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Algorithm {
+    Md5,
+    Sha256,
+}
+
+impl Algorithm {
+    fn hash(self, data: &str) -> String {
+        let digest = match self {
+            Algorithm::Md5 => Md5::digest(data.as_bytes()).to_vec(),
+            Algorithm::Sha256 => Sha256::digest(data.as_bytes()).to_vec(),
+        };
+
+        digest.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+}
+
+/// A Digest challenge this client can answer: one using MD5 or SHA-256,
+/// and either no quality of protection or `auth`.
+struct DigestChallenge<'a> {
+    challenge: &'a Challenge,
+    algorithm: Algorithm,
+    /// Whether the session variant of the algorithm is asked for.
+    session: bool,
+    qop: bool,
+}
+
+impl<'a> DigestChallenge<'a> {
+    fn new(challenge: &'a Challenge) -> Option<Self> {
+        if !challenge.scheme.eq_ignore_ascii_case("Digest") {
+            return None;
+        }
+        challenge.param("nonce")?;
+
+        let algorithm = challenge.param("algorithm").unwrap_or("MD5");
+        let (algorithm, session) = match algorithm.strip_suffix("-sess") {
+            Some(algorithm) => (algorithm, true),
+            None => (algorithm, false),
+        };
+        let algorithm = if algorithm.eq_ignore_ascii_case("MD5") {
+            Algorithm::Md5
+        } else if algorithm.eq_ignore_ascii_case("SHA-256") {
+            Algorithm::Sha256
+        } else {
+            return None;
+        };
+
+        let qop = match challenge.param("qop") {
+            Some(qop) if qop.split(',').any(|qop| qop.trim() == "auth") => true,
+            Some(_) => return None,
+            None => false,
+        };
+
+        Some(Self {
+            challenge,
+            algorithm,
+            session,
+            qop,
+        })
+    }
+
+    /// The `Authorization` header value answering the challenge for
+    /// `request`.
+    fn authorize(&self, request: &HttpRequest, credentials: &DigestCredentials) -> Result<String> {
+        let param = |name| self.challenge.param(name).unwrap_or_default();
+        let hash = |data: String| self.algorithm.hash(&data);
+
+        let realm = param("realm");
+        let nonce = param("nonce");
+        let uri = match request.url.query() {
+            Some(query) => format!("{}?{query}", request.url.path()),
+            None => request.url.path().to_string(),
+        };
+
+        let mut cnonce = [0; 16];
+        SystemRandom::new()
+            .fill(&mut cnonce)
+            .map_err(|_| anyhow!("no randomness for the Digest client nonce"))?;
+        let cnonce: String = cnonce.iter().map(|byte| format!("{byte:02x}")).collect();
+        let nonce_count = "00000001";
+
+        let mut secret = hash(format!(
+            "{}:{realm}:{}",
+            credentials.username, credentials.password
+        ));
+        if self.session {
+            secret = hash(format!("{secret}:{nonce}:{cnonce}"));
+        }
+        let target = hash(format!("{}:{uri}", request.method));
+        let response = if self.qop {
+            hash(format!(
+                "{secret}:{nonce}:{nonce_count}:{cnonce}:auth:{target}"
+            ))
+        } else {
+            hash(format!("{secret}:{nonce}:{target}"))
+        };
+
+        let mut header = format!(
+            "Digest username=\"{}\", realm=\"{}\", nonce=\"{}\", uri=\"{}\", algorithm={}, response=\"{response}\"",
+            quote(&credentials.username),
+            quote(realm),
+            quote(nonce),
+            quote(&uri),
+            self.challenge.param("algorithm").unwrap_or("MD5"),
+        );
+        if let Some(opaque) = self.challenge.param("opaque") {
+            header.push_str(&format!(", opaque=\"{}\"", quote(opaque)));
+        }
+        if self.qop {
+            header.push_str(&format!(
+                ", qop=auth, nc={nonce_count}, cnonce=\"{cnonce}\""
+            ));
+        }
+
+        Ok(header)
+    }
+}
+
+/// The `Authorization` header value answering the Digest challenge of a
+/// `401` response to `request` with `headers`, preferring SHA-256 when the
+/// server offers several. `None` when there is no challenge this client
+/// can answer.
+pub(crate) fn digest_authorization(
+    request: &HttpRequest,
+    headers: &HeaderMap,
+    credentials: &DigestCredentials,
+) -> Result<Option<String>> {
+    let challenges: Vec<Challenge> = headers
+        .get_all(WWW_AUTHENTICATE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(parse_challenges)
+        .collect();
+
+    let mut digests: Vec<DigestChallenge> =
+        challenges.iter().filter_map(DigestChallenge::new).collect();
+    digests.sort_by_key(|digest| digest.algorithm != Algorithm::Sha256);
+
+    digests
+        .first()
+        .map(|digest| digest.authorize(request, credentials))
+        .transpose()
+}
+
+/// Splits a `WWW-Authenticate` header value into its challenges, each a
+/// scheme followed by comma separated parameters.
+fn parse_challenges(value: &str) -> Vec<Challenge> {
+    let mut challenges: Vec<Challenge> = Vec::new();
+    let mut rest = value;
+
+    loop {
+        rest = rest.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+        if rest.is_empty() {
+            return challenges;
+        }
+
+        let end = rest
+            .find(|c: char| c == ',' || c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let token = &rest[..end];
+        rest = rest[end..].trim_start();
+
+        let Some(after_equals) = rest.strip_prefix('=') else {
+            challenges.push(Challenge {
+                scheme: token.to_string(),
+                params: Vec::new(),
+            });
+            continue;
+        };
+        // `token68` credentials, such as `Negotiate abc==`, end in padding
+        // rather than a value.
+        if after_equals.starts_with('=') || after_equals.trim().is_empty() {
+            rest = after_equals.trim_start_matches('=');
+            continue;
+        }
+
+        let (value, remaining) = parse_value(after_equals.trim_start());
+        rest = remaining;
+        if let Some(challenge) = challenges.last_mut() {
+            challenge.params.push((token.to_string(), value));
+        }
+    }
+}
+
+/// Reads a quoted string or a token at the start of `input`, returning it
+/// unescaped along with what follows.
+fn parse_value(input: &str) -> (String, &str) {
+    let Some(quoted) = input.strip_prefix('"') else {
+        let end = input.find(',').unwrap_or(input.len());
+        return (input[..end].trim().to_string(), &input[end..]);
+    };
+
+    let mut value = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return (value, &quoted[index + 1..]),
+            '\\' => value.extend(chars.next().map(|(_, c)| c)),
+            c => value.push(c),
+        }
+    }
+
+    (value, "")
+}
+
+fn quote(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
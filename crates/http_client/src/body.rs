@@ -0,0 +1,141 @@
+use std::{io, path::PathBuf};
+
+use anyhow::{Context as _, Result};
+use bytes::{Bytes, BytesMut};
+use futures::{StreamExt as _, TryStreamExt as _, stream};
+use http_body_util::{BodyExt as _, StreamBody, combinators::UnsyncBoxBody};
+use hyper::body::Frame;
+use tokio::{fs::File, io::AsyncReadExt as _};
+
+/// How much of a file is read at a time while it is sent.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The body as written to the connection.
+pub(crate) type WireBody = UnsyncBoxBody<Bytes, io::Error>;
+
+/// The body of a request, made of bytes and of files that are only read as
+/// the request is sent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HttpBody {
+    parts: Vec<BodyPart>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BodyPart {
+    Bytes(Bytes),
+    File(PathBuf),
+}
+
+impl HttpBody {
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        Self {
+            parts: vec![BodyPart::File(path.into())],
+        }
+    }
+
+    pub fn parts(&self) -> &[BodyPart] {
+        &self.parts
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parts
+            .iter()
+            .all(|part| matches!(part, BodyPart::Bytes(bytes) if bytes.is_empty()))
+    }
+
+    pub fn push_bytes(&mut self, bytes: impl Into<Bytes>) {
+        let bytes = bytes.into();
+
+        // Consecutive bytes are merged to write fewer frames.
+        match self.parts.last_mut() {
+            Some(BodyPart::Bytes(last)) => {
+                let mut merged = BytesMut::from(&last[..]);
+                merged.extend_from_slice(&bytes);
+                *last = merged.freeze();
+            }
+            _ => self.parts.push(BodyPart::Bytes(bytes)),
+        }
+    }
+
+    pub fn push_file(&mut self, path: impl Into<PathBuf>) {
+        self.parts.push(BodyPart::File(path.into()));
+    }
+
+    /// The bytes of the body when it has no files.
+    pub fn as_bytes(&self) -> Option<Bytes> {
+        match self.parts.as_slice() {
+            [] => Some(Bytes::new()),
+            [BodyPart::Bytes(bytes)] => Some(bytes.clone()),
+            _ => None,
+        }
+    }
+
+    /// The length of the body, with the size its files have right now.
+    pub async fn len(&self) -> Result<u64> {
+        let mut len = 0;
+
+        for part in &self.parts {
+            len += match part {
+                BodyPart::Bytes(bytes) => bytes.len() as u64,
+                BodyPart::File(path) => tokio::fs::metadata(path)
+                    .await
+                    .with_context(|| format!("reading {}", path.display()))?
+                    .len(),
+            };
+        }
+
+        Ok(len)
+    }
+
+    /// The bytes of the body, reading files a chunk at a time.
+    pub(crate) fn chunks(&self) -> impl futures::Stream<Item = io::Result<Bytes>> + Send + 'static {
+        stream::iter(self.parts.clone())
+            .flat_map(|part| match part {
+                BodyPart::Bytes(bytes) => stream::once(async move { Ok(bytes) }).boxed(),
+                BodyPart::File(path) => read_file(path).boxed(),
+            })
+            .try_filter(|bytes| std::future::ready(!bytes.is_empty()))
+    }
+
+    /// Streams the body, reading files a chunk at a time.
+    pub(crate) fn to_wire(&self) -> WireBody {
+        StreamBody::new(self.chunks().map_ok(Frame::data)).boxed_unsync()
+    }
+}
+
+impl From<Bytes> for HttpBody {
+    fn from(bytes: Bytes) -> Self {
+        let mut body = Self::default();
+        body.push_bytes(bytes);
+        body
+    }
+}
+
+impl From<String> for HttpBody {
+    fn from(text: String) -> Self {
+        Bytes::from(text).into()
+    }
+}
+
+impl From<&'static [u8]> for HttpBody {
+    fn from(bytes: &'static [u8]) -> Self {
+        Bytes::from_static(bytes).into()
+    }
+}
+
+fn read_file(path: PathBuf) -> impl futures::Stream<Item = io::Result<Bytes>> + Send {
+    stream::once(async move {
+        File::open(&path)
+            .await
+            .map_err(|error| io::Error::new(error.kind(), format!("{}: {error}", path.display())))
+    })
+    .map_ok(|file| {
+        stream::try_unfold(file, |mut file| async move {
+            let mut chunk = BytesMut::with_capacity(CHUNK_SIZE);
+            let read = file.read_buf(&mut chunk).await?;
+
+            Ok((read > 0).then(|| (chunk.freeze(), file)))
+        })
+    })
+    .try_flatten()
+}
@@ -0,0 +1,158 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use anyhow::{Result, bail};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::der::{self, Reader};
+
+const SUBJECT_ALT_NAME: &str = "2.5.29.17";
+
+/// What a certificate of the chain presented by a server says, for display.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CertificateInfo {
+    pub subject: String,
+    pub issuer: String,
+    /// The serial number as colon separated hex bytes.
+    pub serial: String,
+    pub not_before: String,
+    pub not_after: String,
+    pub subject_alt_names: Vec<String>,
+    /// The SHA-256 digest of the DER encoding, as colon separated hex bytes.
+    pub sha256: String,
+}
+
+impl CertificateInfo {
+    pub fn parse(der: &[u8]) -> Result<Self> {
+        let mut certificate = Reader::new(der).read_sequence()?;
+        let mut tbs = certificate.read_sequence()?;
+
+        tbs.read_optional(der::context(0))?;
+        let serial = hex_bytes(tbs.read(der::INTEGER)?);
+        tbs.read_sequence()?;
+        let issuer = format_name(tbs.read(der::SEQUENCE)?)?;
+        let mut validity = tbs.read_sequence()?;
+        let not_before = format_time(validity.read_any()?)?;
+        let not_after = format_time(validity.read_any()?)?;
+        let subject = format_name(tbs.read(der::SEQUENCE)?)?;
+        tbs.read_sequence()?;
+
+        let mut subject_alt_names = Vec::new();
+        while let Some(tag) = tbs.peek_tag() {
+            let contents = tbs.read(tag)?;
+
+            if tag == der::context(3) {
+                subject_alt_names = alt_names(contents)?;
+            }
+        }
+
+        Ok(Self {
+            subject,
+            issuer,
+            serial,
+            not_before,
+            not_after,
+            subject_alt_names,
+            sha256: hex_bytes(&Sha256::digest(der)),
+        })
+    }
+}
+
+fn hex_bytes(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Formats a distinguished name the way OpenSSL does, e.g.
+/// `CN=example.com, O=Example`.
+fn format_name(name: &[u8]) -> Result<String> {
+    let mut name = Reader::new(name);
+    let mut parts = Vec::new();
+
+    while !name.is_empty() {
+        let mut set = Reader::new(name.read(der::SET)?);
+
+        while !set.is_empty() {
+            let mut attribute = set.read_sequence()?;
+            let oid = attribute.read_oid()?;
+            let (_, value) = attribute.read_any()?;
+            let key = match oid.as_str() {
+                "2.5.4.3" => "CN",
+                "2.5.4.5" => "serialNumber",
+                "2.5.4.6" => "C",
+                "2.5.4.7" => "L",
+                "2.5.4.8" => "ST",
+                "2.5.4.10" => "O",
+                "2.5.4.11" => "OU",
+                "1.2.840.113549.1.9.1" => "emailAddress",
+                _ => &oid,
+            };
+
+            parts.push(format!("{key}={}", String::from_utf8_lossy(value)));
+        }
+    }
+
+    Ok(parts.join(", "))
+}
+
+fn format_time((tag, value): (u8, &[u8])) -> Result<String> {
+    let value = std::str::from_utf8(value)?;
+    let (year, rest) = match tag {
+        der::UTC_TIME if value.len() >= 12 => {
+            let year: u32 = value[..2].parse()?;
+            let year = if year < 50 { 2000 + year } else { 1900 + year };
+
+            (year.to_string(), &value[2..])
+        }
+        der::GENERALIZED_TIME if value.len() >= 14 => (value[..4].to_string(), &value[4..]),
+        _ => bail!("invalid certificate time"),
+    };
+
+    Ok(format!(
+        "{year}-{}-{} {}:{}:{} UTC",
+        &rest[..2],
+        &rest[2..4],
+        &rest[4..6],
+        &rest[6..8],
+        &rest[8..10]
+    ))
+}
+
+/// The DNS names and IP addresses of the subject alternative name extension,
+/// given the contents of the extensions field.
+fn alt_names(extensions: &[u8]) -> Result<Vec<String>> {
+    let mut extensions = Reader::new(extensions).read_sequence()?;
+
+    while !extensions.is_empty() {
+        let mut extension = extensions.read_sequence()?;
+
+        if extension.read_oid()? != SUBJECT_ALT_NAME {
+            continue;
+        }
+
+        extension.read_optional(der::BOOLEAN)?;
+        let mut names = Reader::new(extension.read(der::OCTET_STRING)?).read_sequence()?;
+        let mut result = Vec::new();
+
+        while !names.is_empty() {
+            match names.read_any()? {
+                (0x82, name) => result.push(format!("DNS:{}", String::from_utf8_lossy(name))),
+                (0x87, ip) => {
+                    if let Ok(octets) = <[u8; 4]>::try_from(ip) {
+                        result.push(format!("IP:{}", Ipv4Addr::from(octets)));
+                    } else if let Ok(octets) = <[u8; 16]>::try_from(ip) {
+                        result.push(format!("IP:{}", Ipv6Addr::from(octets)));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        return Ok(result);
+    }
+
+    Ok(Vec::new())
+}
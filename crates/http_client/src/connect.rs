@@ -0,0 +1,231 @@
+use std::{
+    net::SocketAddr,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpStream,
+};
+use tokio_rustls::TlsConnector;
+use url::Url;
+
+use crate::{
+    RequestOptions,
+    protocol::HttpVersion,
+    proxy::ProxyScheme,
+    resolve::{ResolveOverride, resolve},
+    timing::RemoteAddr,
+    tls::{TlsInfo, TlsOptions},
+};
+
+/// A byte stream a connection can be layered on, such as a TCP socket, a
+/// tunnel through a proxy or a TLS session over either.
+pub trait Io: AsyncRead + AsyncWrite + Send + Unpin + 'static {}
+
+impl<T> Io for T where T: AsyncRead + AsyncWrite + Send + Unpin + 'static {}
+
+/// A connection to the origin of a request, possibly through a proxy, along
+/// with the protocol to speak over it.
+pub struct Connection {
+    pub stream: Box<dyn Io>,
+    pub tls: Option<TlsInfo>,
+    /// Whether h2 was negotiated via ALPN, or is to be spoken right away.
+    pub h2: bool,
+    /// Whether requests are handed to an HTTP proxy in absolute form,
+    /// rather than to the origin through a tunnel.
+    pub forward: bool,
+}
+
+/// How long each step of opening a connection took.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConnectTiming {
+    pub dns: Option<Duration>,
+    /// Opening the socket, including the handshake with a proxy.
+    pub connect: Duration,
+    pub tls: Option<Duration>,
+}
+
+pub async fn connect(
+    url: &Url,
+    options: &RequestOptions,
+) -> Result<(Connection, RemoteAddr, ConnectTiming)> {
+    let version = options.version;
+    let host = url.host_str().ok_or_else(|| anyhow!("{url} has no host"))?;
+    let port = url
+        .port_or_known_default()
+        .ok_or_else(|| anyhow!("{url} has no port"))?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let https = match url.scheme() {
+        "http" => false,
+        "https" => true,
+        scheme => bail!("unsupported scheme {scheme}"),
+    };
+    if !https && version == HttpVersion::Http2 {
+        bail!("h2 is negotiated via TLS; use HTTP/2 prior knowledge for cleartext h2c");
+    }
+
+    let mut timing = ConnectTiming::default();
+    let start = Instant::now();
+
+    // A Unix domain socket leads to the origin itself, so proxies are left
+    // out.
+    if let Some(path) = &options.unix_socket {
+        let stream = open_unix(path)
+            .await
+            .with_context(|| format!("connecting to {}", path.display()))?;
+        timing.connect = start.elapsed();
+
+        let connection = establish(stream, host, port, https, options, &mut timing).await?;
+        return Ok((connection, RemoteAddr::Unix(path.clone()), timing));
+    }
+
+    let resolve_overrides = &options.resolve;
+    let Some(proxy) = &options.proxy else {
+        let (tcp, address) = open(host, port, resolve_overrides, &mut timing)
+            .await
+            .with_context(|| format!("connecting to {host}:{port}"))?;
+        timing.connect = start.elapsed() - timing.dns.unwrap_or_default();
+
+        let connection = establish(Box::new(tcp), host, port, https, options, &mut timing).await?;
+        return Ok((connection, address.into(), timing));
+    };
+
+    let (tcp, address) = open(proxy.host(), proxy.port(), resolve_overrides, &mut timing)
+        .await
+        .with_context(|| format!("connecting to proxy {proxy}"))?;
+    let stream: Box<dyn Io> = match proxy.scheme() {
+        ProxyScheme::Https => {
+            // The proxy's certificate is checked against the same
+            // authorities as the origin's.
+            let tls = TlsOptions {
+                ca_certificates: options.tls.ca_certificates.clone(),
+                skip_verification: options.tls.skip_verification,
+                ..TlsOptions::default()
+            };
+            let config = tls.client_config(vec![b"http/1.1".to_vec()])?;
+            let stream = TlsConnector::from(Arc::new(config))
+                .connect(tls.server_name(proxy.host())?, tcp)
+                .await
+                .with_context(|| format!("TLS handshake with proxy {proxy}"))?;
+
+            Box::new(stream)
+        }
+        _ => Box::new(tcp),
+    };
+
+    // Plain HTTP/1.1 requests are forwarded by HTTP proxies as they are,
+    // anything else goes through a tunnel.
+    let forward = matches!(proxy.scheme(), ProxyScheme::Http | ProxyScheme::Https)
+        && !https
+        && version != HttpVersion::Http2PriorKnowledge;
+
+    let stream = match proxy.scheme() {
+        _ if forward => stream,
+        ProxyScheme::Http | ProxyScheme::Https => proxy.tunnel(stream, host, port).await?,
+        ProxyScheme::Socks5 => {
+            let target = resolve(host, port, resolve_overrides, &mut timing)
+                .await?
+                .first()
+                .copied()
+                .ok_or_else(|| anyhow!("no addresses found for {host}"))?;
+
+            proxy.socks5(stream, host, port, Some(target)).await?
+        }
+        ProxyScheme::Socks5h => proxy.socks5(stream, host, port, None).await?,
+    };
+    timing.connect = start.elapsed() - timing.dns.unwrap_or_default();
+
+    let mut connection = establish(stream, host, port, https, options, &mut timing).await?;
+    connection.forward = forward;
+
+    Ok((connection, address.into(), timing))
+}
+
+/// Completes a connection to the origin over `stream`, starting TLS for
+/// `https` URLs.
+async fn establish(
+    stream: Box<dyn Io>,
+    host: &str,
+    port: u16,
+    https: bool,
+    options: &RequestOptions,
+    timing: &mut ConnectTiming,
+) -> Result<Connection> {
+    let version = options.version;
+
+    if !https {
+        return Ok(Connection {
+            stream,
+            tls: None,
+            h2: version == HttpVersion::Http2PriorKnowledge,
+            forward: false,
+        });
+    }
+
+    let start = Instant::now();
+    let config = options.tls.client_config(version.alpn_protocols())?;
+    let server_name = options.tls.server_name(host)?;
+    let stream = TlsConnector::from(Arc::new(config))
+        .connect(server_name, stream)
+        .await
+        .with_context(|| format!("TLS handshake with {host}:{port}"))?;
+    timing.tls = Some(start.elapsed());
+
+    let session = stream.get_ref().1;
+    let h2 = version == HttpVersion::Http2PriorKnowledge || session.alpn_protocol() == Some(b"h2");
+    if version == HttpVersion::Http2 && !h2 {
+        bail!("{host} did not select h2 via ALPN");
+    }
+
+    Ok(Connection {
+        tls: Some(TlsInfo::new(session, &options.tls)),
+        stream: Box::new(stream),
+        h2,
+        forward: false,
+    })
+}
+
+/// Resolves `host` and connects to the first of its addresses that accepts.
+async fn open(
+    host: &str,
+    port: u16,
+    overrides: &[ResolveOverride],
+    timing: &mut ConnectTiming,
+) -> Result<(TcpStream, SocketAddr)> {
+    let addresses = resolve(host, port, overrides, timing).await?;
+    let (tcp, address) = connect_any(&addresses).await?;
+    tcp.set_nodelay(true)?;
+
+    Ok((tcp, address))
+}
+
+#[cfg(unix)]
+async fn open_unix(path: &Path) -> Result<Box<dyn Io>> {
+    Ok(Box::new(tokio::net::UnixStream::connect(path).await?))
+}
+
+#[cfg(not(unix))]
+async fn open_unix(_path: &Path) -> Result<Box<dyn Io>> {
+    bail!("Unix domain sockets aren't supported on this platform")
+}
+
+/// Connects to the first of `addresses` that accepts, in order.
+async fn connect_any(addresses: &[SocketAddr]) -> Result<(TcpStream, SocketAddr)> {
+    let mut last_error = None;
+
+    for address in addresses {
+        match TcpStream::connect(address).await {
+            Ok(stream) => return Ok((stream, *address)),
+            Err(error) => last_error = Some(error),
+        }
+    }
+
+    Err(match last_error {
+        Some(error) => error.into(),
+        None => anyhow!("no addresses found"),
+    })
+}
@@ -0,0 +1,392 @@
+use std::{
+    net::IpAddr,
+    sync::LazyLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use http::{HeaderMap, header::SET_COOKIE};
+use publicsuffix::{List, Psl as _};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// The suffixes under which anyone can register a domain, like `com` or
+/// `co.uk`, that cookies can't be set for.
+static PUBLIC_SUFFIXES: LazyLock<List> = LazyLock::new(|| {
+    include_str!("public_suffix_list.dat")
+        .parse()
+        .expect("invalid public suffix list")
+});
+
+/// A cookie set by a server, following the storage model of RFC 6265.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    /// The host the cookie was set by, or the domain of its `Domain`
+    /// attribute, without a leading dot.
+    pub domain: String,
+    /// Whether the cookie is sent to subdomains of `domain` too, as it is
+    /// when set with a `Domain` attribute.
+    #[serde(default)]
+    pub include_subdomains: bool,
+    pub path: String,
+    #[serde(default)]
+    pub secure: bool,
+    #[serde(default)]
+    pub http_only: bool,
+    /// When the cookie expires, in seconds since the Unix epoch. Session
+    /// cookies have none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<u64>,
+}
+
+impl Cookie {
+    /// Parses a `Set-Cookie` header received in response to a request to
+    /// `url`, returning `None` when it must be ignored.
+    pub fn parse(set_cookie: &str, url: &Url, now: SystemTime) -> Option<Self> {
+        let host = url
+            .host_str()?
+            .trim_matches(['[', ']'])
+            .to_ascii_lowercase();
+        let mut attributes = set_cookie.split(';');
+        let (name, value) = attributes.next()?.split_once('=')?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+
+        let mut cookie = Self {
+            name: name.to_string(),
+            value: value.trim().trim_matches('"').to_string(),
+            domain: host.clone(),
+            include_subdomains: false,
+            path: default_path(url),
+            secure: false,
+            http_only: false,
+            expires: None,
+        };
+        let mut max_age = None;
+
+        for attribute in attributes {
+            let (key, value) = attribute.split_once('=').unwrap_or((attribute, ""));
+            let value = value.trim();
+
+            match key.trim().to_ascii_lowercase().as_str() {
+                "domain" if !value.is_empty() => {
+                    let domain = value.trim_start_matches('.').to_ascii_lowercase();
+                    // A public suffix is only accepted from the host it
+                    // names, for that host alone.
+                    let public_suffix = is_public_suffix(&domain);
+                    if (public_suffix && domain != host) || !domain_matches(&host, &domain) {
+                        return None;
+                    }
+
+                    cookie.include_subdomains = host.parse::<IpAddr>().is_err() && !public_suffix;
+                    cookie.domain = domain;
+                }
+                "path" if value.starts_with('/') => cookie.path = value.to_string(),
+                "secure" => cookie.secure = true,
+                "httponly" => cookie.http_only = true,
+                "max-age" => max_age = value.parse::<i64>().ok(),
+                "expires" => {
+                    if let Some(expires) = parse_cookie_date(value) {
+                        cookie.expires = Some(expires);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Only secure origins may set secure cookies.
+        if cookie.secure && url.scheme() != "https" {
+            return None;
+        }
+
+        if let Some(max_age) = max_age {
+            cookie.expires = Some(unix_time(now).saturating_add(max_age.max(0) as u64));
+        }
+
+        Some(cookie)
+    }
+
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        self.expires
+            .is_some_and(|expires| expires <= unix_time(now))
+    }
+
+    /// Whether the cookie is sent with a request to `url`.
+    pub fn matches(&self, url: &Url, now: SystemTime) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        let host = host.trim_matches(['[', ']']).to_ascii_lowercase();
+
+        let domain_matches = if self.include_subdomains {
+            domain_matches(&host, &self.domain)
+        } else {
+            host == self.domain
+        };
+
+        domain_matches
+            && path_matches(url.path(), &self.path)
+            && (!self.secure || url.scheme() == "https")
+            && !self.is_expired(now)
+    }
+
+    /// Whether `other` is the same cookie, possibly with another value.
+    fn same_as(&self, other: &Cookie) -> bool {
+        self.name == other.name && self.domain == other.domain && self.path == other.path
+    }
+
+    /// When the cookie expires, formatted for display.
+    pub fn expires_display(&self) -> Option<String> {
+        self.expires.map(format_unix_time)
+    }
+}
+
+/// The cookies received from servers, to send back with later requests.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CookieJar {
+    /// In the order they were first set.
+    cookies: Vec<Cookie>,
+}
+
+impl CookieJar {
+    pub fn cookies(&self) -> &[Cookie] {
+        &self.cookies
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cookies.is_empty()
+    }
+
+    /// Stores the cookies set by a response to a request to `url`,
+    /// returning whether the jar changed.
+    pub fn store_response(&mut self, url: &Url, headers: &HeaderMap, now: SystemTime) -> bool {
+        let mut changed = false;
+
+        for set_cookie in headers.get_all(SET_COOKIE) {
+            if let Ok(set_cookie) = set_cookie.to_str()
+                && let Some(cookie) = Cookie::parse(set_cookie, url, now)
+            {
+                changed |= self.insert(cookie, now);
+            }
+        }
+
+        changed
+    }
+
+    /// Adds `cookie`, replacing the one with the same name, domain and path
+    /// in place. An expired cookie only removes the one it replaces.
+    pub fn insert(&mut self, cookie: Cookie, now: SystemTime) -> bool {
+        let existing = self
+            .cookies
+            .iter()
+            .position(|existing| existing.same_as(&cookie));
+
+        match existing {
+            Some(index) if cookie.is_expired(now) => {
+                self.cookies.remove(index);
+                true
+            }
+            Some(index) if self.cookies[index] == cookie => false,
+            Some(index) => {
+                self.cookies[index] = cookie;
+                true
+            }
+            None if cookie.is_expired(now) => false,
+            None => {
+                self.cookies.push(cookie);
+                true
+            }
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<Cookie> {
+        (index < self.cookies.len()).then(|| self.cookies.remove(index))
+    }
+
+    /// Replaces the cookie at `index`, keeping its place in the jar.
+    pub fn replace(&mut self, index: usize, cookie: Cookie) {
+        if let Some(existing) = self.cookies.get_mut(index) {
+            *existing = cookie;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cookies.clear();
+    }
+
+    pub fn remove_expired(&mut self, now: SystemTime) -> bool {
+        let len = self.cookies.len();
+        self.cookies.retain(|cookie| !cookie.is_expired(now));
+
+        self.cookies.len() != len
+    }
+
+    /// The `Cookie` header to send with a request to `url`, listing cookies
+    /// with longer paths first.
+    pub fn header_for(&self, url: &Url, now: SystemTime) -> Option<String> {
+        let mut cookies = self
+            .cookies
+            .iter()
+            .filter(|cookie| cookie.matches(url, now))
+            .collect::<Vec<_>>();
+        if cookies.is_empty() {
+            return None;
+        }
+
+        cookies.sort_by_key(|cookie| std::cmp::Reverse(cookie.path.len()));
+
+        Some(
+            cookies
+                .iter()
+                .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+                .collect::<Vec<_>>()
+                .join("; "),
+        )
+    }
+}
+
+fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain
+        || (host.parse::<IpAddr>().is_err()
+            && host
+                .strip_suffix(domain)
+                .is_some_and(|prefix| prefix.ends_with('.'))
+            && !is_public_suffix(domain))
+}
+
+/// Whether `domain` is a public suffix. Single labels not on the list are,
+/// as the list says of unknown top-level domains.
+fn is_public_suffix(domain: &str) -> bool {
+    PUBLIC_SUFFIXES
+        .suffix(domain.as_bytes())
+        .is_some_and(|suffix| suffix.as_bytes() == domain.as_bytes())
+}
+
+fn path_matches(request_path: &str, cookie_path: &str) -> bool {
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || request_path[cookie_path.len()..].starts_with('/')))
+}
+
+/// The directory of the path of `url`, which cookies without a `Path`
+/// attribute are scoped to.
+fn default_path(url: &Url) -> String {
+    match url.path().rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(index) => url.path()[..index].to_string(),
+    }
+}
+
+fn unix_time(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Parses the date of an `Expires` attribute with the lenient algorithm of
+/// RFC 6265, which accepts the formats servers use in practice.
+fn parse_cookie_date(value: &str) -> Option<u64> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+
+    let mut time = None;
+    let mut day = None;
+    let mut month = None;
+    let mut year = None;
+
+    for token in value
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == ':'))
+        .filter(|token| !token.is_empty())
+    {
+        if time.is_none() {
+            let parts = token
+                .split(':')
+                .map(|part| part.parse::<u64>().ok())
+                .collect::<Option<Vec<_>>>();
+            if let Some([hours, minutes, seconds]) = parts.as_deref() {
+                time = Some((*hours, *minutes, *seconds));
+                continue;
+            }
+        }
+
+        let digits = token.len() - token.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let number = token[..digits].parse::<u64>().ok();
+
+        if day.is_none() && (1..=2).contains(&digits) {
+            day = number;
+        } else if month.is_none()
+            && let Some(index) = MONTHS
+                .iter()
+                .position(|month| token.to_ascii_lowercase().starts_with(month))
+        {
+            month = Some(index as u64 + 1);
+        } else if year.is_none() && (2..=4).contains(&digits) {
+            year = number.map(|year| match year {
+                0..=69 => year + 2000,
+                70..=99 => year + 1900,
+                year => year,
+            });
+        }
+    }
+
+    let (hours, minutes, seconds) = time?;
+    let (day, month, year) = (day?, month?, year?);
+    if !(1..=31).contains(&day) || year < 1601 || hours > 23 || minutes > 59 || seconds > 59 {
+        return None;
+    }
+
+    let days = days_from_civil(year as i64, month as i64, day as i64);
+    let seconds = days * 86_400 + (hours * 3600 + minutes * 60 + seconds) as i64;
+
+    Some(seconds.max(0) as u64)
+}
+
+/// Days since the Unix epoch of a date of the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Formats seconds since the Unix epoch like certificate validity dates.
+fn format_unix_time(time: u64) -> String {
+    let (year, month, day) = civil_from_days((time / 86_400) as i64);
+    let seconds = time % 86_400;
+
+    format!(
+        "{year}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// The year, month and day of the proleptic Gregorian calendar a number of
+/// days since the Unix epoch falls on.
+pub(crate) fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
@@ -0,0 +1,123 @@
+use anyhow::{Result, anyhow, bail, ensure};
+
+pub const BOOLEAN: u8 = 0x01;
+pub const INTEGER: u8 = 0x02;
+pub const OCTET_STRING: u8 = 0x04;
+pub const OID: u8 = 0x06;
+pub const UTC_TIME: u8 = 0x17;
+pub const GENERALIZED_TIME: u8 = 0x18;
+pub const SEQUENCE: u8 = 0x30;
+pub const SET: u8 = 0x31;
+
+/// The tag of the context specific, constructed field `[number]`.
+pub const fn context(number: u8) -> u8 {
+    0xa0 | number
+}
+
+/// Reads consecutive DER values out of a buffer, just enough of the format
+/// to pull fields out of certificates and PKCS#12 archives.
+#[derive(Clone, Copy)]
+pub struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn peek_tag(&self) -> Option<u8> {
+        self.data.first().copied()
+    }
+
+    /// Reads the next value, returning its tag and contents.
+    pub fn read_any(&mut self) -> Result<(u8, &'a [u8])> {
+        let (&tag, rest) = self
+            .data
+            .split_first()
+            .ok_or_else(|| anyhow!("unexpected end of DER data"))?;
+        let (&first, mut rest) = rest
+            .split_first()
+            .ok_or_else(|| anyhow!("truncated DER length"))?;
+
+        let len = if first < 0x80 {
+            first as usize
+        } else {
+            let count = (first & 0x7f) as usize;
+            ensure!(
+                (1..=4).contains(&count) && rest.len() >= count,
+                "unsupported DER length"
+            );
+
+            let len = rest[..count]
+                .iter()
+                .fold(0usize, |len, byte| (len << 8) | *byte as usize);
+            rest = &rest[count..];
+            len
+        };
+
+        ensure!(rest.len() >= len, "truncated DER value");
+        let (contents, rest) = rest.split_at(len);
+        self.data = rest;
+
+        Ok((tag, contents))
+    }
+
+    /// Reads the next value, failing unless it has the given tag.
+    pub fn read(&mut self, tag: u8) -> Result<&'a [u8]> {
+        let (found, contents) = self.read_any()?;
+
+        if found != tag {
+            bail!("expected DER tag {tag:#04x}, found {found:#04x}");
+        }
+
+        Ok(contents)
+    }
+
+    /// Reads the next value when it has the given tag.
+    pub fn read_optional(&mut self, tag: u8) -> Result<Option<&'a [u8]>> {
+        if self.peek_tag() == Some(tag) {
+            self.read(tag).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn read_sequence(&mut self) -> Result<Reader<'a>> {
+        self.read(SEQUENCE).map(Reader::new)
+    }
+
+    pub fn read_oid(&mut self) -> Result<String> {
+        self.read(OID).map(oid_to_string)
+    }
+}
+
+/// Formats an encoded object identifier in its dotted form.
+pub fn oid_to_string(bytes: &[u8]) -> String {
+    let mut arcs = Vec::new();
+    let mut value = 0u64;
+
+    for byte in bytes {
+        value = (value << 7) | (byte & 0x7f) as u64;
+
+        if byte & 0x80 == 0 {
+            if arcs.is_empty() {
+                let first = (value / 40).min(2);
+                arcs.push(first);
+                arcs.push(value - first * 40);
+            } else {
+                arcs.push(value);
+            }
+            value = 0;
+        }
+    }
+
+    arcs.iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(".")
+}
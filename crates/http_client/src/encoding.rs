@@ -0,0 +1,115 @@
+use std::{
+    fmt,
+    io::{self, BufRead, Write},
+};
+
+use anyhow::{Context as _, Result, anyhow};
+use bytes::Bytes;
+use flate2::read::{DeflateDecoder, MultiGzDecoder, ZlibDecoder};
+use http::{HeaderMap, header::CONTENT_ENCODING};
+use serde::{Serialize, Serializer};
+
+/// The value of `Accept-Encoding` sent unless a request sets its own.
+pub(crate) const ACCEPT_ENCODING: &str = "gzip, deflate, br, zstd";
+
+/// A compression applied to a response body, as named by `Content-Encoding`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContentEncoding {
+    Gzip,
+    Deflate,
+    Brotli,
+    Zstd,
+    /// An encoding the client can't decode.
+    Other(String),
+}
+
+impl ContentEncoding {
+    /// The encodings listed by the headers, in the order they were applied.
+    pub fn from_headers(headers: &HeaderMap) -> Vec<Self> {
+        headers
+            .get_all(CONTENT_ENCODING)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|name| name.trim().to_ascii_lowercase())
+            .filter(|name| !name.is_empty() && name != "identity")
+            .map(|name| match name.as_str() {
+                "gzip" | "x-gzip" => Self::Gzip,
+                "deflate" => Self::Deflate,
+                "br" => Self::Brotli,
+                "zstd" => Self::Zstd,
+                _ => Self::Other(name),
+            })
+            .collect()
+    }
+
+    /// Undoes the encoding of `body`, writing the result to `decoded`.
+    pub(crate) fn decode(&self, mut body: impl BufRead, decoded: &mut impl Write) -> Result<()> {
+        match self {
+            Self::Gzip => io::copy(&mut MultiGzDecoder::new(body), decoded),
+            // The specification asks for a zlib stream, yet some servers
+            // send raw deflate data.
+            Self::Deflate => match body.fill_buf() {
+                Ok(header) if is_zlib(header) => io::copy(&mut ZlibDecoder::new(body), decoded),
+                Ok(_) => io::copy(&mut DeflateDecoder::new(body), decoded),
+                Err(error) => Err(error),
+            },
+            Self::Brotli => io::copy(
+                &mut brotli_decompressor::Decompressor::new(body, 4096),
+                decoded,
+            ),
+            Self::Zstd => ruzstd::decoding::StreamingDecoder::new(body)
+                .map_err(io::Error::other)
+                .and_then(|mut decoder| io::copy(&mut decoder, decoded)),
+            Self::Other(name) => return Err(anyhow!("unsupported content encoding {name:?}")),
+        }
+        .with_context(|| format!("decoding {self} body"))?;
+
+        Ok(())
+    }
+}
+
+/// Whether `header` starts a zlib stream, with the deflate method and a
+/// valid check value.
+fn is_zlib(header: &[u8]) -> bool {
+    match header {
+        [cmf, flg, ..] => cmf & 0x0f == 8 && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0,
+        _ => false,
+    }
+}
+
+impl fmt::Display for ContentEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Gzip => "gzip",
+            Self::Deflate => "deflate",
+            Self::Brotli => "br",
+            Self::Zstd => "zstd",
+            Self::Other(name) => name,
+        })
+    }
+}
+
+/// Serializes as the `Content-Encoding` name.
+impl Serialize for ContentEncoding {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Undoes `encodings` on `body`, last applied first.
+pub(crate) fn decode(body: &Bytes, encodings: &[ContentEncoding]) -> Result<Bytes> {
+    if body.is_empty() {
+        return Ok(body.clone());
+    }
+
+    encodings
+        .iter()
+        .rev()
+        .try_fold(body.clone(), |body, encoding| {
+            let mut decoded = Vec::new();
+            encoding.decode(&body[..], &mut decoded)?;
+
+            Ok(decoded.into())
+        })
+}
@@ -0,0 +1,459 @@
+use std::{collections::BTreeMap, fmt};
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+use sha2::{Digest, Sha256};
+
+/// The query asking a server for its schema, as GraphiQL sends it.
+pub const INTROSPECTION_QUERY: &str = "query IntrospectionQuery {
+  __schema {
+    queryType { name }
+    mutationType { name }
+    subscriptionType { name }
+    types { ...FullType }
+    directives {
+      name
+      description
+      locations
+      args { ...InputValue }
+    }
+  }
+}
+
+fragment FullType on __Type {
+  kind
+  name
+  description
+  fields(includeDeprecated: true) {
+    name
+    description
+    args { ...InputValue }
+    type { ...TypeRef }
+    isDeprecated
+    deprecationReason
+  }
+  inputFields { ...InputValue }
+  interfaces { ...TypeRef }
+  enumValues(includeDeprecated: true) {
+    name
+    description
+    isDeprecated
+    deprecationReason
+  }
+  possibleTypes { ...TypeRef }
+}
+
+fragment InputValue on __InputValue {
+  name
+  description
+  type { ...TypeRef }
+  defaultValue
+}
+
+fragment TypeRef on __Type {
+  kind
+  name
+  ofType {
+    kind
+    name
+    ofType {
+      kind
+      name
+      ofType {
+        kind
+        name
+        ofType {
+          kind
+          name
+          ofType {
+            kind
+            name
+            ofType {
+              kind
+              name
+              ofType { kind name }
+            }
+          }
+        }
+      }
+    }
+  }
+}
+";
+
+/// The message of the error servers answer a hash-only persisted query
+/// with when they don't have its query yet.
+const PERSISTED_QUERY_NOT_FOUND: &str = "PersistedQueryNotFound";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TypeKind {
+    Scalar,
+    Object,
+    Interface,
+    Union,
+    Enum,
+    InputObject,
+}
+
+impl TypeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Scalar => "scalar",
+            Self::Object => "type",
+            Self::Interface => "interface",
+            Self::Union => "union",
+            Self::Enum => "enum",
+            Self::InputObject => "input",
+        }
+    }
+
+    /// Whether fields can be selected on the type.
+    pub fn is_composite(&self) -> bool {
+        matches!(self, Self::Object | Self::Interface | Self::Union)
+    }
+
+    /// Whether values of the type can be passed as arguments.
+    pub fn is_input(&self) -> bool {
+        matches!(self, Self::Scalar | Self::Enum | Self::InputObject)
+    }
+}
+
+/// The type of a field, argument or variable, such as `[String!]!`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "IntrospectedTypeRef")]
+pub enum TypeRef {
+    Named(String),
+    List(Box<TypeRef>),
+    NonNull(Box<TypeRef>),
+}
+
+impl TypeRef {
+    /// The name of the type once the lists and non-null wrappers are
+    /// removed.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Named(name) => name,
+            Self::List(inner) | Self::NonNull(inner) => inner.name(),
+        }
+    }
+
+    pub fn is_non_null(&self) -> bool {
+        matches!(self, Self::NonNull(_))
+    }
+}
+
+impl fmt::Display for TypeRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Named(name) => write!(f, "{name}"),
+            Self::List(inner) => write!(f, "[{inner}]"),
+            Self::NonNull(inner) => write!(f, "{inner}!"),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IntrospectedTypeRef {
+    kind: String,
+    name: Option<String>,
+    of_type: Option<Box<IntrospectedTypeRef>>,
+}
+
+impl TryFrom<IntrospectedTypeRef> for TypeRef {
+    type Error = anyhow::Error;
+
+    fn try_from(introspected: IntrospectedTypeRef) -> Result<Self> {
+        let inner = |of_type: Option<Box<IntrospectedTypeRef>>| -> Result<Box<TypeRef>> {
+            let of_type = of_type.ok_or_else(|| anyhow!("wrapping type without ofType"))?;
+            Ok(Box::new(TypeRef::try_from(*of_type)?))
+        };
+
+        Ok(match introspected.kind.as_str() {
+            "LIST" => Self::List(inner(introspected.of_type)?),
+            "NON_NULL" => Self::NonNull(inner(introspected.of_type)?),
+            _ => Self::Named(
+                introspected
+                    .name
+                    .ok_or_else(|| anyhow!("named type without a name"))?,
+            ),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaType {
+    pub kind: TypeKind,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub fields: Vec<SchemaField>,
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub input_fields: Vec<InputValue>,
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub interfaces: Vec<TypeRef>,
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub enum_values: Vec<EnumValue>,
+    /// The object types of an interface or union.
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub possible_types: Vec<TypeRef>,
+}
+
+impl SchemaType {
+    pub fn field(&self, name: &str) -> Option<&SchemaField> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaField {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub args: Vec<InputValue>,
+    #[serde(rename = "type")]
+    pub ty: TypeRef,
+    /// Why the field shouldn't be used anymore, when it shouldn't.
+    #[serde(default)]
+    pub deprecation_reason: Option<String>,
+    #[serde(default)]
+    pub is_deprecated: bool,
+}
+
+/// An argument, or a field of an input type.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputValue {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(rename = "type")]
+    pub ty: TypeRef,
+    /// The default as a GraphQL literal, such as `"en"` or `10`.
+    #[serde(default)]
+    pub default_value: Option<String>,
+}
+
+impl InputValue {
+    /// Whether the value has to be given.
+    pub fn is_required(&self) -> bool {
+        self.ty.is_non_null() && self.default_value.is_none()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnumValue {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub is_deprecated: bool,
+    #[serde(default)]
+    pub deprecation_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaDirective {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub locations: Vec<String>,
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub args: Vec<InputValue>,
+}
+
+/// The schema of a GraphQL server, as answered to [`INTROSPECTION_QUERY`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GraphQlSchema {
+    pub query_type: Option<String>,
+    pub mutation_type: Option<String>,
+    pub subscription_type: Option<String>,
+    /// The types by name, the built-in introspection ones included.
+    pub types: BTreeMap<String, SchemaType>,
+    pub directives: Vec<SchemaDirective>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IntrospectedSchema {
+    query_type: Option<NamedType>,
+    mutation_type: Option<NamedType>,
+    subscription_type: Option<NamedType>,
+    types: Vec<SchemaType>,
+    #[serde(default)]
+    directives: Vec<SchemaDirective>,
+}
+
+#[derive(Deserialize)]
+struct NamedType {
+    name: String,
+}
+
+fn null_as_empty<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Ok(Option::<Vec<T>>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+impl GraphQlSchema {
+    /// Reads the schema from the response to [`INTROSPECTION_QUERY`],
+    /// failing with the errors of the response when it has no data.
+    pub fn from_introspection(body: &[u8]) -> Result<Self> {
+        let response: Value = serde_json::from_slice(body).context("the response isn't JSON")?;
+
+        let schema = response
+            .pointer("/data/__schema")
+            .filter(|schema| !schema.is_null());
+        let Some(schema) = schema else {
+            match error_messages(&response) {
+                messages if messages.is_empty() => bail!("the response has no schema"),
+                messages => bail!("{}", messages.join("; ")),
+            }
+        };
+
+        let schema = IntrospectedSchema::deserialize(schema).context("invalid schema")?;
+
+        Ok(Self {
+            query_type: schema.query_type.map(|ty| ty.name),
+            mutation_type: schema.mutation_type.map(|ty| ty.name),
+            subscription_type: schema.subscription_type.map(|ty| ty.name),
+            types: schema
+                .types
+                .into_iter()
+                .map(|ty| (ty.name.clone(), ty))
+                .collect(),
+            directives: schema.directives,
+        })
+    }
+
+    pub fn get_type(&self, name: &str) -> Option<&SchemaType> {
+        self.types.get(name)
+    }
+
+    /// The type `query`, `mutation` or `subscription` operations start
+    /// from.
+    pub fn root_type(&self, operation: &str) -> Option<&SchemaType> {
+        let name = match operation {
+            "query" => self.query_type.as_deref(),
+            "mutation" => self.mutation_type.as_deref(),
+            "subscription" => self.subscription_type.as_deref(),
+            _ => None,
+        }?;
+
+        self.get_type(name)
+    }
+
+    pub fn directive(&self, name: &str) -> Option<&SchemaDirective> {
+        self.directives
+            .iter()
+            .find(|directive| directive.name == name)
+    }
+
+    /// The types defined by the server, leaving out the introspection
+    /// ones.
+    pub fn user_types(&self) -> impl Iterator<Item = &SchemaType> {
+        self.types.values().filter(|ty| !ty.name.starts_with("__"))
+    }
+}
+
+/// The messages of the `errors` of a GraphQL response.
+pub fn error_messages(response: &Value) -> Vec<String> {
+    response
+        .get("errors")
+        .and_then(Value::as_array)
+        .map(|errors| {
+            errors
+                .iter()
+                .filter_map(|error| error.get("message").and_then(Value::as_str))
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// How a GraphQL document is sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryMode {
+    /// The query in full.
+    Full,
+    /// Only the hash of the query, for servers caching persisted queries.
+    PersistedHash,
+    /// The query along with its hash, for the server to cache it.
+    PersistedFull,
+}
+
+/// The JSON body of a GraphQL request over HTTP, with `variables` given as
+/// JSON text that may be blank.
+pub fn graphql_body(
+    query: &str,
+    variables: &str,
+    operation_name: Option<&str>,
+    mode: QueryMode,
+) -> Result<String> {
+    let mut body = Map::new();
+
+    if mode != QueryMode::PersistedHash {
+        body.insert("query".into(), query.into());
+    }
+
+    if !variables.trim().is_empty() {
+        let variables: Value =
+            serde_json::from_str(variables).context("the variables aren't valid JSON")?;
+        if !variables.is_object() && !variables.is_null() {
+            bail!("the variables must be a JSON object");
+        }
+        body.insert("variables".into(), variables);
+    }
+
+    if let Some(operation_name) = operation_name.filter(|name| !name.is_empty()) {
+        body.insert("operationName".into(), operation_name.into());
+    }
+
+    if mode != QueryMode::Full {
+        body.insert(
+            "extensions".into(),
+            json!({
+                "persistedQuery": {
+                    "version": 1,
+                    "sha256Hash": persisted_query_hash(query),
+                }
+            }),
+        );
+    }
+
+    Ok(Value::Object(body).to_string())
+}
+
+/// The hash identifying a persisted query, as hex-encoded SHA-256.
+pub fn persisted_query_hash(query: &str) -> String {
+    Sha256::digest(query.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Whether `body` is the answer of a server that doesn't know the hash of
+/// a persisted query yet and needs the query itself.
+pub fn is_persisted_query_not_found(body: &[u8]) -> bool {
+    let Ok(response) = serde_json::from_slice::<Value>(body) else {
+        return false;
+    };
+
+    let errors = response.get("errors").and_then(Value::as_array);
+    errors.into_iter().flatten().any(|error| {
+        error.get("message").and_then(Value::as_str) == Some(PERSISTED_QUERY_NOT_FOUND)
+            || error.pointer("/extensions/code").and_then(Value::as_str)
+                == Some("PERSISTED_QUERY_NOT_FOUND")
+    })
+}
@@ -0,0 +1,1535 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    ops::Range,
+};
+
+use crate::graphql::{GraphQlSchema, InputValue, SchemaType, TypeKind, TypeRef};
+
+const KEYWORDS: [&str; 4] = ["query", "mutation", "subscription", "fragment"];
+
+/// A problem in a GraphQL document, located by byte range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphQlError {
+    pub range: Range<usize>,
+    pub message: String,
+}
+
+impl GraphQlError {
+    fn new(range: Range<usize>, message: impl Into<String>) -> Self {
+        Self {
+            range,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for GraphQlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// A suggestion for the word at some offset of a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphQlCompletion {
+    pub label: String,
+    /// The type of the field or argument, or what kind of name it is.
+    pub detail: String,
+    /// The range of the word being completed.
+    pub range: Range<usize>,
+    pub insert: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Name,
+    Number,
+    String,
+    Punctuator,
+    Spread,
+    Comment,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    range: Range<usize>,
+}
+
+/// Splits `source` into tokens, stopping at the first invalid one.
+fn tokenize(source: &str) -> (Vec<Token>, Option<GraphQlError>) {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut offset = 0;
+
+    let is_name_start = |byte: u8| byte == b'_' || byte.is_ascii_alphabetic();
+    let is_name_continue = |byte: u8| byte == b'_' || byte.is_ascii_alphanumeric();
+
+    while offset < bytes.len() {
+        let start = offset;
+        let byte = bytes[offset];
+
+        let kind = match byte {
+            b' ' | b'\t' | b'\n' | b'\r' | b',' => {
+                offset += 1;
+                continue;
+            }
+            b'#' => {
+                while offset < bytes.len() && bytes[offset] != b'\n' {
+                    offset += 1;
+                }
+                TokenKind::Comment
+            }
+            b'!' | b'$' | b'&' | b'(' | b')' | b':' | b'=' | b'@' | b'[' | b']' | b'{' | b'|'
+            | b'}' => {
+                offset += 1;
+                TokenKind::Punctuator
+            }
+            b'.' if source[offset..].starts_with("...") => {
+                offset += 3;
+                TokenKind::Spread
+            }
+            b'"' if source[offset..].starts_with("\"\"\"") => {
+                offset += 3;
+                loop {
+                    if offset >= bytes.len() {
+                        let error = GraphQlError::new(start..offset, "unterminated block string");
+                        return (tokens, Some(error));
+                    }
+                    if source[offset..].starts_with("\\\"\"\"") {
+                        offset += 4;
+                    } else if source[offset..].starts_with("\"\"\"") {
+                        offset += 3;
+                        break;
+                    } else {
+                        offset += 1;
+                    }
+                }
+                TokenKind::String
+            }
+            b'"' => {
+                offset += 1;
+                loop {
+                    match bytes.get(offset) {
+                        None | Some(b'\n' | b'\r') => {
+                            let error = GraphQlError::new(start..offset, "unterminated string");
+                            return (tokens, Some(error));
+                        }
+                        Some(b'\\') => offset += 2,
+                        Some(b'"') => {
+                            offset += 1;
+                            break;
+                        }
+                        Some(_) => offset += 1,
+                    }
+                }
+                TokenKind::String
+            }
+            b'-' | b'0'..=b'9' => {
+                offset += 1;
+                while offset < bytes.len()
+                    && (bytes[offset].is_ascii_digit()
+                        || matches!(bytes[offset], b'.' | b'e' | b'E')
+                        || (matches!(bytes[offset], b'+' | b'-')
+                            && matches!(bytes[offset - 1], b'e' | b'E')))
+                {
+                    offset += 1;
+                }
+                if offset < bytes.len() && is_name_start(bytes[offset]) {
+                    let error = GraphQlError::new(start..offset + 1, "invalid number");
+                    return (tokens, Some(error));
+                }
+                TokenKind::Number
+            }
+            byte if is_name_start(byte) => {
+                while offset < bytes.len() && is_name_continue(bytes[offset]) {
+                    offset += 1;
+                }
+                TokenKind::Name
+            }
+            _ => {
+                let len = source[offset..].chars().next().map_or(1, char::len_utf8);
+                let error = GraphQlError::new(
+                    start..start + len,
+                    format!("unexpected character {:?}", &source[start..start + len]),
+                );
+                return (tokens, Some(error));
+            }
+        };
+
+        tokens.push(Token {
+            kind,
+            range: start..offset,
+        });
+    }
+
+    (tokens, None)
+}
+
+#[derive(Debug, Clone)]
+struct Name {
+    value: String,
+    range: Range<usize>,
+}
+
+#[derive(Debug)]
+enum Definition {
+    Operation(Operation),
+    Fragment(Fragment),
+}
+
+#[derive(Debug)]
+struct Operation {
+    /// `query`, `mutation` or `subscription`.
+    kind: String,
+    /// Where the operation starts, for anonymous ones to be pointed at.
+    keyword: Range<usize>,
+    name: Option<Name>,
+    variables: Vec<VariableDefinition>,
+    directives: Vec<Directive>,
+    selection_set: SelectionSet,
+}
+
+#[derive(Debug)]
+struct VariableDefinition {
+    name: Name,
+    ty: TypeRef,
+    ty_range: Range<usize>,
+    default: Option<ValueNode>,
+}
+
+#[derive(Debug)]
+struct Fragment {
+    name: Name,
+    type_condition: Name,
+    directives: Vec<Directive>,
+    selection_set: SelectionSet,
+}
+
+#[derive(Debug, Default)]
+struct SelectionSet {
+    selections: Vec<Selection>,
+}
+
+#[derive(Debug)]
+enum Selection {
+    Field(Field),
+    FragmentSpread {
+        name: Name,
+        directives: Vec<Directive>,
+    },
+    InlineFragment {
+        type_condition: Option<Name>,
+        directives: Vec<Directive>,
+        selection_set: SelectionSet,
+    },
+}
+
+#[derive(Debug)]
+struct Field {
+    name: Name,
+    arguments: Vec<Argument>,
+    directives: Vec<Directive>,
+    selection_set: Option<SelectionSet>,
+}
+
+#[derive(Debug)]
+struct Argument {
+    name: Name,
+    value: ValueNode,
+}
+
+#[derive(Debug)]
+struct Directive {
+    name: Name,
+    arguments: Vec<Argument>,
+}
+
+#[derive(Debug)]
+struct ValueNode {
+    value: Value,
+    range: Range<usize>,
+}
+
+#[derive(Debug)]
+enum Value {
+    Variable(String),
+    Int,
+    Float,
+    String,
+    Boolean,
+    Null,
+    Enum(String),
+    List(Vec<ValueNode>),
+    Object(Vec<(Name, ValueNode)>),
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+type ParseResult<T> = Result<T, GraphQlError>;
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> ParseResult<Self> {
+        let (mut tokens, error) = tokenize(source);
+        if let Some(error) = error {
+            return Err(error);
+        }
+        tokens.retain(|token| token.kind != TokenKind::Comment);
+
+        Ok(Self {
+            source,
+            tokens,
+            position: 0,
+        })
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn peek_text(&self) -> Option<&'a str> {
+        self.peek().map(|token| &self.source[token.range.clone()])
+    }
+
+    fn peek_is(&self, text: &str) -> bool {
+        self.peek().is_some_and(|token| {
+            token.kind != TokenKind::String && &self.source[token.range.clone()] == text
+        })
+    }
+
+    /// Where an error about the next token points, the last token at the
+    /// end of the document.
+    fn error_range(&self) -> Range<usize> {
+        self.peek()
+            .or(self.tokens.last())
+            .map_or(0..0, |token| token.range.clone())
+    }
+
+    fn unexpected(&self, expected: &str) -> GraphQlError {
+        match self.peek_text() {
+            Some(found) => GraphQlError::new(
+                self.error_range(),
+                format!("expected {expected}, found {found}"),
+            ),
+            None => GraphQlError::new(
+                self.error_range(),
+                format!("expected {expected}, found the end of the document"),
+            ),
+        }
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, text: &str) -> bool {
+        let found = self.peek_is(text);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, text: &str) -> ParseResult<Range<usize>> {
+        if self.peek_is(text) {
+            Ok(self.next().map(|token| token.range).unwrap_or_default())
+        } else {
+            Err(self.unexpected(&format!("{text:?}")))
+        }
+    }
+
+    fn name(&mut self) -> ParseResult<Name> {
+        match self.peek() {
+            Some(token) if token.kind == TokenKind::Name => {
+                let range = token.range.clone();
+                self.position += 1;
+
+                Ok(Name {
+                    value: self.source[range.clone()].to_string(),
+                    range,
+                })
+            }
+            _ => Err(self.unexpected("a name")),
+        }
+    }
+
+    fn document(&mut self) -> ParseResult<Vec<Definition>> {
+        let mut definitions = Vec::new();
+
+        if self.peek().is_none() {
+            return Err(GraphQlError::new(0..0, "the document is empty"));
+        }
+
+        while self.peek().is_some() {
+            definitions.push(self.definition()?);
+        }
+
+        Ok(definitions)
+    }
+
+    fn definition(&mut self) -> ParseResult<Definition> {
+        match self.peek_text() {
+            Some("{") => Ok(Definition::Operation(Operation {
+                kind: "query".to_string(),
+                keyword: self.error_range(),
+                name: None,
+                variables: Vec::new(),
+                directives: Vec::new(),
+                selection_set: self.selection_set()?,
+            })),
+            Some(kind @ ("query" | "mutation" | "subscription")) => {
+                let keyword = self.next().map(|token| token.range).unwrap_or_default();
+                let name = match self.peek() {
+                    Some(token) if token.kind == TokenKind::Name => Some(self.name()?),
+                    _ => None,
+                };
+                let variables = if self.peek_is("(") {
+                    self.variable_definitions()?
+                } else {
+                    Vec::new()
+                };
+
+                Ok(Definition::Operation(Operation {
+                    kind: kind.to_string(),
+                    keyword,
+                    name,
+                    variables,
+                    directives: self.directives()?,
+                    selection_set: self.selection_set()?,
+                }))
+            }
+            Some("fragment") => {
+                self.next();
+                let name = self.name()?;
+                if name.value == "on" {
+                    return Err(GraphQlError::new(
+                        name.range,
+                        "a fragment can't be named on",
+                    ));
+                }
+                self.expect("on")?;
+
+                Ok(Definition::Fragment(Fragment {
+                    name,
+                    type_condition: self.name()?,
+                    directives: self.directives()?,
+                    selection_set: self.selection_set()?,
+                }))
+            }
+            Some(
+                "schema" | "scalar" | "type" | "interface" | "union" | "enum" | "input"
+                | "directive" | "extend",
+            ) => Err(GraphQlError::new(
+                self.error_range(),
+                "only operations and fragments can be sent, not type definitions",
+            )),
+            _ => Err(self.unexpected("an operation or a fragment")),
+        }
+    }
+
+    fn variable_definitions(&mut self) -> ParseResult<Vec<VariableDefinition>> {
+        self.expect("(")?;
+        let mut variables = Vec::new();
+
+        while !self.eat(")") {
+            let dollar = self.expect("$")?;
+            let mut name = self.name()?;
+            name.range.start = dollar.start;
+            self.expect(":")?;
+            let start = self.error_range().start;
+            let ty = self.type_ref()?;
+            let ty_range = start..self.tokens[self.position - 1].range.end;
+            let default = if self.eat("=") {
+                Some(self.value(true)?)
+            } else {
+                None
+            };
+            self.directives()?;
+
+            variables.push(VariableDefinition {
+                name,
+                ty,
+                ty_range,
+                default,
+            });
+        }
+
+        Ok(variables)
+    }
+
+    fn type_ref(&mut self) -> ParseResult<TypeRef> {
+        let ty = if self.eat("[") {
+            let inner = self.type_ref()?;
+            self.expect("]")?;
+            TypeRef::List(Box::new(inner))
+        } else {
+            TypeRef::Named(self.name()?.value)
+        };
+
+        if self.eat("!") {
+            Ok(TypeRef::NonNull(Box::new(ty)))
+        } else {
+            Ok(ty)
+        }
+    }
+
+    fn directives(&mut self) -> ParseResult<Vec<Directive>> {
+        let mut directives = Vec::new();
+
+        while self.peek_is("@") {
+            let at = self.next().map(|token| token.range).unwrap_or_default();
+            let mut name = self.name()?;
+            name.range.start = at.start;
+
+            directives.push(Directive {
+                name,
+                arguments: self.arguments()?,
+            });
+        }
+
+        Ok(directives)
+    }
+
+    fn arguments(&mut self) -> ParseResult<Vec<Argument>> {
+        let mut arguments = Vec::new();
+
+        if !self.eat("(") {
+            return Ok(arguments);
+        }
+
+        while !self.eat(")") {
+            if self.peek().is_none() {
+                return Err(self.unexpected("\")\""));
+            }
+            let name = self.name()?;
+            self.expect(":")?;
+
+            arguments.push(Argument {
+                name,
+                value: self.value(false)?,
+            });
+        }
+
+        Ok(arguments)
+    }
+
+    fn value(&mut self, constant: bool) -> ParseResult<ValueNode> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.unexpected("a value"));
+        };
+        let text = &self.source[token.range.clone()];
+        let start = token.range.start;
+
+        let value = match (token.kind, text) {
+            (TokenKind::Punctuator, "$") if !constant => {
+                self.next();
+                let name = self.name()?;
+                return Ok(ValueNode {
+                    value: Value::Variable(name.value),
+                    range: start..name.range.end,
+                });
+            }
+            (TokenKind::Punctuator, "[") => {
+                self.next();
+                let mut values = Vec::new();
+                while !self.peek_is("]") {
+                    values.push(self.value(constant)?);
+                }
+                let end = self.expect("]")?.end;
+                return Ok(ValueNode {
+                    value: Value::List(values),
+                    range: start..end,
+                });
+            }
+            (TokenKind::Punctuator, "{") => {
+                self.next();
+                let mut fields = Vec::new();
+                while !self.peek_is("}") {
+                    let name = self.name()?;
+                    self.expect(":")?;
+                    fields.push((name, self.value(constant)?));
+                }
+                let end = self.expect("}")?.end;
+                return Ok(ValueNode {
+                    value: Value::Object(fields),
+                    range: start..end,
+                });
+            }
+            (TokenKind::Number, text) if text.contains(['.', 'e', 'E']) => Value::Float,
+            (TokenKind::Number, _) => Value::Int,
+            (TokenKind::String, _) => Value::String,
+            (TokenKind::Name, "true" | "false") => Value::Boolean,
+            (TokenKind::Name, "null") => Value::Null,
+            (TokenKind::Name, name) => Value::Enum(name.to_string()),
+            _ => return Err(self.unexpected("a value")),
+        };
+        self.next();
+
+        Ok(ValueNode {
+            value,
+            range: token.range,
+        })
+    }
+
+    fn selection_set(&mut self) -> ParseResult<SelectionSet> {
+        self.expect("{")?;
+        let mut selections = Vec::new();
+
+        while !self.eat("}") {
+            if self.peek().is_none() {
+                return Err(self.unexpected("\"}\""));
+            }
+            selections.push(self.selection()?);
+        }
+
+        if selections.is_empty() {
+            return Err(GraphQlError::new(
+                self.tokens[self.position - 1].range.clone(),
+                "a selection set can't be empty",
+            ));
+        }
+
+        Ok(SelectionSet { selections })
+    }
+
+    fn selection(&mut self) -> ParseResult<Selection> {
+        if self.eat("...") {
+            if self.peek_is("on") {
+                self.next();
+                return Ok(Selection::InlineFragment {
+                    type_condition: Some(self.name()?),
+                    directives: self.directives()?,
+                    selection_set: self.selection_set()?,
+                });
+            }
+
+            if self.peek_is("@") || self.peek_is("{") {
+                return Ok(Selection::InlineFragment {
+                    type_condition: None,
+                    directives: self.directives()?,
+                    selection_set: self.selection_set()?,
+                });
+            }
+
+            return Ok(Selection::FragmentSpread {
+                name: self.name()?,
+                directives: self.directives()?,
+            });
+        }
+
+        let mut name = self.name()?;
+        if self.eat(":") {
+            name = self.name()?;
+        }
+
+        Ok(Selection::Field(Field {
+            name,
+            arguments: self.arguments()?,
+            directives: self.directives()?,
+            selection_set: if self.peek_is("{") {
+                Some(self.selection_set()?)
+            } else {
+                None
+            },
+        }))
+    }
+}
+
+fn parse(source: &str) -> ParseResult<Vec<Definition>> {
+    Parser::new(source)?.document()
+}
+
+/// The names of the operations of `query`, in order, leaving out
+/// anonymous ones. Empty when the document doesn't parse.
+pub fn operation_names(query: &str) -> Vec<String> {
+    parse(query)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|definition| match definition {
+            Definition::Operation(operation) => operation.name.map(|name| name.value),
+            Definition::Fragment(_) => None,
+        })
+        .collect()
+}
+
+/// The problems of `query`: its syntax errors and, given a schema, what it
+/// asks for that the schema doesn't have.
+pub fn validate_query(query: &str, schema: Option<&GraphQlSchema>) -> Vec<GraphQlError> {
+    let definitions = match parse(query) {
+        Ok(definitions) => definitions,
+        Err(error) => return vec![error],
+    };
+
+    let mut validator = Validator {
+        schema,
+        fragments: HashMap::new(),
+        errors: Vec::new(),
+    };
+    validator.validate(&definitions);
+    validator.errors.sort_by_key(|error| error.range.start);
+
+    validator.errors
+}
+
+struct Validator<'a> {
+    schema: Option<&'a GraphQlSchema>,
+    fragments: HashMap<&'a str, &'a Fragment>,
+    errors: Vec<GraphQlError>,
+}
+
+impl<'a> Validator<'a> {
+    fn error(&mut self, range: Range<usize>, message: impl Into<String>) {
+        self.errors.push(GraphQlError::new(range, message));
+    }
+
+    fn validate(&mut self, definitions: &'a [Definition]) {
+        let operations = definitions
+            .iter()
+            .filter_map(|definition| match definition {
+                Definition::Operation(operation) => Some(operation),
+                Definition::Fragment(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        let mut operation_names = HashSet::new();
+        for operation in &operations {
+            match &operation.name {
+                Some(name) if !operation_names.insert(name.value.as_str()) => self.error(
+                    name.range.clone(),
+                    format!("there is already an operation named {}", name.value),
+                ),
+                None if operations.len() > 1 => self.error(
+                    operation.keyword.clone(),
+                    "an anonymous operation must be the only one of the document",
+                ),
+                _ => {}
+            }
+        }
+
+        for definition in definitions {
+            if let Definition::Fragment(fragment) = definition
+                && self
+                    .fragments
+                    .insert(&fragment.name.value, fragment)
+                    .is_some()
+            {
+                self.error(
+                    fragment.name.range.clone(),
+                    format!("there is already a fragment named {}", fragment.name.value),
+                );
+            }
+        }
+
+        let mut used_fragments = HashSet::new();
+        for operation in &operations {
+            self.validate_operation(operation, &mut used_fragments);
+        }
+
+        for definition in definitions {
+            if let Definition::Fragment(fragment) = definition {
+                if !used_fragments.contains(fragment.name.value.as_str()) {
+                    self.error(
+                        fragment.name.range.clone(),
+                        format!("fragment {} is never used", fragment.name.value),
+                    );
+                }
+
+                let ty = self.composite_type(&fragment.type_condition);
+                self.validate_directives(&fragment.directives);
+                self.validate_selection_set(&fragment.selection_set, ty);
+            }
+        }
+    }
+
+    fn validate_operation(&mut self, operation: &'a Operation, used: &mut HashSet<&'a str>) {
+        let root = match self.schema {
+            Some(schema) => match schema.root_type(&operation.kind) {
+                Some(root) => Some(root),
+                None => {
+                    let range = operation
+                        .name
+                        .as_ref()
+                        .map_or(operation.keyword.clone(), |name| name.range.clone());
+                    self.error(
+                        range,
+                        format!("the schema doesn't support {} operations", operation.kind),
+                    );
+                    return;
+                }
+            },
+            None => None,
+        };
+
+        let mut defined = HashSet::new();
+        for variable in &operation.variables {
+            let name = &variable.name.value;
+            if !defined.insert(name.as_str()) {
+                self.error(
+                    variable.name.range.clone(),
+                    format!("there is already a variable named ${name}"),
+                );
+            }
+
+            if let Some(schema) = self.schema {
+                match schema.get_type(variable.ty.name()) {
+                    None => self.error(
+                        variable.ty_range.clone(),
+                        format!("unknown type {}", variable.ty.name()),
+                    ),
+                    Some(ty) if !ty.kind.is_input() => self.error(
+                        variable.ty_range.clone(),
+                        format!("${name} can't be of the output type {}", ty.name),
+                    ),
+                    Some(_) => {}
+                }
+            }
+
+            if let Some(default) = &variable.default {
+                self.validate_value(default, &variable.ty);
+            }
+        }
+
+        self.validate_directives(&operation.directives);
+        self.validate_selection_set(&operation.selection_set, root);
+
+        let mut usages = Vec::new();
+        let mut visited = HashSet::new();
+        self.collect_usages(&operation.selection_set, &mut usages, &mut visited);
+        for directive in &operation.directives {
+            arguments_usages(&directive.arguments, &mut usages);
+        }
+        used.extend(visited);
+
+        for (name, range) in usages {
+            if !defined.contains(name) {
+                let message = match &operation.name {
+                    Some(operation) => {
+                        format!("${name} isn't defined by operation {}", operation.value)
+                    }
+                    None => format!("${name} isn't defined by the operation"),
+                };
+                self.error(range, message);
+            }
+        }
+    }
+
+    /// The variables used by `selection_set` and the fragments it spreads,
+    /// recording the fragments in `visited`.
+    fn collect_usages(
+        &self,
+        selection_set: &'a SelectionSet,
+        usages: &mut Vec<(&'a str, Range<usize>)>,
+        visited: &mut HashSet<&'a str>,
+    ) {
+        for selection in &selection_set.selections {
+            match selection {
+                Selection::Field(field) => {
+                    arguments_usages(&field.arguments, usages);
+                    for directive in &field.directives {
+                        arguments_usages(&directive.arguments, usages);
+                    }
+                    if let Some(selection_set) = &field.selection_set {
+                        self.collect_usages(selection_set, usages, visited);
+                    }
+                }
+                Selection::FragmentSpread { name, directives } => {
+                    for directive in directives {
+                        arguments_usages(&directive.arguments, usages);
+                    }
+                    if visited.insert(&name.value)
+                        && let Some(fragment) = self.fragments.get(name.value.as_str())
+                    {
+                        self.collect_usages(&fragment.selection_set, usages, visited);
+                    }
+                }
+                Selection::InlineFragment {
+                    directives,
+                    selection_set,
+                    ..
+                } => {
+                    for directive in directives {
+                        arguments_usages(&directive.arguments, usages);
+                    }
+                    self.collect_usages(selection_set, usages, visited);
+                }
+            }
+        }
+    }
+
+    /// The type a fragment applies to, when the schema has it and fields
+    /// can be selected on it.
+    fn composite_type(&mut self, name: &Name) -> Option<&'a SchemaType> {
+        let schema = self.schema?;
+
+        match schema.get_type(&name.value) {
+            None => {
+                self.error(name.range.clone(), format!("unknown type {}", name.value));
+                None
+            }
+            Some(ty) if !ty.kind.is_composite() => {
+                self.error(
+                    name.range.clone(),
+                    format!(
+                        "fields can't be selected on {} {}",
+                        ty.kind.as_str(),
+                        ty.name
+                    ),
+                );
+                None
+            }
+            Some(ty) => Some(ty),
+        }
+    }
+
+    fn validate_selection_set(
+        &mut self,
+        selection_set: &'a SelectionSet,
+        parent: Option<&'a SchemaType>,
+    ) {
+        for selection in &selection_set.selections {
+            match selection {
+                Selection::Field(field) => self.validate_field(field, parent),
+                Selection::FragmentSpread { name, directives } => {
+                    if !self.fragments.contains_key(name.value.as_str()) {
+                        self.error(
+                            name.range.clone(),
+                            format!("unknown fragment {}", name.value),
+                        );
+                    }
+                    self.validate_directives(directives);
+                }
+                Selection::InlineFragment {
+                    type_condition,
+                    directives,
+                    selection_set,
+                } => {
+                    let ty = match type_condition {
+                        Some(name) => self.composite_type(name),
+                        None => parent,
+                    };
+                    self.validate_directives(directives);
+                    self.validate_selection_set(selection_set, ty);
+                }
+            }
+        }
+    }
+
+    fn validate_field(&mut self, field: &'a Field, parent: Option<&'a SchemaType>) {
+        self.validate_directives(&field.directives);
+
+        let (Some(schema), Some(parent)) = (self.schema, parent) else {
+            if let Some(selection_set) = &field.selection_set {
+                self.validate_selection_set(selection_set, None);
+            }
+            return;
+        };
+
+        let name = &field.name.value;
+        if name == "__typename" {
+            if field.selection_set.is_some() {
+                self.error(field.name.range.clone(), "__typename has no fields");
+            }
+            return;
+        }
+
+        let definition = match parent.field(name) {
+            Some(definition) => Some(definition),
+            None if Some(&parent.name) == schema.query_type.as_ref()
+                && matches!(name.as_str(), "__schema" | "__type") =>
+            {
+                None
+            }
+            None => {
+                self.error(
+                    field.name.range.clone(),
+                    format!("{} has no field {name}", parent.name),
+                );
+                return;
+            }
+        };
+
+        let ty = match definition {
+            Some(definition) => {
+                self.validate_arguments(
+                    &field.arguments,
+                    &definition.args,
+                    &field.name,
+                    &format!("{}.{name}", parent.name),
+                );
+                schema.get_type(definition.ty.name())
+            }
+            None => schema.get_type(if name == "__schema" {
+                "__Schema"
+            } else {
+                "__Type"
+            }),
+        };
+
+        match (ty, &field.selection_set) {
+            (Some(ty), Some(_)) if !ty.kind.is_composite() => self.error(
+                field.name.range.clone(),
+                format!(
+                    "{name} is a {} {} and has no fields",
+                    ty.kind.as_str(),
+                    ty.name
+                ),
+            ),
+            (Some(ty), None) if ty.kind.is_composite() => self.error(
+                field.name.range.clone(),
+                format!(
+                    "{name} is of type {} and needs a selection of its fields",
+                    ty.name
+                ),
+            ),
+            (ty, Some(selection_set)) => self.validate_selection_set(selection_set, ty),
+            _ => {}
+        }
+    }
+
+    fn validate_directives(&mut self, directives: &'a [Directive]) {
+        let Some(schema) = self.schema else {
+            return;
+        };
+
+        for directive in directives {
+            match schema.directive(&directive.name.value) {
+                Some(definition) => self.validate_arguments(
+                    &directive.arguments,
+                    &definition.args,
+                    &directive.name,
+                    &format!("@{}", definition.name),
+                ),
+                None => self.error(
+                    directive.name.range.clone(),
+                    format!("unknown directive @{}", directive.name.value),
+                ),
+            }
+        }
+    }
+
+    fn validate_arguments(
+        &mut self,
+        arguments: &[Argument],
+        definitions: &[InputValue],
+        owner: &Name,
+        owner_label: &str,
+    ) {
+        for argument in arguments {
+            match definitions
+                .iter()
+                .find(|definition| definition.name == argument.name.value)
+            {
+                Some(definition) => self.validate_value(&argument.value, &definition.ty),
+                None => self.error(
+                    argument.name.range.clone(),
+                    format!("{owner_label} has no argument {}", argument.name.value),
+                ),
+            }
+        }
+
+        for definition in definitions
+            .iter()
+            .filter(|definition| definition.is_required())
+        {
+            if !arguments
+                .iter()
+                .any(|argument| argument.name.value == definition.name)
+            {
+                self.error(
+                    owner.range.clone(),
+                    format!(
+                        "{owner_label} needs the argument {}: {}",
+                        definition.name, definition.ty
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Checks the literals of `value` against `ty`, leaving variables to
+    /// the server.
+    fn validate_value(&mut self, value: &ValueNode, ty: &TypeRef) {
+        let Some(schema) = self.schema else {
+            return;
+        };
+
+        let mismatch = |expected: &TypeRef| format!("expected a value of type {expected}");
+
+        match (&value.value, ty) {
+            (Value::Variable(_), _) => {}
+            (Value::Null, TypeRef::NonNull(_)) => {
+                self.error(value.range.clone(), format!("{ty} can't be null"))
+            }
+            (Value::Null, _) => {}
+            (_, TypeRef::NonNull(inner)) => self.validate_value(value, inner),
+            (Value::List(values), TypeRef::List(inner)) => {
+                for value in values {
+                    self.validate_value(value, inner);
+                }
+            }
+            // A single value is accepted where a list is expected.
+            (_, TypeRef::List(inner)) => self.validate_value(value, inner),
+            (_, TypeRef::Named(name)) => {
+                let Some(named) = schema.get_type(name) else {
+                    return;
+                };
+
+                match (&value.value, named.kind) {
+                    (Value::Enum(variant), TypeKind::Enum) => {
+                        if !named.enum_values.iter().any(|value| value.name == *variant) {
+                            self.error(
+                                value.range.clone(),
+                                format!("{name} has no value {variant}"),
+                            );
+                        }
+                    }
+                    (Value::Object(fields), TypeKind::InputObject) => {
+                        for (field, field_value) in fields {
+                            match named
+                                .input_fields
+                                .iter()
+                                .find(|input| input.name == field.value)
+                            {
+                                Some(input) => self.validate_value(field_value, &input.ty),
+                                None => self.error(
+                                    field.range.clone(),
+                                    format!("{name} has no field {}", field.value),
+                                ),
+                            }
+                        }
+                        for input in named
+                            .input_fields
+                            .iter()
+                            .filter(|input| input.is_required())
+                        {
+                            if !fields.iter().any(|(field, _)| field.value == input.name) {
+                                self.error(
+                                    value.range.clone(),
+                                    format!("{name} needs the field {}: {}", input.name, input.ty),
+                                );
+                            }
+                        }
+                    }
+                    (_, TypeKind::Scalar) => {
+                        let valid = match name.as_str() {
+                            "Int" => matches!(value.value, Value::Int),
+                            "Float" => matches!(value.value, Value::Int | Value::Float),
+                            "String" => matches!(value.value, Value::String),
+                            "Boolean" => matches!(value.value, Value::Boolean),
+                            "ID" => matches!(value.value, Value::Int | Value::String),
+                            // Custom scalars accept whatever the server makes of them.
+                            _ => true,
+                        };
+                        if !valid {
+                            self.error(value.range.clone(), mismatch(ty));
+                        }
+                    }
+                    _ => self.error(value.range.clone(), mismatch(ty)),
+                }
+            }
+        }
+    }
+}
+
+fn arguments_usages<'a>(arguments: &'a [Argument], usages: &mut Vec<(&'a str, Range<usize>)>) {
+    fn value_usages<'a>(value: &'a ValueNode, usages: &mut Vec<(&'a str, Range<usize>)>) {
+        match &value.value {
+            Value::Variable(name) => usages.push((name, value.range.clone())),
+            Value::List(values) => {
+                for value in values {
+                    value_usages(value, usages);
+                }
+            }
+            Value::Object(fields) => {
+                for (_, value) in fields {
+                    value_usages(value, usages);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for argument in arguments {
+        value_usages(&argument.value, usages);
+    }
+}
+
+/// Where in the document completions are asked for, as found by walking
+/// the tokens before the cursor.
+enum Frame<'s> {
+    /// Inside the selection set of a type, unknown when the schema doesn't
+    /// have it.
+    Selection(Option<&'s SchemaType>),
+    /// Inside the arguments of a field or directive, or an input object,
+    /// closed by `closing`.
+    Inputs {
+        inputs: &'s [InputValue],
+        current: Option<&'s InputValue>,
+        closing: &'static str,
+    },
+    /// Inside a list value of the named type.
+    List(Option<&'s str>),
+    /// Inside the variable definitions of an operation.
+    Variables,
+}
+
+/// The suggestions for the word at `offset` of `query`, given the schema.
+pub fn complete_query(
+    query: &str,
+    offset: usize,
+    schema: &GraphQlSchema,
+) -> Vec<GraphQlCompletion> {
+    let Some(before) = query.get(..offset) else {
+        return Vec::new();
+    };
+
+    let is_name = |ch: char| ch == '_' || ch.is_ascii_alphanumeric();
+    let start = before.trim_end_matches(is_name).len();
+    let end = offset + query[offset..].len() - query[offset..].trim_start_matches(is_name).len();
+    let prefix = &query[start..offset];
+
+    // Nothing to suggest within comments and strings, which either fail to
+    // end before the cursor or end right at it.
+    let (tokens, error) = tokenize(before);
+    if error.is_some()
+        || tokens
+            .last()
+            .is_some_and(|last| last.kind == TokenKind::Comment && last.range.end == offset)
+    {
+        return Vec::new();
+    }
+
+    let tokens = tokens
+        .into_iter()
+        .filter(|token| token.kind != TokenKind::Comment && token.range.end <= start)
+        .collect::<Vec<_>>();
+    let text = |token: &Token| &query[token.range.clone()];
+    let fragment_names = fragment_names(query);
+
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut variables = Vec::new();
+    // What a following `{` or `(` opens.
+    let mut pending_selection: Option<Option<&SchemaType>> = None;
+    let mut pending_inputs: Option<&[InputValue]> = None;
+
+    for (index, token) in tokens.iter().enumerate() {
+        let previous = index.checked_sub(1).map(|index| text(&tokens[index]));
+        let value = text(token);
+        let is_name = token.kind == TokenKind::Name;
+
+        match stack.last_mut() {
+            None => match value {
+                "query" | "mutation" | "subscription" if is_name => {
+                    pending_selection = Some(schema.root_type(value));
+                    variables.clear();
+                }
+                "{" => {
+                    stack.push(Frame::Selection(
+                        pending_selection
+                            .take()
+                            .unwrap_or(schema.root_type("query")),
+                    ));
+                    pending_inputs = None;
+                }
+                "(" if previous.is_some_and(|previous| previous != "@")
+                    && pending_inputs.is_none() =>
+                {
+                    stack.push(Frame::Variables)
+                }
+                "(" => stack.push(Frame::Inputs {
+                    inputs: pending_inputs.take().unwrap_or_default(),
+                    current: None,
+                    closing: ")",
+                }),
+                _ if is_name && previous == Some("on") => {
+                    pending_selection = Some(schema.get_type(value));
+                }
+                _ if is_name && previous == Some("@") => {
+                    pending_inputs = schema
+                        .directive(value)
+                        .map(|directive| directive.args.as_slice());
+                }
+                _ => {}
+            },
+            Some(Frame::Selection(parent)) => {
+                let parent = *parent;
+
+                match value {
+                    "}" => {
+                        stack.pop();
+                        pending_selection = None;
+                    }
+                    "{" => {
+                        let ty = pending_selection.take().unwrap_or(None);
+                        stack.push(Frame::Selection(ty));
+                        pending_inputs = None;
+                    }
+                    "(" => stack.push(Frame::Inputs {
+                        inputs: pending_inputs.take().unwrap_or_default(),
+                        current: None,
+                        closing: ")",
+                    }),
+                    "..." => {
+                        pending_selection = Some(parent);
+                        pending_inputs = None;
+                    }
+                    _ if !is_name => {}
+                    _ if previous == Some("@") => {
+                        pending_inputs = schema
+                            .directive(value)
+                            .map(|directive| directive.args.as_slice());
+                    }
+                    "on" if previous == Some("...") => {}
+                    _ if previous == Some("on")
+                        && index >= 2
+                        && text(&tokens[index - 2]) == "..." =>
+                    {
+                        pending_selection = Some(schema.get_type(value));
+                    }
+                    _ if previous == Some("...") => pending_selection = None,
+                    _ => {
+                        let field = parent.and_then(|parent| parent.field(value));
+                        pending_selection = Some(
+                            field
+                                .and_then(|field| schema.get_type(field.ty.name()))
+                                .or_else(|| match value {
+                                    "__schema" => schema.get_type("__Schema"),
+                                    "__type" => schema.get_type("__Type"),
+                                    _ => None,
+                                }),
+                        );
+                        pending_inputs = field.map(|field| field.args.as_slice());
+                    }
+                }
+            }
+            Some(Frame::Inputs {
+                inputs,
+                current,
+                closing,
+            }) => {
+                let inputs = *inputs;
+                let input_type = current.map(|current| current.ty.name());
+
+                match value {
+                    _ if value == *closing => {
+                        stack.pop();
+                    }
+                    "{" => stack.push(Frame::Inputs {
+                        inputs: input_fields(schema, input_type),
+                        current: None,
+                        closing: "}",
+                    }),
+                    "[" => stack.push(Frame::List(input_type)),
+                    _ if is_name && previous != Some(":") && previous != Some("$") => {
+                        *current = inputs.iter().find(|input| input.name == value);
+                    }
+                    _ => {}
+                }
+            }
+            Some(Frame::List(ty)) => {
+                let ty = *ty;
+
+                match value {
+                    "]" => {
+                        stack.pop();
+                    }
+                    "{" => stack.push(Frame::Inputs {
+                        inputs: input_fields(schema, ty),
+                        current: None,
+                        closing: "}",
+                    }),
+                    "[" => stack.push(Frame::List(ty)),
+                    _ => {}
+                }
+            }
+            Some(Frame::Variables) => match value {
+                ")" => {
+                    stack.pop();
+                }
+                "{" => stack.push(Frame::Inputs {
+                    inputs: &[],
+                    current: None,
+                    closing: "}",
+                }),
+                "[" if previous == Some("=") => stack.push(Frame::List(None)),
+                _ if is_name && previous == Some("$") => variables.push(value.to_string()),
+                _ => {}
+            },
+        }
+    }
+
+    let previous = tokens.last().map(text);
+    let before_previous = tokens
+        .len()
+        .checked_sub(2)
+        .map(|index| text(&tokens[index]));
+    let mut completions = Vec::new();
+    let mut push = |label: &str, detail: String, insert: String| {
+        completions.push(GraphQlCompletion {
+            label: label.to_string(),
+            detail,
+            range: start..end,
+            insert,
+        });
+    };
+
+    let value_completions = |ty: Option<&str>, push: &mut dyn FnMut(&str, String, String)| match ty
+        .and_then(|ty| schema.get_type(ty))
+    {
+        Some(ty) if ty.kind == TypeKind::Enum => {
+            for value in &ty.enum_values {
+                push(&value.name, ty.name.clone(), value.name.clone());
+            }
+        }
+        Some(ty) if ty.name == "Boolean" => {
+            for value in ["true", "false"] {
+                push(value, ty.name.clone(), value.to_string());
+            }
+        }
+        _ => {}
+    };
+
+    if previous == Some("$") {
+        for variable in &variables {
+            push(variable, "variable".to_string(), variable.clone());
+        }
+    } else if previous == Some("@") {
+        for directive in &schema.directives {
+            push(
+                &directive.name,
+                "directive".to_string(),
+                directive.name.clone(),
+            );
+        }
+    } else {
+        match stack.last() {
+            None if previous == Some("on") => {
+                for ty in schema.user_types().filter(|ty| ty.kind.is_composite()) {
+                    push(&ty.name, ty.kind.as_str().to_string(), ty.name.clone());
+                }
+            }
+            None if previous.is_none() || previous == Some("}") => {
+                for keyword in KEYWORDS {
+                    push(keyword, "keyword".to_string(), keyword.to_string());
+                }
+            }
+            None => {}
+            Some(Frame::Selection(parent)) => {
+                if previous == Some("...") {
+                    push("on", "keyword".to_string(), "on ".to_string());
+                    for name in &fragment_names {
+                        push(name, "fragment".to_string(), name.clone());
+                    }
+                } else if previous == Some("on") && before_previous == Some("...") {
+                    let possible = parent.map(|parent| possible_types(schema, parent));
+                    for ty in schema.user_types().filter(|ty| ty.kind.is_composite()) {
+                        if possible
+                            .as_ref()
+                            .is_none_or(|possible| possible.contains(&ty.name.as_str()))
+                        {
+                            push(&ty.name, ty.kind.as_str().to_string(), ty.name.clone());
+                        }
+                    }
+                } else if let Some(parent) = parent {
+                    for field in &parent.fields {
+                        push(&field.name, field.ty.to_string(), field.name.clone());
+                    }
+                    push(
+                        "__typename",
+                        "String!".to_string(),
+                        "__typename".to_string(),
+                    );
+                }
+            }
+            Some(Frame::Inputs {
+                inputs, current, ..
+            }) => {
+                if previous == Some(":") {
+                    value_completions(current.map(|current| current.ty.name()), &mut push);
+                } else {
+                    for input in *inputs {
+                        push(
+                            &input.name,
+                            input.ty.to_string(),
+                            format!("{}: ", input.name),
+                        );
+                    }
+                }
+            }
+            Some(Frame::List(ty)) => value_completions(*ty, &mut push),
+            Some(Frame::Variables) => {
+                if matches!(previous, Some(":" | "[")) {
+                    for ty in schema.user_types().filter(|ty| ty.kind.is_input()) {
+                        push(&ty.name, ty.kind.as_str().to_string(), ty.name.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    filter_completions(completions, prefix)
+}
+
+/// The fields of the input object named `ty`, if it is one.
+fn input_fields<'s>(schema: &'s GraphQlSchema, ty: Option<&str>) -> &'s [InputValue] {
+    ty.and_then(|ty| schema.get_type(ty))
+        .map(|ty| ty.input_fields.as_slice())
+        .unwrap_or_default()
+}
+
+/// The types a fragment within a selection on `parent` can apply to.
+fn possible_types<'s>(schema: &'s GraphQlSchema, parent: &'s SchemaType) -> Vec<&'s str> {
+    let mut possible = vec![parent.name.as_str()];
+    possible.extend(parent.possible_types.iter().map(TypeRef::name));
+    possible.extend(parent.interfaces.iter().map(TypeRef::name));
+
+    // Interfaces implemented by the possible types are valid conditions too.
+    for ty in parent
+        .possible_types
+        .iter()
+        .filter_map(|ty| schema.get_type(ty.name()))
+    {
+        possible.extend(ty.interfaces.iter().map(TypeRef::name));
+    }
+
+    possible
+}
+
+/// The names of the fragments defined anywhere in the document.
+fn fragment_names(query: &str) -> Vec<String> {
+    let (tokens, _) = tokenize(query);
+    let tokens = tokens
+        .iter()
+        .filter(|token| token.kind != TokenKind::Comment)
+        .collect::<Vec<_>>();
+
+    tokens
+        .windows(2)
+        .filter(|pair| {
+            pair[0].kind == TokenKind::Name
+                && &query[pair[0].range.clone()] == "fragment"
+                && pair[1].kind == TokenKind::Name
+        })
+        .map(|pair| query[pair[1].range.clone()].to_string())
+        .collect()
+}
+
+/// Keeps the completions matching `prefix`, case-insensitively, those
+/// starting with it first.
+fn filter_completions(completions: Vec<GraphQlCompletion>, prefix: &str) -> Vec<GraphQlCompletion> {
+    let prefix = prefix.to_ascii_lowercase();
+    let (mut starting, containing): (Vec<_>, Vec<_>) = completions
+        .into_iter()
+        .filter(|completion| completion.label.to_ascii_lowercase().contains(&prefix))
+        .partition(|completion| completion.label.to_ascii_lowercase().starts_with(&prefix));
+
+    starting.extend(containing);
+    starting
+}
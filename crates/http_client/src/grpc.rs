@@ -0,0 +1,562 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt,
+    pin::Pin,
+    time::{Instant, SystemTime},
+};
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use bytes::{Buf as _, BufMut as _, Bytes, BytesMut};
+use futures::{StreamExt as _, channel::mpsc};
+use http::{HeaderMap, Method, StatusCode, header::CONTENT_TYPE};
+use http_body_util::{BodyExt as _, StreamBody};
+use hyper::body::Frame;
+use percent_encoding::percent_decode_str;
+use prost::Message as _;
+use url::Url;
+
+use crate::{
+    HttpBody, HttpRequest, HttpVersion, RequestOptions, build_request, open_connection,
+    pool::PoolKey,
+    protobuf::{DescriptorPool, decode_file_descriptor},
+    runtime,
+    timeout::timeout,
+};
+
+/// The reflection services tried in turn, the first one being the current
+/// one.
+const REFLECTION_SERVICES: [&str; 2] = [
+    "grpc.reflection.v1.ServerReflection",
+    "grpc.reflection.v1alpha.ServerReflection",
+];
+
+/// A call to a gRPC method, with its metadata.
+#[derive(Debug, Clone)]
+pub struct GrpcRequest {
+    /// The server, as `http`, `https`, `grpc` or `grpcs` URL. A path is kept
+    /// as a prefix of the method path.
+    pub url: Url,
+    /// The fully qualified name of the service.
+    pub service: String,
+    pub method: String,
+    /// Sent as headers, along with those gRPC requires.
+    pub metadata: Vec<(String, String)>,
+}
+
+/// What happens on a gRPC call, in order.
+#[derive(Debug, Clone)]
+pub enum GrpcEvent {
+    /// The server started answering, with these headers.
+    Headers {
+        headers: HeaderMap,
+    },
+    /// A message was written to the server.
+    Sent {
+        message: Bytes,
+        at: SystemTime,
+    },
+    Received {
+        message: Bytes,
+        at: SystemTime,
+    },
+}
+
+/// The status a gRPC call ended with, from the trailers of the response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrpcStatus {
+    pub code: u32,
+    pub message: String,
+    /// The trailers, or the headers of a trailers-only response.
+    pub trailers: HeaderMap,
+    /// How long the call took, from sending its headers.
+    pub elapsed: std::time::Duration,
+}
+
+impl GrpcStatus {
+    pub fn is_ok(&self) -> bool {
+        self.code == 0
+    }
+
+    pub fn code_name(&self) -> &'static str {
+        match self.code {
+            0 => "OK",
+            1 => "CANCELLED",
+            2 => "UNKNOWN",
+            3 => "INVALID_ARGUMENT",
+            4 => "DEADLINE_EXCEEDED",
+            5 => "NOT_FOUND",
+            6 => "ALREADY_EXISTS",
+            7 => "PERMISSION_DENIED",
+            8 => "RESOURCE_EXHAUSTED",
+            9 => "FAILED_PRECONDITION",
+            10 => "ABORTED",
+            11 => "OUT_OF_RANGE",
+            12 => "UNIMPLEMENTED",
+            13 => "INTERNAL",
+            14 => "UNAVAILABLE",
+            15 => "DATA_LOSS",
+            16 => "UNAUTHENTICATED",
+            _ => "UNKNOWN",
+        }
+    }
+
+    /// Reads the status from the `grpc-status` and `grpc-message` of
+    /// `trailers`.
+    fn from_trailers(trailers: HeaderMap, elapsed: std::time::Duration) -> Result<Self> {
+        let code = trailers
+            .get("grpc-status")
+            .ok_or_else(|| anyhow!("the response has no grpc-status"))?
+            .to_str()
+            .ok()
+            .and_then(|code| code.trim().parse().ok())
+            .ok_or_else(|| anyhow!("invalid grpc-status"))?;
+        let message = trailers
+            .get("grpc-message")
+            .and_then(|message| message.to_str().ok())
+            .map(|message| percent_decode_str(message).decode_utf8_lossy().into_owned())
+            .unwrap_or_default();
+
+        Ok(Self {
+            code,
+            message,
+            trailers,
+            elapsed,
+        })
+    }
+}
+
+impl fmt::Display for GrpcStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.code, self.code_name())?;
+        if !self.message.is_empty() {
+            write!(f, ": {}", self.message)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Queues the messages of a call, already encoded.
+#[derive(Debug, Clone)]
+pub struct GrpcSender(mpsc::UnboundedSender<Bytes>);
+
+impl GrpcSender {
+    /// Queues `message`, failing when the call is over or was half-closed.
+    pub fn send(&self, message: impl Into<Bytes>) -> Result<()> {
+        self.0
+            .unbounded_send(message.into())
+            .map_err(|_| anyhow!("the request stream is closed"))
+    }
+
+    /// Ends the request stream, telling the server no more messages come.
+    pub fn finish(&self) {
+        self.0.close_channel();
+    }
+}
+
+/// Calls a gRPC method on the http client runtime. Messages queued on the
+/// returned sender make up the request stream, which ends once the sender
+/// is finished or dropped, and the messages of the response come as
+/// events.
+///
+/// The returned future completes with the status of the call, which may be
+/// an error status, and fails when the call couldn't be made or wasn't
+/// answered in gRPC. Dropping it cancels the call.
+pub fn call_grpc(
+    request: GrpcRequest,
+    options: RequestOptions,
+) -> (
+    impl Future<Output = Result<GrpcStatus>> + Send + 'static,
+    GrpcSender,
+    mpsc::UnboundedReceiver<GrpcEvent>,
+) {
+    let (sender, outgoing) = mpsc::unbounded();
+    let (events, receiver) = mpsc::unbounded();
+
+    (
+        runtime::spawn(run(request, options, outgoing, events)),
+        GrpcSender(sender),
+        receiver,
+    )
+}
+
+/// The `http` or `https` URL a gRPC server at `url` is reached at.
+pub fn grpc_http_url(url: &Url) -> Result<Url> {
+    let scheme = match url.scheme() {
+        "http" | "grpc" => "http",
+        "https" | "grpcs" => "https",
+        scheme => bail!("unsupported gRPC scheme {scheme}"),
+    };
+    // `Url` won't turn a custom scheme into a special one in place.
+    let rest = &url.as_str()[url.scheme().len()..];
+
+    Url::parse(&format!("{scheme}{rest}")).with_context(|| format!("invalid gRPC URL {url}"))
+}
+
+async fn run(
+    request: GrpcRequest,
+    options: RequestOptions,
+    outgoing: mpsc::UnboundedReceiver<Bytes>,
+    events: mpsc::UnboundedSender<GrpcEvent>,
+) -> Result<GrpcStatus> {
+    let mut url = grpc_http_url(&request.url)?;
+    let scheme = url.scheme().to_string();
+    url.set_path(&format!(
+        "{}/{}/{}",
+        url.path().trim_end_matches('/'),
+        request.service,
+        request.method
+    ));
+    url.set_query(None);
+
+    // gRPC needs h2, which cleartext connections can only speak from the
+    // start.
+    let version = match options.version {
+        HttpVersion::Http2 | HttpVersion::Http2PriorKnowledge => options.version,
+        _ if scheme == "https" => HttpVersion::Http2,
+        _ => HttpVersion::Http2PriorKnowledge,
+    };
+    let options = RequestOptions { version, ..options };
+
+    let mut headers = vec![
+        ("Content-Type".to_string(), "application/grpc".to_string()),
+        ("TE".to_string(), "trailers".to_string()),
+        ("Accept-Encoding".to_string(), "identity".to_string()),
+    ];
+    headers.extend(request.metadata);
+    let http_request = HttpRequest {
+        method: Method::POST,
+        url,
+        headers,
+        body: HttpBody::default(),
+    };
+
+    let key = PoolKey::new(&http_request.url, &options);
+    let (mut connection, _) = open_connection(&http_request.url, &options, &key).await?;
+    if !connection.sender.is_h2() {
+        bail!("the server doesn't speak h2, which gRPC needs");
+    }
+
+    let body_events = events.clone();
+    let body = StreamBody::new(outgoing.map(move |message| {
+        body_events
+            .unbounded_send(GrpcEvent::Sent {
+                message: message.clone(),
+                at: SystemTime::now(),
+            })
+            .ok();
+
+        let mut framed = BytesMut::with_capacity(5 + message.len());
+        framed.put_u8(0);
+        framed.put_u32(message.len() as u32);
+        framed.put_slice(&message);
+        Ok(Frame::data(framed.freeze()))
+    }))
+    .boxed_unsync();
+    let (parts, _) = build_request(&http_request, 0, true, None)?.into_parts();
+
+    let start = Instant::now();
+    let response = connection
+        .sender
+        .send(http::Request::from_parts(parts, body));
+    let response = timeout(
+        options.timeouts.read,
+        || "waiting for the response headers".to_string(),
+        async { Ok(response.await?) },
+    )
+    .await?;
+
+    let status = response.status();
+    if status != StatusCode::OK {
+        bail!("the server answered {status}");
+    }
+    let headers = response.headers().clone();
+    let content_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if !content_type.starts_with("application/grpc") {
+        bail!("expected application/grpc, got {content_type:?}");
+    }
+
+    // Trailers-only responses carry the status in their headers.
+    if headers.contains_key("grpc-status") {
+        events
+            .unbounded_send(GrpcEvent::Headers {
+                headers: HeaderMap::new(),
+            })
+            .ok();
+        return GrpcStatus::from_trailers(headers, start.elapsed());
+    }
+    events.unbounded_send(GrpcEvent::Headers { headers }).ok();
+
+    let mut body = response.into_body();
+    let mut buffer = BytesMut::new();
+    while let Some(frame) = body.frame().await {
+        let frame = frame.context("reading the response")?;
+
+        match frame.into_data() {
+            Ok(data) => {
+                buffer.extend_from_slice(&data);
+                for message in take_messages(&mut buffer)? {
+                    events
+                        .unbounded_send(GrpcEvent::Received {
+                            message,
+                            at: SystemTime::now(),
+                        })
+                        .ok();
+                }
+            }
+            Err(frame) => {
+                if let Ok(trailers) = frame.into_trailers() {
+                    if !buffer.is_empty() {
+                        bail!("the response ended in the middle of a message");
+                    }
+                    return GrpcStatus::from_trailers(trailers, start.elapsed());
+                }
+            }
+        }
+    }
+
+    bail!("the response ended without a grpc-status")
+}
+
+/// Splits the complete length-prefixed messages off the start of `buffer`.
+fn take_messages(buffer: &mut BytesMut) -> Result<Vec<Bytes>> {
+    let mut messages = Vec::new();
+
+    while buffer.len() >= 5 {
+        let compressed = buffer[0] & 1 != 0;
+        let len = u32::from_be_bytes([buffer[1], buffer[2], buffer[3], buffer[4]]) as usize;
+        if buffer.len() < 5 + len {
+            break;
+        }
+        if compressed {
+            bail!("the server sent a compressed message, which isn't supported");
+        }
+
+        buffer.advance(5);
+        messages.push(buffer.split_to(len).freeze());
+    }
+
+    Ok(messages)
+}
+
+/// Asks the server at `url` for its services and their types over server
+/// reflection, on the http client runtime.
+pub fn reflect_services(
+    url: Url,
+    metadata: Vec<(String, String)>,
+    options: RequestOptions,
+) -> impl Future<Output = Result<DescriptorPool>> + Send + 'static {
+    runtime::spawn(async move {
+        let mut last_status = None;
+
+        for service in REFLECTION_SERVICES {
+            let request = GrpcRequest {
+                url: url.clone(),
+                service: service.to_string(),
+                method: "ServerReflectionInfo".to_string(),
+                metadata: metadata.clone(),
+            };
+
+            match Reflection::open(request, options.clone()).load().await? {
+                Ok(pool) => return Ok(pool),
+                // Older servers only have the v1alpha service.
+                Err(status) if status.code == 12 => last_status = Some(status),
+                Err(status) => bail!("server reflection failed with {status}"),
+            }
+        }
+
+        match last_status {
+            Some(status) => bail!("the server doesn't support reflection ({status})"),
+            None => unreachable!("reflection is tried at least once"),
+        }
+    })
+}
+
+/// A reflection stream, where each request is answered by one response.
+struct Reflection {
+    done: Pin<Box<dyn Future<Output = Result<GrpcStatus>> + Send>>,
+    sender: GrpcSender,
+    events: mpsc::UnboundedReceiver<GrpcEvent>,
+}
+
+/// The questions of `ServerReflectionRequest` asked here, only one of
+/// which is set.
+#[derive(Clone, PartialEq, prost::Message)]
+struct ReflectionRequest {
+    #[prost(string, optional, tag = "3")]
+    file_by_filename: Option<String>,
+    #[prost(string, optional, tag = "4")]
+    file_containing_symbol: Option<String>,
+    #[prost(string, optional, tag = "7")]
+    list_services: Option<String>,
+}
+
+/// The answers of `ServerReflectionResponse` to those questions.
+#[derive(Clone, PartialEq, prost::Message)]
+struct ReflectionResponse {
+    #[prost(message, optional, tag = "4")]
+    file_descriptor_response: Option<FileDescriptorResponse>,
+    #[prost(message, optional, tag = "6")]
+    list_services_response: Option<ListServiceResponse>,
+    #[prost(message, optional, tag = "7")]
+    error_response: Option<ErrorResponse>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct FileDescriptorResponse {
+    #[prost(bytes = "bytes", repeated, tag = "1")]
+    file_descriptor_proto: Vec<Bytes>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ListServiceResponse {
+    #[prost(message, repeated, tag = "1")]
+    service: Vec<ServiceResponse>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ServiceResponse {
+    #[prost(string, tag = "1")]
+    name: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ErrorResponse {
+    #[prost(int32, tag = "1")]
+    error_code: i32,
+    #[prost(string, tag = "2")]
+    error_message: String,
+}
+
+/// What a reflection response holds.
+enum ReflectionAnswer {
+    Services(Vec<String>),
+    Files(Vec<Bytes>),
+    Error(String),
+}
+
+impl Reflection {
+    fn open(request: GrpcRequest, options: RequestOptions) -> Self {
+        let (done, sender, events) = call_grpc(request, options);
+
+        Self {
+            done: Box::pin(done),
+            sender,
+            events,
+        }
+    }
+
+    /// Sends a request and waits for its answer, or for the status the
+    /// stream ended with instead.
+    async fn ask(
+        &mut self,
+        request: ReflectionRequest,
+    ) -> Result<Result<ReflectionAnswer, GrpcStatus>> {
+        self.sender.send(request.encode_to_vec())?;
+
+        while let Some(event) = self.events.next().await {
+            if let GrpcEvent::Received { message, .. } = event {
+                return parse_reflection_response(&message).map(Ok);
+            }
+        }
+
+        let status = (&mut self.done).await?;
+        if status.is_ok() {
+            bail!("the reflection stream ended before answering");
+        }
+        Ok(Err(status))
+    }
+
+    /// Loads the services of the server, or gives the status the stream
+    /// failed with.
+    async fn load(mut self) -> Result<Result<DescriptorPool, GrpcStatus>> {
+        let list_services = ReflectionRequest {
+            list_services: Some(String::new()),
+            ..Default::default()
+        };
+        let services = match self.ask(list_services).await? {
+            Ok(ReflectionAnswer::Services(services)) => services,
+            Ok(ReflectionAnswer::Error(error)) => bail!("listing the services failed: {error}"),
+            Ok(ReflectionAnswer::Files(_)) => bail!("unexpected answer to listing the services"),
+            Err(status) => return Ok(Err(status)),
+        };
+
+        let mut files = BTreeMap::new();
+        let mut missing = VecDeque::new();
+        let add_files = |files: &mut BTreeMap<String, _>,
+                         missing: &mut VecDeque<String>,
+                         answer: Vec<Bytes>|
+         -> Result<()> {
+            for file in answer {
+                let file = decode_file_descriptor(&file)?;
+                missing.extend(file.dependency.iter().cloned());
+                files.insert(file.name().to_string(), file);
+            }
+            Ok(())
+        };
+
+        for service in services
+            .iter()
+            .filter(|service| !service.starts_with("grpc.reflection."))
+        {
+            let request = ReflectionRequest {
+                file_containing_symbol: Some(service.clone()),
+                ..Default::default()
+            };
+            match self.ask(request).await? {
+                Ok(ReflectionAnswer::Files(answer)) => add_files(&mut files, &mut missing, answer)?,
+                Ok(ReflectionAnswer::Error(error)) => {
+                    bail!("looking up {service} failed: {error}")
+                }
+                Ok(ReflectionAnswer::Services(_)) => {
+                    bail!("unexpected answer to looking up {service}")
+                }
+                Err(status) => return Ok(Err(status)),
+            }
+        }
+
+        // Types may come from files the server didn't send along; those it
+        // can't find either are left out, along with the services whose
+        // files import them.
+        while let Some(file) = missing.pop_front() {
+            if files.contains_key(&file) {
+                continue;
+            }
+            let request = ReflectionRequest {
+                file_by_filename: Some(file),
+                ..Default::default()
+            };
+            if let Ok(ReflectionAnswer::Files(answer)) = self.ask(request).await? {
+                add_files(&mut files, &mut missing, answer)?;
+            }
+        }
+
+        self.sender.finish();
+        let mut pool = DescriptorPool::default();
+        pool.add_files(files.into_values().collect())?;
+        Ok(Ok(pool))
+    }
+}
+
+fn parse_reflection_response(bytes: &[u8]) -> Result<ReflectionAnswer> {
+    let response = ReflectionResponse::decode(bytes).context("invalid reflection response")?;
+
+    if let Some(files) = response.file_descriptor_response {
+        Ok(ReflectionAnswer::Files(files.file_descriptor_proto))
+    } else if let Some(services) = response.list_services_response {
+        Ok(ReflectionAnswer::Services(
+            services
+                .service
+                .into_iter()
+                .map(|service| service.name)
+                .collect(),
+        ))
+    } else if let Some(error) = response.error_response {
+        Ok(ReflectionAnswer::Error(error.error_message))
+    } else {
+        bail!("empty reflection response")
+    }
+}
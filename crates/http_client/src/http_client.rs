@@ -0,0 +1,630 @@
+mod auth;
+mod body;
+mod certificate;
+mod connect;
+mod cookie;
+mod der;
+mod encoding;
+mod graphql;
+mod graphql_document;
+mod grpc;
+mod oauth;
+mod pkcs12;
+mod pool;
+mod progress;
+mod proto_file;
+mod protobuf;
+mod protocol;
+mod proxy;
+mod redirect;
+mod resolve;
+mod runtime;
+mod signing;
+mod spill;
+mod sse;
+mod timeout;
+mod timing;
+mod tls;
+mod websocket;
+
+use std::{
+    fmt,
+    path::PathBuf,
+    time::{Instant, SystemTime},
+};
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use bytes::Bytes;
+use futures::channel::mpsc;
+use http::{
+    HeaderMap, HeaderName, HeaderValue, Method, StatusCode, Uri, Version,
+    header::{ACCEPT_ENCODING, AUTHORIZATION, CONTENT_LENGTH, COOKIE, HOST, PROXY_AUTHORIZATION},
+};
+use http_body_util::BodyExt;
+use hyper::client::conn::{http1, http2};
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use url::Url;
+
+pub use auth::{DigestCredentials, basic_authorization};
+pub use body::{BodyPart, HttpBody};
+pub use graphql::{
+    EnumValue, GraphQlSchema, INTROSPECTION_QUERY, InputValue, QueryMode, SchemaDirective,
+    SchemaField, SchemaType, TypeKind, TypeRef, error_messages, graphql_body,
+    is_persisted_query_not_found, persisted_query_hash,
+};
+pub use graphql_document::{
+    GraphQlCompletion, GraphQlError, complete_query, operation_names, validate_query,
+};
+pub use grpc::{
+    GrpcEvent, GrpcRequest, GrpcSender, GrpcStatus, call_grpc, grpc_http_url, reflect_services,
+};
+pub use http;
+pub use oauth::{
+    DeviceAuthorization, Jwt, OAuthClient, OAuthToken, PendingAuthorization, decode_jwt,
+};
+pub use progress::ResponseProgress;
+pub use protobuf::{
+    DescriptorPool, EnumDescriptor, FieldDescriptor, MessageDescriptor, MethodDescriptor,
+    ServiceDescriptor, method_kind,
+};
+pub use protocol::{HttpVersion, protocol_name};
+pub use proxy::{NoProxy, Proxy, ProxyConfig, ProxyScheme};
+pub use redirect::{RedirectHop, RedirectPolicy};
+pub use resolve::ResolveOverride;
+pub use signing::{
+    AwsCredentials, AwsSigV4, HmacAlgorithm, HmacSigner, RequestSigner, SignatureEncoding,
+};
+pub use spill::{PREVIEW_SIZE, SpilledBody, TempFile};
+pub use sse::{DEFAULT_RETRY, SseEvent, SseMessage, SseParser, subscribe};
+pub use timeout::Timeouts;
+pub use url;
+pub use websocket::{
+    CloseFrame, WebSocketEvent, WebSocketMessage, WebSocketSender, connect_websocket,
+    websocket_accept,
+};
+
+pub use certificate::CertificateInfo;
+pub use cookie::{Cookie, CookieJar};
+pub use encoding::ContentEncoding;
+pub use timing::{ConnectionInfo, RemoteAddr, Timing};
+pub use tls::{ClientCertificate, TlsInfo, TlsOptions, TlsVersion};
+
+use crate::{
+    body::WireBody,
+    connect::{Io, connect},
+    pool::{PoolKey, PooledConnection, Sender},
+    progress::ProgressSender,
+    spill::{BodySink, ReceivedBody},
+    timeout::timeout,
+};
+
+/// A request ready to be sent, with its variables already resolved.
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: Url,
+    pub headers: Vec<(String, String)>,
+    pub body: HttpBody,
+}
+
+/// How a request is sent, independently of what it contains.
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    pub version: HttpVersion,
+    pub tls: TlsOptions,
+    /// The proxy to go through, already picked for the URL of the request.
+    pub proxy: Option<Proxy>,
+    /// Connects to the Unix domain socket at this path rather than to the
+    /// host of the URL, which is still sent as the `Host` header. Proxies
+    /// are left out.
+    pub unix_socket: Option<PathBuf>,
+    /// Addresses to connect to instead of resolving the hosts they name.
+    pub resolve: Vec<ResolveOverride>,
+    pub redirect: RedirectPolicy,
+    /// Answers the Digest challenge of a `401` response by sending the
+    /// request again once, on the origin of the request or, when the
+    /// redirect policy forwards credentials, on any.
+    pub digest: Option<DigestCredentials>,
+    /// Cookies to send with each request, including redirected ones, which
+    /// also receive those set by the redirect responses. A `Cookie` header
+    /// of the request takes precedence.
+    pub cookies: Option<CookieJar>,
+    /// Headers of the request holding credentials, like an API key, that
+    /// redirects to other origins drop as they do `Authorization`.
+    pub auth_headers: Vec<String>,
+    /// Signs each request sent, as the last step before it goes out.
+    pub signer: Option<RequestSigner>,
+    pub timeouts: Timeouts,
+    /// Bodies larger than this many bytes are written to a temporary file
+    /// as they are received rather than kept in memory.
+    pub spill_threshold: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    /// Where the response came from, after following redirects.
+    pub url: Url,
+    /// The protocol the response was received over.
+    pub version: Version,
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    /// The body with its content encodings undone, or as received when
+    /// they couldn't be. Only its first `PREVIEW_SIZE` bytes when spilled.
+    pub body: Bytes,
+    /// The body as received, still compressed. Only its first
+    /// `PREVIEW_SIZE` bytes when spilled.
+    pub raw_body: Bytes,
+    /// Where the whole body is when it was too large to be kept in memory.
+    pub spilled: Option<SpilledBody>,
+    /// The content encodings of the body, in the order they were applied.
+    pub encodings: Vec<ContentEncoding>,
+    /// Why the body couldn't be decoded, if it couldn't.
+    pub decode_error: Option<String>,
+    pub timing: Timing,
+    pub connection: ConnectionInfo,
+    /// The TLS session the response was received over, if any.
+    pub tls: Option<TlsInfo>,
+    /// The redirects followed to get the response, in order.
+    pub redirects: Vec<RedirectHop>,
+}
+
+impl HttpResponse {
+    /// The size of the body with its content encodings undone.
+    pub fn body_len(&self) -> u64 {
+        match &self.spilled {
+            Some(spilled) => spilled.body.len(),
+            None => self.body.len() as u64,
+        }
+    }
+
+    /// The size of the body as received.
+    pub fn raw_body_len(&self) -> u64 {
+        match &self.spilled {
+            Some(spilled) => spilled.raw_body.len(),
+            None => self.raw_body.len() as u64,
+        }
+    }
+}
+
+/// Sends `request` on the http client runtime. The returned future can be
+/// awaited from any executor, and dropping it cancels the request.
+pub fn send(
+    request: HttpRequest,
+    options: RequestOptions,
+) -> impl Future<Output = Result<HttpResponse>> + Send + 'static {
+    runtime::spawn(execute(request, options, None))
+}
+
+/// Like [`send`], also reporting the response as it arrives. The progress
+/// stream ends along with the request.
+pub fn send_with_progress(
+    request: HttpRequest,
+    options: RequestOptions,
+) -> (
+    impl Future<Output = Result<HttpResponse>> + Send + 'static,
+    mpsc::UnboundedReceiver<ResponseProgress>,
+) {
+    let (progress, receiver) = mpsc::unbounded();
+
+    (
+        runtime::spawn(execute(request, options, Some(progress))),
+        receiver,
+    )
+}
+
+async fn execute(
+    request: HttpRequest,
+    options: RequestOptions,
+    progress: Option<ProgressSender>,
+) -> Result<HttpResponse> {
+    let url = request.url.clone();
+
+    timeout(
+        options.timeouts.total,
+        || format!("waiting for {url}"),
+        follow(request, &options, progress.as_ref()),
+    )
+    .await
+}
+
+/// Sends `request`, then the requests its redirects lead to as allowed by
+/// the redirect policy. Every hop goes with the same TLS settings and proxy.
+async fn follow(
+    mut request: HttpRequest,
+    options: &RequestOptions,
+    progress: Option<&ProgressSender>,
+) -> Result<HttpResponse> {
+    let mut cookies = options.cookies.clone();
+    let mut redirects = Vec::new();
+    let origin = request.url.origin();
+
+    loop {
+        let mut sent = request.clone();
+        if let Some(jar) = &cookies
+            && !has_header(&sent, COOKIE.as_str())
+            && let Some(cookie) = jar.header_for(&sent.url, SystemTime::now())
+        {
+            sent.headers.push((COOKIE.to_string(), cookie));
+        }
+        if let Some(signer) = &options.signer {
+            signer.sign(&mut sent, SystemTime::now()).await?;
+        }
+
+        let mut response = execute_once(&sent, options, progress).await?;
+
+        if response.status == StatusCode::UNAUTHORIZED
+            && let Some(credentials) = &options.digest
+            && (sent.url.origin() == origin || options.redirect.forward_auth)
+            && !has_header(&sent, AUTHORIZATION.as_str())
+            && let Some(authorization) =
+                auth::digest_authorization(&sent, &response.headers, credentials)?
+        {
+            sent.headers
+                .push((AUTHORIZATION.to_string(), authorization));
+            response = execute_once(&sent, options, progress).await?;
+        }
+
+        let location = if options.redirect.follow {
+            redirect::location(&request.url, response.status, &response.headers)?
+        } else {
+            None
+        };
+        let Some(location) = location else {
+            response.redirects = redirects;
+            return Ok(response);
+        };
+
+        if redirects.len() == options.redirect.max_hops {
+            bail!(
+                "too many redirects, stopped after {} at {location}",
+                redirects.len()
+            );
+        }
+
+        if let Some(jar) = cookies.as_mut() {
+            jar.store_response(&request.url, &response.headers, SystemTime::now());
+        }
+
+        let next = redirect::next_request(&request, response.status, location.clone(), options);
+        redirects.push(RedirectHop {
+            method: request.method,
+            url: request.url,
+            version: response.version,
+            status: response.status,
+            headers: response.headers,
+            location,
+            timing: response.timing,
+        });
+        request = next;
+    }
+}
+
+pub(crate) fn has_header(request: &HttpRequest, name: &str) -> bool {
+    request
+        .headers
+        .iter()
+        .any(|(header, _)| header.trim().eq_ignore_ascii_case(name))
+}
+
+async fn execute_once(
+    request: &HttpRequest,
+    options: &RequestOptions,
+    progress: Option<&ProgressSender>,
+) -> Result<HttpResponse> {
+    let start = Instant::now();
+    let key = PoolKey::new(&request.url, options);
+    let content_length = request.body.len().await?;
+    let exchange = |connection, timing, reused| Exchange {
+        key: &key,
+        connection,
+        request,
+        content_length,
+        options,
+        progress,
+        timing,
+        reused,
+        start,
+    };
+
+    if let Some(connection) = pool::checkout(&key).await {
+        match exchange(connection, Timing::default(), true).run().await {
+            Ok(response) => return Ok(response),
+            // The server may have closed the idle connection, in which case
+            // a new one is opened.
+            Err(error) if error.is::<StaleConnection>() => {}
+            Err(error) => return Err(error),
+        }
+    }
+
+    let (connection, timing) = open_connection(&request.url, options, &key).await?;
+
+    exchange(connection, timing, false).run().await
+}
+
+/// Opens a new connection for `url`, within the connect timeout.
+pub(crate) async fn open_connection(
+    url: &Url,
+    options: &RequestOptions,
+    key: &PoolKey,
+) -> Result<(PooledConnection, Timing)> {
+    timeout(
+        options.timeouts.connect,
+        || format!("connecting to {}", url.host_str().unwrap_or_default()),
+        async {
+            let (connection, remote_addr, connect_timing) = connect(url, options).await?;
+            let timing = Timing {
+                dns: connect_timing.dns,
+                connect: Some(connect_timing.connect),
+                tls: connect_timing.tls,
+                ..Timing::default()
+            };
+            let sender = handshake(connection.stream, connection.h2).await?;
+            let connection =
+                PooledConnection::new(key, remote_addr, connection.tls, connection.forward, sender);
+
+            Ok((connection, timing))
+        },
+    )
+    .await
+}
+
+/// Starts speaking h2 over `io` when `h2` is set, and HTTP/1.1 otherwise.
+async fn handshake(io: Box<dyn Io>, h2: bool) -> Result<Sender> {
+    let io = TokioIo::new(io);
+
+    if h2 {
+        let (sender, connection) = http2::handshake(TokioExecutor::new(), io)
+            .await
+            .context("h2 handshake")?;
+        tokio::spawn(connection);
+
+        Ok(Sender::Http2(sender))
+    } else {
+        let (sender, connection) = http1::handshake(io).await?;
+        tokio::spawn(connection.with_upgrades());
+
+        Ok(Sender::Http1(sender))
+    }
+}
+
+/// A request to send over a connection, along with what the response is
+/// measured and reported with.
+struct Exchange<'a> {
+    key: &'a PoolKey,
+    connection: PooledConnection,
+    request: &'a HttpRequest,
+    content_length: u64,
+    options: &'a RequestOptions,
+    progress: Option<&'a ProgressSender>,
+    timing: Timing,
+    reused: bool,
+    start: Instant,
+}
+
+impl Exchange<'_> {
+    /// Sends the request and reads the whole response, then gives the
+    /// connection back to the pool.
+    async fn run(self) -> Result<HttpResponse> {
+        let Self {
+            key,
+            mut connection,
+            request,
+            content_length,
+            options,
+            progress,
+            mut timing,
+            reused,
+            start,
+        } = self;
+        let read_timeout = options.timeouts.read;
+
+        let sent = Instant::now();
+        let response = connection.sender.try_send(build_request(
+            request,
+            content_length,
+            connection.sender.is_h2(),
+            connection.proxy.as_ref().filter(|_| connection.forward),
+        )?);
+        let response = timeout(
+            read_timeout,
+            || "waiting for the response".to_string(),
+            async {
+                response.await.map_err(|mut error| {
+                    let unsent = error.take_message().is_some();
+                    let error = error.into_error();
+                    // Once written, the request may have been acted on
+                    // before the connection closed, so only those that can
+                    // be repeated safely are sent again.
+                    let closed = error.is_canceled() || error.is_incomplete_message();
+                    if reused && (unsent || closed && request.method.is_idempotent()) {
+                        anyhow::Error::new(StaleConnection(error))
+                    } else {
+                        error.into()
+                    }
+                })
+            },
+        )
+        .await?;
+        timing.first_byte = sent.elapsed();
+
+        let (parts, mut body) = response.into_parts();
+        let total = parts
+            .headers
+            .get(CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok()?.parse().ok());
+        if let Some(progress) = progress {
+            progress
+                .unbounded_send(ResponseProgress::Head {
+                    version: parts.version,
+                    status: parts.status,
+                    headers: parts.headers.clone(),
+                })
+                .ok();
+        }
+
+        let downloading = Instant::now();
+        let mut sink = BodySink::new(options.spill_threshold);
+        let mut received = 0;
+        while let Some(frame) = timeout(read_timeout, || "reading the body".to_string(), async {
+            body.frame().await.transpose().context("reading body")
+        })
+        .await?
+        {
+            let Ok(data) = frame.into_data() else {
+                continue;
+            };
+
+            sink.write(&data).await?;
+            let previewed = received.min(PREVIEW_SIZE as u64) as usize;
+            received += data.len() as u64;
+
+            if let Some(progress) = progress {
+                let chunk = data.slice(..data.len().min(PREVIEW_SIZE - previewed));
+
+                progress
+                    .unbounded_send(ResponseProgress::Body {
+                        chunk,
+                        received,
+                        total,
+                    })
+                    .ok();
+            }
+        }
+        let received = sink.finish().await?;
+        timing.download = downloading.elapsed();
+
+        let encodings = ContentEncoding::from_headers(&parts.headers);
+        let (body, raw_body, spilled, decode_error) = match received {
+            ReceivedBody::Memory(raw_body) => match encoding::decode(&raw_body, &encodings) {
+                Ok(body) => (body, raw_body, None, None),
+                Err(error) => (raw_body.clone(), raw_body, None, Some(format!("{error:#}"))),
+            },
+            ReceivedBody::Spilled(raw_body) => {
+                let encodings = encodings.clone();
+                let (body, raw_preview, spilled, decode_error) =
+                    tokio::task::spawn_blocking(move || spill::decode(raw_body, &encodings))
+                        .await??;
+
+                (body, raw_preview, Some(spilled), decode_error)
+            }
+        };
+        timing.total = start.elapsed();
+
+        let tls = connection.tls.clone();
+        let info = ConnectionInfo {
+            id: connection.id,
+            remote_addr: connection.remote_addr.clone(),
+            proxy: connection.proxy.as_ref().map(ToString::to_string),
+            reused,
+        };
+        pool::checkin(key.clone(), connection);
+
+        Ok(HttpResponse {
+            url: request.url.clone(),
+            version: parts.version,
+            status: parts.status,
+            headers: parts.headers,
+            body,
+            raw_body,
+            spilled,
+            encodings,
+            decode_error,
+            timing,
+            connection: info,
+            tls,
+            redirects: Vec::new(),
+        })
+    }
+}
+
+/// How a request fails on a pooled connection the server closed while it
+/// was idle, when it can be sent again on a new one.
+#[derive(Debug)]
+struct StaleConnection(hyper::Error);
+
+impl fmt::Display for StaleConnection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for StaleConnection {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+/// Builds the request to write for `request`, in absolute form with the
+/// credentials of `forward_proxy` when it goes to an HTTP proxy as is.
+pub(crate) fn build_request(
+    request: &HttpRequest,
+    content_length: u64,
+    h2: bool,
+    forward_proxy: Option<&Proxy>,
+) -> Result<http::Request<WireBody>> {
+    let url = &request.url;
+    let path = match url.query() {
+        Some(query) => format!("{}?{query}", url.path()),
+        None => url.path().to_string(),
+    };
+
+    // h2 carries the scheme and authority as pseudo-headers, so it needs the
+    // absolute URI, as do proxies, while HTTP/1.1 sends the path and a Host
+    // header.
+    let uri: Uri = if h2 || forward_proxy.is_some() {
+        url.as_str().parse()?
+    } else {
+        path.parse()?
+    };
+
+    let mut builder = http::Request::builder()
+        .method(request.method.clone())
+        .uri(uri)
+        .version(if h2 {
+            Version::HTTP_2
+        } else {
+            Version::HTTP_11
+        });
+
+    let headers = builder
+        .headers_mut()
+        .ok_or_else(|| anyhow!("invalid request"))?;
+
+    for (name, value) in &request.headers {
+        let name = HeaderName::try_from(name.trim())
+            .with_context(|| format!("invalid header name {name:?}"))?;
+        let value = HeaderValue::try_from(value.trim())
+            .with_context(|| format!("invalid value for header {name}"))?;
+
+        headers.append(name, value);
+    }
+
+    if !h2 && !headers.contains_key(HOST) {
+        let host = url.host_str().ok_or_else(|| anyhow!("{url} has no host"))?;
+        let host = match url.port() {
+            Some(port) => format!("{host}:{port}"),
+            None => host.to_string(),
+        };
+
+        headers.insert(HOST, HeaderValue::try_from(host)?);
+    }
+
+    if !headers.contains_key(ACCEPT_ENCODING) {
+        headers.insert(
+            ACCEPT_ENCODING,
+            HeaderValue::from_static(encoding::ACCEPT_ENCODING),
+        );
+    }
+
+    if let Some(authorization) = forward_proxy.and_then(Proxy::authorization)
+        && !headers.contains_key(PROXY_AUTHORIZATION)
+    {
+        headers.insert(PROXY_AUTHORIZATION, authorization);
+    }
+
+    if content_length > 0 && !headers.contains_key(CONTENT_LENGTH) {
+        headers.insert(CONTENT_LENGTH, HeaderValue::from(content_length));
+    }
+
+    Ok(builder.body(request.body.to_wire())?)
+}
@@ -0,0 +1,600 @@
+use std::{
+    future::Future,
+    pin::pin,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use base64::{Engine as _, prelude::BASE64_URL_SAFE_NO_PAD};
+use futures::future::{self, Either};
+use http::{Method, header::CONTENT_TYPE};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    task::JoinSet,
+};
+use url::{Url, form_urlencoded};
+
+use crate::{HttpRequest, RequestOptions, auth::basic_authorization, runtime};
+
+/// How long before it expires an access token is considered expired, so
+/// that it doesn't expire on the way to the server.
+const EXPIRY_MARGIN: Duration = Duration::from_secs(30);
+/// How often the device code flow polls when the server doesn't say.
+const DEFAULT_POLL_INTERVAL: u64 = 5;
+/// The path the loopback listener expects the authorization redirect on.
+const REDIRECT_PATH: &str = "/callback";
+/// What the browser shows once the authorization redirect was received.
+const REDIRECT_PAGE: &str = "<!doctype html><title>Authorized</title>\
+    <p>The authorization was received. You can close this window.</p>";
+/// How long the browser has to send a request once connected.
+const REDIRECT_READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// The size of the longest request head the loopback listener reads.
+const MAX_REDIRECT_HEAD_LEN: usize = 16 * 1024;
+
+/// An OAuth 2.0 client, with its variables already resolved. Fields that
+/// a flow doesn't use may be empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OAuthClient {
+    pub token_url: String,
+    pub authorization_url: String,
+    pub device_authorization_url: String,
+    pub client_id: String,
+    /// Empty for public clients, which authenticate with PKCE alone.
+    pub client_secret: String,
+    pub scope: String,
+    /// The port of the loopback listener the authorization code flow
+    /// redirects to, any free one when 0.
+    pub redirect_port: u16,
+}
+
+/// The tokens a token endpoint answered with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OAuthToken {
+    pub access_token: String,
+    #[serde(default = "bearer")]
+    pub token_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    /// When the access token expires, in seconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// The OpenID Connect ID token, when the scope asked for one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id_token: Option<String>,
+}
+
+fn bearer() -> String {
+    "Bearer".to_string()
+}
+
+impl OAuthToken {
+    /// Whether the access token has expired, or is about to.
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| unix_time(now + EXPIRY_MARGIN) >= expires_at)
+    }
+
+    /// The `Authorization` header value sending the access token.
+    pub fn authorization(&self) -> String {
+        let token_type = if self.token_type.eq_ignore_ascii_case("bearer") {
+            "Bearer"
+        } else {
+            &self.token_type
+        };
+
+        format!("{token_type} {}", self.access_token)
+    }
+}
+
+/// The parts of a JSON Web Token, decoded but not verified.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Jwt {
+    pub header: serde_json::Value,
+    pub claims: serde_json::Value,
+}
+
+/// Decodes `token` when it is a JSON Web Token, as access and ID tokens
+/// often are.
+pub fn decode_jwt(token: &str) -> Option<Jwt> {
+    let mut parts = token.trim().split('.');
+    let (header, claims, _signature) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let decode = |part: &str| {
+        let bytes = BASE64_URL_SAFE_NO_PAD
+            .decode(part.trim_end_matches('='))
+            .ok()?;
+        serde_json::from_slice::<serde_json::Value>(&bytes).ok()
+    };
+
+    Some(Jwt {
+        header: decode(header)?,
+        claims: decode(claims)?,
+    })
+}
+
+/// The error a token endpoint answers with, as RFC 6749 describes it.
+#[derive(Debug, Deserialize)]
+struct TokenError {
+    error: String,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+impl std::fmt::Display for TokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.error_description {
+            Some(description) => write!(f, "{} ({description})", self.error),
+            None => f.write_str(&self.error),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default = "bearer")]
+    token_type: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    expires_in: Option<u64>,
+    #[serde(default)]
+    scope: Option<String>,
+    #[serde(default)]
+    id_token: Option<String>,
+}
+
+impl OAuthClient {
+    /// Gets a token for the client itself, with the client credentials
+    /// grant.
+    pub fn client_credentials(
+        &self,
+        options: RequestOptions,
+    ) -> impl Future<Output = Result<OAuthToken>> + Send + 'static {
+        let client = self.clone();
+
+        runtime::spawn(async move {
+            let mut params = vec![("grant_type", "client_credentials".to_string())];
+            client.push_scope(&mut params);
+
+            client
+                .request_token(params, &options)
+                .await?
+                .map_err(|error| {
+                    anyhow!("the token endpoint refused the client credentials: {error}")
+                })
+        })
+    }
+
+    /// Gets a new access token with `refresh_token`. The answer keeps the
+    /// refresh token when the server doesn't rotate it.
+    pub fn refresh(
+        &self,
+        refresh_token: String,
+        options: RequestOptions,
+    ) -> impl Future<Output = Result<OAuthToken>> + Send + 'static {
+        let client = self.clone();
+
+        runtime::spawn(async move {
+            let params = vec![
+                ("grant_type", "refresh_token".to_string()),
+                ("refresh_token", refresh_token.clone()),
+            ];
+
+            let mut token = client
+                .request_token(params, &options)
+                .await?
+                .map_err(|error| {
+                    anyhow!("the token endpoint refused the refresh token: {error}")
+                })?;
+            token.refresh_token.get_or_insert(refresh_token);
+            Ok(token)
+        })
+    }
+
+    /// Starts the authorization code flow with PKCE, listening on the
+    /// loopback interface for the redirect the authorization server sends
+    /// the browser back with.
+    pub fn authorize(&self) -> impl Future<Output = Result<PendingAuthorization>> + Send + 'static {
+        let client = self.clone();
+
+        runtime::spawn(async move {
+            let listener = TcpListener::bind(("127.0.0.1", client.redirect_port))
+                .await
+                .context("listening for the authorization redirect")?;
+            let redirect_uri = format!(
+                "http://127.0.0.1:{}{REDIRECT_PATH}",
+                listener.local_addr()?.port()
+            );
+
+            let verifier = random_string(32)?;
+            let state = random_string(16)?;
+            let challenge = BASE64_URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+
+            let mut url = Url::parse(client.authorization_url.trim()).with_context(|| {
+                format!(
+                    "invalid authorization URL {:?}",
+                    client.authorization_url.trim()
+                )
+            })?;
+            url.query_pairs_mut()
+                .append_pair("response_type", "code")
+                .append_pair("client_id", &client.client_id)
+                .append_pair("redirect_uri", &redirect_uri)
+                .append_pair("state", &state)
+                .append_pair("code_challenge", &challenge)
+                .append_pair("code_challenge_method", "S256");
+            if !client.scope.trim().is_empty() {
+                url.query_pairs_mut()
+                    .append_pair("scope", client.scope.trim());
+            }
+
+            Ok(PendingAuthorization {
+                client,
+                url,
+                listener,
+                redirect_uri,
+                state,
+                verifier,
+            })
+        })
+    }
+
+    /// Starts the device code flow, asking for the code the user enters on
+    /// another device.
+    pub fn device_authorization(
+        &self,
+        options: RequestOptions,
+    ) -> impl Future<Output = Result<DeviceAuthorization>> + Send + 'static {
+        let client = self.clone();
+
+        runtime::spawn(async move {
+            #[derive(Deserialize)]
+            struct Response {
+                device_code: String,
+                user_code: String,
+                #[serde(alias = "verification_url")]
+                verification_uri: String,
+                #[serde(default)]
+                verification_uri_complete: Option<String>,
+                #[serde(default)]
+                interval: Option<u64>,
+            }
+
+            let mut params = vec![("client_id", client.client_id.clone())];
+            client.push_scope(&mut params);
+
+            let response = crate::send(
+                form_request(&client.device_authorization_url, &params)?,
+                options,
+            )
+            .await?;
+            if !response.status.is_success() {
+                bail!(
+                    "the device authorization endpoint answered {}: {}",
+                    response.status,
+                    describe_error(&response.body)
+                );
+            }
+
+            let response: Response = serde_json::from_slice(&response.body)
+                .context("parsing the device authorization response")?;
+
+            Ok(DeviceAuthorization {
+                client,
+                user_code: response.user_code,
+                verification_uri: response.verification_uri,
+                verification_uri_complete: response.verification_uri_complete,
+                device_code: response.device_code,
+                interval: response.interval.unwrap_or(DEFAULT_POLL_INTERVAL),
+            })
+        })
+    }
+
+    fn push_scope(&self, params: &mut Vec<(&'static str, String)>) {
+        if !self.scope.trim().is_empty() {
+            params.push(("scope", self.scope.trim().to_string()));
+        }
+    }
+
+    /// Posts `params` to the token endpoint, authenticating with the client
+    /// secret when there is one.
+    async fn request_token(
+        &self,
+        mut params: Vec<(&'static str, String)>,
+        options: &RequestOptions,
+    ) -> Result<Result<OAuthToken, TokenError>> {
+        if self.client_secret.is_empty() {
+            params.push(("client_id", self.client_id.clone()));
+        }
+        let mut request = form_request(&self.token_url, &params)?;
+        if !self.client_secret.is_empty() {
+            request.headers.push((
+                "Authorization".to_string(),
+                basic_authorization(
+                    &form_encode(&self.client_id),
+                    &form_encode(&self.client_secret),
+                ),
+            ));
+        }
+
+        let response = crate::send(request, options.clone()).await?;
+        if !response.status.is_success() {
+            return match serde_json::from_slice::<TokenError>(&response.body) {
+                Ok(error) => Ok(Err(error)),
+                Err(_) => bail!(
+                    "the token endpoint answered {}: {}",
+                    response.status,
+                    describe_error(&response.body)
+                ),
+            };
+        }
+
+        let token: TokenResponse =
+            serde_json::from_slice(&response.body).context("parsing the token response")?;
+
+        Ok(Ok(OAuthToken {
+            access_token: token.access_token,
+            token_type: token.token_type,
+            refresh_token: token.refresh_token,
+            expires_at: token
+                .expires_in
+                .map(|expires_in| unix_time(SystemTime::now()) + expires_in),
+            scope: token.scope,
+            id_token: token.id_token,
+        }))
+    }
+}
+
+/// An authorization code flow waiting for the browser to come back.
+pub struct PendingAuthorization {
+    client: OAuthClient,
+    /// Where to send the user to authorize the client.
+    pub url: Url,
+    listener: TcpListener,
+    redirect_uri: String,
+    state: String,
+    verifier: String,
+}
+
+impl PendingAuthorization {
+    /// Waits for the authorization redirect, then exchanges its code for a
+    /// token. Dropping the future stops listening.
+    pub fn finish(
+        self,
+        options: RequestOptions,
+    ) -> impl Future<Output = Result<OAuthToken>> + Send + 'static {
+        runtime::spawn(async move {
+            let code = self.receive_code().await?;
+            let params = vec![
+                ("grant_type", "authorization_code".to_string()),
+                ("code", code),
+                ("redirect_uri", self.redirect_uri.clone()),
+                ("code_verifier", self.verifier.clone()),
+            ];
+
+            self.client
+                .request_token(params, &options)
+                .await?
+                .map_err(|error| anyhow!("the token endpoint refused the code: {error}"))
+        })
+    }
+
+    /// Answers the requests of the browser until one brings the code back.
+    /// Each connection is read on its own, so that one left open, like the
+    /// ones browsers open ahead of time, doesn't hold up the others.
+    async fn receive_code(&self) -> Result<String> {
+        let mut connections = JoinSet::new();
+
+        loop {
+            let next = {
+                let accept = pin!(self.listener.accept());
+                let answered = pin!(next_redirect(&mut connections));
+
+                match future::select(accept, answered).await {
+                    Either::Left((accepted, _)) => Either::Left(accepted),
+                    Either::Right((url, _)) => Either::Right(url),
+                }
+            };
+            let url = match next {
+                Either::Left(accepted) => {
+                    let (socket, _) = accepted?;
+                    connections.spawn(answer_redirect(socket));
+                    continue;
+                }
+                Either::Right(url) => url,
+            };
+
+            let param = |name: &str| {
+                url.query_pairs()
+                    .find(|(key, _)| key == name)
+                    .map(|(_, value)| value.into_owned())
+            };
+            if let Some(error) = param("error") {
+                match param("error_description") {
+                    Some(description) => bail!("authorization failed: {error} ({description})"),
+                    None => bail!("authorization failed: {error}"),
+                }
+            }
+            if param("state").as_deref() != Some(self.state.as_str()) {
+                bail!("the authorization redirect has the wrong state");
+            }
+
+            return param("code").context("the authorization redirect has no code");
+        }
+    }
+}
+
+/// The next authorization redirect one of `connections` received, pending
+/// while there is none.
+async fn next_redirect(connections: &mut JoinSet<Option<Url>>) -> Url {
+    loop {
+        match connections.join_next().await {
+            Some(Ok(Some(url))) => return url,
+            Some(_) => {}
+            None => future::pending().await,
+        }
+    }
+}
+
+/// Reads a request of the browser and answers it, returning its URL when
+/// it is the authorization redirect. Requests that are too slow to come or
+/// too large are dropped.
+async fn answer_redirect(mut socket: TcpStream) -> Option<Url> {
+    let mut head = Vec::new();
+    let mut buffer = [0; 1024];
+    let read = async {
+        while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+            match socket.read(&mut buffer).await {
+                Ok(0) | Err(_) => return false,
+                Ok(read) => head.extend_from_slice(&buffer[..read]),
+            }
+            if head.len() > MAX_REDIRECT_HEAD_LEN {
+                return false;
+            }
+        }
+        true
+    };
+    if !tokio::time::timeout(REDIRECT_READ_TIMEOUT, read)
+        .await
+        .unwrap_or(false)
+    {
+        return None;
+    }
+
+    let head = String::from_utf8_lossy(&head);
+    let target = head.split(' ').nth(1).unwrap_or_default();
+    let url = Url::parse(&format!("http://127.0.0.1{target}"))
+        .ok()
+        .filter(|url| {
+            url.path() == REDIRECT_PATH
+                && url
+                    .query_pairs()
+                    .any(|(key, _)| key == "code" || key == "error")
+        });
+
+    let response = if url.is_some() {
+        format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: text/html; charset=utf-8\r\n\
+             content-length: {}\r\nconnection: close\r\n\r\n{REDIRECT_PAGE}",
+            REDIRECT_PAGE.len()
+        )
+    } else {
+        "HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\nconnection: close\r\n\r\n".to_string()
+    };
+    socket.write_all(response.as_bytes()).await.ok();
+    socket.shutdown().await.ok();
+
+    url
+}
+
+/// A device code flow waiting for the user to enter the code.
+pub struct DeviceAuthorization {
+    client: OAuthClient,
+    /// The code to enter.
+    pub user_code: String,
+    /// Where to enter it.
+    pub verification_uri: String,
+    /// Where to go to with the code already entered, when the server has
+    /// such a page.
+    pub verification_uri_complete: Option<String>,
+    device_code: String,
+    /// How many seconds to wait between polls.
+    interval: u64,
+}
+
+impl DeviceAuthorization {
+    /// Polls the token endpoint until the user has entered the code, or the
+    /// server gives up.
+    pub fn finish(
+        self,
+        options: RequestOptions,
+    ) -> impl Future<Output = Result<OAuthToken>> + Send + 'static {
+        runtime::spawn(async move {
+            let mut interval = self.interval;
+
+            loop {
+                tokio::time::sleep(Duration::from_secs(interval)).await;
+
+                let params = vec![
+                    (
+                        "grant_type",
+                        "urn:ietf:params:oauth:grant-type:device_code".to_string(),
+                    ),
+                    ("device_code", self.device_code.clone()),
+                ];
+                match self.client.request_token(params, &options).await? {
+                    Ok(token) => return Ok(token),
+                    Err(error) if error.error == "authorization_pending" => {}
+                    Err(error) if error.error == "slow_down" => interval += 5,
+                    Err(error) => bail!("the device code wasn't authorized: {error}"),
+                }
+            }
+        })
+    }
+}
+
+/// A `POST` of `params` as a form to `url`, asking for JSON back.
+fn form_request(url: &str, params: &[(&str, String)]) -> Result<HttpRequest> {
+    let url = url.trim();
+    let url = Url::parse(url).with_context(|| format!("invalid OAuth endpoint {url:?}"))?;
+
+    let mut body = form_urlencoded::Serializer::new(String::new());
+    for (name, value) in params {
+        body.append_pair(name, value);
+    }
+
+    Ok(HttpRequest {
+        method: Method::POST,
+        url,
+        headers: vec![
+            (
+                CONTENT_TYPE.to_string(),
+                "application/x-www-form-urlencoded".to_string(),
+            ),
+            ("Accept".to_string(), "application/json".to_string()),
+        ],
+        body: body.finish().into(),
+    })
+}
+
+/// Client credentials go through form encoding before Basic encoding, as
+/// RFC 6749 asks.
+fn form_encode(value: &str) -> String {
+    form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
+
+/// The error description of an error response, or its start when it isn't
+/// the JSON RFC 6749 describes.
+fn describe_error(body: &[u8]) -> String {
+    if let Ok(error) = serde_json::from_slice::<TokenError>(body) {
+        return error.to_string();
+    }
+
+    String::from_utf8_lossy(body).chars().take(200).collect()
+}
+
+/// `bytes` random bytes, encoded as base64url.
+fn random_string(bytes: usize) -> Result<String> {
+    let mut random = vec![0; bytes];
+    SystemRandom::new()
+        .fill(&mut random)
+        .map_err(|_| anyhow!("no randomness for the OAuth flow"))?;
+
+    Ok(BASE64_URL_SAFE_NO_PAD.encode(random))
+}
+
+fn unix_time(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
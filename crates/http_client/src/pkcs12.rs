@@ -0,0 +1,38 @@
+//! Client certificates and their keys read from PKCS#12 archives, the
+//! current PBES2 ones as well as the legacy ones encrypted with 3DES or RC2.
+
+use anyhow::{Result, anyhow};
+use p12_keystore::{KeyStore, error::Error};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+
+/// A client identity read from a PKCS#12 archive.
+pub struct Identity {
+    /// The certificate matching the key first, then the rest of the chain.
+    pub certificates: Vec<CertificateDer<'static>>,
+    pub key: PrivateKeyDer<'static>,
+}
+
+/// Checks the integrity of `archive` with `password`, decrypts it and
+/// returns the identity it holds.
+pub fn parse(archive: &[u8], password: &str) -> Result<Identity> {
+    let keystore = KeyStore::from_pkcs12(archive, password).map_err(|error| match error {
+        Error::MacError(_) => anyhow!("wrong PKCS#12 password, or the archive is damaged"),
+        // Archives without a MAC only tell a wrong password by the padding
+        // of what it decrypts.
+        Error::UnpadError | Error::Pkcs5Error(_) => anyhow!("wrong PKCS#12 password"),
+        error => anyhow!("invalid PKCS#12 archive: {error}"),
+    })?;
+
+    let (_, chain) = keystore
+        .private_key_chain()
+        .ok_or_else(|| anyhow!("no private key with its certificate in PKCS#12 archive"))?;
+
+    Ok(Identity {
+        certificates: chain
+            .chain()
+            .iter()
+            .map(|certificate| CertificateDer::from(certificate.as_der().to_vec()))
+            .collect(),
+        key: PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(chain.key().to_vec())),
+    })
+}
@@ -0,0 +1,207 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    path::PathBuf,
+    sync::{
+        LazyLock, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use hyper::{
+    Response,
+    body::Incoming,
+    client::conn::{TrySendError, http1, http2},
+};
+use url::Url;
+
+use crate::{
+    RequestOptions,
+    body::WireBody,
+    protocol::HttpVersion,
+    proxy::Proxy,
+    resolve::resolve_override,
+    timing::RemoteAddr,
+    tls::{TlsInfo, TlsOptions},
+};
+
+/// Idle connections by the origin and options they were opened for.
+static IDLE: LazyLock<Mutex<HashMap<PoolKey, Vec<PooledConnection>>>> =
+    LazyLock::new(Default::default);
+
+static NEXT_CONNECTION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// What a connection must have been opened with to be reused for a request.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PoolKey {
+    scheme: String,
+    host: String,
+    port: Option<u16>,
+    version: HttpVersion,
+    tls: TlsOptions,
+    proxy: Option<Proxy>,
+    unix_socket: Option<PathBuf>,
+    /// The address the host is connected to in place of resolving it.
+    resolved: Option<IpAddr>,
+}
+
+impl PoolKey {
+    pub fn new(url: &Url, options: &RequestOptions) -> Self {
+        let host = url.host_str().unwrap_or_default().to_string();
+        let port = url.port_or_known_default();
+
+        Self {
+            scheme: url.scheme().to_string(),
+            resolved: port.and_then(|port| resolve_override(&options.resolve, &host, port)),
+            host,
+            port,
+            version: options.version,
+            tls: options.tls.clone(),
+            // Connections over a Unix domain socket skip proxies.
+            proxy: options
+                .proxy
+                .clone()
+                .filter(|_| options.unix_socket.is_none()),
+            unix_socket: options.unix_socket.clone(),
+        }
+    }
+}
+
+pub enum Sender {
+    Http1(http1::SendRequest<WireBody>),
+    Http2(http2::SendRequest<WireBody>),
+}
+
+impl Sender {
+    pub fn is_h2(&self) -> bool {
+        matches!(self, Sender::Http2(_))
+    }
+
+    fn is_closed(&self) -> bool {
+        match self {
+            Sender::Http1(sender) => sender.is_closed(),
+            Sender::Http2(sender) => sender.is_closed(),
+        }
+    }
+
+    async fn ready(&mut self) -> hyper::Result<()> {
+        match self {
+            Sender::Http1(sender) => sender.ready().await,
+            Sender::Http2(sender) => sender.ready().await,
+        }
+    }
+
+    pub async fn send(
+        &mut self,
+        request: http::Request<WireBody>,
+    ) -> hyper::Result<Response<Incoming>> {
+        match self {
+            Sender::Http1(sender) => sender.send_request(request).await,
+            Sender::Http2(sender) => sender.send_request(request).await,
+        }
+    }
+
+    /// Like [`Self::send`], handing the request back in the error when the
+    /// connection closed before any of it was written.
+    pub async fn try_send(
+        &mut self,
+        request: http::Request<WireBody>,
+    ) -> Result<Response<Incoming>, TrySendError<http::Request<WireBody>>> {
+        match self {
+            Sender::Http1(sender) => sender.try_send_request(request).await,
+            Sender::Http2(sender) => sender.try_send_request(request).await,
+        }
+    }
+}
+
+pub struct PooledConnection {
+    pub id: u64,
+    pub remote_addr: RemoteAddr,
+    pub tls: Option<TlsInfo>,
+    /// The proxy the connection goes through, if any.
+    pub proxy: Option<Proxy>,
+    /// Whether requests are written in absolute form for the proxy to
+    /// forward, rather than to the origin through a tunnel.
+    pub forward: bool,
+    pub sender: Sender,
+}
+
+impl PooledConnection {
+    /// Wraps a freshly opened connection, sharing it right away when it is
+    /// multiplexed.
+    pub fn new(
+        key: &PoolKey,
+        remote_addr: RemoteAddr,
+        tls: Option<TlsInfo>,
+        forward: bool,
+        sender: Sender,
+    ) -> Self {
+        let connection = Self {
+            id: NEXT_CONNECTION_ID.fetch_add(1, Ordering::Relaxed),
+            remote_addr,
+            tls,
+            proxy: key.proxy.clone(),
+            forward,
+            sender,
+        };
+
+        if let Some(shared) = connection.share() {
+            idle_connections()
+                .entry(key.clone())
+                .or_default()
+                .push(shared);
+        }
+
+        connection
+    }
+
+    /// A handle to the same h2 connection, which can carry requests
+    /// concurrently with this one.
+    fn share(&self) -> Option<PooledConnection> {
+        match &self.sender {
+            Sender::Http1(_) => None,
+            Sender::Http2(sender) => Some(PooledConnection {
+                id: self.id,
+                remote_addr: self.remote_addr.clone(),
+                tls: self.tls.clone(),
+                proxy: self.proxy.clone(),
+                forward: self.forward,
+                sender: Sender::Http2(sender.clone()),
+            }),
+        }
+    }
+}
+
+fn idle_connections() -> std::sync::MutexGuard<'static, HashMap<PoolKey, Vec<PooledConnection>>> {
+    IDLE.lock().unwrap_or_else(|error| error.into_inner())
+}
+
+/// Takes a live connection for `key` out of the pool. h2 connections stay
+/// in the pool since they can be shared.
+pub async fn checkout(key: &PoolKey) -> Option<PooledConnection> {
+    loop {
+        let mut connection = {
+            let mut idle = idle_connections();
+            let connections = idle.get_mut(key)?;
+
+            connections.retain(|connection| !connection.sender.is_closed());
+            let connection = connections.last()?;
+
+            match connection.share() {
+                Some(shared) => shared,
+                None => connections.pop()?,
+            }
+        };
+
+        if connection.sender.ready().await.is_ok() {
+            return Some(connection);
+        }
+    }
+}
+
+/// Returns an HTTP/1.1 connection to the pool once its response was read.
+pub fn checkin(key: PoolKey, connection: PooledConnection) {
+    if !connection.sender.is_h2() && !connection.sender.is_closed() {
+        idle_connections().entry(key).or_default().push(connection);
+    }
+}
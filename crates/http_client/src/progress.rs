@@ -0,0 +1,26 @@
+use bytes::Bytes;
+use futures::channel::mpsc;
+use http::{HeaderMap, StatusCode, Version};
+
+pub(crate) type ProgressSender = mpsc::UnboundedSender<ResponseProgress>;
+
+/// What is known of a response while it is being received.
+#[derive(Debug, Clone)]
+pub enum ResponseProgress {
+    /// The head of a response arrived. Each redirect sends its own, after
+    /// which the count of bytes received starts over.
+    Head {
+        version: Version,
+        status: StatusCode,
+        headers: HeaderMap,
+    },
+    /// Part of the body arrived.
+    Body {
+        /// The bytes as received, up to `PREVIEW_SIZE` of them in all, and
+        /// empty past that.
+        chunk: Bytes,
+        received: u64,
+        /// The size announced by `Content-Length`, if any.
+        total: Option<u64>,
+    },
+}
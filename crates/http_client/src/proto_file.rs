@@ -0,0 +1,65 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, Result};
+use protobuf::Message as _;
+use protobuf_parse::Parser;
+
+use crate::protobuf::{DescriptorPool, decode_file_descriptor};
+
+impl DescriptorPool {
+    /// Loads `files` and the files they import, looking for imports in
+    /// `import_paths` and then next to the files themselves. The well-known
+    /// types are found without being at hand.
+    pub fn load_proto_files(files: &[PathBuf], import_paths: &[PathBuf]) -> Result<Self> {
+        let mut includes = import_paths.to_vec();
+        for file in files {
+            if let Some(dir) = file.parent()
+                && !includes.iter().any(|include| include == dir)
+            {
+                includes.push(dir.to_path_buf());
+            }
+        }
+
+        let parse = |parser: &mut Parser| {
+            parser
+                .includes(&includes)
+                .inputs(files)
+                .parse_and_typecheck()
+        };
+        let parsed = match parse(Parser::new().pure()) {
+            Ok(parsed) => parsed,
+            // The bundled parser predates editions, which protoc reads when
+            // it is installed.
+            Err(_) if files.iter().any(|file| declares_edition(file)) => {
+                parse(Parser::new().protoc().capture_stderr())
+                    .context("files of an edition are read with protoc, which must be installed")?
+            }
+            Err(error) => return Err(error),
+        };
+
+        let files = parsed
+            .file_descriptors
+            .iter()
+            .map(|file| decode_file_descriptor(&file.write_to_bytes()?))
+            .collect::<Result<Vec<_>>>()?;
+        let mut pool = DescriptorPool::default();
+        pool.add_files(files)?;
+
+        Ok(pool)
+    }
+}
+
+/// Whether the `.proto` file at `path` starts with an `edition` rather than
+/// a `syntax` declaration.
+fn declares_edition(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|source| {
+        source.lines().any(|line| {
+            line.trim_start()
+                .strip_prefix("edition")
+                .is_some_and(|rest| rest.trim_start().starts_with('='))
+        })
+    })
+}
@@ -0,0 +1,339 @@
+use anyhow::{Context as _, Result, anyhow};
+use prost_reflect::{
+    DeserializeOptions, DynamicMessage, Kind, ReflectMessage as _, SerializeOptions,
+    Value as ProtoValue, prost::Message as _, prost_types::FileDescriptorProto,
+};
+use serde_json::{Map, Value};
+
+pub use prost_reflect::{
+    EnumDescriptor, FieldDescriptor, MessageDescriptor, MethodDescriptor, ServiceDescriptor,
+};
+
+/// How deep templates go into messages that contain themselves.
+const TEMPLATE_DEPTH: usize = 8;
+
+/// The messages, enums and services of a set of `.proto` files, as loaded
+/// from the files themselves or from server reflection.
+#[derive(Debug, Clone, Default)]
+pub struct DescriptorPool(prost_reflect::DescriptorPool);
+
+/// What kind of call `method` makes, as gRPC tools name them.
+pub fn method_kind(method: &MethodDescriptor) -> &'static str {
+    match (method.is_client_streaming(), method.is_server_streaming()) {
+        (false, false) => "unary",
+        (false, true) => "server streaming",
+        (true, false) => "client streaming",
+        (true, true) => "bidirectional streaming",
+    }
+}
+
+impl DescriptorPool {
+    pub fn services(&self) -> impl ExactSizeIterator<Item = ServiceDescriptor> + '_ {
+        self.0.services()
+    }
+
+    pub fn service(&self, full_name: &str) -> Option<ServiceDescriptor> {
+        self.0.get_service_by_name(full_name)
+    }
+
+    pub fn message(&self, full_name: &str) -> Option<MessageDescriptor> {
+        self.0.get_message_by_name(full_name)
+    }
+
+    pub fn enumeration(&self, full_name: &str) -> Option<EnumDescriptor> {
+        self.0.get_enum_by_name(full_name)
+    }
+
+    pub fn has_file(&self, name: &str) -> bool {
+        self.0.get_file_by_name(name).is_some()
+    }
+
+    /// Adds `files`, in any order. Those importing a file that is neither
+    /// in the pool nor among them are left out, along with the files
+    /// importing them in turn.
+    pub(crate) fn add_files(&mut self, mut files: Vec<FileDescriptorProto>) -> Result<()> {
+        loop {
+            let available =
+                |name: &str| self.has_file(name) || files.iter().any(|file| file.name() == name);
+            let complete = files
+                .iter()
+                .map(|file| file.dependency.iter().all(|name| available(name)))
+                .collect::<Vec<_>>();
+            if complete.iter().all(|complete| *complete) {
+                break;
+            }
+
+            let mut complete = complete.into_iter();
+            files.retain(|_| complete.next().unwrap_or_default());
+        }
+
+        self.0
+            .add_file_descriptor_protos(files)
+            .context("invalid descriptors")
+    }
+
+    fn message_or_err(&self, full_name: &str) -> Result<MessageDescriptor> {
+        self.message(full_name)
+            .ok_or_else(|| anyhow!("unknown message type {full_name}"))
+    }
+
+    /// Encodes the JSON form of a `message`, as protobuf's JSON mapping
+    /// defines it, to its binary form.
+    pub fn encode_json(&self, message: &str, json: &str) -> Result<Vec<u8>> {
+        let descriptor = self.message_or_err(message)?;
+        let json = if json.trim().is_empty() { "{}" } else { json };
+
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let message = DynamicMessage::deserialize_with_options(
+            descriptor.clone(),
+            &mut deserializer,
+            &DeserializeOptions::new(),
+        )
+        .with_context(|| format!("invalid {}", descriptor.full_name()))?;
+        deserializer.end().context("invalid JSON")?;
+
+        Ok(message.encode_to_vec())
+    }
+
+    /// Decodes the binary form of a `message` to its JSON form. Unknown
+    /// fields are left out.
+    pub fn decode_json(&self, message: &str, bytes: &[u8]) -> Result<Value> {
+        let descriptor = self.message_or_err(message)?;
+        let message = DynamicMessage::decode(descriptor.clone(), bytes)
+            .with_context(|| format!("invalid {}", descriptor.full_name()))?;
+
+        to_json(&message, &SerializeOptions::new())
+    }
+
+    /// A JSON `message` with every field set to its default value, to start
+    /// writing a request from.
+    pub fn json_template(&self, message: &str) -> Value {
+        match self.message(message) {
+            Some(descriptor) => template(&descriptor, 0),
+            None => Value::Object(Map::new()),
+        }
+    }
+}
+
+fn to_json(message: &DynamicMessage, options: &SerializeOptions) -> Result<Value> {
+    message
+        .serialize_with_options(serde_json::value::Serializer, options)
+        .context("the message has no JSON form")
+}
+
+fn template(message: &MessageDescriptor, depth: usize) -> Value {
+    // The well-known types have JSON forms of their own, such as RFC 3339
+    // strings for timestamps.
+    if message.package_name() == "google.protobuf" {
+        let options = SerializeOptions::new()
+            .skip_default_fields(false)
+            .stringify_64_bit_integers(false);
+        return to_json(&DynamicMessage::new(message.clone()), &options).unwrap_or(Value::Null);
+    }
+
+    let fields = message
+        .fields()
+        .map(|field| {
+            let value = if field.is_map() {
+                Value::Object(Map::new())
+            } else if field.is_list() {
+                Value::Array(Vec::new())
+            } else {
+                match field.kind() {
+                    Kind::Message(message) if depth < TEMPLATE_DEPTH => {
+                        template(&message, depth + 1)
+                    }
+                    Kind::Message(_) => Value::Null,
+                    Kind::Enum(enumeration) => enumeration
+                        .values()
+                        .next()
+                        .map_or(Value::from(0), |value| Value::from(value.name())),
+                    Kind::Bool => Value::Bool(false),
+                    Kind::String | Kind::Bytes => Value::from(""),
+                    Kind::Double | Kind::Float => Value::from(0.0),
+                    _ => Value::from(0),
+                }
+            };
+
+            (field.json_name().to_string(), value)
+        })
+        .collect();
+
+    Value::Object(fields)
+}
+
+/// `FieldPresence`, `RepeatedFieldEncoding` and `MessageEncoding` values of
+/// `google.protobuf.FeatureSet`.
+const LEGACY_REQUIRED: i32 = 3;
+const PACKED: i32 = 1;
+const DELIMITED: i32 = 2;
+
+const LABEL_REQUIRED: i32 = 2;
+const LABEL_REPEATED: i32 = 3;
+const TYPE_STRING: i32 = 9;
+const TYPE_GROUP: i32 = 10;
+const TYPE_MESSAGE: i32 = 11;
+const TYPE_BYTES: i32 = 12;
+
+/// The features of an edition that have a proto2 counterpart.
+#[derive(Clone, Copy)]
+struct Features {
+    field_presence: i32,
+    repeated_field_encoding: i32,
+    message_encoding: i32,
+}
+
+impl Features {
+    /// The defaults of editions 2023 and 2024.
+    const DEFAULT: Self = Self {
+        field_presence: 1,
+        repeated_field_encoding: PACKED,
+        message_encoding: 1,
+    };
+
+    /// These features, overridden by those the `options` of a descriptor
+    /// set.
+    fn resolve(self, descriptor: &DynamicMessage) -> Self {
+        let Some(options) = descriptor.get_field_by_name("options") else {
+            return self;
+        };
+        let Some(features) = options
+            .as_message()
+            .and_then(|options| options.get_field_by_name("features"))
+        else {
+            return self;
+        };
+        let Some(features) = features.as_message() else {
+            return self;
+        };
+        let feature = |name: &str, inherited: i32| {
+            features
+                .get_field_by_name(name)
+                .and_then(|value| value.as_enum_number())
+                .filter(|value| *value != 0)
+                .unwrap_or(inherited)
+        };
+
+        Self {
+            field_presence: feature("field_presence", self.field_presence),
+            repeated_field_encoding: feature(
+                "repeated_field_encoding",
+                self.repeated_field_encoding,
+            ),
+            message_encoding: feature("message_encoding", self.message_encoding),
+        }
+    }
+}
+
+/// Decodes a serialized `FileDescriptorProto`. Files of an edition are
+/// rewritten as the proto2 files they amount to, which is all descriptors
+/// can be read as: repeated scalars are packed, fields required and
+/// messages delimited as their features say.
+pub(crate) fn decode_file_descriptor(bytes: &[u8]) -> Result<FileDescriptorProto> {
+    let descriptor = FileDescriptorProto::default().descriptor();
+    let mut file = DynamicMessage::decode(descriptor, bytes).context("invalid descriptor")?;
+
+    let syntax = file.get_field_by_name("syntax");
+    if syntax.as_deref().and_then(ProtoValue::as_str) == Some("editions") {
+        let features = Features::DEFAULT.resolve(&file);
+        lower_fields(&mut file, "extension", features);
+        lower_messages(&mut file, features);
+        file.set_field_by_name("syntax", ProtoValue::String("proto2".into()));
+    }
+
+    file.transcode_to().context("invalid descriptor")
+}
+
+fn lower_messages(parent: &mut DynamicMessage, features: Features) {
+    let field = if parent.descriptor().name() == "FileDescriptorProto" {
+        "message_type"
+    } else {
+        "nested_type"
+    };
+    let Some(messages) = parent
+        .get_field_by_name_mut(field)
+        .and_then(ProtoValue::as_list_mut)
+    else {
+        return;
+    };
+
+    for message in messages.iter_mut().filter_map(ProtoValue::as_message_mut) {
+        let features = features.resolve(message);
+        lower_fields(message, "field", features);
+        lower_fields(message, "extension", features);
+        lower_messages(message, features);
+    }
+}
+
+/// The names of the entry types of the map fields of `message`.
+fn map_entries(message: &DynamicMessage) -> Vec<String> {
+    let Some(nested) = message.get_field_by_name("nested_type") else {
+        return Vec::new();
+    };
+    let Some(nested) = nested.as_list() else {
+        return Vec::new();
+    };
+
+    nested
+        .iter()
+        .filter_map(ProtoValue::as_message)
+        .filter(|nested| {
+            nested.get_field_by_name("options").is_some_and(|options| {
+                options
+                    .as_message()
+                    .and_then(|options| options.get_field_by_name("map_entry"))
+                    .is_some_and(|map_entry| map_entry.as_bool() == Some(true))
+            })
+        })
+        .filter_map(|nested| Some(nested.get_field_by_name("name")?.as_str()?.to_string()))
+        .collect()
+}
+
+fn lower_fields(parent: &mut DynamicMessage, name: &str, features: Features) {
+    let map_entries = map_entries(parent);
+    let Some(fields) = parent
+        .get_field_by_name_mut(name)
+        .and_then(ProtoValue::as_list_mut)
+    else {
+        return;
+    };
+
+    for field in fields.iter_mut().filter_map(ProtoValue::as_message_mut) {
+        let features = features.resolve(field);
+        let number = |field: &DynamicMessage, name: &str| {
+            field
+                .get_field_by_name(name)
+                .and_then(|value| value.as_enum_number())
+        };
+        let label = number(field, "label");
+        let field_type = number(field, "type");
+
+        if label == Some(LABEL_REPEATED)
+            && features.repeated_field_encoding == PACKED
+            && !matches!(
+                field_type,
+                Some(TYPE_STRING | TYPE_GROUP | TYPE_MESSAGE | TYPE_BYTES)
+            )
+            && let Some(options) = field
+                .get_field_by_name_mut("options")
+                .and_then(ProtoValue::as_message_mut)
+            && !options.has_field_by_name("packed")
+        {
+            options.set_field_by_name("packed", ProtoValue::Bool(true));
+        }
+        if label != Some(LABEL_REPEATED) && features.field_presence == LEGACY_REQUIRED {
+            field.set_field_by_name("label", ProtoValue::EnumNumber(LABEL_REQUIRED));
+        }
+        // Map fields are encoded as they are whatever the features say.
+        let map = field
+            .get_field_by_name("type_name")
+            .and_then(|type_name| {
+                let entry = type_name.as_str()?.rsplit('.').next()?;
+                Some(map_entries.iter().any(|name| name == entry))
+            })
+            .unwrap_or_default();
+        if field_type == Some(TYPE_MESSAGE) && features.message_encoding == DELIMITED && !map {
+            field.set_field_by_name("type", ProtoValue::EnumNumber(TYPE_GROUP));
+        }
+    }
+}
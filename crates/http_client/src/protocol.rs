@@ -0,0 +1,64 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// How to pick the HTTP version of a request.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HttpVersion {
+    /// Offer both h2 and HTTP/1.1 via ALPN over TLS, HTTP/1.1 in cleartext.
+    #[default]
+    Auto,
+    /// Only ever speak HTTP/1.1.
+    Http1,
+    /// Require the server to select h2 via ALPN; needs TLS.
+    Http2,
+    /// Speak h2 right away without negotiating, in cleartext (h2c) or over
+    /// TLS.
+    Http2PriorKnowledge,
+}
+
+impl HttpVersion {
+    pub const ALL: [HttpVersion; 4] = [
+        HttpVersion::Auto,
+        HttpVersion::Http1,
+        HttpVersion::Http2,
+        HttpVersion::Http2PriorKnowledge,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HttpVersion::Auto => "Auto",
+            HttpVersion::Http1 => "HTTP/1.1",
+            HttpVersion::Http2 => "HTTP/2",
+            HttpVersion::Http2PriorKnowledge => "HTTP/2 (prior knowledge)",
+        }
+    }
+
+    /// The protocols offered via ALPN during the TLS handshake.
+    pub(crate) fn alpn_protocols(&self) -> Vec<Vec<u8>> {
+        match self {
+            HttpVersion::Auto => vec![b"h2".to_vec(), b"http/1.1".to_vec()],
+            HttpVersion::Http1 => vec![b"http/1.1".to_vec()],
+            HttpVersion::Http2 | HttpVersion::Http2PriorKnowledge => vec![b"h2".to_vec()],
+        }
+    }
+}
+
+impl fmt::Display for HttpVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// The name of a negotiated protocol, as shown on responses.
+pub fn protocol_name(version: http::Version) -> &'static str {
+    match version {
+        http::Version::HTTP_09 => "HTTP/0.9",
+        http::Version::HTTP_10 => "HTTP/1.0",
+        http::Version::HTTP_11 => "HTTP/1.1",
+        http::Version::HTTP_2 => "HTTP/2",
+        http::Version::HTTP_3 => "HTTP/3",
+        _ => "HTTP",
+    }
+}
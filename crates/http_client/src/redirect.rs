@@ -0,0 +1,122 @@
+use anyhow::{Context as _, Result};
+use http::{
+    HeaderMap, Method, StatusCode, Version,
+    header::{AUTHORIZATION, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, COOKIE, LOCATION},
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{HttpBody, HttpRequest, RequestOptions, timing::Timing};
+
+/// Whether and how redirect responses are followed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedirectPolicy {
+    pub follow: bool,
+    /// How many redirects to follow before giving up.
+    pub max_hops: usize,
+    /// Whether to resend the method and body on 301 and 302, rather than
+    /// switching to GET as browsers do. 303 always switches to GET.
+    pub keep_method: bool,
+    /// Whether to send the `Authorization` and `Cookie` headers of the
+    /// request, and the other headers its authentication added, to other
+    /// origins than its own.
+    pub forward_auth: bool,
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        Self {
+            follow: true,
+            max_hops: 10,
+            keep_method: false,
+            forward_auth: false,
+        }
+    }
+}
+
+/// A redirect response received on the way to the final one.
+#[derive(Debug, Clone)]
+pub struct RedirectHop {
+    pub method: Method,
+    pub url: Url,
+    pub version: Version,
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    /// Where the response redirected to, resolved against `url`.
+    pub location: Url,
+    pub timing: Timing,
+}
+
+/// Where a response redirects to, if it is a redirect with a location.
+pub(crate) fn location(url: &Url, status: StatusCode, headers: &HeaderMap) -> Result<Option<Url>> {
+    if !matches!(status.as_u16(), 301 | 302 | 303 | 307 | 308) {
+        return Ok(None);
+    }
+
+    let Some(location) = headers.get(LOCATION) else {
+        return Ok(None);
+    };
+    let location = location.to_str().context("invalid Location header")?.trim();
+
+    url.join(location)
+        .with_context(|| format!("invalid redirect location {location:?}"))
+        .map(Some)
+}
+
+/// The request to send to `location` after `request` received `status`.
+pub(crate) fn next_request(
+    request: &HttpRequest,
+    status: StatusCode,
+    location: Url,
+    options: &RequestOptions,
+) -> HttpRequest {
+    let policy = &options.redirect;
+    let switch_to_get = match status {
+        StatusCode::SEE_OTHER => request.method != Method::HEAD,
+        StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND => {
+            !policy.keep_method && request.method == Method::POST
+        }
+        _ => false,
+    };
+    let cross_origin = location.origin() != request.url.origin();
+
+    let headers = request
+        .headers
+        .iter()
+        .filter(|(name, _)| {
+            let name = name.trim();
+            let is = |header: &http::HeaderName| name.eq_ignore_ascii_case(header.as_str());
+
+            // The host of the first URL must not follow the request, and
+            // neither must the description of a body that is dropped.
+            !is(&http::header::HOST)
+                && !(switch_to_get
+                    && (is(&CONTENT_TYPE) || is(&CONTENT_LENGTH) || is(&CONTENT_ENCODING)))
+                && !(cross_origin
+                    && !policy.forward_auth
+                    && (is(&AUTHORIZATION)
+                        || is(&COOKIE)
+                        || options
+                            .auth_headers
+                            .iter()
+                            .any(|header| name.eq_ignore_ascii_case(header.trim()))))
+        })
+        .cloned()
+        .collect();
+
+    HttpRequest {
+        method: if switch_to_get {
+            Method::GET
+        } else {
+            request.method.clone()
+        },
+        url: location,
+        headers,
+        body: if switch_to_get {
+            HttpBody::default()
+        } else {
+            request.body.clone()
+        },
+    }
+}
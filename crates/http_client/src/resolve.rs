@@ -0,0 +1,91 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Instant,
+};
+
+use anyhow::{Context as _, Result, bail};
+use tokio::net::lookup_host;
+
+use crate::connect::ConnectTiming;
+
+/// An address to connect to instead of those DNS has for a host, like
+/// curl's `--resolve`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ResolveOverride {
+    pub host: String,
+    /// The only port redirected, when not every port is.
+    pub port: Option<u16>,
+    pub address: IpAddr,
+}
+
+impl ResolveOverride {
+    /// Parses `host`, optionally followed by `:port`, and the IP address to
+    /// connect to instead, which may be in brackets.
+    pub fn parse(host: &str, address: &str) -> Result<Self> {
+        let host = host.trim();
+        let (host, port) = match host.rsplit_once(':') {
+            Some((name, port)) if !name.contains(':') => {
+                let port = port
+                    .parse()
+                    .with_context(|| format!("invalid port in {host:?}"))?;
+                (name, Some(port))
+            }
+            _ => (host, None),
+        };
+        if host.is_empty() {
+            bail!("missing host to resolve");
+        }
+
+        let address = address.trim();
+        let address = address
+            .strip_prefix('[')
+            .and_then(|address| address.strip_suffix(']'))
+            .unwrap_or(address)
+            .parse()
+            .with_context(|| format!("invalid IP address {address:?} for {host}"))?;
+
+        Ok(Self {
+            host: host.to_ascii_lowercase(),
+            port,
+            address,
+        })
+    }
+
+    fn matches(&self, host: &str, port: u16) -> bool {
+        self.host.eq_ignore_ascii_case(host) && self.port.is_none_or(|only| only == port)
+    }
+}
+
+/// The address `host` is connected to on `port` in place of resolving it,
+/// if any.
+pub fn resolve_override(overrides: &[ResolveOverride], host: &str, port: u16) -> Option<IpAddr> {
+    overrides
+        .iter()
+        .find(|candidate| candidate.matches(host, port))
+        .map(|candidate| candidate.address)
+}
+
+/// The addresses of `host`, adding how long the lookup took to the DNS
+/// phase unless it is an IP address or is overridden.
+pub async fn resolve(
+    host: &str,
+    port: u16,
+    overrides: &[ResolveOverride],
+    timing: &mut ConnectTiming,
+) -> Result<Vec<SocketAddr>> {
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return Ok(vec![SocketAddr::new(ip, port)]);
+    }
+    if let Some(ip) = resolve_override(overrides, host, port) {
+        return Ok(vec![SocketAddr::new(ip, port)]);
+    }
+
+    let start = Instant::now();
+    let addresses = lookup_host((host, port))
+        .await
+        .with_context(|| format!("resolving {host}"))?
+        .collect();
+    timing.dns = Some(timing.dns.unwrap_or_default() + start.elapsed());
+
+    Ok(addresses)
+}
@@ -0,0 +1,36 @@
+use std::{future::Future, sync::LazyLock};
+
+use anyhow::Result;
+use tokio::{runtime::Runtime, task::AbortHandle};
+
+/// The runtime requests run on, separate from the app's own executors.
+static RUNTIME: LazyLock<Runtime> = LazyLock::new(|| {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .thread_name("http-client")
+        .enable_all()
+        .build()
+        .expect("failed to start the http client runtime")
+});
+
+/// Runs `future` on the http client runtime, returning a future that can be
+/// awaited from any executor. Dropping it aborts `future`.
+pub fn spawn<T: Send + 'static>(
+    future: impl Future<Output = Result<T>> + Send + 'static,
+) -> impl Future<Output = Result<T>> + Send + 'static {
+    let task = RUNTIME.spawn(future);
+    let abort = AbortOnDrop(task.abort_handle());
+
+    async move {
+        let _abort = abort;
+        task.await?
+    }
+}
+
+struct AbortOnDrop(AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
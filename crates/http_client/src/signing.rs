@@ -0,0 +1,558 @@
+use std::{
+    env, fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use base64::{Engine as _, prelude::BASE64_STANDARD};
+use futures::TryStreamExt as _;
+use ring::hmac;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+
+use crate::{HttpBody, HttpRequest, cookie::civil_from_days};
+
+const SIGV4_ALGORITHM: &str = "AWS4-HMAC-SHA256";
+/// Sent as the payload hash when the body isn't signed.
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+/// Headers left out of the signature, as proxies and clients may change
+/// them on the way.
+const UNSIGNED_HEADERS: [&str; 4] = ["authorization", "user-agent", "expect", "x-amzn-trace-id"];
+
+/// Signs a request right before it is sent, once every other header was
+/// added. Redirected requests and challenge answers are signed again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestSigner {
+    AwsSigV4(AwsSigV4),
+    Hmac(HmacSigner),
+}
+
+impl RequestSigner {
+    /// Adds the signature of `request` at `now` to its headers, replacing
+    /// the signature of an earlier hop.
+    pub async fn sign(&self, request: &mut HttpRequest, now: SystemTime) -> Result<()> {
+        match self {
+            RequestSigner::AwsSigV4(signer) => signer.sign(request, now).await,
+            RequestSigner::Hmac(signer) => signer.sign(request, now).await,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Sent along with temporary credentials.
+    pub session_token: Option<String>,
+}
+
+impl AwsCredentials {
+    /// The credentials in `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and
+    /// `AWS_SESSION_TOKEN`.
+    pub fn from_env() -> Result<Self> {
+        Self::from_vars(|name| env::var(name).ok())
+    }
+
+    /// Like [`AwsCredentials::from_env`], looking variables up with `var`.
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let lookup = |name: &str| var(name).filter(|value| !value.trim().is_empty());
+
+        Ok(Self {
+            access_key_id: lookup("AWS_ACCESS_KEY_ID").context("AWS_ACCESS_KEY_ID is not set")?,
+            secret_access_key: lookup("AWS_SECRET_ACCESS_KEY")
+                .context("AWS_SECRET_ACCESS_KEY is not set")?,
+            session_token: lookup("AWS_SESSION_TOKEN"),
+        })
+    }
+
+    /// The credentials of `profile` in the shared credentials file, which
+    /// `AWS_SHARED_CREDENTIALS_FILE` points to when not `~/.aws/credentials`.
+    pub fn from_profile(profile: &str) -> Result<Self> {
+        let path = match env::var_os("AWS_SHARED_CREDENTIALS_FILE") {
+            Some(path) => PathBuf::from(path),
+            None => env::var_os("HOME")
+                .map(|home| PathBuf::from(home).join(".aws").join("credentials"))
+                .context("HOME is not set")?,
+        };
+        let content =
+            fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+
+        Self::parse_profile(&content, profile)
+            .with_context(|| format!("reading profile {profile} of {}", path.display()))
+    }
+
+    /// The credentials of `profile` in the INI `content` of a shared
+    /// credentials file.
+    pub fn parse_profile(content: &str, profile: &str) -> Result<Self> {
+        let mut in_profile = false;
+        let mut found = false;
+        let mut values = Vec::new();
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(['#', ';']) {
+                continue;
+            }
+
+            if let Some(section) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+            {
+                let section = section.trim();
+                in_profile = section == profile
+                    || section.strip_prefix("profile ").map(str::trim) == Some(profile);
+                found |= in_profile;
+            } else if in_profile && let Some((key, value)) = line.split_once('=') {
+                values.push((key.trim().to_ascii_uppercase(), value.trim().to_string()));
+            }
+        }
+
+        if !found {
+            bail!("no such profile");
+        }
+
+        Self::from_vars(|name| {
+            values
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.clone())
+        })
+    }
+}
+
+/// Signs requests to AWS with Signature Version 4.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AwsSigV4 {
+    /// The signing name of the service, such as `execute-api` or `s3`.
+    pub service: String,
+    pub region: String,
+    pub credentials: AwsCredentials,
+    /// Signs `UNSIGNED-PAYLOAD` rather than the hash of the body, which is
+    /// then not read twice.
+    pub unsigned_payload: bool,
+}
+
+impl AwsSigV4 {
+    async fn sign(&self, request: &mut HttpRequest, now: SystemTime) -> Result<()> {
+        let is_s3 = self.service == "s3";
+        let mut remove = vec!["authorization", "x-amz-date", "x-amz-security-token"];
+        if is_s3 {
+            remove.push("x-amz-content-sha256");
+        }
+        request.headers.retain(|(name, _)| {
+            !remove
+                .iter()
+                .any(|removed| name.trim().eq_ignore_ascii_case(removed))
+        });
+
+        let payload_hash = if self.unsigned_payload {
+            UNSIGNED_PAYLOAD.to_string()
+        } else {
+            hex(&body_sha256(&request.body).await?)
+        };
+        let timestamp = amz_date(now);
+        let date = &timestamp[..8];
+
+        request
+            .headers
+            .push(("X-Amz-Date".to_string(), timestamp.clone()));
+        if let Some(token) = &self.credentials.session_token {
+            request
+                .headers
+                .push(("X-Amz-Security-Token".to_string(), token.clone()));
+        }
+        if is_s3 {
+            request
+                .headers
+                .push(("X-Amz-Content-Sha256".to_string(), payload_hash.clone()));
+        }
+
+        let (canonical_request, signed_headers) =
+            canonical_request(request, !is_s3, &payload_hash)?;
+        let scope = format!("{date}/{}/{}/aws4_request", self.region, self.service);
+        let string_to_sign = format!(
+            "{SIGV4_ALGORITHM}\n{timestamp}\n{scope}\n{}",
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let key = signing_key(
+            &self.credentials.secret_access_key,
+            date,
+            &self.region,
+            &self.service,
+        );
+        let signature = hex(hmac::sign(&key, string_to_sign.as_bytes()).as_ref());
+
+        request.headers.push((
+            "Authorization".to_string(),
+            format!(
+                "{SIGV4_ALGORITHM} Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+                self.credentials.access_key_id
+            ),
+        ));
+
+        Ok(())
+    }
+}
+
+/// The key SigV4 signs with, derived from the secret for the day, region
+/// and service of the request.
+fn signing_key(secret: &str, date: &str, region: &str, service: &str) -> hmac::Key {
+    [date, region, service, "aws4_request"].into_iter().fold(
+        hmac::Key::new(hmac::HMAC_SHA256, format!("AWS4{secret}").as_bytes()),
+        |key, part| {
+            hmac::Key::new(
+                hmac::HMAC_SHA256,
+                hmac::sign(&key, part.as_bytes()).as_ref(),
+            )
+        },
+    )
+}
+
+/// The canonical form of `request` SigV4 signs, along with the names of
+/// the headers it signs. Services other than S3 encode the path twice.
+fn canonical_request(
+    request: &HttpRequest,
+    encode_path_twice: bool,
+    payload_hash: &str,
+) -> Result<(String, String)> {
+    let url = &request.url;
+
+    let path = match url.path() {
+        "" => "/",
+        path => path,
+    };
+    let path = if encode_path_twice {
+        uri_encode(path, false)
+    } else {
+        path.to_string()
+    };
+
+    let mut query: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(name, value)| (uri_encode(&name, true), uri_encode(&value, true)))
+        .collect();
+    query.sort();
+    let query = query
+        .iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    let mut headers: Vec<(String, String)> = Vec::new();
+    if !request
+        .headers
+        .iter()
+        .any(|(name, _)| name.trim().eq_ignore_ascii_case("host"))
+    {
+        headers.push(("host".to_string(), host(url)?));
+    }
+    for (name, value) in &request.headers {
+        let name = name.trim().to_ascii_lowercase();
+        if UNSIGNED_HEADERS.contains(&name.as_str()) {
+            continue;
+        }
+
+        let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+        match headers.iter_mut().find(|(header, _)| *header == name) {
+            Some((_, values)) => {
+                values.push(',');
+                values.push_str(&value);
+            }
+            None => headers.push((name, value)),
+        }
+    }
+    headers.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{name}:{value}\n"))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+
+    let canonical_request = format!(
+        "{}\n{path}\n{query}\n{canonical_headers}\n{signed_headers}\n{payload_hash}",
+        request.method
+    );
+
+    Ok((canonical_request, signed_headers))
+}
+
+/// Percent-encodes everything but unreserved characters, and slashes too
+/// when `encode_slash`.
+fn uri_encode(text: &str, encode_slash: bool) -> String {
+    let mut encoded = String::with_capacity(text.len());
+
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' if !encode_slash => encoded.push('/'),
+            byte => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+
+    encoded
+}
+
+/// The `Host` header the request is sent with.
+fn host(url: &url::Url) -> Result<String> {
+    let host = url.host_str().ok_or_else(|| anyhow!("{url} has no host"))?;
+
+    Ok(match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_string(),
+    })
+}
+
+/// Formats `time` the way `X-Amz-Date` has it, as `20150830T123600Z`.
+fn amz_date(time: SystemTime) -> String {
+    let time = unix_time(time);
+    let (year, month, day) = civil_from_days((time / 86_400) as i64);
+    let seconds = time % 86_400;
+
+    format!(
+        "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Signs a message made of parts of the request with a shared key, as
+/// many webhook and partner APIs expect.
+///
+/// The message and the header value are templates, in which `{method}`,
+/// `{path}` (with the query), `{host}`, `{timestamp}` (seconds since the
+/// Unix epoch), `{body}`, `{body_sha256}` and `{header:Name}` stand for
+/// those parts of the request. `{signature}` in the header value stands for
+/// the signature.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HmacSigner {
+    #[serde(default)]
+    pub algorithm: HmacAlgorithm,
+    pub key: String,
+    #[serde(default = "default_message")]
+    pub message: String,
+    #[serde(default = "default_header")]
+    pub header: String,
+    #[serde(default = "default_value")]
+    pub value: String,
+    #[serde(default)]
+    pub encoding: SignatureEncoding,
+    /// Also sends the timestamp signed in this header, for the server to
+    /// check the signature with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_header: Option<String>,
+}
+
+fn default_message() -> String {
+    "{method}\n{path}\n{timestamp}\n{body_sha256}".to_string()
+}
+
+fn default_header() -> String {
+    "X-Signature".to_string()
+}
+
+fn default_value() -> String {
+    "{signature}".to_string()
+}
+
+impl Default for HmacSigner {
+    fn default() -> Self {
+        Self {
+            algorithm: HmacAlgorithm::default(),
+            key: String::new(),
+            message: default_message(),
+            header: default_header(),
+            value: default_value(),
+            encoding: SignatureEncoding::default(),
+            timestamp_header: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HmacAlgorithm {
+    Sha1,
+    #[default]
+    Sha256,
+    Sha512,
+}
+
+impl HmacAlgorithm {
+    pub const ALL: [HmacAlgorithm; 3] = [Self::Sha1, Self::Sha256, Self::Sha512];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HmacAlgorithm::Sha1 => "HMAC-SHA1",
+            HmacAlgorithm::Sha256 => "HMAC-SHA256",
+            HmacAlgorithm::Sha512 => "HMAC-SHA512",
+        }
+    }
+
+    fn ring(&self) -> hmac::Algorithm {
+        match self {
+            HmacAlgorithm::Sha1 => hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY,
+            HmacAlgorithm::Sha256 => hmac::HMAC_SHA256,
+            HmacAlgorithm::Sha512 => hmac::HMAC_SHA512,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureEncoding {
+    #[default]
+    Hex,
+    Base64,
+}
+
+impl HmacSigner {
+    async fn sign(&self, request: &mut HttpRequest, now: SystemTime) -> Result<()> {
+        let header = self.header.trim();
+        if header.is_empty() {
+            bail!("the HMAC signature has no header to go in");
+        }
+        let replaced = [
+            Some(header),
+            self.timestamp_header.as_deref().map(str::trim),
+        ];
+        request.headers.retain(|(name, _)| {
+            !replaced
+                .iter()
+                .flatten()
+                .any(|replaced| name.trim().eq_ignore_ascii_case(replaced))
+        });
+
+        let timestamp = unix_time(now).to_string();
+        if let Some(timestamp_header) = &self.timestamp_header {
+            request
+                .headers
+                .push((timestamp_header.trim().to_string(), timestamp.clone()));
+        }
+
+        let body = if self.message.contains("{body}") {
+            Some(body_bytes(&request.body).await?)
+        } else {
+            None
+        };
+        let body_sha256 = if self.message.contains("{body_sha256}") {
+            Some(hex(&body_sha256(&request.body).await?))
+        } else {
+            None
+        };
+
+        let request_ref = &*request;
+        let part = |name: &str| -> Result<Option<String>> {
+            Ok(match name {
+                "method" => Some(request_ref.method.to_string()),
+                "path" => Some(match request_ref.url.query() {
+                    Some(query) => format!("{}?{query}", request_ref.url.path()),
+                    None => request_ref.url.path().to_string(),
+                }),
+                "host" => Some(host(&request_ref.url)?),
+                "timestamp" => Some(timestamp.clone()),
+                "body" => body
+                    .as_ref()
+                    .map(|body| String::from_utf8_lossy(body).into_owned()),
+                "body_sha256" => body_sha256.clone(),
+                name => match name.strip_prefix("header:") {
+                    Some(header) => Some(
+                        request_ref
+                            .headers
+                            .iter()
+                            .filter(|(name, _)| name.trim().eq_ignore_ascii_case(header.trim()))
+                            .map(|(_, value)| value.trim())
+                            .collect::<Vec<_>>()
+                            .join(","),
+                    ),
+                    None => None,
+                },
+            })
+        };
+
+        let message = expand(&self.message, |name| part(name))
+            .context("building the message of the HMAC signature")?;
+        let key = hmac::Key::new(self.algorithm.ring(), self.key.as_bytes());
+        let signature = hmac::sign(&key, message.as_bytes());
+        let signature = match self.encoding {
+            SignatureEncoding::Hex => hex(signature.as_ref()),
+            SignatureEncoding::Base64 => BASE64_STANDARD.encode(signature.as_ref()),
+        };
+
+        let value = expand(&self.value, |name| match name {
+            "signature" => Ok(Some(signature.clone())),
+            name => part(name),
+        })
+        .context("building the HMAC signature header")?;
+        request.headers.push((header.to_string(), value));
+
+        Ok(())
+    }
+}
+
+/// Replaces the `{name}` placeholders of `template` with what `part` has
+/// for them.
+fn expand(template: &str, part: impl Fn(&str) -> Result<Option<String>>) -> Result<String> {
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .map(|end| start + end)
+            .with_context(|| format!("unclosed placeholder in {template:?}"))?;
+        let name = &rest[start + 1..end];
+
+        match part(name)? {
+            Some(value) => expanded.push_str(&value),
+            None => bail!("unknown placeholder {{{name}}}"),
+        }
+        rest = &rest[end + 1..];
+    }
+    expanded.push_str(rest);
+
+    Ok(expanded)
+}
+
+async fn body_bytes(body: &HttpBody) -> Result<Vec<u8>> {
+    body.chunks()
+        .try_fold(Vec::new(), |mut bytes, chunk| async move {
+            bytes.extend_from_slice(&chunk);
+            Ok(bytes)
+        })
+        .await
+        .context("reading the body to sign")
+}
+
+async fn body_sha256(body: &HttpBody) -> Result<Vec<u8>> {
+    let hasher = body
+        .chunks()
+        .try_fold(Sha256::new(), |mut hasher, chunk| async move {
+            hasher.update(&chunk);
+            Ok(hasher)
+        })
+        .await
+        .context("reading the body to sign")?;
+
+    Ok(hasher.finalize().to_vec())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn unix_time(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
@@ -0,0 +1,184 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read as _, Write as _},
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use anyhow::{Context as _, Result};
+use bytes::{Bytes, BytesMut};
+use tokio::io::AsyncWriteExt as _;
+
+use crate::encoding::ContentEncoding;
+
+/// How much of a spilled body is kept in memory to be shown.
+pub const PREVIEW_SIZE: usize = 1 << 20;
+
+static NEXT_FILE_ID: AtomicU64 = AtomicU64::new(1);
+
+/// A file in the temporary folder, removed once dropped.
+#[derive(Debug)]
+pub struct TempFile {
+    path: PathBuf,
+    len: u64,
+}
+
+impl TempFile {
+    fn new() -> Self {
+        let name = format!(
+            "http-client-{}-{}.body",
+            std::process::id(),
+            NEXT_FILE_ID.fetch_add(1, Ordering::Relaxed)
+        );
+
+        Self {
+            path: std::env::temp_dir().join(name),
+            len: 0,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The first `PREVIEW_SIZE` bytes of the file.
+    fn preview(&self) -> Result<Bytes> {
+        let mut preview = Vec::new();
+
+        File::open(&self.path)?
+            .take(PREVIEW_SIZE as u64)
+            .read_to_end(&mut preview)?;
+
+        Ok(preview.into())
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        std::fs::remove_file(&self.path).ok();
+    }
+}
+
+/// A response body that was written to disk as it was received, for being
+/// larger than `RequestOptions::spill_threshold`.
+#[derive(Debug, Clone)]
+pub struct SpilledBody {
+    /// The body with its content encodings undone, or as received when they
+    /// couldn't be.
+    pub body: Arc<TempFile>,
+    /// The body as received, the same file as `body` when it had no content
+    /// encoding.
+    pub raw_body: Arc<TempFile>,
+}
+
+/// Collects a body in memory, moving it to a temporary file once it grows
+/// past the threshold.
+pub(crate) struct BodySink {
+    threshold: Option<u64>,
+    buffer: BytesMut,
+    file: Option<(TempFile, tokio::fs::File)>,
+}
+
+/// A body once fully received.
+pub(crate) enum ReceivedBody {
+    Memory(Bytes),
+    Spilled(TempFile),
+}
+
+impl BodySink {
+    pub fn new(threshold: Option<u64>) -> Self {
+        Self {
+            threshold,
+            buffer: BytesMut::new(),
+            file: None,
+        }
+    }
+
+    pub async fn write(&mut self, data: &[u8]) -> Result<()> {
+        if let Some((temp, file)) = self.file.as_mut() {
+            file.write_all(data).await.context("writing spilled body")?;
+            temp.len += data.len() as u64;
+            return Ok(());
+        }
+
+        self.buffer.extend_from_slice(data);
+
+        if let Some(threshold) = self.threshold
+            && self.buffer.len() as u64 > threshold
+        {
+            let mut temp = TempFile::new();
+            let mut file = tokio::fs::File::create(&temp.path)
+                .await
+                .with_context(|| format!("creating {}", temp.path.display()))?;
+
+            file.write_all(&self.buffer)
+                .await
+                .context("writing spilled body")?;
+            temp.len = self.buffer.len() as u64;
+            self.buffer = BytesMut::new();
+            self.file = Some((temp, file));
+        }
+
+        Ok(())
+    }
+
+    pub async fn finish(self) -> Result<ReceivedBody> {
+        match self.file {
+            Some((temp, mut file)) => {
+                file.flush().await.context("writing spilled body")?;
+                Ok(ReceivedBody::Spilled(temp))
+            }
+            None => Ok(ReceivedBody::Memory(self.buffer.freeze())),
+        }
+    }
+}
+
+/// Undoes `encodings` on a spilled body, last applied first, writing each
+/// step to a new file. Returns the decoded body along with the previews of
+/// it and of the raw body.
+pub(crate) fn decode(
+    raw_body: TempFile,
+    encodings: &[ContentEncoding],
+) -> Result<(Bytes, Bytes, SpilledBody, Option<String>)> {
+    let raw_preview = raw_body.preview()?;
+    let raw_body = Arc::new(raw_body);
+
+    let decoded = encodings
+        .iter()
+        .rev()
+        .try_fold(raw_body.clone(), |body, encoding| {
+            let mut decoded = TempFile::new();
+            let reader = BufReader::new(File::open(body.path())?);
+            let mut writer = BufWriter::new(File::create(decoded.path())?);
+
+            encoding.decode(reader, &mut writer)?;
+            writer.flush()?;
+            decoded.len = std::fs::metadata(decoded.path())?.len();
+
+            anyhow::Ok(Arc::new(decoded))
+        });
+
+    let (body, decode_error) = match decoded {
+        Ok(body) => (body, None),
+        Err(error) => (raw_body.clone(), Some(format!("{error:#}"))),
+    };
+    let preview = body.preview()?;
+
+    Ok((
+        preview,
+        raw_preview,
+        SpilledBody { body, raw_body },
+        decode_error,
+    ))
+}
@@ -0,0 +1,415 @@
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use futures::channel::mpsc;
+use http::{
+    HeaderMap, StatusCode,
+    header::{ACCEPT, ACCEPT_ENCODING, CACHE_CONTROL, CONTENT_ENCODING, CONTENT_TYPE, COOKIE},
+    response::Parts,
+};
+use http_body_util::BodyExt;
+use hyper::body::Incoming;
+
+use crate::{
+    HttpRequest, RequestOptions, build_request, has_header, open_connection,
+    pool::{PoolKey, PooledConnection},
+    redirect, runtime,
+    timeout::timeout,
+};
+
+/// How long to wait before reconnecting when the server didn't say.
+pub const DEFAULT_RETRY: Duration = Duration::from_secs(3);
+
+/// An event of a `text/event-stream` response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseEvent {
+    /// The `event` field, `message` when the event has none.
+    pub event: String,
+    pub data: String,
+    /// The last event ID when the event was dispatched, which may have been
+    /// set by an earlier event.
+    pub id: Option<String>,
+    /// The reconnection time set along with the event, if any.
+    pub retry: Option<Duration>,
+}
+
+/// Splits an event stream into events as its bytes arrive, following the
+/// HTML event stream interpretation rules.
+#[derive(Debug, Default)]
+pub struct SseParser {
+    /// The bytes of the line being received.
+    line: Vec<u8>,
+    /// Whether the first line was read, which may start with a BOM.
+    started: bool,
+    /// Whether the last line ended with a CR, in which case an LF right
+    /// after it belongs to the same line ending.
+    after_cr: bool,
+    event: String,
+    data: String,
+    has_data: bool,
+    retry: Option<Duration>,
+    last_event_id: Option<String>,
+    reconnection_time: Option<Duration>,
+}
+
+impl SseParser {
+    /// A parser for a stream resumed after `last_event_id`, if any.
+    pub fn new(last_event_id: Option<String>) -> Self {
+        Self {
+            last_event_id,
+            ..Self::default()
+        }
+    }
+
+    /// The ID to send in `Last-Event-ID` when reconnecting.
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.last_event_id.as_deref()
+    }
+
+    /// The reconnection time last set by the stream.
+    pub fn reconnection_time(&self) -> Option<Duration> {
+        self.reconnection_time
+    }
+
+    /// Parses `bytes`, returning the events they complete.
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<SseEvent> {
+        let mut events = Vec::new();
+
+        for &byte in bytes {
+            match byte {
+                b'\n' if self.after_cr => self.after_cr = false,
+                b'\r' | b'\n' => {
+                    self.after_cr = byte == b'\r';
+                    let line = std::mem::take(&mut self.line);
+                    let mut line = &line[..];
+                    if !std::mem::replace(&mut self.started, true) {
+                        line = line.strip_prefix("\u{feff}".as_bytes()).unwrap_or(line);
+                    }
+                    events.extend(self.process_line(&String::from_utf8_lossy(line)));
+                }
+                _ => {
+                    self.after_cr = false;
+                    self.line.push(byte);
+                }
+            }
+        }
+
+        events
+    }
+
+    /// Drops the line and event being received when the stream is lost.
+    fn discard_partial(&mut self) {
+        *self = Self {
+            started: self.started,
+            last_event_id: self.last_event_id.take(),
+            reconnection_time: self.reconnection_time,
+            ..Self::default()
+        };
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+
+        match field {
+            "event" => self.event = value.to_string(),
+            "data" => {
+                if self.has_data {
+                    self.data.push('\n');
+                }
+                self.data.push_str(value);
+                self.has_data = true;
+            }
+            "id" if !value.contains('\0') => {
+                self.last_event_id = Some(value.to_string()).filter(|id| !id.is_empty());
+            }
+            "retry" if !value.is_empty() && value.bytes().all(|byte| byte.is_ascii_digit()) => {
+                if let Ok(millis) = value.parse() {
+                    let retry = Duration::from_millis(millis);
+                    self.retry = Some(retry);
+                    self.reconnection_time = Some(retry);
+                }
+            }
+            _ => {}
+        }
+
+        None
+    }
+
+    /// Ends the event being received at a blank line. Events without data
+    /// are dropped.
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let event = std::mem::take(&mut self.event);
+        let data = std::mem::take(&mut self.data);
+        let retry = self.retry.take();
+
+        if !std::mem::take(&mut self.has_data) {
+            return None;
+        }
+
+        Some(SseEvent {
+            event: if event.is_empty() {
+                "message".to_string()
+            } else {
+                event
+            },
+            data,
+            id: self.last_event_id.clone(),
+            retry,
+        })
+    }
+}
+
+/// What happens on an event stream subscription, in order.
+#[derive(Debug, Clone)]
+pub enum SseMessage {
+    /// The stream was opened, or reopened after a reconnection.
+    Open {
+        status: StatusCode,
+        headers: HeaderMap,
+    },
+    Event {
+        event: SseEvent,
+        received_at: SystemTime,
+    },
+    /// The stream was lost, and is reopened after `retry_in`.
+    Reconnecting {
+        /// Why the stream was lost, when it didn't simply end.
+        error: Option<String>,
+        retry_in: Duration,
+    },
+}
+
+/// Opens `request` as an event stream on the http client runtime, resuming
+/// after `last_event_id` if given. The stream is reopened whenever it ends or
+/// is lost once it was opened, with `Last-Event-ID` set to the last ID seen.
+///
+/// The returned future completes when the server answers a reconnection
+/// with 204 No Content, and fails when the stream can't be opened. Dropping
+/// it closes the stream.
+pub fn subscribe(
+    request: HttpRequest,
+    options: RequestOptions,
+    last_event_id: Option<String>,
+) -> (
+    impl Future<Output = Result<()>> + Send + 'static,
+    mpsc::UnboundedReceiver<SseMessage>,
+) {
+    let (messages, receiver) = mpsc::unbounded();
+
+    (
+        runtime::spawn(run(request, options, last_event_id, messages)),
+        receiver,
+    )
+}
+
+/// How a connection to the stream ended.
+enum Closed {
+    /// For good, successfully or not.
+    Stop(Result<()>),
+    /// In a way the stream can be reopened after.
+    Lost(Option<String>),
+}
+
+async fn run(
+    request: HttpRequest,
+    options: RequestOptions,
+    last_event_id: Option<String>,
+    messages: mpsc::UnboundedSender<SseMessage>,
+) -> Result<()> {
+    let mut parser = SseParser::new(last_event_id);
+    let mut opened = false;
+
+    loop {
+        let error = match stream(&request, &options, &mut parser, &messages, &mut opened).await {
+            Closed::Stop(result) => return result,
+            Closed::Lost(error) => error,
+        };
+
+        let retry_in = parser.reconnection_time().unwrap_or(DEFAULT_RETRY);
+        if messages
+            .unbounded_send(SseMessage::Reconnecting { error, retry_in })
+            .is_err()
+        {
+            return Ok(());
+        }
+        tokio::time::sleep(retry_in).await;
+    }
+}
+
+/// Opens the stream once, following redirects, and reads it until it ends.
+async fn stream(
+    request: &HttpRequest,
+    options: &RequestOptions,
+    parser: &mut SseParser,
+    messages: &mpsc::UnboundedSender<SseMessage>,
+    opened: &mut bool,
+) -> Closed {
+    // Failing to reach the server is only worth retrying once it served the
+    // stream, since it's likely a mistake otherwise.
+    let lost = |error: anyhow::Error, opened: bool| {
+        if opened {
+            Closed::Lost(Some(format!("{error:#}")))
+        } else {
+            Closed::Stop(Err(error))
+        }
+    };
+
+    parser.discard_partial();
+    let (parts, mut body, _connection) = match open(request, options, parser.last_event_id()).await
+    {
+        Ok(response) => response,
+        Err(error) => return lost(error, *opened),
+    };
+
+    if parts.status == StatusCode::NO_CONTENT {
+        return Closed::Stop(Ok(()));
+    }
+    if let Err(error) = check_head(&parts) {
+        return Closed::Stop(Err(error));
+    }
+
+    *opened = true;
+    if messages
+        .unbounded_send(SseMessage::Open {
+            status: parts.status,
+            headers: parts.headers,
+        })
+        .is_err()
+    {
+        return Closed::Stop(Ok(()));
+    }
+
+    loop {
+        let frame = match body.frame().await {
+            Some(Ok(frame)) => frame,
+            Some(Err(error)) => return lost(anyhow!(error).context("reading the stream"), true),
+            None => return Closed::Lost(None),
+        };
+        let Ok(data) = frame.into_data() else {
+            continue;
+        };
+
+        let received_at = SystemTime::now();
+        for event in parser.feed(&data) {
+            if messages
+                .unbounded_send(SseMessage::Event { event, received_at })
+                .is_err()
+            {
+                return Closed::Stop(Ok(()));
+            }
+        }
+    }
+}
+
+/// Sends the request for the stream and waits for the head of the response,
+/// following redirects. The connection is returned along with the response
+/// so that it stays open while the body is read.
+async fn open(
+    request: &HttpRequest,
+    options: &RequestOptions,
+    last_event_id: Option<&str>,
+) -> Result<(Parts, Incoming, PooledConnection)> {
+    let mut request = request.clone();
+    let mut cookies = options.cookies.clone();
+    let mut hops = 0;
+
+    loop {
+        let mut sent = request.clone();
+        let mut default = |name: &str, value: &str| {
+            if !has_header(&sent, name) {
+                sent.headers.push((name.to_string(), value.to_string()));
+            }
+        };
+        default(ACCEPT.as_str(), "text/event-stream");
+        default(CACHE_CONTROL.as_str(), "no-cache");
+        // Events are parsed as they arrive, which compression would get in
+        // the way of.
+        default(ACCEPT_ENCODING.as_str(), "identity");
+        if let Some(id) = last_event_id {
+            default("last-event-id", id);
+        }
+        if let Some(jar) = &cookies
+            && !has_header(&sent, COOKIE.as_str())
+            && let Some(cookie) = jar.header_for(&sent.url, SystemTime::now())
+        {
+            sent.headers.push((COOKIE.to_string(), cookie));
+        }
+
+        let key = PoolKey::new(&sent.url, options);
+        let (mut connection, _) = open_connection(&sent.url, options, &key).await?;
+        let content_length = sent.body.len().await?;
+        let response = connection.sender.send(build_request(
+            &sent,
+            content_length,
+            connection.sender.is_h2(),
+            connection.proxy.as_ref().filter(|_| connection.forward),
+        )?);
+        let response = timeout(
+            options.timeouts.read,
+            || "waiting for the response".to_string(),
+            async { Ok(response.await?) },
+        )
+        .await?;
+        let (parts, body) = response.into_parts();
+
+        let location = if options.redirect.follow {
+            redirect::location(&request.url, parts.status, &parts.headers)?
+        } else {
+            None
+        };
+        let Some(location) = location else {
+            return Ok((parts, body, connection));
+        };
+
+        if hops == options.redirect.max_hops {
+            bail!("too many redirects, stopped after {hops} at {location}");
+        }
+        hops += 1;
+
+        if let Some(jar) = cookies.as_mut() {
+            jar.store_response(&request.url, &parts.headers, SystemTime::now());
+        }
+        request = redirect::next_request(&request, parts.status, location, options);
+    }
+}
+
+/// Fails unless the response is an uncompressed event stream.
+fn check_head(parts: &Parts) -> Result<()> {
+    if parts.status != StatusCode::OK {
+        bail!(
+            "the server answered {} instead of an event stream",
+            parts.status
+        );
+    }
+
+    let content_type = parts
+        .headers
+        .get(CONTENT_TYPE)
+        .map(|value| value.to_str().context("invalid Content-Type header"))
+        .transpose()?
+        .unwrap_or_default();
+    let mime = content_type.split(';').next().unwrap_or_default().trim();
+    if !mime.eq_ignore_ascii_case("text/event-stream") {
+        bail!("expected text/event-stream, got {content_type:?}");
+    }
+
+    if parts
+        .headers
+        .get(CONTENT_ENCODING)
+        .is_some_and(|value| value.as_bytes() != b"identity")
+    {
+        bail!("compressed event streams aren't supported");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+/// How long a request may wait at each stage before failing. Stages without
+/// a limit wait for as long as it takes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Timeouts {
+    /// Resolving the host and opening the connection, including the TLS and
+    /// proxy handshakes.
+    pub connect: Option<Duration>,
+    /// Waiting for the response to start, then for each chunk of its body.
+    pub read: Option<Duration>,
+    /// The whole request, redirects included.
+    pub total: Option<Duration>,
+}
+
+/// Awaits `future`, failing with "timed out {what}" when it takes longer
+/// than `limit`.
+pub(crate) async fn timeout<T>(
+    limit: Option<Duration>,
+    what: impl FnOnce() -> String,
+    future: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    let Some(limit) = limit else {
+        return future.await;
+    };
+
+    tokio::time::timeout(limit, future)
+        .await
+        .map_err(|_| anyhow!("timed out {} after {limit:?}", what()))?
+}
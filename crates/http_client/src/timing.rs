@@ -0,0 +1,100 @@
+use std::{fmt, net::SocketAddr, path::PathBuf, time::Duration};
+
+use serde::{Serialize, Serializer};
+
+/// How long each phase of a request took, in the order they happen.
+///
+/// The connection phases are absent when a pooled connection was reused,
+/// and DNS is also absent when the URL names an IP address.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Timing {
+    #[serde(rename = "dns_ms", serialize_with = "optional_millis")]
+    pub dns: Option<Duration>,
+    #[serde(rename = "connect_ms", serialize_with = "optional_millis")]
+    pub connect: Option<Duration>,
+    #[serde(rename = "tls_ms", serialize_with = "optional_millis")]
+    pub tls: Option<Duration>,
+    /// From writing the request until the response head arrived.
+    #[serde(rename = "first_byte_ms", serialize_with = "millis")]
+    pub first_byte: Duration,
+    #[serde(rename = "download_ms", serialize_with = "millis")]
+    pub download: Duration,
+    /// Everything, including waiting for a pooled connection.
+    #[serde(rename = "total_ms", serialize_with = "millis")]
+    pub total: Duration,
+}
+
+impl Timing {
+    /// The phases that took place, labelled for display.
+    pub fn phases(&self) -> Vec<(&'static str, Duration)> {
+        [
+            ("DNS Lookup", self.dns),
+            ("TCP Connect", self.connect),
+            ("TLS Handshake", self.tls),
+            ("Time to First Byte", Some(self.first_byte)),
+            ("Download", Some(self.download)),
+        ]
+        .into_iter()
+        .filter_map(|(label, duration)| Some((label, duration?)))
+        .collect()
+    }
+}
+
+/// Which connection a request was sent over.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionInfo {
+    /// Identifies the connection among those opened by this process.
+    pub id: u64,
+    /// The address of the proxy when going through one, and of the origin
+    /// otherwise.
+    pub remote_addr: RemoteAddr,
+    /// The URL of the proxy the connection goes through, without its
+    /// password.
+    pub proxy: Option<String>,
+    /// Whether the connection was taken from the pool rather than opened.
+    pub reused: bool,
+}
+
+/// Where a connection leads to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum RemoteAddr {
+    Tcp(SocketAddr),
+    /// The path of a Unix domain socket.
+    Unix(PathBuf),
+}
+
+impl From<SocketAddr> for RemoteAddr {
+    fn from(address: SocketAddr) -> Self {
+        Self::Tcp(address)
+    }
+}
+
+impl PartialEq<SocketAddr> for RemoteAddr {
+    fn eq(&self, other: &SocketAddr) -> bool {
+        matches!(self, Self::Tcp(address) if address == other)
+    }
+}
+
+impl fmt::Display for RemoteAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(address) => write!(f, "{address}"),
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+fn millis<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64() * 1000.0)
+}
+
+fn optional_millis<S: Serializer>(
+    duration: &Option<Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match duration {
+        Some(duration) => millis(duration, serializer),
+        None => serializer.serialize_none(),
+    }
+}
@@ -0,0 +1,240 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{Arc, LazyLock},
+};
+
+use anyhow::{Context as _, Result, ensure};
+use rustls::{
+    ClientConfig, ClientConnection, DigitallySignedStruct, RootCertStore, SignatureScheme,
+    client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    crypto::{CryptoProvider, verify_tls12_signature, verify_tls13_signature},
+    pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime, pem::PemObject},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{certificate::CertificateInfo, pkcs12};
+
+/// The platform's trusted root certificates, loaded once.
+static NATIVE_ROOTS: LazyLock<RootCertStore> = LazyLock::new(|| {
+    let mut roots = RootCertStore::empty();
+    let certificates = rustls_native_certs::load_native_certs();
+
+    roots.add_parsable_certificates(certificates.certs);
+    roots
+});
+
+/// The lowest TLS version a connection may negotiate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TlsVersion {
+    #[default]
+    #[serde(rename = "1.2")]
+    Tls12,
+    #[serde(rename = "1.3")]
+    Tls13,
+}
+
+/// A certificate and key to authenticate to servers requiring mutual TLS.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "format", rename_all = "snake_case")]
+pub enum ClientCertificate {
+    Pem {
+        certificate: PathBuf,
+        key: PathBuf,
+    },
+    Pkcs12 {
+        path: PathBuf,
+        #[serde(default)]
+        password: String,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TlsOptions {
+    /// PEM bundles of certificate authorities to trust on top of the
+    /// platform's.
+    pub ca_certificates: Vec<PathBuf>,
+    pub client_certificate: Option<ClientCertificate>,
+    /// The name to send as SNI and verify the certificate against, instead
+    /// of the host of the URL.
+    pub server_name: Option<String>,
+    pub min_version: TlsVersion,
+    /// Accepts any certificate, leaving the connection open to interception.
+    pub skip_verification: bool,
+}
+
+impl TlsOptions {
+    pub(crate) fn server_name(&self, host: &str) -> Result<ServerName<'static>> {
+        let name = self.server_name.as_deref().unwrap_or(host);
+
+        ServerName::try_from(name.to_string())
+            .with_context(|| format!("invalid server name {name}"))
+    }
+
+    pub(crate) fn client_config(&self, alpn_protocols: Vec<Vec<u8>>) -> Result<ClientConfig> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let versions: &[_] = match self.min_version {
+            TlsVersion::Tls12 => &[&rustls::version::TLS13, &rustls::version::TLS12],
+            TlsVersion::Tls13 => &[&rustls::version::TLS13],
+        };
+        let builder = ClientConfig::builder_with_provider(provider.clone())
+            .with_protocol_versions(versions)?;
+
+        let builder = if self.skip_verification {
+            builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(SkipVerification(provider)))
+        } else {
+            builder.with_root_certificates(self.root_certificates()?)
+        };
+
+        let mut config = match &self.client_certificate {
+            Some(certificate) => {
+                let (certificates, key) = load_client_certificate(certificate)?;
+
+                builder
+                    .with_client_auth_cert(certificates, key)
+                    .context("invalid client certificate")?
+            }
+            None => builder.with_no_client_auth(),
+        };
+
+        config.alpn_protocols = alpn_protocols;
+        Ok(config)
+    }
+
+    fn root_certificates(&self) -> Result<RootCertStore> {
+        let mut roots = NATIVE_ROOTS.clone();
+
+        for path in &self.ca_certificates {
+            let certificates = CertificateDer::pem_file_iter(path)
+                .and_then(|certificates| certificates.collect::<Result<Vec<_>, _>>())
+                .with_context(|| format!("reading CA certificates from {}", path.display()))?;
+            ensure!(
+                !certificates.is_empty(),
+                "no certificates found in {}",
+                path.display()
+            );
+
+            for certificate in certificates {
+                roots
+                    .add(certificate)
+                    .with_context(|| format!("invalid CA certificate in {}", path.display()))?;
+            }
+        }
+
+        Ok(roots)
+    }
+}
+
+fn load_client_certificate(
+    certificate: &ClientCertificate,
+) -> Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>)> {
+    match certificate {
+        ClientCertificate::Pem { certificate, key } => {
+            let certificates = CertificateDer::pem_file_iter(certificate)
+                .and_then(|certificates| certificates.collect::<Result<Vec<_>, _>>())
+                .with_context(|| format!("reading client certificate {}", certificate.display()))?;
+            let key = PrivateKeyDer::from_pem_file(key)
+                .with_context(|| format!("reading client key {}", key.display()))?;
+
+            Ok((certificates, key))
+        }
+        ClientCertificate::Pkcs12 { path, password } => {
+            let archive = fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+            let identity = pkcs12::parse(&archive, password)
+                .with_context(|| format!("reading client certificate {}", path.display()))?;
+
+            Ok((identity.certificates, identity.key))
+        }
+    }
+}
+
+/// Accepts every certificate while still checking that the server holds the
+/// key of the one it presented.
+#[derive(Debug)]
+struct SkipVerification(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for SkipVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        certificate: &CertificateDer<'_>,
+        signature: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            certificate,
+            signature,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        certificate: &CertificateDer<'_>,
+        signature: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            certificate,
+            signature,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// What was negotiated with the server, and the certificates it presented.
+#[derive(Debug, Clone, Serialize)]
+pub struct TlsInfo {
+    pub version: String,
+    pub cipher_suite: String,
+    /// The chain presented by the server, its own certificate first.
+    pub certificates: Vec<CertificateInfo>,
+    /// Whether the chain was checked against the trusted authorities.
+    pub verified: bool,
+}
+
+impl TlsInfo {
+    pub(crate) fn new(connection: &ClientConnection, options: &TlsOptions) -> Self {
+        let version = match connection.protocol_version() {
+            Some(rustls::ProtocolVersion::TLSv1_2) => "TLS 1.2".to_string(),
+            Some(rustls::ProtocolVersion::TLSv1_3) => "TLS 1.3".to_string(),
+            Some(version) => format!("{version:?}"),
+            None => "Unknown".to_string(),
+        };
+        let cipher_suite = connection
+            .negotiated_cipher_suite()
+            .map(|suite| format!("{:?}", suite.suite()))
+            .unwrap_or_default();
+        let certificates = connection
+            .peer_certificates()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|certificate| CertificateInfo::parse(certificate).ok())
+            .collect();
+
+        Self {
+            version,
+            cipher_suite,
+            certificates,
+            verified: !options.skip_verification,
+        }
+    }
+}
@@ -0,0 +1,551 @@
+use std::{
+    fmt,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use base64::{Engine as _, prelude::BASE64_STANDARD};
+use bytes::{Bytes, BytesMut};
+use futures::{Stream, StreamExt as _, channel::mpsc, stream};
+use http::{
+    HeaderMap, StatusCode,
+    header::{CONNECTION, COOKIE, SEC_WEBSOCKET_ACCEPT, SEC_WEBSOCKET_PROTOCOL, UPGRADE},
+};
+use hyper_util::rt::TokioIo;
+use ring::rand::{SecureRandom, SystemRandom};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::{
+    HttpRequest, HttpVersion, RequestOptions, build_request, has_header, open_connection,
+    pool::PoolKey, runtime, timeout::timeout,
+};
+
+/// The GUID the `Sec-WebSocket-Key` of the handshake is hashed with.
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+/// How large a message may get, fragments included.
+const MAX_MESSAGE_SIZE: usize = 64 << 20;
+/// How long to wait for the server to answer a close frame.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+const CONTINUATION: u8 = 0x0;
+const TEXT: u8 = 0x1;
+const BINARY: u8 = 0x2;
+const CLOSE: u8 = 0x8;
+const PING: u8 = 0x9;
+const PONG: u8 = 0xa;
+
+/// A message of a WebSocket connection, in either direction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebSocketMessage {
+    Text(String),
+    Binary(Bytes),
+    Ping(Bytes),
+    Pong(Bytes),
+    /// Starts or answers the closing handshake.
+    Close(Option<CloseFrame>),
+}
+
+/// The status code and reason of a close frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CloseFrame {
+    pub code: u16,
+    pub reason: String,
+}
+
+impl CloseFrame {
+    /// The meaning of the code when it is one of those RFC 6455 defines.
+    pub fn code_name(&self) -> Option<&'static str> {
+        Some(match self.code {
+            1000 => "Normal Closure",
+            1001 => "Going Away",
+            1002 => "Protocol Error",
+            1003 => "Unsupported Data",
+            1007 => "Invalid Payload Data",
+            1008 => "Policy Violation",
+            1009 => "Message Too Big",
+            1010 => "Mandatory Extension",
+            1011 => "Internal Error",
+            1012 => "Service Restart",
+            1013 => "Try Again Later",
+            1014 => "Bad Gateway",
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for CloseFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code)?;
+        if let Some(name) = self.code_name() {
+            write!(f, " {name}")?;
+        }
+        if !self.reason.is_empty() {
+            write!(f, ": {}", self.reason)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// What happens on a WebSocket connection, in order.
+#[derive(Debug, Clone)]
+pub enum WebSocketEvent {
+    /// The server accepted the handshake.
+    Open {
+        status: StatusCode,
+        headers: HeaderMap,
+        /// The subprotocol the server picked among those offered.
+        protocol: Option<String>,
+    },
+    Received {
+        message: WebSocketMessage,
+        at: SystemTime,
+    },
+    /// A message was written, including the pongs answering pings and the
+    /// close frame answering the server's.
+    Sent {
+        message: WebSocketMessage,
+        at: SystemTime,
+    },
+}
+
+/// Queues messages to send on a WebSocket connection.
+#[derive(Debug, Clone)]
+pub struct WebSocketSender(mpsc::UnboundedSender<WebSocketMessage>);
+
+impl WebSocketSender {
+    /// Queues `message`, failing when the connection is gone.
+    pub fn send(&self, message: WebSocketMessage) -> Result<()> {
+        self.0
+            .unbounded_send(message)
+            .map_err(|_| anyhow!("the connection is closed"))
+    }
+}
+
+/// The `Sec-WebSocket-Accept` a server answers the handshake `key` with.
+pub fn websocket_accept(key: &str) -> String {
+    let digest = ring::digest::digest(
+        &ring::digest::SHA1_FOR_LEGACY_USE_ONLY,
+        format!("{key}{ACCEPT_GUID}").as_bytes(),
+    );
+
+    BASE64_STANDARD.encode(digest.as_ref())
+}
+
+/// Opens a WebSocket connection to the `ws://` or `wss://` URL of `request`
+/// on the http client runtime, offering `protocols` as subprotocols. The
+/// headers of the request go with the handshake.
+///
+/// The returned future completes with the close frame of the server once
+/// the closing handshake is done, and fails when the connection can't be
+/// opened or is lost. Dropping it drops the connection.
+pub fn connect_websocket(
+    request: HttpRequest,
+    protocols: Vec<String>,
+    options: RequestOptions,
+) -> (
+    impl Future<Output = Result<Option<CloseFrame>>> + Send + 'static,
+    WebSocketSender,
+    mpsc::UnboundedReceiver<WebSocketEvent>,
+) {
+    let (sender, outgoing) = mpsc::unbounded();
+    let (events, receiver) = mpsc::unbounded();
+
+    (
+        runtime::spawn(run(request, protocols, options, outgoing, events)),
+        WebSocketSender(sender),
+        receiver,
+    )
+}
+
+/// What the connection loop waits for.
+enum Input {
+    Frame(Result<Frame>),
+    /// The server closed the connection.
+    Ended,
+    Send(WebSocketMessage),
+}
+
+async fn run(
+    request: HttpRequest,
+    protocols: Vec<String>,
+    options: RequestOptions,
+    outgoing: mpsc::UnboundedReceiver<WebSocketMessage>,
+    events: mpsc::UnboundedSender<WebSocketEvent>,
+) -> Result<Option<CloseFrame>> {
+    let (io, open) = handshake(request, &protocols, &options).await?;
+    events.unbounded_send(open).ok();
+
+    let (reader, mut writer) = tokio::io::split(io);
+    let mut inputs = stream::select(
+        frames(reader)
+            .map(Input::Frame)
+            .chain(stream::once(async { Input::Ended }))
+            .boxed(),
+        outgoing.map(Input::Send),
+    );
+    let random = SystemRandom::new();
+    let mut message = PartialMessage::default();
+    let mut close_sent = false;
+
+    loop {
+        let input = if close_sent {
+            tokio::time::timeout(CLOSE_TIMEOUT, inputs.next())
+                .await
+                .map_err(|_| anyhow!("the server didn't answer the close frame in time"))?
+        } else {
+            inputs.next().await
+        };
+
+        let reply = match input {
+            None | Some(Input::Ended | Input::Frame(Err(_))) if close_sent => return Ok(None),
+            None | Some(Input::Ended) => bail!("the connection was closed without a close frame"),
+            Some(Input::Frame(Err(error))) => return Err(error),
+            Some(Input::Send(message)) => {
+                // Nothing may follow a close frame.
+                if close_sent {
+                    continue;
+                }
+                message
+            }
+            Some(Input::Frame(Ok(frame))) => {
+                let Some(received) = message.push(frame)? else {
+                    continue;
+                };
+                let reply = match &received {
+                    WebSocketMessage::Ping(payload) if !close_sent => {
+                        Some(WebSocketMessage::Pong(payload.clone()))
+                    }
+                    WebSocketMessage::Close(frame) if !close_sent => {
+                        Some(WebSocketMessage::Close(frame.clone()))
+                    }
+                    _ => None,
+                };
+                let close = match &received {
+                    WebSocketMessage::Close(frame) => Some(frame.clone()),
+                    _ => None,
+                };
+
+                events
+                    .unbounded_send(WebSocketEvent::Received {
+                        message: received,
+                        at: SystemTime::now(),
+                    })
+                    .ok();
+
+                if let Some(reply) = reply {
+                    write_message(&mut writer, &reply, &random).await?;
+                    events
+                        .unbounded_send(WebSocketEvent::Sent {
+                            message: reply,
+                            at: SystemTime::now(),
+                        })
+                        .ok();
+                }
+                match close {
+                    Some(frame) => {
+                        writer.shutdown().await.ok();
+                        return Ok(frame);
+                    }
+                    None => continue,
+                }
+            }
+        };
+
+        close_sent = matches!(reply, WebSocketMessage::Close(_));
+        write_message(&mut writer, &reply, &random).await?;
+        events
+            .unbounded_send(WebSocketEvent::Sent {
+                message: reply,
+                at: SystemTime::now(),
+            })
+            .ok();
+    }
+}
+
+/// Opens the connection and upgrades it to a WebSocket, within the connect
+/// and read timeouts.
+async fn handshake(
+    mut request: HttpRequest,
+    protocols: &[String],
+    options: &RequestOptions,
+) -> Result<(TokioIo<hyper::upgrade::Upgraded>, WebSocketEvent)> {
+    let scheme = match request.url.scheme() {
+        "ws" | "http" => "http",
+        "wss" | "https" => "https",
+        scheme => bail!("unsupported WebSocket scheme {scheme}"),
+    };
+    request
+        .url
+        .set_scheme(scheme)
+        .map_err(|_| anyhow!("invalid WebSocket URL {}", request.url))?;
+
+    let mut key = [0; 16];
+    SystemRandom::new()
+        .fill(&mut key)
+        .map_err(|_| anyhow!("no randomness for the WebSocket key"))?;
+    let key = BASE64_STANDARD.encode(key);
+
+    request.headers.extend([
+        ("Connection".to_string(), "Upgrade".to_string()),
+        ("Upgrade".to_string(), "websocket".to_string()),
+        ("Sec-WebSocket-Version".to_string(), "13".to_string()),
+        ("Sec-WebSocket-Key".to_string(), key.clone()),
+    ]);
+    if !protocols.is_empty() {
+        request
+            .headers
+            .push(("Sec-WebSocket-Protocol".to_string(), protocols.join(", ")));
+    }
+    if let Some(jar) = &options.cookies
+        && !has_header(&request, COOKIE.as_str())
+        && let Some(cookie) = jar.header_for(&request.url, SystemTime::now())
+    {
+        request.headers.push((COOKIE.to_string(), cookie));
+    }
+
+    // Upgrades only exist in HTTP/1.1.
+    let options = RequestOptions {
+        version: HttpVersion::Http1,
+        ..options.clone()
+    };
+    let key_for_pool = PoolKey::new(&request.url, &options);
+    let (mut connection, _) = open_connection(&request.url, &options, &key_for_pool).await?;
+    let response = connection.sender.send(build_request(
+        &request,
+        0,
+        false,
+        connection.proxy.as_ref().filter(|_| connection.forward),
+    )?);
+    let response = timeout(
+        options.timeouts.read,
+        || "waiting for the handshake response".to_string(),
+        async { Ok(response.await?) },
+    )
+    .await?;
+
+    let status = response.status();
+    let headers = response.headers().clone();
+    if status != StatusCode::SWITCHING_PROTOCOLS {
+        bail!("the server answered {status} instead of switching to WebSocket");
+    }
+
+    let header = |name| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+    };
+    if !header(UPGRADE).eq_ignore_ascii_case("websocket")
+        || !header(CONNECTION)
+            .split(',')
+            .any(|token| token.trim().eq_ignore_ascii_case("upgrade"))
+    {
+        bail!("the server didn't upgrade the connection to WebSocket");
+    }
+    if header(SEC_WEBSOCKET_ACCEPT) != websocket_accept(&key) {
+        bail!("the server answered the handshake with the wrong Sec-WebSocket-Accept");
+    }
+    let protocol = Some(header(SEC_WEBSOCKET_PROTOCOL).to_string()).filter(|p| !p.is_empty());
+    if let Some(protocol) = &protocol
+        && !protocols.contains(protocol)
+    {
+        bail!("the server picked the subprotocol {protocol:?}, which wasn't offered");
+    }
+
+    let upgraded = hyper::upgrade::on(response)
+        .await
+        .context("upgrading the connection")?;
+
+    Ok((
+        TokioIo::new(upgraded),
+        WebSocketEvent::Open {
+            status,
+            headers,
+            protocol,
+        },
+    ))
+}
+
+/// A frame as read from the server.
+struct Frame {
+    fin: bool,
+    opcode: u8,
+    payload: Bytes,
+}
+
+/// The frames read from `reader`, until it ends or fails.
+fn frames(reader: impl AsyncRead + Send + Unpin) -> impl Stream<Item = Result<Frame>> + Send {
+    stream::unfold(Some(reader), |reader| async move {
+        let mut reader = reader?;
+
+        match read_frame(&mut reader).await {
+            Ok(Some(frame)) => Some((Ok(frame), Some(reader))),
+            Ok(None) => None,
+            Err(error) => Some((Err(error), None)),
+        }
+    })
+}
+
+/// Reads a frame, or `None` when the connection ends before one starts.
+async fn read_frame(reader: &mut (impl AsyncRead + Unpin)) -> Result<Option<Frame>> {
+    let mut head = [0; 2];
+    match reader.read_exact(&mut head).await {
+        Ok(_) => {}
+        Err(error) if error.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(error) => return Err(error.into()),
+    }
+
+    let fin = head[0] & 0x80 != 0;
+    if head[0] & 0x70 != 0 {
+        bail!("the server set reserved bits without an extension");
+    }
+    let opcode = head[0] & 0x0f;
+    let masked = head[1] & 0x80 != 0;
+    let len = match head[1] & 0x7f {
+        126 => u64::from(reader.read_u16().await?),
+        127 => reader.read_u64().await?,
+        len => u64::from(len),
+    };
+    if len > MAX_MESSAGE_SIZE as u64 {
+        bail!("the server sent a frame of {len} bytes, more than allowed");
+    }
+
+    let mut mask = [0; 4];
+    if masked {
+        reader.read_exact(&mut mask).await?;
+    }
+    let mut payload = vec![0; len as usize];
+    reader
+        .read_exact(&mut payload)
+        .await
+        .context("reading a frame")?;
+    if masked {
+        apply_mask(&mut payload, mask);
+    }
+
+    Ok(Some(Frame {
+        fin,
+        opcode,
+        payload: payload.into(),
+    }))
+}
+
+/// The fragments of a data message received so far.
+#[derive(Default)]
+struct PartialMessage {
+    opcode: Option<u8>,
+    payload: BytesMut,
+}
+
+impl PartialMessage {
+    /// Adds `frame`, returning the message it completes, if any. Control
+    /// frames may come between fragments.
+    fn push(&mut self, frame: Frame) -> Result<Option<WebSocketMessage>> {
+        if frame.opcode & 0x8 != 0 {
+            if !frame.fin || frame.payload.len() > 125 {
+                bail!("the server sent an invalid control frame");
+            }
+
+            return Ok(Some(match frame.opcode {
+                CLOSE => WebSocketMessage::Close(parse_close(&frame.payload)?),
+                PING => WebSocketMessage::Ping(frame.payload),
+                PONG => WebSocketMessage::Pong(frame.payload),
+                opcode => bail!("the server sent a frame with the unknown opcode {opcode:#x}"),
+            }));
+        }
+
+        match (frame.opcode, self.opcode) {
+            (CONTINUATION, None) => bail!("the server continued a message it didn't start"),
+            (CONTINUATION, Some(_)) => {}
+            (TEXT | BINARY, None) => self.opcode = Some(frame.opcode),
+            (TEXT | BINARY, Some(_)) => bail!("the server started a message within another"),
+            (opcode, _) => bail!("the server sent a frame with the unknown opcode {opcode:#x}"),
+        }
+
+        if self.payload.len() + frame.payload.len() > MAX_MESSAGE_SIZE {
+            bail!("the server sent a message larger than {MAX_MESSAGE_SIZE} bytes");
+        }
+        self.payload.extend_from_slice(&frame.payload);
+        if !frame.fin {
+            return Ok(None);
+        }
+
+        let payload = std::mem::take(&mut self.payload).freeze();
+        Ok(Some(match self.opcode.take() {
+            Some(TEXT) => WebSocketMessage::Text(
+                String::from_utf8(payload.into()).context("the server sent invalid UTF-8 text")?,
+            ),
+            _ => WebSocketMessage::Binary(payload),
+        }))
+    }
+}
+
+fn parse_close(payload: &[u8]) -> Result<Option<CloseFrame>> {
+    match payload {
+        [] => Ok(None),
+        [_] => bail!("the server sent a truncated close frame"),
+        [high, low, reason @ ..] => Ok(Some(CloseFrame {
+            code: u16::from_be_bytes([*high, *low]),
+            reason: String::from_utf8(reason.to_vec())
+                .context("the server sent an invalid close reason")?,
+        })),
+    }
+}
+
+/// Writes `message` as a single masked frame, as clients must.
+async fn write_message(
+    writer: &mut (impl AsyncWrite + Unpin),
+    message: &WebSocketMessage,
+    random: &SystemRandom,
+) -> Result<()> {
+    let (opcode, payload) = match message {
+        WebSocketMessage::Text(text) => (TEXT, Bytes::copy_from_slice(text.as_bytes())),
+        WebSocketMessage::Binary(bytes) => (BINARY, bytes.clone()),
+        WebSocketMessage::Ping(bytes) => (PING, bytes.clone()),
+        WebSocketMessage::Pong(bytes) => (PONG, bytes.clone()),
+        WebSocketMessage::Close(None) => (CLOSE, Bytes::new()),
+        WebSocketMessage::Close(Some(frame)) => {
+            let mut payload = frame.code.to_be_bytes().to_vec();
+            payload.extend_from_slice(frame.reason.as_bytes());
+            (CLOSE, payload.into())
+        }
+    };
+    if opcode & 0x8 != 0 && payload.len() > 125 {
+        bail!("control frames carry at most 125 bytes");
+    }
+
+    let mut frame = Vec::with_capacity(payload.len() + 14);
+    frame.push(0x80 | opcode);
+    match payload.len() {
+        len @ 0..126 => frame.push(0x80 | len as u8),
+        len @ 126..65_536 => {
+            frame.push(0x80 | 126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(0x80 | 127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+
+    let mut mask = [0; 4];
+    random
+        .fill(&mut mask)
+        .map_err(|_| anyhow!("no randomness for the frame mask"))?;
+    frame.extend_from_slice(&mask);
+    let start = frame.len();
+    frame.extend_from_slice(&payload);
+    apply_mask(&mut frame[start..], mask);
+
+    writer.write_all(&frame).await?;
+    writer.flush().await?;
+
+    Ok(())
+}
+
+fn apply_mask(bytes: &mut [u8], mask: [u8; 4]) {
+    for (index, byte) in bytes.iter_mut().enumerate() {
+        *byte ^= mask[index % 4];
+    }
+}
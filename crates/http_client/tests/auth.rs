@@ -0,0 +1,218 @@
+mod common;
+
+use std::{
+    net::SocketAddr,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use bytes::Bytes;
+use h2::{RecvStream, server::SendResponse};
+use http_client::{
+    DigestCredentials, HttpVersion, RequestOptions, basic_authorization,
+    http::{Request, Response},
+};
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use tokio::net::TcpListener;
+
+use crate::common::{get, runtime};
+
+const PASSWORD: &str = "secret";
+
+/// Serves h2c with prior knowledge, challenging requests without a valid
+/// Digest answer to the challenges the path names, and counting requests.
+async fn serve_digest(requests: Arc<AtomicUsize>) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            let requests = requests.clone();
+
+            tokio::spawn(async move {
+                let mut connection = h2::server::handshake(socket).await.unwrap();
+
+                while let Some(Ok((request, respond))) = connection.accept().await {
+                    requests.fetch_add(1, Ordering::SeqCst);
+                    tokio::spawn(challenge(request, respond));
+                }
+            });
+        }
+    });
+
+    address
+}
+
+async fn challenge(request: Request<RecvStream>, mut respond: SendResponse<Bytes>) {
+    let challenges: &[&str] = match request.uri().path() {
+        "/md5" => &[r#"Digest realm="api", nonce="abc", qop="auth,auth-int", opaque="xyz""#],
+        "/both" => &[
+            r#"Digest realm="api", nonce="abc", algorithm=MD5"#,
+            r#"Digest realm="api", nonce="def", algorithm=SHA-256, qop="auth""#,
+        ],
+        "/legacy" => &[r#"Basic realm="api", Digest realm="api", nonce="abc""#],
+        _ => &[r#"Basic realm="api""#],
+    };
+
+    let answer = request
+        .headers()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Digest "))
+        .map(|value| value.to_string());
+    let response = match answer {
+        Some(ref answer) if is_valid(answer, request.method().as_str()) => {
+            Response::builder().status(200).body(()).unwrap()
+        }
+        _ => {
+            let mut response = Response::builder().status(401);
+            for challenge in challenges {
+                response = response.header("www-authenticate", *challenge);
+            }
+            response.body(()).unwrap()
+        }
+    };
+
+    let mut stream = respond.send_response(response, false).unwrap();
+    stream
+        .send_data(Bytes::from(answer.unwrap_or_default()), true)
+        .unwrap();
+}
+
+/// Checks a Digest answer the way a server would, for the `PASSWORD` of
+/// whichever user it names.
+fn is_valid(answer: &str, method: &str) -> bool {
+    let param = |name: &str| {
+        answer
+            .split(", ")
+            .find_map(|param| param.strip_prefix(&format!("{name}=")))
+            .map(|value| value.trim_matches('"').to_string())
+            .unwrap_or_default()
+    };
+    let hash = |data: String| -> String {
+        let digest = if param("algorithm") == "SHA-256" {
+            Sha256::digest(data.as_bytes()).to_vec()
+        } else {
+            Md5::digest(data.as_bytes()).to_vec()
+        };
+        digest.iter().map(|byte| format!("{byte:02x}")).collect()
+    };
+
+    let secret = hash(format!(
+        "{}:{}:{PASSWORD}",
+        param("username"),
+        param("realm")
+    ));
+    let target = hash(format!("{method}:{}", param("uri")));
+    let expected = if param("qop").is_empty() {
+        hash(format!("{secret}:{}:{target}", param("nonce")))
+    } else {
+        hash(format!(
+            "{secret}:{}:{}:{}:{}:{target}",
+            param("nonce"),
+            param("nc"),
+            param("cnonce"),
+            param("qop")
+        ))
+    };
+
+    param("response") == expected
+}
+
+fn options(password: &str) -> RequestOptions {
+    RequestOptions {
+        version: HttpVersion::Http2PriorKnowledge,
+        digest: Some(DigestCredentials {
+            username: "ada".into(),
+            password: password.into(),
+        }),
+        ..RequestOptions::default()
+    }
+}
+
+#[test]
+fn digest_challenges_are_answered() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let address = serve_digest(requests.clone()).await;
+
+        let response = http_client::send(get(address, "/md5?page=2"), options(PASSWORD))
+            .await
+            .unwrap();
+        let answer = String::from_utf8_lossy(&response.body).into_owned();
+
+        assert_eq!(response.status, 200);
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+        assert!(answer.contains(r#"uri="/md5?page=2""#), "{answer}");
+        assert!(answer.contains(r#"opaque="xyz""#), "{answer}");
+        assert!(answer.contains("qop=auth, nc=00000001"), "{answer}");
+    });
+}
+
+#[test]
+fn sha256_is_preferred_over_md5() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_digest(Arc::default()).await;
+
+        let response = http_client::send(get(address, "/both"), options(PASSWORD))
+            .await
+            .unwrap();
+        let answer = String::from_utf8_lossy(&response.body).into_owned();
+
+        assert_eq!(response.status, 200);
+        assert!(answer.contains("algorithm=SHA-256"), "{answer}");
+        assert!(answer.contains(r#"nonce="def""#), "{answer}");
+    });
+}
+
+#[test]
+fn digest_challenges_are_found_among_others() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_digest(Arc::default()).await;
+
+        let response = http_client::send(get(address, "/legacy"), options(PASSWORD))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status, 200);
+    });
+}
+
+#[test]
+fn rejected_answers_are_not_retried() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let address = serve_digest(requests.clone()).await;
+
+        let wrong = http_client::send(get(address, "/md5"), options("wrong"))
+            .await
+            .unwrap();
+        assert_eq!(wrong.status, 401);
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        let basic_only = http_client::send(get(address, "/basic"), options(PASSWORD))
+            .await
+            .unwrap();
+        assert_eq!(basic_only.status, 401);
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+    });
+}
+
+#[test]
+fn basic_credentials_are_encoded() {
+    assert_eq!(
+        basic_authorization("Aladdin", "open sesame"),
+        "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ=="
+    );
+}
@@ -0,0 +1,203 @@
+mod common;
+
+use std::{fs, net::SocketAddr, path::PathBuf};
+
+use bytes::{Bytes, BytesMut};
+use h2::{RecvStream, server::SendResponse};
+use http_client::{
+    HttpBody, HttpRequest, HttpVersion, RequestOptions,
+    http::{Method, Request, Response},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+use crate::common::{get, runtime};
+
+/// Serves h2c with prior knowledge, echoing back the request body with its
+/// `Content-Length` in `x-content-length`.
+async fn serve_h2c_echo() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut connection = h2::server::handshake(socket).await.unwrap();
+
+                while let Some(Ok((request, respond))) = connection.accept().await {
+                    tokio::spawn(echo(request, respond));
+                }
+            });
+        }
+    });
+
+    address
+}
+
+async fn echo(request: Request<RecvStream>, mut respond: SendResponse<Bytes>) {
+    let (parts, mut body) = request.into_parts();
+    let mut received = BytesMut::new();
+    while let Some(Ok(data)) = body.data().await {
+        body.flow_control().release_capacity(data.len()).unwrap();
+        received.extend_from_slice(&data);
+    }
+
+    let content_length = parts
+        .headers
+        .get("content-length")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("-")
+        .to_string();
+    let response = Response::builder()
+        .header("x-content-length", content_length)
+        .body(())
+        .unwrap();
+    let mut stream = respond.send_response(response, false).unwrap();
+
+    stream.send_data(received.freeze(), true).unwrap();
+}
+
+/// Serves HTTP/1.1, echoing back a request body sent with `Content-Length`.
+async fn serve_http1_echo() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                let mut buffer = [0; 8192];
+                let head_end = loop {
+                    if let Some(end) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+                        break end + 4;
+                    }
+                    match socket.read(&mut buffer).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(read) => request.extend_from_slice(&buffer[..read]),
+                    }
+                };
+
+                let head = String::from_utf8_lossy(&request[..head_end]).to_lowercase();
+                let length: usize = head
+                    .lines()
+                    .find_map(|line| line.strip_prefix("content-length:"))
+                    .map_or(0, |value| value.trim().parse().unwrap());
+                while request.len() < head_end + length {
+                    match socket.read(&mut buffer).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(read) => request.extend_from_slice(&buffer[..read]),
+                    }
+                }
+
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-length: {length}\r\nconnection: close\r\n\r\n"
+                );
+                socket.write_all(response.as_bytes()).await.ok();
+                socket
+                    .write_all(&request[head_end..head_end + length])
+                    .await
+                    .ok();
+            });
+        }
+    });
+
+    address
+}
+
+/// A file of `len` bytes in the temporary folder, unique to the test.
+fn temp_file(name: &str, len: usize) -> (PathBuf, Vec<u8>) {
+    let path = std::env::temp_dir().join(format!("http-client-{}-{name}", std::process::id()));
+    let content = (0..len)
+        .map(|index| (index % 251) as u8)
+        .collect::<Vec<_>>();
+
+    fs::write(&path, &content).unwrap();
+    (path, content)
+}
+
+fn post(address: SocketAddr, body: HttpBody) -> HttpRequest {
+    HttpRequest {
+        method: Method::POST,
+        body,
+        ..get(address, "/upload")
+    }
+}
+
+#[test]
+fn files_are_streamed_between_bytes() {
+    let runtime = runtime();
+    // Several chunks, the last one partial.
+    let (path, content) = temp_file("streamed", 200_000);
+
+    runtime.block_on(async {
+        let mut body = HttpBody::default();
+        body.push_bytes("head:");
+        body.push_file(&path);
+        body.push_bytes(":tail");
+
+        let mut expected = b"head:".to_vec();
+        expected.extend_from_slice(&content);
+        expected.extend_from_slice(b":tail");
+
+        assert_eq!(body.len().await.unwrap(), expected.len() as u64);
+        assert_eq!(body.as_bytes(), None);
+
+        let address = serve_h2c_echo().await;
+        let options = RequestOptions {
+            version: HttpVersion::Http2PriorKnowledge,
+            ..RequestOptions::default()
+        };
+        let response = http_client::send(post(address, body.clone()), options)
+            .await
+            .unwrap();
+
+        assert_eq!(response.body, expected);
+        assert_eq!(
+            response.headers["x-content-length"],
+            expected.len().to_string()
+        );
+
+        let address = serve_http1_echo().await;
+        let response = http_client::send(post(address, body), RequestOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(response.body, expected);
+    });
+
+    fs::remove_file(path).ok();
+}
+
+#[test]
+fn consecutive_bytes_are_merged() {
+    let mut body = HttpBody::from(Bytes::from_static(b"a=1"));
+    body.push_bytes("&b=2");
+
+    assert_eq!(body.parts().len(), 1);
+    assert_eq!(body.as_bytes().unwrap(), "a=1&b=2");
+    assert!(!body.is_empty());
+    assert!(HttpBody::default().is_empty());
+}
+
+#[test]
+fn missing_files_fail_the_request() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_http1_echo().await;
+        let path = std::env::temp_dir().join("http-client-no-such-file");
+        let error = http_client::send(
+            post(address, HttpBody::file(&path)),
+            RequestOptions::default(),
+        )
+        .await
+        .unwrap_err();
+
+        assert!(
+            format!("{error:#}").contains(&path.display().to_string()),
+            "{error:#}"
+        );
+    });
+}
@@ -0,0 +1,171 @@
+#![allow(dead_code)]
+
+use std::{net::SocketAddr, path::PathBuf, sync::Arc};
+
+use bytes::Bytes;
+use http_client::{
+    HttpBody, HttpRequest,
+    http::{Method, Response},
+    url::Url,
+};
+use rustls::{
+    RootCertStore, ServerConfig, SupportedProtocolVersion,
+    pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject},
+    server::WebPkiClientVerifier,
+};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpListener,
+    runtime::Runtime,
+};
+use tokio_rustls::TlsAcceptor;
+
+pub fn runtime() -> Runtime {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+}
+
+/// Serves h2c with prior knowledge, echoing back the request path.
+pub async fn serve_h2c() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut connection = h2::server::handshake(socket).await.unwrap();
+
+                while let Some(Ok((request, mut respond))) = connection.accept().await {
+                    let body = Bytes::from(request.uri().path().to_string());
+                    let response = Response::builder().status(200).body(()).unwrap();
+                    let mut stream = respond.send_response(response, false).unwrap();
+
+                    stream.send_data(body, true).unwrap();
+                }
+            });
+        }
+    });
+
+    address
+}
+
+/// Serves a fixed HTTP/1.1 response to every request, keeping connections
+/// alive.
+pub async fn serve_http1() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            tokio::spawn(respond_http1(socket));
+        }
+    });
+
+    address
+}
+
+/// Answers every request read from `socket` with `200 OK` and `ok`.
+pub async fn respond_http1(mut socket: impl AsyncRead + AsyncWrite + Unpin) {
+    let mut request = Vec::new();
+    let mut buffer = [0; 1024];
+
+    loop {
+        while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+            match socket.read(&mut buffer).await {
+                Ok(0) | Err(_) => return,
+                Ok(read) => request.extend_from_slice(&buffer[..read]),
+            }
+        }
+
+        request.clear();
+        if socket
+            .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\nok")
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+pub fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+pub struct TlsServer {
+    pub require_client_certificate: bool,
+    pub versions: &'static [&'static SupportedProtocolVersion],
+}
+
+impl Default for TlsServer {
+    fn default() -> Self {
+        Self {
+            require_client_certificate: false,
+            versions: rustls::DEFAULT_VERSIONS,
+        }
+    }
+}
+
+impl TlsServer {
+    /// Serves HTTP/1.1 over TLS with a certificate for `localhost` and
+    /// `127.0.0.1` issued by the test CA.
+    pub async fn start(self) -> SocketAddr {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let certificates = CertificateDer::pem_file_iter(fixture("server.pem"))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let key = PrivateKeyDer::from_pem_file(fixture("server.key")).unwrap();
+
+        let builder = ServerConfig::builder_with_provider(provider.clone())
+            .with_protocol_versions(self.versions)
+            .unwrap();
+        let builder = if self.require_client_certificate {
+            let mut roots = RootCertStore::empty();
+            roots
+                .add(CertificateDer::from_pem_file(fixture("ca.pem")).unwrap())
+                .unwrap();
+
+            builder.with_client_cert_verifier(
+                WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                    .build()
+                    .unwrap(),
+            )
+        } else {
+            builder.with_no_client_auth()
+        };
+        let mut config = builder.with_single_cert(certificates, key).unwrap();
+        config.alpn_protocols = vec![b"http/1.1".to_vec()];
+
+        let acceptor = TlsAcceptor::from(Arc::new(config));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let acceptor = acceptor.clone();
+
+                tokio::spawn(async move {
+                    if let Ok(stream) = acceptor.accept(socket).await {
+                        respond_http1(stream).await;
+                    }
+                });
+            }
+        });
+
+        address
+    }
+}
+
+pub fn get(address: SocketAddr, path: &str) -> HttpRequest {
+    HttpRequest {
+        method: Method::GET,
+        url: Url::parse(&format!("http://{address}{path}")).unwrap(),
+        headers: vec![("accept".into(), "*/*".into())],
+        body: HttpBody::default(),
+    }
+}
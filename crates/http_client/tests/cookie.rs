@@ -0,0 +1,235 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use http_client::{
+    Cookie, CookieJar,
+    http::{HeaderMap, HeaderValue, header::SET_COOKIE},
+    url::Url,
+};
+
+fn url(url: &str) -> Url {
+    Url::parse(url).unwrap()
+}
+
+fn at(seconds: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(seconds)
+}
+
+fn jar_with(set_by: &str, set_cookies: &[&str], now: SystemTime) -> CookieJar {
+    let mut headers = HeaderMap::new();
+    for set_cookie in set_cookies {
+        headers.append(SET_COOKIE, HeaderValue::from_str(set_cookie).unwrap());
+    }
+
+    let mut jar = CookieJar::default();
+    jar.store_response(&url(set_by), &headers, now);
+    jar
+}
+
+#[test]
+fn domain_attribute_covers_subdomains() {
+    let now = SystemTime::now();
+    let jar = jar_with(
+        "https://api.example.com/",
+        &["host=1", "shared=2; Domain=.example.com"],
+        now,
+    );
+
+    assert_eq!(
+        jar.header_for(&url("https://api.example.com/"), now)
+            .as_deref(),
+        Some("host=1; shared=2")
+    );
+    assert_eq!(
+        jar.header_for(&url("https://www.example.com/"), now)
+            .as_deref(),
+        Some("shared=2")
+    );
+    assert_eq!(jar.header_for(&url("https://example.org/"), now), None);
+
+    // Servers can't set cookies for unrelated domains or public suffixes.
+    let jar = jar_with(
+        "https://api.example.com/",
+        &["a=1; Domain=other.com", "b=2; Domain=com"],
+        now,
+    );
+    assert!(jar.is_empty());
+    let jar = jar_with(
+        "https://shop.example.co.uk/",
+        &[
+            "a=1; Domain=co.uk",
+            "b=2; Domain=uk",
+            "c=3; Domain=example.co.uk",
+        ],
+        now,
+    );
+    assert_eq!(jar.cookies().len(), 1);
+    assert_eq!(jar.cookies()[0].domain, "example.co.uk");
+    let jar = jar_with(
+        "https://user.github.io/",
+        &["a=1; Domain=github.io", "b=2; Domain=example"],
+        now,
+    );
+    assert!(jar.is_empty());
+}
+
+#[test]
+fn public_suffixes_only_cover_themselves() {
+    let now = SystemTime::now();
+    let jar = jar_with("https://github.io/", &["a=1; Domain=github.io"], now);
+
+    assert!(!jar.cookies()[0].include_subdomains);
+    assert_eq!(
+        jar.header_for(&url("https://github.io/"), now).as_deref(),
+        Some("a=1")
+    );
+    assert_eq!(jar.header_for(&url("https://user.github.io/"), now), None);
+
+    // Nor do cookies stored for one while they were still accepted.
+    let mut cookie = jar.cookies()[0].clone();
+    cookie.include_subdomains = true;
+    assert!(!cookie.matches(&url("https://user.github.io/"), now));
+
+    let jar = jar_with("http://localhost:8080/", &["a=1; Domain=localhost"], now);
+    assert_eq!(
+        jar.header_for(&url("http://localhost/"), now).as_deref(),
+        Some("a=1")
+    );
+}
+
+#[test]
+fn public_suffix_domains_are_refused_from_other_hosts() {
+    let now = SystemTime::now();
+    let from = url("https://a.example.com/");
+
+    assert_eq!(Cookie::parse("a=1; Domain=com", &from, now), None);
+    assert_eq!(Cookie::parse("a=1; Domain=.COM", &from, now), None);
+    assert_eq!(
+        Cookie::parse(
+            "a=1; Domain=github.io",
+            &url("https://user.github.io/"),
+            now
+        ),
+        None
+    );
+
+    let cookie = Cookie::parse("a=1; Domain=example.com", &from, now).unwrap();
+    assert_eq!(cookie.domain, "example.com");
+    assert!(cookie.include_subdomains);
+}
+
+#[test]
+fn path_scopes_cookies_with_longest_first() {
+    let now = SystemTime::now();
+    let jar = jar_with(
+        "http://example.com/api/v1/users",
+        &["default=1", "root=2; Path=/", "docs=3; Path=/docs"],
+        now,
+    );
+
+    assert_eq!(
+        jar.header_for(&url("http://example.com/api/v1/items"), now)
+            .as_deref(),
+        Some("default=1; root=2")
+    );
+    assert_eq!(
+        jar.header_for(&url("http://example.com/api/v10"), now)
+            .as_deref(),
+        Some("root=2"),
+        "/api/v1 does not cover /api/v10"
+    );
+    assert_eq!(
+        jar.header_for(&url("http://example.com/docs/guide"), now)
+            .as_deref(),
+        Some("docs=3; root=2")
+    );
+}
+
+#[test]
+fn secure_cookies_need_https() {
+    let now = SystemTime::now();
+    let jar = jar_with("https://example.com/", &["token=1; Secure; HttpOnly"], now);
+
+    assert!(jar.cookies()[0].http_only);
+    assert_eq!(jar.header_for(&url("http://example.com/"), now), None);
+    assert_eq!(
+        jar.header_for(&url("https://example.com/"), now).as_deref(),
+        Some("token=1")
+    );
+
+    let jar = jar_with("http://example.com/", &["token=1; Secure"], now);
+    assert!(jar.is_empty(), "plain HTTP can't set secure cookies");
+}
+
+#[test]
+fn expiry_follows_max_age_then_expires() {
+    let now = at(1_700_000_000);
+    let mut jar = jar_with(
+        "https://example.com/",
+        &[
+            "short=1; Max-Age=60; Expires=Wed, 09 Jun 2100 10:18:14 GMT",
+            "dated=2; Expires=Wed, 09 Jun 2100 10:18:14 GMT",
+            "session=3",
+        ],
+        now,
+    );
+
+    let cookies = jar.cookies();
+    assert_eq!(cookies[0].expires, Some(1_700_000_060));
+    assert_eq!(cookies[1].expires, Some(4_116_219_494));
+    assert_eq!(
+        cookies[1].expires_display().as_deref(),
+        Some("2100-06-09 10:18:14 UTC")
+    );
+    assert_eq!(cookies[2].expires, None);
+
+    let later = at(1_700_000_061);
+    assert_eq!(
+        jar.header_for(&url("https://example.com/"), later)
+            .as_deref(),
+        Some("dated=2; session=3")
+    );
+
+    // Setting a cookie in the past deletes it.
+    let deleted = Cookie::parse("dated=; Max-Age=0", &url("https://example.com/"), now).unwrap();
+    assert!(jar.insert(deleted, now));
+    assert!(jar.remove_expired(later));
+    assert_eq!(jar.cookies().len(), 1);
+}
+
+#[test]
+fn cookie_dates_are_parsed_leniently() {
+    let now = SystemTime::now();
+    let expires = |date: &str| {
+        Cookie::parse(
+            &format!("a=1; Expires={date}"),
+            &url("http://example.com/"),
+            now,
+        )
+        .unwrap()
+        .expires
+    };
+
+    let expected = Some(784_111_777);
+    assert_eq!(expires("Sun, 06 Nov 1994 08:49:37 GMT"), expected);
+    assert_eq!(expires("Sunday, 06-Nov-94 08:49:37 GMT"), expected);
+    assert_eq!(expires("Sun Nov  6 08:49:37 1994"), expected);
+    assert_eq!(expires("not a date"), None);
+}
+
+#[test]
+fn replacing_a_cookie_keeps_its_place() {
+    let now = SystemTime::now();
+    let mut jar = jar_with("http://example.com/", &["a=1", "b=2"], now);
+
+    let replaced = Cookie::parse("a=3", &url("http://example.com/"), now).unwrap();
+    assert!(jar.insert(replaced.clone(), now));
+    assert!(
+        !jar.insert(replaced, now),
+        "unchanged cookies are no change"
+    );
+
+    assert_eq!(
+        jar.header_for(&url("http://example.com/"), now).as_deref(),
+        Some("a=3; b=2")
+    );
+}
@@ -0,0 +1,192 @@
+mod common;
+
+use std::{io::Write, net::SocketAddr};
+
+use flate2::{
+    Compression,
+    write::{DeflateEncoder, GzEncoder, ZlibEncoder},
+};
+use http_client::{ContentEncoding, HttpResponse, RequestOptions};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+use crate::common::{get, runtime};
+
+const TEXT: &[u8] = b"The quick brown fox jumps over the lazy dog. The quick brown fox \
+                      jumps over the lazy dog. The quick brown fox jumps over the lazy dog.";
+
+/// Serves `body` with the given `Content-Encoding` over HTTP/1.1, echoing the
+/// `Accept-Encoding` of the request in `x-accept-encoding`.
+async fn serve_encoded(content_encoding: &'static str, body: Vec<u8>) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let body = body.clone();
+
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                let mut buffer = [0; 1024];
+                while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                    match socket.read(&mut buffer).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(read) => request.extend_from_slice(&buffer[..read]),
+                    }
+                }
+
+                let request = String::from_utf8_lossy(&request).to_lowercase();
+                let accept_encoding = request
+                    .lines()
+                    .find_map(|line| line.strip_prefix("accept-encoding:"))
+                    .unwrap_or("-")
+                    .trim()
+                    .to_string();
+
+                let head = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-encoding: {content_encoding}\r\n\
+                     x-accept-encoding: {accept_encoding}\r\ncontent-length: {}\r\n\
+                     connection: close\r\n\r\n",
+                    body.len()
+                );
+                socket.write_all(head.as_bytes()).await.ok();
+                socket.write_all(&body).await.ok();
+            });
+        }
+    });
+
+    address
+}
+
+async fn fetch(content_encoding: &'static str, body: Vec<u8>) -> HttpResponse {
+    let address = serve_encoded(content_encoding, body).await;
+
+    http_client::send(get(address, "/"), RequestOptions::default())
+        .await
+        .unwrap()
+}
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn raw_deflate(data: &[u8]) -> Vec<u8> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// A brotli stream storing `data` in a single uncompressed meta-block, as
+/// there is no brotli encoder at hand.
+fn brotli_stored(data: &[u8]) -> Vec<u8> {
+    // A 16 bit window, a meta-block that isn't the last with four length
+    // nibbles, its length minus one, then the uncompressed flag.
+    let header = ((data.len() as u32 - 1) << 4) | (1 << 20);
+    let mut stream = header.to_le_bytes()[..3].to_vec();
+
+    stream.extend_from_slice(data);
+    // The last meta-block, empty.
+    stream.push(0b11);
+    stream
+}
+
+#[test]
+fn compressed_bodies_are_decoded() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let zstd =
+            ruzstd::encoding::compress_to_vec(TEXT, ruzstd::encoding::CompressionLevel::Fastest);
+        let cases = [
+            ("gzip", gzip(TEXT), ContentEncoding::Gzip),
+            ("deflate", zlib(TEXT), ContentEncoding::Deflate),
+            ("deflate", raw_deflate(TEXT), ContentEncoding::Deflate),
+            ("br", brotli_stored(TEXT), ContentEncoding::Brotli),
+            ("zstd", zstd, ContentEncoding::Zstd),
+        ];
+
+        for (name, encoded, encoding) in cases {
+            let response = fetch(name, encoded.clone()).await;
+
+            assert_eq!(response.decode_error, None, "{name}");
+            assert_eq!(&response.body[..], TEXT, "{name}");
+            assert_eq!(&response.raw_body[..], encoded, "{name}");
+            assert_eq!(response.encodings, [encoding]);
+            assert_eq!(
+                response.headers["x-accept-encoding"],
+                "gzip, deflate, br, zstd"
+            );
+        }
+    });
+}
+
+#[test]
+fn stacked_encodings_are_undone_in_reverse() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let response = fetch("deflate, gzip", gzip(&zlib(TEXT))).await;
+
+        assert_eq!(
+            response.encodings,
+            [ContentEncoding::Deflate, ContentEncoding::Gzip]
+        );
+        assert_eq!(&response.body[..], TEXT);
+    });
+}
+
+#[test]
+fn broken_bodies_are_kept_as_received() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let mut truncated = gzip(TEXT);
+        truncated.truncate(truncated.len() / 2);
+
+        let response = fetch("gzip", truncated.clone()).await;
+        let error = response.decode_error.unwrap();
+
+        assert!(error.contains("decoding gzip body"), "{error}");
+        assert_eq!(&response.body[..], truncated);
+        assert_eq!(response.body, response.raw_body);
+
+        let response = fetch("compress", TEXT.to_vec()).await;
+
+        assert_eq!(
+            response.encodings,
+            [ContentEncoding::Other("compress".into())]
+        );
+        assert!(response.decode_error.unwrap().contains("unsupported"));
+    });
+}
+
+#[test]
+fn requests_can_ask_for_their_own_encodings() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_encoded("identity", TEXT.to_vec()).await;
+        let mut request = get(address, "/");
+        request
+            .headers
+            .push(("Accept-Encoding".into(), "identity".into()));
+
+        let response = http_client::send(request, RequestOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(response.headers["x-accept-encoding"], "identity");
+        assert!(response.encodings.is_empty());
+        assert_eq!(&response.body[..], TEXT);
+    });
+}
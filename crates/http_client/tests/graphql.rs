@@ -0,0 +1,266 @@
+mod common;
+
+use std::fs;
+
+use http_client::{
+    GraphQlSchema, QueryMode, TypeKind, complete_query, graphql_body, is_persisted_query_not_found,
+    operation_names, persisted_query_hash, validate_query,
+};
+use serde_json::{Value, json};
+
+use crate::common::fixture;
+
+fn library() -> GraphQlSchema {
+    let body = fs::read(fixture("graphql/introspection.json")).unwrap();
+
+    GraphQlSchema::from_introspection(&body).unwrap()
+}
+
+/// The messages of the problems of `query`, along with the text each one
+/// points at.
+fn problems(query: &str) -> Vec<(String, &str)> {
+    validate_query(query, Some(&library()))
+        .into_iter()
+        .map(|error| (error.message, &query[error.range]))
+        .collect()
+}
+
+/// The labels of the completions where `|` stands in `query`.
+fn completions(query: &str) -> Vec<String> {
+    let offset = query.find('|').unwrap();
+    let query = query.replace('|', "");
+
+    complete_query(&query, offset, &library())
+        .into_iter()
+        .map(|completion| completion.label)
+        .collect()
+}
+
+#[test]
+fn schema_is_read_from_introspection() {
+    let schema = library();
+
+    assert_eq!(schema.query_type.as_deref(), Some("Query"));
+    assert_eq!(schema.root_type("mutation").unwrap().name, "Mutation");
+    assert!(schema.root_type("subscription").is_none());
+
+    let books = schema.get_type("Query").unwrap().field("books").unwrap();
+    assert_eq!(books.ty.to_string(), "[Book!]!");
+    assert_eq!(books.ty.name(), "Book");
+    assert_eq!(books.args[0].default_value.as_deref(), Some("10"));
+    assert!(!books.args[0].is_required());
+
+    let book = schema.get_type("Book").unwrap();
+    assert_eq!(book.description.as_deref(), Some("A book of the catalog."));
+    assert!(book.field("isbn").unwrap().is_deprecated);
+    assert_eq!(
+        schema.get_type("SearchResult").unwrap().kind,
+        TypeKind::Union
+    );
+    assert!(schema.user_types().all(|ty| !ty.name.starts_with("__")));
+}
+
+#[test]
+fn introspection_errors_are_reported() {
+    let body = json!({"errors": [{"message": "introspection is disabled"}]}).to_string();
+    let error = GraphQlSchema::from_introspection(body.as_bytes()).unwrap_err();
+
+    assert_eq!(error.to_string(), "introspection is disabled");
+    assert!(GraphQlSchema::from_introspection(b"<html>").is_err());
+}
+
+#[test]
+fn valid_documents_have_no_problems() {
+    let query = r#"
+        # The catalog, by genre.
+        query Catalog($genre: Genre, $first: Int = 5) {
+          books(first: $first, genre: $genre) {
+            ...BookFields
+            author { name }
+          }
+          search(text: "dune") {
+            __typename
+            ... on Book { title }
+            ... on Author { name @include(if: true) }
+          }
+        }
+
+        fragment BookFields on Book { id title genre }
+
+        mutation Add {
+          addBook(input: {title: "Dune", genre: FICTION, authorId: 1}) { id }
+        }
+    "#;
+
+    assert_eq!(problems(query), []);
+}
+
+#[test]
+fn queries_are_checked_against_the_schema() {
+    let query = r#"query Q($id: ID!) {
+      book(id: $id) { title pages }
+      books(genre: POETRY) { title author }
+      node { id { value } }
+      search(text: "x") { title }
+      byIsbn: book(id: $isbn, format: "short") { ...Missing }
+    }"#;
+
+    assert_eq!(
+        problems(query),
+        [
+            ("Book has no field pages".to_string(), "pages"),
+            ("Genre has no value POETRY".to_string(), "POETRY"),
+            (
+                "author is of type Author and needs a selection of its fields".to_string(),
+                "author"
+            ),
+            ("Query.node needs the argument id: ID!".to_string(), "node"),
+            ("id is a scalar ID and has no fields".to_string(), "id"),
+            ("SearchResult has no field title".to_string(), "title"),
+            ("$isbn isn't defined by operation Q".to_string(), "$isbn"),
+            ("Query.book has no argument format".to_string(), "format"),
+            ("unknown fragment Missing".to_string(), "Missing"),
+        ]
+    );
+}
+
+#[test]
+fn documents_are_checked_as_a_whole() {
+    let query = "{ books { id } }\nquery A { books { id } }\nquery A { books { id } }\n\
+                 fragment Unused on Genre { id }\nsubscription S { books { id } }";
+
+    assert_eq!(
+        problems(query),
+        [
+            (
+                "an anonymous operation must be the only one of the document".to_string(),
+                "{"
+            ),
+            ("there is already an operation named A".to_string(), "A"),
+            ("fragment Unused is never used".to_string(), "Unused"),
+            (
+                "fields can't be selected on enum Genre".to_string(),
+                "Genre"
+            ),
+            (
+                "the schema doesn't support subscription operations".to_string(),
+                "S"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn syntax_errors_are_located_without_a_schema() {
+    let errors = validate_query("query { books { id ", None);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].message,
+        "expected \"}\", found the end of the document"
+    );
+    assert_eq!(errors[0].range, 16..18);
+
+    let errors = validate_query("{ books(first: 1x) { id } }", None);
+    assert_eq!(errors[0].message, "invalid number");
+
+    let errors = validate_query("type Book { id: ID }", None);
+    assert_eq!(
+        errors[0].message,
+        "only operations and fragments can be sent, not type definitions"
+    );
+
+    // Without a schema, only the syntax and the document itself are checked.
+    assert_eq!(validate_query("{ anything { at all } }", None), []);
+}
+
+#[test]
+fn operation_names_are_listed_in_order() {
+    let query = "query First { a }\nmutation Second { b }\n{ c }\nfragment F on T { d }";
+
+    assert_eq!(operation_names(query), ["First", "Second"]);
+    assert!(operation_names("query Broken {").is_empty());
+}
+
+#[test]
+fn fields_and_arguments_are_completed() {
+    assert_eq!(
+        completions("{ book(id: 1) { t| } }"),
+        ["title", "author", "__typename"]
+    );
+    assert_eq!(completions("query { books(|) { id } }"), ["first", "genre"]);
+    assert_eq!(
+        completions("query { books(first: 2, genre: |) { id } }"),
+        ["FICTION", "HISTORY", "SCIENCE"]
+    );
+    assert_eq!(
+        completions("mutation { addBook(input: {title: \"x\", g|}) { id } }"),
+        ["genre"]
+    );
+    assert_eq!(
+        completions("{ search(text: \"a\") { ... on | } }"),
+        ["Author", "Book", "Node", "SearchResult"]
+    );
+    assert_eq!(completions("{ books { id @| } }"), ["include", "skip"]);
+    assert_eq!(completions("query ($genre: G|) { a }"), ["Genre", "String"]);
+    assert_eq!(
+        completions("query ($genre: Genre) { books(genre: $|) }"),
+        ["genre"]
+    );
+    assert_eq!(completions("m|"), ["mutation", "fragment"]);
+}
+
+#[test]
+fn completions_replace_the_word_at_the_cursor() {
+    let query = "{ books { ti } }";
+    let completion = complete_query(query, 11, &library()).remove(0);
+
+    assert_eq!(completion.label, "title");
+    assert_eq!(completion.detail, "String!");
+    assert_eq!(completion.range, 10..12);
+
+    let query = "{ books(ge) { id } }";
+    let completion = complete_query(query, 10, &library()).remove(0);
+    assert_eq!(completion.insert, "genre: ");
+
+    assert!(complete_query("{ books { id } } # ti", 21, &library()).is_empty());
+    assert!(complete_query("{ search(text: \"ti", 18, &library()).is_empty());
+}
+
+#[test]
+fn persisted_queries_are_sent_by_hash() {
+    let query = "{ books { id } }";
+    let hash = "b381363e28b14ed818b3d63f7dbeaf328f60410e148e972b4cc6946a3f66883b";
+    assert_eq!(persisted_query_hash(query), hash);
+
+    let body = |mode| -> Value {
+        serde_json::from_str(&graphql_body(query, "{\"first\": 2}", Some("Books"), mode).unwrap())
+            .unwrap()
+    };
+
+    assert_eq!(
+        body(QueryMode::Full),
+        json!({"query": query, "variables": {"first": 2}, "operationName": "Books"})
+    );
+    assert_eq!(
+        body(QueryMode::PersistedHash),
+        json!({
+            "variables": {"first": 2},
+            "operationName": "Books",
+            "extensions": {"persistedQuery": {"version": 1, "sha256Hash": hash}},
+        })
+    );
+    assert_eq!(body(QueryMode::PersistedFull)["query"], query);
+
+    assert_eq!(
+        graphql_body(query, "  ", None, QueryMode::Full).unwrap(),
+        json!({"query": query}).to_string()
+    );
+    assert!(graphql_body(query, "[1]", None, QueryMode::Full).is_err());
+
+    let not_found = json!({"errors": [{"message": "PersistedQueryNotFound"}]}).to_string();
+    let by_code =
+        json!({"errors": [{"message": "x", "extensions": {"code": "PERSISTED_QUERY_NOT_FOUND"}}]});
+    assert!(is_persisted_query_not_found(not_found.as_bytes()));
+    assert!(is_persisted_query_not_found(by_code.to_string().as_bytes()));
+    assert!(!is_persisted_query_not_found(b"{\"data\": {}}"));
+}
@@ -0,0 +1,586 @@
+mod common;
+
+use std::{net::SocketAddr, time::Duration};
+
+use bytes::{Buf as _, BufMut as _, Bytes, BytesMut};
+use futures::StreamExt;
+use http_client::{
+    DescriptorPool, GrpcEvent, GrpcRequest, RequestOptions,
+    http::{HeaderMap, HeaderValue, Request, Response},
+    url::Url,
+};
+use serde_json::json;
+use tokio::net::TcpListener;
+
+use crate::common::{fixture, runtime};
+
+fn shop() -> DescriptorPool {
+    DescriptorPool::load_proto_files(&[fixture("proto/shop/orders.proto")], &[fixture("proto")])
+        .unwrap()
+}
+
+#[test]
+fn proto_files_are_loaded_with_their_imports() {
+    let pool = shop();
+
+    let service = pool.service("shop.v1.Orders").unwrap();
+    let methods = service.methods().collect::<Vec<_>>();
+    let kinds = methods
+        .iter()
+        .map(|method| (method.name(), http_client::method_kind(method)))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            ("Place", "unary"),
+            ("Follow", "server streaming"),
+            ("Upload", "client streaming"),
+            ("Chat", "bidirectional streaming"),
+            ("Ping", "unary"),
+        ]
+    );
+    assert_eq!(methods[1].output().full_name(), "shop.v1.Order.Update");
+    assert_eq!(methods[4].input().full_name(), "google.protobuf.Empty");
+
+    let order = pool.message("shop.v1.Order").unwrap();
+    let prices = order.get_field(3).unwrap();
+    assert_eq!(prices.json_name(), "prices");
+    assert!(prices.is_map());
+    assert_eq!(
+        prices.kind().as_message().unwrap().full_name(),
+        "shop.v1.Order.PricesEntry"
+    );
+    let status = order.get_field(4).unwrap();
+    assert_eq!(
+        status.kind().as_enum().unwrap().full_name(),
+        "shop.v1.Status"
+    );
+}
+
+#[test]
+fn messages_round_trip_through_json() {
+    let pool = shop();
+
+    // Field numbers and varints straight from the encoding spec.
+    let bytes = pool
+        .encode_json("shop.v1.Money", r#"{"currency": "EUR", "cents": 150}"#)
+        .unwrap();
+    assert_eq!(bytes, b"\x0a\x03EUR\x10\x96\x01");
+
+    let order = json!({
+        "customerName": "Ada",
+        "quantities": [1, 2, 300],
+        "prices": {"tea": {"currency": "EUR", "cents": "250"}},
+        "status": "STATUS_SHIPPED",
+        "placedAtMillis": "-1700000000000",
+        "tags": ["gift", "fragile"],
+        "signature": "3q2+7w==",
+        "pickup": true,
+        "priority": -3,
+        "discount": 0.25,
+    });
+    let bytes = pool
+        .encode_json("shop.v1.Order", &order.to_string())
+        .unwrap();
+    assert_eq!(pool.decode_json("shop.v1.Order", &bytes).unwrap(), order);
+
+    // Original field names are accepted too, and enums by number.
+    let bytes = pool
+        .encode_json(
+            "shop.v1.Order",
+            r#"{"customer_name": "Bob", "status": 1, "placed_at": 5}"#,
+        )
+        .unwrap();
+    assert_eq!(
+        pool.decode_json("shop.v1.Order", &bytes).unwrap(),
+        json!({"customerName": "Bob", "status": "STATUS_PLACED", "placedAtMillis": "5"})
+    );
+
+    let error = pool
+        .encode_json("shop.v1.Order", r#"{"customer": "Bob"}"#)
+        .unwrap_err();
+    assert_eq!(
+        format!("{error:#}"),
+        "invalid shop.v1.Order: unrecognized field name 'customer' at line 1 column 11"
+    );
+}
+
+#[test]
+fn well_known_types_have_json_forms_of_their_own() {
+    let pool = DescriptorPool::load_proto_files(
+        &[fixture("proto/clock/events.proto")],
+        &[fixture("proto")],
+    )
+    .unwrap();
+
+    let event = json!({
+        "at": "2024-05-01T12:30:00.500Z",
+        "took": "1.500s",
+        "count": "7",
+        "label": "deploy",
+        "details": {"hosts": ["a", "b"], "forced": true, "retries": 2.0, "note": null},
+    });
+    let bytes = pool
+        .encode_json("clock.v1.Event", &event.to_string())
+        .unwrap();
+    assert_eq!(pool.decode_json("clock.v1.Event", &bytes).unwrap(), event);
+
+    // Timestamps are RFC 3339 strings, not their seconds and nanos.
+    let at = pool
+        .encode_json("google.protobuf.Timestamp", r#""1970-01-01T00:01:40Z""#)
+        .unwrap();
+    assert_eq!(at, b"\x08\x64");
+    assert!(
+        pool.encode_json("clock.v1.Event", r#"{"at": {"seconds": 100}}"#)
+            .is_err()
+    );
+
+    assert_eq!(
+        pool.json_template("clock.v1.Event"),
+        json!({
+            "at": "1970-01-01T00:00:00Z",
+            "took": "0s",
+            "count": 0,
+            "label": "",
+            "details": {},
+        })
+    );
+}
+
+#[test]
+fn templates_list_every_field() {
+    let template = shop().json_template("shop.v1.Receipt");
+
+    assert_eq!(
+        template,
+        json!({"id": "", "total": {"currency": "", "cents": 0}})
+    );
+}
+
+fn frame(message: &[u8]) -> Bytes {
+    let mut framed = BytesMut::new();
+    framed.put_u8(0);
+    framed.put_u32(message.len() as u32);
+    framed.put_slice(message);
+    framed.freeze()
+}
+
+/// Reads the messages of a request as they come, handing each to `answer`,
+/// whose replies are sent back framed. Ends with the trailers `status`
+/// gives once the request stream is over.
+async fn serve_grpc<A, S>(answer: A, status: S) -> SocketAddr
+where
+    A: Fn(&str, Bytes) -> Vec<Bytes> + Clone + Send + Sync + 'static,
+    S: Fn(&str, usize) -> HeaderMap + Clone + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            let (answer, status) = (answer.clone(), status.clone());
+
+            tokio::spawn(async move {
+                let mut connection = h2::server::handshake(socket).await.unwrap();
+
+                while let Some(Ok((request, respond))) = connection.accept().await {
+                    tokio::spawn(respond_grpc(
+                        request,
+                        respond,
+                        answer.clone(),
+                        status.clone(),
+                    ));
+                }
+            });
+        }
+    });
+
+    address
+}
+
+async fn respond_grpc<A, S>(
+    request: Request<h2::RecvStream>,
+    mut respond: h2::server::SendResponse<Bytes>,
+    answer: A,
+    status: S,
+) where
+    A: Fn(&str, Bytes) -> Vec<Bytes>,
+    S: Fn(&str, usize) -> HeaderMap,
+{
+    let path = request.uri().path().to_string();
+    assert_eq!(request.headers()["content-type"], "application/grpc");
+    assert_eq!(request.headers()["te"], "trailers");
+
+    let response = Response::builder()
+        .header("content-type", "application/grpc+proto")
+        .header("x-served-by", "test")
+        .body(())
+        .unwrap();
+    let mut stream = respond.send_response(response, false).unwrap();
+
+    let mut body = request.into_body();
+    let mut buffer = BytesMut::new();
+    let mut count = 0;
+    while let Some(data) = body.data().await {
+        let data = data.unwrap();
+        body.flow_control().release_capacity(data.len()).unwrap();
+        buffer.extend_from_slice(&data);
+
+        while buffer.len() >= 5 {
+            let len = u32::from_be_bytes(buffer[1..5].try_into().unwrap()) as usize;
+            if buffer.len() < 5 + len {
+                break;
+            }
+            buffer.advance(5);
+            let message = buffer.split_to(len).freeze();
+            count += 1;
+
+            for reply in answer(&path, message) {
+                stream.send_data(frame(&reply), false).unwrap();
+            }
+        }
+    }
+
+    stream.send_trailers(status(&path, count)).unwrap();
+}
+
+fn ok_status(_: &str, _: usize) -> HeaderMap {
+    let mut trailers = HeaderMap::new();
+    trailers.insert("grpc-status", HeaderValue::from_static("0"));
+    trailers.insert("x-trailer", HeaderValue::from_static("done"));
+    trailers
+}
+
+fn grpc_request(address: SocketAddr, method: &str) -> GrpcRequest {
+    GrpcRequest {
+        url: Url::parse(&format!("grpc://{address}")).unwrap(),
+        service: "shop.v1.Orders".into(),
+        method: method.into(),
+        metadata: vec![("authorization".into(), "Bearer token".into())],
+    }
+}
+
+#[test]
+fn unary_calls_end_with_their_status_and_trailers() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let pool = shop();
+        let address = serve_grpc(
+            |path, message| {
+                assert_eq!(path, "/shop.v1.Orders/Place");
+                let receipt = shop()
+                    .decode_json("shop.v1.Order", &message)
+                    .map(|order| format!(r#"{{"id": {}}}"#, order["customerName"]))
+                    .unwrap();
+                vec![
+                    shop()
+                        .encode_json("shop.v1.Receipt", &receipt)
+                        .unwrap()
+                        .into(),
+                ]
+            },
+            ok_status,
+        )
+        .await;
+
+        let (done, sender, events) =
+            http_client::call_grpc(grpc_request(address, "Place"), RequestOptions::default());
+        sender
+            .send(
+                pool.encode_json("shop.v1.Order", r#"{"customerName": "Ada"}"#)
+                    .unwrap(),
+            )
+            .unwrap();
+        sender.finish();
+
+        let status = tokio::time::timeout(Duration::from_secs(5), done)
+            .await
+            .unwrap()
+            .unwrap();
+        let events = events.collect::<Vec<_>>().await;
+
+        assert!(status.is_ok(), "{status}");
+        assert_eq!(status.trailers["x-trailer"], "done");
+        // The request goes out before the response headers come, so only
+        // the order of what is received is certain.
+        let Some(GrpcEvent::Headers { headers }) = events
+            .iter()
+            .find(|event| !matches!(event, GrpcEvent::Sent { .. }))
+        else {
+            panic!("expected the headers before any message, got {events:?}");
+        };
+        assert_eq!(headers["x-served-by"], "test");
+        let received = events
+            .iter()
+            .filter_map(|event| match event {
+                GrpcEvent::Received { message, .. } => {
+                    Some(pool.decode_json("shop.v1.Receipt", message).unwrap())
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(received, [json!({"id": "Ada"})]);
+        assert!(
+            events
+                .iter()
+                .any(|event| matches!(event, GrpcEvent::Sent { .. }))
+        );
+    });
+}
+
+#[test]
+fn streams_go_both_ways_and_end_with_error_statuses() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_grpc(
+            // Echoes every message twice.
+            |_, message| vec![message.clone(), message],
+            |_, count| {
+                let mut trailers = HeaderMap::new();
+                trailers.insert("grpc-status", HeaderValue::from_static("3"));
+                trailers.insert(
+                    "grpc-message",
+                    HeaderValue::try_from(format!("got%20{count}%20updates")).unwrap(),
+                );
+                trailers
+            },
+        )
+        .await;
+
+        let (done, sender, events) =
+            http_client::call_grpc(grpc_request(address, "Chat"), RequestOptions::default());
+        let mut events = events.fuse();
+        for note in ["one", "two"] {
+            sender.send(Bytes::from(note)).unwrap();
+
+            // Each message is answered before the next one is sent.
+            let mut echoes = 0;
+            while echoes < 2 {
+                if let Some(GrpcEvent::Received { message, .. }) = events.next().await {
+                    assert_eq!(message, note);
+                    echoes += 1;
+                }
+            }
+        }
+        sender.finish();
+
+        let status = done.await.unwrap();
+        assert_eq!(status.code, 3);
+        assert_eq!(status.to_string(), "3 INVALID_ARGUMENT: got 2 updates");
+    });
+}
+
+#[test]
+fn trailers_only_responses_give_their_status() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut connection = h2::server::handshake(socket).await.unwrap();
+
+            while let Some(Ok((_, mut respond))) = connection.accept().await {
+                let response = Response::builder()
+                    .header("content-type", "application/grpc")
+                    .header("grpc-status", "12")
+                    .header("grpc-message", "not here")
+                    .body(())
+                    .unwrap();
+                respond.send_response(response, true).unwrap();
+            }
+        });
+
+        let (done, _sender, _) =
+            http_client::call_grpc(grpc_request(address, "Ping"), RequestOptions::default());
+        let status = done.await.unwrap();
+
+        assert_eq!(status.to_string(), "12 UNIMPLEMENTED: not here");
+    });
+}
+
+#[test]
+fn services_are_loaded_over_reflection() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_grpc(
+            |path, request| {
+                assert_eq!(
+                    path,
+                    "/grpc.reflection.v1.ServerReflection/ServerReflectionInfo"
+                );
+                let descriptors =
+                    DescriptorPool::load_proto_files(&[fixture("proto/descriptor.proto")], &[])
+                        .unwrap();
+                let encode = |message: &str, json: serde_json::Value| {
+                    descriptors.encode_json(message, &json.to_string()).unwrap()
+                };
+
+                // Answers by what the request asks for: services (7), the
+                // file of a symbol (4) or a file by name (3).
+                let response = match request[0] >> 3 {
+                    7 => json!({"6": {"1": [
+                        {"1": "greet.Greeter"},
+                        {"1": "grpc.reflection.v1.ServerReflection"},
+                    ]}}),
+                    4 => json!({"4": {"1": [encode("google.protobuf.FileDescriptorProto", json!({
+                        "name": "greet.proto",
+                        "package": "greet",
+                        "dependency": ["names.proto"],
+                        "syntax": "proto3",
+                        "service": [{"name": "Greeter", "method": [{
+                            "name": "Hello",
+                            "inputType": ".greet.Name",
+                            "outputType": ".greet.Name",
+                            "serverStreaming": true,
+                        }]}],
+                    }))]}}),
+                    _ => json!({"4": {"1": [encode("google.protobuf.FileDescriptorProto", json!({
+                        "name": "names.proto",
+                        "package": "greet",
+                        "syntax": "proto3",
+                        "messageType": [{"name": "Name", "field": [
+                            {"name": "first_name", "number": 1, "label": 1, "type": 9},
+                        ]}],
+                    }))]}}),
+                };
+
+                vec![reflection_response(&response)]
+            },
+            ok_status,
+        )
+        .await;
+
+        let pool = http_client::reflect_services(
+            Url::parse(&format!("http://{address}")).unwrap(),
+            Vec::new(),
+            RequestOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(pool.services().len(), 1);
+        let method = pool
+            .service("greet.Greeter")
+            .unwrap()
+            .methods()
+            .next()
+            .unwrap();
+        assert_eq!(http_client::method_kind(&method), "server streaming");
+        assert_eq!(method.input().full_name(), "greet.Name");
+        assert_eq!(
+            pool.json_template(method.input().full_name()),
+            json!({"firstName": ""})
+        );
+    });
+}
+
+#[test]
+fn files_of_editions_are_read_over_reflection() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_grpc(
+            |_, request| {
+                let descriptors =
+                    DescriptorPool::load_proto_files(&[fixture("proto/descriptor.proto")], &[])
+                        .unwrap();
+                let file = json!({
+                    "name": "tally.proto",
+                    "package": "tally",
+                    "syntax": "editions",
+                    "edition": 1000,
+                    "messageType": [{"name": "Count", "field": [
+                        {"name": "packed", "number": 1, "label": 3, "type": 5},
+                        {"name": "expanded", "number": 2, "label": 3, "type": 5,
+                         "options": {"features": {"repeatedFieldEncoding": 2}}},
+                        {"name": "id", "number": 3, "label": 1, "type": 5,
+                         "options": {"features": {"fieldPresence": 3}}},
+                    ]}],
+                    "service": [{"name": "Tally", "method": [{
+                        "name": "Add",
+                        "inputType": ".tally.Count",
+                        "outputType": ".tally.Count",
+                    }]}],
+                });
+
+                let response = match request[0] >> 3 {
+                    7 => json!({"6": {"1": [{"1": "tally.Tally"}]}}),
+                    _ => json!({"4": {"1": [descriptors
+                        .encode_json("google.protobuf.FileDescriptorProto", &file.to_string())
+                        .unwrap()]}}),
+                };
+
+                vec![reflection_response(&response)]
+            },
+            ok_status,
+        )
+        .await;
+
+        let pool = http_client::reflect_services(
+            Url::parse(&format!("http://{address}")).unwrap(),
+            Vec::new(),
+            RequestOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        // Repeated scalars are packed unless their features say otherwise,
+        // and fields with explicit presence are written even when zero.
+        let bytes = pool
+            .encode_json(
+                "tally.Count",
+                r#"{"packed": [1, 2], "expanded": [3, 4], "id": 0}"#,
+            )
+            .unwrap();
+        assert_eq!(bytes, b"\x0a\x02\x01\x02\x10\x03\x10\x04\x18\x00");
+        let count = pool.message("tally.Count").unwrap();
+        assert!(count.get_field(3).unwrap().is_required());
+    });
+}
+
+/// Encodes a reflection response written as JSON keyed by field number,
+/// where arrays hold the values of repeated fields, strings are strings,
+/// arrays of numbers are bytes and objects are nested messages.
+fn reflection_response(value: &serde_json::Value) -> Bytes {
+    fn encode(value: &serde_json::Value, out: &mut Vec<u8>) {
+        for (number, value) in value.as_object().unwrap() {
+            let number: u8 = number.parse().unwrap();
+            let values = match value {
+                serde_json::Value::Array(values) => values.clone(),
+                value => vec![value.clone()],
+            };
+
+            for value in values {
+                let bytes = match &value {
+                    serde_json::Value::String(text) => text.as_bytes().to_vec(),
+                    serde_json::Value::Array(bytes) => bytes
+                        .iter()
+                        .map(|byte| byte.as_u64().unwrap() as u8)
+                        .collect(),
+                    value => {
+                        let mut nested = Vec::new();
+                        encode(value, &mut nested);
+                        nested
+                    }
+                };
+                out.push((number << 3) | 2);
+                let mut len = bytes.len();
+                while len >= 0x80 {
+                    out.push(len as u8 | 0x80);
+                    len >>= 7;
+                }
+                out.push(len as u8);
+                out.extend_from_slice(&bytes);
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    encode(value, &mut out);
+    out.into()
+}
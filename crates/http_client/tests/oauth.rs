@@ -0,0 +1,398 @@
+mod common;
+
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, SystemTime},
+};
+
+use base64::{Engine as _, prelude::BASE64_URL_SAFE_NO_PAD};
+use http_client::{
+    HttpBody, HttpRequest, OAuthClient, OAuthToken, RequestOptions, basic_authorization,
+    decode_jwt, http::Method, url::Url,
+};
+use sha2::{Digest, Sha256};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+use crate::common::runtime;
+
+/// Serves a token endpoint on `/token` and a device authorization endpoint
+/// on `/device`, for the client `app` with the secret `s3cret`.
+async fn serve_oauth() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let polls = Arc::new(AtomicUsize::new(0));
+
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            tokio::spawn(answer(socket, polls.clone()));
+        }
+    });
+
+    address
+}
+
+async fn answer(mut socket: TcpStream, polls: Arc<AtomicUsize>) {
+    let mut request = Vec::new();
+    let mut buffer = [0; 1024];
+
+    loop {
+        let head_end = loop {
+            if let Some(end) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+                break end + 4;
+            }
+            match socket.read(&mut buffer).await {
+                Ok(0) | Err(_) => return,
+                Ok(read) => request.extend_from_slice(&buffer[..read]),
+            }
+        };
+
+        let head = String::from_utf8_lossy(&request[..head_end]).to_lowercase();
+        let header = |name: &str| {
+            head.lines()
+                .find_map(|line| line.strip_prefix(&format!("{name}: ")))
+                .map(str::to_string)
+        };
+        let length: usize = header("content-length")
+            .and_then(|length| length.parse().ok())
+            .unwrap_or_default();
+        while request.len() < head_end + length {
+            match socket.read(&mut buffer).await {
+                Ok(0) | Err(_) => return,
+                Ok(read) => request.extend_from_slice(&buffer[..read]),
+            }
+        }
+
+        let path = head.split(' ').nth(1).unwrap_or_default().to_string();
+        let form: HashMap<String, String> =
+            url::form_urlencoded::parse(&request[head_end..head_end + length])
+                .into_owned()
+                .collect();
+        request.drain(..head_end + length);
+
+        let authenticated =
+            header("authorization") == Some(basic_authorization("app", "s3cret").to_lowercase());
+        let param = |name: &str| form.get(name).cloned().unwrap_or_default();
+
+        let (status, body) = match (path.as_str(), param("grant_type").as_str()) {
+            ("/device", _) => (
+                200,
+                r#"{"device_code":"d1","user_code":"ABCD-EFGH","verification_uri":"https://example.com/activate","interval":0}"#.to_string(),
+            ),
+            (_, _) if !authenticated && param("client_id") != "public" => {
+                (401, r#"{"error":"invalid_client"}"#.to_string())
+            }
+            (_, "client_credentials") => (
+                200,
+                format!(
+                    r#"{{"access_token":"cc","token_type":"bearer","expires_in":3600,"scope":"{}"}}"#,
+                    param("scope")
+                ),
+            ),
+            (_, "refresh_token") if param("refresh_token") == "r1" => {
+                (200, r#"{"access_token":"refreshed","expires_in":60}"#.to_string())
+            }
+            (_, "authorization_code") => {
+                let challenge =
+                    BASE64_URL_SAFE_NO_PAD.encode(Sha256::digest(param("code_verifier")));
+                (
+                    200,
+                    format!(
+                        r#"{{"access_token":"{}:{challenge}","refresh_token":"r1","expires_in":60}}"#,
+                        param("code")
+                    ),
+                )
+            }
+            (_, "urn:ietf:params:oauth:grant-type:device_code") => {
+                if polls.fetch_add(1, Ordering::SeqCst) == 0 {
+                    (400, r#"{"error":"authorization_pending"}"#.to_string())
+                } else {
+                    (200, r#"{"access_token":"device"}"#.to_string())
+                }
+            }
+            _ => (
+                400,
+                r#"{"error":"invalid_grant","error_description":"unknown grant"}"#.to_string(),
+            ),
+        };
+
+        let response = format!(
+            "HTTP/1.1 {status} X\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        if socket.write_all(response.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+fn client(address: SocketAddr) -> OAuthClient {
+    OAuthClient {
+        token_url: format!("http://{address}/token"),
+        authorization_url: "https://example.com/authorize?prompt=consent".to_string(),
+        device_authorization_url: format!("http://{address}/device"),
+        client_id: "app".to_string(),
+        client_secret: "s3cret".to_string(),
+        scope: "read write".to_string(),
+        redirect_port: 0,
+    }
+}
+
+#[test]
+fn client_credentials_are_exchanged_for_a_token() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_oauth().await;
+
+        let token = client(address)
+            .client_credentials(RequestOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(token.access_token, "cc");
+        assert_eq!(token.authorization(), "Bearer cc");
+        assert_eq!(token.scope.as_deref(), Some("read write"));
+        assert!(!token.is_expired(SystemTime::now()));
+        assert!(token.is_expired(SystemTime::now() + Duration::from_secs(3600)));
+
+        let wrong = OAuthClient {
+            client_secret: "wrong".to_string(),
+            ..client(address)
+        };
+        let error = wrong
+            .client_credentials(RequestOptions::default())
+            .await
+            .unwrap_err();
+        assert!(format!("{error:#}").contains("invalid_client"), "{error:#}");
+    });
+}
+
+#[test]
+fn refreshing_keeps_the_refresh_token() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_oauth().await;
+
+        let token = client(address)
+            .refresh("r1".to_string(), RequestOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(token.access_token, "refreshed");
+        assert_eq!(token.refresh_token.as_deref(), Some("r1"));
+
+        let error = client(address)
+            .refresh("stale".to_string(), RequestOptions::default())
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{error:#}").contains("invalid_grant (unknown grant)"),
+            "{error:#}"
+        );
+    });
+}
+
+#[test]
+fn authorization_codes_are_received_on_the_loopback_interface() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_oauth().await;
+
+        let authorization = client(address).authorize().await.unwrap();
+        let param = |name: &str| {
+            authorization
+                .url
+                .query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+                .unwrap()
+        };
+        let (redirect_uri, state, challenge) = (
+            param("redirect_uri"),
+            param("state"),
+            param("code_challenge"),
+        );
+        assert_eq!(param("prompt"), "consent");
+        assert_eq!(param("code_challenge_method"), "S256");
+        assert_eq!(param("scope"), "read write");
+        assert!(redirect_uri.starts_with("http://127.0.0.1:"));
+
+        let token = tokio::spawn(authorization.finish(RequestOptions::default()));
+
+        // The browser asks for other things before following the redirect.
+        let browse = |url: String| {
+            http_client::send(
+                HttpRequest {
+                    method: Method::GET,
+                    url: Url::parse(&url).unwrap(),
+                    headers: Vec::new(),
+                    body: HttpBody::default(),
+                },
+                RequestOptions::default(),
+            )
+        };
+        let favicon = browse(redirect_uri.replace("/callback", "/favicon.ico"))
+            .await
+            .unwrap();
+        assert_eq!(favicon.status, 404);
+        let redirect = browse(format!("{redirect_uri}?code=abc&state={state}"))
+            .await
+            .unwrap();
+        assert_eq!(redirect.status, 200);
+
+        let token: OAuthToken = token.await.unwrap().unwrap();
+        assert_eq!(token.access_token, format!("abc:{challenge}"));
+        assert_eq!(token.refresh_token.as_deref(), Some("r1"));
+    });
+}
+
+#[test]
+fn redirects_with_the_wrong_state_are_refused() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_oauth().await;
+
+        let authorization = client(address).authorize().await.unwrap();
+        let redirect_uri = authorization
+            .url
+            .query_pairs()
+            .find(|(key, _)| key == "redirect_uri")
+            .map(|(_, value)| value.into_owned())
+            .unwrap();
+        let token = tokio::spawn(authorization.finish(RequestOptions::default()));
+
+        http_client::send(
+            HttpRequest {
+                method: Method::GET,
+                url: Url::parse(&format!("{redirect_uri}?code=abc&state=forged")).unwrap(),
+                headers: Vec::new(),
+                body: HttpBody::default(),
+            },
+            RequestOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        let error = token.await.unwrap().unwrap_err();
+        assert!(format!("{error:#}").contains("wrong state"), "{error:#}");
+    });
+}
+
+#[test]
+fn idle_and_oversized_connections_dont_hold_up_the_redirect() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_oauth().await;
+
+        let authorization = client(address).authorize().await.unwrap();
+        let param = |name: &str| {
+            authorization
+                .url
+                .query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+                .unwrap()
+        };
+        let (redirect_uri, state) = (param("redirect_uri"), param("state"));
+        let listener = Url::parse(&redirect_uri).unwrap();
+        let listener = (
+            listener.host_str().unwrap().to_string(),
+            listener.port().unwrap(),
+        );
+        let token = tokio::spawn(authorization.finish(RequestOptions::default()));
+
+        // A connection opened ahead of time that never sends anything.
+        let _idle = TcpStream::connect(listener.clone()).await.unwrap();
+
+        // A request head without an end is cut off once too large, without
+        // waiting for the rest.
+        let mut oversized = TcpStream::connect(listener).await.unwrap();
+        let head = format!(
+            "GET /callback?code=abc HTTP/1.1\r\nx-filler: {}",
+            "a".repeat(64 * 1024)
+        );
+        oversized.write_all(head.as_bytes()).await.ok();
+        let mut response = Vec::new();
+        tokio::time::timeout(Duration::from_secs(2), oversized.read_to_end(&mut response))
+            .await
+            .expect("the oversized request was waited on")
+            .ok();
+        assert!(
+            response.is_empty(),
+            "{}",
+            String::from_utf8_lossy(&response)
+        );
+
+        let redirect = tokio::time::timeout(
+            Duration::from_secs(2),
+            http_client::send(
+                HttpRequest {
+                    method: Method::GET,
+                    url: Url::parse(&format!("{redirect_uri}?code=abc&state={state}")).unwrap(),
+                    headers: Vec::new(),
+                    body: HttpBody::default(),
+                },
+                RequestOptions::default(),
+            ),
+        )
+        .await
+        .expect("the redirect waited for the idle connection")
+        .unwrap();
+        assert_eq!(redirect.status, 200);
+        assert!(token.await.unwrap().is_ok());
+    });
+}
+
+#[test]
+fn device_codes_are_polled_until_authorized() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_oauth().await;
+        let public = OAuthClient {
+            client_id: "public".to_string(),
+            client_secret: String::new(),
+            ..client(address)
+        };
+
+        let device = public
+            .device_authorization(RequestOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(device.user_code, "ABCD-EFGH");
+        assert_eq!(device.verification_uri, "https://example.com/activate");
+
+        let token = device.finish(RequestOptions::default()).await.unwrap();
+        assert_eq!(token.access_token, "device");
+        assert_eq!(token.expires_at, None);
+    });
+}
+
+#[test]
+fn jwt_claims_are_decoded() {
+    let encode = |json: &str| BASE64_URL_SAFE_NO_PAD.encode(json);
+    let token = format!(
+        "{}.{}.signature",
+        encode(r#"{"alg":"RS256","typ":"JWT"}"#),
+        encode(r#"{"sub":"ada","exp":1700000000}"#)
+    );
+
+    let jwt = decode_jwt(&token).unwrap();
+    assert_eq!(jwt.header["alg"], "RS256");
+    assert_eq!(jwt.claims["sub"], "ada");
+    assert_eq!(jwt.claims["exp"], 1700000000);
+
+    assert!(decode_jwt("opaque-token").is_none());
+    assert!(decode_jwt("a.b.c").is_none());
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use http_client::{HttpVersion, RequestOptions, http::Version};
+
+use crate::common::{get, runtime, serve_h2c, serve_http1};
+
+fn options(version: HttpVersion) -> RequestOptions {
+    RequestOptions {
+        version,
+        ..RequestOptions::default()
+    }
+}
+
+#[test]
+fn prior_knowledge_speaks_h2c() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_h2c().await;
+        let response = http_client::send(
+            get(address, "/hello?x=1"),
+            options(HttpVersion::Http2PriorKnowledge),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.version, Version::HTTP_2);
+        assert_eq!(response.status, 200);
+        assert_eq!(&response.body[..], b"/hello");
+        assert_eq!(http_client::protocol_name(response.version), "HTTP/2");
+    });
+}
+
+#[test]
+fn cleartext_defaults_to_http1() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_http1().await;
+
+        for version in [HttpVersion::Auto, HttpVersion::Http1] {
+            let response = http_client::send(get(address, "/"), options(version))
+                .await
+                .unwrap();
+
+            assert_eq!(response.version, Version::HTTP_11);
+            assert_eq!(&response.body[..], b"ok");
+        }
+    });
+}
+
+#[test]
+fn forcing_http1_against_h2c_server_fails() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_h2c().await;
+        let result = http_client::send(get(address, "/"), options(HttpVersion::Http1)).await;
+
+        assert!(result.is_err());
+    });
+}
+
+#[test]
+fn negotiated_h2_requires_tls() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_h2c().await;
+        let error = http_client::send(get(address, "/"), options(HttpVersion::Http2))
+            .await
+            .unwrap_err();
+
+        assert!(error.to_string().contains("prior knowledge"));
+    });
+}
@@ -0,0 +1,326 @@
+mod common;
+
+use std::net::SocketAddr;
+
+use bytes::Bytes;
+use h2::{RecvStream, server::SendResponse};
+use http_client::{
+    CookieJar, HttpRequest, HttpVersion, RedirectPolicy, RequestOptions,
+    http::{Method, Request, Response, StatusCode},
+    url::Url,
+};
+use tokio::net::TcpListener;
+
+use crate::common::{get, runtime};
+
+/// Serves h2c with prior knowledge, redirecting as the path says and
+/// describing the request on `/echo`.
+async fn serve_redirects() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut connection = h2::server::handshake(socket).await.unwrap();
+
+                // Streams are answered in their own tasks, since the
+                // connection only makes progress while being accepted on.
+                while let Some(Ok((request, respond))) = connection.accept().await {
+                    tokio::spawn(redirect(request, respond, address));
+                }
+            });
+        }
+    });
+
+    address
+}
+
+async fn redirect(
+    request: Request<RecvStream>,
+    mut respond: SendResponse<Bytes>,
+    address: SocketAddr,
+) {
+    let (parts, mut body) = request.into_parts();
+    let mut length = 0;
+    while let Some(Ok(data)) = body.data().await {
+        length += data.len();
+    }
+
+    let header = |name: &str| {
+        parts
+            .headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("-")
+            .to_string()
+    };
+    let other_origin = format!("http://localhost:{}", address.port());
+
+    let (status, location, set_cookie) = match parts.uri.path() {
+        "/login" => (302, "/echo".to_string(), Some("session=1")),
+        "/moved" => (301, "/echo".to_string(), None),
+        "/see-other" => (303, "/echo".to_string(), None),
+        "/temporary" => (307, "/echo".to_string(), None),
+        "/elsewhere" => (302, format!("{other_origin}/echo"), None),
+        "/chain" => (302, "/elsewhere".to_string(), Some("step=1")),
+        "/loop" => (302, "/loop".to_string(), None),
+        _ => (200, String::new(), None),
+    };
+
+    let mut response = Response::builder().status(status);
+    if status != 200 {
+        response = response.header("location", location);
+    }
+    if let Some(set_cookie) = set_cookie {
+        response = response.header("set-cookie", set_cookie);
+    }
+
+    let mut body = format!(
+        "{} authorization={} cookie={} body={length}",
+        parts.method,
+        header("authorization"),
+        header("cookie"),
+    );
+    if parts.headers.contains_key("x-api-key") {
+        body.push_str(&format!(" x-api-key={}", header("x-api-key")));
+    }
+    let body = Bytes::from(body);
+    let mut stream = respond
+        .send_response(response.body(()).unwrap(), false)
+        .unwrap();
+
+    stream.send_data(body, true).unwrap();
+}
+
+fn options(redirect: RedirectPolicy) -> RequestOptions {
+    RequestOptions {
+        version: HttpVersion::Http2PriorKnowledge,
+        redirect,
+        ..RequestOptions::default()
+    }
+}
+
+fn post(address: SocketAddr, path: &str) -> HttpRequest {
+    HttpRequest {
+        method: Method::POST,
+        body: Bytes::from_static(b"payload").into(),
+        headers: vec![("content-type".into(), "text/plain".into())],
+        ..get(address, path)
+    }
+}
+
+fn with_authorization(mut request: HttpRequest) -> HttpRequest {
+    request
+        .headers
+        .push(("authorization".into(), "Bearer token".into()));
+    request
+}
+
+#[test]
+fn hops_are_recorded_in_order() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_redirects().await;
+        let request = with_authorization(get(address, "/chain"));
+        let response = http_client::send(request, options(RedirectPolicy::default()))
+            .await
+            .unwrap();
+
+        let hops = response
+            .redirects
+            .iter()
+            .map(|hop| (hop.status.as_u16(), hop.url.path(), hop.location.as_str()))
+            .collect::<Vec<_>>();
+        let elsewhere = format!("http://{address}/elsewhere");
+        let echo = format!("http://localhost:{}/echo", address.port());
+
+        assert_eq!(
+            hops,
+            [
+                (302, "/chain", elsewhere.as_str()),
+                (302, "/elsewhere", echo.as_str())
+            ]
+        );
+        assert_eq!(
+            response.redirects[0].headers["set-cookie"], "step=1",
+            "hops keep their headers"
+        );
+        assert_eq!(response.url.as_str(), echo);
+        assert_eq!(
+            &response.body[..],
+            b"GET authorization=- cookie=- body=0",
+            "credentials stay with the origin they were meant for"
+        );
+    });
+}
+
+#[test]
+fn authorization_is_forwarded_when_allowed() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_redirects().await;
+        let policy = RedirectPolicy {
+            forward_auth: true,
+            ..RedirectPolicy::default()
+        };
+        let request = with_authorization(get(address, "/elsewhere"));
+        let response = http_client::send(request, options(policy)).await.unwrap();
+
+        assert_eq!(
+            &response.body[..],
+            b"GET authorization=Bearer token cookie=- body=0"
+        );
+
+        let request = with_authorization(get(address, "/moved"));
+        let response = http_client::send(request, options(RedirectPolicy::default()))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            &response.body[..],
+            b"GET authorization=Bearer token cookie=- body=0",
+            "same origin redirects keep credentials"
+        );
+    });
+}
+
+#[test]
+fn authentication_headers_stay_with_their_origin() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_redirects().await;
+        let with_key = |path| {
+            let mut request = get(address, path);
+            request.headers.push(("X-Api-Key".into(), "secret".into()));
+            request
+        };
+        let options = |forward_auth| RequestOptions {
+            auth_headers: vec!["x-api-key".into()],
+            ..options(RedirectPolicy {
+                forward_auth,
+                ..RedirectPolicy::default()
+            })
+        };
+
+        let response = http_client::send(with_key("/elsewhere"), options(false))
+            .await
+            .unwrap();
+        assert_eq!(
+            &response.body[..],
+            b"GET authorization=- cookie=- body=0",
+            "the API key isn't sent to another origin"
+        );
+
+        let response = http_client::send(with_key("/moved"), options(false))
+            .await
+            .unwrap();
+        assert_eq!(
+            &response.body[..],
+            b"GET authorization=- cookie=- body=0 x-api-key=secret"
+        );
+
+        let response = http_client::send(with_key("/elsewhere"), options(true))
+            .await
+            .unwrap();
+        assert_eq!(
+            &response.body[..],
+            b"GET authorization=- cookie=- body=0 x-api-key=secret"
+        );
+    });
+}
+
+#[test]
+fn methods_follow_the_status() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_redirects().await;
+        let send = |path: &str, policy| http_client::send(post(address, path), options(policy));
+        let keep_method = RedirectPolicy {
+            keep_method: true,
+            ..RedirectPolicy::default()
+        };
+
+        let cases = [
+            ("/moved", RedirectPolicy::default(), "GET", 0),
+            ("/moved", keep_method, "POST", 7),
+            ("/see-other", keep_method, "GET", 0),
+            ("/temporary", RedirectPolicy::default(), "POST", 7),
+        ];
+
+        for (path, policy, method, length) in cases {
+            let response = send(path, policy).await.unwrap();
+            let expected = format!("{method} authorization=- cookie=- body={length}");
+
+            assert_eq!(response.body, expected.as_bytes(), "{path}");
+            assert_eq!(response.redirects[0].method, Method::POST);
+        }
+    });
+}
+
+#[test]
+fn cookies_set_by_hops_are_sent_to_the_next() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_redirects().await;
+        let request = get(address, "/login");
+        let response = http_client::send(
+            request.clone(),
+            RequestOptions {
+                cookies: Some(CookieJar::default()),
+                ..options(RedirectPolicy::default())
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            &response.body[..],
+            b"GET authorization=- cookie=session=1 body=0"
+        );
+
+        // Without a jar, cookies are left to the caller.
+        let response = http_client::send(request, options(RedirectPolicy::default()))
+            .await
+            .unwrap();
+
+        assert_eq!(&response.body[..], b"GET authorization=- cookie=- body=0");
+    });
+}
+
+#[test]
+fn redirects_can_be_limited_or_turned_off() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_redirects().await;
+        let error = http_client::send(get(address, "/loop"), options(RedirectPolicy::default()))
+            .await
+            .unwrap_err();
+
+        assert!(
+            error.to_string().contains("too many redirects"),
+            "{error:#}"
+        );
+
+        let policy = RedirectPolicy {
+            follow: false,
+            ..RedirectPolicy::default()
+        };
+        let response = http_client::send(get(address, "/moved"), options(policy))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status, StatusCode::MOVED_PERMANENTLY);
+        assert!(response.redirects.is_empty());
+        assert_eq!(
+            response.url,
+            Url::parse(&format!("http://{address}/moved")).unwrap()
+        );
+    });
+}
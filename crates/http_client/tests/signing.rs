@@ -0,0 +1,344 @@
+mod common;
+
+use std::{
+    net::SocketAddr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use bytes::Bytes;
+use http_client::{
+    AwsCredentials, AwsSigV4, HmacAlgorithm, HmacSigner, HttpBody, HttpRequest, HttpVersion,
+    RequestOptions, RequestSigner, SignatureEncoding,
+    http::{Method, Response},
+    url::Url,
+};
+use sha2::{Digest, Sha256};
+use tokio::net::TcpListener;
+
+use crate::common::{get, runtime};
+
+/// The time of the examples of the AWS documentation, 2015-08-30T12:36:00Z.
+fn example_time() -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(1_440_938_160)
+}
+
+/// The credentials of the examples of the AWS documentation.
+fn example_signer(service: &str) -> RequestSigner {
+    RequestSigner::AwsSigV4(AwsSigV4 {
+        service: service.to_string(),
+        region: "us-east-1".to_string(),
+        credentials: AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+        },
+        unsigned_payload: false,
+    })
+}
+
+fn request(method: Method, url: &str, headers: &[(&str, &str)]) -> HttpRequest {
+    HttpRequest {
+        method,
+        url: Url::parse(url).unwrap(),
+        headers: headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+        body: HttpBody::default(),
+    }
+}
+
+fn header<'a>(request: &'a HttpRequest, name: &str) -> Option<&'a str> {
+    request
+        .headers
+        .iter()
+        .find(|(header, _)| header.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Signs `request` like the examples of the AWS documentation, returning
+/// its `Authorization` header.
+fn sign_example(service: &str, mut request: HttpRequest) -> String {
+    runtime().block_on(async {
+        example_signer(service)
+            .sign(&mut request, example_time())
+            .await
+            .unwrap();
+    });
+
+    assert_eq!(header(&request, "x-amz-date"), Some("20150830T123600Z"));
+    header(&request, "authorization").unwrap().to_string()
+}
+
+#[test]
+fn sigv4_matches_the_iam_example() {
+    let authorization = sign_example(
+        "iam",
+        request(
+            Method::GET,
+            "https://iam.amazonaws.com/?Action=ListUsers&Version=2010-05-08",
+            &[(
+                "Content-Type",
+                "application/x-www-form-urlencoded; charset=utf-8",
+            )],
+        ),
+    );
+
+    assert_eq!(
+        authorization,
+        "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/iam/aws4_request, \
+         SignedHeaders=content-type;host;x-amz-date, \
+         Signature=5d672d79c15b13162d9279b0855cfba6789a8edb4c82c400e06b5924a6f2b5d7"
+    );
+}
+
+#[test]
+fn sigv4_matches_the_test_suite() {
+    let cases = [
+        (
+            Method::GET,
+            "https://example.amazonaws.com/",
+            "5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31",
+        ),
+        (
+            Method::GET,
+            "https://example.amazonaws.com/?Param2=value2&Param1=value1",
+            "b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500",
+        ),
+        (
+            Method::POST,
+            "https://example.amazonaws.com/",
+            "5da7c1a2acd57cee7505fc6676e4e544621c30862966e37dddb68e92efbe5d6b",
+        ),
+    ];
+
+    for (method, url, signature) in cases {
+        let authorization = sign_example("service", request(method, url, &[]));
+
+        assert!(
+            authorization.ends_with(&format!(
+                "SignedHeaders=host;x-amz-date, Signature={signature}"
+            )),
+            "{url}: {authorization}"
+        );
+    }
+}
+
+#[test]
+fn sigv4_signs_session_tokens_and_replaces_earlier_signatures() {
+    let runtime = runtime();
+    let RequestSigner::AwsSigV4(mut signer) = example_signer("s3") else {
+        unreachable!()
+    };
+    signer.credentials.session_token = Some("token".to_string());
+    let signer = RequestSigner::AwsSigV4(signer);
+
+    let mut request = request(
+        Method::PUT,
+        "https://bucket.s3.amazonaws.com/a%20file.txt",
+        &[("authorization", "stale"), ("x-amz-date", "stale")],
+    );
+    request.body = HttpBody::from("hello".to_string());
+
+    runtime.block_on(async {
+        signer.sign(&mut request, example_time()).await.unwrap();
+        signer.sign(&mut request, example_time()).await.unwrap();
+    });
+
+    let count = |name: &str| {
+        request
+            .headers
+            .iter()
+            .filter(|(header, _)| header.eq_ignore_ascii_case(name))
+            .count()
+    };
+    assert_eq!(count("authorization"), 1);
+    assert_eq!(count("x-amz-date"), 1);
+    assert_eq!(header(&request, "x-amz-security-token"), Some("token"));
+    assert_eq!(
+        header(&request, "x-amz-content-sha256"),
+        Some("2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824")
+    );
+    assert!(
+        header(&request, "authorization")
+            .unwrap()
+            .contains("SignedHeaders=host;x-amz-content-sha256;x-amz-date;x-amz-security-token,")
+    );
+}
+
+#[test]
+fn aws_credentials_are_read_from_profiles() {
+    let content = "\
+[default]
+aws_access_key_id = AKIDDEFAULT
+aws_secret_access_key = default-secret
+
+# Temporary credentials
+[profile staging]
+aws_access_key_id = AKIDSTAGING
+aws_secret_access_key = staging-secret
+aws_session_token = staging-token
+";
+
+    let staging = AwsCredentials::parse_profile(content, "staging").unwrap();
+    assert_eq!(staging.access_key_id, "AKIDSTAGING");
+    assert_eq!(staging.secret_access_key, "staging-secret");
+    assert_eq!(staging.session_token.as_deref(), Some("staging-token"));
+
+    let default = AwsCredentials::parse_profile(content, "default").unwrap();
+    assert_eq!(default.access_key_id, "AKIDDEFAULT");
+    assert_eq!(default.session_token, None);
+
+    assert!(AwsCredentials::parse_profile(content, "production").is_err());
+    assert!(AwsCredentials::from_vars(|_| None).is_err());
+}
+
+fn hmac_signer() -> HmacSigner {
+    HmacSigner {
+        key: "key".to_string(),
+        message: "{method} {path} {timestamp} {body_sha256}".to_string(),
+        value: "t={timestamp},v1={signature}".to_string(),
+        timestamp_header: Some("X-Timestamp".to_string()),
+        ..HmacSigner::default()
+    }
+}
+
+#[test]
+fn hmac_signatures_follow_the_templates() {
+    let runtime = runtime();
+
+    // The example of the HMAC article of Wikipedia.
+    let mut request = request(Method::GET, "https://example.com/", &[]);
+    request.body = HttpBody::from("The quick brown fox jumps over the lazy dog".to_string());
+    let signer = |algorithm, encoding| {
+        RequestSigner::Hmac(HmacSigner {
+            algorithm,
+            key: "key".to_string(),
+            message: "{body}".to_string(),
+            encoding,
+            ..HmacSigner::default()
+        })
+    };
+    runtime.block_on(async {
+        signer(HmacAlgorithm::Sha256, SignatureEncoding::Hex)
+            .sign(&mut request, example_time())
+            .await
+            .unwrap();
+    });
+    assert_eq!(
+        header(&request, "x-signature"),
+        Some("f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8")
+    );
+
+    runtime.block_on(async {
+        signer(HmacAlgorithm::Sha1, SignatureEncoding::Base64)
+            .sign(&mut request, example_time())
+            .await
+            .unwrap();
+    });
+    assert_eq!(
+        header(&request, "x-signature"),
+        Some("3nybhbi3iqa8ino29wqQcBydtNk=")
+    );
+
+    let mut request = self::request(Method::POST, "https://example.com/hooks?id=7", &[]);
+    runtime.block_on(async {
+        RequestSigner::Hmac(hmac_signer())
+            .sign(&mut request, example_time())
+            .await
+            .unwrap();
+    });
+    assert_eq!(header(&request, "x-timestamp"), Some("1440938160"));
+    let expected = expected_hmac(&format!(
+        "POST /hooks?id=7 1440938160 {}",
+        hex(&Sha256::digest(b""))
+    ));
+    assert_eq!(
+        header(&request, "x-signature"),
+        Some(format!("t=1440938160,v1={expected}").as_str())
+    );
+
+    let unknown = RequestSigner::Hmac(HmacSigner {
+        key: "key".to_string(),
+        message: "{method} {nonce}".to_string(),
+        ..HmacSigner::default()
+    });
+    let error = runtime
+        .block_on(unknown.sign(&mut request, example_time()))
+        .unwrap_err();
+    assert!(format!("{error:#}").contains("unknown placeholder {nonce}"));
+}
+
+fn expected_hmac(message: &str) -> String {
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, b"key");
+    hex(ring::hmac::sign(&key, message.as_bytes()).as_ref())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Serves h2c with prior knowledge, redirecting `/redirect` to `/target`
+/// and echoing back the signature of other requests.
+async fn serve_signed() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut connection = h2::server::handshake(socket).await.unwrap();
+
+                while let Some(Ok((request, mut respond))) = connection.accept().await {
+                    if request.uri().path() == "/redirect" {
+                        let response = Response::builder()
+                            .status(302)
+                            .header("location", "/target")
+                            .body(())
+                            .unwrap();
+                        respond.send_response(response, true).unwrap();
+                        continue;
+                    }
+
+                    let signature = request
+                        .headers()
+                        .get("x-signature")
+                        .and_then(|value| value.to_str().ok())
+                        .unwrap_or_default()
+                        .to_string();
+                    let response = Response::builder().status(200).body(()).unwrap();
+                    let mut stream = respond.send_response(response, false).unwrap();
+                    stream.send_data(Bytes::from(signature), true).unwrap();
+                }
+            });
+        }
+    });
+
+    address
+}
+
+#[test]
+fn redirected_requests_are_signed_again() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_signed().await;
+        let options = RequestOptions {
+            version: HttpVersion::Http2PriorKnowledge,
+            signer: Some(RequestSigner::Hmac(HmacSigner {
+                key: "key".to_string(),
+                message: "{method} {path}".to_string(),
+                ..HmacSigner::default()
+            })),
+            ..RequestOptions::default()
+        };
+
+        let response = http_client::send(get(address, "/redirect"), options)
+            .await
+            .unwrap();
+
+        assert_eq!(response.status, 200);
+        assert_eq!(response.redirects.len(), 1);
+        assert_eq!(response.body, expected_hmac("GET /target"));
+    });
+}
@@ -0,0 +1,189 @@
+mod common;
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::StreamExt;
+use http_client::{RequestOptions, SseEvent, SseMessage, SseParser};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+use crate::common::{get, runtime};
+
+fn event(event: &str, data: &str, id: Option<&str>) -> SseEvent {
+    SseEvent {
+        event: event.into(),
+        data: data.into(),
+        id: id.map(Into::into),
+        retry: None,
+    }
+}
+
+#[test]
+fn events_are_parsed_across_chunks() {
+    let stream = "\u{feff}: a comment\r\nevent: greeting\r\ndata: hello\r\ndata:  world\r\n\r\n\
+                  id: 7\ndata\n\n\
+                  event: ignored\nretry: 1500\n\n\
+                  data: last\rretry: nope\r\r\
+                  data: unfinished";
+
+    // Every split point must give the same events, CRLF and BOM included.
+    for split in 0..=stream.len() {
+        let mut parser = SseParser::new(None);
+        let mut events = parser.feed(&stream.as_bytes()[..split]);
+        events.extend(parser.feed(&stream.as_bytes()[split..]));
+
+        assert_eq!(
+            events,
+            [
+                event("greeting", "hello\n world", None),
+                event("message", "", Some("7")),
+                event("message", "last", Some("7")),
+            ],
+            "split at {split}"
+        );
+        assert_eq!(parser.last_event_id(), Some("7"));
+        assert_eq!(
+            parser.reconnection_time(),
+            Some(Duration::from_millis(1500))
+        );
+    }
+}
+
+#[test]
+fn retry_is_reported_with_its_event() {
+    let mut parser = SseParser::new(Some("3".into()));
+    let events = parser.feed(b"retry: 10\ndata: a\n\ndata: b\nid\n\n");
+
+    assert_eq!(
+        events,
+        [
+            SseEvent {
+                retry: Some(Duration::from_millis(10)),
+                ..event("message", "a", Some("3"))
+            },
+            event("message", "b", None),
+        ]
+    );
+}
+
+/// Serves an event stream, recording the `Last-Event-ID` of each request.
+/// The first connection gets two events and is closed, and the second
+/// one is told to stop with 204 No Content.
+async fn serve_events(last_event_ids: Arc<Mutex<Vec<Option<String>>>>) -> std::net::SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let last_event_ids = last_event_ids.clone();
+
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                let mut buffer = [0; 1024];
+                while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                    match socket.read(&mut buffer).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(read) => request.extend_from_slice(&buffer[..read]),
+                    }
+                }
+
+                let request = String::from_utf8_lossy(&request).to_lowercase();
+                assert!(request.contains("accept: text/event-stream"), "{request}");
+                let last_event_id = request
+                    .lines()
+                    .find_map(|line| line.strip_prefix("last-event-id: "))
+                    .map(|id| id.trim().to_string());
+                let first = last_event_id.is_none();
+                last_event_ids.lock().unwrap().push(last_event_id);
+
+                if first {
+                    socket
+                        .write_all(
+                            b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\n\
+                              connection: close\r\n\r\nretry: 50\nid: 1\ndata: one\n\n",
+                        )
+                        .await
+                        .unwrap();
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    socket
+                        .write_all(b"event: update\nid: 2\ndata: two\n\ndata: cut")
+                        .await
+                        .unwrap();
+                } else {
+                    socket
+                        .write_all(b"HTTP/1.1 204 No Content\r\n\r\n")
+                        .await
+                        .unwrap();
+                }
+            });
+        }
+    });
+
+    address
+}
+
+#[test]
+fn streams_reconnect_with_the_last_event_id() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let last_event_ids = Arc::new(Mutex::new(Vec::new()));
+        let address = serve_events(last_event_ids.clone()).await;
+        let mut request = get(address, "/events");
+        request.headers.clear();
+
+        let (done, messages) = http_client::subscribe(request, RequestOptions::default(), None);
+        tokio::time::timeout(Duration::from_secs(5), done)
+            .await
+            .expect("the stream stops on 204")
+            .unwrap();
+        let messages = messages.collect::<Vec<_>>().await;
+
+        let events = messages
+            .iter()
+            .filter_map(|message| match message {
+                SseMessage::Event { event, .. } => Some(event.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            [
+                SseEvent {
+                    retry: Some(Duration::from_millis(50)),
+                    ..event("message", "one", Some("1"))
+                },
+                event("update", "two", Some("2")),
+            ]
+        );
+
+        assert!(matches!(messages[0], SseMessage::Open { .. }));
+        let SseMessage::Reconnecting { error, retry_in } = messages.last().unwrap() else {
+            panic!("expected a reconnection last, got {messages:?}");
+        };
+        assert_eq!(*error, None);
+        assert_eq!(*retry_in, Duration::from_millis(50));
+        assert_eq!(
+            *last_event_ids.lock().unwrap(),
+            [None, Some("2".to_string())]
+        );
+    });
+}
+
+#[test]
+fn other_responses_fail_the_stream() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = common::serve_http1().await;
+        let (done, _) = http_client::subscribe(get(address, "/"), RequestOptions::default(), None);
+        let error = done.await.unwrap_err();
+
+        assert_eq!(error.to_string(), "expected text/event-stream, got \"\"");
+    });
+}
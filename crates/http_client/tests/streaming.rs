@@ -0,0 +1,325 @@
+mod common;
+
+use std::{fs, io::Write, net::SocketAddr, sync::Arc, time::Duration};
+
+use flate2::{Compression, write::GzEncoder};
+use futures::StreamExt;
+use http_client::{PREVIEW_SIZE, RequestOptions, ResponseProgress, Timeouts, url::Url};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::Notify,
+};
+
+use crate::common::{get, runtime};
+
+/// Serves every connection with `handler`, once the request head was read.
+async fn serve<F, Fut>(handler: F) -> SocketAddr
+where
+    F: Fn(TcpStream) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let handler = Arc::new(handler);
+
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let handler = handler.clone();
+
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                let mut buffer = [0; 1024];
+                while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                    match socket.read(&mut buffer).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(read) => request.extend_from_slice(&buffer[..read]),
+                    }
+                }
+
+                handler(socket).await;
+            });
+        }
+    });
+
+    address
+}
+
+/// Writes `body` a chunk at a time, pausing between chunks.
+async fn serve_chunks(body: Vec<u8>, chunk_size: usize, pause: Duration) -> SocketAddr {
+    serve(move |mut socket| {
+        let body = body.clone();
+
+        async move {
+            let head = format!("HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n", body.len());
+            socket.write_all(head.as_bytes()).await.unwrap();
+
+            for chunk in body.chunks(chunk_size) {
+                socket.write_all(chunk).await.unwrap();
+                socket.flush().await.unwrap();
+                tokio::time::sleep(pause).await;
+            }
+        }
+    })
+    .await
+}
+
+fn with_timeouts(timeouts: Timeouts) -> RequestOptions {
+    RequestOptions {
+        timeouts,
+        ..RequestOptions::default()
+    }
+}
+
+#[test]
+fn progress_reports_the_head_then_the_body() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let body = (0..3000)
+            .map(|index| b'a' + (index % 26) as u8)
+            .collect::<Vec<_>>();
+        let address = serve_chunks(body.clone(), 1000, Duration::from_millis(20)).await;
+        let (response, progress) =
+            http_client::send_with_progress(get(address, "/"), RequestOptions::default());
+
+        let response = response.await.unwrap();
+        let progress = progress.collect::<Vec<_>>().await;
+
+        let Some(ResponseProgress::Head { status, .. }) = progress.first() else {
+            panic!("expected the head first, got {progress:?}");
+        };
+        assert_eq!(status.as_u16(), 200);
+
+        let mut streamed = Vec::new();
+        let mut last = None;
+        for progress in &progress[1..] {
+            let ResponseProgress::Body {
+                chunk,
+                received,
+                total,
+            } = progress
+            else {
+                panic!("expected a single head, got {progress:?}");
+            };
+
+            streamed.extend_from_slice(chunk);
+            assert_eq!(*total, Some(3000));
+            last = Some(*received);
+        }
+
+        assert!(progress.len() > 2, "the body arrives in several chunks");
+        assert_eq!(last, Some(3000));
+        assert_eq!(streamed, body);
+        assert_eq!(response.body, body);
+    });
+}
+
+#[test]
+fn dropping_the_request_cancels_it() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let closed = Arc::new(Notify::new());
+        let address = serve({
+            let closed = closed.clone();
+
+            move |mut socket| {
+                let closed = closed.clone();
+
+                async move {
+                    socket
+                        .write_all(b"HTTP/1.1 200 OK\r\ntransfer-encoding: chunked\r\n\r\n")
+                        .await
+                        .unwrap();
+
+                    // Streams until the client goes away.
+                    while socket.write_all(b"5\r\nchunk\r\n").await.is_ok() {
+                        tokio::time::sleep(Duration::from_millis(10)).await;
+                    }
+                    closed.notify_one();
+                }
+            }
+        })
+        .await;
+
+        let (response, mut progress) =
+            http_client::send_with_progress(get(address, "/"), RequestOptions::default());
+        let response = tokio::spawn(response);
+
+        while let Some(progress) = progress.next().await {
+            if let ResponseProgress::Body { .. } = progress {
+                break;
+            }
+        }
+        response.abort();
+
+        tokio::time::timeout(Duration::from_secs(5), closed.notified())
+            .await
+            .expect("the connection is closed once the request is dropped");
+    });
+}
+
+#[test]
+fn read_timeout_fails_stalled_responses() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let timeouts = Timeouts {
+            read: Some(Duration::from_millis(200)),
+            ..Timeouts::default()
+        };
+
+        let address = serve(|socket| async move {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            drop(socket);
+        })
+        .await;
+        let error = http_client::send(get(address, "/"), with_timeouts(timeouts))
+            .await
+            .unwrap_err();
+
+        assert!(
+            error
+                .to_string()
+                .contains("timed out waiting for the response"),
+            "{error:#}"
+        );
+
+        let address = serve(|mut socket| async move {
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 10\r\n\r\nhalf")
+                .await
+                .unwrap();
+            tokio::time::sleep(Duration::from_secs(10)).await;
+        })
+        .await;
+        let error = http_client::send(get(address, "/"), with_timeouts(timeouts))
+            .await
+            .unwrap_err();
+
+        assert!(
+            error.to_string().contains("timed out reading the body"),
+            "{error:#}"
+        );
+
+        // Chunks arriving in time keep the request going past the timeout.
+        let address = serve_chunks(vec![b'x'; 10], 1, Duration::from_millis(50)).await;
+        let response = http_client::send(get(address, "/"), with_timeouts(timeouts))
+            .await
+            .unwrap();
+
+        assert_eq!(&response.body[..], b"xxxxxxxxxx");
+    });
+}
+
+#[test]
+fn connect_timeout_covers_the_tls_handshake() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        // Accepts connections without ever answering the client hello.
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut sockets = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                sockets.push(socket);
+            }
+        });
+
+        let mut request = get(address, "/");
+        request.url = Url::parse(&format!("https://{address}/")).unwrap();
+        let timeouts = Timeouts {
+            connect: Some(Duration::from_millis(200)),
+            ..Timeouts::default()
+        };
+        let error = http_client::send(request, with_timeouts(timeouts))
+            .await
+            .unwrap_err();
+
+        assert!(
+            error
+                .to_string()
+                .contains("timed out connecting to 127.0.0.1"),
+            "{error:#}"
+        );
+    });
+}
+
+#[test]
+fn total_timeout_bounds_the_whole_request() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_chunks(vec![b'x'; 100], 1, Duration::from_millis(20)).await;
+        let timeouts = Timeouts {
+            read: Some(Duration::from_secs(1)),
+            total: Some(Duration::from_millis(300)),
+            ..Timeouts::default()
+        };
+        let error = http_client::send(get(address, "/slow"), with_timeouts(timeouts))
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            format!("timed out waiting for http://{address}/slow after 300ms")
+        );
+    });
+}
+
+#[test]
+fn large_bodies_are_spilled_to_a_temporary_file() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let text = (0..PREVIEW_SIZE + 200_000)
+            .map(|index| b"0123456789abcdef\n"[index % 17])
+            .collect::<Vec<_>>();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+        encoder.write_all(&text).unwrap();
+        let gzip = encoder.finish().unwrap();
+
+        let address = serve(move |mut socket| {
+            let gzip = gzip.clone();
+
+            async move {
+                let head = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-encoding: gzip\r\ncontent-length: {}\r\n\r\n",
+                    gzip.len()
+                );
+                socket.write_all(head.as_bytes()).await.unwrap();
+                socket.write_all(&gzip).await.unwrap();
+            }
+        })
+        .await;
+
+        let options = RequestOptions {
+            spill_threshold: Some(1024),
+            ..RequestOptions::default()
+        };
+        let response = http_client::send(get(address, "/"), options).await.unwrap();
+        let spilled = response.spilled.clone().unwrap();
+
+        assert_eq!(response.decode_error, None);
+        assert_eq!(response.body_len(), text.len() as u64);
+        assert_eq!(&response.body[..], &text[..PREVIEW_SIZE]);
+        assert_eq!(fs::read(spilled.body.path()).unwrap(), text);
+        assert_eq!(
+            response.raw_body_len(),
+            fs::metadata(spilled.raw_body.path()).unwrap().len()
+        );
+
+        let paths = [
+            spilled.body.path().to_path_buf(),
+            spilled.raw_body.path().to_path_buf(),
+        ];
+        drop((response, spilled));
+
+        assert!(
+            paths.iter().all(|path| !path.exists()),
+            "temporary files are removed with the response"
+        );
+    });
+}
@@ -0,0 +1,170 @@
+mod common;
+
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use http_client::{HttpBody, HttpVersion, RequestOptions, http::Method};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+use crate::common::{get, runtime, serve_h2c, serve_http1};
+
+/// Answers the first request of each connection, keeping it alive, then
+/// closes it once the next one has been read. Logs the request line of each.
+async fn serve_closing_after_one(log: Arc<Mutex<Vec<String>>>) -> SocketAddr {
+    async fn read_head(socket: &mut TcpStream) -> Option<String> {
+        let mut head = Vec::new();
+        let mut buffer = [0; 1024];
+        while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+            match socket.read(&mut buffer).await {
+                Ok(0) | Err(_) => return None,
+                Ok(read) => head.extend_from_slice(&buffer[..read]),
+            }
+        }
+        let head = String::from_utf8_lossy(&head);
+        head.lines().next().map(str::to_string)
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let log = log.clone();
+            tokio::spawn(async move {
+                let Some(line) = read_head(&mut socket).await else {
+                    return;
+                };
+                log.lock().unwrap().push(line);
+                socket
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\nok")
+                    .await
+                    .unwrap();
+                if let Some(line) = read_head(&mut socket).await {
+                    log.lock().unwrap().push(line);
+                }
+            });
+        }
+    });
+
+    address
+}
+
+#[test]
+fn fresh_connection_reports_connect_phases() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_http1().await;
+        let response = http_client::send(get(address, "/fresh"), RequestOptions::default())
+            .await
+            .unwrap();
+        let timing = response.timing;
+
+        assert!(!response.connection.reused);
+        assert_eq!(response.connection.remote_addr, address);
+        assert!(timing.dns.is_none(), "IP literals are not resolved");
+        assert!(timing.connect.is_some());
+        assert!(timing.tls.is_none());
+        assert!(timing.total >= timing.connect.unwrap() + timing.first_byte + timing.download);
+        assert_eq!(timing.phases().len(), 3);
+    });
+}
+
+#[test]
+fn http1_connection_is_reused() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_http1().await;
+        let first = http_client::send(get(address, "/"), RequestOptions::default())
+            .await
+            .unwrap();
+        let second = http_client::send(get(address, "/"), RequestOptions::default())
+            .await
+            .unwrap();
+
+        assert!(second.connection.reused);
+        assert_eq!(second.connection.id, first.connection.id);
+        assert!(second.timing.connect.is_none());
+        assert_eq!(&second.body[..], b"ok");
+    });
+}
+
+#[test]
+fn h2_connection_is_shared() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_h2c().await;
+        let options = RequestOptions {
+            version: HttpVersion::Http2PriorKnowledge,
+            ..RequestOptions::default()
+        };
+        let first = http_client::send(get(address, "/a"), options.clone())
+            .await
+            .unwrap();
+        let second = tokio::spawn(http_client::send(get(address, "/b"), options.clone()));
+        let third = http_client::send(get(address, "/c"), options).await;
+
+        for response in [second.await.unwrap().unwrap(), third.unwrap()] {
+            assert!(response.connection.reused);
+            assert_eq!(response.connection.id, first.connection.id);
+        }
+    });
+}
+
+#[test]
+fn idempotent_requests_are_resent_when_a_reused_connection_closes() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let address = serve_closing_after_one(log.clone()).await;
+        let first = http_client::send(get(address, "/first"), RequestOptions::default())
+            .await
+            .unwrap();
+        let second = http_client::send(get(address, "/second"), RequestOptions::default())
+            .await
+            .unwrap();
+
+        assert_ne!(second.connection.id, first.connection.id);
+        assert_eq!(&second.body[..], b"ok");
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "GET /first HTTP/1.1",
+                "GET /second HTTP/1.1",
+                "GET /second HTTP/1.1",
+            ]
+        );
+    });
+}
+
+#[test]
+fn posts_are_not_resent_when_a_reused_connection_closes() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let address = serve_closing_after_one(log.clone()).await;
+        http_client::send(get(address, "/first"), RequestOptions::default())
+            .await
+            .unwrap();
+
+        let mut post = get(address, "/orders");
+        post.method = Method::POST;
+        post.body = HttpBody::from(b"order".as_slice());
+        let result = http_client::send(post, RequestOptions::default()).await;
+
+        assert!(result.is_err());
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["GET /first HTTP/1.1", "POST /orders HTTP/1.1"]
+        );
+    });
+}
@@ -0,0 +1,197 @@
+mod common;
+
+use std::net::SocketAddr;
+
+use http_client::{ClientCertificate, RequestOptions, TlsOptions, TlsVersion};
+use rustls::SupportedProtocolVersion;
+
+use crate::common::{TlsServer, fixture, get, runtime};
+
+static TLS12_ONLY: &[&SupportedProtocolVersion] = &[&rustls::version::TLS12];
+
+fn trusting_test_ca() -> TlsOptions {
+    TlsOptions {
+        ca_certificates: vec![fixture("ca.pem")],
+        ..TlsOptions::default()
+    }
+}
+
+async fn send(address: SocketAddr, tls: TlsOptions) -> anyhow::Result<http_client::HttpResponse> {
+    let mut request = get(address, "/");
+    request.url = format!("https://localhost:{}/", address.port())
+        .parse()
+        .unwrap();
+
+    http_client::send(
+        request,
+        RequestOptions {
+            tls,
+            ..RequestOptions::default()
+        },
+    )
+    .await
+}
+
+#[test]
+fn private_ca_must_be_configured() {
+    runtime().block_on(async {
+        let address = TlsServer::default().start().await;
+
+        let error = send(address, TlsOptions::default()).await.unwrap_err();
+        assert!(format!("{error:#}").contains("TLS handshake"));
+
+        let response = send(address, trusting_test_ca()).await.unwrap();
+        let tls = response.tls.unwrap();
+
+        assert_eq!(&response.body[..], b"ok");
+        assert!(tls.verified);
+        assert_eq!(tls.version, "TLS 1.3");
+        assert_eq!(tls.certificates[0].subject, "CN=localhost");
+        assert_eq!(
+            tls.certificates[0].issuer,
+            "CN=Test CA, O=http_client tests"
+        );
+        assert_eq!(
+            tls.certificates[0].subject_alt_names,
+            ["DNS:localhost", "IP:127.0.0.1"]
+        );
+        assert!(tls.certificates[0].not_after.starts_with("2126-"));
+        assert_eq!(tls.certificates[0].sha256.len(), 32 * 3 - 1);
+    });
+}
+
+#[test]
+fn skipping_verification_accepts_untrusted_certificates() {
+    runtime().block_on(async {
+        let address = TlsServer::default().start().await;
+        let response = send(
+            address,
+            TlsOptions {
+                skip_verification: true,
+                ..TlsOptions::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        assert!(!response.tls.unwrap().verified);
+    });
+}
+
+#[test]
+fn server_name_override_is_verified() {
+    runtime().block_on(async {
+        let address = TlsServer::default().start().await;
+        let error = send(
+            address,
+            TlsOptions {
+                server_name: Some("other.example".into()),
+                ..trusting_test_ca()
+            },
+        )
+        .await
+        .unwrap_err();
+
+        assert!(format!("{error:#}").contains("TLS handshake"));
+    });
+}
+
+#[test]
+fn minimum_version_is_enforced() {
+    runtime().block_on(async {
+        let address = TlsServer {
+            versions: TLS12_ONLY,
+            ..TlsServer::default()
+        }
+        .start()
+        .await;
+
+        let response = send(address, trusting_test_ca()).await.unwrap();
+        assert_eq!(response.tls.unwrap().version, "TLS 1.2");
+
+        let result = send(
+            address,
+            TlsOptions {
+                min_version: TlsVersion::Tls13,
+                ..trusting_test_ca()
+            },
+        )
+        .await;
+        assert!(result.is_err());
+    });
+}
+
+#[test]
+fn client_certificates_authenticate() {
+    runtime().block_on(async {
+        let address = TlsServer {
+            require_client_certificate: true,
+            ..TlsServer::default()
+        }
+        .start()
+        .await;
+        let with_certificate = |certificate| TlsOptions {
+            client_certificate: Some(certificate),
+            ..trusting_test_ca()
+        };
+
+        assert!(send(address, trusting_test_ca()).await.is_err());
+
+        let pem = ClientCertificate::Pem {
+            certificate: fixture("client.pem"),
+            key: fixture("client.key"),
+        };
+        send(address, with_certificate(pem)).await.unwrap();
+
+        let pkcs12 = ClientCertificate::Pkcs12 {
+            path: fixture("client.p12"),
+            password: "secret".into(),
+        };
+        send(address, with_certificate(pkcs12)).await.unwrap();
+
+        // Encrypted with RC2 and 3DES, with a SHA-1 MAC.
+        let legacy = ClientCertificate::Pkcs12 {
+            path: fixture("client-legacy.p12"),
+            password: "secret".into(),
+        };
+        send(address, with_certificate(legacy)).await.unwrap();
+    });
+}
+
+#[test]
+fn pkcs12_errors_are_explained() {
+    runtime().block_on(async {
+        let address = TlsServer::default().start().await;
+        let error = |path: &str, password: &str| {
+            let options = TlsOptions {
+                client_certificate: Some(ClientCertificate::Pkcs12 {
+                    path: fixture(path),
+                    password: password.into(),
+                }),
+                ..trusting_test_ca()
+            };
+
+            async move { format!("{:#}", send(address, options).await.unwrap_err()) }
+        };
+
+        assert!(
+            error("client.p12", "wrong")
+                .await
+                .contains("wrong PKCS#12 password")
+        );
+        assert!(
+            error("client-legacy.p12", "wrong")
+                .await
+                .contains("wrong PKCS#12 password")
+        );
+
+        let mut damaged = std::fs::read(fixture("client.p12")).unwrap();
+        let middle = damaged.len() / 2;
+        damaged[middle] ^= 1;
+        let path = std::env::temp_dir().join(format!("damaged-{}.p12", std::process::id()));
+        std::fs::write(&path, damaged).unwrap();
+        let message = error(path.to_str().unwrap(), "secret").await;
+        std::fs::remove_file(&path).ok();
+        assert!(message.contains("archive is damaged"), "{message}");
+    });
+}
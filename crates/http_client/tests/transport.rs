@@ -0,0 +1,171 @@
+mod common;
+
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use http_client::{
+    HttpBody, HttpRequest, RemoteAddr, RequestOptions, ResolveOverride, http::Method, url::Url,
+};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::common::{runtime, serve_http1};
+
+fn request(url: &str) -> HttpRequest {
+    HttpRequest {
+        method: Method::GET,
+        url: Url::parse(url).unwrap(),
+        headers: Vec::new(),
+        body: HttpBody::default(),
+    }
+}
+
+/// Answers every request with `200 OK` and `ok`, logging the head of each.
+async fn respond_logging(
+    mut socket: impl AsyncRead + AsyncWrite + Unpin,
+    log: Arc<Mutex<Vec<String>>>,
+) {
+    let mut buffer = [0; 1024];
+
+    loop {
+        let mut head = Vec::new();
+        while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+            match socket.read(&mut buffer).await {
+                Ok(0) | Err(_) => return,
+                Ok(read) => head.extend_from_slice(&buffer[..read]),
+            }
+        }
+
+        log.lock()
+            .unwrap()
+            .push(String::from_utf8_lossy(&head).into_owned());
+        if socket
+            .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\nok")
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn requests_are_sent_over_unix_sockets() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let path = std::env::temp_dir().join(format!("http-client-{}.sock", std::process::id()));
+        std::fs::remove_file(&path).ok();
+
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        tokio::spawn({
+            let log = log.clone();
+
+            async move {
+                while let Ok((socket, _)) = listener.accept().await {
+                    tokio::spawn(respond_logging(socket, log.clone()));
+                }
+            }
+        });
+
+        let options = RequestOptions {
+            unix_socket: Some(path.clone()),
+            ..RequestOptions::default()
+        };
+        let first = http_client::send(request("http://docker/v1.43/_ping"), options.clone())
+            .await
+            .unwrap();
+        let second = http_client::send(request("http://docker/info"), options)
+            .await
+            .unwrap();
+
+        assert_eq!(&first.body[..], b"ok");
+        assert_eq!(first.connection.remote_addr, RemoteAddr::Unix(path.clone()));
+        assert_eq!(
+            first.connection.remote_addr.to_string(),
+            format!("unix:{}", path.display())
+        );
+        assert!(first.timing.dns.is_none());
+        assert!(second.connection.reused);
+
+        let log = log.lock().unwrap();
+        assert!(log[0].starts_with("GET /v1.43/_ping HTTP/1.1\r\n"));
+        assert!(log[0].to_lowercase().contains("\r\nhost: docker\r\n"));
+        assert!(log[1].starts_with("GET /info HTTP/1.1\r\n"));
+
+        std::fs::remove_file(&path).ok();
+    });
+}
+
+#[test]
+fn missing_unix_socket_is_reported() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let path = PathBuf::from("/nonexistent/http-client.sock");
+        let options = RequestOptions {
+            unix_socket: Some(path),
+            ..RequestOptions::default()
+        };
+        let error = http_client::send(request("http://localhost/"), options)
+            .await
+            .unwrap_err();
+
+        assert!(
+            format!("{error:#}").contains("/nonexistent/http-client.sock"),
+            "{error:#}"
+        );
+    });
+}
+
+#[test]
+fn overridden_hosts_connect_to_their_address() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve_http1().await;
+        let url = format!("http://backend.invalid:{}/health", address.port());
+        let options = RequestOptions {
+            resolve: vec![
+                ResolveOverride::parse(&format!("backend.invalid:{}", address.port()), "127.0.0.1")
+                    .unwrap(),
+            ],
+            ..RequestOptions::default()
+        };
+
+        let response = http_client::send(request(&url), options.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(&response.body[..], b"ok");
+        assert_eq!(response.connection.remote_addr, address);
+        assert!(
+            response.timing.dns.is_none(),
+            "overridden hosts aren't resolved"
+        );
+
+        // The connection is pooled along with the address it went to.
+        let again = http_client::send(request(&url), options).await.unwrap();
+        assert!(again.connection.reused);
+    });
+}
+
+#[test]
+fn resolve_overrides_are_parsed() {
+    let with_port = ResolveOverride::parse(" API.example.com:8443 ", "10.0.0.5").unwrap();
+    assert_eq!(with_port.host, "api.example.com");
+    assert_eq!(with_port.port, Some(8443));
+    assert_eq!(with_port.address, IpAddr::V4(Ipv4Addr::new(10, 0, 0, 5)));
+
+    let any_port = ResolveOverride::parse("api.example.com", "[::1]").unwrap();
+    assert_eq!(any_port.port, None);
+    assert_eq!(any_port.address, IpAddr::V6(Ipv6Addr::LOCALHOST));
+
+    assert!(ResolveOverride::parse("api.example.com", "backend").is_err());
+    assert!(ResolveOverride::parse("api.example.com:https", "10.0.0.5").is_err());
+    assert!(ResolveOverride::parse("", "10.0.0.5").is_err());
+}
@@ -0,0 +1,278 @@
+mod common;
+
+use std::{net::SocketAddr, time::Duration};
+
+use bytes::Bytes;
+use futures::StreamExt;
+use http_client::{
+    CloseFrame, HttpBody, HttpRequest, RequestOptions, WebSocketEvent, WebSocketMessage,
+    http::Method, url::Url,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+use crate::common::runtime;
+
+/// Accepts a single WebSocket connection, answering the handshake with the
+/// first subprotocol offered, then hands it to `handler`.
+async fn serve<F, Fut>(handler: F) -> SocketAddr
+where
+    F: FnOnce(TcpStream) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buffer = [0; 1024];
+        while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+            let read = socket.read(&mut buffer).await.unwrap();
+            request.extend_from_slice(&buffer[..read]);
+        }
+
+        let request = String::from_utf8(request).unwrap();
+        let header = |name: &str| {
+            request.lines().find_map(|line| {
+                let (header, value) = line.split_once(':')?;
+                header
+                    .eq_ignore_ascii_case(name)
+                    .then(|| value.trim().to_string())
+            })
+        };
+        assert_eq!(header("upgrade").as_deref(), Some("websocket"));
+        assert_eq!(header("x-token").as_deref(), Some("secret"));
+
+        let mut response = format!(
+            "HTTP/1.1 101 Switching Protocols\r\nupgrade: websocket\r\nconnection: upgrade\r\n\
+             sec-websocket-accept: {}\r\n",
+            http_client::websocket_accept(&header("sec-websocket-key").unwrap())
+        );
+        if let Some(protocols) = header("sec-websocket-protocol") {
+            let protocol = protocols.split(',').next().unwrap().trim();
+            response.push_str(&format!("sec-websocket-protocol: {protocol}\r\n"));
+        }
+        response.push_str("\r\n");
+        socket.write_all(response.as_bytes()).await.unwrap();
+
+        handler(socket).await;
+    });
+
+    address
+}
+
+/// Reads a frame from the client, which must be masked.
+async fn read_frame(socket: &mut TcpStream) -> (bool, u8, Vec<u8>) {
+    let mut head = [0; 2];
+    socket.read_exact(&mut head).await.unwrap();
+    assert!(head[1] & 0x80 != 0, "client frames are masked");
+
+    let len = match head[1] & 0x7f {
+        126 => socket.read_u16().await.unwrap() as usize,
+        127 => socket.read_u64().await.unwrap() as usize,
+        len => len as usize,
+    };
+    let mut mask = [0; 4];
+    socket.read_exact(&mut mask).await.unwrap();
+    let mut payload = vec![0; len];
+    socket.read_exact(&mut payload).await.unwrap();
+    for (index, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[index % 4];
+    }
+
+    (head[0] & 0x80 != 0, head[0] & 0x0f, payload)
+}
+
+async fn write_frame(socket: &mut TcpStream, fin: bool, opcode: u8, payload: &[u8]) {
+    let mut frame = vec![if fin { 0x80 } else { 0 } | opcode];
+    if payload.len() < 126 {
+        frame.push(payload.len() as u8);
+    } else {
+        frame.push(126);
+        frame.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+    }
+    frame.extend_from_slice(payload);
+
+    socket.write_all(&frame).await.unwrap();
+}
+
+fn request(address: SocketAddr) -> HttpRequest {
+    HttpRequest {
+        method: Method::GET,
+        url: Url::parse(&format!("ws://{address}/socket")).unwrap(),
+        headers: vec![("X-Token".into(), "secret".into())],
+        body: HttpBody::default(),
+    }
+}
+
+#[test]
+fn accept_keys_follow_rfc_6455() {
+    assert_eq!(
+        http_client::websocket_accept("dGhlIHNhbXBsZSBub25jZQ=="),
+        "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+    );
+}
+
+#[test]
+fn messages_go_both_ways_until_the_client_closes() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = serve(|mut socket| async move {
+            // A ping, then a text message in two fragments with a ping
+            // between them.
+            write_frame(&mut socket, true, 0x9, b"are you there").await;
+            write_frame(&mut socket, false, 0x1, "hé".as_bytes()).await;
+            write_frame(&mut socket, true, 0x9, b"").await;
+            write_frame(&mut socket, true, 0x0, b"llo").await;
+
+            loop {
+                let (fin, opcode, payload) = read_frame(&mut socket).await;
+                assert!(fin);
+
+                match opcode {
+                    // Echoes data messages.
+                    0x1 | 0x2 => write_frame(&mut socket, true, opcode, &payload).await,
+                    0x8 => {
+                        write_frame(&mut socket, true, 0x8, &payload).await;
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+        })
+        .await;
+
+        let (done, sender, events) = http_client::connect_websocket(
+            request(address),
+            vec!["chat".into(), "superchat".into()],
+            RequestOptions::default(),
+        );
+        sender.send(WebSocketMessage::Text("hello".into())).unwrap();
+        sender
+            .send(WebSocketMessage::Binary(Bytes::from_static(&[
+                0, 1, 2, 255,
+            ])))
+            .unwrap();
+
+        // Waits for the echoes before closing.
+        let mut events = events.fuse();
+        let mut log = Vec::new();
+        while let Some(event) = events.next().await {
+            let echoed = matches!(
+                &event,
+                WebSocketEvent::Received {
+                    message: WebSocketMessage::Binary(_),
+                    ..
+                }
+            );
+            log.push(event);
+            if echoed {
+                break;
+            }
+        }
+        let close = CloseFrame {
+            code: 1000,
+            reason: "done".into(),
+        };
+        sender
+            .send(WebSocketMessage::Close(Some(close.clone())))
+            .unwrap();
+
+        let closed = tokio::time::timeout(Duration::from_secs(5), done)
+            .await
+            .unwrap()
+            .unwrap();
+        log.extend(events.collect::<Vec<_>>().await);
+
+        assert_eq!(closed, Some(close.clone()));
+        let WebSocketEvent::Open { protocol, .. } = &log[0] else {
+            panic!("expected the connection to open first, got {log:?}");
+        };
+        assert_eq!(protocol.as_deref(), Some("chat"));
+
+        let received = log
+            .iter()
+            .filter_map(|event| match event {
+                WebSocketEvent::Received { message, .. } => Some(message.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert!(
+            received.contains(&WebSocketMessage::Ping(Bytes::from_static(
+                b"are you there"
+            )))
+        );
+        assert!(received.contains(&WebSocketMessage::Text("héllo".into())));
+        assert!(received.contains(&WebSocketMessage::Text("hello".into())));
+        assert!(
+            received.contains(&WebSocketMessage::Binary(Bytes::from_static(&[
+                0, 1, 2, 255
+            ])))
+        );
+        assert_eq!(
+            received.last(),
+            Some(&WebSocketMessage::Close(Some(close.clone())))
+        );
+
+        let sent = log
+            .iter()
+            .filter_map(|event| match event {
+                WebSocketEvent::Sent { message, .. } => Some(message.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert!(sent.contains(&WebSocketMessage::Pong(Bytes::from_static(
+            b"are you there"
+        ))));
+        assert_eq!(sent.last(), Some(&WebSocketMessage::Close(Some(close))));
+    });
+}
+
+#[test]
+fn closes_from_the_server_are_answered() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let (answered, answer) = tokio::sync::oneshot::channel();
+        let address = serve(|mut socket| async move {
+            let mut payload = 1001u16.to_be_bytes().to_vec();
+            payload.extend_from_slice(b"restarting");
+            write_frame(&mut socket, true, 0x8, &payload).await;
+
+            answered.send(read_frame(&mut socket).await).unwrap();
+        })
+        .await;
+
+        let (done, _sender, _events) =
+            http_client::connect_websocket(request(address), Vec::new(), RequestOptions::default());
+        let closed = done.await.unwrap().unwrap();
+
+        assert_eq!(closed.code, 1001);
+        assert_eq!(closed.to_string(), "1001 Going Away: restarting");
+
+        let (_, opcode, payload) = answer.await.unwrap();
+        assert_eq!(opcode, 0x8);
+        assert_eq!(&payload[..2], 1001u16.to_be_bytes());
+    });
+}
+
+#[test]
+fn refused_handshakes_fail() {
+    let runtime = runtime();
+
+    runtime.block_on(async {
+        let address = common::serve_http1().await;
+        let (done, _, _) =
+            http_client::connect_websocket(request(address), Vec::new(), RequestOptions::default());
+        let error = done.await.unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "the server answered 200 OK instead of switching to WebSocket"
+        );
+    });
+}
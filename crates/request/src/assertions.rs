@@ -0,0 +1,351 @@
+use collection::assertions::{Assertion, Check, Expectation};
+use gpui::{
+    App, AppContext, Context, Entity, EventEmitter, InteractiveElement, IntoElement, ParentElement,
+    Render, Styled, Subscription, Window, div, prelude::FluentBuilder,
+};
+use serde_json::Value;
+use theme::ActiveTheme;
+use ui::{
+    components::{
+        button::{Button, ButtonSize},
+        checkbox::Checkbox,
+        dropdown::{Dropdown, DropdownEvent},
+        icon::IconName,
+        icon_button::IconButton,
+        input::{InputEvent, TextInput},
+    },
+    traits::{clickable::Clickable, styled_ext::StyledExt},
+};
+
+/// The choices of the kind dropdown of an assertion, in order.
+const KINDS: [&str; 5] = [
+    "Status",
+    "Header",
+    "JSONPath",
+    "Response Time (ms)",
+    "JSON Schema",
+];
+const STATUS: usize = 0;
+const HEADER: usize = 1;
+const JSON_PATH: usize = 2;
+const RESPONSE_TIME: usize = 3;
+const JSON_SCHEMA: usize = 4;
+
+/// The choices of the comparison dropdown of a JSONPath assertion, in
+/// order.
+const COMPARISONS: [&str; 3] = ["Equals", "Contains", "Matches"];
+const EQUALS: usize = 0;
+const CONTAINS: usize = 1;
+const MATCHES: usize = 2;
+
+pub enum AssertionsEditorEvent {
+    Changed,
+}
+
+struct Row {
+    id: usize,
+    enabled: bool,
+    kind: Entity<Dropdown>,
+    /// The name of the header, or the JSONPath expression.
+    target: Entity<TextInput>,
+    comparison: Entity<Dropdown>,
+    /// The status, regular expression, value, time or schema expected.
+    expected: Entity<TextInput>,
+    _subscriptions: [Subscription; 4],
+}
+
+impl Row {
+    fn kind(&self, cx: &App) -> usize {
+        self.kind.read(cx).selected_index().unwrap_or(STATUS)
+    }
+
+    fn assertion(&self, cx: &App) -> Assertion {
+        let target = self.target.read(cx).text().trim().to_string();
+        let expected = self.expected.read(cx).text().to_string();
+
+        let check = match self.kind(cx) {
+            HEADER => Check::Header {
+                name: target,
+                matches: expected,
+            },
+            JSON_PATH => Check::JsonPath {
+                path: target,
+                expect: match self.comparison.read(cx).selected_index() {
+                    Some(CONTAINS) => Expectation::Contains(json_value(&expected)),
+                    Some(MATCHES) => Expectation::Matches(expected),
+                    _ => Expectation::Equals(json_value(&expected)),
+                },
+            },
+            RESPONSE_TIME => Check::ResponseTime {
+                below_ms: expected.trim().parse().unwrap_or_default(),
+            },
+            JSON_SCHEMA => Check::JsonSchema { schema: expected },
+            _ => Check::Status {
+                equals: expected.trim().parse().unwrap_or_default(),
+            },
+        };
+
+        Assertion {
+            check,
+            enabled: self.enabled,
+        }
+    }
+}
+
+/// Parses what was typed as JSON, falling back to a string so that text
+/// needs no quotes.
+fn json_value(text: &str) -> Value {
+    serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.to_string()))
+}
+
+/// A JSON value as typed, strings without their quotes unless they would
+/// read back as something else.
+fn json_text(value: &Value) -> String {
+    match value {
+        Value::String(text) if serde_json::from_str::<Value>(text).is_err() => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// An editable list of the checks made on the response of a request after
+/// every send.
+pub struct AssertionsEditor {
+    rows: Vec<Row>,
+    next_row_id: usize,
+}
+
+impl AssertionsEditor {
+    pub fn new() -> Self {
+        Self {
+            rows: Vec::new(),
+            next_row_id: 0,
+        }
+    }
+
+    pub fn assertions(&self, cx: &App) -> Vec<Assertion> {
+        self.rows.iter().map(|row| row.assertion(cx)).collect()
+    }
+
+    /// Replaces every assertion without emitting
+    /// [`AssertionsEditorEvent::Changed`].
+    pub fn set_assertions(&mut self, assertions: &[Assertion], cx: &mut Context<Self>) {
+        self.rows.clear();
+
+        for assertion in assertions {
+            self.push_row(assertion, cx);
+        }
+
+        cx.notify();
+    }
+
+    fn push_row(&mut self, assertion: &Assertion, cx: &mut Context<Self>) {
+        let id = self.next_row_id;
+        let (kind_index, target, comparison_index, expected) = match &assertion.check {
+            Check::Status { equals } => (STATUS, String::new(), EQUALS, equals.to_string()),
+            Check::Header { name, matches } => (HEADER, name.clone(), MATCHES, matches.clone()),
+            Check::JsonPath { path, expect } => {
+                let (comparison, expected) = match expect {
+                    Expectation::Equals(value) => (EQUALS, json_text(value)),
+                    Expectation::Contains(value) => (CONTAINS, json_text(value)),
+                    Expectation::Matches(pattern) => (MATCHES, pattern.clone()),
+                };
+                (JSON_PATH, path.clone(), comparison, expected)
+            }
+            Check::ResponseTime { below_ms } => {
+                (RESPONSE_TIME, String::new(), EQUALS, below_ms.to_string())
+            }
+            Check::JsonSchema { schema } => (JSON_SCHEMA, String::new(), EQUALS, schema.clone()),
+        };
+
+        let kind = cx.new(|cx| {
+            let mut dropdown = Dropdown::new(("assertion-kind", id), KINDS, cx);
+            dropdown.set_selected_index(Some(kind_index), cx);
+            dropdown
+        });
+        let comparison = cx.new(|cx| {
+            let mut dropdown = Dropdown::new(("assertion-comparison", id), COMPARISONS, cx);
+            dropdown.set_selected_index(Some(comparison_index), cx);
+            dropdown
+        });
+        let target = cx.new(|cx| {
+            TextInput::new(cx)
+                .placeholder("Content-Type or $.items[0].id")
+                .default_text(target)
+        });
+        let expected = cx.new(|cx| {
+            TextInput::new(cx)
+                .placeholder("Expected")
+                .default_text(expected)
+        });
+
+        let subscriptions = [
+            cx.subscribe(&kind, |this, _, event, cx| {
+                let DropdownEvent::Changed(_) = event;
+                this.changed(cx);
+            }),
+            cx.subscribe(&comparison, |this, _, event, cx| {
+                let DropdownEvent::Changed(_) = event;
+                this.changed(cx);
+            }),
+            cx.subscribe(&target, Self::handle_input_event),
+            cx.subscribe(&expected, Self::handle_input_event),
+        ];
+
+        self.rows.push(Row {
+            id,
+            enabled: assertion.enabled,
+            kind,
+            target,
+            comparison,
+            expected,
+            _subscriptions: subscriptions,
+        });
+        self.next_row_id += 1;
+    }
+
+    fn add_assertion(&mut self, cx: &mut Context<Self>) {
+        self.push_row(
+            &Assertion {
+                check: Check::Status { equals: 200 },
+                enabled: true,
+            },
+            cx,
+        );
+        self.changed(cx);
+    }
+
+    fn handle_input_event(
+        &mut self,
+        _: Entity<TextInput>,
+        event: &InputEvent,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputEvent::Changed = event {
+            self.changed(cx);
+        }
+    }
+
+    fn changed(&mut self, cx: &mut Context<Self>) {
+        cx.emit(AssertionsEditorEvent::Changed);
+        cx.notify();
+    }
+
+    fn toggle_row(&mut self, id: usize, enabled: bool, cx: &mut Context<Self>) {
+        if let Some(row) = self.rows.iter_mut().find(|row| row.id == id) {
+            row.enabled = enabled;
+            self.changed(cx);
+        }
+    }
+
+    fn remove_row(&mut self, id: usize, cx: &mut Context<Self>) {
+        self.rows.retain(|row| row.id != id);
+        self.changed(cx);
+    }
+}
+
+impl EventEmitter<AssertionsEditorEvent> for AssertionsEditor {}
+
+impl Render for AssertionsEditor {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = *cx.theme().colors();
+
+        div()
+            .v_flex()
+            .w_full()
+            .gap_2()
+            .child(div().text_xs().text_color(colors.muted_foreground).child(
+                "Checked on the response after every send. JSONPath values are JSON, or text \
+                 without quotes; headers and Matches take regular expressions; a JSON schema is \
+                 written inline or as the path of its file.",
+            ))
+            .child(
+                div()
+                    .v_flex()
+                    .w_full()
+                    .rounded_sm()
+                    .border_1()
+                    .border_color(colors.table_row_border)
+                    .bg(colors.table)
+                    .text_sm()
+                    .child(
+                        div()
+                            .h_flex()
+                            .gap_2()
+                            .px_2()
+                            .py_1()
+                            .bg(colors.table_head)
+                            .text_color(colors.table_head_foreground)
+                            .child(div().w_4())
+                            .child(div().w_40().child("Check"))
+                            .child(div().flex_1().child("Header or JSONPath"))
+                            .child(div().w_24().child("Comparison"))
+                            .child(div().flex_1().child("Expected"))
+                            .child(div().w_6()),
+                    )
+                    .children(self.rows.iter().enumerate().map(|(index, row)| {
+                        let id = row.id;
+                        let kind = row.kind(cx);
+                        let comparison = match kind {
+                            JSON_PATH => Some(row.comparison.clone().into_any_element()),
+                            HEADER => Some("Matches".into_any_element()),
+                            STATUS => Some("Equals".into_any_element()),
+                            RESPONSE_TIME => Some("Below".into_any_element()),
+                            _ => None,
+                        };
+
+                        div()
+                            .id(id)
+                            .h_flex()
+                            .gap_2()
+                            .px_2()
+                            .py_1()
+                            .border_t_1()
+                            .border_color(colors.table_row_border)
+                            .when(index % 2 == 1, |this| this.bg(colors.table_even))
+                            .hover(|this| this.bg(colors.table_hover))
+                            .child(div().w_4().child(
+                                Checkbox::new(("enabled", id), row.enabled).on_toggle(cx.listener(
+                                    move |this, enabled, _, cx| this.toggle_row(id, *enabled, cx),
+                                )),
+                            ))
+                            .child(div().w_40().child(row.kind.clone()))
+                            .child(
+                                div()
+                                    .flex_1()
+                                    .when(!row.enabled, |this| this.opacity(0.5))
+                                    .when(matches!(kind, HEADER | JSON_PATH), |this| {
+                                        this.child(row.target.clone())
+                                    }),
+                            )
+                            .child(
+                                div()
+                                    .w_24()
+                                    .text_color(colors.muted_foreground)
+                                    .children(comparison),
+                            )
+                            .child(
+                                div()
+                                    .flex_1()
+                                    .when(!row.enabled, |this| this.opacity(0.5))
+                                    .child(row.expected.clone()),
+                            )
+                            .child(
+                                div().w_6().child(
+                                    IconButton::new(("remove", id), IconName::Close)
+                                        .tooltip("Remove")
+                                        .on_click(cx.listener(move |this, _, _, cx| {
+                                            this.remove_row(id, cx)
+                                        })),
+                                ),
+                            )
+                    })),
+            )
+            .child(
+                div().h_flex().child(
+                    Button::new("add-assertion", "Add Assertion")
+                        .size(ButtonSize::Compact)
+                        .on_click(cx.listener(|this, _, _, cx| this.add_assertion(cx))),
+                ),
+            )
+    }
+}
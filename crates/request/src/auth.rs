@@ -0,0 +1,803 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use collection::auth::{ApiKeyLocation, Auth, AwsCredentialSource, OAuth2Config, OAuthGrant};
+use gpui::{
+    App, AppContext, ClipboardItem, Context, Div, Entity, EventEmitter, IntoElement, ParentElement,
+    Render, SharedString, Styled, Subscription, Window, div, prelude::FluentBuilder,
+};
+use http_client::{HmacAlgorithm, HmacSigner, OAuthToken, SignatureEncoding, decode_jwt};
+use theme::ActiveTheme;
+use ui::{
+    components::{
+        button::{Button, ButtonSize, ButtonStyle},
+        checkbox::Checkbox,
+        dropdown::{Dropdown, DropdownEvent},
+        icon::IconName,
+        icon_button::IconButton,
+        input::{InputEvent, TextInput},
+    },
+    traits::{clickable::Clickable, disableable::Disableable, styled_ext::StyledExt},
+};
+
+/// The choices of the scheme dropdown, in order.
+const SCHEMES: [&str; 9] = [
+    "Inherit",
+    "No Auth",
+    "Basic",
+    "Bearer Token",
+    "Digest",
+    "API Key",
+    "OAuth 2.0",
+    "AWS Signature",
+    "HMAC Signature",
+];
+const INHERIT: usize = 0;
+const NO_AUTH: usize = 1;
+const BASIC: usize = 2;
+const BEARER: usize = 3;
+const DIGEST: usize = 4;
+const API_KEY: usize = 5;
+const OAUTH2: usize = 6;
+const AWS_SIGV4: usize = 7;
+const HMAC: usize = 8;
+
+/// The choices of the API key location dropdown, in order.
+const LOCATIONS: [ApiKeyLocation; 2] = [ApiKeyLocation::Header, ApiKeyLocation::Query];
+
+/// The choices of the OAuth 2.0 grant dropdown, in order.
+const GRANTS: [(OAuthGrant, &str); 3] = [
+    (OAuthGrant::ClientCredentials, "Client Credentials"),
+    (OAuthGrant::AuthorizationCode, "Authorization Code (PKCE)"),
+    (OAuthGrant::DeviceCode, "Device Code"),
+];
+
+/// The choices of the AWS credentials dropdown, in order.
+const CREDENTIAL_SOURCES: [&str; 3] = ["Environment Variables", "Profile", "Access Keys"];
+const ENVIRONMENT_CREDENTIALS: usize = 0;
+const PROFILE_CREDENTIALS: usize = 1;
+const KEY_CREDENTIALS: usize = 2;
+
+/// The choices of the HMAC signature encoding dropdown, in order.
+const ENCODINGS: [(SignatureEncoding, &str); 2] = [
+    (SignatureEncoding::Hex, "Hex"),
+    (SignatureEncoding::Base64, "Base64"),
+];
+
+/// How much of the access token the inspector shows.
+const TOKEN_PREVIEW_LENGTH: usize = 48;
+
+pub enum AuthEditorEvent {
+    Changed,
+    /// Enter was pressed in one of the fields.
+    Submit,
+    /// Get New Token was clicked.
+    GetToken,
+    /// Clear Token was clicked.
+    ClearToken,
+}
+
+/// What the editor shows of the access token of the OAuth 2.0 client in
+/// effect, for owners that get and cache tokens.
+#[derive(Default)]
+pub struct TokenState {
+    pub token: Option<OAuthToken>,
+    /// What getting a token waits for, or why it failed.
+    pub status: Option<SharedString>,
+    /// Whether a token is being got.
+    pub pending: bool,
+}
+
+/// Edits the authentication of a request or folder, which inherits that of
+/// its parent folders or collection when unset.
+pub struct AuthEditor {
+    scheme_dropdown: Entity<Dropdown>,
+    location_dropdown: Entity<Dropdown>,
+    /// Shared by Basic and Digest, so that switching between them keeps
+    /// the credentials.
+    username_input: Entity<TextInput>,
+    password_input: Entity<TextInput>,
+    token_input: Entity<TextInput>,
+    key_name_input: Entity<TextInput>,
+    key_value_input: Entity<TextInput>,
+    grant_dropdown: Entity<Dropdown>,
+    token_url_input: Entity<TextInput>,
+    authorization_url_input: Entity<TextInput>,
+    device_authorization_url_input: Entity<TextInput>,
+    client_id_input: Entity<TextInput>,
+    client_secret_input: Entity<TextInput>,
+    scope_input: Entity<TextInput>,
+    redirect_port_input: Entity<TextInput>,
+    service_input: Entity<TextInput>,
+    region_input: Entity<TextInput>,
+    credential_source_dropdown: Entity<Dropdown>,
+    profile_input: Entity<TextInput>,
+    access_key_id_input: Entity<TextInput>,
+    secret_access_key_input: Entity<TextInput>,
+    session_token_input: Entity<TextInput>,
+    unsigned_payload: bool,
+    algorithm_dropdown: Entity<Dropdown>,
+    encoding_dropdown: Entity<Dropdown>,
+    hmac_key_input: Entity<TextInput>,
+    /// The message template, with line breaks written `\n`.
+    message_input: Entity<TextInput>,
+    signature_header_input: Entity<TextInput>,
+    signature_value_input: Entity<TextInput>,
+    timestamp_header_input: Entity<TextInput>,
+    /// `None` hides the token, and the buttons getting one.
+    token_state: Option<TokenState>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl AuthEditor {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let scheme_dropdown = cx.new(|cx| {
+            let mut dropdown = Dropdown::new("auth-scheme", SCHEMES, cx);
+            dropdown.set_selected_index(Some(INHERIT), cx);
+            dropdown
+        });
+        let location_dropdown = cx.new(|cx| {
+            let mut dropdown = Dropdown::new("api-key-location", ["Header", "Query"], cx);
+            dropdown.set_selected_index(Some(0), cx);
+            dropdown
+        });
+        let username_input = cx.new(|cx| TextInput::new(cx).placeholder("Username"));
+        let password_input = cx.new(|cx| TextInput::new(cx).placeholder("{{password}}"));
+        let token_input = cx.new(|cx| TextInput::new(cx).placeholder("{{token}}"));
+        let key_name_input = cx.new(|cx| TextInput::new(cx).placeholder("X-API-Key"));
+        let key_value_input = cx.new(|cx| TextInput::new(cx).placeholder("{{api_key}}"));
+        let grant_dropdown = cx.new(|cx| {
+            let mut dropdown =
+                Dropdown::new("oauth-grant", GRANTS.iter().map(|(_, name)| *name), cx);
+            dropdown.set_selected_index(Some(0), cx);
+            dropdown
+        });
+        let token_url_input =
+            cx.new(|cx| TextInput::new(cx).placeholder("https://auth.example.com/oauth/token"));
+        let authorization_url_input =
+            cx.new(|cx| TextInput::new(cx).placeholder("https://auth.example.com/oauth/authorize"));
+        let device_authorization_url_input =
+            cx.new(|cx| TextInput::new(cx).placeholder("https://auth.example.com/oauth/device"));
+        let client_id_input = cx.new(|cx| TextInput::new(cx).placeholder("{{client_id}}"));
+        let client_secret_input = cx.new(|cx| {
+            TextInput::new(cx).placeholder("{{client_secret}}, empty for public clients")
+        });
+        let scope_input = cx.new(|cx| TextInput::new(cx).placeholder("openid profile"));
+        let redirect_port_input = cx.new(|cx| TextInput::new(cx).placeholder("Any free port"));
+        let service_input = cx.new(|cx| TextInput::new(cx).placeholder("execute-api"));
+        let region_input = cx.new(|cx| TextInput::new(cx).placeholder("us-east-1"));
+        let credential_source_dropdown = cx.new(|cx| {
+            let mut dropdown = Dropdown::new("aws-credentials", CREDENTIAL_SOURCES, cx);
+            dropdown.set_selected_index(Some(ENVIRONMENT_CREDENTIALS), cx);
+            dropdown
+        });
+        let profile_input = cx.new(|cx| TextInput::new(cx).placeholder("default"));
+        let access_key_id_input =
+            cx.new(|cx| TextInput::new(cx).placeholder("{{aws_access_key_id}}"));
+        let secret_access_key_input =
+            cx.new(|cx| TextInput::new(cx).placeholder("{{aws_secret_access_key}}"));
+        let session_token_input = cx.new(|cx| {
+            TextInput::new(cx).placeholder("{{aws_session_token}}, for temporary credentials")
+        });
+        let algorithm_dropdown = cx.new(|cx| {
+            let mut dropdown = Dropdown::new(
+                "hmac-algorithm",
+                HmacAlgorithm::ALL.iter().map(HmacAlgorithm::as_str),
+                cx,
+            );
+            let default = HmacAlgorithm::ALL
+                .iter()
+                .position(|algorithm| *algorithm == HmacAlgorithm::default());
+            dropdown.set_selected_index(default, cx);
+            dropdown
+        });
+        let encoding_dropdown = cx.new(|cx| {
+            let mut dropdown =
+                Dropdown::new("hmac-encoding", ENCODINGS.iter().map(|(_, name)| *name), cx);
+            dropdown.set_selected_index(Some(0), cx);
+            dropdown
+        });
+        let hmac_key_input = cx.new(|cx| TextInput::new(cx).placeholder("{{signing_key}}"));
+        let message_input = cx.new(|cx| {
+            let mut input = TextInput::new(cx).placeholder("{method}\\n{path}");
+            input.set_text(escape_line_breaks(&HmacSigner::default().message), cx);
+            input
+        });
+        let signature_header_input = cx.new(|cx| {
+            let mut input = TextInput::new(cx).placeholder("X-Signature");
+            input.set_text(HmacSigner::default().header, cx);
+            input
+        });
+        let signature_value_input = cx.new(|cx| {
+            let mut input = TextInput::new(cx).placeholder("{signature}");
+            input.set_text(HmacSigner::default().value, cx);
+            input
+        });
+        let timestamp_header_input = cx.new(|cx| TextInput::new(cx).placeholder("X-Timestamp"));
+
+        let mut subscriptions = Vec::new();
+        for dropdown in [
+            &scheme_dropdown,
+            &location_dropdown,
+            &grant_dropdown,
+            &credential_source_dropdown,
+            &algorithm_dropdown,
+            &encoding_dropdown,
+        ] {
+            subscriptions.push(cx.subscribe(dropdown, |_, _, event, cx| {
+                let DropdownEvent::Changed(_) = event;
+                cx.emit(AuthEditorEvent::Changed);
+                cx.notify();
+            }));
+        }
+        for input in [
+            &username_input,
+            &password_input,
+            &token_input,
+            &key_name_input,
+            &key_value_input,
+            &token_url_input,
+            &authorization_url_input,
+            &device_authorization_url_input,
+            &client_id_input,
+            &client_secret_input,
+            &scope_input,
+            &redirect_port_input,
+            &service_input,
+            &region_input,
+            &profile_input,
+            &access_key_id_input,
+            &secret_access_key_input,
+            &session_token_input,
+            &hmac_key_input,
+            &message_input,
+            &signature_header_input,
+            &signature_value_input,
+            &timestamp_header_input,
+        ] {
+            subscriptions.push(cx.subscribe(input, |_, _, event, cx| match event {
+                InputEvent::Changed => cx.emit(AuthEditorEvent::Changed),
+                InputEvent::PressEnter => cx.emit(AuthEditorEvent::Submit),
+            }));
+        }
+
+        Self {
+            scheme_dropdown,
+            location_dropdown,
+            username_input,
+            password_input,
+            token_input,
+            key_name_input,
+            key_value_input,
+            grant_dropdown,
+            token_url_input,
+            authorization_url_input,
+            device_authorization_url_input,
+            client_id_input,
+            client_secret_input,
+            scope_input,
+            redirect_port_input,
+            service_input,
+            region_input,
+            credential_source_dropdown,
+            profile_input,
+            access_key_id_input,
+            secret_access_key_input,
+            session_token_input,
+            unsigned_payload: false,
+            algorithm_dropdown,
+            encoding_dropdown,
+            hmac_key_input,
+            message_input,
+            signature_header_input,
+            signature_value_input,
+            timestamp_header_input,
+            token_state: None,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    /// The authentication edited, `None` to inherit it.
+    pub fn auth(&self, cx: &App) -> Option<Auth> {
+        let text = |input: &Entity<TextInput>| input.read(cx).text().to_string();
+
+        match self.scheme(cx) {
+            NO_AUTH => Some(Auth::None),
+            BASIC => Some(Auth::Basic {
+                username: text(&self.username_input),
+                password: text(&self.password_input),
+            }),
+            BEARER => Some(Auth::Bearer {
+                token: text(&self.token_input),
+            }),
+            DIGEST => Some(Auth::Digest {
+                username: text(&self.username_input),
+                password: text(&self.password_input),
+            }),
+            API_KEY => Some(Auth::ApiKey {
+                name: text(&self.key_name_input),
+                value: text(&self.key_value_input),
+                location: self
+                    .location_dropdown
+                    .read(cx)
+                    .selected_index()
+                    .and_then(|index| LOCATIONS.get(index).copied())
+                    .unwrap_or_default(),
+            }),
+            OAUTH2 => Some(Auth::OAuth2(OAuth2Config {
+                grant: self.grant(cx),
+                token_url: text(&self.token_url_input),
+                authorization_url: text(&self.authorization_url_input),
+                device_authorization_url: text(&self.device_authorization_url_input),
+                client_id: text(&self.client_id_input),
+                client_secret: text(&self.client_secret_input),
+                scope: text(&self.scope_input),
+                redirect_port: text(&self.redirect_port_input).trim().parse().ok(),
+            })),
+            AWS_SIGV4 => Some(Auth::AwsSigV4 {
+                service: text(&self.service_input),
+                region: text(&self.region_input),
+                credentials: match self.credential_source_dropdown.read(cx).selected_index() {
+                    Some(PROFILE_CREDENTIALS) => AwsCredentialSource::Profile {
+                        name: text(&self.profile_input),
+                    },
+                    Some(KEY_CREDENTIALS) => AwsCredentialSource::Keys {
+                        access_key_id: text(&self.access_key_id_input),
+                        secret_access_key: text(&self.secret_access_key_input),
+                        session_token: text(&self.session_token_input),
+                    },
+                    _ => AwsCredentialSource::Environment,
+                },
+                unsigned_payload: self.unsigned_payload,
+            }),
+            HMAC => Some(Auth::Hmac(HmacSigner {
+                algorithm: self
+                    .algorithm_dropdown
+                    .read(cx)
+                    .selected_index()
+                    .and_then(|index| HmacAlgorithm::ALL.get(index).copied())
+                    .unwrap_or_default(),
+                key: text(&self.hmac_key_input),
+                message: text(&self.message_input).replace("\\n", "\n"),
+                header: text(&self.signature_header_input),
+                value: text(&self.signature_value_input),
+                encoding: self
+                    .encoding_dropdown
+                    .read(cx)
+                    .selected_index()
+                    .and_then(|index| ENCODINGS.get(index))
+                    .map(|(encoding, _)| *encoding)
+                    .unwrap_or_default(),
+                timestamp_header: Some(text(&self.timestamp_header_input))
+                    .filter(|header| !header.trim().is_empty()),
+            })),
+            _ => None,
+        }
+    }
+
+    /// Replaces the authentication edited without emitting
+    /// [`AuthEditorEvent::Changed`].
+    pub fn set_auth(&mut self, auth: Option<&Auth>, cx: &mut Context<Self>) {
+        let set_text = |input: &Entity<TextInput>, text: &str, cx: &mut Context<Self>| {
+            input.update(cx, |input, cx| input.set_text(text.to_string(), cx));
+        };
+
+        let scheme = match auth {
+            None => INHERIT,
+            Some(Auth::None) => NO_AUTH,
+            Some(Auth::Basic { username, password })
+            | Some(Auth::Digest { username, password }) => {
+                set_text(&self.username_input, username, cx);
+                set_text(&self.password_input, password, cx);
+                if matches!(auth, Some(Auth::Basic { .. })) {
+                    BASIC
+                } else {
+                    DIGEST
+                }
+            }
+            Some(Auth::Bearer { token }) => {
+                set_text(&self.token_input, token, cx);
+                BEARER
+            }
+            Some(Auth::ApiKey {
+                name,
+                value,
+                location,
+            }) => {
+                set_text(&self.key_name_input, name, cx);
+                set_text(&self.key_value_input, value, cx);
+                let location = LOCATIONS.iter().position(|candidate| candidate == location);
+                self.location_dropdown
+                    .update(cx, |dropdown, cx| dropdown.set_selected_index(location, cx));
+                API_KEY
+            }
+            Some(Auth::OAuth2(config)) => {
+                set_text(&self.token_url_input, &config.token_url, cx);
+                set_text(&self.authorization_url_input, &config.authorization_url, cx);
+                set_text(
+                    &self.device_authorization_url_input,
+                    &config.device_authorization_url,
+                    cx,
+                );
+                set_text(&self.client_id_input, &config.client_id, cx);
+                set_text(&self.client_secret_input, &config.client_secret, cx);
+                set_text(&self.scope_input, &config.scope, cx);
+                let port = config
+                    .redirect_port
+                    .map(|port| port.to_string())
+                    .unwrap_or_default();
+                set_text(&self.redirect_port_input, &port, cx);
+                let grant = GRANTS.iter().position(|(grant, _)| *grant == config.grant);
+                self.grant_dropdown
+                    .update(cx, |dropdown, cx| dropdown.set_selected_index(grant, cx));
+                OAUTH2
+            }
+            Some(Auth::AwsSigV4 {
+                service,
+                region,
+                credentials,
+                unsigned_payload,
+            }) => {
+                set_text(&self.service_input, service, cx);
+                set_text(&self.region_input, region, cx);
+                let source = match credentials {
+                    AwsCredentialSource::Environment => ENVIRONMENT_CREDENTIALS,
+                    AwsCredentialSource::Profile { name } => {
+                        set_text(&self.profile_input, name, cx);
+                        PROFILE_CREDENTIALS
+                    }
+                    AwsCredentialSource::Keys {
+                        access_key_id,
+                        secret_access_key,
+                        session_token,
+                    } => {
+                        set_text(&self.access_key_id_input, access_key_id, cx);
+                        set_text(&self.secret_access_key_input, secret_access_key, cx);
+                        set_text(&self.session_token_input, session_token, cx);
+                        KEY_CREDENTIALS
+                    }
+                };
+                self.credential_source_dropdown.update(cx, |dropdown, cx| {
+                    dropdown.set_selected_index(Some(source), cx)
+                });
+                self.unsigned_payload = *unsigned_payload;
+                AWS_SIGV4
+            }
+            Some(Auth::Hmac(signer)) => {
+                set_text(&self.hmac_key_input, &signer.key, cx);
+                set_text(
+                    &self.message_input,
+                    &escape_line_breaks(&signer.message),
+                    cx,
+                );
+                set_text(&self.signature_header_input, &signer.header, cx);
+                set_text(&self.signature_value_input, &signer.value, cx);
+                set_text(
+                    &self.timestamp_header_input,
+                    signer.timestamp_header.as_deref().unwrap_or_default(),
+                    cx,
+                );
+                let algorithm = HmacAlgorithm::ALL
+                    .iter()
+                    .position(|algorithm| *algorithm == signer.algorithm);
+                self.algorithm_dropdown.update(cx, |dropdown, cx| {
+                    dropdown.set_selected_index(algorithm, cx)
+                });
+                let encoding = ENCODINGS
+                    .iter()
+                    .position(|(encoding, _)| *encoding == signer.encoding);
+                self.encoding_dropdown
+                    .update(cx, |dropdown, cx| dropdown.set_selected_index(encoding, cx));
+                HMAC
+            }
+        };
+
+        self.scheme_dropdown.update(cx, |dropdown, cx| {
+            dropdown.set_selected_index(Some(scheme), cx)
+        });
+        cx.notify();
+    }
+
+    /// Shows `state` as the access token of the OAuth 2.0 client in
+    /// effect, or hides the token when `None`.
+    pub fn set_token_state(&mut self, state: Option<TokenState>, cx: &mut Context<Self>) {
+        self.token_state = state;
+        cx.notify();
+    }
+
+    fn grant(&self, cx: &App) -> OAuthGrant {
+        self.grant_dropdown
+            .read(cx)
+            .selected_index()
+            .and_then(|index| GRANTS.get(index))
+            .map(|(grant, _)| *grant)
+            .unwrap_or_default()
+    }
+
+    fn scheme(&self, cx: &App) -> usize {
+        self.scheme_dropdown
+            .read(cx)
+            .selected_index()
+            .unwrap_or(INHERIT)
+    }
+}
+
+impl EventEmitter<AuthEditorEvent> for AuthEditor {}
+
+/// A labelled row of the editor.
+fn field(label: &'static str, input: &Entity<TextInput>) -> Div {
+    div()
+        .h_flex()
+        .gap_2()
+        .child(div().w_24().text_sm().child(label))
+        .child(div().flex_1().child(input.clone()))
+}
+
+/// A labelled row of the editor holding something other than an input.
+fn labelled(label: &'static str, child: impl IntoElement) -> Div {
+    div()
+        .h_flex()
+        .gap_2()
+        .child(div().w_24().text_sm().child(label))
+        .child(child)
+}
+
+/// Writes the line breaks of an HMAC message as `\n`, to edit it on one
+/// line.
+fn escape_line_breaks(message: &str) -> String {
+    message.replace('\n', "\\n")
+}
+
+/// A labelled row of the token inspector.
+fn detail(label: &'static str, value: impl Into<SharedString>) -> Div {
+    div()
+        .h_flex()
+        .gap_2()
+        .text_xs()
+        .child(div().w_24().child(label))
+        .child(div().flex_1().truncate().child(value.into()))
+}
+
+/// When the token expires, relative to now.
+fn describe_expiry(expires_at: u64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let describe = |seconds: u64| match seconds {
+        0..60 => format!("{seconds} s"),
+        60..3600 => format!("{} min", seconds / 60),
+        _ => format!("{} h {} min", seconds / 3600, seconds % 3600 / 60),
+    };
+
+    if expires_at > now {
+        format!("In {}", describe(expires_at - now))
+    } else {
+        format!("{} ago", describe(now - expires_at))
+    }
+}
+
+impl AuthEditor {
+    fn render_oauth2_fields(&self, fields: Div, cx: &App) -> Div {
+        let grant = self.grant(cx);
+
+        fields
+            .child(labelled("Grant", self.grant_dropdown.clone()))
+            .when(grant == OAuthGrant::AuthorizationCode, |this| {
+                this.child(field("Authorize URL", &self.authorization_url_input))
+            })
+            .when(grant == OAuthGrant::DeviceCode, |this| {
+                this.child(field("Device URL", &self.device_authorization_url_input))
+            })
+            .child(field("Token URL", &self.token_url_input))
+            .child(field("Client ID", &self.client_id_input))
+            .child(field("Client Secret", &self.client_secret_input))
+            .child(field("Scope", &self.scope_input))
+            .when(grant == OAuthGrant::AuthorizationCode, |this| {
+                this.child(field("Redirect Port", &self.redirect_port_input))
+            })
+    }
+
+    fn render_aws_fields(&self, fields: Div, cx: &mut Context<Self>) -> Div {
+        let source = self
+            .credential_source_dropdown
+            .read(cx)
+            .selected_index()
+            .unwrap_or(ENVIRONMENT_CREDENTIALS);
+
+        fields
+            .child(field("Service", &self.service_input))
+            .child(field("Region", &self.region_input))
+            .child(labelled(
+                "Credentials",
+                self.credential_source_dropdown.clone(),
+            ))
+            .when(source == PROFILE_CREDENTIALS, |this| {
+                this.child(field("Profile", &self.profile_input))
+            })
+            .when(source == KEY_CREDENTIALS, |this| {
+                this.child(field("Access Key", &self.access_key_id_input))
+                    .child(field("Secret Key", &self.secret_access_key_input))
+                    .child(field("Session Token", &self.session_token_input))
+            })
+            .child(
+                Checkbox::new("unsigned-payload", self.unsigned_payload)
+                    .label("Leave the body unsigned")
+                    .on_toggle(cx.listener(|this, checked, _, cx| {
+                        this.unsigned_payload = *checked;
+                        cx.emit(AuthEditorEvent::Changed);
+                        cx.notify();
+                    })),
+            )
+    }
+
+    fn render_hmac_fields(&self, fields: Div) -> Div {
+        fields
+            .child(labelled("Algorithm", self.algorithm_dropdown.clone()))
+            .child(field("Key", &self.hmac_key_input))
+            .child(field("Message", &self.message_input))
+            .child(field("Header", &self.signature_header_input))
+            .child(field("Value", &self.signature_value_input))
+            .child(labelled("Encoding", self.encoding_dropdown.clone()))
+            .child(field("Timestamp Header", &self.timestamp_header_input))
+    }
+
+    fn render_token(&self, state: &TokenState, cx: &mut Context<Self>) -> Div {
+        let colors = *cx.theme().colors();
+
+        let actions = div()
+            .h_flex()
+            .gap_2()
+            .child(
+                Button::new("get-token", "Get New Token")
+                    .style(ButtonStyle::Primary)
+                    .disabled(state.pending)
+                    .tooltip("Run the Grant Again for a Fresh Access Token")
+                    .on_click(cx.listener(|_, _, _, cx| cx.emit(AuthEditorEvent::GetToken))),
+            )
+            .when(state.token.is_some() || state.pending, |this| {
+                this.child(
+                    Button::new("clear-token", "Clear Token")
+                        .style(ButtonStyle::Ghost)
+                        .tooltip("Forget the Token of this Environment")
+                        .on_click(cx.listener(|_, _, _, cx| cx.emit(AuthEditorEvent::ClearToken))),
+                )
+            })
+            .when_some(state.status.clone(), |this, status| {
+                this.child(
+                    div()
+                        .flex_1()
+                        .text_xs()
+                        .text_color(colors.muted_foreground)
+                        .child(status),
+                )
+            });
+
+        let Some(token) = &state.token else {
+            return div().v_flex().gap_2().child(actions).child(
+                div()
+                    .text_xs()
+                    .text_color(colors.muted_foreground)
+                    .child("No token yet in this environment."),
+            );
+        };
+
+        let preview = if token.access_token.chars().count() > TOKEN_PREVIEW_LENGTH {
+            let preview: String = token
+                .access_token
+                .chars()
+                .take(TOKEN_PREVIEW_LENGTH)
+                .collect();
+            format!("{preview}…")
+        } else {
+            token.access_token.clone()
+        };
+        let access_token = token.access_token.clone();
+
+        let jwts = [
+            ("Access Token", Some(&token.access_token)),
+            ("ID Token", token.id_token.as_ref()),
+        ]
+        .into_iter()
+        .filter_map(|(name, token)| Some((name, decode_jwt(token?)?)))
+        .map(|(name, jwt)| {
+            let pretty =
+                |value: &serde_json::Value| serde_json::to_string_pretty(value).unwrap_or_default();
+
+            div()
+                .v_flex()
+                .gap_1()
+                .text_xs()
+                .child(div().text_color(colors.muted_foreground).child(name))
+                .child(div().child(pretty(&jwt.header)))
+                .child(div().child(pretty(&jwt.claims)))
+        });
+
+        div()
+            .v_flex()
+            .gap_2()
+            .child(actions)
+            .child(
+                div()
+                    .h_flex()
+                    .gap_2()
+                    .text_xs()
+                    .child(div().w_24().child("Access Token"))
+                    .child(div().flex_1().truncate().child(preview))
+                    .child(
+                        IconButton::new("copy-token", IconName::Copy)
+                            .size(ButtonSize::Compact)
+                            .tooltip("Copy the Access Token")
+                            .on_click(move |_, _, cx| {
+                                cx.write_to_clipboard(ClipboardItem::new_string(
+                                    access_token.clone(),
+                                ))
+                            }),
+                    ),
+            )
+            .child(detail("Type", token.token_type.clone()))
+            .child(detail(
+                "Expires",
+                token
+                    .expires_at
+                    .map_or_else(|| "Unknown".to_string(), describe_expiry),
+            ))
+            .when_some(token.scope.clone(), |this, scope| {
+                this.child(detail("Scope", scope))
+            })
+            .child(detail(
+                "Refresh Token",
+                if token.refresh_token.is_some() {
+                    "Renews the access token once it expires"
+                } else {
+                    "None"
+                },
+            ))
+            .children(jwts)
+    }
+}
+
+impl Render for AuthEditor {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = *cx.theme().colors();
+        let scheme = self.scheme(cx);
+        let token = self
+            .token_state
+            .as_ref()
+            .map(|state| self.render_token(state, cx));
+
+        div()
+            .v_flex()
+            .gap_2()
+            .child(labelled("Auth", self.scheme_dropdown.clone()))
+            .when(scheme == BASIC || scheme == DIGEST, |this| {
+                this.child(field("Username", &self.username_input))
+                    .child(field("Password", &self.password_input))
+            })
+            .when(scheme == BEARER, |this| {
+                this.child(field("Token", &self.token_input))
+            })
+            .when(scheme == API_KEY, |this| {
+                this.child(field("Key", &self.key_name_input))
+                    .child(field("Value", &self.key_value_input))
+                    .child(labelled("Add To", self.location_dropdown.clone()))
+            })
+            .when(scheme == OAUTH2, |this| self.render_oauth2_fields(this, cx))
+            .when(scheme == AWS_SIGV4, |this| self.render_aws_fields(this, cx))
+            .when(scheme == HMAC, |this| self.render_hmac_fields(this))
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(colors.muted_foreground)
+                    .child(match scheme {
+                        INHERIT => "Uses the authentication of the closest folder having one, or else that of the collection.",
+                        NO_AUTH => "Sends no credentials.",
+                        DIGEST => "Answers the challenge of the server by sending the request again. Credentials may use {{variables}} of the active environment.",
+                        OAUTH2 => "Tokens are cached per environment and refreshed once expired. Settings may use {{variables}} of the active environment.",
+                        AWS_SIGV4 => "Signs the request as it is sent, after every other header. Settings may use {{variables}} of the active environment.",
+                        HMAC => "Signs the request as it is sent. The message and value may use {method}, {path}, {host}, {timestamp}, {body}, {body_sha256} and {header:Name}, the value also {signature}. Line breaks are written \\n.",
+                        _ => "Credentials may use {{variables}} of the active environment.",
+                    }),
+            )
+            .children(token)
+    }
+}
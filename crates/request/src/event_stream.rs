@@ -0,0 +1,538 @@
+use std::{
+    collections::VecDeque,
+    fs,
+    ops::Range,
+    path::Path,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use editor::{Editor, language::Language};
+use futures::StreamExt as _;
+use gpui::{
+    App, AppContext, Context, Entity, Hsla, InteractiveElement, IntoElement, ParentElement, Render,
+    ScrollStrategy, SharedString, StatefulInteractiveElement, Styled, Subscription, Task,
+    UniformListScrollHandle, Window, div, prelude::FluentBuilder, uniform_list,
+};
+use http_client::{HttpRequest, RequestOptions, SseEvent, SseMessage, http::StatusCode};
+use theme::ActiveTheme;
+use ui::{
+    components::{
+        button::{Button, ButtonSize, ButtonStyle},
+        checkbox::Checkbox,
+        input::{InputEvent, TextInput},
+    },
+    traits::{clickable::Clickable, styled_ext::StyledExt},
+};
+
+/// How many events are kept, the oldest ones being dropped past that.
+const MAX_EVENTS: usize = 10_000;
+/// How many messages of the stream are shown at once, at most.
+const MESSAGE_BATCH: usize = 256;
+/// How long to wait between showing messages of the stream.
+const MESSAGE_INTERVAL: Duration = Duration::from_millis(50);
+
+enum StreamState {
+    Idle,
+    Connecting,
+    Open(StatusCode),
+    Reconnecting {
+        error: Option<SharedString>,
+        retry_in: Duration,
+    },
+    /// Closed from here.
+    Closed,
+    /// Closed by the server answering 204 No Content.
+    Ended,
+    Failed(SharedString),
+}
+
+struct ReceivedEvent {
+    /// The position of the event in the stream, counting from 1.
+    number: usize,
+    event: SseEvent,
+    received_at: SystemTime,
+}
+
+/// The events received on a `text/event-stream` subscription, as they
+/// arrive.
+pub struct EventStreamView {
+    state: StreamState,
+    events: VecDeque<ReceivedEvent>,
+    received: usize,
+    /// The indices in `events` of the events matching the filter.
+    matches: Vec<usize>,
+    /// The number of the event whose data is shown.
+    selected: Option<usize>,
+    /// Whether the list scrolls to new events as they arrive.
+    follow: bool,
+    /// The ID the stream resumes after when connecting again.
+    last_event_id: Option<String>,
+    /// Where the event log is saved to by default.
+    dir: Arc<Path>,
+    save_error: Option<SharedString>,
+    filter_input: Entity<TextInput>,
+    data_editor: Entity<Editor>,
+    scroll_handle: UniformListScrollHandle,
+    _subscription: Subscription,
+    _stream: Option<Task<()>>,
+    _messages: Option<Task<()>>,
+}
+
+impl EventStreamView {
+    pub fn new(dir: Arc<Path>, cx: &mut Context<Self>) -> Self {
+        let filter_input = cx.new(|cx| TextInput::new(cx).placeholder("Filter events"));
+        let subscription = cx.subscribe(&filter_input, |this, _, event, cx| {
+            if let InputEvent::Changed = event {
+                this.update_matches(cx);
+            }
+        });
+
+        Self {
+            state: StreamState::Idle,
+            events: VecDeque::new(),
+            received: 0,
+            matches: Vec::new(),
+            selected: None,
+            follow: true,
+            last_event_id: None,
+            dir,
+            save_error: None,
+            filter_input,
+            data_editor: cx.new(|cx| Editor::new(cx).read_only(true)),
+            scroll_handle: UniformListScrollHandle::new(),
+            _subscription: subscription,
+            _stream: None,
+            _messages: None,
+        }
+    }
+
+    /// Whether the stream is open or being opened.
+    pub fn is_connected(&self) -> bool {
+        matches!(
+            self.state,
+            StreamState::Connecting | StreamState::Open(_) | StreamState::Reconnecting { .. }
+        )
+    }
+
+    /// Opens `request` as an event stream, resuming after the last event
+    /// received before, if any.
+    pub fn connect(
+        &mut self,
+        request: HttpRequest,
+        options: RequestOptions,
+        cx: &mut Context<Self>,
+    ) {
+        let (stream, messages) =
+            http_client::subscribe(request, options, self.last_event_id.clone());
+        self.state = StreamState::Connecting;
+        cx.notify();
+
+        self._messages = Some(cx.spawn(async move |this, cx| {
+            let mut messages = messages.ready_chunks(MESSAGE_BATCH);
+
+            while let Some(messages) = messages.next().await {
+                if this
+                    .update(cx, |this, cx| this.receive(messages, cx))
+                    .is_err()
+                {
+                    break;
+                }
+
+                // Lets events pile up a little rather than redrawing for
+                // every one of them.
+                cx.background_executor().timer(MESSAGE_INTERVAL).await;
+            }
+        }));
+
+        // Replacing the task drops the previous one, which closes the stream
+        // it was waiting for.
+        self._stream = Some(cx.spawn(async move |this, cx| {
+            let result = stream.await;
+
+            this.update(cx, |this, cx| {
+                this.state = match result {
+                    Ok(()) => StreamState::Ended,
+                    Err(error) => StreamState::Failed(format!("{error:#}").into()),
+                };
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    /// Closes the stream, keeping the events received so far.
+    pub fn disconnect(&mut self, cx: &mut Context<Self>) {
+        if self._stream.take().is_none() || !self.is_connected() {
+            return;
+        }
+
+        self.state = StreamState::Closed;
+        cx.notify();
+    }
+
+    pub fn set_error(&mut self, error: impl Into<SharedString>, cx: &mut Context<Self>) {
+        self._stream = None;
+        self.state = StreamState::Failed(error.into());
+        cx.notify();
+    }
+
+    fn receive(&mut self, messages: Vec<SseMessage>, cx: &mut Context<Self>) {
+        for message in messages {
+            match message {
+                SseMessage::Open { status, .. } => {
+                    if self.is_connected() {
+                        self.state = StreamState::Open(status);
+                    }
+                }
+                SseMessage::Reconnecting { error, retry_in } => {
+                    if self.is_connected() {
+                        self.state = StreamState::Reconnecting {
+                            error: error.map(Into::into),
+                            retry_in,
+                        };
+                    }
+                }
+                SseMessage::Event { event, received_at } => {
+                    self.received += 1;
+                    self.last_event_id = event.id.clone();
+                    self.events.push_back(ReceivedEvent {
+                        number: self.received,
+                        event,
+                        received_at,
+                    });
+                }
+            }
+        }
+
+        if self.events.len() > MAX_EVENTS {
+            self.events.drain(..self.events.len() - MAX_EVENTS);
+        }
+        self.update_matches(cx);
+
+        if self.follow && !self.matches.is_empty() {
+            self.scroll_handle
+                .scroll_to_item(self.matches.len() - 1, ScrollStrategy::Bottom);
+        }
+    }
+
+    /// Lists the events whose name or data contains the filter, ignoring
+    /// case.
+    fn update_matches(&mut self, cx: &mut Context<Self>) {
+        let filter = self.filter_input.read(cx).text().to_lowercase();
+
+        self.matches = self
+            .events
+            .iter()
+            .enumerate()
+            .filter(|(_, received)| {
+                filter.is_empty()
+                    || received.event.event.to_lowercase().contains(&filter)
+                    || received.event.data.to_lowercase().contains(&filter)
+            })
+            .map(|(index, _)| index)
+            .collect();
+        cx.notify();
+    }
+
+    fn select(&mut self, number: usize, cx: &mut Context<Self>) {
+        let Some(received) = self
+            .events
+            .iter()
+            .find(|received| received.number == number)
+        else {
+            return;
+        };
+
+        let data = received.event.data.clone();
+        let language = Language::detect(&data);
+        self.data_editor.update(cx, |editor, cx| {
+            editor.set_text(data, cx);
+            editor.set_language(language, cx);
+        });
+        self.selected = Some(number);
+        cx.notify();
+    }
+
+    /// Forgets the events received so far, so that connecting again starts
+    /// the stream over rather than resuming it.
+    fn clear(&mut self, cx: &mut Context<Self>) {
+        self.events.clear();
+        self.matches.clear();
+        self.received = 0;
+        self.selected = None;
+        self.last_event_id = None;
+        self.data_editor
+            .update(cx, |editor, cx| editor.set_text("", cx));
+        cx.notify();
+    }
+
+    /// Saves the events received so far as JSON Lines, one event per line.
+    fn save_log(&mut self, cx: &mut Context<Self>) {
+        let log = self
+            .events
+            .iter()
+            .map(|received| {
+                let event = &received.event;
+                let line = serde_json::json!({
+                    "received_at_ms": received_at_millis(received.received_at),
+                    "event": event.event,
+                    "id": event.id,
+                    "data": event.data,
+                    "retry_ms": event.retry.map(|retry| retry.as_millis() as u64),
+                });
+
+                format!("{line}\n")
+            })
+            .collect::<String>();
+        let path = cx.prompt_for_new_path(&self.dir, Some("events.jsonl"));
+
+        cx.spawn(async move |this, cx| {
+            let Ok(Ok(Some(path))) = path.await else {
+                return;
+            };
+
+            let result = cx
+                .background_spawn(async move {
+                    fs::write(&path, log).map_err(|error| {
+                        format!("Couldn't save the event log to {}: {error}", path.display())
+                    })
+                })
+                .await;
+
+            this.update(cx, |this, cx| {
+                this.save_error = result.err().map(Into::into);
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn status(&self, cx: &App) -> (SharedString, Hsla) {
+        let colors = cx.theme().colors();
+
+        match &self.state {
+            StreamState::Idle => ("Not connected".into(), colors.muted_foreground),
+            StreamState::Connecting => ("Connecting…".into(), colors.muted_foreground),
+            StreamState::Open(status) => (format!("Open, {status}").into(), colors.success),
+            StreamState::Reconnecting { error, retry_in } => {
+                let reason = match error {
+                    Some(error) => format!("Lost the stream: {error}"),
+                    None => "The stream ended".to_string(),
+                };
+
+                (
+                    format!("{reason}, reconnecting in {:.1} s", retry_in.as_secs_f64()).into(),
+                    colors.warning,
+                )
+            }
+            StreamState::Closed => ("Disconnected".into(), colors.muted_foreground),
+            StreamState::Ended => (
+                "Closed by the server with 204 No Content".into(),
+                colors.muted_foreground,
+            ),
+            StreamState::Failed(error) => (error.clone(), colors.danger),
+        }
+    }
+}
+
+fn received_at_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Formats the time of day of `time` in UTC, to the millisecond.
+pub(crate) fn format_time(time: SystemTime) -> String {
+    let millis = received_at_millis(time) % 86_400_000;
+
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+impl Render for EventStreamView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let (status, status_color) = self.status(cx);
+        let colors = *cx.theme().colors();
+        let shown = if self.matches.len() == self.events.len() {
+            format!("{} events", self.events.len())
+        } else {
+            format!("{} of {} events", self.matches.len(), self.events.len())
+        };
+        let resumes = self
+            .last_event_id
+            .clone()
+            .filter(|_| !self.is_connected() && !self.events.is_empty());
+
+        div()
+            .v_flex()
+            .size_full()
+            .gap_2()
+            .child(
+                div()
+                    .h_flex()
+                    .gap_3()
+                    .text_sm()
+                    .child(div().text_color(status_color).child(status))
+                    .child(div().text_color(colors.muted_foreground).child(shown))
+                    .when(self.received > self.events.len(), |this| {
+                        this.child(div().text_xs().text_color(colors.muted_foreground).child(
+                            format!("Keeping the last {MAX_EVENTS} of {}", self.received),
+                        ))
+                    })
+                    .when_some(resumes, |this, id| {
+                        this.child(
+                            div()
+                                .text_xs()
+                                .text_color(colors.muted_foreground)
+                                .child(format!("Connecting again resumes after event {id}")),
+                        )
+                    })
+                    .child(div().flex_1())
+                    .child(div().w_48().child(self.filter_input.clone()))
+                    .child(
+                        Checkbox::new("follow-events", self.follow)
+                            .label("Follow")
+                            .on_toggle(cx.listener(|this, follow, _, cx| {
+                                this.follow = *follow;
+                                cx.notify();
+                            })),
+                    )
+                    .child(
+                        Button::new("clear-events", "Clear")
+                            .style(ButtonStyle::Ghost)
+                            .size(ButtonSize::Compact)
+                            .tooltip("Forget the events received so far")
+                            .on_click(cx.listener(|this, _, _, cx| this.clear(cx))),
+                    )
+                    .child(
+                        Button::new("save-events", "Save Log")
+                            .style(ButtonStyle::Ghost)
+                            .size(ButtonSize::Compact)
+                            .tooltip("Save the events as JSON Lines")
+                            .on_click(cx.listener(|this, _, _, cx| this.save_log(cx))),
+                    ),
+            )
+            .when_some(self.save_error.clone(), |this, error| {
+                this.child(div().text_xs().text_color(colors.danger).child(error))
+            })
+            .child(
+                div()
+                    .flex_1()
+                    .min_h_0()
+                    .h_flex()
+                    .gap_2()
+                    .child(
+                        div()
+                            .flex_1()
+                            .h_full()
+                            .v_flex()
+                            .rounded_sm()
+                            .border_1()
+                            .border_color(colors.table_row_border)
+                            .bg(colors.table)
+                            .text_xs()
+                            .child(
+                                div()
+                                    .h_flex()
+                                    .gap_2()
+                                    .px_2()
+                                    .py_1()
+                                    .bg(colors.table_head)
+                                    .text_color(colors.table_head_foreground)
+                                    .child(div().w_24().child("Time (UTC)"))
+                                    .child(div().w_24().child("Event"))
+                                    .child(div().w_16().child("ID"))
+                                    .child(div().flex_1().child("Data")),
+                            )
+                            .child(
+                                uniform_list(
+                                    "events",
+                                    self.matches.len(),
+                                    cx.processor(|this, range: Range<usize>, _window, cx| {
+                                        let colors = *cx.theme().colors();
+
+                                        this.matches[range]
+                                            .iter()
+                                            .filter_map(|index| this.events.get(*index))
+                                            .map(|received| {
+                                                let number = received.number;
+                                                let event = &received.event;
+                                                let data = event.data.lines().next().unwrap_or("");
+
+                                                div()
+                                                    .id(number)
+                                                    .h_flex()
+                                                    .gap_2()
+                                                    .px_2()
+                                                    .py_0p5()
+                                                    .border_t_1()
+                                                    .border_color(colors.table_row_border)
+                                                    .when(number % 2 == 0, |this| {
+                                                        this.bg(colors.table_even)
+                                                    })
+                                                    .when(this.selected == Some(number), |this| {
+                                                        this.bg(colors.table_hover)
+                                                    })
+                                                    .hover(|this| this.bg(colors.table_hover))
+                                                    .child(
+                                                        div()
+                                                            .w_24()
+                                                            .text_color(colors.muted_foreground)
+                                                            .child(format_time(
+                                                                received.received_at,
+                                                            )),
+                                                    )
+                                                    .child(
+                                                        div()
+                                                            .w_24()
+                                                            .truncate()
+                                                            .child(event.event.clone()),
+                                                    )
+                                                    .child(
+                                                        div()
+                                                            .w_16()
+                                                            .truncate()
+                                                            .text_color(colors.muted_foreground)
+                                                            .child(
+                                                                event
+                                                                    .id
+                                                                    .clone()
+                                                                    .unwrap_or_default(),
+                                                            ),
+                                                    )
+                                                    .child(
+                                                        div()
+                                                            .flex_1()
+                                                            .truncate()
+                                                            .child(data.to_string()),
+                                                    )
+                                                    .on_click(cx.listener(move |this, _, _, cx| {
+                                                        this.select(number, cx)
+                                                    }))
+                                            })
+                                            .collect()
+                                    }),
+                                )
+                                .flex_1()
+                                .track_scroll(self.scroll_handle.clone()),
+                            ),
+                    )
+                    .when(self.selected.is_some(), |this| {
+                        this.child(
+                            div()
+                                .flex_1()
+                                .h_full()
+                                .min_w_0()
+                                .child(self.data_editor.clone()),
+                        )
+                    }),
+            )
+    }
+}
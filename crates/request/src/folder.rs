@@ -0,0 +1,205 @@
+use std::{path::Path, sync::Arc, time::Duration};
+
+use collection::{auth::FolderConfig, session::Session};
+use gpui::{
+    App, AppContext, Context, Entity, IntoElement, ParentElement, Render, SharedString, Styled,
+    Subscription, Task, Window, div, prelude::FluentBuilder,
+};
+use theme::ActiveTheme;
+use ui::{components::icon::IconName, traits::styled_ext::StyledExt};
+use workspace::item::Item;
+
+use crate::{
+    auth::{AuthEditor, AuthEditorEvent},
+    scripts::{ScriptsEditor, ScriptsEditorEvent},
+    secrets::SecretsEditor,
+};
+
+/// How long editing has to pause before a file is written.
+pub(crate) const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// A center area item editing what a folder of a collection gives the
+/// requests inside it, saved to its `folder.json` as it changes.
+pub struct FolderItem {
+    path: Arc<Path>,
+    config: Option<FolderConfig>,
+    error: Option<SharedString>,
+    /// Why the configuration shouldn't be committed as it is.
+    warning: Option<SharedString>,
+    session: Option<Entity<Session>>,
+    auth_editor: Entity<AuthEditor>,
+    scripts_editor: Entity<ScriptsEditor>,
+    /// Shown for the root folder of a collection, whose settings are those
+    /// of the whole collection.
+    secrets_editor: Option<Entity<SecretsEditor>>,
+    _subscriptions: Vec<Subscription>,
+    _load: Task<()>,
+    _save: Task<()>,
+}
+
+impl FolderItem {
+    pub fn open(path: Arc<Path>, session: Option<Entity<Session>>, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(path, session, cx))
+    }
+
+    fn new(path: Arc<Path>, session: Option<Entity<Session>>, cx: &mut Context<Self>) -> Self {
+        let auth_editor = cx.new(AuthEditor::new);
+        let scripts_editor = cx.new(|cx| {
+            ScriptsEditor::new(
+                "Run for each request inside the folder, before the scripts of its subfolders \
+                 and of the request.",
+                cx,
+            )
+        });
+        let secrets_editor = session
+            .clone()
+            .filter(|session| **session.read(cx).root() == *path)
+            .map(|session| cx.new(|cx| SecretsEditor::new(session, cx)));
+
+        let subscriptions = vec![
+            cx.subscribe(&auth_editor, |this, editor, event, cx| {
+                if let AuthEditorEvent::Changed = event
+                    && let Some(config) = this.config.as_mut()
+                {
+                    config.auth = editor.read(cx).auth(cx);
+                    this.save(cx);
+                }
+            }),
+            cx.subscribe(&scripts_editor, |this, editor, event, cx| {
+                let ScriptsEditorEvent::Changed = event;
+                if let Some(config) = this.config.as_mut() {
+                    config.scripts = editor.read(cx).scripts(cx);
+                    this.save(cx);
+                }
+            }),
+        ];
+
+        let load = cx.background_spawn({
+            let path = path.clone();
+
+            async move { FolderConfig::load(&path) }
+        });
+
+        let load = cx.spawn(async move |this, cx| {
+            let config = load.await;
+
+            this.update(cx, |this, cx| {
+                match config {
+                    Ok(config) => {
+                        this.auth_editor
+                            .update(cx, |editor, cx| editor.set_auth(config.auth.as_ref(), cx));
+                        this.scripts_editor
+                            .update(cx, |editor, cx| editor.set_scripts(&config.scripts, cx));
+                        this.config = Some(config);
+                    }
+                    Err(error) => this.error = Some(format!("{error:#}").into()),
+                }
+
+                cx.notify();
+            })
+            .ok();
+        });
+
+        Self {
+            path,
+            config: None,
+            error: None,
+            warning: None,
+            session,
+            auth_editor,
+            scripts_editor,
+            secrets_editor,
+            _subscriptions: subscriptions,
+            _load: load,
+            _save: Task::ready(()),
+        }
+    }
+
+    /// Writes the configuration once editing pauses. Replacing the task
+    /// drops the previous one, so that only the last edit is written.
+    fn save(&mut self, cx: &mut Context<Self>) {
+        let Some(config) = self.config.clone() else {
+            return;
+        };
+        let path = self.path.clone();
+        self.warning = self.session.as_ref().and_then(|session| {
+            let content = serde_json::to_string(&config).unwrap_or_default();
+            leak_warning(&session.read(cx).leaked_secrets(&content))
+        });
+
+        self._save = cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(SAVE_DELAY).await;
+
+            let result = cx.background_spawn(async move { config.save(&path) }).await;
+
+            this.update(cx, |this, cx| {
+                this.error = result.err().map(|error| format!("{error:#}").into());
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+}
+
+impl Item for FolderItem {
+    fn tab_content_text(&self, _cx: &App) -> SharedString {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned().into())
+            .unwrap_or_else(|| "Folder".into())
+    }
+
+    fn tab_icon(&self, _cx: &App) -> Option<IconName> {
+        Some(IconName::Folder)
+    }
+
+    fn path(&self, _cx: &App) -> Option<Arc<Path>> {
+        Some(self.path.clone())
+    }
+}
+
+impl Render for FolderItem {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = cx.theme().colors();
+
+        div()
+            .v_flex()
+            .size_full()
+            .p_2()
+            .gap_2()
+            .child(
+                div().text_sm().text_color(colors.muted_foreground).child(
+                    "Requests inside the folder use these settings unless they have their own.",
+                ),
+            )
+            .when_some(self.error.clone(), |this, error| {
+                this.child(div().text_color(colors.danger).child(error))
+            })
+            .when_some(self.warning.clone(), |this, warning| {
+                this.child(div().text_color(colors.warning).child(warning))
+            })
+            .when(self.config.is_some(), |this| {
+                this.child(self.auth_editor.clone())
+                    .child(self.scripts_editor.clone())
+            })
+            .children(self.secrets_editor.clone())
+    }
+}
+
+/// Warns that the values of the `leaked` secrets are about to be written in
+/// plain text to a file of the collection.
+pub(crate) fn leak_warning(leaked: &[String]) -> Option<SharedString> {
+    let names = leaked.join(", ");
+
+    match leaked.len() {
+        0 => None,
+        1 => Some(
+            format!("The value of the secret {names} is written to this file, use {{{{{names}}}}} instead.")
+                .into(),
+        ),
+        _ => Some(
+            format!("The values of the secrets {names} are written to this file, reference them as {{{{name}}}} instead.")
+                .into(),
+        ),
+    }
+}
@@ -0,0 +1,455 @@
+use std::{collections::BTreeMap, rc::Rc, sync::Arc};
+
+use anyhow::Result;
+use collection::body::GraphQlBody;
+use editor::{Completion, CompletionProvider, Diagnostic, Editor, EditorEvent, language::Language};
+use gpui::{
+    App, AppContext, Context, Entity, EventEmitter, Global, Hsla, IntoElement, ParentElement,
+    Render, SharedString, Styled, Subscription, Task, WeakEntity, Window, div,
+    prelude::FluentBuilder,
+};
+use http_client::{GraphQlSchema, HttpRequest, RequestOptions, complete_query, operation_names};
+use theme::ActiveTheme;
+use ui::{
+    components::{
+        button::{Button, ButtonSize},
+        checkbox::Checkbox,
+        dropdown::{Dropdown, DropdownEvent},
+    },
+    traits::{clickable::Clickable, styled_ext::StyledExt},
+};
+
+use crate::schema_explorer::ToggleSchemaExplorer;
+
+/// The first choice of the operation dropdown, sending no operation name.
+const NO_OPERATION: &str = "No Operation Name";
+
+/// How many problems of the query are listed below it, at most.
+const MAX_PROBLEMS: usize = 5;
+
+/// The schemas introspected since the app started, by endpoint, shared by
+/// the requests and the schema explorer of every window.
+pub struct SchemaStore {
+    schemas: BTreeMap<String, Arc<GraphQlSchema>>,
+    /// The endpoint whose schema the explorer shows.
+    selected: Option<String>,
+}
+
+struct GlobalSchemaStore(Entity<SchemaStore>);
+
+impl Global for GlobalSchemaStore {}
+
+impl SchemaStore {
+    pub fn global(cx: &mut App) -> Entity<Self> {
+        if let Some(store) = cx.try_global::<GlobalSchemaStore>() {
+            return store.0.clone();
+        }
+
+        let store = cx.new(|_| Self {
+            schemas: BTreeMap::new(),
+            selected: None,
+        });
+        cx.set_global(GlobalSchemaStore(store.clone()));
+        store
+    }
+
+    pub fn schema(&self, endpoint: &str) -> Option<Arc<GraphQlSchema>> {
+        self.schemas.get(endpoint).cloned()
+    }
+
+    pub fn endpoints(&self) -> impl Iterator<Item = &str> {
+        self.schemas.keys().map(String::as_str)
+    }
+
+    pub fn selected(&self) -> Option<&str> {
+        self.selected.as_deref()
+    }
+
+    pub fn select(&mut self, endpoint: &str, cx: &mut Context<Self>) {
+        if self.schemas.contains_key(endpoint) && self.selected() != Some(endpoint) {
+            self.selected = Some(endpoint.to_string());
+            cx.notify();
+        }
+    }
+
+    /// Stores the schema of `endpoint`, replacing the one introspected
+    /// before, and shows it in the explorer.
+    pub fn insert(&mut self, endpoint: String, schema: Arc<GraphQlSchema>, cx: &mut Context<Self>) {
+        self.selected = Some(endpoint.clone());
+        self.schemas.insert(endpoint, schema);
+        cx.notify();
+    }
+}
+
+pub enum GraphQlEditorEvent {
+    Changed,
+    /// The schema of the endpoint was asked for, which the request item
+    /// sends with the headers and options of the request.
+    Introspect,
+}
+
+enum SchemaState {
+    Unloaded,
+    Loading,
+    Failed(SharedString),
+}
+
+/// Completes the query from the schema of the endpoint of its editor.
+struct QueryCompletions(WeakEntity<GraphQlEditor>);
+
+impl CompletionProvider for QueryCompletions {
+    fn completions(&self, text: &str, offset: usize, cx: &App) -> Vec<Completion> {
+        let Some(schema) = self
+            .0
+            .upgrade()
+            .and_then(|editor| editor.read(cx).schema.clone())
+        else {
+            return Vec::new();
+        };
+
+        complete_query(text, offset, &schema)
+            .into_iter()
+            .map(|completion| Completion {
+                label: completion.label.into(),
+                detail: Some(completion.detail)
+                    .filter(|detail| !detail.is_empty())
+                    .map(Into::into),
+                range: completion.range,
+                new_text: completion.insert,
+            })
+            .collect()
+    }
+
+    fn is_trigger_character(&self, ch: char) -> bool {
+        matches!(ch, '@' | '$')
+    }
+}
+
+/// The editors of a GraphQL body: the query, checked against the schema of
+/// the endpoint once introspected, its variables and the operation to run.
+pub struct GraphQlEditor {
+    query_editor: Entity<Editor>,
+    variables_editor: Entity<Editor>,
+    operation_dropdown: Entity<Dropdown>,
+    /// The named operations of the query, in order.
+    operations: Vec<String>,
+    operation_name: Option<String>,
+    persisted: bool,
+    /// The URL the request is sent to, which schemas are stored by.
+    endpoint: String,
+    schema: Option<Arc<GraphQlSchema>>,
+    schema_state: SchemaState,
+    /// The problems of the query, located by line and column.
+    problems: Vec<SharedString>,
+    _subscriptions: Vec<Subscription>,
+    _introspect: Option<Task<()>>,
+}
+
+impl GraphQlEditor {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let completions = Rc::new(QueryCompletions(cx.weak_entity()));
+        let query_editor = cx.new(|cx| {
+            let mut editor = Editor::new(cx).placeholder("query { … }");
+            editor.set_language(Some(Language::GraphQl), cx);
+            editor.set_completion_provider(Some(completions), cx);
+            editor
+        });
+        let variables_editor = cx.new(|cx| {
+            let mut editor = Editor::new(cx).placeholder("{ \"variable\": \"value\" }");
+            editor.set_language(Some(Language::Json), cx);
+            editor
+        });
+        let operation_dropdown = cx.new(|cx| {
+            let mut dropdown = Dropdown::new("graphql-operation", [NO_OPERATION], cx);
+            dropdown.set_selected_index(Some(0), cx);
+            dropdown
+        });
+        let store = SchemaStore::global(cx);
+
+        let subscriptions = vec![
+            cx.subscribe(&query_editor, |this, _, event, cx| {
+                let EditorEvent::Changed = event;
+                this.query_changed(cx);
+                cx.emit(GraphQlEditorEvent::Changed);
+            }),
+            cx.subscribe(&variables_editor, |_, _, event, cx| {
+                let EditorEvent::Changed = event;
+                cx.emit(GraphQlEditorEvent::Changed);
+            }),
+            cx.subscribe(&operation_dropdown, |this, _, event, cx| {
+                let DropdownEvent::Changed(index) = event;
+                this.operation_name = index
+                    .checked_sub(1)
+                    .and_then(|index| this.operations.get(index).cloned());
+                cx.emit(GraphQlEditorEvent::Changed);
+            }),
+            cx.observe(&store, |this, store, cx| {
+                this.schema = store.read(cx).schema(&this.endpoint);
+                this.validate(cx);
+            }),
+        ];
+
+        Self {
+            query_editor,
+            variables_editor,
+            operation_dropdown,
+            operations: Vec::new(),
+            operation_name: None,
+            persisted: false,
+            endpoint: String::new(),
+            schema: None,
+            schema_state: SchemaState::Unloaded,
+            problems: Vec::new(),
+            _subscriptions: subscriptions,
+            _introspect: None,
+        }
+    }
+
+    pub fn body(&self, cx: &App) -> GraphQlBody {
+        GraphQlBody {
+            query: self.query_editor.read(cx).text().to_string(),
+            variables: self.variables_editor.read(cx).text().to_string(),
+            operation_name: self.operation_name.clone(),
+            persisted: self.persisted,
+        }
+    }
+
+    /// Replaces the whole body without emitting [`GraphQlEditorEvent::Changed`].
+    pub fn set_body(&mut self, body: &GraphQlBody, cx: &mut Context<Self>) {
+        self.query_editor
+            .update(cx, |editor, cx| editor.set_text(body.query.clone(), cx));
+        self.variables_editor
+            .update(cx, |editor, cx| editor.set_text(body.variables.clone(), cx));
+        self.operation_name = body.operation_name.clone();
+        self.persisted = body.persisted;
+        self.operations.clear();
+        self.query_changed(cx);
+    }
+
+    /// Checks the query against the schema of `endpoint`, when one was
+    /// introspected.
+    pub fn set_endpoint(&mut self, endpoint: String, cx: &mut Context<Self>) {
+        if self.endpoint == endpoint {
+            return;
+        }
+
+        self.schema = SchemaStore::global(cx).read(cx).schema(&endpoint);
+        self.schema_state = SchemaState::Unloaded;
+        self.endpoint = endpoint;
+        self.validate(cx);
+    }
+
+    /// Sends the introspection query and stores the schema it answers with.
+    pub fn introspect(
+        &mut self,
+        prepared: Result<(HttpRequest, RequestOptions)>,
+        cx: &mut Context<Self>,
+    ) {
+        let (request, options) = match prepared {
+            Ok(prepared) => prepared,
+            Err(error) => {
+                self.schema_state = SchemaState::Failed(format!("{error:#}").into());
+                cx.notify();
+                return;
+            }
+        };
+
+        let endpoint = request.url.to_string();
+        let send = http_client::send(request, options);
+
+        self.schema_state = SchemaState::Loading;
+        cx.notify();
+
+        self._introspect = Some(cx.spawn(async move |this, cx| {
+            let schema = match send.await {
+                Ok(response) => {
+                    cx.background_spawn(
+                        async move { GraphQlSchema::from_introspection(&response.body) },
+                    )
+                    .await
+                }
+                Err(error) => Err(error),
+            };
+
+            this.update(cx, |this, cx| {
+                match schema {
+                    Ok(schema) => {
+                        this.schema_state = SchemaState::Unloaded;
+                        SchemaStore::global(cx)
+                            .update(cx, |store, cx| store.insert(endpoint, Arc::new(schema), cx));
+                    }
+                    Err(error) => {
+                        this.schema_state = SchemaState::Failed(format!("{error:#}").into());
+                    }
+                }
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    /// Lists the operations of the query and checks it again.
+    fn query_changed(&mut self, cx: &mut Context<Self>) {
+        let operations = operation_names(self.query_editor.read(cx).text());
+
+        if operations != self.operations {
+            let selected = match &self.operation_name {
+                None => Some(0),
+                Some(name) => operations
+                    .iter()
+                    .position(|operation| operation == name)
+                    .map(|index| index + 1),
+            };
+
+            let options = std::iter::once(NO_OPERATION.to_string())
+                .chain(operations.iter().cloned())
+                .collect::<Vec<_>>();
+
+            self.operation_dropdown.update(cx, |dropdown, cx| {
+                dropdown.set_options(options, cx);
+                dropdown.set_selected_index(selected, cx);
+            });
+            self.operations = operations;
+        }
+
+        self.validate(cx);
+    }
+
+    fn validate(&mut self, cx: &mut Context<Self>) {
+        let text = self.query_editor.read(cx).text();
+        let errors = http_client::validate_query(text, self.schema.as_deref());
+
+        self.problems = errors
+            .iter()
+            .map(|error| {
+                let before = &text[..error.range.start];
+                let line = before.matches('\n').count() + 1;
+                let column = before.len() - before.rfind('\n').map_or(0, |index| index + 1) + 1;
+
+                format!("{line}:{column}  {}", error.message).into()
+            })
+            .collect();
+
+        let diagnostics = errors
+            .into_iter()
+            .map(|error| Diagnostic {
+                range: error.range,
+                message: error.message.into(),
+            })
+            .collect();
+        self.query_editor
+            .update(cx, |editor, cx| editor.set_diagnostics(diagnostics, cx));
+        cx.notify();
+    }
+
+    fn schema_status(&self, cx: &App) -> Option<(SharedString, Hsla)> {
+        let colors = cx.theme().colors();
+
+        match &self.schema_state {
+            SchemaState::Loading => Some(("Introspecting…".into(), colors.muted_foreground)),
+            SchemaState::Failed(error) => Some((error.clone(), colors.danger)),
+            SchemaState::Unloaded => self.schema.as_ref().map(|schema| {
+                let count = schema.user_types().count();
+                (format!("{count} types").into(), colors.muted_foreground)
+            }),
+        }
+    }
+}
+
+impl EventEmitter<GraphQlEditorEvent> for GraphQlEditor {}
+
+impl Render for GraphQlEditor {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = cx.theme().colors();
+        let hidden_problems = self.problems.len().saturating_sub(MAX_PROBLEMS);
+
+        div()
+            .flex_1()
+            .min_h_0()
+            .v_flex()
+            .gap_2()
+            .child(
+                div()
+                    .h_flex()
+                    .gap_2()
+                    .child(div().text_sm().child("Operation"))
+                    .child(self.operation_dropdown.clone())
+                    .child(
+                        Checkbox::new("persisted-query", self.persisted)
+                            .label("Persisted Query")
+                            .on_toggle(cx.listener(|this, persisted, _, cx| {
+                                this.persisted = *persisted;
+                                cx.emit(GraphQlEditorEvent::Changed);
+                                cx.notify();
+                            })),
+                    )
+                    .child(div().flex_1())
+                    .when_some(self.schema_status(cx), |this, (status, color)| {
+                        this.child(div().text_xs().text_color(color).truncate().child(status))
+                    })
+                    .child(
+                        Button::new("introspect", "Introspect")
+                            .size(ButtonSize::Compact)
+                            .tooltip("Fetch the Schema of the Endpoint")
+                            .on_click(
+                                cx.listener(|_, _, _, cx| cx.emit(GraphQlEditorEvent::Introspect)),
+                            ),
+                    )
+                    .child(
+                        Button::new("show-schema", "Schema")
+                            .size(ButtonSize::Compact)
+                            .tooltip("Toggle Schema Explorer")
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                SchemaStore::global(cx)
+                                    .update(cx, |store, cx| store.select(&this.endpoint, cx));
+                                window.dispatch_action(Box::new(ToggleSchemaExplorer), cx);
+                            })),
+                    ),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .min_h_0()
+                    .h_flex()
+                    .items_start()
+                    .gap_2()
+                    .child(
+                        div()
+                            .flex_1()
+                            .h_full()
+                            .v_flex()
+                            .gap_1()
+                            .child(div().flex_1().min_h_0().child(self.query_editor.clone()))
+                            .children(self.problems.iter().take(MAX_PROBLEMS).map(|problem| {
+                                div()
+                                    .text_xs()
+                                    .text_color(colors.danger)
+                                    .truncate()
+                                    .child(problem.clone())
+                            }))
+                            .when(hidden_problems > 0, |this| {
+                                this.child(
+                                    div()
+                                        .text_xs()
+                                        .text_color(colors.muted_foreground)
+                                        .child(format!("{hidden_problems} more problems")),
+                                )
+                            }),
+                    )
+                    .child(
+                        div()
+                            .w_1_3()
+                            .h_full()
+                            .v_flex()
+                            .gap_1()
+                            .child(div().text_sm().child("Variables"))
+                            .child(
+                                div()
+                                    .flex_1()
+                                    .min_h_0()
+                                    .child(self.variables_editor.clone()),
+                            ),
+                    ),
+            )
+    }
+}
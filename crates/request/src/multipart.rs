@@ -0,0 +1,334 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use collection::body::{MultipartPart, PartContent};
+use gpui::{
+    App, AppContext, Context, Entity, EventEmitter, InteractiveElement, IntoElement, ParentElement,
+    PathPromptOptions, Render, Styled, Subscription, Window, div, prelude::FluentBuilder,
+};
+use theme::ActiveTheme;
+use ui::{
+    components::{
+        button::{Button, ButtonSize},
+        checkbox::Checkbox,
+        dropdown::{Dropdown, DropdownEvent},
+        icon::IconName,
+        icon_button::IconButton,
+        input::{InputEvent, TextInput},
+    },
+    traits::{clickable::Clickable, styled_ext::StyledExt},
+};
+
+/// The choices of the kind dropdown of a part, in order.
+const PART_KINDS: [&str; 2] = ["Text", "File"];
+const FILE_PART: usize = 1;
+
+pub enum MultipartEditorEvent {
+    Changed,
+}
+
+struct Row {
+    id: usize,
+    enabled: bool,
+    kind: Entity<Dropdown>,
+    name: Entity<TextInput>,
+    /// The text of a text part, or the path of a file part.
+    value: Entity<TextInput>,
+    content_type: Entity<TextInput>,
+    filename: Entity<TextInput>,
+    _subscriptions: [Subscription; 5],
+}
+
+impl Row {
+    fn is_file(&self, cx: &App) -> bool {
+        self.kind.read(cx).selected_index() == Some(FILE_PART)
+    }
+
+    fn part(&self, cx: &App) -> MultipartPart {
+        let text = |input: &Entity<TextInput>| input.read(cx).text().to_string();
+        let optional =
+            |input: &Entity<TextInput>| Some(text(input)).filter(|text| !text.is_empty());
+
+        MultipartPart {
+            name: text(&self.name),
+            content: if self.is_file(cx) {
+                PartContent::File(text(&self.value).into())
+            } else {
+                PartContent::Value(text(&self.value))
+            },
+            content_type: optional(&self.content_type),
+            filename: optional(&self.filename),
+            enabled: self.enabled,
+        }
+    }
+}
+
+/// An editable list of the parts of a `multipart/form-data` body, each
+/// holding either text or a file.
+pub struct MultipartEditor {
+    rows: Vec<Row>,
+    next_row_id: usize,
+    /// The folder of the request, which chosen files are made relative to.
+    dir: Arc<Path>,
+}
+
+impl MultipartEditor {
+    pub fn new(dir: Arc<Path>) -> Self {
+        Self {
+            rows: Vec::new(),
+            next_row_id: 0,
+            dir,
+        }
+    }
+
+    pub fn parts(&self, cx: &App) -> Vec<MultipartPart> {
+        self.rows.iter().map(|row| row.part(cx)).collect()
+    }
+
+    /// Replaces every part without emitting [`MultipartEditorEvent::Changed`].
+    pub fn set_parts(&mut self, parts: &[MultipartPart], cx: &mut Context<Self>) {
+        self.rows.clear();
+
+        for part in parts {
+            self.push_row(part, cx);
+        }
+
+        cx.notify();
+    }
+
+    fn push_row(&mut self, part: &MultipartPart, cx: &mut Context<Self>) {
+        let id = self.next_row_id;
+        let (kind_index, value) = match &part.content {
+            PartContent::Value(value) => (0, value.clone()),
+            PartContent::File(path) => (FILE_PART, path.to_string_lossy().into_owned()),
+        };
+
+        let kind = cx.new(|cx| {
+            let mut dropdown = Dropdown::new(("part-kind", id), PART_KINDS, cx);
+            dropdown.set_selected_index(Some(kind_index), cx);
+            dropdown
+        });
+        let input = |text: String, placeholder: &'static str, cx: &mut Context<Self>| {
+            cx.new(|cx| {
+                TextInput::new(cx)
+                    .placeholder(placeholder)
+                    .default_text(text)
+            })
+        };
+        let name = input(part.name.clone(), "Name", cx);
+        let value = input(value, "Value", cx);
+        let content_type = input(
+            part.content_type.clone().unwrap_or_default(),
+            "Content Type",
+            cx,
+        );
+        let filename = input(part.filename.clone().unwrap_or_default(), "Filename", cx);
+
+        let subscriptions = [
+            cx.subscribe(&kind, |this, _, event, cx| {
+                let DropdownEvent::Changed(_) = event;
+                this.changed(cx);
+            }),
+            cx.subscribe(&name, Self::handle_input_event),
+            cx.subscribe(&value, Self::handle_input_event),
+            cx.subscribe(&content_type, Self::handle_input_event),
+            cx.subscribe(&filename, Self::handle_input_event),
+        ];
+
+        self.rows.push(Row {
+            id,
+            enabled: part.enabled,
+            kind,
+            name,
+            value,
+            content_type,
+            filename,
+            _subscriptions: subscriptions,
+        });
+        self.next_row_id += 1;
+    }
+
+    fn add_part(&mut self, cx: &mut Context<Self>) {
+        self.push_row(
+            &MultipartPart {
+                name: String::new(),
+                content: PartContent::Value(String::new()),
+                content_type: None,
+                filename: None,
+                enabled: true,
+            },
+            cx,
+        );
+        self.changed(cx);
+    }
+
+    fn handle_input_event(
+        &mut self,
+        _: Entity<TextInput>,
+        event: &InputEvent,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputEvent::Changed = event {
+            self.changed(cx);
+        }
+    }
+
+    fn changed(&mut self, cx: &mut Context<Self>) {
+        cx.emit(MultipartEditorEvent::Changed);
+        cx.notify();
+    }
+
+    fn toggle_row(&mut self, id: usize, enabled: bool, cx: &mut Context<Self>) {
+        if let Some(row) = self.rows.iter_mut().find(|row| row.id == id) {
+            row.enabled = enabled;
+            self.changed(cx);
+        }
+    }
+
+    fn remove_row(&mut self, id: usize, cx: &mut Context<Self>) {
+        self.rows.retain(|row| row.id != id);
+        self.changed(cx);
+    }
+
+    fn choose_file(&mut self, id: usize, cx: &mut Context<Self>) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some("Choose File".into()),
+        });
+
+        cx.spawn(async move |this, cx| {
+            let Ok(Ok(Some(paths))) = paths.await else {
+                return;
+            };
+            let Some(path) = paths.into_iter().next() else {
+                return;
+            };
+
+            this.update(cx, |this, cx| {
+                let path = relative_to(&path, &this.dir);
+
+                if let Some(row) = this.rows.iter().find(|row| row.id == id) {
+                    row.value.update(cx, |input, cx| input.set_text(path, cx));
+                    this.changed(cx);
+                }
+            })
+            .ok();
+        })
+        .detach();
+    }
+}
+
+/// `path` relative to `dir` when it is inside it, so that collections can
+/// be moved around along with their files.
+pub(crate) fn relative_to(path: &Path, dir: &Path) -> String {
+    path.strip_prefix(dir)
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .to_string_lossy()
+        .into_owned()
+}
+
+impl EventEmitter<MultipartEditorEvent> for MultipartEditor {}
+
+impl Render for MultipartEditor {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = *cx.theme().colors();
+
+        div()
+            .v_flex()
+            .w_full()
+            .gap_2()
+            .child(
+                div()
+                    .v_flex()
+                    .w_full()
+                    .rounded_sm()
+                    .border_1()
+                    .border_color(colors.table_row_border)
+                    .bg(colors.table)
+                    .text_sm()
+                    .child(
+                        div()
+                            .h_flex()
+                            .gap_2()
+                            .px_2()
+                            .py_1()
+                            .bg(colors.table_head)
+                            .text_color(colors.table_head_foreground)
+                            .child(div().w_4())
+                            .child(div().flex_1().child("Name"))
+                            .child(div().w_20().child("Kind"))
+                            .child(div().flex_1().child("Value"))
+                            .child(div().w_40().child("Content Type"))
+                            .child(div().w_32().child("Filename"))
+                            .child(div().w_6()),
+                    )
+                    .children(self.rows.iter().enumerate().map(|(index, row)| {
+                        let id = row.id;
+                        let is_file = row.is_file(cx);
+
+                        div()
+                            .id(id)
+                            .h_flex()
+                            .gap_2()
+                            .px_2()
+                            .py_1()
+                            .border_t_1()
+                            .border_color(colors.table_row_border)
+                            .when(index % 2 == 1, |this| this.bg(colors.table_even))
+                            .hover(|this| this.bg(colors.table_hover))
+                            .child(div().w_4().child(
+                                Checkbox::new(("enabled", id), row.enabled).on_toggle(cx.listener(
+                                    move |this, enabled, _, cx| this.toggle_row(id, *enabled, cx),
+                                )),
+                            ))
+                            .child(
+                                div()
+                                    .flex_1()
+                                    .when(!row.enabled, |this| this.opacity(0.5))
+                                    .child(row.name.clone()),
+                            )
+                            .child(div().w_20().child(row.kind.clone()))
+                            .child(
+                                div()
+                                    .flex_1()
+                                    .h_flex()
+                                    .gap_1()
+                                    .when(!row.enabled, |this| this.opacity(0.5))
+                                    .child(div().flex_1().child(row.value.clone()))
+                                    .when(is_file, |this| {
+                                        this.child(
+                                            IconButton::new(("browse", id), IconName::FolderOpen)
+                                                .tooltip("Choose File")
+                                                .on_click(cx.listener(move |this, _, _, cx| {
+                                                    this.choose_file(id, cx)
+                                                })),
+                                        )
+                                    }),
+                            )
+                            .child(div().w_40().child(row.content_type.clone()))
+                            .child(div().w_32().child(row.filename.clone()))
+                            .child(
+                                div().w_6().child(
+                                    IconButton::new(("remove", id), IconName::Close)
+                                        .tooltip("Remove")
+                                        .on_click(cx.listener(move |this, _, _, cx| {
+                                            this.remove_row(id, cx)
+                                        })),
+                                ),
+                            )
+                    })),
+            )
+            .child(
+                div().h_flex().child(
+                    Button::new("add-part", "Add Part")
+                        .size(ButtonSize::Compact)
+                        .on_click(cx.listener(|this, _, _, cx| this.add_part(cx))),
+                ),
+            )
+    }
+}
@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use http_client::{HttpVersion, NoProxy, Proxy, ProxyConfig, RedirectPolicy, Timeouts};
+use settings::{Settings, SettingsContent};
+
+/// How long scripts may run when the setting asks for no limit, which
+/// would let a runaway script hang its request.
+const DEFAULT_SCRIPT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Defaults for requests that don't override them.
+pub struct RequestSettings {
+    pub http_version: HttpVersion,
+    pub proxy: Option<Proxy>,
+    pub no_proxy: Option<NoProxy>,
+    pub use_proxy_environment: bool,
+    pub redirect: RedirectPolicy,
+    pub timeouts: Timeouts,
+    /// How long the scripts run before or after a request may take.
+    pub script_timeout: Duration,
+    /// The size past which response bodies are written to a temporary file.
+    pub spill_threshold: Option<u64>,
+}
+
+impl RequestSettings {
+    /// The proxies requests go through unless they pick their own. The
+    /// environment is read anew each time.
+    pub fn proxy_config(&self) -> Result<ProxyConfig> {
+        let mut config = match &self.proxy {
+            Some(proxy) => ProxyConfig::new(proxy.clone()),
+            None if self.use_proxy_environment => ProxyConfig::from_env()?,
+            None => ProxyConfig::default(),
+        };
+
+        if let Some(no_proxy) = &self.no_proxy {
+            config.no_proxy = no_proxy.clone();
+        }
+
+        Ok(config)
+    }
+}
+
+impl Settings for RequestSettings {
+    fn from_settings(content: &SettingsContent) -> Self {
+        Self {
+            http_version: content.http.version.unwrap_or_default(),
+            proxy: content.proxy.url.clone(),
+            no_proxy: content.proxy.no_proxy.clone(),
+            use_proxy_environment: content.proxy.use_environment.unwrap_or(true),
+            redirect: {
+                let redirects = &content.redirects;
+                let default = RedirectPolicy::default();
+
+                RedirectPolicy {
+                    follow: redirects.follow.unwrap_or(default.follow),
+                    max_hops: redirects.max_hops.unwrap_or(default.max_hops),
+                    keep_method: redirects.keep_method.unwrap_or(default.keep_method),
+                    forward_auth: redirects.forward_auth.unwrap_or(default.forward_auth),
+                }
+            },
+            timeouts: Timeouts {
+                connect: seconds(content.timeouts.connect),
+                read: seconds(content.timeouts.read),
+                total: seconds(content.timeouts.total),
+            },
+            script_timeout: seconds(content.timeouts.script).unwrap_or(DEFAULT_SCRIPT_TIMEOUT),
+            spill_threshold: content
+                .http
+                .max_body_in_memory_mb
+                .map(|megabytes| megabytes << 20),
+        }
+    }
+}
+
+/// A timeout in seconds, where 0 and negative values mean there is none.
+fn seconds(seconds: Option<f64>) -> Option<Duration> {
+    seconds
+        .filter(|seconds| *seconds > 0.0)
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+}
@@ -0,0 +1,946 @@
+use std::{fmt::Write as _, time::Duration};
+
+use collection::assertions::AssertionResult;
+use editor::{Editor, language::Language};
+use gpui::{
+    AnyElement, App, AppContext, Context, Div, Entity, Hsla, InteractiveElement, IntoElement,
+    ParentElement, Render, SharedString, StatefulInteractiveElement, Styled, Window, div,
+    prelude::FluentBuilder, px, relative,
+};
+use http_client::{
+    ContentEncoding, HttpResponse, PREVIEW_SIZE, RedirectHop, ResponseProgress, TlsInfo,
+    http::{HeaderMap, StatusCode, Version, header::CONTENT_TYPE},
+    protocol_name,
+};
+use script::{ConsoleLevel, ConsoleLine};
+use theme::ActiveTheme;
+use ui::{
+    components::{
+        button::{Button, ButtonSize, ButtonStyle},
+        tooltip::Tooltip,
+    },
+    traits::{clickable::Clickable, styled_ext::StyledExt},
+};
+
+enum ResponseState {
+    Empty,
+    /// Sent, with what arrived of the response so far.
+    Pending(Option<Box<Receiving>>),
+    Received(Box<HttpResponse>),
+    Failed(SharedString),
+    Cancelled,
+}
+
+/// A response whose head arrived while its body is still being received.
+struct Receiving {
+    version: Version,
+    status: StatusCode,
+    headers: HeaderMap,
+    received: u64,
+    total: Option<u64>,
+    /// Whether the body has a content encoding, in which case it isn't
+    /// shown until it can be decoded.
+    encoded: bool,
+    /// The start of the body as received.
+    preview: Vec<u8>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ResponseTab {
+    Body,
+    Headers,
+    Redirects,
+    Timing,
+    Tls,
+    Tests,
+    Console,
+}
+
+impl ResponseTab {
+    const ALL: [ResponseTab; 7] = [
+        ResponseTab::Body,
+        ResponseTab::Headers,
+        ResponseTab::Redirects,
+        ResponseTab::Timing,
+        ResponseTab::Tls,
+        ResponseTab::Tests,
+        ResponseTab::Console,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            ResponseTab::Body => "Body",
+            ResponseTab::Headers => "Headers",
+            ResponseTab::Redirects => "Redirects",
+            ResponseTab::Timing => "Timing",
+            ResponseTab::Tls => "TLS",
+            ResponseTab::Tests => "Tests",
+            ResponseTab::Console => "Console",
+        }
+    }
+}
+
+/// How much of a raw body is shown as hex, since a dump takes about four
+/// times the size of the bytes.
+const MAX_HEX_DUMP: usize = 1 << 20;
+
+/// A line printed by a script run around the request, or why it failed.
+struct ConsoleEntry {
+    phase: &'static str,
+    level: ConsoleLevel,
+    message: SharedString,
+}
+
+/// The outcome of the last time a request was sent.
+pub struct ResponseView {
+    state: ResponseState,
+    console: Vec<ConsoleEntry>,
+    /// The outcome of the assertions of the request on the response.
+    tests: Vec<AssertionResult>,
+    tab: ResponseTab,
+    /// Whether the body tab shows the bytes received, as hex, rather than
+    /// the decoded body.
+    show_raw: bool,
+    body_editor: Entity<Editor>,
+}
+
+impl ResponseView {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        Self {
+            state: ResponseState::Empty,
+            console: Vec::new(),
+            tests: Vec::new(),
+            tab: ResponseTab::Body,
+            show_raw: false,
+            body_editor: cx.new(|cx| Editor::new(cx).read_only(true)),
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        matches!(self.state, ResponseState::Pending(_))
+    }
+
+    pub fn set_pending(&mut self, cx: &mut Context<Self>) {
+        self.state = ResponseState::Pending(None);
+        self.tests.clear();
+        cx.notify();
+    }
+
+    /// Shows what arrived of the response. Ignored once the request is no
+    /// longer pending, as progress may be reported after it completed.
+    pub fn update_progress(
+        &mut self,
+        progress: impl IntoIterator<Item = ResponseProgress>,
+        cx: &mut Context<Self>,
+    ) {
+        let ResponseState::Pending(receiving) = &mut self.state else {
+            return;
+        };
+
+        let mut preview_changed = false;
+        for progress in progress {
+            match progress {
+                ResponseProgress::Head {
+                    version,
+                    status,
+                    headers,
+                } => {
+                    *receiving = Some(Box::new(Receiving {
+                        version,
+                        status,
+                        encoded: !ContentEncoding::from_headers(&headers).is_empty(),
+                        headers,
+                        received: 0,
+                        total: None,
+                        preview: Vec::new(),
+                    }));
+                    preview_changed = true;
+                }
+                ResponseProgress::Body {
+                    chunk,
+                    received,
+                    total,
+                } => {
+                    if let Some(receiving) = receiving.as_mut() {
+                        receiving.received = received;
+                        receiving.total = total;
+
+                        if !receiving.encoded && !chunk.is_empty() {
+                            receiving.preview.extend_from_slice(&chunk);
+                            preview_changed = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        if preview_changed && let Some(receiving) = receiving {
+            let body = String::from_utf8_lossy(&receiving.preview).into_owned();
+            let language = body_language(&receiving.headers, &body);
+
+            self.body_editor.update(cx, |editor, cx| {
+                editor.set_text(body, cx);
+                editor.set_language(language, cx);
+            });
+        }
+
+        cx.notify();
+    }
+
+    pub fn set_response(&mut self, response: HttpResponse, cx: &mut Context<Self>) {
+        if !is_available(self.tab, &response, &self.console, &self.tests) {
+            self.tab = ResponseTab::Body;
+        }
+        self.state = ResponseState::Received(Box::new(response));
+        self.update_body(cx);
+        cx.notify();
+    }
+
+    fn toggle_raw(&mut self, cx: &mut Context<Self>) {
+        self.show_raw = !self.show_raw;
+        self.update_body(cx);
+        cx.notify();
+    }
+
+    /// Shows the decoded body, or a hex dump of the bytes received when
+    /// `show_raw` is set.
+    fn update_body(&mut self, cx: &mut Context<Self>) {
+        let ResponseState::Received(response) = &self.state else {
+            return;
+        };
+
+        let (body, language) = if self.show_raw {
+            (hex_dump(&response.raw_body, response.raw_body_len()), None)
+        } else {
+            let body = String::from_utf8_lossy(&response.body).into_owned();
+            let language = body_language(&response.headers, &body);
+
+            (body, language)
+        };
+
+        self.body_editor.update(cx, |editor, cx| {
+            editor.set_text(body, cx);
+            editor.set_language(language, cx);
+        });
+    }
+
+    pub fn set_error(&mut self, error: impl Into<SharedString>, cx: &mut Context<Self>) {
+        self.state = ResponseState::Failed(error.into());
+        cx.notify();
+    }
+
+    pub fn set_cancelled(&mut self, cx: &mut Context<Self>) {
+        self.state = ResponseState::Cancelled;
+        cx.notify();
+    }
+
+    /// Shows the outcome of the assertions of the request on the response
+    /// about to be set.
+    pub fn set_tests(&mut self, tests: Vec<AssertionResult>, cx: &mut Context<Self>) {
+        self.tests = tests;
+        cx.notify();
+    }
+
+    /// Forgets what scripts printed when the request was last sent.
+    pub fn clear_console(&mut self, cx: &mut Context<Self>) {
+        self.console.clear();
+        cx.notify();
+    }
+
+    /// Adds what the scripts of `phase` printed, followed by why they
+    /// failed if they did.
+    pub fn log_scripts(
+        &mut self,
+        phase: &'static str,
+        lines: &[ConsoleLine],
+        error: Option<&str>,
+        cx: &mut Context<Self>,
+    ) {
+        let lines = lines.iter().map(|line| ConsoleEntry {
+            phase,
+            level: line.level,
+            message: line.message.clone().into(),
+        });
+        let error = error.map(|error| ConsoleEntry {
+            phase,
+            level: ConsoleLevel::Error,
+            message: format!("Script failed: {error}").into(),
+        });
+
+        self.console.extend(lines.chain(error));
+        cx.notify();
+    }
+}
+
+/// Highlights a body according to its `Content-Type`, or to what it looks
+/// like when there is none.
+fn body_language(headers: &HeaderMap, body: &str) -> Option<Language> {
+    match headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(content_type) => Language::from_content_type(content_type),
+        None => Language::detect(body),
+    }
+}
+
+/// Whether `tab` has something to show for `response`, given what scripts
+/// printed around it and the outcome of its assertions.
+fn is_available(
+    tab: ResponseTab,
+    response: &HttpResponse,
+    console: &[ConsoleEntry],
+    tests: &[AssertionResult],
+) -> bool {
+    match tab {
+        ResponseTab::Redirects => !response.redirects.is_empty(),
+        ResponseTab::Tls => response.tls.is_some(),
+        ResponseTab::Tests => !tests.is_empty(),
+        ResponseTab::Console => !console.is_empty(),
+        _ => true,
+    }
+}
+
+/// Formats a byte count with the largest fitting binary unit.
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{bytes} B"),
+        1024..1_048_576 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        1_048_576..1_073_741_824 => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+        _ => format!("{:.2} GB", bytes as f64 / 1_073_741_824.0),
+    }
+}
+
+/// Lists `bytes` sixteen per line with their offset and the printable
+/// ones as text, like `hexdump -C`. `len` is the size of the whole body,
+/// of which `bytes` may only be the start.
+fn hex_dump(bytes: &[u8], len: u64) -> String {
+    let mut dump = String::new();
+
+    for (index, line) in bytes[..bytes.len().min(MAX_HEX_DUMP)]
+        .chunks(16)
+        .enumerate()
+    {
+        let _ = write!(dump, "{:08x} ", index * 16);
+        for column in 0..16 {
+            if column % 8 == 0 {
+                dump.push(' ');
+            }
+            match line.get(column) {
+                Some(byte) => {
+                    let _ = write!(dump, "{byte:02x} ");
+                }
+                None => dump.push_str("   "),
+            }
+        }
+
+        dump.push_str(" |");
+        dump.extend(line.iter().map(|byte| match byte {
+            0x20..0x7f => *byte as char,
+            _ => '.',
+        }));
+        dump.push_str("|\n");
+    }
+
+    let shown = bytes.len().min(MAX_HEX_DUMP) as u64;
+    if len > shown {
+        let _ = writeln!(dump, "… {} more not shown", format_size(len - shown));
+    }
+
+    dump
+}
+
+fn status_text(status: StatusCode) -> String {
+    match status.canonical_reason() {
+        Some(reason) => format!("{} {reason}", status.as_u16()),
+        None => status.as_u16().to_string(),
+    }
+}
+
+fn status_color(status: StatusCode, cx: &App) -> Hsla {
+    let colors = cx.theme().colors();
+
+    match status.as_u16() {
+        200..300 => colors.success,
+        300..400 => colors.info,
+        400..500 => colors.warning,
+        _ => colors.danger,
+    }
+}
+
+pub(crate) fn format_duration(duration: Duration) -> String {
+    let millis = duration.as_secs_f64() * 1000.0;
+
+    if millis < 1000.0 {
+        format!("{millis:.1} ms")
+    } else {
+        format!("{:.2} s", millis / 1000.0)
+    }
+}
+
+impl ResponseView {
+    fn render_headers(&self, headers: &HeaderMap, cx: &Context<Self>) -> AnyElement {
+        let colors = cx.theme().colors();
+
+        div()
+            .v_flex()
+            .text_xs()
+            .children(headers.iter().map(|(name, value)| {
+                div()
+                    .h_flex()
+                    .gap_2()
+                    .child(
+                        div()
+                            .text_color(colors.muted_foreground)
+                            .child(name.to_string()),
+                    )
+                    .child(String::from_utf8_lossy(value.as_bytes()).into_owned())
+            }))
+            .into_any_element()
+    }
+
+    /// Lists the redirects that led to the response, each with the headers
+    /// it was received with.
+    fn render_redirects(&self, redirects: &[RedirectHop], cx: &Context<Self>) -> AnyElement {
+        let colors = cx.theme().colors();
+
+        let hops = redirects.iter().enumerate().map(|(index, hop)| {
+            div()
+                .v_flex()
+                .gap_1()
+                .p_2()
+                .rounded_md()
+                .border_1()
+                .border_color(colors.border)
+                .child(
+                    div()
+                        .h_flex()
+                        .gap_2()
+                        .text_sm()
+                        .child(
+                            div()
+                                .text_color(status_color(hop.status, cx))
+                                .child(status_text(hop.status)),
+                        )
+                        .child(hop.method.to_string())
+                        .child(div().flex_1().truncate().child(hop.url.to_string()))
+                        .child(
+                            div()
+                                .text_color(colors.muted_foreground)
+                                .child(format_duration(hop.timing.total)),
+                        ),
+                )
+                .child(
+                    div()
+                        .text_xs()
+                        .text_color(colors.muted_foreground)
+                        .child(format!("{}. Redirected to {}", index + 1, hop.location)),
+                )
+                .child(self.render_headers(&hop.headers, cx))
+        });
+
+        div()
+            .id("redirects")
+            .v_flex()
+            .flex_1()
+            .min_h_0()
+            .gap_2()
+            .overflow_y_scroll()
+            .children(hops)
+            .into_any_element()
+    }
+
+    /// Lays out each phase as a bar starting where the previous one ended,
+    /// scaled to the total duration.
+    fn render_timing(&self, response: &HttpResponse, cx: &Context<Self>) -> AnyElement {
+        let colors = cx.theme().colors();
+        let timing = &response.timing;
+        let total = timing.total.as_secs_f32().max(f32::EPSILON);
+        let mut offset = Duration::ZERO;
+
+        let rows = timing.phases().into_iter().map(|(label, duration)| {
+            let start = offset.as_secs_f32() / total;
+            let width = duration.as_secs_f32() / total;
+            offset += duration;
+
+            div()
+                .h_flex()
+                .gap_2()
+                .child(
+                    div()
+                        .w_32()
+                        .flex_none()
+                        .text_color(colors.muted_foreground)
+                        .child(label),
+                )
+                .child(
+                    div().relative().flex_1().h_3().child(
+                        div()
+                            .absolute()
+                            .top_0()
+                            .bottom_0()
+                            .left(relative(start.min(1.)))
+                            .w(relative(width.min(1. - start).max(0.)))
+                            .min_w(px(2.))
+                            .rounded_sm()
+                            .bg(colors.primary),
+                    ),
+                )
+                .child(
+                    div()
+                        .w_20()
+                        .flex_none()
+                        .text_right()
+                        .child(format_duration(duration)),
+                )
+        });
+
+        let connection = &response.connection;
+        let connection = format!(
+            "Connection #{} to {}{}{}",
+            connection.id,
+            connection.remote_addr,
+            match &connection.proxy {
+                Some(proxy) => format!(" via proxy {proxy}"),
+                None => String::new(),
+            },
+            if connection.reused { ", reused" } else { "" },
+        );
+
+        div()
+            .v_flex()
+            .gap_1()
+            .text_xs()
+            .children(rows)
+            .child(
+                div()
+                    .h_flex()
+                    .gap_2()
+                    .pt_1()
+                    .border_t_1()
+                    .border_color(colors.border)
+                    .child(div().flex_1().child("Total"))
+                    .child(format_duration(timing.total)),
+            )
+            .child(div().text_color(colors.muted_foreground).child(connection))
+            .into_any_element()
+    }
+}
+
+impl ResponseView {
+    fn render_tls(&self, tls: &TlsInfo, cx: &Context<Self>) -> AnyElement {
+        let colors = cx.theme().colors();
+        let field = |label: &'static str, value: String| {
+            div()
+                .h_flex()
+                .gap_2()
+                .child(
+                    div()
+                        .w_24()
+                        .flex_none()
+                        .text_color(colors.muted_foreground)
+                        .child(label),
+                )
+                .child(value)
+        };
+
+        let certificates = tls
+            .certificates
+            .iter()
+            .enumerate()
+            .map(|(index, certificate)| {
+                div()
+                    .v_flex()
+                    .gap_0p5()
+                    .p_2()
+                    .rounded_md()
+                    .border_1()
+                    .border_color(colors.border)
+                    .child(div().text_sm().child(match index {
+                        0 => "Server certificate".to_string(),
+                        index => format!("Issuer certificate {index}"),
+                    }))
+                    .child(field("Subject", certificate.subject.clone()))
+                    .child(field("Issuer", certificate.issuer.clone()))
+                    .child(field("Valid from", certificate.not_before.clone()))
+                    .child(field("Valid until", certificate.not_after.clone()))
+                    .when(!certificate.subject_alt_names.is_empty(), |this| {
+                        this.child(field("Alt names", certificate.subject_alt_names.join(", ")))
+                    })
+                    .child(field("Serial", certificate.serial.clone()))
+                    .child(field("SHA-256", certificate.sha256.clone()))
+            });
+
+        div()
+            .id("tls")
+            .v_flex()
+            .flex_1()
+            .min_h_0()
+            .gap_2()
+            .overflow_y_scroll()
+            .text_xs()
+            .child(field("Version", tls.version.clone()))
+            .child(field("Cipher suite", tls.cipher_suite.clone()))
+            .when(!tls.verified, |this| {
+                this.child(
+                    div()
+                        .text_color(colors.warning)
+                        .child("The certificate chain was not verified"),
+                )
+            })
+            .children(certificates)
+            .into_any_element()
+    }
+}
+
+impl ResponseView {
+    /// Lists the assertions checked on the response, with why those that
+    /// failed did.
+    fn render_tests(&self, cx: &Context<Self>) -> AnyElement {
+        let colors = cx.theme().colors();
+
+        div()
+            .id("tests")
+            .v_flex()
+            .flex_1()
+            .min_h_0()
+            .gap_1()
+            .overflow_y_scroll()
+            .text_sm()
+            .children(self.tests.iter().map(|test| {
+                div()
+                    .h_flex()
+                    .items_start()
+                    .gap_2()
+                    .child(
+                        div()
+                            .w_12()
+                            .flex_none()
+                            .text_color(if test.passed() {
+                                colors.success
+                            } else {
+                                colors.danger
+                            })
+                            .child(if test.passed() { "Pass" } else { "Fail" }),
+                    )
+                    .child(div().v_flex().flex_1().child(test.name.clone()).when_some(
+                        test.failure.clone(),
+                        |this, failure| {
+                            this.child(
+                                div()
+                                    .text_xs()
+                                    .text_color(colors.muted_foreground)
+                                    .child(failure),
+                            )
+                        },
+                    ))
+            }))
+            .into_any_element()
+    }
+
+    /// Lists what scripts printed, in the order they ran.
+    fn render_console(&self, cx: &Context<Self>) -> AnyElement {
+        let colors = cx.theme().colors();
+
+        div()
+            .id("console")
+            .v_flex()
+            .flex_1()
+            .min_h_0()
+            .gap_0p5()
+            .overflow_y_scroll()
+            .text_xs()
+            .children(self.console.iter().map(|entry| {
+                let color = match entry.level {
+                    ConsoleLevel::Log => colors.foreground,
+                    ConsoleLevel::Info => colors.info,
+                    ConsoleLevel::Warn => colors.warning,
+                    ConsoleLevel::Error => colors.danger,
+                };
+
+                div()
+                    .h_flex()
+                    .items_start()
+                    .gap_2()
+                    .child(
+                        div()
+                            .w_24()
+                            .flex_none()
+                            .text_color(colors.muted_foreground)
+                            .child(entry.phase),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_color(color)
+                            .child(entry.message.clone()),
+                    )
+            }))
+            .into_any_element()
+    }
+
+    /// Shows the head of the response and how much of its body arrived, with
+    /// the start of the body when it can be shown as is.
+    fn render_receiving(&self, receiving: &Receiving, cx: &Context<Self>) -> Div {
+        let colors = cx.theme().colors();
+        let received = match receiving.total {
+            Some(total) => format!(
+                "{} of {}",
+                format_size(receiving.received),
+                format_size(total)
+            ),
+            None => format_size(receiving.received),
+        };
+
+        div()
+            .v_flex()
+            .size_full()
+            .gap_2()
+            .child(
+                div()
+                    .h_flex()
+                    .gap_3()
+                    .text_sm()
+                    .child(
+                        div()
+                            .text_color(status_color(receiving.status, cx))
+                            .child(status_text(receiving.status)),
+                    )
+                    .child(
+                        div()
+                            .px_1()
+                            .rounded_sm()
+                            .bg(colors.muted)
+                            .text_color(colors.muted_foreground)
+                            .child(protocol_name(receiving.version)),
+                    )
+                    .child(
+                        div()
+                            .text_color(colors.muted_foreground)
+                            .child(format!("Receiving… {received}")),
+                    ),
+            )
+            .child(
+                div()
+                    .relative()
+                    .h_1()
+                    .w_full()
+                    .rounded_sm()
+                    .bg(colors.muted)
+                    .when_some(receiving.total, |this, total| {
+                        let done = receiving.received as f32 / total.max(1) as f32;
+
+                        this.child(
+                            div()
+                                .absolute()
+                                .top_0()
+                                .bottom_0()
+                                .left_0()
+                                .w(relative(done.min(1.)))
+                                .rounded_sm()
+                                .bg(colors.primary),
+                        )
+                    }),
+            )
+            .map(|this| {
+                if receiving.encoded {
+                    this.child(
+                        div()
+                            .text_xs()
+                            .text_color(colors.muted_foreground)
+                            .child("The body is shown once it is received and decoded"),
+                    )
+                } else {
+                    this.child(div().flex_1().min_h_0().child(self.body_editor.clone()))
+                }
+            })
+    }
+}
+
+impl Render for ResponseView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = cx.theme().colors();
+        let message = |message: SharedString| {
+            div()
+                .h_flex()
+                .justify_center()
+                .size_full()
+                .text_sm()
+                .text_color(colors.muted_foreground)
+                .child(message)
+        };
+
+        let response = match &self.state {
+            ResponseState::Empty => return message("Send the request to see its response".into()),
+            ResponseState::Pending(None) => return message("Sending…".into()),
+            ResponseState::Pending(Some(receiving)) => {
+                return self.render_receiving(receiving, cx);
+            }
+            // What scripts printed may tell why a request failed.
+            ResponseState::Failed(error) if !self.console.is_empty() => {
+                return div()
+                    .v_flex()
+                    .size_full()
+                    .gap_2()
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(colors.danger)
+                            .child(error.clone()),
+                    )
+                    .child(self.render_console(cx));
+            }
+            ResponseState::Failed(error) => {
+                return message(error.clone()).text_color(colors.danger);
+            }
+            ResponseState::Cancelled => return message("Request cancelled".into()),
+            ResponseState::Received(response) => response,
+        };
+
+        div()
+            .v_flex()
+            .size_full()
+            .gap_2()
+            .child(
+                div()
+                    .h_flex()
+                    .gap_3()
+                    .text_sm()
+                    .child(
+                        div()
+                            .text_color(status_color(response.status, cx))
+                            .child(status_text(response.status)),
+                    )
+                    .child(
+                        div()
+                            .px_1()
+                            .rounded_sm()
+                            .bg(colors.muted)
+                            .text_color(colors.muted_foreground)
+                            .child(protocol_name(response.version)),
+                    )
+                    .child(
+                        div()
+                            .text_color(colors.muted_foreground)
+                            .child(format_size(response.body_len())),
+                    )
+                    .when(!response.encodings.is_empty(), |this| {
+                        let encodings = response
+                            .encodings
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                            .join(", ");
+
+                        this.child(
+                            div()
+                                .id("content-encoding")
+                                .px_1()
+                                .rounded_sm()
+                                .bg(colors.muted)
+                                .text_color(colors.muted_foreground)
+                                .child(format!(
+                                    "{encodings} {}",
+                                    format_size(response.raw_body_len())
+                                ))
+                                .tooltip(Tooltip::text(format!(
+                                    "Received {} with {encodings}, decoded to {}",
+                                    format_size(response.raw_body_len()),
+                                    format_size(response.body_len()),
+                                ))),
+                        )
+                    })
+                    .child(
+                        div()
+                            .text_color(colors.muted_foreground)
+                            .child(format_duration(response.timing.total)),
+                    )
+                    .when(!self.tests.is_empty(), |this| {
+                        let passed = self.tests.iter().filter(|test| test.passed()).count();
+
+                        this.child(
+                            div()
+                                .text_color(if passed == self.tests.len() {
+                                    colors.success
+                                } else {
+                                    colors.danger
+                                })
+                                .child(format!("{passed}/{} passed", self.tests.len())),
+                        )
+                    })
+                    .child(div().flex_1())
+                    .children(
+                        ResponseTab::ALL
+                            .into_iter()
+                            .filter(|tab| is_available(*tab, response, &self.console, &self.tests))
+                            .map(|tab| {
+                                Button::new(tab.label(), tab.label())
+                                    .style(ButtonStyle::Ghost)
+                                    .size(ButtonSize::Compact)
+                                    .selected(tab == self.tab)
+                                    .on_click(cx.listener(move |this, _, _, cx| {
+                                        this.tab = tab;
+                                        cx.notify();
+                                    }))
+                            }),
+                    ),
+            )
+            .map(|this| match self.tab {
+                ResponseTab::Body => this
+                    .child(
+                        div()
+                            .h_flex()
+                            .gap_2()
+                            .text_xs()
+                            .child(div().flex_1().when_some(
+                                response.decode_error.clone(),
+                                |this, error| {
+                                    this.text_color(colors.warning)
+                                        .child(format!("{error}, showing the body as received"))
+                                },
+                            ))
+                            .when_some(response.spilled.as_ref(), |this, spilled| {
+                                let file = if self.show_raw {
+                                    spilled.raw_body.clone()
+                                } else {
+                                    spilled.body.clone()
+                                };
+
+                                this.child(div().text_color(colors.muted_foreground).child(
+                                    format!(
+                                        "Showing the first {} of {}",
+                                        format_size(PREVIEW_SIZE as u64),
+                                        format_size(file.len())
+                                    ),
+                                ))
+                                .child(
+                                    Button::new("reveal-body", "Reveal File")
+                                        .style(ButtonStyle::Ghost)
+                                        .size(ButtonSize::Compact)
+                                        .tooltip("Show the temporary file holding the whole body")
+                                        .on_click(move |_, _, cx| cx.reveal_path(file.path())),
+                                )
+                            })
+                            .child(
+                                Button::new("raw-bytes", "Raw Bytes")
+                                    .style(ButtonStyle::Ghost)
+                                    .size(ButtonSize::Compact)
+                                    .selected(self.show_raw)
+                                    .tooltip("Show the bytes received as hex")
+                                    .on_click(cx.listener(|this, _, _, cx| this.toggle_raw(cx))),
+                            ),
+                    )
+                    .child(div().flex_1().min_h_0().child(self.body_editor.clone())),
+                ResponseTab::Headers => this.child(self.render_headers(&response.headers, cx)),
+                ResponseTab::Redirects => {
+                    this.child(self.render_redirects(&response.redirects, cx))
+                }
+                ResponseTab::Timing => this.child(self.render_timing(response, cx)),
+                ResponseTab::Tls => this.when_some(response.tls.as_ref(), |this, tls| {
+                    this.child(self.render_tls(tls, cx))
+                }),
+                ResponseTab::Tests => this.child(self.render_tests(cx)),
+                ResponseTab::Console => this.child(self.render_console(cx)),
+            })
+    }
+}
@@ -0,0 +1,449 @@
+use std::sync::Arc;
+
+use gpui::{
+    AnyElement, App, AppContext, Context, Div, ElementId, Entity, FontWeight, InteractiveElement,
+    IntoElement, ParentElement, Render, SharedString, Stateful, StatefulInteractiveElement, Styled,
+    Subscription, Window, actions, div, prelude::FluentBuilder,
+};
+use http_client::{GraphQlSchema, InputValue, SchemaType, TypeRef};
+use theme::ActiveTheme;
+use ui::{
+    components::{
+        dropdown::{Dropdown, DropdownEvent},
+        input::{InputEvent, TextInput},
+    },
+    placement::Placement,
+    traits::styled_ext::StyledExt,
+};
+use workspace::{Workspace, dock::Panel};
+
+use crate::graphql::SchemaStore;
+
+actions!(
+    request,
+    [
+        /// Shows or hides the types of the GraphQL schemas introspected so
+        /// far.
+        ToggleSchemaExplorer,
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &ToggleSchemaExplorer, window, cx| {
+            workspace.toggle_panel::<SchemaExplorer>(window, cx);
+        });
+    })
+    .detach();
+}
+
+/// A bottom dock panel browsing the types of an introspected GraphQL
+/// schema, along with their fields, arguments and values.
+pub struct SchemaExplorer {
+    store: Entity<SchemaStore>,
+    endpoint_dropdown: Entity<Dropdown>,
+    filter_input: Entity<TextInput>,
+    /// The name of the type whose details are shown.
+    selected_type: Option<String>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl SchemaExplorer {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let store = SchemaStore::global(cx);
+        let endpoint_dropdown = cx.new(|cx| {
+            Dropdown::new("schema-endpoint", Vec::<SharedString>::new(), cx)
+                .placeholder("No Schema")
+        });
+        let filter_input = cx.new(|cx| TextInput::new(cx).placeholder("Filter types"));
+
+        let subscriptions = vec![
+            cx.observe(&store, |this, _, cx| this.sync_endpoints(cx)),
+            cx.subscribe(&endpoint_dropdown, |this, dropdown, event, cx| {
+                let DropdownEvent::Changed(_) = event;
+                if let Some(endpoint) = dropdown.read(cx).selected().cloned() {
+                    this.store
+                        .update(cx, |store, cx| store.select(&endpoint, cx));
+                }
+            }),
+            cx.subscribe(&filter_input, |_, _, event, cx| {
+                if let InputEvent::Changed = event {
+                    cx.notify();
+                }
+            }),
+        ];
+
+        let mut this = Self {
+            store,
+            endpoint_dropdown,
+            filter_input,
+            selected_type: None,
+            _subscriptions: subscriptions,
+        };
+
+        this.sync_endpoints(cx);
+        this
+    }
+
+    /// Lists the endpoints of the store, showing the type selected before
+    /// when the schema still has it and its query type otherwise.
+    fn sync_endpoints(&mut self, cx: &mut Context<Self>) {
+        let (endpoints, selected) = {
+            let store = self.store.read(cx);
+            let endpoints = store.endpoints().map(str::to_string).collect::<Vec<_>>();
+            let selected = store
+                .selected()
+                .and_then(|endpoint| endpoints.iter().position(|known| known == endpoint));
+
+            (endpoints, selected)
+        };
+
+        self.endpoint_dropdown.update(cx, |dropdown, cx| {
+            dropdown.set_options(endpoints, cx);
+            dropdown.set_selected_index(selected, cx);
+        });
+
+        let schema = self.schema(cx);
+        let known = self
+            .selected_type
+            .as_ref()
+            .is_some_and(|name| schema.as_ref().is_some_and(|s| s.get_type(name).is_some()));
+        if !known {
+            self.selected_type = schema.and_then(|schema| schema.query_type.clone());
+        }
+
+        cx.notify();
+    }
+
+    fn schema(&self, cx: &App) -> Option<Arc<GraphQlSchema>> {
+        let store = self.store.read(cx);
+
+        store.selected().and_then(|endpoint| store.schema(endpoint))
+    }
+
+    fn select_type(&mut self, name: String, cx: &mut Context<Self>) {
+        self.selected_type = Some(name);
+        cx.notify();
+    }
+
+    fn render_types(&self, schema: &GraphQlSchema, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = *cx.theme().colors();
+        let filter = self.filter_input.read(cx).text().to_lowercase();
+        let roots = [
+            &schema.query_type,
+            &schema.mutation_type,
+            &schema.subscription_type,
+        ];
+        let is_root = |ty: &&SchemaType| roots.iter().any(|root| root.as_ref() == Some(&ty.name));
+
+        // The root types come first, then the others by name.
+        let types = schema
+            .user_types()
+            .filter(is_root)
+            .chain(schema.user_types().filter(|ty| !is_root(ty)))
+            .filter(|ty| ty.name.to_lowercase().contains(&filter));
+
+        div()
+            .id("schema-types")
+            .w_64()
+            .h_full()
+            .v_flex()
+            .overflow_y_scroll()
+            .border_r_1()
+            .border_color(colors.border)
+            .text_sm()
+            .children(types.map(|ty| {
+                let name = ty.name.clone();
+                let selected = self.selected_type.as_ref() == Some(&ty.name);
+
+                div()
+                    .id(ElementId::Name(ty.name.clone().into()))
+                    .h_flex()
+                    .justify_between()
+                    .gap_2()
+                    .px_2()
+                    .py_0p5()
+                    .cursor_pointer()
+                    .when(selected, |this| this.bg(colors.list_active))
+                    .hover(|this| this.bg(colors.list_hover))
+                    .on_click(cx.listener(move |this, _, _, cx| this.select_type(name.clone(), cx)))
+                    .child(div().truncate().child(ty.name.clone()))
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(colors.muted_foreground)
+                            .child(ty.kind.as_str()),
+                    )
+            }))
+    }
+
+    fn render_details(&self, ty: &SchemaType, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = *cx.theme().colors();
+        let mut rows: Vec<AnyElement> = Vec::new();
+
+        for (index, field) in ty.fields.iter().enumerate() {
+            let arguments = (!field.args.is_empty()).then(|| {
+                div()
+                    .h_flex()
+                    .flex_wrap()
+                    .child("(")
+                    .children(field.args.iter().enumerate().map(|(arg_index, arg)| {
+                        div()
+                            .h_flex()
+                            .when(arg_index > 0, |this| this.child(", "))
+                            .child(self.render_input_value(
+                                ElementId::Name(format!("argument-{index}-{arg_index}").into()),
+                                arg,
+                                cx,
+                            ))
+                    }))
+                    .child(")")
+            });
+
+            rows.push(
+                self.render_member(
+                    div()
+                        .h_flex()
+                        .flex_wrap()
+                        .child(field.name.clone())
+                        .children(arguments)
+                        .child(": ")
+                        .child(self.render_type_link(("field-type", index), &field.ty, cx))
+                        .when(field.is_deprecated, |this| {
+                            this.child(
+                                div()
+                                    .ml_2()
+                                    .text_xs()
+                                    .text_color(colors.warning)
+                                    .child("deprecated"),
+                            )
+                        }),
+                    field
+                        .deprecation_reason
+                        .as_ref()
+                        .filter(|_| field.is_deprecated)
+                        .or(field.description.as_ref()),
+                    cx,
+                )
+                .into_any_element(),
+            );
+        }
+
+        for (index, input_field) in ty.input_fields.iter().enumerate() {
+            let signature = self.render_input_value(("input-field", index), input_field, cx);
+
+            rows.push(
+                self.render_member(signature, input_field.description.as_ref(), cx)
+                    .into_any_element(),
+            );
+        }
+
+        for value in &ty.enum_values {
+            rows.push(
+                self.render_member(
+                    div()
+                        .h_flex()
+                        .child(value.name.clone())
+                        .when(value.is_deprecated, |this| {
+                            this.child(
+                                div()
+                                    .ml_2()
+                                    .text_xs()
+                                    .text_color(colors.warning)
+                                    .child("deprecated"),
+                            )
+                        }),
+                    value.description.as_ref(),
+                    cx,
+                )
+                .into_any_element(),
+            );
+        }
+
+        let interfaces = self.render_related("Implements", "interface", &ty.interfaces, cx);
+        let possible_types =
+            self.render_related("Possible types", "possible-type", &ty.possible_types, cx);
+
+        div()
+            .id("schema-type-details")
+            .flex_1()
+            .h_full()
+            .v_flex()
+            .gap_2()
+            .p_2()
+            .overflow_y_scroll()
+            .text_sm()
+            .child(
+                div()
+                    .h_flex()
+                    .gap_2()
+                    .child(
+                        div()
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .child(ty.name.clone()),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(colors.muted_foreground)
+                            .child(ty.kind.as_str()),
+                    ),
+            )
+            .when_some(ty.description.clone(), |this, description| {
+                this.child(div().text_color(colors.muted_foreground).child(description))
+            })
+            .children(interfaces)
+            .children(possible_types)
+            .child(div().v_flex().children(rows))
+    }
+
+    /// The interfaces or possible types of a type, each showing its type
+    /// when clicked.
+    fn render_related(
+        &self,
+        label: &'static str,
+        id: &'static str,
+        types: &[TypeRef],
+        cx: &mut Context<Self>,
+    ) -> Option<Div> {
+        if types.is_empty() {
+            return None;
+        }
+
+        let links = types
+            .iter()
+            .enumerate()
+            .map(|(index, ty)| {
+                self.render_type_link((id, index), ty, cx)
+                    .into_any_element()
+            })
+            .collect::<Vec<_>>();
+
+        Some(
+            div()
+                .h_flex()
+                .flex_wrap()
+                .gap_2()
+                .child(
+                    div()
+                        .text_color(cx.theme().colors().muted_foreground)
+                        .child(label),
+                )
+                .children(links),
+        )
+    }
+
+    /// A field, input field or enum value, with its description below.
+    fn render_member(
+        &self,
+        signature: impl IntoElement,
+        description: Option<&String>,
+        cx: &App,
+    ) -> Div {
+        let colors = *cx.theme().colors();
+
+        div()
+            .v_flex()
+            .py_1()
+            .border_b_1()
+            .border_color(colors.border)
+            .child(signature)
+            .when_some(description.cloned(), |this, description| {
+                this.child(
+                    div()
+                        .text_xs()
+                        .text_color(colors.muted_foreground)
+                        .child(description),
+                )
+            })
+    }
+
+    fn render_input_value(
+        &self,
+        id: impl Into<ElementId>,
+        value: &InputValue,
+        cx: &mut Context<Self>,
+    ) -> Div {
+        div()
+            .h_flex()
+            .child(value.name.clone())
+            .child(": ")
+            .child(self.render_type_link(id, &value.ty, cx))
+            .when_some(value.default_value.clone(), |this, default| {
+                this.child(format!(" = {default}"))
+            })
+    }
+
+    /// The type of a field or value, showing the named type when clicked.
+    fn render_type_link(
+        &self,
+        id: impl Into<ElementId>,
+        ty: &TypeRef,
+        cx: &mut Context<Self>,
+    ) -> Stateful<Div> {
+        let name = ty.name().to_string();
+
+        div()
+            .id(id.into())
+            .text_color(cx.theme().colors().accent)
+            .cursor_pointer()
+            .hover(|this| this.underline())
+            .on_click(cx.listener(move |this, _, _, cx| this.select_type(name.clone(), cx)))
+            .child(ty.to_string())
+    }
+}
+
+impl Panel for SchemaExplorer {
+    fn priority(&self) -> u32 {
+        0
+    }
+
+    fn placement(&self) -> Placement {
+        Placement::Bottom
+    }
+}
+
+impl Render for SchemaExplorer {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = *cx.theme().colors();
+        let schema = self.schema(cx);
+        let selected_type = schema
+            .as_ref()
+            .zip(self.selected_type.as_ref())
+            .and_then(|(schema, name)| schema.get_type(name));
+
+        div()
+            .v_flex()
+            .size_full()
+            .child(
+                div()
+                    .h_flex()
+                    .gap_2()
+                    .p_2()
+                    .border_b_1()
+                    .border_color(colors.border)
+                    .child(div().text_sm().child("GraphQL Schema"))
+                    .child(self.endpoint_dropdown.clone())
+                    .child(div().w_64().child(self.filter_input.clone())),
+            )
+            .map(|this| match &schema {
+                Some(schema) => this.child(
+                    div()
+                        .flex_1()
+                        .min_h_0()
+                        .h_flex()
+                        .child(self.render_types(schema, cx))
+                        .children(selected_type.map(|ty| self.render_details(ty, cx))),
+                ),
+                None => this.child(
+                    div()
+                        .p_2()
+                        .text_sm()
+                        .text_color(colors.muted_foreground)
+                        .child(
+                            "Introspect a GraphQL request to explore the schema of its endpoint.",
+                        ),
+                ),
+            })
+    }
+}
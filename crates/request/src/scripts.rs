@@ -0,0 +1,105 @@
+use collection::scripts::Scripts;
+use editor::{Editor, EditorEvent, language::Language};
+use gpui::{
+    App, AppContext, Context, Entity, EventEmitter, IntoElement, ParentElement, Render, Styled,
+    Subscription, Window, div,
+};
+use theme::ActiveTheme;
+use ui::traits::styled_ext::StyledExt;
+
+pub enum ScriptsEditorEvent {
+    Changed,
+}
+
+/// Edits the JavaScript run before a request is sent and after its
+/// response arrives.
+pub struct ScriptsEditor {
+    /// Says where the scripts run relative to the others.
+    description: &'static str,
+    pre_request_editor: Entity<Editor>,
+    post_response_editor: Entity<Editor>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ScriptsEditor {
+    pub fn new(description: &'static str, cx: &mut Context<Self>) -> Self {
+        let script_editor = |placeholder: &'static str, cx: &mut Context<Self>| {
+            cx.new(|cx| {
+                let mut editor = Editor::new(cx).placeholder(placeholder);
+                editor.set_language(Some(Language::JavaScript), cx);
+                editor
+            })
+        };
+        let pre_request_editor = script_editor("request.headers[\"X-Request-Id\"] = uuid()", cx);
+        let post_response_editor =
+            script_editor("env.set(\"token\", response.json().access_token)", cx);
+
+        let subscriptions = [&pre_request_editor, &post_response_editor]
+            .into_iter()
+            .map(|editor| {
+                cx.subscribe(editor, |_, _, event, cx| {
+                    let EditorEvent::Changed = event;
+                    cx.emit(ScriptsEditorEvent::Changed);
+                })
+            })
+            .collect();
+
+        Self {
+            description,
+            pre_request_editor,
+            post_response_editor,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    pub fn set_scripts(&mut self, scripts: &Scripts, cx: &mut Context<Self>) {
+        self.pre_request_editor.update(cx, |editor, cx| {
+            editor.set_text(scripts.pre_request.clone(), cx)
+        });
+        self.post_response_editor.update(cx, |editor, cx| {
+            editor.set_text(scripts.post_response.clone(), cx)
+        });
+    }
+
+    pub fn scripts(&self, cx: &App) -> Scripts {
+        Scripts {
+            pre_request: self.pre_request_editor.read(cx).text().to_string(),
+            post_response: self.post_response_editor.read(cx).text().to_string(),
+        }
+    }
+}
+
+impl EventEmitter<ScriptsEditorEvent> for ScriptsEditor {}
+
+impl Render for ScriptsEditor {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = cx.theme().colors();
+        let label = |label: &'static str| div().text_sm().child(label);
+
+        div()
+            .v_flex()
+            .flex_1()
+            .min_h_0()
+            .gap_2()
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(colors.muted_foreground)
+                    .child(self.description),
+            )
+            .child(label("Pre-request"))
+            .child(
+                div()
+                    .flex_1()
+                    .min_h_0()
+                    .child(self.pre_request_editor.clone()),
+            )
+            .child(label("Post-response"))
+            .child(
+                div()
+                    .flex_1()
+                    .min_h_0()
+                    .child(self.post_response_editor.clone()),
+            )
+    }
+}
@@ -0,0 +1,297 @@
+use std::collections::HashSet;
+
+use collection::{secrets::MASK, session::Session};
+use gpui::{
+    AppContext, Context, Div, Entity, IntoElement, ParentElement, Render, SharedString, Styled,
+    Subscription, Window, div,
+};
+use theme::ActiveTheme;
+use ui::{
+    components::{
+        button::{Button, ButtonSize, ButtonStyle},
+        icon::IconName,
+        icon_button::IconButton,
+        input::{InputEvent, TextInput},
+    },
+    traits::{clickable::Clickable, disableable::Disableable, styled_ext::StyledExt},
+};
+
+/// Edits which variables of the active environment are secret, and the
+/// values this user keeps for them in the vault of the collection.
+pub struct SecretsEditor {
+    session: Entity<Session>,
+    /// The secrets shown in clear rather than masked.
+    revealed: HashSet<String>,
+    /// The secret whose value is being changed, along with its input.
+    editing: Option<(String, Entity<TextInput>)>,
+    name_input: Entity<TextInput>,
+    value_input: Entity<TextInput>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl SecretsEditor {
+    pub fn new(session: Entity<Session>, cx: &mut Context<Self>) -> Self {
+        let name_input = cx.new(|cx| TextInput::new(cx).placeholder("Name"));
+        let value_input = cx.new(|cx| TextInput::new(cx).placeholder("Value"));
+
+        let subscriptions = vec![
+            cx.observe(&session, |_, _, cx| cx.notify()),
+            cx.subscribe(&name_input, |_, _, _: &InputEvent, cx| cx.notify()),
+            cx.subscribe(&value_input, |this, _, event, cx| {
+                if let InputEvent::PressEnter = event {
+                    this.add_secret(cx);
+                }
+            }),
+        ];
+
+        Self {
+            session,
+            revealed: HashSet::new(),
+            editing: None,
+            name_input,
+            value_input,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn environment(&self, cx: &Context<Self>) -> Option<String> {
+        self.session.read(cx).environment().map(str::to_string)
+    }
+
+    /// Marks the variable typed in as secret with the value typed in,
+    /// adding it to the active environment when it isn't there yet.
+    fn add_secret(&mut self, cx: &mut Context<Self>) {
+        let Some(environment) = self.environment(cx) else {
+            return;
+        };
+        let name = self.name_input.read(cx).text().trim().to_string();
+        let value = self.value_input.read(cx).text().to_string();
+        if name.is_empty() {
+            return;
+        }
+
+        self.session.update(cx, |session, cx| {
+            session.mark_secret(&environment, &name, cx);
+            session.set_secret(&environment, &name, Some(value), cx);
+        });
+        self.name_input
+            .update(cx, |input, cx| input.set_text("", cx));
+        self.value_input
+            .update(cx, |input, cx| input.set_text("", cx));
+    }
+
+    fn edit_secret(&mut self, name: String, cx: &mut Context<Self>) {
+        let value = self
+            .session
+            .read(cx)
+            .secrets(self.environment(cx).as_deref())
+            .and_then(|secrets| secrets.get(&name))
+            .cloned()
+            .unwrap_or_default();
+        let input = cx.new(|cx| TextInput::new(cx).placeholder("Value").default_text(value));
+
+        cx.subscribe(&input, |this, input, event, cx| {
+            if let InputEvent::PressEnter = event {
+                let value = input.read(cx).text().to_string();
+                this.store_edit(value, cx);
+            }
+        })
+        .detach();
+
+        self.editing = Some((name, input));
+        cx.notify();
+    }
+
+    fn store_edit(&mut self, value: String, cx: &mut Context<Self>) {
+        let (Some(environment), Some((name, _))) = (self.environment(cx), self.editing.take())
+        else {
+            return;
+        };
+
+        self.session.update(cx, |session, cx| {
+            session.set_secret(&environment, &name, Some(value), cx)
+        });
+        cx.notify();
+    }
+
+    fn render_secret(
+        &self,
+        index: usize,
+        name: &str,
+        value: Option<&String>,
+        cx: &mut Context<Self>,
+    ) -> Div {
+        let colors = *cx.theme().colors();
+        let revealed = self.revealed.contains(name);
+        let editing = self
+            .editing
+            .as_ref()
+            .filter(|(editing, _)| editing == name)
+            .map(|(_, input)| input.clone());
+
+        let value_cell = match (&editing, value) {
+            (Some(input), _) => div().flex_1().child(input.clone()),
+            (None, Some(value)) if revealed => div().flex_1().child(value.clone()),
+            (None, Some(_)) => div().flex_1().child(MASK),
+            (None, None) => div()
+                .flex_1()
+                .text_color(colors.muted_foreground)
+                .child("Not set on this machine"),
+        };
+
+        let toggled = name.to_string();
+        let edited = name.to_string();
+        let removed = name.to_string();
+
+        div()
+            .h_flex()
+            .gap_2()
+            .text_sm()
+            .child(div().w_32().child(name.to_string()))
+            .child(value_cell)
+            .child(
+                IconButton::new(
+                    ("reveal-secret", index),
+                    if revealed {
+                        IconName::EyeOff
+                    } else {
+                        IconName::Eye
+                    },
+                )
+                .size(ButtonSize::Compact)
+                .tooltip(if revealed { "Hide Value" } else { "Show Value" })
+                .on_click(cx.listener(move |this, _, _, cx| {
+                    if !this.revealed.remove(&toggled) {
+                        this.revealed.insert(toggled.clone());
+                    }
+                    cx.notify();
+                })),
+            )
+            .child(match editing {
+                Some(input) => IconButton::new(("store-secret", index), IconName::Check)
+                    .size(ButtonSize::Compact)
+                    .tooltip("Store Value")
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        let value = input.read(cx).text().to_string();
+                        this.store_edit(value, cx);
+                    })),
+                None => IconButton::new(("edit-secret", index), IconName::Pencil)
+                    .size(ButtonSize::Compact)
+                    .tooltip("Change Value")
+                    .on_click(
+                        cx.listener(move |this, _, _, cx| this.edit_secret(edited.clone(), cx)),
+                    ),
+            })
+            .child(
+                IconButton::new(("unmark-secret", index), IconName::Unlock)
+                    .size(ButtonSize::Compact)
+                    .tooltip("No Longer Secret, Forgetting the Value")
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        if let Some(environment) = this.environment(cx) {
+                            this.session.update(cx, |session, cx| {
+                                session.unmark_secret(&environment, &removed, cx)
+                            });
+                        }
+                    })),
+            )
+    }
+}
+
+impl Render for SecretsEditor {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = *cx.theme().colors();
+        let heading = div().text_sm().child("Secrets");
+
+        let Some(environment) = self.environment(cx) else {
+            return div().v_flex().gap_2().child(heading).child(
+                div()
+                    .text_xs()
+                    .text_color(colors.muted_foreground)
+                    .child("Choose an environment to manage its secrets."),
+            );
+        };
+
+        let session = self.session.read(cx);
+        let config = session
+            .environment_config(&environment)
+            .cloned()
+            .unwrap_or_default();
+        let values = session
+            .secrets(Some(&environment))
+            .cloned()
+            .unwrap_or_default();
+
+        let secrets: Vec<Div> = config
+            .secrets
+            .iter()
+            .enumerate()
+            .map(|(index, name)| self.render_secret(index, name, values.get(name), cx))
+            .collect();
+
+        let plain: Vec<Div> = config
+            .variables
+            .keys()
+            .filter(|name| !config.secrets.contains(*name))
+            .enumerate()
+            .map(|(index, name)| {
+                let marked = name.clone();
+                let environment = environment.clone();
+
+                div()
+                    .h_flex()
+                    .gap_2()
+                    .text_sm()
+                    .child(div().w_32().child(name.clone()))
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_color(colors.muted_foreground)
+                            .child("Plain text in collection.json"),
+                    )
+                    .child(
+                        Button::new(("mark-secret", index), "Make Secret")
+                            .style(ButtonStyle::Ghost)
+                            .size(ButtonSize::Compact)
+                            .tooltip("Move the Value to the Vault")
+                            .on_click(cx.listener(move |this, _, _, cx| {
+                                this.session.update(cx, |session, cx| {
+                                    session.mark_secret(&environment, &marked, cx)
+                                });
+                            })),
+                    )
+            })
+            .collect();
+
+        let can_add = !self.name_input.read(cx).text().trim().is_empty();
+
+        div()
+            .v_flex()
+            .gap_2()
+            .child(heading)
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(colors.muted_foreground)
+                    .child(SharedString::from(format!(
+                        "Values of secret variables of {environment} stay encrypted on this \
+                         machine, out of the collection files. Requests use them as {{{{name}}}}."
+                    ))),
+            )
+            .children(secrets)
+            .children(plain)
+            .child(
+                div()
+                    .h_flex()
+                    .gap_2()
+                    .child(div().w_32().child(self.name_input.clone()))
+                    .child(div().flex_1().child(self.value_input.clone()))
+                    .child(
+                        Button::new("add-secret", "Add Secret")
+                            .style(ButtonStyle::Primary)
+                            .size(ButtonSize::Compact)
+                            .disabled(!can_add)
+                            .on_click(cx.listener(|this, _, _, cx| this.add_secret(cx))),
+                    ),
+            )
+    }
+}
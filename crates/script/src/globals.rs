@@ -0,0 +1,222 @@
+//! What scripts find besides the language itself: `console`, `env`,
+//! `crypto`, `uuid()` and a few helpers.
+
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use base64::{Engine as _, prelude::BASE64_STANDARD};
+use md5::{Digest as _, Md5};
+use ring::{
+    digest, hmac,
+    rand::{SecureRandom, SystemRandom},
+};
+use rquickjs::{
+    Ctx, Exception, Function, Object, Result, Type, Value,
+    prelude::{Coerced, Opt, Rest, This},
+};
+
+use crate::{ConsoleLevel, ConsoleLine};
+
+/// The most bytes `crypto.randomBytes` returns at once.
+const MAX_RANDOM_BYTES: usize = 1024;
+
+/// Helpers written in JavaScript, run before each script.
+const PRELUDE: &str = "Date.iso = (millis = Date.now()) => new Date(millis).toISOString()";
+
+/// What scripts leave behind, shared by all the contexts they run in.
+#[derive(Default)]
+pub struct State {
+    pub console: Vec<ConsoleLine>,
+    pub variables: BTreeMap<String, String>,
+    pub changes: BTreeMap<String, Option<String>>,
+}
+
+/// Declares the globals of a script in the context of `ctx`.
+pub fn declare(ctx: &Ctx<'_>, state: &Rc<RefCell<State>>) -> Result<()> {
+    let globals = ctx.globals();
+
+    let console = Object::new(ctx.clone())?;
+    for (name, level) in [
+        ("log", ConsoleLevel::Log),
+        ("info", ConsoleLevel::Info),
+        ("warn", ConsoleLevel::Warn),
+        ("error", ConsoleLevel::Error),
+    ] {
+        let state = state.clone();
+        let log = move |values: Rest<Value<'_>>| -> Result<()> {
+            let message = values
+                .0
+                .into_iter()
+                .map(inspect)
+                .collect::<Result<Vec<_>>>()?
+                .join(" ");
+            state
+                .borrow_mut()
+                .console
+                .push(ConsoleLine { level, message });
+            Ok(())
+        };
+        console.set(name, Function::new(ctx.clone(), log)?.with_name(name)?)?;
+    }
+    globals.set("console", console)?;
+
+    let env = Object::new(ctx.clone())?;
+    let variables = state.clone();
+    env.set(
+        "get",
+        Function::new(ctx.clone(), move |name: Coerced<String>| {
+            variables.borrow().variables.get(&name.0).cloned()
+        })?,
+    )?;
+    let variables = state.clone();
+    env.set(
+        "has",
+        Function::new(ctx.clone(), move |name: Coerced<String>| {
+            variables.borrow().variables.contains_key(&name.0)
+        })?,
+    )?;
+    let variables = state.clone();
+    env.set(
+        "set",
+        Function::new(
+            ctx.clone(),
+            move |name: Coerced<String>, value: Coerced<String>| {
+                let mut state = variables.borrow_mut();
+                state.variables.insert(name.0.clone(), value.0.clone());
+                state.changes.insert(name.0, Some(value.0));
+            },
+        )?,
+    )?;
+    let variables = state.clone();
+    env.set(
+        "unset",
+        Function::new(ctx.clone(), move |name: Coerced<String>| {
+            let mut state = variables.borrow_mut();
+            state.variables.remove(&name.0);
+            state.changes.insert(name.0, None);
+        })?,
+    )?;
+    globals.set("env", env)?;
+
+    let crypto = Object::new(ctx.clone())?;
+    crypto.set(
+        "md5",
+        Function::new(
+            ctx.clone(),
+            |text: Coerced<String>, encoding: Opt<String>| encode(&Md5::digest(text.0), encoding),
+        )?,
+    )?;
+    for (name, algorithm) in [
+        ("sha1", &digest::SHA1_FOR_LEGACY_USE_ONLY),
+        ("sha256", &digest::SHA256),
+        ("sha512", &digest::SHA512),
+    ] {
+        let digest = move |text: Coerced<String>, encoding: Opt<String>| {
+            encode(
+                digest::digest(algorithm, text.0.as_bytes()).as_ref(),
+                encoding,
+            )
+        };
+        crypto.set(name, Function::new(ctx.clone(), digest)?)?;
+    }
+    for (name, algorithm) in [
+        ("hmacSha1", hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY),
+        ("hmacSha256", hmac::HMAC_SHA256),
+        ("hmacSha512", hmac::HMAC_SHA512),
+    ] {
+        let sign = move |key: Coerced<String>, text: Coerced<String>, encoding: Opt<String>| {
+            let key = hmac::Key::new(algorithm, key.0.as_bytes());
+            encode(hmac::sign(&key, text.0.as_bytes()).as_ref(), encoding)
+        };
+        crypto.set(name, Function::new(ctx.clone(), sign)?)?;
+    }
+    crypto.set(
+        "randomBytes",
+        Function::new(
+            ctx.clone(),
+            |ctx: Ctx<'_>, count: f64, encoding: Opt<String>| -> Result<String> {
+                if !(0.0..=MAX_RANDOM_BYTES as f64).contains(&count) {
+                    return Err(Exception::throw_range(
+                        &ctx,
+                        &format!("crypto.randomBytes returns at most {MAX_RANDOM_BYTES} bytes"),
+                    ));
+                }
+                Ok(encode(&random_bytes(count as usize), encoding))
+            },
+        )?,
+    )?;
+    crypto.set("base64Encode", Function::new(ctx.clone(), base64_encode)?)?;
+    crypto.set("base64Decode", Function::new(ctx.clone(), base64_decode)?)?;
+    globals.set("crypto", crypto)?;
+
+    // Unlike the browser ones, these take text of any script, as UTF-8.
+    globals.set("btoa", Function::new(ctx.clone(), base64_encode)?)?;
+    globals.set("atob", Function::new(ctx.clone(), base64_decode)?)?;
+    globals.set(
+        "uuid",
+        Function::new(ctx.clone(), || {
+            uuid::Builder::from_random_bytes(random_bytes(16).try_into().unwrap())
+                .into_uuid()
+                .to_string()
+        })?,
+    )?;
+
+    ctx.eval::<(), _>(PRELUDE)
+}
+
+/// The `json()` method of responses, parsing their body.
+pub fn response_json<'js>(ctx: Ctx<'js>, response: This<Object<'js>>) -> Result<Value<'js>> {
+    let body: Coerced<String> = response.0.get("body")?;
+    ctx.json_parse(body.0).map_err(|_| {
+        let error = ctx.catch();
+        let reason = error
+            .as_object()
+            .and_then(|error| error.get::<_, Coerced<String>>("message").ok())
+            .map_or_else(|| "invalid JSON".to_string(), |message| message.0);
+        Exception::throw_syntax(&ctx, &format!("the response body isn't JSON: {reason}"))
+    })
+}
+
+/// How `console` shows a value: strings as they are, arrays and objects as
+/// compact JSON, and anything else as JavaScript turns it into a string.
+pub fn inspect(value: Value<'_>) -> Result<String> {
+    if let Some(string) = value.as_string() {
+        return string.to_string();
+    }
+    if matches!(value.type_of(), Type::Array | Type::Object)
+        && let Some(json) = value.ctx().json_stringify(value.clone())?
+    {
+        return json.to_string();
+    }
+    Ok(value.get::<Coerced<String>>()?.0)
+}
+
+fn random_bytes(count: usize) -> Vec<u8> {
+    let mut bytes = vec![0; count];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .expect("the system should provide random bytes");
+    bytes
+}
+
+/// Encodes a digest as hex, or as base64 when asked for.
+fn encode(bytes: &[u8], encoding: Opt<String>) -> String {
+    if encoding.0.as_deref() == Some("base64") {
+        BASE64_STANDARD.encode(bytes)
+    } else {
+        bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+}
+
+fn base64_encode(text: Coerced<String>) -> String {
+    BASE64_STANDARD.encode(text.0)
+}
+
+fn base64_decode(ctx: Ctx<'_>, text: Coerced<String>) -> Result<String> {
+    match BASE64_STANDARD.decode(text.0.trim()) {
+        Ok(bytes) => Ok(String::from_utf8_lossy(&bytes).into_owned()),
+        Err(error) => Err(Exception::throw_message(
+            &ctx,
+            &format!("invalid base64: {error}"),
+        )),
+    }
+}
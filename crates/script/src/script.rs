@@ -0,0 +1,327 @@
+mod globals;
+
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    mem,
+    rc::Rc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::anyhow;
+use indexmap::IndexMap;
+use rquickjs::{
+    Context, Ctx, Function, Object, Runtime, Value, context::EvalOptions, prelude::Coerced,
+};
+
+use crate::globals::State;
+
+/// The stack scripts run on, with room to spare past `MAX_JS_STACK_SIZE`.
+const STACK_SIZE: usize = 16 << 20;
+
+/// How deep the calls of scripts may nest, in bytes of stack.
+const MAX_JS_STACK_SIZE: usize = 4 << 20;
+
+/// The memory scripts may use altogether, which bounds their strings and
+/// arrays.
+const MEMORY_LIMIT: usize = 64 << 20;
+
+/// A script along with where it comes from, which names it in errors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Script {
+    pub source: String,
+    pub origin: String,
+}
+
+/// The request as scripts see and change it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScriptRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+/// The response as scripts after it see it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScriptResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    pub time: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleLevel {
+    Log,
+    Info,
+    Warn,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsoleLine {
+    pub level: ConsoleLevel,
+    pub message: String,
+}
+
+/// What running scripts did.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScriptOutput {
+    /// The request once the scripts changed it.
+    pub request: ScriptRequest,
+    pub console: Vec<ConsoleLine>,
+    /// The variables the scripts set, or unset when `None`.
+    pub changes: BTreeMap<String, Option<String>>,
+    /// Why the scripts stopped early, if they did.
+    pub error: Option<String>,
+}
+
+/// Runs `scripts` one after another, stopping at the first that fails.
+///
+/// They share `request`, which they may change, and the `variables` of the
+/// environment, which they read and set through `env`. Scripts run after a
+/// response also see it as `response`. They can't reach files or the
+/// network, and stop once they take longer than `timeout` altogether.
+pub fn run(
+    scripts: Vec<Script>,
+    variables: BTreeMap<String, String>,
+    request: ScriptRequest,
+    response: Option<ScriptResponse>,
+    timeout: Duration,
+) -> ScriptOutput {
+    let fallback = request.clone();
+    let result = thread::Builder::new()
+        .name("script".into())
+        .stack_size(STACK_SIZE)
+        .spawn(move || run_now(scripts, variables, request, response, timeout))
+        .map_err(|error| anyhow!("couldn't start the script: {error}"))
+        .and_then(|thread| {
+            thread
+                .join()
+                .map_err(|_| anyhow!("the script crashed the interpreter"))
+        });
+
+    result.unwrap_or_else(|error| ScriptOutput {
+        request: fallback,
+        error: Some(error.to_string()),
+        ..Default::default()
+    })
+}
+
+fn run_now(
+    scripts: Vec<Script>,
+    variables: BTreeMap<String, String>,
+    mut request: ScriptRequest,
+    response: Option<ScriptResponse>,
+    timeout: Duration,
+) -> ScriptOutput {
+    let state = Rc::new(RefCell::new(State {
+        variables,
+        ..Default::default()
+    }));
+
+    let error = run_scripts(
+        &scripts,
+        &state,
+        &mut request,
+        response.as_ref(),
+        Instant::now() + timeout,
+    )
+    .err()
+    .map(|error| error.to_string());
+
+    let State {
+        console, changes, ..
+    } = state.take();
+    ScriptOutput {
+        request,
+        console,
+        changes,
+        error,
+    }
+}
+
+fn run_scripts(
+    scripts: &[Script],
+    state: &Rc<RefCell<State>>,
+    request: &mut ScriptRequest,
+    response: Option<&ScriptResponse>,
+    deadline: Instant,
+) -> anyhow::Result<()> {
+    let runtime = Runtime::new()?;
+    runtime.set_memory_limit(MEMORY_LIMIT);
+    runtime.set_max_stack_size(MAX_JS_STACK_SIZE);
+    // Interrupting throws an exception scripts can't catch.
+    runtime.set_interrupt_handler(Some(Box::new(move || Instant::now() >= deadline)));
+
+    for script in scripts {
+        // Each script gets globals of its own, so that its declarations
+        // don't clash with those of the scripts before.
+        let context = Context::full(&runtime)?;
+        context.with(|ctx| run_script(&ctx, script, state, request, response, deadline))?;
+    }
+    Ok(())
+}
+
+fn run_script(
+    ctx: &Ctx<'_>,
+    script: &Script,
+    state: &Rc<RefCell<State>>,
+    request: &mut ScriptRequest,
+    response: Option<&ScriptResponse>,
+    deadline: Instant,
+) -> anyhow::Result<()> {
+    let globals = ctx.globals();
+    globals::declare(ctx, state)?;
+    let request_object = request_to_object(ctx, request)?;
+    globals.set("request", request_object.clone())?;
+    if let Some(response) = response {
+        globals.set("response", response_to_object(ctx, response)?)?;
+    }
+
+    let mut options = EvalOptions::default();
+    options.strict = false;
+    options.filename = Some(script.origin.clone());
+    let result = ctx.eval_with_options::<(), _>(script.source.as_str(), options);
+    // Promise callbacks run once the script is done, and what they throw
+    // goes unnoticed as it would in a browser.
+    if result.is_ok() {
+        while ctx.execute_pending_job() {}
+    }
+
+    *request = request_from_object(&request_object, mem::take(request));
+    result.map_err(|_| {
+        if Instant::now() >= deadline {
+            anyhow!("{}: the script ran out of time", script.origin)
+        } else {
+            anyhow!("{}", describe_thrown(ctx.catch(), &script.origin))
+        }
+    })
+}
+
+/// Describes an error a script didn't catch, as `TypeError: message` when
+/// it is an error object, along with the line it was thrown at when known.
+fn describe_thrown(value: Value<'_>, origin: &str) -> String {
+    let property = |name: &str| {
+        value
+            .as_object()
+            .and_then(|object| object.get::<_, Option<Coerced<String>>>(name).ok())
+            .flatten()
+            .map(|value| value.0)
+    };
+
+    let description = match property("message") {
+        Some(message) => format!(
+            "{}: {message}",
+            property("name").unwrap_or_else(|| "Error".into())
+        ),
+        None => format!(
+            "Uncaught {}",
+            globals::inspect(value.clone()).unwrap_or_default()
+        ),
+    };
+
+    // Frames read `at name (origin:line:column)`, or `at origin:line:column`
+    // for syntax errors.
+    let line = property("stack").and_then(|stack| {
+        let prefix = format!("{origin}:");
+        stack.lines().find_map(|frame| {
+            let (_, position) = frame.split_once(&prefix)?;
+            let line = position.split(|c: char| !c.is_ascii_digit()).next()?;
+            line.parse::<u32>().ok()
+        })
+    });
+
+    match line {
+        Some(line) => format!("{origin}: line {line}: {description}"),
+        None => format!("{origin}: {description}"),
+    }
+}
+
+/// Headers as an object, joining the values of repeated names with commas.
+fn headers_to_object<'js>(
+    ctx: &Ctx<'js>,
+    headers: &[(String, String)],
+    lowercase: bool,
+) -> rquickjs::Result<Object<'js>> {
+    let mut joined: IndexMap<String, String> = IndexMap::new();
+    for (name, value) in headers {
+        let name = if lowercase {
+            name.to_ascii_lowercase()
+        } else {
+            name.clone()
+        };
+        joined
+            .entry(name)
+            .and_modify(|joined| {
+                joined.push_str(", ");
+                joined.push_str(value);
+            })
+            .or_insert_with(|| value.clone());
+    }
+
+    let object = Object::new(ctx.clone())?;
+    for (name, value) in joined {
+        object.set(name, value)?;
+    }
+    Ok(object)
+}
+
+fn request_to_object<'js>(
+    ctx: &Ctx<'js>,
+    request: &ScriptRequest,
+) -> rquickjs::Result<Object<'js>> {
+    let object = Object::new(ctx.clone())?;
+    object.set("method", request.method.as_str())?;
+    object.set("url", request.url.as_str())?;
+    object.set("headers", headers_to_object(ctx, &request.headers, false)?)?;
+    object.set("body", request.body.as_str())?;
+    Ok(object)
+}
+
+fn response_to_object<'js>(
+    ctx: &Ctx<'js>,
+    response: &ScriptResponse,
+) -> rquickjs::Result<Object<'js>> {
+    let object = Object::new(ctx.clone())?;
+    object.set("status", response.status)?;
+    object.set("headers", headers_to_object(ctx, &response.headers, true)?)?;
+    object.set("body", response.body.as_str())?;
+    object.set("time", response.time.as_secs_f64() * 1000.0)?;
+    object.set("json", Function::new(ctx.clone(), globals::response_json)?)?;
+    Ok(object)
+}
+
+/// Reads back the request scripts may have changed, keeping what they
+/// replaced with something that isn't a string as it was.
+fn request_from_object(object: &Object<'_>, original: ScriptRequest) -> ScriptRequest {
+    let string = |name: &str, original: String| match object.get::<_, Value>(name) {
+        Ok(value) if value.is_string() || value.is_number() || value.is_bool() => value
+            .get::<Coerced<String>>()
+            .map_or(original, |value| value.0),
+        _ => original,
+    };
+
+    let headers = match object.get::<_, Object>("headers") {
+        Ok(headers) => headers
+            .props::<String, Value>()
+            .filter_map(|property| {
+                let (name, value) = property.ok()?;
+                if value.is_null() || value.is_undefined() {
+                    return None;
+                }
+                Some((name, value.get::<Coerced<String>>().ok()?.0))
+            })
+            .collect(),
+        Err(_) => original.headers,
+    };
+
+    ScriptRequest {
+        method: string("method", original.method),
+        url: string("url", original.url),
+        headers,
+        body: string("body", original.body),
+    }
+}
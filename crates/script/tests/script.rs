@@ -0,0 +1,315 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use script::{ConsoleLevel, Script, ScriptOutput, ScriptRequest, ScriptResponse, run};
+
+fn request() -> ScriptRequest {
+    ScriptRequest {
+        method: "GET".into(),
+        url: "https://example.com/items".into(),
+        headers: vec![("Accept".into(), "application/json".into())],
+        body: String::new(),
+    }
+}
+
+fn script(source: &str) -> Script {
+    Script {
+        source: source.into(),
+        origin: "request".into(),
+    }
+}
+
+fn run_one(source: &str) -> ScriptOutput {
+    run(
+        vec![script(source)],
+        BTreeMap::new(),
+        request(),
+        None,
+        Duration::from_secs(5),
+    )
+}
+
+fn logged(output: &ScriptOutput) -> Vec<&str> {
+    output
+        .console
+        .iter()
+        .map(|line| line.message.as_str())
+        .collect()
+}
+
+#[test]
+fn evaluates_javascript() {
+    let output = run_one(
+        r#"
+        function fib(n) {
+            return n < 2 ? n : fib(n - 1) + fib(n - 2)
+        }
+        const squares = [1, 2, 3].map(n => n * n)
+        let total = 0
+        for (const square of squares) total += square
+        const user = { name: "ada", roles: ["admin"] }
+        console.log(fib(10), total, `hi ${user.name.toUpperCase()}`)
+        console.log(JSON.stringify({ squares, missing: undefined }), user?.address?.city ?? "none")
+        console.log(typeof nothing, [3, 1, 2].sort().join("-"), "a,b".split(","))
+        console.log(0.1 + 0.2 > 0.3, 7 % 3, 2 ** 10, parseInt("0x1f"), (1.005).toFixed(1))
+        "#,
+    );
+
+    assert_eq!(output.error, None);
+    assert_eq!(
+        logged(&output),
+        [
+            "55 14 hi ADA",
+            r#"{"squares":[1,4,9]} none"#,
+            r#"undefined 1-2-3 ["a","b"]"#,
+            "true 1 1024 31 1.0",
+        ]
+    );
+}
+
+#[test]
+fn supports_the_whole_language() {
+    let output = run_one(
+        r#"
+        class Point {
+            constructor(x, y) { Object.assign(this, { x, y }) }
+            get length() { return Math.hypot(this.x, this.y) }
+        }
+        const { x, ...rest } = { ...new Point(3, 4), z: 5 }
+        const [first, , third = "none"] = [1, 2]
+        console.log(new Point(3, 4).length, x, rest, first, third)
+        console.log("a1b22c333".match(/\d+/g), /^ab/i.test("ABC"), "a-b".replace(/-/, "+"))
+        console.log(new Date(0).toISOString(), Date.iso(0), [3, 1, 2].sort(), [10, 9, 1].sort())
+        const seen = new Map([["a", 1]])
+        console.log(seen.get("a"), [...new Set([1, 1, 2])], Array.from("ab").reverse())
+        Promise.resolve(42).then(value => console.log("then", value))
+        "#,
+    );
+
+    assert_eq!(output.error, None);
+    assert_eq!(
+        logged(&output),
+        [
+            r#"5 3 {"y":4,"z":5} 1 none"#,
+            r#"["1","22","333"] true a+b"#,
+            r#"1970-01-01T00:00:00.000Z 1970-01-01T00:00:00.000Z [1,2,3] [1,10,9]"#,
+            r#"1 [1,2] ["b","a"]"#,
+            "then 42",
+        ]
+    );
+}
+
+#[test]
+fn catches_thrown_errors() {
+    let output = run_one(
+        r#"
+        try {
+            missing.value
+        } catch (error) {
+            console.warn(error.name, error.message)
+        }
+        try {
+            throw { code: 42 }
+        } catch (error) {
+            console.error(error.code)
+        }
+        try {
+            atob("%")
+        } catch ({ message }) {
+            console.log(message.startsWith("invalid base64"))
+        }
+        try {
+            crypto.randomBytes(4096)
+        } catch (error) {
+            console.log(error instanceof RangeError)
+        }
+        "#,
+    );
+
+    assert_eq!(output.error, None);
+    assert_eq!(output.console[0].level, ConsoleLevel::Warn);
+    assert_eq!(
+        logged(&output),
+        [
+            "ReferenceError missing is not defined",
+            "42",
+            "true",
+            "true"
+        ]
+    );
+}
+
+#[test]
+fn reports_where_scripts_fail() {
+    let output = run_one("const a = 1\n\nnull.b");
+    assert_eq!(
+        output.error.as_deref(),
+        Some("request: line 3: TypeError: cannot read property 'b' of null")
+    );
+
+    let output = run_one("const a = 1\nlet b = ;");
+    assert!(
+        output
+            .error
+            .unwrap()
+            .starts_with("request: line 2: SyntaxError:")
+    );
+
+    // Only errors know where they were thrown.
+    let output = run_one("throw 'nope'");
+    assert_eq!(output.error.as_deref(), Some("request: Uncaught nope"));
+
+    let output = run_one("\nthrow new Error('x')");
+    assert_eq!(output.error.as_deref(), Some("request: line 2: Error: x"));
+}
+
+#[test]
+fn changes_the_request() {
+    let output = run_one(
+        r#"
+        request.headers["X-Signature"] = crypto.hmacSha256("key", request.method + request.url)
+        request.headers.Accept = undefined
+        request.url += "?page=2"
+        request.body = JSON.stringify({ id: uuid() })
+        "#,
+    );
+
+    assert_eq!(output.error, None);
+    assert_eq!(output.request.url, "https://example.com/items?page=2");
+    assert_eq!(output.request.headers.len(), 1);
+    assert_eq!(output.request.headers[0].0, "X-Signature");
+    assert_eq!(output.request.headers[0].1.len(), 64);
+    assert!(output.request.body.starts_with(r#"{"id":""#));
+}
+
+#[test]
+fn computes_digests() {
+    let output = run_one(
+        r#"
+        console.log(crypto.md5("abc"))
+        console.log(crypto.sha256("abc", "base64"))
+        console.log(crypto.hmacSha1("key", "The quick brown fox jumps over the lazy dog"))
+        console.log(btoa("user:pass"), atob("dXNlcjpwYXNz"), encodeURIComponent("a b&c"))
+        "#,
+    );
+
+    assert_eq!(output.error, None);
+    assert_eq!(
+        logged(&output),
+        [
+            "900150983cd24fb0d6963f7d28e17f72",
+            "ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0=",
+            "de7c9b85b8b78aa6bc8a7a36f70a90701c9db4d9",
+            "dXNlcjpwYXNz user:pass a%20b%26c",
+        ]
+    );
+}
+
+#[test]
+fn reads_and_sets_variables() {
+    let variables = BTreeMap::from([
+        ("base".to_string(), "https://api".to_string()),
+        ("stale".to_string(), "1".to_string()),
+    ]);
+    let response = ScriptResponse {
+        status: 201,
+        headers: vec![
+            ("Content-Type".into(), "application/json".into()),
+            ("Set-Cookie".into(), "a=1".into()),
+            ("Set-Cookie".into(), "b=2".into()),
+        ],
+        body: r#"{"token":"abc","items":[1,2]}"#.into(),
+        time: Duration::from_millis(12),
+    };
+
+    let output = run(
+        vec![
+            script(r#"env.set("seen", env.get("base") + "/seen")"#),
+            script(
+                r#"
+                if (response.status === 201) env.set("token", response.json().token)
+                env.unset("stale")
+                console.log(env.has("seen"), response.headers["set-cookie"], response.time)
+                "#,
+            ),
+        ],
+        variables,
+        request(),
+        Some(response),
+        Duration::from_secs(5),
+    );
+
+    assert_eq!(output.error, None);
+    assert_eq!(logged(&output), ["true a=1, b=2 12"]);
+    assert_eq!(
+        output.changes,
+        BTreeMap::from([
+            ("seen".to_string(), Some("https://api/seen".to_string())),
+            ("stale".to_string(), None),
+            ("token".to_string(), Some("abc".to_string())),
+        ])
+    );
+}
+
+#[test]
+fn stops_at_the_first_failing_script() {
+    let output = run(
+        vec![
+            script("console.log('first')"),
+            Script {
+                source: "undefinedFunction()".into(),
+                origin: "folder api".into(),
+            },
+            script("console.log('never')"),
+        ],
+        BTreeMap::new(),
+        request(),
+        None,
+        Duration::from_secs(5),
+    );
+
+    assert_eq!(logged(&output), ["first"]);
+    assert_eq!(
+        output.error.as_deref(),
+        Some("folder api: line 1: ReferenceError: undefinedFunction is not defined")
+    );
+}
+
+#[test]
+fn stops_runaway_scripts() {
+    let output = run(
+        vec![script("while (true) {}")],
+        BTreeMap::new(),
+        request(),
+        None,
+        Duration::from_millis(100),
+    );
+    assert_eq!(
+        output.error.as_deref(),
+        Some("request: the script ran out of time")
+    );
+
+    // Running out of time can't be caught.
+    let output = run(
+        vec![script(
+            "try { for (;;) {} } catch (e) { console.log('caught') }",
+        )],
+        BTreeMap::new(),
+        request(),
+        None,
+        Duration::from_millis(100),
+    );
+    assert!(output.console.is_empty());
+    assert!(output.error.is_some());
+
+    let output = run_one("function again() { return again() }\nagain()");
+    assert_eq!(
+        output.error.as_deref(),
+        Some("request: line 1: RangeError: Maximum call stack size exceeded")
+    );
+
+    let output = run_one("let text = 'x'\nwhile (true) text += text");
+    assert_eq!(
+        output.error.as_deref(),
+        Some("request: line 2: InternalError: out of memory")
+    );
+}
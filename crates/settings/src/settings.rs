@@ -5,18 +5,24 @@ use gpui::App;
 use rust_embed::Embed as RustEmbed;
 use util::asset_str;
 
-use crate::settings_store::SettingsStore;
-
 mod settings_content;
 mod settings_store;
 
+pub use settings_content::*;
+pub use settings_store::{Settings, SettingsHandle, SettingsStore};
+
 pub fn init(cx: &mut App) {
     let builder = ConfigBuilder::<AsyncState>::default().add_source(config::File::from_str(
         &default_settings(),
         config::FileFormat::Json5,
     ));
 
-    cx.set_global(SettingsStore::new(builder));
+    let mut store = SettingsStore::new(builder);
+
+    cx.background_executor()
+        .block(store.reload())
+        .expect("default settings must be valid");
+    cx.set_global(store);
 }
 
 #[derive(RustEmbed)]
@@ -1,4 +1,62 @@
+use http_client::{HttpVersion, NoProxy, Proxy};
 use serde::Deserialize;
 
 #[derive(Deserialize)]
-pub struct SettingsContent {}
+pub struct SettingsContent {
+    #[serde(default)]
+    pub http: HttpSettingsContent,
+    #[serde(default)]
+    pub proxy: ProxySettingsContent,
+    #[serde(default)]
+    pub redirects: RedirectSettingsContent,
+    #[serde(default)]
+    pub timeouts: TimeoutSettingsContent,
+}
+
+#[derive(Default, Deserialize)]
+pub struct HttpSettingsContent {
+    /// The HTTP version requests use unless they pick their own.
+    pub version: Option<HttpVersion>,
+    /// How many megabytes of a response body are kept in memory before it
+    /// is written to a temporary file instead.
+    pub max_body_in_memory_mb: Option<u64>,
+}
+
+#[derive(Default, Deserialize)]
+pub struct ProxySettingsContent {
+    /// The proxy every request goes through unless it picks its own, instead
+    /// of those from the environment.
+    pub url: Option<Proxy>,
+    /// Hosts to connect to directly, replacing `NO_PROXY`.
+    pub no_proxy: Option<NoProxy>,
+    /// Whether to read `HTTP_PROXY`, `HTTPS_PROXY`, `ALL_PROXY` and
+    /// `NO_PROXY` when no url is set.
+    pub use_environment: Option<bool>,
+}
+
+#[derive(Default, Deserialize)]
+pub struct RedirectSettingsContent {
+    /// Whether requests follow redirects unless they say otherwise.
+    pub follow: Option<bool>,
+    /// How many redirects a request follows before failing.
+    pub max_hops: Option<usize>,
+    /// Whether 301 and 302 redirects resend the method and body instead of
+    /// switching to GET.
+    pub keep_method: Option<bool>,
+    /// Whether the `Authorization` and `Cookie` headers follow redirects to
+    /// other origins.
+    pub forward_auth: Option<bool>,
+}
+
+#[derive(Default, Deserialize)]
+pub struct TimeoutSettingsContent {
+    /// Seconds to wait for a connection to open, or 0 to wait forever.
+    pub connect: Option<f64>,
+    /// Seconds to wait for the response to start and for each chunk of its
+    /// body, or 0 to wait forever.
+    pub read: Option<f64>,
+    /// Seconds a whole request may take, or 0 to wait forever.
+    pub total: Option<f64>,
+    /// Seconds the scripts run around a request may take altogether.
+    pub script: Option<f64>,
+}
@@ -22,7 +22,7 @@ pub trait Settings: 'static + Send + Sync + Sized {
         });
     }
 
-    fn get<'a>(cx: &'a App) -> &'a Self
+    fn get(cx: &App) -> &Self
     where
         Self: Sized,
     {
@@ -63,6 +63,7 @@ pub trait Settings: 'static + Send + Sync + Sized {
 }
 
 pub trait SettingsHandle: 'static + Send + Sync {
+    #[allow(clippy::wrong_self_convention)]
     fn from_settings(&self, s: &SettingsContent) -> Box<dyn Any>;
 
     fn value(&self) -> &dyn Any;
@@ -19,6 +19,7 @@ pub struct ColorScale {
 
 impl ColorScale {
     /// Create a new color scale from Hsla values
+    #[allow(clippy::too_many_arguments)]
     pub const fn new(
         shade_50: Hsla,
         shade_100: Hsla,
@@ -0,0 +1,131 @@
+use gpui::{FontStyle, FontWeight, HighlightStyle, Hsla};
+
+use crate::scales::*;
+
+/// Maps tree-sitter capture names such as `string.special.key` to styles.
+#[derive(Debug, Default, Clone)]
+pub struct SyntaxTheme {
+    highlights: Vec<(&'static str, HighlightStyle)>,
+}
+
+impl SyntaxTheme {
+    pub fn light() -> Self {
+        let zinc = zinc();
+
+        Self::new(Palette {
+            comment: zinc.shade_500,
+            keyword: violet().shade_700,
+            string: green().shade_700,
+            escape: teal().shade_700,
+            number: orange().shade_700,
+            constant: orange().shade_700,
+            property: sky().shade_700,
+            function: blue().shade_700,
+            type_: amber().shade_700,
+            tag: rose().shade_700,
+            attribute: amber().shade_700,
+            variable: zinc.shade_900,
+            punctuation: zinc.shade_500,
+            error: red().shade_600,
+        })
+    }
+
+    pub fn dark() -> Self {
+        let zinc = zinc();
+
+        Self::new(Palette {
+            comment: zinc.shade_500,
+            keyword: violet().shade_400,
+            string: green().shade_400,
+            escape: teal().shade_300,
+            number: orange().shade_300,
+            constant: orange().shade_300,
+            property: sky().shade_300,
+            function: blue().shade_400,
+            type_: amber().shade_300,
+            tag: rose().shade_400,
+            attribute: amber().shade_300,
+            variable: zinc.shade_100,
+            punctuation: zinc.shade_400,
+            error: red().shade_400,
+        })
+    }
+
+    fn new(palette: Palette) -> Self {
+        let color = |color: Hsla| HighlightStyle {
+            color: Some(color),
+            ..Default::default()
+        };
+
+        Self {
+            highlights: vec![
+                (
+                    "comment",
+                    HighlightStyle {
+                        color: Some(palette.comment),
+                        font_style: Some(FontStyle::Italic),
+                        ..Default::default()
+                    },
+                ),
+                ("keyword", color(palette.keyword)),
+                ("operator", color(palette.punctuation)),
+                ("string.special.key", color(palette.property)),
+                ("string", color(palette.string)),
+                ("escape", color(palette.escape)),
+                ("number", color(palette.number)),
+                ("boolean", color(palette.constant)),
+                ("constant", color(palette.constant)),
+                ("label", color(palette.constant)),
+                ("property", color(palette.property)),
+                ("function", color(palette.function)),
+                ("constructor", color(palette.type_)),
+                ("type", color(palette.type_)),
+                ("tag.error", color(palette.error)),
+                (
+                    "tag",
+                    HighlightStyle {
+                        color: Some(palette.tag),
+                        font_weight: Some(FontWeight::MEDIUM),
+                        ..Default::default()
+                    },
+                ),
+                ("attribute", color(palette.attribute)),
+                ("variable.builtin", color(palette.keyword)),
+                ("variable", color(palette.variable)),
+                ("embedded", color(palette.variable)),
+                ("punctuation", color(palette.punctuation)),
+            ],
+        }
+    }
+
+    /// Resolves `name`, falling back to its dotted prefixes so that
+    /// `punctuation.bracket` uses the `punctuation` style.
+    pub fn get(&self, name: &str) -> Option<HighlightStyle> {
+        let mut name = name;
+
+        loop {
+            if let Some((_, style)) = self.highlights.iter().find(|(key, _)| *key == name) {
+                return Some(*style);
+            }
+
+            name = &name[..name.rfind('.')?];
+        }
+    }
+}
+
+struct Palette {
+    comment: Hsla,
+    keyword: Hsla,
+    string: Hsla,
+    escape: Hsla,
+    number: Hsla,
+    constant: Hsla,
+    property: Hsla,
+    function: Hsla,
+    type_: Hsla,
+    tag: Hsla,
+    attribute: Hsla,
+    variable: Hsla,
+    punctuation: Hsla,
+    error: Hsla,
+}
@@ -1,18 +1,20 @@
-mod colors;
-mod scales;
+pub mod colors;
+pub mod scales;
+pub mod syntax;
 
 use std::sync::Arc;
 
 use derive_more::{Deref, DerefMut};
 use gpui::{App, BorrowAppContext, Global, WindowAppearance};
 
-use crate::colors::ThemeColors;
+use crate::{colors::ThemeColors, syntax::SyntaxTheme};
 
 pub fn init(cx: &mut App) {
     SystemAppearance::init(cx);
 
     let default_theme = Theme {
         colors: ThemeColors::dark(),
+        syntax: Arc::new(SyntaxTheme::dark()),
         appearance: ThemeAppearance::Dark,
     };
 
@@ -87,6 +89,7 @@ impl SystemAppearance {
 
 pub struct Theme {
     pub colors: ThemeColors,
+    pub syntax: Arc<SyntaxTheme>,
     pub appearance: ThemeAppearance,
 }
 
@@ -96,6 +99,11 @@ impl Theme {
         &self.colors
     }
 
+    #[inline]
+    pub fn syntax(&self) -> &Arc<SyntaxTheme> {
+        &self.syntax
+    }
+
     #[inline]
     pub fn appearance(&self) -> ThemeAppearance {
         self.appearance
@@ -112,13 +120,14 @@ impl GlobalTheme {
     pub fn reload_theme(cx: &mut App) {
         let system = SystemAppearance::global(cx);
 
-        let colors = match system.0 {
-            ThemeAppearance::Light => ThemeColors::light(),
-            ThemeAppearance::Dark => ThemeColors::dark(),
+        let (colors, syntax) = match system.0 {
+            ThemeAppearance::Light => (ThemeColors::light(), SyntaxTheme::light()),
+            ThemeAppearance::Dark => (ThemeColors::dark(), SyntaxTheme::dark()),
         };
 
         let theme = Theme {
             colors,
+            syntax: Arc::new(syntax),
             appearance: system.0,
         };
 
@@ -0,0 +1,35 @@
+use gpui::{App, Hsla};
+use theme::ActiveTheme;
+
+/// A semantic color resolved against the active theme at render time.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Color {
+    #[default]
+    Default,
+    Muted,
+    Accent,
+    Disabled,
+    Danger,
+    Warning,
+    Success,
+    Info,
+    Custom(Hsla),
+}
+
+impl Color {
+    pub fn hsla(&self, cx: &App) -> Hsla {
+        let colors = cx.theme().colors();
+
+        match self {
+            Color::Default => colors.foreground,
+            Color::Muted => colors.muted_foreground,
+            Color::Accent => colors.primary,
+            Color::Disabled => colors.muted_foreground.opacity(0.6),
+            Color::Danger => colors.danger,
+            Color::Warning => colors.warning,
+            Color::Success => colors.success,
+            Color::Info => colors.info,
+            Color::Custom(color) => *color,
+        }
+    }
+}
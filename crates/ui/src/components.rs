@@ -1 +1,10 @@
+pub mod button;
+pub mod checkbox;
+pub mod context_menu;
+pub mod dropdown;
+pub mod icon;
+pub mod icon_button;
+pub mod input;
 pub mod root;
+pub mod table;
+pub mod tooltip;
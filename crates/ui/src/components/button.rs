@@ -0,0 +1,214 @@
+use gpui::{
+    App, ClickEvent, CursorStyle, Div, ElementId, Hsla, InteractiveElement, IntoElement,
+    ParentElement, RenderOnce, SharedString, Stateful, StatefulInteractiveElement, Styled, Window,
+    div, prelude::FluentBuilder,
+};
+use theme::ActiveTheme;
+
+use crate::{
+    components::tooltip::Tooltip,
+    traits::{clickable::Clickable, disableable::Disableable, styled_ext::StyledExt},
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonStyle {
+    Primary,
+    #[default]
+    Secondary,
+    Ghost,
+    Danger,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonSize {
+    Compact,
+    #[default]
+    Default,
+    Large,
+}
+
+pub(crate) struct ButtonColors {
+    pub background: Hsla,
+    pub hover: Hsla,
+    pub active: Hsla,
+    pub foreground: Hsla,
+}
+
+impl ButtonStyle {
+    pub(crate) fn colors(&self, selected: bool, cx: &App) -> ButtonColors {
+        let colors = cx.theme().colors();
+
+        match self {
+            ButtonStyle::Primary => ButtonColors {
+                background: colors.primary,
+                hover: colors.primary_hover,
+                active: colors.primary_active,
+                foreground: colors.primary_foreground,
+            },
+            ButtonStyle::Secondary => ButtonColors {
+                background: if selected {
+                    colors.secondary_active
+                } else {
+                    colors.secondary
+                },
+                hover: colors.secondary_hover,
+                active: colors.secondary_active,
+                foreground: colors.secondary_foreground,
+            },
+            ButtonStyle::Ghost => ButtonColors {
+                background: if selected {
+                    colors.accent
+                } else {
+                    gpui::transparent_black()
+                },
+                hover: colors.list_hover,
+                active: colors.accent,
+                foreground: if selected {
+                    colors.accent_foreground
+                } else {
+                    colors.foreground
+                },
+            },
+            ButtonStyle::Danger => ButtonColors {
+                background: colors.danger,
+                hover: colors.danger_hover,
+                active: colors.danger_active,
+                foreground: colors.danger_foreground,
+            },
+        }
+    }
+}
+
+type ClickHandler = Box<dyn Fn(&ClickEvent, &mut Window, &mut App) + 'static>;
+
+/// The interactive shell shared by [`Button`] and
+/// [`crate::components::icon_button::IconButton`].
+pub(crate) struct ButtonBase {
+    pub id: ElementId,
+    pub style: ButtonStyle,
+    pub size: ButtonSize,
+    pub disabled: bool,
+    pub selected: bool,
+    pub tooltip: Option<SharedString>,
+    pub cursor_style: CursorStyle,
+    pub on_click: Option<ClickHandler>,
+}
+
+impl ButtonBase {
+    pub fn new(id: impl Into<ElementId>) -> Self {
+        Self {
+            id: id.into(),
+            style: ButtonStyle::default(),
+            size: ButtonSize::default(),
+            disabled: false,
+            selected: false,
+            tooltip: None,
+            cursor_style: CursorStyle::PointingHand,
+            on_click: None,
+        }
+    }
+
+    pub fn render(self, cx: &App) -> Stateful<Div> {
+        let colors = self.style.colors(self.selected, cx);
+        let theme = cx.theme().colors();
+
+        div()
+            .id(self.id)
+            .h_flex()
+            .flex_none()
+            .justify_center()
+            .gap_1()
+            .rounded_sm()
+            .map(|this| match self.size {
+                ButtonSize::Compact => this.h_6().px_1p5().text_xs(),
+                ButtonSize::Default => this.h_7().px_2().text_sm(),
+                ButtonSize::Large => this.h_8().px_3(),
+            })
+            .when(self.style == ButtonStyle::Secondary, |this| {
+                this.border_1().border_color(theme.input)
+            })
+            .map(|this| {
+                if self.disabled {
+                    this.bg(colors.background)
+                        .text_color(theme.muted_foreground)
+                        .opacity(0.6)
+                        .cursor(CursorStyle::OperationNotAllowed)
+                } else {
+                    this.bg(colors.background)
+                        .text_color(colors.foreground)
+                        .cursor(self.cursor_style)
+                        .hover(|this| this.bg(colors.hover))
+                        .active(|this| this.bg(colors.active))
+                        .when_some(self.on_click, |this, on_click| {
+                            this.on_click(move |event, window, cx| {
+                                cx.stop_propagation();
+                                on_click(event, window, cx)
+                            })
+                        })
+                }
+            })
+            .when_some(self.tooltip, |this, tooltip| {
+                this.tooltip(Tooltip::text(tooltip))
+            })
+    }
+}
+
+#[derive(IntoElement)]
+pub struct Button {
+    base: ButtonBase,
+    label: SharedString,
+}
+
+impl Button {
+    pub fn new(id: impl Into<ElementId>, label: impl Into<SharedString>) -> Self {
+        Self {
+            base: ButtonBase::new(id),
+            label: label.into(),
+        }
+    }
+
+    pub fn style(mut self, style: ButtonStyle) -> Self {
+        self.base.style = style;
+        self
+    }
+
+    pub fn size(mut self, size: ButtonSize) -> Self {
+        self.base.size = size;
+        self
+    }
+
+    pub fn selected(mut self, selected: bool) -> Self {
+        self.base.selected = selected;
+        self
+    }
+
+    pub fn tooltip(mut self, tooltip: impl Into<SharedString>) -> Self {
+        self.base.tooltip = Some(tooltip.into());
+        self
+    }
+}
+
+impl Clickable for Button {
+    fn on_click(mut self, handler: impl Fn(&ClickEvent, &mut Window, &mut App) + 'static) -> Self {
+        self.base.on_click = Some(Box::new(handler));
+        self
+    }
+
+    fn cursor_style(mut self, cursor_style: CursorStyle) -> Self {
+        self.base.cursor_style = cursor_style;
+        self
+    }
+}
+
+impl Disableable for Button {
+    fn disabled(mut self, disabled: bool) -> Self {
+        self.base.disabled = disabled;
+        self
+    }
+}
+
+impl RenderOnce for Button {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        self.base.render(cx).child(self.label)
+    }
+}
@@ -0,0 +1,98 @@
+use gpui::{
+    App, CursorStyle, ElementId, InteractiveElement, IntoElement, ParentElement, RenderOnce,
+    SharedString, StatefulInteractiveElement, Styled, Window, div, prelude::FluentBuilder,
+};
+use theme::ActiveTheme;
+
+use crate::{
+    color::Color,
+    components::icon::{Icon, IconName, IconSize},
+    traits::{disableable::Disableable, styled_ext::StyledExt},
+};
+
+type ToggleHandler = Box<dyn Fn(&bool, &mut Window, &mut App) + 'static>;
+
+#[derive(IntoElement)]
+pub struct Checkbox {
+    id: ElementId,
+    checked: bool,
+    label: Option<SharedString>,
+    disabled: bool,
+    on_toggle: Option<ToggleHandler>,
+}
+
+impl Checkbox {
+    pub fn new(id: impl Into<ElementId>, checked: bool) -> Self {
+        Self {
+            id: id.into(),
+            checked,
+            label: None,
+            disabled: false,
+            on_toggle: None,
+        }
+    }
+
+    pub fn label(mut self, label: impl Into<SharedString>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Called with the new checked state when the checkbox is clicked.
+    pub fn on_toggle(mut self, handler: impl Fn(&bool, &mut Window, &mut App) + 'static) -> Self {
+        self.on_toggle = Some(Box::new(handler));
+        self
+    }
+}
+
+impl Disableable for Checkbox {
+    fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+}
+
+impl RenderOnce for Checkbox {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let colors = cx.theme().colors();
+        let checked = self.checked;
+
+        div()
+            .id(self.id)
+            .h_flex()
+            .flex_none()
+            .gap_1p5()
+            .child(
+                div()
+                    .h_flex()
+                    .justify_center()
+                    .size_4()
+                    .rounded_sm()
+                    .border_1()
+                    .map(|this| {
+                        if checked {
+                            this.bg(colors.primary).border_color(colors.primary).child(
+                                Icon::new(IconName::Check)
+                                    .size(IconSize::XSmall)
+                                    .color(Color::Custom(colors.primary_foreground)),
+                            )
+                        } else {
+                            this.bg(colors.background).border_color(colors.input)
+                        }
+                    }),
+            )
+            .when_some(self.label, |this, label| this.child(label))
+            .map(|this| {
+                if self.disabled {
+                    this.opacity(0.5).cursor(CursorStyle::OperationNotAllowed)
+                } else {
+                    this.cursor_pointer()
+                        .when_some(self.on_toggle, |this, on_toggle| {
+                            this.on_click(move |_, window, cx| {
+                                cx.stop_propagation();
+                                on_toggle(&!checked, window, cx)
+                            })
+                        })
+                }
+            })
+    }
+}
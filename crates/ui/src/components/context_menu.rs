@@ -0,0 +1,261 @@
+use std::rc::Rc;
+
+use gpui::{
+    AnyElement, App, AppContext, Context, Corner, DismissEvent, Entity, EventEmitter, FocusHandle,
+    Focusable, InteractiveElement, IntoElement, KeyBinding, ParentElement, Pixels, Point, Render,
+    SharedString, StatefulInteractiveElement, Styled, Window, actions, anchored, deferred, div,
+    prelude::FluentBuilder,
+};
+use theme::ActiveTheme;
+
+use crate::traits::styled_ext::StyledExt;
+
+const KEY_CONTEXT: &str = "menu";
+
+actions!(
+    menu,
+    [
+        /// Moves the highlight to the next entry.
+        SelectNext,
+        /// Moves the highlight to the previous entry.
+        SelectPrevious,
+        /// Activates the highlighted entry.
+        Confirm,
+        /// Closes the menu without activating anything.
+        Cancel,
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    let context = Some(KEY_CONTEXT);
+
+    cx.bind_keys([
+        KeyBinding::new("down", SelectNext, context),
+        KeyBinding::new("up", SelectPrevious, context),
+        KeyBinding::new("enter", Confirm, context),
+        KeyBinding::new("escape", Cancel, context),
+    ]);
+}
+
+type EntryHandler = Rc<dyn Fn(&mut Window, &mut App)>;
+
+enum ContextMenuItem {
+    Header(SharedString),
+    Separator,
+    Entry {
+        label: SharedString,
+        disabled: bool,
+        handler: EntryHandler,
+    },
+}
+
+impl ContextMenuItem {
+    fn is_selectable(&self) -> bool {
+        matches!(
+            self,
+            ContextMenuItem::Entry {
+                disabled: false,
+                ..
+            }
+        )
+    }
+}
+
+/// A popup list of actions, dismissed once an entry runs or focus leaves it.
+///
+/// Owners keep the entity alongside the position it was deployed at, render
+/// it through [`ContextMenu::popover`] and drop it on [`DismissEvent`].
+pub struct ContextMenu {
+    focus_handle: FocusHandle,
+    items: Vec<ContextMenuItem>,
+    selected_index: Option<usize>,
+}
+
+impl ContextMenu {
+    pub fn build(
+        window: &mut Window,
+        cx: &mut App,
+        f: impl FnOnce(Self, &mut Window, &mut Context<Self>) -> Self,
+    ) -> Entity<Self> {
+        cx.new(|cx| {
+            let focus_handle = cx.focus_handle();
+
+            cx.on_blur(&focus_handle, window, |_, _, cx| cx.emit(DismissEvent))
+                .detach();
+
+            f(
+                Self {
+                    focus_handle,
+                    items: Vec::new(),
+                    selected_index: None,
+                },
+                window,
+                cx,
+            )
+        })
+    }
+
+    pub fn header(mut self, label: impl Into<SharedString>) -> Self {
+        self.items.push(ContextMenuItem::Header(label.into()));
+        self
+    }
+
+    pub fn separator(mut self) -> Self {
+        self.items.push(ContextMenuItem::Separator);
+        self
+    }
+
+    pub fn entry(
+        mut self,
+        label: impl Into<SharedString>,
+        handler: impl Fn(&mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.items.push(ContextMenuItem::Entry {
+            label: label.into(),
+            disabled: false,
+            handler: Rc::new(handler),
+        });
+        self
+    }
+
+    pub fn disabled_entry(mut self, label: impl Into<SharedString>) -> Self {
+        self.items.push(ContextMenuItem::Entry {
+            label: label.into(),
+            disabled: true,
+            handler: Rc::new(|_, _| {}),
+        });
+        self
+    }
+
+    /// Renders `menu` on top of everything else at `position`.
+    pub fn popover(menu: &Entity<Self>, position: Point<Pixels>) -> AnyElement {
+        deferred(
+            anchored()
+                .position(position)
+                .anchor(Corner::TopLeft)
+                .snap_to_window_with_margin(gpui::px(8.))
+                .child(menu.clone()),
+        )
+        .with_priority(1)
+        .into_any_element()
+    }
+
+    fn run_entry(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ContextMenuItem::Entry {
+            disabled: false,
+            handler,
+            ..
+        }) = self.items.get(index)
+        {
+            let handler = handler.clone();
+
+            cx.emit(DismissEvent);
+            handler(window, cx);
+        }
+    }
+
+    fn select_next(&mut self, _: &SelectNext, _window: &mut Window, cx: &mut Context<Self>) {
+        let start = self.selected_index.map_or(0, |index| index + 1);
+
+        self.selected_index = (start..self.items.len())
+            .chain(0..start)
+            .find(|&index| self.items[index].is_selectable());
+        cx.notify();
+    }
+
+    fn select_previous(
+        &mut self,
+        _: &SelectPrevious,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let end = self.selected_index.unwrap_or(self.items.len());
+
+        self.selected_index = (0..end)
+            .rev()
+            .chain((end..self.items.len()).rev())
+            .find(|&index| self.items[index].is_selectable());
+        cx.notify();
+    }
+
+    fn confirm(&mut self, _: &Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(index) = self.selected_index {
+            self.run_entry(index, window, cx);
+        }
+    }
+
+    fn cancel(&mut self, _: &Cancel, _window: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+}
+
+impl EventEmitter<DismissEvent> for ContextMenu {}
+
+impl Focusable for ContextMenu {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for ContextMenu {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = *cx.theme().colors();
+
+        div()
+            .v_flex()
+            .key_context(KEY_CONTEXT)
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .on_mouse_down_out(cx.listener(|_, _, _, cx| cx.emit(DismissEvent)))
+            .min_w_40()
+            .p_1()
+            .rounded_md()
+            .border_1()
+            .border_color(colors.border)
+            .bg(colors.popover)
+            .text_color(colors.popover_foreground)
+            .text_sm()
+            .shadow_lg()
+            .children(self.items.iter().enumerate().map(|(index, item)| {
+                match item {
+                    ContextMenuItem::Header(label) => div()
+                        .px_2()
+                        .py_1()
+                        .text_xs()
+                        .text_color(colors.muted_foreground)
+                        .child(label.clone())
+                        .into_any_element(),
+                    ContextMenuItem::Separator => {
+                        div().my_1().h_px().bg(colors.border).into_any_element()
+                    }
+                    ContextMenuItem::Entry {
+                        label, disabled, ..
+                    } => div()
+                        .id(index)
+                        .h_flex()
+                        .px_2()
+                        .py_1()
+                        .rounded_sm()
+                        .child(label.clone())
+                        .map(|this| {
+                            if *disabled {
+                                this.text_color(colors.muted_foreground)
+                            } else {
+                                this.cursor_pointer()
+                                    .when(self.selected_index == Some(index), |this| {
+                                        this.bg(colors.list_active)
+                                    })
+                                    .hover(|this| this.bg(colors.list_hover))
+                                    .on_click(cx.listener(move |this, _, window, cx| {
+                                        this.run_entry(index, window, cx)
+                                    }))
+                            }
+                        })
+                        .into_any_element(),
+                }
+            }))
+    }
+}
@@ -0,0 +1,286 @@
+use gpui::{
+    App, Context, Corner, ElementId, EventEmitter, FocusHandle, Focusable, InteractiveElement,
+    IntoElement, ParentElement, Render, SharedString, StatefulInteractiveElement, Styled, Window,
+    anchored, deferred, div, prelude::FluentBuilder, px,
+};
+use theme::ActiveTheme;
+
+use crate::{
+    color::Color,
+    components::{
+        context_menu::{Cancel, Confirm, SelectNext, SelectPrevious},
+        icon::{Icon, IconName, IconSize},
+    },
+    traits::styled_ext::StyledExt,
+};
+
+const KEY_CONTEXT: &str = "menu";
+
+pub enum DropdownEvent {
+    /// The selection changed to the option at the given index.
+    Changed(usize),
+}
+
+/// A select control picking a single option out of a fixed list.
+///
+/// Shares the `menu` key bindings with
+/// [`crate::components::context_menu::ContextMenu`] while focused.
+pub struct Dropdown {
+    id: ElementId,
+    focus_handle: FocusHandle,
+    options: Vec<SharedString>,
+    selected_index: Option<usize>,
+    highlighted_index: Option<usize>,
+    placeholder: SharedString,
+    open: bool,
+    disabled: bool,
+}
+
+impl Dropdown {
+    pub fn new(
+        id: impl Into<ElementId>,
+        options: impl IntoIterator<Item = impl Into<SharedString>>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            focus_handle: cx.focus_handle(),
+            options: options.into_iter().map(Into::into).collect(),
+            selected_index: None,
+            highlighted_index: None,
+            placeholder: "Select…".into(),
+            open: false,
+            disabled: false,
+        }
+    }
+
+    pub fn placeholder(mut self, placeholder: impl Into<SharedString>) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    pub fn selected_index(&self) -> Option<usize> {
+        self.selected_index
+    }
+
+    pub fn selected(&self) -> Option<&SharedString> {
+        self.selected_index
+            .and_then(|index| self.options.get(index))
+    }
+
+    pub fn options(&self) -> &[SharedString] {
+        &self.options
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.disabled
+    }
+
+    /// Selects the option at `index` without emitting [`DropdownEvent::Changed`].
+    pub fn set_selected_index(&mut self, index: Option<usize>, cx: &mut Context<Self>) {
+        self.selected_index = index.filter(|&index| index < self.options.len());
+        cx.notify();
+    }
+
+    pub fn set_options(
+        &mut self,
+        options: impl IntoIterator<Item = impl Into<SharedString>>,
+        cx: &mut Context<Self>,
+    ) {
+        self.options = options.into_iter().map(Into::into).collect();
+        self.selected_index = self
+            .selected_index
+            .filter(|&index| index < self.options.len());
+        self.highlighted_index = None;
+        cx.notify();
+    }
+
+    pub fn set_disabled(&mut self, disabled: bool, cx: &mut Context<Self>) {
+        self.disabled = disabled;
+        self.open &= !disabled;
+        cx.notify();
+    }
+
+    fn toggle(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.disabled {
+            return;
+        }
+
+        self.open = !self.open;
+        self.highlighted_index = self.selected_index;
+        window.focus(&self.focus_handle);
+        cx.notify();
+    }
+
+    fn close(&mut self, cx: &mut Context<Self>) {
+        self.open = false;
+        cx.notify();
+    }
+
+    fn select(&mut self, index: usize, cx: &mut Context<Self>) {
+        self.open = false;
+
+        if self.selected_index != Some(index) {
+            self.selected_index = Some(index);
+            cx.emit(DropdownEvent::Changed(index));
+        }
+
+        cx.notify();
+    }
+
+    fn select_next(&mut self, _: &SelectNext, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.open {
+            return self.toggle(window, cx);
+        }
+
+        if !self.options.is_empty() {
+            self.highlighted_index = Some(
+                self.highlighted_index
+                    .map_or(0, |index| (index + 1) % self.options.len()),
+            );
+            cx.notify();
+        }
+    }
+
+    fn select_previous(&mut self, _: &SelectPrevious, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.open {
+            return self.toggle(window, cx);
+        }
+
+        if !self.options.is_empty() {
+            let len = self.options.len();
+
+            self.highlighted_index = Some(
+                self.highlighted_index
+                    .map_or(len - 1, |index| (index + len - 1) % len),
+            );
+            cx.notify();
+        }
+    }
+
+    fn confirm(&mut self, _: &Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        match self.highlighted_index {
+            Some(index) if self.open => self.select(index, cx),
+            _ => self.toggle(window, cx),
+        }
+    }
+
+    fn cancel(&mut self, _: &Cancel, _window: &mut Window, cx: &mut Context<Self>) {
+        self.close(cx);
+    }
+}
+
+impl EventEmitter<DropdownEvent> for Dropdown {}
+
+impl Focusable for Dropdown {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for Dropdown {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = *cx.theme().colors();
+        let focused = self.focus_handle.is_focused(window);
+        let label = self.selected().cloned();
+
+        div()
+            .relative()
+            .key_context(KEY_CONTEXT)
+            .when(!self.disabled, |this| {
+                this.track_focus(&self.focus_handle)
+                    .on_action(cx.listener(Self::select_next))
+                    .on_action(cx.listener(Self::select_previous))
+                    .on_action(cx.listener(Self::confirm))
+                    .on_action(cx.listener(Self::cancel))
+            })
+            .child(
+                div()
+                    .id(self.id.clone())
+                    .h_flex()
+                    .h_7()
+                    .min_w_24()
+                    .gap_2()
+                    .px_2()
+                    .justify_between()
+                    .rounded_sm()
+                    .border_1()
+                    .border_color(if focused { colors.ring } else { colors.input })
+                    .bg(colors.background)
+                    .text_sm()
+                    .map(|this| {
+                        if self.disabled {
+                            this.bg(colors.muted).text_color(colors.muted_foreground)
+                        } else {
+                            this.cursor_pointer()
+                                .text_color(colors.foreground)
+                                .hover(|this| this.bg(colors.list_hover))
+                                .on_click(
+                                    cx.listener(|this, _, window, cx| this.toggle(window, cx)),
+                                )
+                        }
+                    })
+                    .child(match label {
+                        Some(label) => div().child(label),
+                        None => div()
+                            .text_color(colors.muted_foreground)
+                            .child(self.placeholder.clone()),
+                    })
+                    .child(
+                        Icon::new(IconName::ChevronDown)
+                            .size(IconSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .when(self.open, |this| {
+                this.child(
+                    deferred(
+                        anchored()
+                            .anchor(Corner::TopLeft)
+                            .snap_to_window_with_margin(px(8.))
+                            .child(
+                                div()
+                                    .id("options")
+                                    .v_flex()
+                                    .mt_1()
+                                    .min_w_24()
+                                    .max_h_64()
+                                    .overflow_y_scroll()
+                                    .p_1()
+                                    .rounded_md()
+                                    .border_1()
+                                    .border_color(colors.border)
+                                    .bg(colors.popover)
+                                    .text_color(colors.popover_foreground)
+                                    .text_sm()
+                                    .shadow_lg()
+                                    .on_mouse_down_out(cx.listener(|this, _, _, cx| this.close(cx)))
+                                    .children(self.options.iter().enumerate().map(
+                                        |(index, option)| {
+                                            div()
+                                                .id(index)
+                                                .px_2()
+                                                .py_1()
+                                                .rounded_sm()
+                                                .cursor_pointer()
+                                                .when(self.selected_index == Some(index), |this| {
+                                                    this.text_color(colors.accent_foreground)
+                                                })
+                                                .when(
+                                                    self.highlighted_index == Some(index),
+                                                    |this| this.bg(colors.list_active),
+                                                )
+                                                .hover(|this| this.bg(colors.list_hover))
+                                                .on_click(cx.listener(move |this, _, _, cx| {
+                                                    this.select(index, cx)
+                                                }))
+                                                .child(option.clone())
+                                        },
+                                    )),
+                            ),
+                    )
+                    .with_priority(1),
+                )
+            })
+    }
+}
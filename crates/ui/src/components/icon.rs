@@ -0,0 +1,213 @@
+use gpui::{
+    App, IntoElement, Rems, RenderOnce, SharedString, Styled, Transformation, Window, rems, svg,
+};
+
+use crate::color::Color;
+
+/// An icon from the embedded `assets/icons` set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IconName {
+    Check,
+    ChevronDown,
+    ChevronRight,
+    ChevronUp,
+    Clock,
+    Close,
+    Code,
+    Cookie,
+    Copy,
+    Download,
+    Ellipsis,
+    Error,
+    ExternalLink,
+    Eye,
+    EyeOff,
+    File,
+    Folder,
+    FolderOpen,
+    Globe,
+    Info,
+    Key,
+    Lock,
+    Minus,
+    Pencil,
+    Play,
+    Plug,
+    Plus,
+    Refresh,
+    Search,
+    Send,
+    Settings,
+    Shield,
+    Stop,
+    Terminal,
+    Trash,
+    Unlock,
+    Upload,
+    Warning,
+    Zap,
+}
+
+impl IconName {
+    pub const ALL: [IconName; 39] = [
+        IconName::Check,
+        IconName::ChevronDown,
+        IconName::ChevronRight,
+        IconName::ChevronUp,
+        IconName::Clock,
+        IconName::Close,
+        IconName::Code,
+        IconName::Cookie,
+        IconName::Copy,
+        IconName::Download,
+        IconName::Ellipsis,
+        IconName::Error,
+        IconName::ExternalLink,
+        IconName::Eye,
+        IconName::EyeOff,
+        IconName::File,
+        IconName::Folder,
+        IconName::FolderOpen,
+        IconName::Globe,
+        IconName::Info,
+        IconName::Key,
+        IconName::Lock,
+        IconName::Minus,
+        IconName::Pencil,
+        IconName::Play,
+        IconName::Plug,
+        IconName::Plus,
+        IconName::Refresh,
+        IconName::Search,
+        IconName::Send,
+        IconName::Settings,
+        IconName::Shield,
+        IconName::Stop,
+        IconName::Terminal,
+        IconName::Trash,
+        IconName::Unlock,
+        IconName::Upload,
+        IconName::Warning,
+        IconName::Zap,
+    ];
+
+    pub fn file_stem(&self) -> &'static str {
+        match self {
+            IconName::Check => "check",
+            IconName::ChevronDown => "chevron_down",
+            IconName::ChevronRight => "chevron_right",
+            IconName::ChevronUp => "chevron_up",
+            IconName::Clock => "clock",
+            IconName::Close => "close",
+            IconName::Code => "code",
+            IconName::Cookie => "cookie",
+            IconName::Copy => "copy",
+            IconName::Download => "download",
+            IconName::Ellipsis => "ellipsis",
+            IconName::Error => "error",
+            IconName::ExternalLink => "external_link",
+            IconName::Eye => "eye",
+            IconName::EyeOff => "eye_off",
+            IconName::File => "file",
+            IconName::Folder => "folder",
+            IconName::FolderOpen => "folder_open",
+            IconName::Globe => "globe",
+            IconName::Info => "info",
+            IconName::Key => "key",
+            IconName::Lock => "lock",
+            IconName::Minus => "minus",
+            IconName::Pencil => "pencil",
+            IconName::Play => "play",
+            IconName::Plug => "plug",
+            IconName::Plus => "plus",
+            IconName::Refresh => "refresh",
+            IconName::Search => "search",
+            IconName::Send => "send",
+            IconName::Settings => "settings",
+            IconName::Shield => "shield",
+            IconName::Stop => "stop",
+            IconName::Terminal => "terminal",
+            IconName::Trash => "trash",
+            IconName::Unlock => "unlock",
+            IconName::Upload => "upload",
+            IconName::Warning => "warning",
+            IconName::Zap => "zap",
+        }
+    }
+
+    /// The asset path the icon is served under.
+    pub fn path(&self) -> SharedString {
+        format!("icons/{}.svg", self.file_stem()).into()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum IconSize {
+    XSmall,
+    Small,
+    #[default]
+    Medium,
+    Large,
+}
+
+impl IconSize {
+    pub fn rems(&self) -> Rems {
+        match self {
+            IconSize::XSmall => rems(0.625),
+            IconSize::Small => rems(0.75),
+            IconSize::Medium => rems(0.875),
+            IconSize::Large => rems(1.),
+        }
+    }
+}
+
+#[derive(IntoElement)]
+pub struct Icon {
+    name: IconName,
+    size: IconSize,
+    color: Color,
+    transformation: Transformation,
+}
+
+impl Icon {
+    pub fn new(name: IconName) -> Self {
+        Self {
+            name,
+            size: IconSize::default(),
+            color: Color::default(),
+            transformation: Transformation::default(),
+        }
+    }
+
+    pub fn size(mut self, size: IconSize) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn transform(mut self, transformation: Transformation) -> Self {
+        self.transformation = transformation;
+        self
+    }
+}
+
+impl From<IconName> for Icon {
+    fn from(name: IconName) -> Self {
+        Icon::new(name)
+    }
+}
+
+impl RenderOnce for Icon {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        svg()
+            .flex_none()
+            .path(self.name.path())
+            .size(self.size.rems())
+            .text_color(self.color.hsla(cx))
+            .with_transformation(self.transformation)
+    }
+}
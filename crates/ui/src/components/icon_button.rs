@@ -0,0 +1,107 @@
+use gpui::{
+    App, ClickEvent, CursorStyle, ElementId, IntoElement, ParentElement, RenderOnce, SharedString,
+    Styled, Window, prelude::FluentBuilder,
+};
+
+use crate::{
+    color::Color,
+    components::{
+        button::{ButtonBase, ButtonSize, ButtonStyle},
+        icon::{Icon, IconName, IconSize},
+    },
+    traits::{clickable::Clickable, disableable::Disableable},
+};
+
+/// A square button showing a single icon, ghost styled by default.
+#[derive(IntoElement)]
+pub struct IconButton {
+    base: ButtonBase,
+    icon: IconName,
+    icon_color: Color,
+}
+
+impl IconButton {
+    pub fn new(id: impl Into<ElementId>, icon: IconName) -> Self {
+        let mut base = ButtonBase::new(id);
+        base.style = ButtonStyle::Ghost;
+
+        Self {
+            base,
+            icon,
+            icon_color: Color::default(),
+        }
+    }
+
+    pub fn icon_color(mut self, color: Color) -> Self {
+        self.icon_color = color;
+        self
+    }
+
+    pub fn style(mut self, style: ButtonStyle) -> Self {
+        self.base.style = style;
+        self
+    }
+
+    pub fn size(mut self, size: ButtonSize) -> Self {
+        self.base.size = size;
+        self
+    }
+
+    pub fn selected(mut self, selected: bool) -> Self {
+        self.base.selected = selected;
+        self
+    }
+
+    pub fn tooltip(mut self, tooltip: impl Into<SharedString>) -> Self {
+        self.base.tooltip = Some(tooltip.into());
+        self
+    }
+}
+
+impl Clickable for IconButton {
+    fn on_click(mut self, handler: impl Fn(&ClickEvent, &mut Window, &mut App) + 'static) -> Self {
+        self.base.on_click = Some(Box::new(handler));
+        self
+    }
+
+    fn cursor_style(mut self, cursor_style: CursorStyle) -> Self {
+        self.base.cursor_style = cursor_style;
+        self
+    }
+}
+
+impl Disableable for IconButton {
+    fn disabled(mut self, disabled: bool) -> Self {
+        self.base.disabled = disabled;
+        self
+    }
+}
+
+impl RenderOnce for IconButton {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let size = self.base.size;
+        let icon_size = match size {
+            ButtonSize::Compact => IconSize::Small,
+            ButtonSize::Default => IconSize::Medium,
+            ButtonSize::Large => IconSize::Large,
+        };
+        // Filled styles draw the icon in their own foreground color.
+        let icon_color = match self.base.style {
+            ButtonStyle::Primary | ButtonStyle::Danger => {
+                Color::Custom(self.base.style.colors(false, cx).foreground)
+            }
+            _ if self.base.disabled => Color::Disabled,
+            _ => self.icon_color,
+        };
+
+        self.base
+            .render(cx)
+            .px_0()
+            .map(|this| match size {
+                ButtonSize::Compact => this.w_6(),
+                ButtonSize::Default => this.w_7(),
+                ButtonSize::Large => this.w_8(),
+            })
+            .child(Icon::new(self.icon).size(icon_size).color(icon_color))
+    }
+}
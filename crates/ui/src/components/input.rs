@@ -0,0 +1,787 @@
+mod element;
+mod history;
+
+use std::ops::Range;
+
+use gpui::{
+    App, Bounds, ClipboardItem, Context, CursorStyle, EntityInputHandler, EventEmitter,
+    FocusHandle, Focusable, InteractiveElement, IntoElement, KeyBinding, MouseButton,
+    MouseDownEvent, MouseMoveEvent, MouseUpEvent, ParentElement, Pixels, Point, Render,
+    SharedString, Styled, UTF16Selection, Window, actions, div, point, prelude::FluentBuilder,
+};
+use theme::ActiveTheme;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+    components::input::{
+        element::{LastLayout, TextElement},
+        history::{History, Snapshot},
+    },
+    traits::disableable::Disableable,
+};
+
+const KEY_CONTEXT: &str = "TextInput";
+
+actions!(
+    text_input,
+    [
+        Backspace,
+        Delete,
+        Left,
+        Right,
+        Up,
+        Down,
+        SelectLeft,
+        SelectRight,
+        SelectUp,
+        SelectDown,
+        SelectAll,
+        Home,
+        End,
+        SelectToHome,
+        SelectToEnd,
+        Enter,
+        Copy,
+        Cut,
+        Paste,
+        Undo,
+        Redo,
+        ShowCharacterPalette,
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    let context = Some(KEY_CONTEXT);
+
+    cx.bind_keys([
+        KeyBinding::new("backspace", Backspace, context),
+        KeyBinding::new("delete", Delete, context),
+        KeyBinding::new("left", Left, context),
+        KeyBinding::new("right", Right, context),
+        KeyBinding::new("up", Up, context),
+        KeyBinding::new("down", Down, context),
+        KeyBinding::new("shift-left", SelectLeft, context),
+        KeyBinding::new("shift-right", SelectRight, context),
+        KeyBinding::new("shift-up", SelectUp, context),
+        KeyBinding::new("shift-down", SelectDown, context),
+        KeyBinding::new("home", Home, context),
+        KeyBinding::new("end", End, context),
+        KeyBinding::new("shift-home", SelectToHome, context),
+        KeyBinding::new("shift-end", SelectToEnd, context),
+        KeyBinding::new("enter", Enter, context),
+        KeyBinding::new("secondary-a", SelectAll, context),
+        KeyBinding::new("secondary-c", Copy, context),
+        KeyBinding::new("secondary-x", Cut, context),
+        KeyBinding::new("secondary-v", Paste, context),
+        KeyBinding::new("secondary-z", Undo, context),
+        KeyBinding::new("secondary-shift-z", Redo, context),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-ctrl-space", ShowCharacterPalette, context),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-y", Redo, context),
+    ]);
+}
+
+pub enum InputEvent {
+    Changed,
+    PressEnter,
+}
+
+/// A themed text field, either single-line (the default) or multi-line.
+pub struct TextInput {
+    focus_handle: FocusHandle,
+    content: SharedString,
+    /// The text given to [`Self::default_text`] as is, so that it can be
+    /// sanitized again when the builder calls come in the other order.
+    default_text: SharedString,
+    placeholder: SharedString,
+    multi_line: bool,
+    disabled: bool,
+    selected_range: Range<usize>,
+    selection_reversed: bool,
+    marked_range: Option<Range<usize>>,
+    history: History,
+    is_selecting: bool,
+    last_layout: Option<LastLayout>,
+}
+
+impl TextInput {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        Self {
+            focus_handle: cx.focus_handle(),
+            content: SharedString::default(),
+            default_text: SharedString::default(),
+            placeholder: SharedString::default(),
+            multi_line: false,
+            disabled: false,
+            selected_range: 0..0,
+            selection_reversed: false,
+            marked_range: None,
+            history: History::default(),
+            is_selecting: false,
+            last_layout: None,
+        }
+    }
+
+    pub fn multi_line(mut self, multi_line: bool) -> Self {
+        self.multi_line = multi_line;
+        self.content = self.sanitize(self.default_text.clone());
+        self
+    }
+
+    pub fn placeholder(mut self, placeholder: impl Into<SharedString>) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    pub fn default_text(mut self, text: impl Into<SharedString>) -> Self {
+        self.default_text = text.into();
+        self.content = self.sanitize(self.default_text.clone());
+        self
+    }
+
+    pub fn text(&self) -> &SharedString {
+        &self.content
+    }
+
+    pub fn is_multi_line(&self) -> bool {
+        self.multi_line
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.disabled
+    }
+
+    pub fn selected_range(&self) -> Range<usize> {
+        self.selected_range.clone()
+    }
+
+    /// Replaces the whole content, resetting selection and undo history.
+    pub fn set_text(&mut self, text: impl Into<SharedString>, cx: &mut Context<Self>) {
+        self.content = self.sanitize(text.into());
+        self.selected_range = self.content.len()..self.content.len();
+        self.selection_reversed = false;
+        self.marked_range = None;
+        self.history.clear();
+        cx.notify();
+    }
+
+    pub fn set_placeholder(
+        &mut self,
+        placeholder: impl Into<SharedString>,
+        cx: &mut Context<Self>,
+    ) {
+        self.placeholder = placeholder.into();
+        cx.notify();
+    }
+
+    pub fn set_disabled(&mut self, disabled: bool, cx: &mut Context<Self>) {
+        self.disabled = disabled;
+        self.is_selecting = false;
+        cx.notify();
+    }
+
+    pub fn select_range(&mut self, range: Range<usize>, cx: &mut Context<Self>) {
+        let start = self.clip_offset(range.start);
+        let end = self.clip_offset(range.end);
+
+        self.selection_reversed = end < start;
+        self.selected_range = start.min(end)..start.max(end);
+        cx.notify();
+    }
+
+    fn sanitize(&self, text: SharedString) -> SharedString {
+        if self.multi_line || !text.contains(['\n', '\r']) {
+            text
+        } else {
+            text.replace(['\r', '\n'], " ").into()
+        }
+    }
+
+    fn clip_offset(&self, offset: usize) -> usize {
+        let mut offset = offset.min(self.content.len());
+
+        while !self.content.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        offset
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            content: self.content.clone(),
+            selected_range: self.selected_range.clone(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot, cx: &mut Context<Self>) {
+        self.content = snapshot.content;
+        self.selected_range = snapshot.selected_range;
+        self.selection_reversed = false;
+        self.marked_range = None;
+        cx.emit(InputEvent::Changed);
+        cx.notify();
+    }
+
+    fn cursor_offset(&self) -> usize {
+        if self.selection_reversed {
+            self.selected_range.start
+        } else {
+            self.selected_range.end
+        }
+    }
+
+    fn move_to(&mut self, offset: usize, cx: &mut Context<Self>) {
+        self.selected_range = offset..offset;
+        self.selection_reversed = false;
+        self.history.break_group();
+        cx.notify()
+    }
+
+    fn select_to(&mut self, offset: usize, cx: &mut Context<Self>) {
+        if self.selection_reversed {
+            self.selected_range.start = offset
+        } else {
+            self.selected_range.end = offset
+        };
+
+        if self.selected_range.end < self.selected_range.start {
+            self.selection_reversed = !self.selection_reversed;
+            self.selected_range = self.selected_range.end..self.selected_range.start;
+        }
+
+        cx.notify()
+    }
+
+    fn previous_boundary(&self, offset: usize) -> usize {
+        self.content
+            .grapheme_indices(true)
+            .rev()
+            .find_map(|(index, _)| (index < offset).then_some(index))
+            .unwrap_or(0)
+    }
+
+    fn next_boundary(&self, offset: usize) -> usize {
+        self.content
+            .grapheme_indices(true)
+            .find_map(|(index, _)| (index > offset).then_some(index))
+            .unwrap_or(self.content.len())
+    }
+
+    /// Byte ranges of each line of the content, excluding the newlines.
+    fn line_ranges(&self) -> Vec<Range<usize>> {
+        let mut start = 0;
+        let mut ranges = Vec::new();
+
+        for (index, _) in self.content.match_indices('\n') {
+            ranges.push(start..index);
+            start = index + 1;
+        }
+
+        ranges.push(start..self.content.len());
+        ranges
+    }
+
+    fn row_for_offset(&self, offset: usize) -> usize {
+        self.content[..offset].matches('\n').count()
+    }
+
+    fn line_start(&self, offset: usize) -> usize {
+        if !self.multi_line {
+            return 0;
+        }
+
+        self.content[..offset]
+            .rfind('\n')
+            .map_or(0, |index| index + 1)
+    }
+
+    fn line_end(&self, offset: usize) -> usize {
+        if !self.multi_line {
+            return self.content.len();
+        }
+
+        self.content[offset..]
+            .find('\n')
+            .map_or(self.content.len(), |index| offset + index)
+    }
+
+    /// The offset on the row `delta` rows away from `offset`, keeping the
+    /// horizontal position when a layout is available.
+    fn vertical_offset(&self, offset: usize, delta: isize) -> usize {
+        let lines = self.line_ranges();
+        let row = self.row_for_offset(offset);
+        let target = row as isize + delta;
+
+        if target < 0 {
+            return 0;
+        }
+
+        let Some(target_range) = lines.get(target as usize) else {
+            return self.content.len();
+        };
+
+        let column = offset - lines[row].start;
+
+        match self.last_layout.as_ref() {
+            Some(layout) if layout.lines.len() == lines.len() => {
+                let x = layout.lines[row].x_for_index(column);
+
+                target_range.start + layout.lines[target as usize].closest_index_for_x(x)
+            }
+            _ => self.clip_offset((target_range.start + column).min(target_range.end)),
+        }
+    }
+
+    fn index_for_mouse_position(&self, position: Point<Pixels>) -> usize {
+        let Some(layout) = self.last_layout.as_ref() else {
+            return 0;
+        };
+
+        if self.content.is_empty() || position.y < layout.bounds.top() {
+            return 0;
+        }
+
+        let lines = self.line_ranges();
+        let row = ((position.y - layout.bounds.top()) / layout.line_height).floor() as usize;
+
+        match (lines.get(row), layout.lines.get(row)) {
+            (Some(range), Some(line)) => {
+                range.start + line.closest_index_for_x(position.x - layout.origin().x)
+            }
+            _ => self.content.len(),
+        }
+    }
+
+    fn left(&mut self, _: &Left, _: &mut Window, cx: &mut Context<Self>) {
+        if self.selected_range.is_empty() {
+            self.move_to(self.previous_boundary(self.cursor_offset()), cx);
+        } else {
+            self.move_to(self.selected_range.start, cx)
+        }
+    }
+
+    fn right(&mut self, _: &Right, _: &mut Window, cx: &mut Context<Self>) {
+        if self.selected_range.is_empty() {
+            self.move_to(self.next_boundary(self.selected_range.end), cx);
+        } else {
+            self.move_to(self.selected_range.end, cx)
+        }
+    }
+
+    fn up(&mut self, _: &Up, _: &mut Window, cx: &mut Context<Self>) {
+        if !self.multi_line {
+            cx.propagate();
+            return;
+        }
+
+        self.move_to(self.vertical_offset(self.cursor_offset(), -1), cx);
+    }
+
+    fn down(&mut self, _: &Down, _: &mut Window, cx: &mut Context<Self>) {
+        if !self.multi_line {
+            cx.propagate();
+            return;
+        }
+
+        self.move_to(self.vertical_offset(self.cursor_offset(), 1), cx);
+    }
+
+    fn select_left(&mut self, _: &SelectLeft, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_to(self.previous_boundary(self.cursor_offset()), cx);
+    }
+
+    fn select_right(&mut self, _: &SelectRight, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_to(self.next_boundary(self.cursor_offset()), cx);
+    }
+
+    fn select_up(&mut self, _: &SelectUp, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_to(self.vertical_offset(self.cursor_offset(), -1), cx);
+    }
+
+    fn select_down(&mut self, _: &SelectDown, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_to(self.vertical_offset(self.cursor_offset(), 1), cx);
+    }
+
+    fn select_all(&mut self, _: &SelectAll, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_to(0, cx);
+        self.select_to(self.content.len(), cx)
+    }
+
+    fn home(&mut self, _: &Home, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_to(self.line_start(self.cursor_offset()), cx);
+    }
+
+    fn end(&mut self, _: &End, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_to(self.line_end(self.cursor_offset()), cx);
+    }
+
+    fn select_to_home(&mut self, _: &SelectToHome, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_to(self.line_start(self.cursor_offset()), cx);
+    }
+
+    fn select_to_end(&mut self, _: &SelectToEnd, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_to(self.line_end(self.cursor_offset()), cx);
+    }
+
+    fn backspace(&mut self, _: &Backspace, window: &mut Window, cx: &mut Context<Self>) {
+        if self.selected_range.is_empty() {
+            self.select_to(self.previous_boundary(self.cursor_offset()), cx)
+        }
+
+        self.replace_text_in_range(None, "", window, cx)
+    }
+
+    fn delete(&mut self, _: &Delete, window: &mut Window, cx: &mut Context<Self>) {
+        if self.selected_range.is_empty() {
+            self.select_to(self.next_boundary(self.cursor_offset()), cx)
+        }
+
+        self.replace_text_in_range(None, "", window, cx)
+    }
+
+    fn enter(&mut self, _: &Enter, window: &mut Window, cx: &mut Context<Self>) {
+        if self.multi_line {
+            self.history.break_group();
+            self.replace_text_in_range(None, "\n", window, cx);
+        } else {
+            cx.emit(InputEvent::PressEnter);
+        }
+    }
+
+    fn copy(&mut self, _: &Copy, _: &mut Window, cx: &mut Context<Self>) {
+        if !self.selected_range.is_empty() {
+            cx.write_to_clipboard(ClipboardItem::new_string(
+                self.content[self.selected_range.clone()].to_string(),
+            ));
+        }
+    }
+
+    fn cut(&mut self, _: &Cut, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.selected_range.is_empty() {
+            cx.write_to_clipboard(ClipboardItem::new_string(
+                self.content[self.selected_range.clone()].to_string(),
+            ));
+
+            self.history.break_group();
+            self.replace_text_in_range(None, "", window, cx)
+        }
+    }
+
+    fn paste(&mut self, _: &Paste, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(text) = cx.read_from_clipboard().and_then(|item| item.text()) {
+            self.history.break_group();
+            self.replace_text_in_range(None, &text, window, cx);
+        }
+    }
+
+    fn undo(&mut self, _: &Undo, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(snapshot) = self.history.undo(self.snapshot()) {
+            self.restore(snapshot, cx);
+        }
+    }
+
+    fn redo(&mut self, _: &Redo, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(snapshot) = self.history.redo(self.snapshot()) {
+            self.restore(snapshot, cx);
+        }
+    }
+
+    fn show_character_palette(
+        &mut self,
+        _: &ShowCharacterPalette,
+        window: &mut Window,
+        _: &mut Context<Self>,
+    ) {
+        window.show_character_palette();
+    }
+
+    fn on_mouse_down(
+        &mut self,
+        event: &MouseDownEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        window.focus(&self.focus_handle);
+        self.is_selecting = true;
+
+        if event.modifiers.shift {
+            self.select_to(self.index_for_mouse_position(event.position), cx);
+        } else {
+            self.move_to(self.index_for_mouse_position(event.position), cx)
+        }
+    }
+
+    fn on_mouse_up(&mut self, _: &MouseUpEvent, _: &mut Window, _: &mut Context<Self>) {
+        self.is_selecting = false;
+    }
+
+    fn on_mouse_move(&mut self, event: &MouseMoveEvent, _: &mut Window, cx: &mut Context<Self>) {
+        if self.is_selecting {
+            self.select_to(self.index_for_mouse_position(event.position), cx);
+        }
+    }
+
+    fn offset_from_utf16(&self, offset: usize) -> usize {
+        let mut utf8_offset = 0;
+        let mut utf16_count = 0;
+
+        for ch in self.content.chars() {
+            if utf16_count >= offset {
+                break;
+            }
+
+            utf16_count += ch.len_utf16();
+            utf8_offset += ch.len_utf8();
+        }
+
+        utf8_offset
+    }
+
+    fn offset_to_utf16(&self, offset: usize) -> usize {
+        let mut utf16_offset = 0;
+        let mut utf8_count = 0;
+
+        for ch in self.content.chars() {
+            if utf8_count >= offset {
+                break;
+            }
+
+            utf8_count += ch.len_utf8();
+            utf16_offset += ch.len_utf16();
+        }
+
+        utf16_offset
+    }
+
+    fn range_to_utf16(&self, range: &Range<usize>) -> Range<usize> {
+        self.offset_to_utf16(range.start)..self.offset_to_utf16(range.end)
+    }
+
+    fn range_from_utf16(&self, range_utf16: &Range<usize>) -> Range<usize> {
+        self.offset_from_utf16(range_utf16.start)..self.offset_from_utf16(range_utf16.end)
+    }
+
+    fn replace(&mut self, range: Range<usize>, new_text: &str) -> Range<usize> {
+        let new_text = self.sanitize(SharedString::new(new_text));
+
+        self.history.push(self.snapshot());
+        self.content =
+            (self.content[0..range.start].to_owned() + &new_text + &self.content[range.end..])
+                .into();
+
+        range.start..range.start + new_text.len()
+    }
+}
+
+impl EntityInputHandler for TextInput {
+    fn text_for_range(
+        &mut self,
+        range_utf16: Range<usize>,
+        actual_range: &mut Option<Range<usize>>,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<String> {
+        let range = self.range_from_utf16(&range_utf16);
+        actual_range.replace(self.range_to_utf16(&range));
+
+        Some(self.content[range].to_string())
+    }
+
+    fn selected_text_range(
+        &mut self,
+        _ignore_disabled_input: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<UTF16Selection> {
+        Some(UTF16Selection {
+            range: self.range_to_utf16(&self.selected_range),
+            reversed: self.selection_reversed,
+        })
+    }
+
+    fn marked_text_range(
+        &self,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<Range<usize>> {
+        self.marked_range
+            .as_ref()
+            .map(|range| self.range_to_utf16(range))
+    }
+
+    fn unmark_text(&mut self, _window: &mut Window, _cx: &mut Context<Self>) {
+        self.marked_range = None;
+    }
+
+    fn replace_text_in_range(
+        &mut self,
+        range_utf16: Option<Range<usize>>,
+        new_text: &str,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.disabled {
+            return;
+        }
+
+        let range = range_utf16
+            .as_ref()
+            .map(|range_utf16| self.range_from_utf16(range_utf16))
+            .or(self.marked_range.clone())
+            .unwrap_or(self.selected_range.clone());
+
+        let inserted = self.replace(range, new_text);
+
+        self.selected_range = inserted.end..inserted.end;
+        self.selection_reversed = false;
+        self.marked_range.take();
+        cx.emit(InputEvent::Changed);
+        cx.notify();
+    }
+
+    fn replace_and_mark_text_in_range(
+        &mut self,
+        range_utf16: Option<Range<usize>>,
+        new_text: &str,
+        new_selected_range_utf16: Option<Range<usize>>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.disabled {
+            return;
+        }
+
+        let range = range_utf16
+            .as_ref()
+            .map(|range_utf16| self.range_from_utf16(range_utf16))
+            .or(self.marked_range.clone())
+            .unwrap_or(self.selected_range.clone());
+
+        let inserted = self.replace(range.clone(), new_text);
+
+        self.marked_range = (!inserted.is_empty()).then_some(inserted.clone());
+        // The selection is given relative to the start of the new text.
+        let start_utf16 = self.offset_to_utf16(range.start);
+        self.selected_range = new_selected_range_utf16
+            .map(|new_range| {
+                self.range_from_utf16(&(start_utf16 + new_range.start..start_utf16 + new_range.end))
+            })
+            .unwrap_or_else(|| inserted.end..inserted.end);
+
+        cx.emit(InputEvent::Changed);
+        cx.notify();
+    }
+
+    fn bounds_for_range(
+        &mut self,
+        range_utf16: Range<usize>,
+        _bounds: Bounds<Pixels>,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<Bounds<Pixels>> {
+        let layout = self.last_layout.as_ref()?;
+        let range = self.range_from_utf16(&range_utf16);
+        let lines = self.line_ranges();
+        let row = self.row_for_offset(range.start);
+        let line = layout.lines.get(row)?;
+        let start = range.start - lines[row].start;
+        let end = range.end.min(lines[row].end) - lines[row].start;
+        let top = layout.bounds.top() + layout.line_height * row as f32;
+
+        Some(Bounds::from_corners(
+            point(layout.origin().x + line.x_for_index(start), top),
+            point(
+                layout.origin().x + line.x_for_index(end),
+                top + layout.line_height,
+            ),
+        ))
+    }
+
+    fn character_index_for_point(
+        &mut self,
+        point: Point<Pixels>,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<usize> {
+        let layout = self.last_layout.as_ref()?;
+
+        if !layout.bounds.contains(&point) {
+            return None;
+        }
+
+        Some(self.offset_to_utf16(self.index_for_mouse_position(point)))
+    }
+}
+
+impl EventEmitter<InputEvent> for TextInput {}
+
+impl Focusable for TextInput {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Disableable for TextInput {
+    fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+}
+
+impl Render for TextInput {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = cx.theme().colors();
+        let focused = self.focus_handle.is_focused(window);
+
+        div()
+            .id(("text-input", cx.entity_id()))
+            .key_context(KEY_CONTEXT)
+            .w_full()
+            .px_2()
+            .py_1()
+            .border_1()
+            .rounded_sm()
+            .overflow_hidden()
+            .bg(colors.background)
+            .border_color(if focused { colors.ring } else { colors.input })
+            .map(|this| {
+                if self.disabled {
+                    this.cursor(CursorStyle::OperationNotAllowed)
+                        .bg(colors.muted)
+                        .text_color(colors.muted_foreground)
+                } else {
+                    this.cursor(CursorStyle::IBeam)
+                        .track_focus(&self.focus_handle)
+                        .on_action(cx.listener(Self::backspace))
+                        .on_action(cx.listener(Self::delete))
+                        .on_action(cx.listener(Self::enter))
+                        .on_action(cx.listener(Self::cut))
+                        .on_action(cx.listener(Self::paste))
+                        .on_action(cx.listener(Self::undo))
+                        .on_action(cx.listener(Self::redo))
+                        .on_action(cx.listener(Self::show_character_palette))
+                        .on_mouse_down(MouseButton::Left, cx.listener(Self::on_mouse_down))
+                        .on_mouse_up(MouseButton::Left, cx.listener(Self::on_mouse_up))
+                        .on_mouse_up_out(MouseButton::Left, cx.listener(Self::on_mouse_up))
+                        .on_mouse_move(cx.listener(Self::on_mouse_move))
+                }
+            })
+            .on_action(cx.listener(Self::left))
+            .on_action(cx.listener(Self::right))
+            .on_action(cx.listener(Self::up))
+            .on_action(cx.listener(Self::down))
+            .on_action(cx.listener(Self::select_left))
+            .on_action(cx.listener(Self::select_right))
+            .on_action(cx.listener(Self::select_up))
+            .on_action(cx.listener(Self::select_down))
+            .on_action(cx.listener(Self::select_all))
+            .on_action(cx.listener(Self::home))
+            .on_action(cx.listener(Self::end))
+            .on_action(cx.listener(Self::select_to_home))
+            .on_action(cx.listener(Self::select_to_end))
+            .on_action(cx.listener(Self::copy))
+            .child(TextElement::new(cx.entity()))
+    }
+}
@@ -0,0 +1,338 @@
+use std::ops::Range;
+
+use gpui::{
+    App, Bounds, ContentMask, Element, ElementId, ElementInputHandler, Entity, GlobalElementId,
+    Hsla, InspectorElementId, IntoElement, LayoutId, PaintQuad, Pixels, Point, ShapedLine,
+    SharedString, Style, TextRun, UnderlineStyle, Window, fill, point, px, relative, size,
+};
+use theme::ActiveTheme;
+
+use super::TextInput;
+
+/// The shaped lines of the last paint, used to map between offsets and
+/// positions.
+pub struct LastLayout {
+    pub lines: Vec<ShapedLine>,
+    pub bounds: Bounds<Pixels>,
+    pub line_height: Pixels,
+    pub scroll_x: Pixels,
+}
+
+impl LastLayout {
+    /// The origin of the first line, accounting for horizontal scrolling.
+    pub fn origin(&self) -> Point<Pixels> {
+        point(self.bounds.left() - self.scroll_x, self.bounds.top())
+    }
+}
+
+pub struct TextElement {
+    input: Entity<TextInput>,
+}
+
+impl TextElement {
+    pub fn new(input: Entity<TextInput>) -> Self {
+        Self { input }
+    }
+}
+
+pub struct PrepaintState {
+    lines: Vec<ShapedLine>,
+    scroll_x: Pixels,
+    cursor: Option<PaintQuad>,
+    selections: Vec<PaintQuad>,
+}
+
+impl IntoElement for TextElement {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+/// Splits `runs`, which cover the whole text, into the runs of `line`.
+fn runs_for_line(runs: &[TextRun], line: &Range<usize>) -> Vec<TextRun> {
+    let mut start = 0;
+    let mut line_runs = Vec::new();
+
+    for run in runs {
+        let end = start + run.len;
+        let overlap = start.max(line.start)..end.min(line.end);
+
+        if overlap.start < overlap.end {
+            line_runs.push(TextRun {
+                len: overlap.end - overlap.start,
+                ..run.clone()
+            });
+        }
+
+        start = end;
+    }
+
+    line_runs
+}
+
+impl Element for TextElement {
+    type RequestLayoutState = ();
+    type PrepaintState = PrepaintState;
+
+    fn id(&self) -> Option<ElementId> {
+        None
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let input = self.input.read(cx);
+        let rows = if input.multi_line {
+            input.line_ranges().len()
+        } else {
+            1
+        };
+
+        let mut style = Style::default();
+        style.size.width = relative(1.).into();
+        style.size.height = (window.line_height() * rows as f32).into();
+
+        (window.request_layout(style, [], cx), ())
+    }
+
+    fn prepaint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        _request_layout: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Self::PrepaintState {
+        let colors = cx.theme().colors();
+        let input = self.input.read(cx);
+        let style = window.text_style();
+        let line_height = window.line_height();
+        let font_size = style.font_size.to_pixels(window.rem_size());
+        let selected_range = input.selected_range.clone();
+        let cursor = input.cursor_offset();
+
+        let (text, color): (SharedString, Hsla) = if input.content.is_empty() {
+            (input.placeholder.clone(), colors.muted_foreground)
+        } else {
+            (input.content.clone(), style.color)
+        };
+
+        let run = TextRun {
+            len: text.len(),
+            font: style.font(),
+            color,
+            background_color: None,
+            underline: None,
+            strikethrough: None,
+        };
+
+        let runs = match input.marked_range.as_ref() {
+            Some(marked_range) if !input.content.is_empty() => vec![
+                TextRun {
+                    len: marked_range.start,
+                    ..run.clone()
+                },
+                TextRun {
+                    len: marked_range.end - marked_range.start,
+                    underline: Some(UnderlineStyle {
+                        color: Some(run.color),
+                        thickness: px(1.0),
+                        wavy: false,
+                    }),
+                    ..run.clone()
+                },
+                TextRun {
+                    len: text.len() - marked_range.end,
+                    ..run
+                },
+            ],
+            _ => vec![run],
+        };
+
+        let mut start = 0;
+        let mut line_ranges = Vec::new();
+        for (index, _) in text.match_indices('\n') {
+            line_ranges.push(start..index);
+            start = index + 1;
+        }
+        line_ranges.push(start..text.len());
+
+        let lines = line_ranges
+            .iter()
+            .map(|range| {
+                window.text_system().shape_line(
+                    SharedString::new(&text[range.clone()]),
+                    font_size,
+                    &runs_for_line(&runs, range),
+                    None,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let content_lines = if input.content.is_empty() {
+            vec![Range::default()]
+        } else {
+            line_ranges
+        };
+
+        let x_for = |offset: usize| -> (usize, Pixels) {
+            let row = content_lines
+                .iter()
+                .position(|range| offset <= range.end)
+                .unwrap_or(content_lines.len() - 1);
+            let x = if input.content.is_empty() {
+                px(0.)
+            } else {
+                lines[row].x_for_index(offset - content_lines[row].start)
+            };
+
+            (row, x)
+        };
+
+        let (cursor_row, cursor_x) = x_for(cursor);
+
+        // Single-line inputs scroll horizontally to keep the cursor visible.
+        let mut scroll_x = input
+            .last_layout
+            .as_ref()
+            .map_or(px(0.), |layout| layout.scroll_x);
+        if !input.multi_line {
+            let width = bounds.size.width - px(2.);
+
+            if cursor_x - scroll_x > width {
+                scroll_x = cursor_x - width;
+            } else if cursor_x < scroll_x {
+                scroll_x = cursor_x;
+            }
+        } else {
+            scroll_x = px(0.);
+        }
+
+        let origin = point(bounds.left() - scroll_x, bounds.top());
+
+        let selections = if selected_range.is_empty() {
+            Vec::new()
+        } else {
+            content_lines
+                .iter()
+                .enumerate()
+                .filter(|(_, range)| {
+                    range.start <= selected_range.end && selected_range.start <= range.end
+                })
+                .map(|(row, range)| {
+                    let start = selected_range.start.max(range.start) - range.start;
+                    let end = selected_range.end.min(range.end) - range.start;
+                    let top = origin.y + line_height * row as f32;
+                    // Selected newlines are shown as a small trailing block.
+                    let trailing = if selected_range.end > range.end {
+                        px(4.)
+                    } else {
+                        px(0.)
+                    };
+
+                    fill(
+                        Bounds::from_corners(
+                            point(origin.x + lines[row].x_for_index(start), top),
+                            point(
+                                origin.x + lines[row].x_for_index(end) + trailing,
+                                top + line_height,
+                            ),
+                        ),
+                        colors.selection,
+                    )
+                })
+                .collect()
+        };
+
+        let cursor = selected_range.is_empty().then(|| {
+            fill(
+                Bounds::new(
+                    point(
+                        origin.x + cursor_x,
+                        origin.y + line_height * cursor_row as f32,
+                    ),
+                    size(px(1.5), line_height),
+                ),
+                colors.caret,
+            )
+        });
+
+        PrepaintState {
+            lines,
+            scroll_x,
+            cursor,
+            selections,
+        }
+    }
+
+    fn paint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        _request_layout: &mut Self::RequestLayoutState,
+        prepaint: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let input = self.input.read(cx);
+        let focus_handle = input.focus_handle.clone();
+        let disabled = input.disabled;
+
+        if !disabled {
+            window.handle_input(
+                &focus_handle,
+                ElementInputHandler::new(bounds, self.input.clone()),
+                cx,
+            );
+        }
+
+        let line_height = window.line_height();
+        let origin = point(bounds.left() - prepaint.scroll_x, bounds.top());
+        let lines = std::mem::take(&mut prepaint.lines);
+
+        window.with_content_mask(Some(ContentMask { bounds }), |window| {
+            for selection in prepaint.selections.drain(..) {
+                window.paint_quad(selection);
+            }
+
+            for (row, line) in lines.iter().enumerate() {
+                line.paint(
+                    point(origin.x, origin.y + line_height * row as f32),
+                    line_height,
+                    window,
+                    cx,
+                )
+                .ok();
+            }
+
+            if !disabled
+                && focus_handle.is_focused(window)
+                && let Some(cursor) = prepaint.cursor.take()
+            {
+                window.paint_quad(cursor);
+            }
+        });
+
+        let scroll_x = prepaint.scroll_x;
+        self.input.update(cx, |input, _cx| {
+            input.last_layout = Some(LastLayout {
+                lines,
+                bounds,
+                line_height,
+                scroll_x,
+            });
+        });
+    }
+}
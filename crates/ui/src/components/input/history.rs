@@ -0,0 +1,75 @@
+use std::{
+    ops::Range,
+    time::{Duration, Instant},
+};
+
+use gpui::SharedString;
+
+/// Edits closer than this are undone and redone together.
+const GROUP_INTERVAL: Duration = Duration::from_millis(300);
+const MAX_ENTRIES: usize = 100;
+
+#[derive(Clone)]
+pub struct Snapshot {
+    pub content: SharedString,
+    pub selected_range: Range<usize>,
+}
+
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    last_push: Option<Instant>,
+}
+
+impl History {
+    /// Records the state preceding an edit.
+    pub fn push(&mut self, snapshot: Snapshot) {
+        let now = Instant::now();
+        let grouped = self
+            .last_push
+            .is_some_and(|last_push| now.duration_since(last_push) < GROUP_INTERVAL);
+
+        self.last_push = Some(now);
+        self.redo.clear();
+
+        if grouped && !self.undo.is_empty() {
+            return;
+        }
+
+        if self.undo.len() == MAX_ENTRIES {
+            self.undo.remove(0);
+        }
+
+        self.undo.push(snapshot);
+    }
+
+    /// Ends the current group, so that the next edit starts a new undo step.
+    pub fn break_group(&mut self) {
+        self.last_push = None;
+    }
+
+    pub fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let snapshot = self.undo.pop()?;
+
+        self.redo.push(current);
+        self.last_push = None;
+
+        Some(snapshot)
+    }
+
+    pub fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let snapshot = self.redo.pop()?;
+
+        self.undo.push(current);
+        self.last_push = None;
+
+        Some(snapshot)
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.last_push = None;
+    }
+}
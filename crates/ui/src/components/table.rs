@@ -0,0 +1,259 @@
+use gpui::{
+    App, AppContext, Context, Entity, EventEmitter, InteractiveElement, IntoElement, ParentElement,
+    Render, SharedString, Styled, Subscription, Window, div, prelude::FluentBuilder,
+};
+use theme::ActiveTheme;
+
+use crate::{
+    components::{
+        checkbox::Checkbox,
+        icon::IconName,
+        icon_button::IconButton,
+        input::{InputEvent, TextInput},
+    },
+    traits::{clickable::Clickable, disableable::Disableable, styled_ext::StyledExt},
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyValue {
+    pub key: String,
+    pub value: String,
+    pub enabled: bool,
+}
+
+pub enum KeyValueTableEvent {
+    Changed,
+}
+
+struct Row {
+    id: usize,
+    enabled: bool,
+    key: Entity<TextInput>,
+    value: Entity<TextInput>,
+    _subscriptions: [Subscription; 2],
+}
+
+impl Row {
+    fn is_empty(&self, cx: &App) -> bool {
+        self.key.read(cx).text().is_empty() && self.value.read(cx).text().is_empty()
+    }
+}
+
+/// An editable list of key/value pairs, such as headers or query parameters.
+///
+/// A blank row is always kept at the bottom; typing into it appends a new one.
+pub struct KeyValueTable {
+    rows: Vec<Row>,
+    next_row_id: usize,
+    key_placeholder: SharedString,
+    value_placeholder: SharedString,
+    disabled: bool,
+}
+
+impl KeyValueTable {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let mut this = Self {
+            rows: Vec::new(),
+            next_row_id: 0,
+            key_placeholder: "Key".into(),
+            value_placeholder: "Value".into(),
+            disabled: false,
+        };
+
+        this.push_row(KeyValue::default(), cx);
+        this
+    }
+
+    pub fn key_placeholder(mut self, placeholder: impl Into<SharedString>) -> Self {
+        self.key_placeholder = placeholder.into();
+        self
+    }
+
+    pub fn value_placeholder(mut self, placeholder: impl Into<SharedString>) -> Self {
+        self.value_placeholder = placeholder.into();
+        self
+    }
+
+    /// The filled in rows, excluding the trailing blank one.
+    pub fn rows(&self, cx: &App) -> Vec<KeyValue> {
+        self.rows
+            .iter()
+            .filter(|row| !row.is_empty(cx))
+            .map(|row| KeyValue {
+                key: row.key.read(cx).text().to_string(),
+                value: row.value.read(cx).text().to_string(),
+                enabled: row.enabled,
+            })
+            .collect()
+    }
+
+    /// Replaces every row without emitting [`KeyValueTableEvent::Changed`].
+    pub fn set_rows(&mut self, rows: impl IntoIterator<Item = KeyValue>, cx: &mut Context<Self>) {
+        self.rows.clear();
+
+        for row in rows {
+            self.push_row(row, cx);
+        }
+
+        self.push_row(KeyValue::default(), cx);
+        cx.notify();
+    }
+
+    pub fn set_disabled(&mut self, disabled: bool, cx: &mut Context<Self>) {
+        self.disabled = disabled;
+
+        for row in &self.rows {
+            row.key
+                .update(cx, |input, cx| input.set_disabled(disabled, cx));
+            row.value
+                .update(cx, |input, cx| input.set_disabled(disabled, cx));
+        }
+
+        cx.notify();
+    }
+
+    fn push_row(&mut self, row: KeyValue, cx: &mut Context<Self>) {
+        let enabled = row.enabled || (row.key.is_empty() && row.value.is_empty());
+        let key = cx.new(|cx| {
+            TextInput::new(cx)
+                .placeholder(self.key_placeholder.clone())
+                .default_text(row.key)
+        });
+        let value = cx.new(|cx| {
+            TextInput::new(cx)
+                .placeholder(self.value_placeholder.clone())
+                .default_text(row.value)
+        });
+
+        if self.disabled {
+            key.update(cx, |input, cx| input.set_disabled(true, cx));
+            value.update(cx, |input, cx| input.set_disabled(true, cx));
+        }
+
+        let subscriptions = [
+            cx.subscribe(&key, Self::handle_input_event),
+            cx.subscribe(&value, Self::handle_input_event),
+        ];
+
+        self.rows.push(Row {
+            id: self.next_row_id,
+            enabled,
+            key,
+            value,
+            _subscriptions: subscriptions,
+        });
+        self.next_row_id += 1;
+    }
+
+    fn handle_input_event(
+        &mut self,
+        _: Entity<TextInput>,
+        event: &InputEvent,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputEvent::Changed = event {
+            if self.rows.last().is_none_or(|row| !row.is_empty(cx)) {
+                self.push_row(KeyValue::default(), cx);
+            }
+
+            cx.emit(KeyValueTableEvent::Changed);
+            cx.notify();
+        }
+    }
+
+    fn toggle_row(&mut self, id: usize, enabled: bool, cx: &mut Context<Self>) {
+        if let Some(row) = self.rows.iter_mut().find(|row| row.id == id) {
+            row.enabled = enabled;
+            cx.emit(KeyValueTableEvent::Changed);
+            cx.notify();
+        }
+    }
+
+    fn remove_row(&mut self, id: usize, cx: &mut Context<Self>) {
+        self.rows.retain(|row| row.id != id);
+
+        if self.rows.last().is_none_or(|row| !row.is_empty(cx)) {
+            self.push_row(KeyValue::default(), cx);
+        }
+
+        cx.emit(KeyValueTableEvent::Changed);
+        cx.notify();
+    }
+}
+
+impl EventEmitter<KeyValueTableEvent> for KeyValueTable {}
+
+impl Render for KeyValueTable {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = *cx.theme().colors();
+        let last_index = self.rows.len() - 1;
+
+        div()
+            .v_flex()
+            .w_full()
+            .rounded_sm()
+            .border_1()
+            .border_color(colors.table_row_border)
+            .bg(colors.table)
+            .text_sm()
+            .child(
+                div()
+                    .h_flex()
+                    .gap_2()
+                    .px_2()
+                    .py_1()
+                    .bg(colors.table_head)
+                    .text_color(colors.table_head_foreground)
+                    .child(div().w_4())
+                    .child(div().flex_1().child(self.key_placeholder.clone()))
+                    .child(div().flex_1().child(self.value_placeholder.clone()))
+                    .child(div().w_6()),
+            )
+            .children(self.rows.iter().enumerate().map(|(index, row)| {
+                let id = row.id;
+                let is_placeholder = index == last_index;
+
+                div()
+                    .id(id)
+                    .h_flex()
+                    .gap_2()
+                    .px_2()
+                    .py_1()
+                    .border_t_1()
+                    .border_color(colors.table_row_border)
+                    .when(index % 2 == 1, |this| this.bg(colors.table_even))
+                    .hover(|this| this.bg(colors.table_hover))
+                    .child(div().w_4().when(!is_placeholder, |this| {
+                        this.child(
+                            Checkbox::new(("enabled", id), row.enabled)
+                                .disabled(self.disabled)
+                                .on_toggle(cx.listener(move |this, enabled, _, cx| {
+                                    this.toggle_row(id, *enabled, cx)
+                                })),
+                        )
+                    }))
+                    .child(
+                        div()
+                            .flex_1()
+                            .when(!row.enabled, |this| this.opacity(0.5))
+                            .child(row.key.clone()),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .when(!row.enabled, |this| this.opacity(0.5))
+                            .child(row.value.clone()),
+                    )
+                    .child(div().w_6().when(!is_placeholder, |this| {
+                        this.child(
+                            IconButton::new(("remove", id), IconName::Close)
+                                .tooltip("Remove")
+                                .disabled(self.disabled)
+                                .on_click(
+                                    cx.listener(move |this, _, _, cx| this.remove_row(id, cx)),
+                                ),
+                        )
+                    }))
+            }))
+    }
+}
@@ -0,0 +1,71 @@
+use gpui::{
+    AnyView, App, AppContext, Context, IntoElement, ParentElement, Render, SharedString, Styled,
+    Window, div, prelude::FluentBuilder, px,
+};
+use theme::ActiveTheme;
+
+use crate::traits::styled_ext::StyledExt;
+
+pub struct Tooltip {
+    title: SharedString,
+    meta: Option<SharedString>,
+}
+
+impl Tooltip {
+    /// Builds a tooltip for `StatefulInteractiveElement::tooltip`.
+    pub fn text(title: impl Into<SharedString>) -> impl Fn(&mut Window, &mut App) -> AnyView {
+        let title = title.into();
+
+        move |_, cx| {
+            cx.new(|_| Self {
+                title: title.clone(),
+                meta: None,
+            })
+            .into()
+        }
+    }
+
+    /// Like [`Tooltip::text`], with a secondary line below the title.
+    pub fn with_meta(
+        title: impl Into<SharedString>,
+        meta: impl Into<SharedString>,
+    ) -> impl Fn(&mut Window, &mut App) -> AnyView {
+        let title = title.into();
+        let meta = meta.into();
+
+        move |_, cx| {
+            cx.new(|_| Self {
+                title: title.clone(),
+                meta: Some(meta.clone()),
+            })
+            .into()
+        }
+    }
+}
+
+impl Render for Tooltip {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = cx.theme().colors();
+
+        // Tooltips are painted at the mouse position, so the padding keeps
+        // them clear of the cursor.
+        div().pl_2().pt(px(10.)).child(
+            div()
+                .v_flex()
+                .max_w_80()
+                .px_2()
+                .py_1()
+                .rounded_sm()
+                .border_1()
+                .border_color(colors.border)
+                .bg(colors.popover)
+                .text_color(colors.popover_foreground)
+                .text_xs()
+                .shadow_md()
+                .child(self.title.clone())
+                .when_some(self.meta.clone(), |this, meta| {
+                    this.child(div().text_color(colors.muted_foreground).child(meta))
+                }),
+        )
+    }
+}
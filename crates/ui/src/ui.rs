@@ -1,3 +1,11 @@
+pub mod color;
 pub mod components;
 pub mod placement;
 pub mod traits;
+
+use gpui::App;
+
+pub fn init(cx: &mut App) {
+    components::context_menu::init(cx);
+    components::input::init(cx);
+}
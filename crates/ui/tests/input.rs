@@ -0,0 +1,248 @@
+use std::{cell::RefCell, rc::Rc};
+
+use gpui::{
+    AppContext as _, ClipboardItem, Entity, EntityInputHandler as _, TestAppContext,
+    VisualContext as _, VisualTestContext,
+};
+use ui::{
+    components::input::{InputEvent, TextInput},
+    traits::disableable::Disableable as _,
+};
+
+/// Opens a window showing the input `build` makes, focused.
+fn open(
+    cx: &mut TestAppContext,
+    build: impl FnOnce(TextInput) -> TextInput + 'static,
+) -> (Entity<TextInput>, &mut VisualTestContext) {
+    cx.update(|cx| {
+        theme::init(cx);
+        ui::init(cx);
+    });
+
+    let (input, cx) = cx.add_window_view(|_, cx| build(TextInput::new(cx)));
+    cx.focus(&input);
+    cx.run_until_parked();
+    (input, cx)
+}
+
+fn text(input: &Entity<TextInput>, cx: &mut VisualTestContext) -> String {
+    cx.read_entity(input, |input, _| input.text().to_string())
+}
+
+fn selection(input: &Entity<TextInput>, cx: &mut VisualTestContext) -> std::ops::Range<usize> {
+    cx.read_entity(input, |input, _| input.selected_range())
+}
+
+#[gpui::test]
+fn edits_and_selects(cx: &mut TestAppContext) {
+    let (input, cx) = open(cx, |input| input);
+    let events = Rc::new(RefCell::new(Vec::new()));
+    cx.update(|_, cx| {
+        let events = events.clone();
+        cx.subscribe(&input, move |_, event, _| {
+            events.borrow_mut().push(match event {
+                InputEvent::Changed => "changed",
+                InputEvent::PressEnter => "enter",
+            })
+        })
+        .detach();
+    });
+
+    cx.simulate_input("hello world");
+    assert_eq!(text(&input, cx), "hello world");
+    assert_eq!(selection(&input, cx), 11..11);
+
+    cx.simulate_keystrokes("shift-left shift-left shift-left shift-left shift-left");
+    assert_eq!(selection(&input, cx), 6..11);
+    cx.simulate_input("there");
+    assert_eq!(text(&input, cx), "hello there");
+
+    cx.simulate_keystrokes("home shift-right shift-right");
+    assert_eq!(selection(&input, cx), 0..2);
+    cx.simulate_keystrokes("delete end backspace");
+    assert_eq!(text(&input, cx), "llo ther");
+
+    // Graphemes are deleted whole.
+    cx.simulate_input("e\u{301}");
+    cx.simulate_keystrokes("backspace");
+    assert_eq!(text(&input, cx), "llo ther");
+
+    cx.simulate_keystrokes("enter");
+    assert_eq!(
+        text(&input, cx),
+        "llo ther",
+        "single lines take no newlines"
+    );
+    assert_eq!(events.borrow().last(), Some(&"enter"));
+    assert!(
+        events
+            .borrow()
+            .iter()
+            .filter(|event| **event == "changed")
+            .count()
+            > 3
+    );
+}
+
+#[gpui::test]
+fn edits_multiple_lines(cx: &mut TestAppContext) {
+    let (input, cx) = open(cx, |input| input.multi_line(true));
+
+    cx.simulate_input("first");
+    cx.simulate_keystrokes("enter");
+    cx.simulate_input("second");
+    assert_eq!(text(&input, cx), "first\nsecond");
+
+    cx.simulate_keystrokes("up shift-end");
+    assert_eq!(selection(&input, cx), 5..5);
+    cx.simulate_keystrokes("home shift-down");
+    assert_eq!(selection(&input, cx), 0..6);
+    cx.simulate_keystrokes("secondary-a");
+    assert_eq!(selection(&input, cx), 0..12);
+}
+
+#[gpui::test]
+fn undoes_quick_edits_together(cx: &mut TestAppContext) {
+    let (input, cx) = open(cx, |input| input);
+
+    cx.simulate_input("abc");
+    // Moving the cursor ends the group.
+    cx.simulate_keystrokes("left");
+    cx.simulate_input("X");
+    assert_eq!(text(&input, cx), "abXc");
+
+    cx.simulate_keystrokes("secondary-z");
+    assert_eq!(text(&input, cx), "abc");
+    cx.simulate_keystrokes("secondary-z");
+    assert_eq!(text(&input, cx), "");
+    cx.simulate_keystrokes("secondary-z");
+    assert_eq!(text(&input, cx), "", "there is nothing left to undo");
+
+    cx.simulate_keystrokes("secondary-shift-z");
+    assert_eq!(text(&input, cx), "abc");
+    cx.simulate_keystrokes("secondary-shift-z");
+    assert_eq!(text(&input, cx), "abXc");
+    assert_eq!(selection(&input, cx), 3..3);
+
+    // Editing after undoing drops what could be redone, the cursor going
+    // back to where the undone edit was made.
+    cx.simulate_keystrokes("secondary-z");
+    cx.simulate_input("d");
+    cx.simulate_keystrokes("secondary-shift-z");
+    assert_eq!(text(&input, cx), "abdc");
+
+    // Setting the text clears the history.
+    input.update_in(cx, |input, _, cx| input.set_text("new", cx));
+    cx.simulate_keystrokes("secondary-z");
+    assert_eq!(text(&input, cx), "new");
+}
+
+#[gpui::test]
+fn copies_cuts_and_pastes(cx: &mut TestAppContext) {
+    let (input, cx) = open(cx, |input| input.default_text("copy me"));
+
+    cx.simulate_keystrokes("secondary-a secondary-c");
+    assert_eq!(
+        cx.read_from_clipboard()
+            .and_then(|item| item.text())
+            .as_deref(),
+        Some("copy me")
+    );
+
+    cx.simulate_keystrokes("end secondary-v");
+    assert_eq!(text(&input, cx), "copy mecopy me");
+
+    cx.simulate_keystrokes("secondary-a secondary-x");
+    assert_eq!(text(&input, cx), "");
+    assert_eq!(
+        cx.read_from_clipboard()
+            .and_then(|item| item.text())
+            .as_deref(),
+        Some("copy mecopy me")
+    );
+
+    // A paste is undone on its own.
+    cx.simulate_input("x");
+    cx.write_to_clipboard(ClipboardItem::new_string("line\nbreak".into()));
+    cx.simulate_keystrokes("secondary-v");
+    assert_eq!(text(&input, cx), "xline break");
+    cx.simulate_keystrokes("secondary-z");
+    assert_eq!(text(&input, cx), "x");
+}
+
+#[gpui::test]
+fn ignores_edits_while_disabled(cx: &mut TestAppContext) {
+    let (input, cx) = open(cx, |input| input.default_text("fixed").disabled(true));
+
+    cx.simulate_input("typed");
+    cx.simulate_keystrokes("backspace secondary-v");
+    input.update_in(cx, |input, window, cx| {
+        input.replace_text_in_range(None, "replaced", window, cx);
+        input.replace_and_mark_text_in_range(None, "marked", None, window, cx);
+    });
+    assert_eq!(text(&input, cx), "fixed");
+    assert!(cx.read_entity(&input, |input, _| input.is_disabled()));
+
+    input.update_in(cx, |input, _, cx| input.set_disabled(false, cx));
+    cx.run_until_parked();
+    cx.focus(&input);
+    cx.simulate_keystrokes("end");
+    cx.simulate_input("!");
+    assert_eq!(text(&input, cx), "fixed!");
+}
+
+#[gpui::test]
+fn composes_marked_text(cx: &mut TestAppContext) {
+    let (input, cx) = open(cx, |input| input.default_text("é"));
+
+    input.update_in(cx, |input, window, cx| {
+        input.replace_and_mark_text_in_range(Some(1..1), "ni", Some(1..1), window, cx);
+
+        // Ranges are in UTF-16 code units, offsets in bytes.
+        assert_eq!(input.text(), "éni");
+        assert_eq!(input.marked_text_range(window, cx), Some(1..3));
+        assert_eq!(input.selected_range(), 3..3);
+
+        input.replace_and_mark_text_in_range(None, "ni", None, window, cx);
+        assert_eq!(input.selected_range(), 4..4);
+
+        // The marked text is replaced as composition goes on.
+        input.replace_and_mark_text_in_range(None, "你", None, window, cx);
+        assert_eq!(input.text(), "é你");
+        assert_eq!(input.marked_text_range(window, cx), Some(1..2));
+
+        input.replace_text_in_range(None, "你好", window, cx);
+        assert_eq!(input.text(), "é你好");
+        assert_eq!(input.marked_text_range(window, cx), None);
+        assert_eq!(input.selected_range(), 8..8);
+
+        let mut actual = None;
+        assert_eq!(
+            input
+                .text_for_range(1..3, &mut actual, window, cx)
+                .as_deref(),
+            Some("你好")
+        );
+        assert_eq!(actual, Some(1..3));
+
+        input.replace_and_mark_text_in_range(None, "x", None, window, cx);
+        input.unmark_text(window, cx);
+        assert_eq!(input.text(), "é你好x");
+        assert_eq!(input.marked_text_range(window, cx), None);
+    });
+}
+
+#[gpui::test]
+fn keeps_newlines_of_multi_line_default_text(cx: &mut TestAppContext) {
+    cx.update(theme::init);
+
+    let before = cx.new(|cx| TextInput::new(cx).default_text("a\nb").multi_line(true));
+    let after = cx.new(|cx| TextInput::new(cx).multi_line(true).default_text("a\nb"));
+    let single = cx.new(|cx| TextInput::new(cx).default_text("a\r\nb"));
+
+    cx.read(|cx| {
+        assert_eq!(before.read(cx).text(), "a\nb");
+        assert_eq!(after.read(cx).text(), "a\nb");
+        assert_eq!(single.read(cx).text(), "a  b");
+    });
+}
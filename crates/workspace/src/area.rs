@@ -1,8 +1,20 @@
+use std::path::Path;
+
 use gpui::{
-    AppContext, Context, Entity, InteractiveElement, ParentElement, Render, Styled, WeakEntity,
-    div, prelude::FluentBuilder,
+    App, AppContext, Context, Div, Entity, EntityId, InteractiveElement, IntoElement,
+    ParentElement, Render, Stateful, StatefulInteractiveElement, Styled, WeakEntity, Window, div,
+    prelude::FluentBuilder,
+};
+use theme::ActiveTheme;
+use ui::{
+    color::Color,
+    components::{
+        button::ButtonSize,
+        icon::{Icon, IconName, IconSize},
+        icon_button::IconButton,
+    },
+    traits::{clickable::Clickable, styled_ext::StyledExt},
 };
-use ui::traits::styled_ext::StyledExt;
 
 use crate::{Workspace, item::ItemHandle};
 
@@ -23,17 +35,141 @@ impl Area {
         })
     }
 
-    pub fn active_item(&self) -> Option<&Box<dyn ItemHandle>> {
-        self.items.get(self.current)
+    pub fn workspace(&self) -> &WeakEntity<Workspace> {
+        &self.workspace
     }
-}
 
-impl Render for Area {
-    fn render(
+    pub fn items(&self) -> impl Iterator<Item = &dyn ItemHandle> {
+        self.items.iter().map(|item| item.as_ref())
+    }
+
+    pub fn active_item(&self) -> Option<&dyn ItemHandle> {
+        self.items.get(self.current).map(|item| item.as_ref())
+    }
+
+    pub fn index_for_item_id(&self, item_id: EntityId) -> Option<usize> {
+        self.items.iter().position(|item| item.item_id() == item_id)
+    }
+
+    pub fn index_for_path(&self, path: &Path, cx: &App) -> Option<usize> {
+        self.items
+            .iter()
+            .position(|item| item.path(cx).is_some_and(|item_path| &*item_path == path))
+    }
+
+    /// Adds the item after the active one and activates it. Items that are
+    /// already present are only activated.
+    pub fn add_item(&mut self, item: Box<dyn ItemHandle>, cx: &mut Context<Self>) {
+        if let Some(index) = self.index_for_item_id(item.item_id()) {
+            self.activate_item(index, cx);
+            return;
+        }
+
+        let index = if self.items.is_empty() {
+            0
+        } else {
+            self.current + 1
+        };
+
+        self.items.insert(index, item);
+        self.activate_item(index, cx);
+    }
+
+    pub fn activate_item(&mut self, index: usize, cx: &mut Context<Self>) {
+        if index < self.items.len() {
+            self.current = index;
+            cx.notify();
+        }
+    }
+
+    pub fn remove_item(
         &mut self,
-        window: &mut gpui::Window,
+        item_id: EntityId,
+        cx: &mut Context<Self>,
+    ) -> Option<Box<dyn ItemHandle>> {
+        let index = self.index_for_item_id(item_id)?;
+        let item = self.items.remove(index);
+
+        if index < self.current || self.current >= self.items.len() {
+            self.current = self.current.saturating_sub(1);
+        }
+
+        cx.notify();
+
+        Some(item)
+    }
+
+    fn render_tab(
+        &self,
+        index: usize,
+        item: &dyn ItemHandle,
         cx: &mut Context<Self>,
-    ) -> impl gpui::IntoElement {
+    ) -> Stateful<Div> {
+        let colors = cx.theme().colors();
+        let is_active = index == self.current;
+        let item_id = item.item_id();
+
+        div()
+            .id(("tab", item_id))
+            .h_flex()
+            .h_full()
+            .pl_3()
+            .pr_1()
+            .gap_1p5()
+            .border_r_1()
+            .border_color(colors.border)
+            .cursor_pointer()
+            .map(|this| {
+                if is_active {
+                    this.bg(colors.tab_active)
+                        .text_color(colors.tab_active_foreground)
+                } else {
+                    this.bg(colors.tab).text_color(colors.tab_foreground)
+                }
+            })
+            .when_some(item.tab_icon(cx), |this, icon| {
+                this.child(Icon::new(icon).size(IconSize::Small).color(Color::Muted))
+            })
+            .child(item.tab_content_text(cx))
+            .child(
+                IconButton::new(("close", item_id), IconName::Close)
+                    .size(ButtonSize::Compact)
+                    .icon_color(Color::Muted)
+                    .tooltip("Close Tab")
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        this.remove_item(item_id, cx);
+                    })),
+            )
+            .on_click(cx.listener(move |this, _, _window, cx| {
+                this.activate_item(index, cx);
+            }))
+    }
+
+    fn render_tab_bar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = cx.theme().colors();
+
+        div()
+            .id("tab-bar")
+            .h_flex()
+            .flex_none()
+            .w_full()
+            .h_8()
+            .overflow_x_scroll()
+            .bg(colors.tab_bar)
+            .border_b_1()
+            .border_color(colors.border)
+            .children(
+                self.items
+                    .iter()
+                    .enumerate()
+                    .map(|(index, item)| self.render_tab(index, item.as_ref(), cx))
+                    .collect::<Vec<_>>(),
+            )
+    }
+}
+
+impl Render for Area {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         div()
             .v_flex()
             .id("area")
@@ -41,6 +177,9 @@ impl Render for Area {
             .size_full()
             .flex_none()
             .overflow_hidden()
+            .when(!self.items.is_empty(), |this| {
+                this.child(self.render_tab_bar(cx))
+            })
             .child({
                 div().flex().relative().overflow_hidden().map(|this| {
                     if let Some(item) = self.active_item() {
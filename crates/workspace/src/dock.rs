@@ -25,7 +25,7 @@ impl<T: Panel> PanelHandle for Entity<T> {
         self.read(cx).priority()
     }
 
-    fn placement(&self, window: &Window, cx: &App) -> Placement {
+    fn placement(&self, _window: &Window, cx: &App) -> Placement {
         self.read(cx).placement()
     }
 
@@ -55,10 +55,19 @@ impl Dock {
         })
     }
 
+    pub fn workspace(&self) -> &WeakEntity<Workspace> {
+        &self.workspace
+    }
+
     pub fn is_open(&self) -> bool {
         self.is_open
     }
 
+    pub fn set_open(&mut self, is_open: bool, cx: &mut Context<Self>) {
+        self.is_open = is_open;
+        cx.notify();
+    }
+
     pub fn add_panel<T: Panel>(&mut self, panel: Entity<T>, cx: &mut Context<Self>) -> usize {
         let subscription = cx.observe(&panel, |_, _, cx| cx.notify());
 
@@ -81,7 +90,7 @@ impl Dock {
     }
 
     pub fn remove_panel(&mut self, index: usize) {
-        self.items.remove(index);
+        let _ = self.items.remove(index);
 
         if let Some(current) = self.current.as_mut() {
             match index.cmp(current) {
@@ -92,6 +101,13 @@ impl Dock {
         }
     }
 
+    /// The index of the panel of type `T`, if the dock has one.
+    pub fn panel_index<T: Panel>(&self) -> Option<usize> {
+        self.items
+            .iter()
+            .position(|(panel, _)| panel.to_any().downcast::<T>().is_ok())
+    }
+
     pub fn display_panel(&mut self, index: usize) {
         self.current = Some(index);
     }
@@ -120,7 +136,7 @@ impl Render for Dock {
                 .overflow_hidden()
                 .map(|this| match self.placement.axis() {
                     Axis::Vertical => this.h_full().flex_row(),
-                    Axis::Horizontal => this.w_full().flex_col(),
+                    Axis::Horizontal => this.w_full().h_80().flex_col().border_t_1(),
                 })
                 .child(
                     panel
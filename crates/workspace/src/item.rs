@@ -1,7 +1,51 @@
-use gpui::AnyView;
+use std::{path::Path, sync::Arc};
 
-pub trait Item {}
+use gpui::{AnyView, App, Entity, EntityId, Render, SharedString};
+use ui::components::icon::IconName;
 
-pub trait ItemHandle {
+pub trait Item: Render + Sized {
+    fn tab_content_text(&self, cx: &App) -> SharedString;
+
+    fn tab_icon(&self, _cx: &App) -> Option<IconName> {
+        None
+    }
+
+    fn path(&self, _cx: &App) -> Option<Arc<Path>> {
+        None
+    }
+}
+
+pub trait ItemHandle: 'static {
+    fn item_id(&self) -> EntityId;
+    fn tab_content_text(&self, cx: &App) -> SharedString;
+    fn tab_icon(&self, cx: &App) -> Option<IconName>;
+    fn path(&self, cx: &App) -> Option<Arc<Path>>;
+    fn boxed_clone(&self) -> Box<dyn ItemHandle>;
     fn to_any(&self) -> AnyView;
 }
+
+impl<T: Item> ItemHandle for Entity<T> {
+    fn item_id(&self) -> EntityId {
+        self.entity_id()
+    }
+
+    fn tab_content_text(&self, cx: &App) -> SharedString {
+        self.read(cx).tab_content_text(cx)
+    }
+
+    fn tab_icon(&self, cx: &App) -> Option<IconName> {
+        self.read(cx).tab_icon(cx)
+    }
+
+    fn path(&self, cx: &App) -> Option<Arc<Path>> {
+        self.read(cx).path(cx)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn ItemHandle> {
+        Box::new(self.clone())
+    }
+
+    fn to_any(&self) -> AnyView {
+        self.clone().into()
+    }
+}
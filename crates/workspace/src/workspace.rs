@@ -2,12 +2,18 @@ pub mod area;
 pub mod dock;
 pub mod item;
 
-use std::sync::{Arc, Weak};
+use std::{
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::{Arc, Weak},
+};
 
 use anyhow::Ok;
 use gpui::{
-    App, AppContext, Context, Div, Entity, Global, InteractiveElement, ParentElement, Render,
-    Styled, Subscription, Task, WeakEntity, Window, WindowHandle, WindowOptions, div,
+    Action, AnyWindowHandle, App, AppContext, Context, Div, Entity, EntityId, Global,
+    InteractiveElement, KeyBinding, ParentElement, PathPromptOptions, Render, SharedString,
+    Stateful, Styled, Subscription, Task, WeakEntity, Window, WindowHandle, WindowOptions, actions,
+    div,
 };
 use theme::{ActiveTheme, GlobalTheme, SystemAppearance};
 
@@ -17,8 +23,53 @@ use uuid::Uuid;
 use crate::{
     area::Area,
     dock::{Dock, Panel, PanelHandle},
+    item::ItemHandle,
 };
 
+actions!(
+    workspace,
+    [
+        /// Opens a new window without a collection.
+        NewWindow,
+        /// Prompts for a collection directory and opens it.
+        OpenCollection,
+        /// Closes the active item of the center area.
+        CloseActiveItem,
+        /// Moves the active item into a new window.
+        MoveItemToNewWindow,
+        /// Moves the active item into the next workspace window.
+        MoveItemToNextWindow,
+    ]
+);
+
+pub fn init(app_state: Arc<AppState>, cx: &mut App) {
+    cx.bind_keys([
+        KeyBinding::new("secondary-shift-n", NewWindow, None),
+        KeyBinding::new("secondary-o", OpenCollection, None),
+        KeyBinding::new("secondary-w", CloseActiveItem, Some("workspace")),
+    ]);
+
+    cx.on_action({
+        let app_state = Arc::downgrade(&app_state);
+
+        move |_: &NewWindow, cx| {
+            if let Some(app_state) = app_state.upgrade() {
+                open_new(app_state, cx);
+            }
+        }
+    });
+
+    cx.on_action({
+        let app_state = Arc::downgrade(&app_state);
+
+        move |_: &OpenCollection, cx| {
+            if let Some(app_state) = app_state.upgrade() {
+                prompt_and_open_collection(app_state, cx);
+            }
+        }
+    });
+}
+
 pub struct AppState {
     pub build_window_options: fn(Option<Uuid>, &mut App) -> WindowOptions,
 }
@@ -31,18 +82,27 @@ impl AppState {
     pub fn set_global(state: Weak<AppState>, cx: &mut App) {
         cx.set_global(GlobalAppState(state));
     }
+
+    pub fn global(cx: &App) -> Weak<AppState> {
+        cx.global::<GlobalAppState>().0.clone()
+    }
 }
 
 pub struct Workspace {
     weak_self: WeakEntity<Self>,
+    root: Option<Arc<Path>>,
     left_dock: Entity<Dock>,
     bottom_dock: Entity<Dock>,
     center: Entity<Area>,
+    workspace_actions: Vec<WorkspaceActionListener>,
     _subscriptions: Vec<Subscription>,
 }
 
+type WorkspaceActionListener =
+    Box<dyn Fn(Stateful<Div>, &mut Window, &mut Context<Workspace>) -> Stateful<Div>>;
+
 impl Workspace {
-    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+    pub fn new(root: Option<Arc<Path>>, window: &mut Window, cx: &mut Context<Self>) -> Self {
         let weak_self = cx.entity().downgrade();
 
         let left_dock = Dock::new(Placement::Left, cx);
@@ -57,15 +117,63 @@ impl Workspace {
             GlobalTheme::reload_theme(cx);
         })];
 
+        window.set_window_title(&title_for_root(root.as_deref()));
+
         Self {
             weak_self,
+            root,
             left_dock,
             bottom_dock,
             center,
+            workspace_actions: Vec::new(),
             _subscriptions: subscriptions,
         }
     }
 
+    /// Handles `A` while focus is anywhere within this workspace, letting
+    /// crates the workspace does not depend on contribute their own actions.
+    pub fn register_action<A: Action>(
+        &mut self,
+        callback: impl Fn(&mut Self, &A, &mut Window, &mut Context<Self>) + 'static,
+    ) -> &mut Self {
+        let callback = Rc::new(callback);
+
+        self.workspace_actions.push(Box::new(move |div, _, cx| {
+            let callback = callback.clone();
+
+            div.on_action(cx.listener(move |workspace, action, window, cx| {
+                callback(workspace, action, window, cx)
+            }))
+        }));
+        self
+    }
+
+    fn add_workspace_actions_listeners(
+        &mut self,
+        mut div: Stateful<Div>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Stateful<Div> {
+        for listener in &self.workspace_actions {
+            div = listener(div, window, cx);
+        }
+
+        div
+    }
+
+    pub fn weak_handle(&self) -> WeakEntity<Self> {
+        self.weak_self.clone()
+    }
+
+    /// The collection directory this workspace is bound to, if any.
+    pub fn root(&self) -> Option<&Arc<Path>> {
+        self.root.as_ref()
+    }
+
+    pub fn center(&self) -> &Entity<Area> {
+        &self.center
+    }
+
     pub fn add_panel<T: Panel>(
         &mut self,
         panel: Entity<T>,
@@ -80,18 +188,133 @@ impl Workspace {
         })
     }
 
+    /// Shows the panel of type `T` in its dock, or closes the dock when it
+    /// is already showing that panel.
+    pub fn toggle_panel<T: Panel>(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        for dock in [&self.left_dock, &self.bottom_dock] {
+            dock.update(cx, |dock, cx| {
+                let Some(index) = dock.panel_index::<T>() else {
+                    return;
+                };
+
+                let showing = dock.is_open()
+                    && dock
+                        .active_panel()
+                        .is_some_and(|panel| panel.to_any().downcast::<T>().is_ok());
+
+                dock.display_panel(index);
+                dock.set_open(!showing, cx);
+            });
+        }
+
+        window.refresh();
+    }
+
+    pub fn add_item(&mut self, item: Box<dyn ItemHandle>, cx: &mut Context<Self>) {
+        self.center.update(cx, |area, cx| area.add_item(item, cx));
+    }
+
+    /// Activates the item opened for `path`, returning whether one was found.
+    pub fn activate_item_for_path(&mut self, path: &Path, cx: &mut Context<Self>) -> bool {
+        self.center.update(cx, |area, cx| {
+            let Some(index) = area.index_for_path(path, cx) else {
+                return false;
+            };
+
+            area.activate_item(index, cx);
+
+            true
+        })
+    }
+
+    pub fn close_active_item(&mut self, cx: &mut Context<Self>) -> Option<Box<dyn ItemHandle>> {
+        self.center.update(cx, |area, cx| {
+            let item_id = area.active_item()?.item_id();
+
+            area.remove_item(item_id, cx)
+        })
+    }
+
+    /// Moves the active item into `target`, or into a new window bound to the
+    /// same collection when no target is given. The item stays here until
+    /// the other window has taken it.
+    pub fn move_active_item_to_window(
+        &mut self,
+        target: Option<WindowHandle<Workspace>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(item) = self
+            .center
+            .read(cx)
+            .active_item()
+            .map(|item| item.boxed_clone())
+        else {
+            return;
+        };
+        let item_id = item.item_id();
+
+        match target {
+            Some(target) => {
+                let result = target.update(cx, |workspace, window, cx| {
+                    workspace.add_item(item, cx);
+                    window.activate_window();
+                });
+
+                if result.is_ok() {
+                    self.remove_item(item_id, cx);
+                }
+            }
+            None => {
+                let Some(app_state) = AppState::global(cx).upgrade() else {
+                    return;
+                };
+
+                let requesting_window = window.window_handle().downcast::<Workspace>();
+                let task = Workspace::spawn(app_state, self.root.clone(), requesting_window, cx);
+
+                cx.spawn(async move |this, cx| {
+                    let target = task.await?;
+
+                    target.update(cx, |workspace, _, cx| workspace.add_item(item, cx))?;
+                    this.update(cx, |workspace, cx| workspace.remove_item(item_id, cx))?;
+
+                    Ok(())
+                })
+                .detach_and_log_err(cx);
+            }
+        }
+    }
+
+    fn remove_item(&mut self, item_id: EntityId, cx: &mut Context<Self>) {
+        self.center
+            .update(cx, |area, cx| area.remove_item(item_id, cx));
+    }
+
     pub fn spawn(
         app_state: Arc<AppState>,
-        _requesting_window: Option<WindowHandle<Workspace>>,
+        root: Option<Arc<Path>>,
+        requesting_window: Option<WindowHandle<Workspace>>,
         cx: &mut App,
     ) -> Task<anyhow::Result<WindowHandle<Workspace>>> {
-        let options = (app_state.build_window_options)(None, cx);
+        // Windows opened from an existing one land on its display, so that the
+        // system groups them in the same `tabbing_identifier` tab set.
+        let display_uuid = requesting_window.and_then(|handle| {
+            handle
+                .update(cx, |_, window, cx| {
+                    window.display(cx).and_then(|display| display.uuid().ok())
+                })
+                .ok()
+                .flatten()
+        });
+
+        let options = (app_state.build_window_options)(display_uuid, cx);
 
         cx.spawn(async move |cx| {
             let window = cx.open_window(options, {
                 let _app_state = app_state.clone();
 
-                move |window, cx| cx.new(|cx| Workspace::new(window, cx))
+                move |window, cx| cx.new(|cx| Workspace::new(root, window, cx))
             })?;
 
             window.update(cx, |_workspace, window, _cx| {
@@ -119,10 +342,38 @@ impl Workspace {
                 .child(dock.clone()),
         )
     }
+
+    fn next_window(&self, window: &Window, cx: &App) -> Option<WindowHandle<Workspace>> {
+        let windows = workspace_windows(cx);
+        let current = windows
+            .iter()
+            .position(|handle| AnyWindowHandle::from(*handle) == window.window_handle())?;
+        let next = windows[(current + 1) % windows.len()];
+
+        (next != windows[current]).then_some(next)
+    }
+}
+
+fn title_for_root(root: Option<&Path>) -> SharedString {
+    root.and_then(|root| root.file_name())
+        .map(|name| name.to_string_lossy().into_owned().into())
+        .unwrap_or_else(|| "Bridge".into())
+}
+
+/// All open windows whose root view is a [`Workspace`].
+pub fn workspace_windows(cx: &App) -> Vec<WindowHandle<Workspace>> {
+    cx.windows()
+        .into_iter()
+        .filter_map(|window| window.downcast::<Workspace>())
+        .collect()
 }
 
 pub fn open_new(app_state: Arc<AppState>, cx: &mut App) {
-    let task = Workspace::spawn(app_state, None, cx);
+    let requesting_window = cx
+        .active_window()
+        .and_then(|window| window.downcast::<Workspace>());
+
+    let task = Workspace::spawn(app_state, None, requesting_window, cx);
 
     cx.spawn(async move |_| {
         let _ = task.await;
@@ -130,19 +381,88 @@ pub fn open_new(app_state: Arc<AppState>, cx: &mut App) {
     .detach();
 }
 
+/// Opens the collection at `root`, activating the window that already has it
+/// open instead of spawning a new one.
+pub fn open_collection(
+    root: PathBuf,
+    app_state: Arc<AppState>,
+    requesting_window: Option<WindowHandle<Workspace>>,
+    cx: &mut App,
+) -> Task<anyhow::Result<WindowHandle<Workspace>>> {
+    let root: Arc<Path> = root.canonicalize().unwrap_or(root).into();
+
+    let existing = workspace_windows(cx).into_iter().find(|handle| {
+        handle
+            .read(cx)
+            .is_ok_and(|workspace| workspace.root() == Some(&root))
+    });
+
+    if let Some(existing) = existing {
+        return cx.spawn(async move |cx| {
+            existing.update(cx, |_, window, _| window.activate_window())?;
+
+            Ok(existing)
+        });
+    }
+
+    Workspace::spawn(app_state, Some(root), requesting_window, cx)
+}
+
+fn prompt_and_open_collection(app_state: Arc<AppState>, cx: &mut App) {
+    let requesting_window = cx
+        .active_window()
+        .and_then(|window| window.downcast::<Workspace>());
+
+    let paths = cx.prompt_for_paths(PathPromptOptions {
+        files: false,
+        directories: true,
+        multiple: false,
+        prompt: Some("Open Collection".into()),
+    });
+
+    cx.spawn(async move |cx| {
+        let Some(root) = paths.await??.and_then(|paths| paths.into_iter().next()) else {
+            return Ok(());
+        };
+
+        cx.update(|cx| open_collection(root, app_state, requesting_window, cx))?
+            .await?;
+
+        Ok(())
+    })
+    .detach();
+}
+
 impl Render for Workspace {
     fn render(
         &mut self,
-        _window: &mut gpui::Window,
+        window: &mut gpui::Window,
         cx: &mut gpui::Context<Self>,
     ) -> impl gpui::IntoElement {
         let theme = cx.theme().clone();
         let colors = theme.colors();
 
+        let workspace = div().id("workspace").key_context("workspace");
+        let workspace = self.add_workspace_actions_listeners(workspace, window, cx);
+
         // TODO: Extract into separate layers
         root(
-            div()
-                .id("workspace")
+            workspace
+                .on_action(cx.listener(|workspace, _: &CloseActiveItem, _, cx| {
+                    workspace.close_active_item(cx);
+                }))
+                .on_action(
+                    cx.listener(|workspace, _: &MoveItemToNewWindow, window, cx| {
+                        workspace.move_active_item_to_window(None, window, cx);
+                    }),
+                )
+                .on_action(
+                    cx.listener(|workspace, _: &MoveItemToNextWindow, window, cx| {
+                        if let Some(target) = workspace.next_window(window, cx) {
+                            workspace.move_active_item_to_window(Some(target), window, cx);
+                        }
+                    }),
+                )
                 .bg(colors.background)
                 .relative()
                 .flex_1()